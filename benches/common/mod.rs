@@ -0,0 +1,32 @@
+//! Models shared by the benchmarks.
+
+/// An aging chain of `cohorts` stocks, each with an aging flow to the next
+/// and a death flow, as found in population models.
+pub fn aging_chain(cohorts: usize) -> String {
+    let mut variables = String::new();
+    for i in 0..cohorts {
+        let inflow = if i > 0 {
+            format!("<inflow>aging_{}</inflow>", i - 1)
+        } else {
+            String::new()
+        };
+        variables.push_str(&format!(
+            r#"<stock name="Cohort_{i}"><eqn>1000</eqn>{inflow}<outflow>aging_{i}</outflow><outflow>deaths_{i}</outflow></stock>
+            <flow name="aging_{i}"><eqn>Cohort_{i} / years_per_cohort</eqn></flow>
+            <flow name="deaths_{i}"><eqn>Cohort_{i} * mortality_{i} * effect_of_crowding(Cohort_{i} / 1000)</eqn></flow>
+            <aux name="mortality_{i}"><eqn>0.001 * (1 + {i} / 10)</eqn></aux>
+            "#
+        ));
+    }
+    format!(
+        r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header><vendor>Bench</vendor><product version="1.0">Bench</product></header>
+        <sim_specs><start>0</start><stop>100</stop><dt>0.25</dt></sim_specs>
+        <model><variables>
+            <aux name="years_per_cohort"><eqn>5</eqn></aux>
+            <gf name="effect_of_crowding"><xscale min="0" max="2"/><ypts>0.8,0.9,1,1.3,2</ypts></gf>
+            {variables}
+        </variables></model>
+    </xmile>"#
+    )
+}
@@ -0,0 +1,75 @@
+//! Evaluation benchmarks for equations and graphical function lookups.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use xmile::equation::{Expression, Identifier};
+use xmile::{GraphicalFunction, GraphicalFunctionData};
+
+fn parse(equation: &str) -> Expression {
+    xmile::equation::parse::expression(equation).unwrap().1
+}
+
+fn expressions(c: &mut Criterion) {
+    let values: HashMap<Identifier, f64> = [
+        ("contact_rate", 6.0),
+        ("infectivity", 0.25),
+        ("Susceptible", 9999.0),
+        ("Infected", 1.0),
+        ("total_population", 10000.0),
+    ]
+    .into_iter()
+    .map(|(name, value)| (Identifier::parse_default(name).unwrap(), value))
+    .collect();
+    let lookup = |name: &Identifier| values.get(name).copied();
+
+    let mut group = c.benchmark_group("expression");
+    for (name, equation) in [
+        (
+            "infection",
+            "contact_rate * infectivity * Susceptible * Infected / total_population",
+        ),
+        (
+            "conditional",
+            "IF Infected > 100 THEN MIN(Susceptible, Infected) ELSE EXP(-infectivity) * SQRT(total_population)",
+        ),
+    ] {
+        let expression = parse(equation);
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(&expression).evaluate::<f64>(&lookup))
+        });
+    }
+    group.finish();
+}
+
+fn graphical_functions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graphical_function");
+    let xs: Vec<f64> = (0..1000).map(|i| i as f64 * 0.1).collect();
+    let mut ys = vec![0.0; xs.len()];
+
+    for points in [11, 1001] {
+        let x_values: Vec<f64> = (0..points)
+            .map(|i| i as f64 * 100.0 / (points - 1) as f64)
+            .collect();
+        let y_values: Vec<f64> = x_values.iter().map(|x| (x / 10.0).sin()).collect();
+        let gf: GraphicalFunction =
+            GraphicalFunctionData::xy_pairs(x_values, y_values, None).into();
+
+        group.bench_with_input(BenchmarkId::new("evaluate", points), &gf, |b, gf| {
+            b.iter(|| {
+                for (x, y) in xs.iter().zip(ys.iter_mut()) {
+                    *y = gf.evaluate(*x);
+                }
+            })
+        });
+        let table = gf.table();
+        group.bench_with_input(BenchmarkId::new("table", points), &table, |b, table| {
+            b.iter(|| table.evaluate_many(black_box(&xs), &mut ys))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, expressions, graphical_functions);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! Parsing benchmarks for a small hand-written model and a large generated
+//! one.
+
+mod common;
+
+use common::aging_chain;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use xmile::xml::XmileFile;
+
+const SIR: &str = include_str!("../data/conformance/sir.xmile");
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    let large = aging_chain(250);
+    for (name, xml) in [("sir", SIR), ("aging_chain_250", large.as_str())] {
+        group.throughput(Throughput::Bytes(xml.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), xml, |b, xml| {
+            b.iter(|| XmileFile::from_str(black_box(xml)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
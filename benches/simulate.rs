@@ -0,0 +1,36 @@
+//! Full-run simulation benchmarks for a small hand-written model and a large
+//! generated one, with Euler's method and fourth-order Runge-Kutta.
+
+mod common;
+
+use common::aging_chain;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use xmile::simulation::Simulator;
+use xmile::specs::IntegrationMethod;
+use xmile::xml::XmileFile;
+
+const SIR: &str = include_str!("../data/conformance/sir.xmile");
+
+fn simulate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulate");
+    // Each run takes many steps, so fewer samples are taken than usual
+    group.sample_size(10);
+    let large = aging_chain(50);
+    for (name, xml) in [("sir", SIR), ("aging_chain_50", large.as_str())] {
+        let file = XmileFile::from_str(xml).unwrap();
+        for method in [IntegrationMethod::Euler, IntegrationMethod::Rk4] {
+            let mut specs = file.sim_specs.clone().unwrap();
+            specs.method = Some(method.to_string());
+            let simulator = Simulator::new(&file.models[0], &specs).unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(name, method),
+                &simulator,
+                |b, simulator| b.iter(|| simulator.run().unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, simulate);
+criterion_main!(benches);
@@ -0,0 +1,434 @@
+//! Experimental designs: sample matrices over parameter ranges.
+//!
+//! Each design takes [`Parameter`]s, each a named range of values, and
+//! gives a [`SampleMatrix`] with one row per run and one column per
+//! parameter:
+//!
+//! - [`full_factorial`]: every combination of evenly spaced levels
+//! - [`fractional_factorial`]: a two-level factorial over some of the
+//!   parameters, with the others set by generators such as `"ABC"`, the
+//!   product of the levels of the first three parameters
+//! - [`latin_hypercube`]: random samples taking one value from each of as
+//!   many equal strata of every range as there are samples
+//! - [`sobol`]: the low-discrepancy sequence of Sobol, with the direction
+//!   numbers of Joe and Kuo
+//!
+//! [`SampleMatrix::scenarios`] turns the rows into [`Scenario`]s, each
+//! setting the parameters of one run, which can be applied to a file in
+//! turn:
+//!
+//! ```rust
+//! use xmile::analysis::doe::{Parameter, full_factorial};
+//!
+//! let design = full_factorial(&[
+//!     Parameter::new("birth rate", 0.01, 0.03).with_levels(3),
+//!     Parameter::new("area", 100.0, 200.0),
+//! ])
+//! .unwrap();
+//! assert_eq!(design.rows.len(), 6);
+//! assert_eq!(design.rows[1], [0.01, 200.0]);
+//! assert_eq!(design.scenarios().len(), 6);
+//! ```
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use thiserror::Error;
+
+use crate::scenario::{ParameterValue, Scenario};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DesignError {
+    #[error("A design needs at least one parameter")]
+    NoParameters,
+    #[error("Parameter '{name}' has an invalid range from {min} to {max}")]
+    Range { name: String, min: f64, max: f64 },
+    #[error("Parameter '{name}' cannot have {levels} levels")]
+    Levels { name: String, levels: usize },
+    #[error("Invalid generator '{0}'; generators multiply two or more of the base factors")]
+    Generator(String),
+    #[error("Sobol sequences are available for up to {max} parameters, not {found}")]
+    Dimensions { max: usize, found: usize },
+}
+
+/// A parameter varied by a design, over a range of values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    /// The (optionally qualified) name of the variable the parameter sets.
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    /// The number of evenly spaced values a full factorial or Morris design
+    /// takes, from `min` to `max`. Two-level designs ignore it.
+    pub levels: usize,
+}
+
+impl Parameter {
+    /// A parameter from `min` to `max`, with two levels.
+    pub fn new(name: &str, min: f64, max: f64) -> Self {
+        Parameter {
+            name: name.to_string(),
+            min,
+            max,
+            levels: 2,
+        }
+    }
+
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// The value a fraction of the way from `min` to `max`.
+    pub fn value_at(&self, fraction: f64) -> f64 {
+        self.min + fraction * (self.max - self.min)
+    }
+
+    fn validate(&self) -> Result<(), DesignError> {
+        if !(self.min.is_finite() && self.max.is_finite() && self.min <= self.max) {
+            return Err(DesignError::Range {
+                name: self.name.clone(),
+                min: self.min,
+                max: self.max,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The values of the parameters for each run of a design.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleMatrix {
+    /// The names of the parameters, one per column.
+    pub parameters: Vec<String>,
+    /// The values of the parameters, one row per run.
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl SampleMatrix {
+    /// The values of the named parameter, one per run.
+    pub fn column(&self, name: &str) -> Option<Vec<f64>> {
+        let index = self
+            .parameters
+            .iter()
+            .position(|parameter| parameter == name)?;
+        Some(self.rows.iter().map(|row| row[index]).collect())
+    }
+
+    /// A scenario per run, named `Sample 1`, `Sample 2` and so on, setting
+    /// each parameter to its value for the run.
+    pub fn scenarios(&self) -> Vec<Scenario> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(run, row)| Scenario {
+                name: Some(format!("Sample {}", run + 1)),
+                parameters: self
+                    .parameters
+                    .iter()
+                    .zip(row)
+                    .map(|(name, &value)| (name.clone(), ParameterValue::Number(value)))
+                    .collect(),
+                ..Scenario::default()
+            })
+            .collect()
+    }
+
+    pub(super) fn new(parameters: &[Parameter], rows: Vec<Vec<f64>>) -> Self {
+        SampleMatrix {
+            parameters: parameters.iter().map(|p| p.name.clone()).collect(),
+            rows,
+        }
+    }
+}
+
+pub(super) fn validate(parameters: &[Parameter]) -> Result<(), DesignError> {
+    if parameters.is_empty() {
+        return Err(DesignError::NoParameters);
+    }
+    parameters.iter().try_for_each(Parameter::validate)
+}
+
+/// Every combination of the levels of the parameters, with the last
+/// parameter changing fastest.
+pub fn full_factorial(parameters: &[Parameter]) -> Result<SampleMatrix, DesignError> {
+    validate(parameters)?;
+    if let Some(parameter) = parameters.iter().find(|p| p.levels < 2) {
+        return Err(DesignError::Levels {
+            name: parameter.name.clone(),
+            levels: parameter.levels,
+        });
+    }
+
+    let mut rows = vec![Vec::new()];
+    for parameter in parameters {
+        let last = (parameter.levels - 1) as f64;
+        rows = rows
+            .into_iter()
+            .flat_map(|row: Vec<f64>| {
+                (0..parameter.levels).map(move |level| {
+                    let mut row = row.clone();
+                    row.push(parameter.value_at(level as f64 / last));
+                    row
+                })
+            })
+            .collect();
+    }
+    Ok(SampleMatrix::new(parameters, rows))
+}
+
+/// A two-level fractional factorial design.
+///
+/// The first parameters, one fewer for each generator, are the base
+/// factors, named `A`, `B`, `C` and so on, and take every combination of
+/// their lowest and highest values. Each generator sets one of the
+/// remaining parameters, in order, to the product of the base factors it
+/// names, with the lowest value standing for -1 and the highest for +1: the
+/// 2^(4-1) design with the generator `"ABC"` sets the fourth parameter high
+/// when an odd number of the first three are high, in 8 runs instead of 16.
+pub fn fractional_factorial(
+    parameters: &[Parameter],
+    generators: &[&str],
+) -> Result<SampleMatrix, DesignError> {
+    validate(parameters)?;
+    let base = parameters
+        .len()
+        .checked_sub(generators.len())
+        .filter(|&base| base > 0)
+        .ok_or(DesignError::NoParameters)?;
+    // The base factors each generator multiplies
+    let factors = generators
+        .iter()
+        .map(|generator| {
+            let invalid = || DesignError::Generator(generator.to_string());
+            let mut factors = generator
+                .chars()
+                .map(|letter| {
+                    let factor = (letter.to_ascii_uppercase() as usize).checked_sub('A' as usize);
+                    factor.filter(|&factor| factor < base).ok_or_else(invalid)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            factors.sort();
+            factors.dedup();
+            if factors.len() < 2 {
+                return Err(invalid());
+            }
+            Ok(factors)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rows: Vec<Vec<f64>> = (0..1usize << base)
+        .map(|run| {
+            // The sign of each base factor, the last changing fastest
+            let signs: Vec<bool> = (0..base)
+                .map(|f| (run >> (base - 1 - f)) & 1 == 1)
+                .collect();
+            let generated = factors.iter().map(|factors| {
+                factors
+                    .iter()
+                    .filter(|&&f| !signs[f])
+                    .count()
+                    .is_multiple_of(2)
+            });
+            signs
+                .iter()
+                .copied()
+                .chain(generated)
+                .zip(parameters)
+                .map(|(high, parameter)| if high { parameter.max } else { parameter.min })
+                .collect()
+        })
+        .collect();
+    Ok(SampleMatrix::new(parameters, rows))
+}
+
+/// A Latin hypercube of `samples` runs, drawn with a random number
+/// generator seeded with `seed`.
+///
+/// The range of each parameter is split into `samples` equal strata, and
+/// each stratum is sampled once, at a random point within it, in a random
+/// order independent of the other parameters.
+pub fn latin_hypercube(
+    parameters: &[Parameter],
+    samples: usize,
+    seed: u64,
+) -> Result<SampleMatrix, DesignError> {
+    validate(parameters)?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut rows = vec![Vec::with_capacity(parameters.len()); samples];
+    for parameter in parameters {
+        let mut strata: Vec<usize> = (0..samples).collect();
+        strata.shuffle(&mut rng);
+        for (row, stratum) in rows.iter_mut().zip(strata) {
+            let fraction = (stratum as f64 + rng.gen_range(0.0..1.0)) / samples as f64;
+            row.push(parameter.value_at(fraction));
+        }
+    }
+    Ok(SampleMatrix::new(parameters, rows))
+}
+
+/// The primitive polynomials and initial direction numbers of Joe and Kuo
+/// for the dimensions after the first: the degree of the polynomial, its
+/// inner coefficients as bits, and the initial direction numbers.
+const DIRECTIONS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+/// The number of bits of each Sobol point.
+const BITS: usize = 32;
+
+/// The direction numbers of a dimension of the Sobol sequence.
+fn direction_numbers(dimension: usize) -> [u32; BITS] {
+    let mut v = [0u32; BITS];
+    if dimension == 0 {
+        for (k, v) in v.iter_mut().enumerate() {
+            *v = 1 << (BITS - 1 - k);
+        }
+        return v;
+    }
+    let (degree, coefficients, initial) = DIRECTIONS[dimension - 1];
+    let s = degree as usize;
+    for (k, m) in initial.iter().enumerate() {
+        v[k] = *m << (BITS - 1 - k);
+    }
+    for k in s..BITS {
+        v[k] = v[k - s] ^ (v[k - s] >> s);
+        for j in 1..s {
+            if (coefficients >> (s - 1 - j)) & 1 == 1 {
+                v[k] ^= v[k - j];
+            }
+        }
+    }
+    v
+}
+
+/// The first `samples` points of the Sobol sequence after the origin,
+/// scaled to the ranges of the parameters.
+///
+/// The points are generated in Gray code order, so the first `2^m - 1`
+/// points together with the origin are the same set as in the natural
+/// order. Up to 21 parameters are supported.
+pub fn sobol(parameters: &[Parameter], samples: usize) -> Result<SampleMatrix, DesignError> {
+    validate(parameters)?;
+    if parameters.len() > DIRECTIONS.len() + 1 {
+        return Err(DesignError::Dimensions {
+            max: DIRECTIONS.len() + 1,
+            found: parameters.len(),
+        });
+    }
+    let directions: Vec<_> = (0..parameters.len()).map(direction_numbers).collect();
+    let scale = (1u64 << BITS) as f64;
+    let mut point = vec![0u32; parameters.len()];
+    let rows: Vec<Vec<f64>> = (0..samples)
+        .map(|index| {
+            // The direction to add is that of the lowest zero bit of the
+            // index of the previous point
+            let bit = (index.trailing_ones() as usize).min(BITS - 1);
+            point
+                .iter_mut()
+                .zip(&directions)
+                .zip(parameters)
+                .map(|((x, v), parameter)| {
+                    *x ^= v[bit];
+                    parameter.value_at(*x as f64 / scale)
+                })
+                .collect()
+        })
+        .collect();
+    Ok(SampleMatrix::new(parameters, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(names: &str) -> Vec<Parameter> {
+        names
+            .chars()
+            .map(|name| Parameter::new(&name.to_string(), 0.0, 1.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_factorials() {
+        let full = full_factorial(&unit("abc")).unwrap();
+        assert_eq!(full.rows.len(), 8);
+        assert_eq!(full.rows[0], [0.0, 0.0, 0.0]);
+        assert_eq!(full.rows[1], [0.0, 0.0, 1.0]);
+
+        let half = fractional_factorial(&unit("abcd"), &["ABC"]).unwrap();
+        assert_eq!(half.rows.len(), 8);
+        for row in &half.rows {
+            let high = row[..3].iter().filter(|&&x| x == 1.0).count();
+            assert_eq!(row[3], if high % 2 == 1 { 1.0 } else { 0.0 });
+        }
+        assert!(matches!(
+            fractional_factorial(&unit("abcd"), &["AD"]),
+            Err(DesignError::Generator(_))
+        ));
+        assert!(matches!(
+            full_factorial(&[Parameter::new("x", 1.0, 0.0)]),
+            Err(DesignError::Range { .. })
+        ));
+    }
+
+    #[test]
+    fn test_latin_hypercube() {
+        let parameters = [
+            Parameter::new("x", 0.0, 10.0),
+            Parameter::new("y", -1.0, 1.0),
+        ];
+        let design = latin_hypercube(&parameters, 10, 7).unwrap();
+        assert_eq!(design, latin_hypercube(&parameters, 10, 7).unwrap());
+        // Every stratum of every parameter is sampled exactly once
+        for parameter in &parameters {
+            let mut strata: Vec<usize> = design
+                .column(&parameter.name)
+                .unwrap()
+                .iter()
+                .map(|&x| ((x - parameter.min) / (parameter.max - parameter.min) * 10.0) as usize)
+                .collect();
+            strata.sort();
+            assert_eq!(strata, (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_sobol() {
+        let design = sobol(&unit("abc"), 5).unwrap();
+        assert_eq!(
+            design.rows,
+            [
+                [0.5, 0.5, 0.5],
+                [0.75, 0.25, 0.25],
+                [0.25, 0.75, 0.75],
+                [0.375, 0.375, 0.625],
+                [0.875, 0.875, 0.125],
+            ]
+        );
+        assert!(matches!(
+            sobol(&unit("abcdefghijklmnopqrstuv"), 1),
+            Err(DesignError::Dimensions { max: 21, .. })
+        ));
+    }
+}
@@ -0,0 +1,476 @@
+//! Statistics over ensembles of runs.
+//!
+//! An [`Ensemble`] takes runs one at a time, such as the runs of a Monte
+//! Carlo or sensitivity analysis, and keeps for each variable and saved
+//! time only what it needs for its statistics, so runs can be dropped once
+//! added:
+//!
+//! - the mean and standard deviation, updated with Welford's method
+//! - the envelope of the smallest and largest values
+//! - percentiles, estimated with the P² algorithm of Jain and Chlamtac,
+//!   which keeps five markers per percentile instead of every value and is
+//!   exact up to five runs
+//! - exceedance probabilities: the share of runs above a threshold
+//!
+//! ```rust
+//! use xmile::analysis::ensemble::Ensemble;
+//! use xmile::data::ExportData;
+//!
+//! let mut ensemble = Ensemble::new().with_percentiles(&[0.5]).unwrap();
+//! for growth in [1.0, 2.0, 3.0] {
+//!     let run = ExportData::new(vec![0.0, 1.0])
+//!         .with_series("Population", vec![10.0, 10.0 + growth]);
+//!     ensemble.add(&run).unwrap();
+//! }
+//! assert_eq!(ensemble.mean("Population"), Some(vec![10.0, 12.0]));
+//! assert_eq!(ensemble.percentile("Population", 0.5), Some(vec![10.0, 12.0]));
+//! ```
+
+use thiserror::Error;
+
+use crate::data::ExportData;
+use crate::data::export::find_series;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EnsembleError {
+    #[error("Percentiles must be between 0 and 1, not {0}")]
+    Percentile(f64),
+    #[error("Run {run} is not saved at the same times as the first run")]
+    Times { run: usize },
+    #[error("Run {run} has no values for {name}")]
+    MissingSeries { run: usize, name: String },
+    #[error("Series '{name}' of run {run} has {found} values but there are {expected} times")]
+    Length {
+        run: usize,
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Statistics over runs, gathered one run at a time.
+#[derive(Debug, Clone, Default)]
+pub struct Ensemble {
+    percentiles: Vec<f64>,
+    /// Thresholds for exceedance probabilities, by variable name.
+    thresholds: Vec<(String, f64)>,
+    /// The variables to gather statistics for, or all those of the first
+    /// run when empty.
+    names: Vec<String>,
+    runs: usize,
+    times: Vec<f64>,
+    variables: Vec<Variable>,
+}
+
+/// The statistics of one variable.
+#[derive(Debug, Clone)]
+struct Variable {
+    name: String,
+    thresholds: Vec<f64>,
+    points: Vec<Point>,
+}
+
+/// The statistics of one variable at one saved time.
+#[derive(Debug, Clone)]
+struct Point {
+    mean: f64,
+    /// The sum of squared differences from the mean.
+    squares: f64,
+    min: f64,
+    max: f64,
+    percentiles: Vec<Quantile>,
+    /// The number of runs above each threshold.
+    exceeding: Vec<usize>,
+}
+
+impl Ensemble {
+    /// An empty ensemble gathering means, standard deviations and
+    /// envelopes.
+    pub fn new() -> Self {
+        Ensemble::default()
+    }
+
+    /// Also estimates the given percentiles, each a fraction between 0 and
+    /// 1, such as `[0.05, 0.5, 0.95]` for a median with a 90% band.
+    pub fn with_percentiles(mut self, percentiles: &[f64]) -> Result<Self, EnsembleError> {
+        if let Some(&invalid) = percentiles.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return Err(EnsembleError::Percentile(invalid));
+        }
+        self.percentiles = percentiles.to_vec();
+        Ok(self)
+    }
+
+    /// Also counts how often the named variable is above `threshold`.
+    pub fn with_threshold(mut self, name: &str, threshold: f64) -> Self {
+        self.thresholds.push((name.to_string(), threshold));
+        self
+    }
+
+    /// Gathers statistics only for the named variables.
+    pub fn with_variables<S: AsRef<str>>(mut self, names: &[S]) -> Self {
+        self.names = names.iter().map(|name| name.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Adds a run. The first run sets the saved times, and the variables
+    /// unless they were given; every later run must be saved at the same
+    /// times and have values for the same variables.
+    pub fn add(&mut self, run: &ExportData) -> Result<(), EnsembleError> {
+        if self.runs == 0 {
+            self.start(run);
+        } else if run.times.len() != self.times.len()
+            || run
+                .times
+                .iter()
+                .zip(&self.times)
+                .any(|(a, b)| (a - b).abs() > 1e-9 * b.abs().max(1.0))
+        {
+            return Err(EnsembleError::Times { run: self.runs });
+        }
+
+        let values = self
+            .variables
+            .iter()
+            .map(|variable| {
+                let values =
+                    run.series(&variable.name)
+                        .ok_or_else(|| EnsembleError::MissingSeries {
+                            run: self.runs,
+                            name: variable.name.clone(),
+                        })?;
+                if values.len() != self.times.len() {
+                    return Err(EnsembleError::Length {
+                        run: self.runs,
+                        name: variable.name.clone(),
+                        expected: self.times.len(),
+                        found: values.len(),
+                    });
+                }
+                Ok(values)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.runs += 1;
+        let runs = self.runs as f64;
+        for (variable, values) in self.variables.iter_mut().zip(values) {
+            for (point, &value) in variable.points.iter_mut().zip(values) {
+                let delta = value - point.mean;
+                point.mean += delta / runs;
+                point.squares += delta * (value - point.mean);
+                point.min = point.min.min(value);
+                point.max = point.max.max(value);
+                for quantile in &mut point.percentiles {
+                    quantile.add(value);
+                }
+                for (count, &threshold) in point.exceeding.iter_mut().zip(&variable.thresholds) {
+                    if value > threshold {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, run: &ExportData) {
+        self.times = run.times.clone();
+        let names: Vec<String> = if self.names.is_empty() {
+            run.series.iter().map(|(name, _)| name.clone()).collect()
+        } else {
+            self.names.clone()
+        };
+        self.variables = names
+            .into_iter()
+            .map(|name| {
+                let thresholds: Vec<f64> = self
+                    .thresholds
+                    .iter()
+                    .filter(|(variable, _)| {
+                        find_series(std::iter::once(variable.as_str()), &name).is_some()
+                    })
+                    .map(|&(_, threshold)| threshold)
+                    .collect();
+                let point = Point {
+                    mean: 0.0,
+                    squares: 0.0,
+                    min: f64::INFINITY,
+                    max: f64::NEG_INFINITY,
+                    percentiles: self.percentiles.iter().map(|&p| Quantile::new(p)).collect(),
+                    exceeding: vec![0; thresholds.len()],
+                };
+                Variable {
+                    points: vec![point; self.times.len()],
+                    name,
+                    thresholds,
+                }
+            })
+            .collect();
+    }
+
+    /// The number of runs added.
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// The saved times of the runs.
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// The percentiles estimated, as fractions.
+    pub fn percentiles(&self) -> &[f64] {
+        &self.percentiles
+    }
+
+    /// The names of the variables with statistics.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.variables.iter().map(|variable| variable.name.as_str())
+    }
+
+    fn variable(&self, name: &str) -> Option<&Variable> {
+        if self.runs == 0 {
+            return None;
+        }
+        let index = find_series(self.variables(), name)?;
+        Some(&self.variables[index])
+    }
+
+    fn each(&self, name: &str, f: impl Fn(&Point) -> f64) -> Option<Vec<f64>> {
+        Some(self.variable(name)?.points.iter().map(f).collect())
+    }
+
+    /// The mean of the named variable at each saved time.
+    pub fn mean(&self, name: &str) -> Option<Vec<f64>> {
+        self.each(name, |point| point.mean)
+    }
+
+    /// The sample standard deviation of the named variable at each saved
+    /// time, which is zero for a single run.
+    pub fn std_dev(&self, name: &str) -> Option<Vec<f64>> {
+        let denominator = self.runs.saturating_sub(1).max(1) as f64;
+        self.each(name, |point| (point.squares / denominator).sqrt())
+    }
+
+    /// The smallest and largest values of the named variable at each saved
+    /// time.
+    pub fn envelope(&self, name: &str) -> Option<(Vec<f64>, Vec<f64>)> {
+        Some((
+            self.each(name, |point| point.min)?,
+            self.each(name, |point| point.max)?,
+        ))
+    }
+
+    /// The estimated percentile `p` of the named variable at each saved
+    /// time, if `p` is one of the ensemble's percentiles.
+    pub fn percentile(&self, name: &str, p: f64) -> Option<Vec<f64>> {
+        let index = self.percentiles.iter().position(|&q| q == p)?;
+        self.each(name, |point| point.percentiles[index].estimate())
+    }
+
+    /// The share of runs in which the named variable is above `threshold`
+    /// at each saved time, if the ensemble was given that threshold for
+    /// the variable.
+    pub fn exceedance(&self, name: &str, threshold: f64) -> Option<Vec<f64>> {
+        let variable = self.variable(name)?;
+        let index = variable.thresholds.iter().position(|&t| t == threshold)?;
+        let runs = self.runs as f64;
+        Some(
+            variable
+                .points
+                .iter()
+                .map(|point| point.exceeding[index] as f64 / runs)
+                .collect(),
+        )
+    }
+
+    /// All statistics as series for export, named after their variable:
+    /// `Population mean`, `Population sd`, `Population min`,
+    /// `Population max`, a percentile such as `Population p95`, and an
+    /// exceedance probability such as `Population > 1000`.
+    pub fn summary(&self) -> ExportData {
+        let mut data = ExportData::new(self.times.clone());
+        for variable in &self.variables {
+            let name = &variable.name;
+            let (min, max) = self.envelope(name).unwrap_or_default();
+            data = data
+                .with_series(&format!("{name} mean"), self.mean(name).unwrap_or_default())
+                .with_series(
+                    &format!("{name} sd"),
+                    self.std_dev(name).unwrap_or_default(),
+                )
+                .with_series(&format!("{name} min"), min)
+                .with_series(&format!("{name} max"), max);
+            for &p in &self.percentiles {
+                // Rounded so that 0.05 gives p5 rather than p5.000000000000001
+                let percent = (p * 100.0 * 1e6).round() / 1e6;
+                data = data.with_series(
+                    &format!("{name} p{percent}"),
+                    self.percentile(name, p).unwrap_or_default(),
+                );
+            }
+            for &threshold in &variable.thresholds {
+                data = data.with_series(
+                    &format!("{name} > {threshold}"),
+                    self.exceedance(name, threshold).unwrap_or_default(),
+                );
+            }
+        }
+        data
+    }
+}
+
+/// A streaming estimate of one percentile with the P² algorithm.
+///
+/// Five markers track the smallest value, the percentile, the largest
+/// value and two points halfway between. Each new value shifts the
+/// positions of the markers above it, and markers that drift from their
+/// desired positions are moved by one, adjusting their heights along a
+/// parabola through their neighbours.
+#[derive(Debug, Clone)]
+struct Quantile {
+    p: f64,
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl Quantile {
+    fn new(p: f64) -> Self {
+        Quantile {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+
+        let (q, n) = (&mut self.heights, &mut self.positions);
+        let cell = if value < q[0] {
+            q[0] = value;
+            0
+        } else if value >= q[4] {
+            q[4] = value;
+            3
+        } else {
+            (0..4).rfind(|&i| q[i] <= value).unwrap_or(0)
+        };
+        for position in &mut n[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(&self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let drift = self.desired[i] - n[i];
+            if (drift >= 1.0 && n[i + 1] - n[i] > 1.0) || (drift <= -1.0 && n[i - 1] - n[i] < -1.0)
+            {
+                let s = drift.signum();
+                let parabolic = q[i]
+                    + s / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + s) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - s) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if s > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + s * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += s;
+            }
+        }
+    }
+
+    /// The estimate, which is exact, interpolating between values, until
+    /// there are more than five values.
+    fn estimate(&self) -> f64 {
+        if self.count > 5 {
+            return self.heights[2];
+        }
+        let mut values = self.heights[..self.count].to_vec();
+        if values.is_empty() {
+            return f64::NAN;
+        }
+        values.sort_by(f64::total_cmp);
+        let rank = self.p * (values.len() - 1) as f64;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+        values[below] + (values[above] - values[below]) * (rank - below as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let mut ensemble = Ensemble::new()
+            .with_percentiles(&[0.1, 0.5, 0.9])
+            .unwrap()
+            .with_threshold("stock", 80.0);
+        // The values 0 to 100 in a scrambled order
+        for run in 0..101 {
+            let value = ((run * 37) % 101) as f64;
+            let data = ExportData::new(vec![0.0, 1.0]).with_series("Stock", vec![value, 50.0]);
+            ensemble.add(&data).unwrap();
+        }
+        assert_eq!(ensemble.runs(), 101);
+        let mean = ensemble.mean("Stock").unwrap();
+        assert!((mean[0] - 50.0).abs() < 1e-9 && mean[1] == 50.0);
+        assert_eq!(
+            ensemble.envelope("Stock"),
+            Some((vec![0.0, 50.0], vec![100.0, 50.0]))
+        );
+        assert_eq!(ensemble.std_dev("Stock").unwrap()[1], 0.0);
+        for p in [0.1, 0.5, 0.9] {
+            let estimate = ensemble.percentile("Stock", p).unwrap();
+            assert!((estimate[0] - p * 100.0).abs() < 3.0, "p{p}: {estimate:?}");
+            assert_eq!(estimate[1], 50.0);
+        }
+        assert_eq!(ensemble.percentile("Stock", 0.25), None);
+        assert_eq!(
+            ensemble.exceedance("Stock", 80.0),
+            Some(vec![20.0 / 101.0, 0.0])
+        );
+        assert_eq!(ensemble.summary().series.len(), 8);
+    }
+
+    #[test]
+    fn test_mismatched_runs() {
+        let mut ensemble = Ensemble::new();
+        let run = |times: Vec<f64>| {
+            let values = vec![1.0; times.len()];
+            ExportData::new(times).with_series("x", values)
+        };
+        ensemble.add(&run(vec![0.0, 1.0])).unwrap();
+        assert!(matches!(
+            ensemble.add(&run(vec![0.0, 0.5])),
+            Err(EnsembleError::Times { run: 1 })
+        ));
+        assert!(matches!(
+            ensemble.add(&ExportData::new(vec![0.0, 1.0])),
+            Err(EnsembleError::MissingSeries { run: 1, .. })
+        ));
+        assert_eq!(ensemble.runs(), 1);
+        assert!(matches!(
+            Ensemble::new().with_percentiles(&[1.5]),
+            Err(EnsembleError::Percentile(_))
+        ));
+    }
+}
@@ -0,0 +1,373 @@
+//! Mass balance audits of runs.
+//!
+//! Stocks joined by flows between them form a chain that conserves what it
+//! holds: the flows between its stocks only move material around, so the
+//! total can only change through the flows that cross the model boundary,
+//! to and from clouds. [`mass_balance`] checks this over a run, step by
+//! step, for every stock and every chain.
+//!
+//! A stock's change over a step is expected to be its net flow at the
+//! start of the step times the step's length, which is how Euler's method
+//! moves stocks. The run should therefore save every step of an Euler run;
+//! with fewer saved steps or another integration method, differences are
+//! reported that are not losses. Where an Euler run does not balance,
+//! something other than the flows moved a stock: usually a non-negative
+//! stock that was kept from going below zero, taking less out than its
+//! outflows say, which a DT that is too large makes more likely.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::Identifier;
+use crate::data::ExportData;
+use crate::model::vars::Variable;
+use crate::model::vars::flow::FlowEndpoint;
+use crate::xml::schema::Model;
+
+/// The relative tolerance [`mass_balance`] allows for rounding.
+pub const DEFAULT_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MassBalanceError {
+    #[error("The run has no values for {0}")]
+    MissingSeries(String),
+}
+
+/// The outcome of a mass balance audit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MassBalanceReport {
+    /// The chains of stocks, ordered by their first stock in the model.
+    pub chains: Vec<ChainBalance>,
+}
+
+impl MassBalanceReport {
+    /// Whether every chain balances.
+    pub fn is_balanced(&self) -> bool {
+        self.chains.iter().all(ChainBalance::is_balanced)
+    }
+
+    /// The steps at which a stock did not change by its net flow, in order
+    /// of chain, then stock, then step.
+    pub fn violations(&self) -> impl Iterator<Item = &Violation> {
+        self.chains.iter().flat_map(|chain| &chain.violations)
+    }
+}
+
+impl fmt::Display for MassBalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chain in &self.chains {
+            let status = if chain.is_balanced() {
+                "balanced"
+            } else {
+                "NOT BALANCED"
+            };
+            writeln!(f, "{}: {}", chain.stocks.join(", "), status)?;
+            if !chain.is_balanced() {
+                writeln!(
+                    f,
+                    "  changed by {} against net boundary flow of {}",
+                    chain.change, chain.accumulated
+                )?;
+            }
+            for violation in &chain.violations {
+                writeln!(f, "  {violation}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stocks joined by flows between them, and how well they balanced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainBalance {
+    /// The stocks of the chain, in model order.
+    pub stocks: Vec<String>,
+    /// Flows into the chain from clouds.
+    pub inflows: Vec<String>,
+    /// Flows out of the chain into clouds.
+    pub outflows: Vec<String>,
+    /// Flows between stocks of the chain.
+    pub transfers: Vec<String>,
+    /// The change in the total of the chain's stocks over the run.
+    pub change: f64,
+    /// The inflows less the outflows of the chain, accumulated over the run.
+    pub accumulated: f64,
+    /// The tolerance `change` was compared to `accumulated` with.
+    pub tolerance: f64,
+    pub violations: Vec<Violation>,
+}
+
+impl ChainBalance {
+    /// Whether the chain's total changed only by its boundary flows and
+    /// every stock changed by its net flow at every step.
+    pub fn is_balanced(&self) -> bool {
+        self.violations.is_empty() && (self.change - self.accumulated).abs() <= self.tolerance
+    }
+}
+
+/// A step over which a stock did not change by its net flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub stock: String,
+    /// The saved step the change starts from.
+    pub step: usize,
+    pub time: f64,
+    /// The net flow of the stock times the length of the step.
+    pub expected: f64,
+    /// How much the stock changed.
+    pub actual: f64,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} changed by {} instead of {} from time {}",
+            self.stock, self.actual, self.expected, self.time
+        )
+    }
+}
+
+/// Audits the mass balance of `run`, a run of `model`, with the default
+/// tolerance.
+pub fn mass_balance(
+    model: &Model,
+    run: &ExportData,
+) -> Result<MassBalanceReport, MassBalanceError> {
+    mass_balance_with_tolerance(model, run, DEFAULT_TOLERANCE)
+}
+
+/// Audits the mass balance of `run`, a run of `model`.
+///
+/// Differences are allowed up to `tolerance` times the size of the values
+/// compared, or `tolerance` itself for values smaller than one.
+pub fn mass_balance_with_tolerance(
+    model: &Model,
+    run: &ExportData,
+    tolerance: f64,
+) -> Result<MassBalanceReport, MassBalanceError> {
+    let series = |name: &str| {
+        run.series(name)
+            .ok_or_else(|| MassBalanceError::MissingSeries(name.to_string()))
+    };
+    let steps = run.times.len().saturating_sub(1);
+    let lengths: Vec<f64> = run.times.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let allowed = |values: &[f64]| {
+        tolerance
+            * values
+                .iter()
+                .fold(1.0_f64, |largest, value| largest.max(value.abs()))
+    };
+
+    let stocks: Vec<_> = model
+        .variables
+        .variables
+        .iter()
+        .filter_map(|variable| match variable {
+            Variable::Stock(stock) => Some(stock),
+            _ => None,
+        })
+        .collect();
+    let position = |name: &Identifier| stocks.iter().position(|stock| stock.name() == name);
+
+    // Each stock starts in a chain of its own; flows between stocks join
+    // their chains
+    let mut chain_of: Vec<usize> = (0..stocks.len()).collect();
+    let mut flows = Vec::new();
+    for variable in &model.variables.variables {
+        let Variable::Flow(flow) = variable else {
+            continue;
+        };
+        let endpoints = flow.endpoints(model);
+        let end = |endpoint: &FlowEndpoint| match endpoint {
+            FlowEndpoint::Stock(name) => position(name),
+            FlowEndpoint::Cloud => None,
+        };
+        let (from, to) = (end(&endpoints.from), end(&endpoints.to));
+        if let (Some(from), Some(to)) = (from, to) {
+            let (keep, merge) = (chain_of[from], chain_of[to]);
+            for chain in &mut chain_of {
+                if *chain == merge {
+                    *chain = keep;
+                }
+            }
+        }
+        flows.push((flow.name.to_string(), from, to));
+    }
+
+    let mut chains = Vec::new();
+    for (first, &chain) in chain_of.iter().enumerate() {
+        if chain_of[..first].contains(&chain) {
+            continue;
+        }
+        let members: Vec<usize> = (0..stocks.len())
+            .filter(|&stock| chain_of[stock] == chain)
+            .collect();
+        let in_chain = |stock: Option<usize>| stock.is_some_and(|stock| chain_of[stock] == chain);
+
+        let mut balance = ChainBalance {
+            stocks: members
+                .iter()
+                .map(|&stock| stocks[stock].name().to_string())
+                .collect(),
+            inflows: Vec::new(),
+            outflows: Vec::new(),
+            transfers: Vec::new(),
+            change: 0.0,
+            accumulated: 0.0,
+            tolerance: 0.0,
+            violations: Vec::new(),
+        };
+        for (name, from, to) in &flows {
+            match (in_chain(*from), in_chain(*to)) {
+                (true, true) => balance.transfers.push(name.clone()),
+                (false, true) => balance.inflows.push(name.clone()),
+                (true, false) => balance.outflows.push(name.clone()),
+                (false, false) => {}
+            }
+        }
+
+        let mut totals = vec![0.0; steps + 1];
+        for &member in &members {
+            let stock = stocks[member];
+            let name = stock.name().to_string();
+            let values = series(&name)?;
+            let inflows = stock
+                .inflows()
+                .iter()
+                .map(|flow| series(&flow.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let outflows = stock
+                .outflows()
+                .iter()
+                .map(|flow| series(&flow.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            for (total, value) in totals.iter_mut().zip(values) {
+                *total += value;
+            }
+            for step in 0..steps.min(values.len().saturating_sub(1)) {
+                let net: f64 = inflows.iter().map(|flow| flow[step]).sum::<f64>()
+                    - outflows.iter().map(|flow| flow[step]).sum::<f64>();
+                let expected = net * lengths[step];
+                let actual = values[step + 1] - values[step];
+                if (actual - expected).abs() > allowed(&[values[step], values[step + 1], expected])
+                {
+                    balance.violations.push(Violation {
+                        stock: name.clone(),
+                        step,
+                        time: run.times[step],
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        let boundary = |names: &[String]| -> Result<f64, MassBalanceError> {
+            let mut sum = 0.0;
+            for name in names {
+                let values = series(name)?;
+                sum += (0..steps.min(values.len()))
+                    .map(|step| values[step] * lengths[step])
+                    .sum::<f64>();
+            }
+            Ok(sum)
+        };
+        balance.accumulated = boundary(&balance.inflows)? - boundary(&balance.outflows)?;
+        if let (Some(first), Some(last)) = (totals.first(), totals.last()) {
+            balance.change = last - first;
+            balance.tolerance = allowed(&[*first, *last, balance.accumulated]);
+        }
+        chains.push(balance);
+    }
+    Ok(MassBalanceReport { chains })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Simulator;
+    use crate::xml::XmileFile;
+
+    fn audit(variables: &str) -> MassBalanceReport {
+        let file = XmileFile::from_str(&format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+                <sim_specs><start>0</start><stop>3</stop><dt>1</dt></sim_specs>
+                <model><variables>{variables}</variables></model>
+            </xmile>"#
+        ))
+        .unwrap();
+        let model = &file.models[0];
+        let run = Simulator::new(model, file.sim_specs.as_ref().unwrap())
+            .unwrap()
+            .run()
+            .unwrap();
+        mass_balance(model, &run).unwrap()
+    }
+
+    #[test]
+    fn test_balanced_chains() {
+        let report = audit(
+            r#"<stock name="Susceptible"><eqn>100</eqn><outflow>Infection</outflow></stock>
+               <stock name="Infected"><eqn>1</eqn><inflow>Infection</inflow><outflow>Deaths</outflow></stock>
+               <stock name="Births Recorded"><eqn>0</eqn><inflow>Births</inflow></stock>
+               <flow name="Infection"><eqn>Susceptible * 0.25</eqn></flow>
+               <flow name="Deaths"><eqn>Infected * 0.5</eqn></flow>
+               <flow name="Births"><eqn>2</eqn></flow>"#,
+        );
+        assert!(report.is_balanced(), "{report}");
+        let [disease, births] = report.chains.as_slice() else {
+            panic!("Expected two chains");
+        };
+        assert_eq!(disease.stocks, ["Susceptible", "Infected"]);
+        assert_eq!(disease.transfers, ["Infection"]);
+        assert_eq!(disease.outflows, ["Deaths"]);
+        assert!(disease.inflows.is_empty());
+        assert_eq!(births.inflows, ["Births"]);
+        assert_eq!(births.change, 6.0);
+        assert_eq!(births.accumulated, 6.0);
+    }
+
+    #[test]
+    fn test_clamped_stock_breaks_balance() {
+        // The tank cannot drain 3 a step for long, so the bucket receives
+        // more than the tank loses
+        let report = audit(
+            r#"<stock name="Tank"><eqn>5</eqn><outflow>Drain</outflow><non_negative/></stock>
+               <stock name="Bucket"><eqn>0</eqn><inflow>Drain</inflow></stock>
+               <flow name="Drain"><eqn>3</eqn></flow>"#,
+        );
+        assert!(!report.is_balanced());
+        let chain = &report.chains[0];
+        assert_eq!((chain.change, chain.accumulated), (4.0, 0.0));
+        let violations: Vec<(&str, usize, f64, f64)> = report
+            .violations()
+            .map(|v| (v.stock.as_str(), v.step, v.expected, v.actual))
+            .collect();
+        assert_eq!(
+            violations,
+            [("Tank", 1, -3.0, -2.0), ("Tank", 2, -3.0, 0.0)]
+        );
+        assert!(
+            report
+                .to_string()
+                .contains("Tank changed by -2 instead of -3 from time 1")
+        );
+
+        let missing = mass_balance(
+            &XmileFile::from_str(
+                r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                    <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+                    <model><variables><stock name="Tank"><eqn>5</eqn></stock></variables></model>
+                </xmile>"#,
+            )
+            .unwrap()
+            .models[0],
+            &ExportData::new(vec![0.0]),
+        );
+        assert!(matches!(missing, Err(MassBalanceError::MissingSeries(name)) if name == "Tank"));
+    }
+}
@@ -0,0 +1,477 @@
+//! Analyses of models and their runs.
+//!
+//! [`mass_balance`] checks that the stocks of a run changed only by their
+//! flows, [`ensemble`] gathers statistics over many runs, [`doe`] designs
+//! the parameter values of those runs, and [`sensitivity`] ranks the
+//! parameters by their effect on the outputs. The rest of this module
+//! compares a model against a reference model, for grading.
+//!
+//! [`grade`] matches each variable of a reference model with the variable
+//! of the same name in a student's model and checks three things:
+//!
+//! - structure: the variable has the same kind and the same inputs, which
+//!   for stocks are their inflows and outflows as well as the variables
+//!   their initial value uses
+//! - equation: the equations are equivalent, as decided by
+//!   [`Expression::is_equivalent_with`]
+//! - behavior: when runs of both models are given, the variable follows the
+//!   same [`BehaviorPattern`] and stays close to the reference values
+//!
+//! The [`Rubric`] weighs the three into an overall score.
+
+pub mod doe;
+pub mod ensemble;
+pub mod mass_balance;
+pub mod sensitivity;
+pub use doe::{DesignError, Parameter, SampleMatrix};
+pub use ensemble::{Ensemble, EnsembleError};
+pub use mass_balance::{
+    ChainBalance, MassBalanceError, MassBalanceReport, Violation, mass_balance,
+    mass_balance_with_tolerance,
+};
+pub use sensitivity::{ElementaryEffects, SensitivityError, SobolIndex};
+
+use std::collections::HashSet;
+
+use crate::data::export::ExportData;
+use crate::equation::{EquivalenceOptions, Expression, Identifier};
+use crate::model::vars::stock::Stock;
+use crate::model::vars::{Var, Variable};
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+
+/// How a student's model is compared with the reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rubric {
+    pub structure_weight: f64,
+    pub equation_weight: f64,
+    /// Ignored unless runs of both models are given.
+    pub behavior_weight: f64,
+    pub equivalence: EquivalenceOptions,
+    /// The largest root mean square difference from the reference values,
+    /// relative to the range of the reference values, that still matches.
+    pub behavior_tolerance: f64,
+    /// The reference variables to grade, by name. All reference variables
+    /// are graded when empty.
+    pub variables: Vec<String>,
+}
+
+impl Default for Rubric {
+    fn default() -> Self {
+        Rubric {
+            structure_weight: 1.0,
+            equation_weight: 1.0,
+            behavior_weight: 1.0,
+            equivalence: EquivalenceOptions::default(),
+            behavior_tolerance: 0.05,
+            variables: Vec::new(),
+        }
+    }
+}
+
+/// A model to grade, with an optional run of it.
+#[derive(Debug, Clone, Copy)]
+pub struct Submission<'a> {
+    pub model: &'a Model,
+    pub run: Option<&'a ExportData>,
+}
+
+impl<'a> Submission<'a> {
+    pub fn new(model: &'a Model) -> Self {
+        Submission { model, run: None }
+    }
+
+    pub fn with_run(mut self, run: &'a ExportData) -> Self {
+        self.run = Some(run);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariableKind {
+    Stock,
+    Flow,
+    Aux,
+    GraphicalFunction,
+    Module,
+    Group,
+}
+
+/// The shape of a variable's values over a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BehaviorPattern {
+    Constant,
+    /// Rising at a steady or increasing rate.
+    Growth,
+    /// Falling at a steady or increasing rate.
+    Decline,
+    /// Rising or falling at a decreasing rate, towards a goal.
+    GoalSeeking,
+    /// Rising at an increasing and then a decreasing rate.
+    SShapedGrowth,
+    /// Rising then falling, or falling then rising.
+    Overshoot,
+    /// Changing direction more than once.
+    Oscillation,
+}
+
+impl BehaviorPattern {
+    /// Classifies a series of values. Changes smaller than a millionth of
+    /// the range of the values are ignored.
+    pub fn classify(values: &[f64]) -> Self {
+        let (min, max) = values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            });
+        let range = max - min;
+        if values.len() < 2 || !range.is_finite() || range <= 1e-9 * max.abs().max(1.0) {
+            return BehaviorPattern::Constant;
+        }
+        let threshold = range * 1e-6;
+        let differences: Vec<f64> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let directions = signs(&differences, threshold);
+        let turns = directions
+            .windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .count();
+        match turns {
+            0 => {}
+            1 => return BehaviorPattern::Overshoot,
+            _ => return BehaviorPattern::Oscillation,
+        }
+
+        let rising = directions.first() == Some(&1);
+        // Curvature as the rate of change of the speed of change
+        let speeds: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+        let accelerations: Vec<f64> = speeds.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let curvature = signs(&accelerations, threshold / values.len() as f64);
+        let accelerating = curvature.first() == Some(&1);
+        let bends = curvature
+            .windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .count();
+        match (rising, curvature.is_empty(), accelerating, bends) {
+            (true, false, true, 1) => BehaviorPattern::SShapedGrowth,
+            (_, false, false, 0) => BehaviorPattern::GoalSeeking,
+            (true, ..) => BehaviorPattern::Growth,
+            (false, ..) => BehaviorPattern::Decline,
+        }
+    }
+}
+
+/// The signs of the values larger than `threshold` in magnitude.
+fn signs(values: &[f64], threshold: f64) -> Vec<i8> {
+    values
+        .iter()
+        .filter(|value| value.abs() > threshold)
+        .map(|value| if *value > 0.0 { 1 } else { -1 })
+        .collect()
+}
+
+/// How a variable behaved in the two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BehaviorMatch {
+    pub student: BehaviorPattern,
+    pub reference: BehaviorPattern,
+    /// The root mean square difference from the reference values, relative
+    /// to their range (or magnitude, if they are constant).
+    pub error: f64,
+}
+
+impl BehaviorMatch {
+    pub fn matches(&self, tolerance: f64) -> bool {
+        self.student == self.reference && self.error <= tolerance
+    }
+}
+
+/// How one reference variable compares with the student's.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableGrade {
+    pub name: String,
+    pub kind: VariableKind,
+    /// The kind of the student's variable, or `None` if it is missing.
+    pub student_kind: Option<VariableKind>,
+    pub structure_matches: bool,
+    pub equation_matches: bool,
+    /// `None` unless both runs have values for the variable.
+    pub behavior: Option<BehaviorMatch>,
+}
+
+/// The result of grading a model against a reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grade {
+    pub variables: Vec<VariableGrade>,
+    /// The student's variables that are not in the reference.
+    pub extra_variables: Vec<String>,
+    /// The weighted score, from 0 to 1.
+    pub score: f64,
+}
+
+/// Grades `student` against `reference`.
+pub fn grade(student: &Submission, reference: &Submission, rubric: &Rubric) -> Grade {
+    let graded: Option<HashSet<Identifier>> = (!rubric.variables.is_empty()).then(|| {
+        rubric
+            .variables
+            .iter()
+            .filter_map(|name| Identifier::parse_from_attribute(name).ok())
+            .collect()
+    });
+
+    let mut variables = Vec::new();
+    for variable in &reference.model.variables.variables {
+        let Some(name) = get_variable_name(variable) else {
+            continue;
+        };
+        if graded.as_ref().is_some_and(|graded| !graded.contains(name)) {
+            continue;
+        }
+        let theirs = find_variable(student.model, name);
+        let behavior = match (student.run, reference.run) {
+            (Some(student_run), Some(reference_run)) => {
+                compare_runs(student_run, reference_run, &name.to_string())
+            }
+            _ => None,
+        };
+        variables.push(VariableGrade {
+            name: name.to_string(),
+            kind: kind_of(variable),
+            student_kind: theirs.map(kind_of),
+            structure_matches: theirs.is_some_and(|theirs| same_structure(variable, theirs)),
+            equation_matches: theirs.is_some_and(|theirs| {
+                match (equation_of(variable), equation_of(theirs)) {
+                    (Some(ours), Some(theirs)) => {
+                        ours.is_equivalent_with(theirs, &rubric.equivalence)
+                    }
+                    (ours, theirs) => ours.is_none() && theirs.is_none(),
+                }
+            }),
+            behavior,
+        });
+    }
+
+    let extra_variables = student
+        .model
+        .variables
+        .variables
+        .iter()
+        .filter_map(get_variable_name)
+        .filter(|name| find_variable(reference.model, name).is_none())
+        .map(|name| name.to_string())
+        .collect();
+
+    let score = score(&variables, rubric);
+    Grade {
+        variables,
+        extra_variables,
+        score,
+    }
+}
+
+fn score(variables: &[VariableGrade], rubric: &Rubric) -> f64 {
+    let fraction = |count: usize, of: usize| {
+        if of == 0 {
+            None
+        } else {
+            Some(count as f64 / of as f64)
+        }
+    };
+    let structure = fraction(
+        variables.iter().filter(|v| v.structure_matches).count(),
+        variables.len(),
+    );
+    let equation = fraction(
+        variables.iter().filter(|v| v.equation_matches).count(),
+        variables.len(),
+    );
+    let compared: Vec<&BehaviorMatch> = variables
+        .iter()
+        .filter_map(|v| v.behavior.as_ref())
+        .collect();
+    let behavior = fraction(
+        compared
+            .iter()
+            .filter(|behavior| behavior.matches(rubric.behavior_tolerance))
+            .count(),
+        compared.len(),
+    );
+
+    let parts = [
+        (structure, rubric.structure_weight),
+        (equation, rubric.equation_weight),
+        (behavior, rubric.behavior_weight),
+    ];
+    let (total, weights) = parts
+        .iter()
+        .filter_map(|(part, weight)| part.map(|part| (part * weight, *weight)))
+        .fold((0.0, 0.0), |(total, weights), (part, weight)| {
+            (total + part, weights + weight)
+        });
+    if weights > 0.0 { total / weights } else { 0.0 }
+}
+
+fn find_variable<'a>(model: &'a Model, name: &Identifier) -> Option<&'a Variable> {
+    model
+        .variables
+        .variables
+        .iter()
+        .find(|variable| get_variable_name(variable) == Some(name))
+}
+
+fn kind_of(variable: &Variable) -> VariableKind {
+    match variable {
+        Variable::Auxiliary(_) => VariableKind::Aux,
+        Variable::Stock(_) => VariableKind::Stock,
+        Variable::Flow(_) => VariableKind::Flow,
+        Variable::GraphicalFunction(_) => VariableKind::GraphicalFunction,
+        #[cfg(feature = "submodels")]
+        Variable::Module(_) => VariableKind::Module,
+        Variable::Group(_) => VariableKind::Group,
+    }
+}
+
+/// The equation of a variable; for stocks, their initial value.
+fn equation_of(variable: &Variable) -> Option<&Expression> {
+    match variable {
+        Variable::Auxiliary(aux) => aux.equation(),
+        Variable::Stock(stock) => match stock.as_ref() {
+            Stock::Basic(stock) => stock.equation(),
+            Stock::Conveyor(stock) => stock.as_ref().equation(),
+            Stock::Queue(stock) => stock.equation(),
+        },
+        Variable::Flow(flow) => flow.equation(),
+        Variable::GraphicalFunction(gf) => gf.equation(),
+        #[cfg(feature = "submodels")]
+        Variable::Module(module) => module.equation(),
+        Variable::Group(_) => None,
+    }
+}
+
+/// Whether two variables are of the same kind with the same inputs.
+fn same_structure(ours: &Variable, theirs: &Variable) -> bool {
+    if kind_of(ours) != kind_of(theirs) {
+        return false;
+    }
+    if let (Variable::Stock(ours), Variable::Stock(theirs)) = (ours, theirs) {
+        let flows = |flows: &[Identifier]| flows.iter().cloned().collect::<HashSet<_>>();
+        if flows(ours.inflows()) != flows(theirs.inflows())
+            || flows(ours.outflows()) != flows(theirs.outflows())
+        {
+            return false;
+        }
+    }
+    inputs(ours) == inputs(theirs)
+}
+
+/// The names an equation refers to.
+fn inputs(variable: &Variable) -> HashSet<Identifier> {
+    equation_of(variable)
+        .map(|equation| equation.references().into_iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Compares the values of `name` in two runs, at the reference times.
+fn compare_runs(student: &ExportData, reference: &ExportData, name: &str) -> Option<BehaviorMatch> {
+    let expected = reference.series(name)?;
+    if expected.is_empty() {
+        return None;
+    }
+    let resampled: Vec<f64> = reference
+        .times
+        .iter()
+        .map(|&time| student.value_at(name, time))
+        .collect::<Option<_>>()?;
+
+    let (min, max) = expected
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    let scale = match max - min {
+        range if range > 0.0 => range,
+        _ => max.abs().max(1.0),
+    };
+    let squares: f64 = expected
+        .iter()
+        .zip(&resampled)
+        .map(|(expected, actual)| (actual - expected).powi(2))
+        .sum();
+    Some(BehaviorMatch {
+        student: BehaviorPattern::classify(&resampled),
+        reference: BehaviorPattern::classify(expected),
+        error: (squares / expected.len() as f64).sqrt() / scale,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(variables: &str) -> Model {
+        quick_xml::de::from_str(&format!(
+            "<model><variables>{variables}</variables></model>"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_classify_behavior() {
+        let series = |f: fn(f64) -> f64| (0..50).map(|t| f(t as f64)).collect::<Vec<_>>();
+        use BehaviorPattern as P;
+        assert_eq!(P::classify(&series(|_| 3.0)), P::Constant);
+        assert_eq!(P::classify(&series(|t| 1.05f64.powf(t))), P::Growth);
+        assert_eq!(P::classify(&series(|t| 10.0 - t)), P::Decline);
+        assert_eq!(
+            P::classify(&series(|t| 1.0 - (-t / 10.0).exp())),
+            P::GoalSeeking
+        );
+        assert_eq!(
+            P::classify(&series(|t| 100.0 * (-t / 10.0).exp())),
+            P::GoalSeeking
+        );
+        assert_eq!(
+            P::classify(&series(|t| 1.0 / (1.0 + (-(t - 25.0) / 5.0).exp()))),
+            P::SShapedGrowth
+        );
+        assert_eq!(P::classify(&series(|t| t * (50.0 - t))), P::Overshoot);
+        assert_eq!(P::classify(&series(|t| (t / 4.0).sin())), P::Oscillation);
+    }
+
+    #[test]
+    fn test_grade() {
+        let reference = model(
+            r#"<stock name="Population"><eqn>100</eqn><inflow>births</inflow></stock>
+               <flow name="births"><eqn>Population * birth_rate</eqn></flow>
+               <aux name="birth rate"><eqn>0.1</eqn></aux>"#,
+        );
+        let student = model(
+            r#"<stock name="population"><eqn>100</eqn><inflow>Births</inflow></stock>
+               <flow name="Births"><eqn>Birth_Rate * Population</eqn></flow>
+               <aux name="birth rate"><eqn>0.2</eqn></aux>
+               <aux name="unused"><eqn>1</eqn></aux>"#,
+        );
+        let times: Vec<f64> = (0..=10).map(f64::from).collect();
+        let growth = |rate: f64| times.iter().map(|t| 100.0 * (rate * t).exp()).collect();
+        let reference_run = ExportData::new(times.clone()).with_series("Population", growth(0.1));
+        let student_run = ExportData::new(times.clone()).with_series("Population", growth(0.2));
+
+        let grade = grade(
+            &Submission::new(&student).with_run(&student_run),
+            &Submission::new(&reference).with_run(&reference_run),
+            &Rubric::default(),
+        );
+        assert_eq!(grade.variables.len(), 3);
+        assert!(grade.variables.iter().all(|v| v.structure_matches));
+        let equations: Vec<bool> = grade.variables.iter().map(|v| v.equation_matches).collect();
+        assert_eq!(equations, vec![true, true, false]);
+
+        let behavior = grade.variables[0].behavior.as_ref().unwrap();
+        assert_eq!(behavior.student, BehaviorPattern::Growth);
+        assert_eq!(behavior.reference, BehaviorPattern::Growth);
+        assert!(!behavior.matches(0.05));
+        assert_eq!(grade.extra_variables, vec!["unused".to_string()]);
+
+        // Structure counts fully, equations for two of three, behavior not
+        assert!((grade.score - (1.0 + 2.0 / 3.0) / 3.0).abs() < 1e-9);
+    }
+}
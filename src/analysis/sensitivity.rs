@@ -0,0 +1,342 @@
+//! Sensitivity indices: which parameters an output depends on most.
+//!
+//! Two methods are provided, each a design to run and an analysis of the
+//! outputs of its runs, in the order of the design's rows:
+//!
+//! - Variance-based indices, from a [`saltelli`] design of `N * (k + 2)`
+//!   runs for `k` parameters. [`sobol_indices`] estimates each parameter's
+//!   first-order index, the share of the output's variance it causes on its
+//!   own, with the estimator of Saltelli et al. (2010), and its total-effect
+//!   index, which adds its interactions with the other parameters, with the
+//!   estimator of Jansen (1999).
+//! - Elementary effects, from a [`morris`] design of `r * (k + 1)` runs.
+//!   Each of its `r` trajectories changes one parameter at a time, and
+//!   [`morris_effects`] averages the change in the output per change in
+//!   each parameter, as a fraction of its range. It takes far fewer runs and
+//!   is used to screen out parameters that do not matter.
+//!
+//! [`outputs`] picks the value of one variable at one time from each run.
+//! Results are ranked with the most important parameter first:
+//!
+//! ```rust
+//! use xmile::analysis::doe::Parameter;
+//! use xmile::analysis::sensitivity::{saltelli, sobol_indices};
+//!
+//! let parameters = [Parameter::new("a", 0.0, 1.0), Parameter::new("b", 0.0, 1.0)];
+//! let design = saltelli(&parameters, 128).unwrap();
+//! // Stands in for running the model once per scenario
+//! let outputs: Vec<f64> = design.rows.iter().map(|row| row[0] + 3.0 * row[1]).collect();
+//! let indices = sobol_indices(&design, &outputs).unwrap();
+//! assert_eq!(indices[0].parameter, "b");
+//! ```
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use thiserror::Error;
+
+use crate::data::ExportData;
+
+use super::doe::{self, DesignError, Parameter, SampleMatrix};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SensitivityError {
+    #[error("Expected {expected} outputs, one per run of the design, but found {found}")]
+    Outputs { expected: usize, found: usize },
+    #[error("The design does not have the runs of a {0} design")]
+    Design(&'static str),
+    #[error("The output does not vary between runs")]
+    ConstantOutput,
+    #[error("Run {run} has no values for {name}")]
+    MissingSeries { run: usize, name: String },
+}
+
+/// The variance-based indices of a parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SobolIndex {
+    pub parameter: String,
+    /// The share of the output's variance the parameter causes on its own.
+    pub first_order: f64,
+    /// The share of the output's variance the parameter causes on its own
+    /// and through its interactions with the other parameters.
+    pub total: f64,
+}
+
+/// The elementary effects of a parameter, in units of the output per the
+/// parameter's whole range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementaryEffects {
+    pub parameter: String,
+    /// The mean effect, in which effects of opposite signs cancel.
+    pub mu: f64,
+    /// The mean of the absolute effects, which ranks importance.
+    pub mu_star: f64,
+    /// The standard deviation of the effects, which is large for a
+    /// parameter that interacts with others or acts nonlinearly.
+    pub sigma: f64,
+}
+
+/// The value of the named variable at `time` in each run, interpolating
+/// between saved times.
+pub fn outputs(runs: &[ExportData], name: &str, time: f64) -> Result<Vec<f64>, SensitivityError> {
+    runs.iter()
+        .enumerate()
+        .map(|(run, data)| {
+            data.value_at(name, time)
+                .ok_or_else(|| SensitivityError::MissingSeries {
+                    run,
+                    name: name.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// The design for [`sobol_indices`], with `samples` base samples.
+///
+/// Two matrices `A` and `B` of `samples` rows are taken from a Sobol
+/// sequence in twice as many dimensions as there are parameters. The
+/// design's rows are those of `A`, then those of `B`, then for each
+/// parameter in turn those of `A` with that parameter's column taken from
+/// `B`. Up to 10 parameters are supported.
+pub fn saltelli(parameters: &[Parameter], samples: usize) -> Result<SampleMatrix, DesignError> {
+    let k = parameters.len();
+    if k > 10 {
+        return Err(DesignError::Dimensions { max: 10, found: k });
+    }
+    let doubled: Vec<Parameter> = parameters.iter().chain(parameters).cloned().collect();
+    let points = doe::sobol(&doubled, samples)?.rows;
+    let (a, b): (Vec<&[f64]>, Vec<&[f64]>) = points.iter().map(|point| point.split_at(k)).unzip();
+
+    let mut rows: Vec<Vec<f64>> = a.iter().chain(&b).map(|row| row.to_vec()).collect();
+    for i in 0..k {
+        rows.extend(a.iter().zip(&b).map(|(a, b)| {
+            let mut row = a.to_vec();
+            row[i] = b[i];
+            row
+        }));
+    }
+    Ok(SampleMatrix::new(parameters, rows))
+}
+
+/// The first-order and total-effect indices of each parameter of a
+/// [`saltelli`] design, given the output of each of its runs, ranked by
+/// total effect.
+pub fn sobol_indices(
+    design: &SampleMatrix,
+    outputs: &[f64],
+) -> Result<Vec<SobolIndex>, SensitivityError> {
+    check_outputs(design, outputs)?;
+    let k = design.parameters.len();
+    if !design.rows.len().is_multiple_of(k + 2) || design.rows.is_empty() {
+        return Err(SensitivityError::Design("Saltelli"));
+    }
+    let n = design.rows.len() / (k + 2);
+    let (f_a, rest) = outputs.split_at(n);
+    let (f_b, f_ab) = rest.split_at(n);
+
+    let count = (2 * n) as f64;
+    let mean = f_a.iter().chain(f_b).sum::<f64>() / count;
+    let variance = f_a
+        .iter()
+        .chain(f_b)
+        .map(|y| (y - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    if variance == 0.0 {
+        return Err(SensitivityError::ConstantOutput);
+    }
+
+    let mut indices: Vec<SobolIndex> = design
+        .parameters
+        .iter()
+        .zip(f_ab.chunks(n))
+        .map(|(parameter, f_ab)| {
+            let (mut first, mut total) = (0.0, 0.0);
+            for ((a, b), ab) in f_a.iter().zip(f_b).zip(f_ab) {
+                first += b * (ab - a);
+                total += (a - ab).powi(2);
+            }
+            SobolIndex {
+                parameter: parameter.clone(),
+                first_order: first / n as f64 / variance,
+                total: total / (2 * n) as f64 / variance,
+            }
+        })
+        .collect();
+    indices.sort_by(|a, b| b.total.total_cmp(&a.total));
+    Ok(indices)
+}
+
+/// The design for [`morris_effects`]: `trajectories` random trajectories,
+/// drawn with a random number generator seeded with `seed`.
+///
+/// Each parameter takes its `levels` evenly spaced values, which must be
+/// an even number, and moves by `levels / (2 * (levels - 1))` of its range.
+/// A trajectory starts at a random level of every parameter and moves each
+/// parameter once, in a random order, so it has one more point than there
+/// are parameters.
+pub fn morris(
+    parameters: &[Parameter],
+    trajectories: usize,
+    seed: u64,
+) -> Result<SampleMatrix, DesignError> {
+    doe::validate(parameters)?;
+    if let Some(parameter) = parameters
+        .iter()
+        .find(|p| p.levels < 2 || !p.levels.is_multiple_of(2))
+    {
+        return Err(DesignError::Levels {
+            name: parameter.name.clone(),
+            levels: parameter.levels,
+        });
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut rows = Vec::with_capacity(trajectories * (parameters.len() + 1));
+    for _ in 0..trajectories {
+        // The level of each parameter, and the number of levels it moves by
+        let mut levels: Vec<usize> = parameters
+            .iter()
+            .map(|p| rng.gen_range(0..p.levels))
+            .collect();
+        let mut order: Vec<usize> = (0..parameters.len()).collect();
+        order.shuffle(&mut rng);
+
+        let row = |levels: &[usize]| -> Vec<f64> {
+            parameters
+                .iter()
+                .zip(levels)
+                .map(|(p, &level)| p.value_at(level as f64 / (p.levels - 1) as f64))
+                .collect()
+        };
+        rows.push(row(&levels));
+        for i in order {
+            let step = parameters[i].levels / 2;
+            levels[i] = if levels[i] < step {
+                levels[i] + step
+            } else {
+                levels[i] - step
+            };
+            rows.push(row(&levels));
+        }
+    }
+    Ok(SampleMatrix::new(parameters, rows))
+}
+
+/// The elementary effects of each parameter of a [`morris`] design with
+/// the given parameters, given the output of each of its runs, ranked by
+/// `mu_star`.
+pub fn morris_effects(
+    parameters: &[Parameter],
+    design: &SampleMatrix,
+    outputs: &[f64],
+) -> Result<Vec<ElementaryEffects>, SensitivityError> {
+    check_outputs(design, outputs)?;
+    let k = parameters.len();
+    if k != design.parameters.len() || !design.rows.len().is_multiple_of(k + 1) {
+        return Err(SensitivityError::Design("Morris"));
+    }
+
+    let mut effects = vec![Vec::new(); k];
+    for (rows, outputs) in design.rows.chunks(k + 1).zip(outputs.chunks(k + 1)) {
+        for (pair, change) in rows.windows(2).zip(outputs.windows(2)) {
+            let moved = (0..k).find(|&i| pair[0][i] != pair[1][i]);
+            let Some(i) = moved else {
+                // Only a parameter with an empty range stays put
+                continue;
+            };
+            let delta = (pair[1][i] - pair[0][i]) / (parameters[i].max - parameters[i].min);
+            effects[i].push((change[1] - change[0]) / delta);
+        }
+    }
+
+    let mut ranked: Vec<ElementaryEffects> = parameters
+        .iter()
+        .zip(effects)
+        .map(|(parameter, effects)| {
+            let r = effects.len().max(1) as f64;
+            let mu = effects.iter().sum::<f64>() / r;
+            let mu_star = effects.iter().map(|e| e.abs()).sum::<f64>() / r;
+            let squares = effects.iter().map(|e| (e - mu).powi(2)).sum::<f64>();
+            ElementaryEffects {
+                parameter: parameter.name.clone(),
+                mu,
+                mu_star,
+                sigma: (squares / (r - 1.0).max(1.0)).sqrt(),
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.mu_star.total_cmp(&a.mu_star));
+    Ok(ranked)
+}
+
+fn check_outputs(design: &SampleMatrix, outputs: &[f64]) -> Result<(), SensitivityError> {
+    if outputs.len() != design.rows.len() {
+        return Err(SensitivityError::Outputs {
+            expected: design.rows.len(),
+            found: outputs.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> Vec<Parameter> {
+        ["x", "y", "z"]
+            .map(|name| Parameter::new(name, 0.0, 1.0).with_levels(4))
+            .to_vec()
+    }
+
+    /// Depends on `y` twice as much as on `x`, and not on `z`.
+    fn linear(row: &[f64]) -> f64 {
+        row[0] + 2.0 * row[1]
+    }
+
+    #[test]
+    fn test_sobol_indices() {
+        let design = saltelli(&parameters(), 256).unwrap();
+        assert_eq!(design.rows.len(), 256 * 5);
+        let outputs: Vec<f64> = design.rows.iter().map(|row| linear(row)).collect();
+        let indices = sobol_indices(&design, &outputs).unwrap();
+
+        let names: Vec<_> = indices
+            .iter()
+            .map(|index| index.parameter.as_str())
+            .collect();
+        assert_eq!(names, ["y", "x", "z"]);
+        // A linear model has no interactions, and x explains 1/5 of the
+        // variance and y 4/5
+        for (index, expected) in indices.iter().zip([0.8, 0.2, 0.0]) {
+            assert!((index.first_order - expected).abs() < 0.05, "{index:?}");
+            assert!((index.total - expected).abs() < 0.05, "{index:?}");
+        }
+        assert!(matches!(
+            sobol_indices(&design, &outputs[1..]),
+            Err(SensitivityError::Outputs { .. })
+        ));
+    }
+
+    #[test]
+    fn test_morris_effects() {
+        let parameters = parameters();
+        let design = morris(&parameters, 10, 3).unwrap();
+        assert_eq!(design.rows.len(), 40);
+        let outputs: Vec<f64> = design.rows.iter().map(|row| linear(row)).collect();
+        let effects = morris_effects(&parameters, &design, &outputs).unwrap();
+
+        // Every effect of a linear model is its coefficient
+        for (effect, (name, expected)) in effects.iter().zip([("y", 2.0), ("x", 1.0), ("z", 0.0)]) {
+            assert_eq!(effect.parameter, name);
+            assert!((effect.mu_star - expected).abs() < 1e-9, "{effect:?}");
+            assert!(effect.sigma < 1e-9, "{effect:?}");
+        }
+        assert!(matches!(
+            morris(&[Parameter::new("x", 0.0, 1.0).with_levels(3)], 1, 0),
+            Err(DesignError::Levels { levels: 3, .. })
+        ));
+    }
+}
@@ -70,7 +70,7 @@ struct RawBehavior {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct NonNegativeFlag {
-    #[serde(rename = "#text", default = "default_true")]
+    #[serde(rename = "$text", default = "default_true")]
     value: bool,
 }
 
@@ -80,7 +80,11 @@ fn default_true() -> bool {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct EntityBehaviorTag {
-    #[serde(rename = "non_negative", default)]
+    #[serde(
+        rename = "non_negative",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     non_negative: Option<NonNegativeFlag>,
 }
 
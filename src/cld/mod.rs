@@ -0,0 +1,636 @@
+//! Causal loop diagrams.
+//!
+//! A causal loop diagram (CLD) shows which variables influence which, and
+//! in which direction, without the equations of a stock and flow model.
+//! XMILE files holding one list their variables without equations and draw
+//! the influences as connectors carrying a `polarity`. Such files cannot be
+//! read as [`XmileFile`](crate::xml::XmileFile)s, which require every
+//! variable to have an equation, so [`CldModel::from_xmile`] reads them
+//! with the equations left out:
+//!
+//! ```rust
+//! use xmile::cld::{CldModel, LoopPolarity};
+//!
+//! let models = CldModel::from_xmile(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <model>
+//!         <variables><aux name="Population"/><aux name="Births"/></variables>
+//!         <views><view uid="100" width="400" height="300" page_width="400" page_height="300">
+//!             <aux uid="3" name="Population" x="100" y="100"/>
+//!             <aux uid="4" name="Births" x="300" y="100"/>
+//!             <connector uid="1" x="100" y="100" angle="0" polarity="+"><from>Population</from><to>Births</to></connector>
+//!             <connector uid="2" x="300" y="100" angle="180" polarity="+"><from>Births</from><to>Population</to></connector>
+//!         </view></views>
+//!     </model>
+//! </xmile>"#).unwrap();
+//! let loops = models[0].loops();
+//! assert_eq!(loops.len(), 1);
+//! assert_eq!(loops[0].polarity, LoopPolarity::Reinforcing);
+//! ```
+//!
+//! A stock and flow model read as usual can be viewed as a CLD too, with
+//! [`CldModel::from_model`].
+//!
+//! The polarity of a feedback loop is the product of the polarities of its
+//! links: a loop with an even number of negative links is reinforcing, and
+//! one with an odd number is balancing. A loop with a link of unknown
+//! polarity has unknown polarity.
+
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use std::fmt::{self, Write as _};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::model::vars::Variable;
+use crate::view::objects::{Pointer, Polarity};
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+use crate::xml::{ParseError, Views};
+use crate::{Identifier, Uid};
+
+/// The radius of the circle variables without positions are placed on, per
+/// variable.
+const SPACING: f64 = 40.0;
+const MARGIN: f64 = 60.0;
+/// The distance between the centre of a variable's label and the ends of
+/// links attached to it.
+const CLEARANCE: f64 = 28.0;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CldError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("Invalid variable name: {0}")]
+    InvalidName(String),
+    #[error("Connector points to unknown alias {}", .0.value)]
+    UnresolvedAlias(Uid),
+    #[error("Link refers to unknown variable {0}")]
+    UnknownVariable(String),
+    #[error("Link from {0} to itself")]
+    SelfLink(String),
+}
+
+/// A variable of a causal loop diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CldVariable {
+    pub name: Identifier,
+    /// The centre of the variable in the first view showing it.
+    pub position: Option<(f64, f64)>,
+}
+
+/// An influence of one variable on another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CausalLink {
+    pub from: Identifier,
+    pub to: Identifier,
+    pub polarity: Polarity,
+    /// Whether the link is marked as acting with a delay.
+    pub delayed: bool,
+}
+
+/// Whether a feedback loop amplifies or counteracts change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoopPolarity {
+    Reinforcing,
+    Balancing,
+    /// A link of the loop has no polarity.
+    Unknown,
+}
+
+impl fmt::Display for LoopPolarity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LoopPolarity::Reinforcing => "R",
+            LoopPolarity::Balancing => "B",
+            LoopPolarity::Unknown => "?",
+        })
+    }
+}
+
+/// A closed chain of links.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedbackLoop {
+    /// The variables of the loop in link order, starting with the one
+    /// listed first in the model.
+    pub variables: Vec<Identifier>,
+    pub polarity: LoopPolarity,
+    /// Whether any link of the loop is delayed.
+    pub delayed: bool,
+}
+
+/// The variables of a model and the causal links between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CldModel {
+    pub name: Option<String>,
+    pub variables: Vec<CldVariable>,
+    pub links: Vec<CausalLink>,
+}
+
+/// The parts of an XMILE document a CLD is read from.
+#[derive(Deserialize)]
+struct Document {
+    #[serde(rename = "model", default)]
+    models: Vec<DocumentModel>,
+}
+
+#[derive(Deserialize)]
+struct DocumentModel {
+    #[serde(rename = "@name")]
+    name: Option<String>,
+    #[serde(default)]
+    variables: DocumentVariables,
+    views: Option<Views>,
+}
+
+#[derive(Default, Deserialize)]
+struct DocumentVariables {
+    #[serde(rename = "$value", default)]
+    variables: Vec<DocumentVariable>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DocumentVariable {
+    Aux(Named),
+    Stock(Named),
+    Flow(Named),
+    Gf(Named),
+    Module(Named),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    #[serde(rename = "@name")]
+    name: String,
+}
+
+impl CldModel {
+    /// Reads the CLD of each model in an XMILE document. Variables need no
+    /// equations, and anything but their names and the views is ignored.
+    pub fn from_xmile(xml: &str) -> Result<Vec<CldModel>, CldError> {
+        let document: Document =
+            quick_xml::de::from_str(xml).map_err(|e| ParseError::Deserialize(e.to_string()))?;
+        document
+            .models
+            .iter()
+            .map(|model| {
+                let names = model
+                    .variables
+                    .variables
+                    .iter()
+                    .filter_map(|variable| match variable {
+                        DocumentVariable::Aux(named)
+                        | DocumentVariable::Stock(named)
+                        | DocumentVariable::Flow(named)
+                        | DocumentVariable::Gf(named)
+                        | DocumentVariable::Module(named) => Some(parse_name(&named.name)),
+                        DocumentVariable::Other => None,
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::build(model.name.clone(), names, model.views.as_ref())
+            })
+            .collect()
+    }
+
+    /// The CLD of a stock and flow model: its variables, and the
+    /// connectors of its views as links.
+    pub fn from_model(model: &Model) -> Result<CldModel, CldError> {
+        let names = model
+            .variables
+            .variables
+            .iter()
+            .filter(|variable| !matches!(variable, Variable::Group(_)))
+            .filter_map(get_variable_name)
+            .cloned()
+            .collect();
+        Self::build(model.name.clone(), names, model.views.as_ref())
+    }
+
+    fn build(
+        name: Option<String>,
+        names: Vec<Identifier>,
+        views: Option<&Views>,
+    ) -> Result<CldModel, CldError> {
+        let mut variables: Vec<CldVariable> = names
+            .into_iter()
+            .map(|name| CldVariable {
+                name,
+                position: None,
+            })
+            .collect();
+        let mut links = Vec::new();
+        for view in views.iter().flat_map(|views| &views.views) {
+            let objects = view
+                .stocks
+                .iter()
+                .map(|stock| (&stock.name, stock.x, stock.y))
+                .chain(view.flows.iter().map(|flow| (&flow.name, flow.x, flow.y)))
+                .chain(view.auxes.iter().map(|aux| (&aux.name, aux.x, aux.y)));
+            for (name, x, y) in objects {
+                let (Some(x), Some(y)) = (x, y) else {
+                    continue;
+                };
+                let name = parse_name(name)?;
+                if let Some(variable) = variables
+                    .iter_mut()
+                    .find(|variable| variable.name == name && variable.position.is_none())
+                {
+                    variable.position = Some((x, y));
+                }
+            }
+
+            let resolve = |pointer: &Pointer| match pointer {
+                Pointer::Name(name) => parse_name(name),
+                Pointer::Alias(uid) => view
+                    .aliases
+                    .iter()
+                    .find(|alias| alias.uid == *uid)
+                    .ok_or(CldError::UnresolvedAlias(*uid))
+                    .and_then(|alias| parse_name(&alias.of)),
+            };
+            for connector in &view.connectors {
+                links.push(CausalLink {
+                    from: resolve(&connector.from)?,
+                    to: resolve(&connector.to)?,
+                    polarity: connector.polarity.clone().unwrap_or(Polarity::None),
+                    delayed: connector.delay_mark,
+                });
+            }
+        }
+        Ok(CldModel {
+            name,
+            variables,
+            links,
+        })
+    }
+
+    /// Checks that every link joins two different variables of the model.
+    ///
+    /// Unlike a stock and flow model, a CLD needs no equations, and links
+    /// may have no polarity.
+    pub fn validate(&self) -> Result<(), CldError> {
+        for link in &self.links {
+            for end in [&link.from, &link.to] {
+                if self.index_of(end).is_none() {
+                    return Err(CldError::UnknownVariable(end.to_string()));
+                }
+            }
+            if link.from == link.to {
+                return Err(CldError::SelfLink(link.from.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every feedback loop of the diagram, each once, in the order of their
+    /// first variables in the model. Links between unknown variables are
+    /// ignored.
+    ///
+    /// Parallel links between the same variables make distinct loops.
+    pub fn loops(&self) -> Vec<FeedbackLoop> {
+        let mut outgoing = vec![Vec::new(); self.variables.len()];
+        let mut ends = Vec::with_capacity(self.links.len());
+        for (index, link) in self.links.iter().enumerate() {
+            let from = self.index_of(&link.from);
+            let to = self.index_of(&link.to);
+            if let (Some(from), Some(_)) = (from, to) {
+                outgoing[from].push(index);
+            }
+            ends.push(to);
+        }
+
+        let mut loops = Vec::new();
+        for start in 0..self.variables.len() {
+            let mut path = Vec::new();
+            let mut on_path = HashSet::from([start]);
+            self.search(
+                start,
+                start,
+                &outgoing,
+                &ends,
+                &mut path,
+                &mut on_path,
+                &mut loops,
+            );
+        }
+        loops
+    }
+
+    /// Extends `path`, a chain of links from `start` to `node`, along every
+    /// link to `start` or to a later variable not yet on the path.
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        start: usize,
+        node: usize,
+        outgoing: &[Vec<usize>],
+        ends: &[Option<usize>],
+        path: &mut Vec<usize>,
+        on_path: &mut HashSet<usize>,
+        loops: &mut Vec<FeedbackLoop>,
+    ) {
+        for &link in &outgoing[node] {
+            let Some(next) = ends[link] else {
+                continue;
+            };
+            path.push(link);
+            if next == start {
+                loops.push(self.feedback_loop(path));
+            } else if next > start && on_path.insert(next) {
+                self.search(start, next, outgoing, ends, path, on_path, loops);
+                on_path.remove(&next);
+            }
+            path.pop();
+        }
+    }
+
+    fn feedback_loop(&self, path: &[usize]) -> FeedbackLoop {
+        let links: Vec<&CausalLink> = path.iter().map(|&index| &self.links[index]).collect();
+        let negatives = links
+            .iter()
+            .filter(|link| link.polarity == Polarity::Negative)
+            .count();
+        let polarity = if links.iter().any(|link| link.polarity == Polarity::None) {
+            LoopPolarity::Unknown
+        } else if negatives % 2 == 0 {
+            LoopPolarity::Reinforcing
+        } else {
+            LoopPolarity::Balancing
+        };
+        FeedbackLoop {
+            variables: links.iter().map(|link| link.from.clone()).collect(),
+            polarity,
+            delayed: links.iter().any(|link| link.delayed),
+        }
+    }
+
+    fn index_of(&self, name: &Identifier) -> Option<usize> {
+        self.variables
+            .iter()
+            .position(|variable| variable.name == *name)
+    }
+
+    /// Draws the diagram as an SVG document: variable names joined by
+    /// arrows marked with their polarity, and with a double stroke across
+    /// delayed links.
+    ///
+    /// Variables are drawn at their positions in the views. If any variable
+    /// has no position, all are placed evenly around a circle instead.
+    pub fn to_svg(&self) -> String {
+        let count = self.variables.len();
+        let positions: Vec<(f64, f64)> = if self
+            .variables
+            .iter()
+            .all(|variable| variable.position.is_some())
+        {
+            self.variables
+                .iter()
+                .filter_map(|variable| variable.position)
+                .collect()
+        } else {
+            let radius = SPACING * count as f64 / (2.0 * PI) + SPACING;
+            (0..count)
+                .map(|index| {
+                    let angle = 2.0 * PI * index as f64 / count as f64 - PI / 2.0;
+                    (radius * angle.cos(), radius * angle.sin())
+                })
+                .collect()
+        };
+        let (min_x, min_y, max_x, max_y) = positions.iter().fold(
+            (
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(min_x, min_y, max_x, max_y), &(x, y)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
+        );
+        let (min_x, min_y, max_x, max_y) = if count == 0 {
+            (0.0, 0.0, 0.0, 0.0)
+        } else {
+            (min_x, min_y, max_x, max_y)
+        };
+        let at = |index: usize| {
+            let (x, y) = positions[index];
+            (x - min_x + MARGIN, y - min_y + MARGIN)
+        };
+        let width = number(max_x - min_x + 2.0 * MARGIN);
+        let height = number(max_y - min_y + 2.0 * MARGIN);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="12">"#
+        );
+        svg.push_str(
+            r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#,
+        );
+        svg.push_str("\n<g class=\"links\" stroke=\"black\" fill=\"none\">\n");
+        for link in &self.links {
+            let (Some(from), Some(to)) = (self.index_of(&link.from), self.index_of(&link.to))
+            else {
+                continue;
+            };
+            if from == to {
+                continue;
+            }
+            let ((x1, y1), (x2, y2)) = (at(from), at(to));
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            if length <= 2.0 * CLEARANCE {
+                continue;
+            }
+            let (ux, uy) = ((x2 - x1) / length, (y2 - y1) / length);
+            let (start, end) = (
+                (x1 + ux * CLEARANCE, y1 + uy * CLEARANCE),
+                (x2 - ux * CLEARANCE, y2 - uy * CLEARANCE),
+            );
+            let class = match link.polarity {
+                Polarity::Positive => "link positive",
+                Polarity::Negative => "link negative",
+                Polarity::None => "link",
+            };
+            let _ = writeln!(
+                svg,
+                r#"<line class="{class}" x1="{}" y1="{}" x2="{}" y2="{}" marker-end="url(#arrow)"/>"#,
+                number(start.0),
+                number(start.1),
+                number(end.0),
+                number(end.1)
+            );
+            let sign = match link.polarity {
+                Polarity::Positive => Some("+"),
+                Polarity::Negative => Some("\u{2212}"),
+                Polarity::None => None,
+            };
+            if let Some(sign) = sign {
+                // Beside the arrowhead, on the left of the link
+                let _ = writeln!(
+                    svg,
+                    r#"<text class="polarity" x="{}" y="{}" text-anchor="middle" dominant-baseline="middle" fill="black" stroke="none">{sign}</text>"#,
+                    number(end.0 - ux * 10.0 + uy * 10.0),
+                    number(end.1 - uy * 10.0 - ux * 10.0)
+                );
+            }
+            if link.delayed {
+                let (mx, my) = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+                for offset in [-2.0, 2.0] {
+                    let (cx, cy) = (mx + ux * offset, my + uy * offset);
+                    let _ = writeln!(
+                        svg,
+                        r#"<line class="delay" x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                        number(cx - uy * 6.0),
+                        number(cy + ux * 6.0),
+                        number(cx + uy * 6.0),
+                        number(cy - ux * 6.0)
+                    );
+                }
+            }
+        }
+        svg.push_str("</g>\n<g class=\"variables\">\n");
+        for (index, variable) in self.variables.iter().enumerate() {
+            let (x, y) = at(index);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+                number(x),
+                number(y),
+                escape(&variable.name.to_string())
+            );
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+fn parse_name(name: &str) -> Result<Identifier, CldError> {
+    Identifier::parse_from_attribute(name).map_err(|_| CldError::InvalidName(name.to_string()))
+}
+
+/// Rounds `value` to two decimals for output.
+fn number(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLD: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+    <model>
+        <variables>
+            <aux name="Population"/>
+            <aux name="Births"/>
+            <aux name="Deaths"/>
+            <aux name="Crowding"/>
+        </variables>
+        <views><view uid="100" width="600" height="400" page_width="600" page_height="400">
+            <aux uid="11" name="Population" x="200" y="200"/>
+            <aux uid="12" name="Births" x="100" y="100"/>
+            <aux uid="13" name="Deaths" x="300" y="100"/>
+            <alias uid="9" x="200" y="300"><of>Population</of></alias>
+            <connector uid="1" x="0" y="0" angle="0" polarity="+"><from>Births</from><to>Population</to></connector>
+            <connector uid="2" x="0" y="0" angle="0" polarity="+"><from>Population</from><to>Births</to></connector>
+            <connector uid="3" x="0" y="0" angle="0" polarity="-"><from>Deaths</from><to>Population</to></connector>
+            <connector uid="4" x="0" y="0" angle="0" polarity="+" delay_mark="true"><from><alias uid="9"/></from><to>Deaths</to></connector>
+            <connector uid="5" x="0" y="0" angle="0"><from>Population</from><to>Crowding</to></connector>
+            <connector uid="6" x="0" y="0" angle="0" polarity="-"><from>Crowding</from><to>Births</to></connector>
+        </view></views>
+    </model>
+</xmile>"#;
+
+    #[test]
+    fn test_loop_polarities() {
+        let models = CldModel::from_xmile(CLD).unwrap();
+        let cld = &models[0];
+        assert_eq!(cld.variables.len(), 4);
+        assert_eq!(cld.variables[1].position, Some((100.0, 100.0)));
+        assert_eq!(cld.variables[3].position, None);
+        assert_eq!(cld.links[3].from, parse_name("Population").unwrap());
+        cld.validate().unwrap();
+
+        let loops = cld.loops();
+        let summary: Vec<(String, LoopPolarity, bool)> = loops
+            .iter()
+            .map(|feedback| {
+                let names: Vec<String> = feedback.variables.iter().map(|n| n.to_string()).collect();
+                (names.join(" -> "), feedback.polarity, feedback.delayed)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "Population -> Births".to_string(),
+                    LoopPolarity::Reinforcing,
+                    false
+                ),
+                (
+                    "Population -> Deaths".to_string(),
+                    LoopPolarity::Balancing,
+                    true
+                ),
+                (
+                    "Population -> Crowding -> Births".to_string(),
+                    LoopPolarity::Unknown,
+                    false
+                ),
+            ]
+        );
+
+        let mut invalid = cld.clone();
+        invalid.links[0].to = parse_name("Births").unwrap();
+        assert!(matches!(invalid.validate(), Err(CldError::SelfLink(name)) if name == "Births"));
+        invalid.links[0].to = parse_name("Growth").unwrap();
+        assert!(matches!(
+            invalid.validate(),
+            Err(CldError::UnknownVariable(name)) if name == "Growth"
+        ));
+    }
+
+    #[test]
+    fn test_render_and_stock_flow_models() {
+        let cld = &CldModel::from_xmile(CLD).unwrap()[0];
+        let svg = cld.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches(r#"class="link positive""#).count(), 3);
+        assert_eq!(svg.matches(r#"class="link negative""#).count(), 2);
+        assert_eq!(svg.matches(r#"class="delay""#).count(), 2);
+        assert_eq!(svg.matches(r#"class="polarity""#).count(), 5);
+        assert!(svg.contains(">Crowding</text>"));
+
+        let model: Model = quick_xml::de::from_str(
+            r#"<model><variables>
+                <aux name="Birth_Rate"><eqn>0.03</eqn></aux>
+                <aux name="Births"><eqn>Birth_Rate</eqn></aux>
+            </variables>
+            <views><view uid="100" width="400" height="300" page_width="400" page_height="300">
+                <connector uid="1" x="0" y="0" angle="0" polarity="+"><from>Birth_Rate</from><to>Births</to></connector>
+            </view></views></model>"#,
+        )
+        .unwrap();
+        let cld = CldModel::from_model(&model).unwrap();
+        assert_eq!(cld.links.len(), 1);
+        assert!(cld.loops().is_empty());
+        assert_eq!(cld.variables[0].name.to_string(), "Birth Rate");
+        assert!(
+            CldModel::from_xmile(r#"<xmile><model><variables/></model></xmile>"#).unwrap()[0]
+                .to_svg()
+                .contains(r#"width="120""#)
+        );
+    }
+}
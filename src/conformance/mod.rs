@@ -0,0 +1,311 @@
+//! # Conformance
+//!
+//! Runtime reporting of which parts of the XMILE specification this crate
+//! supports, together with a small harness for checking models against it.
+//!
+//! The XMILE `<options>` block (Section 2.2.1) lists optional functionality a
+//! file makes use of. [`Feature`] mirrors that list so callers can ask, before
+//! loading a model, whether the current build is able to handle it:
+//!
+//! ```rust
+//! use xmile::conformance::{self, Feature};
+//!
+//! assert!(Feature::EventPosters.is_supported());
+//! assert!(conformance::supported_features().contains(&Feature::EventPosters));
+//! ```
+//!
+//! Functionality that is parsed but not yet fully implemented is listed by
+//! [`known_gaps`]. [`check_str`] runs a model through parse, validation and
+//! serialization round-trip and collects the outcome into a
+//! [`ConformanceReport`].
+//!
+//! The `conformance` integration test checks the sample models in
+//! `data/conformance`. They are written for this crate, one per area of the
+//! specification. They are not the test models published by the SDXorg
+//! community, which live in their own repository, mostly as Vensim and
+//! Stella files, and are not vendored here. Set `XMILE_TEST_MODELS` to a
+//! checkout of that repository to check its `.xmile` files as well.
+
+use std::fmt;
+
+use crate::header::Options;
+use crate::xml::XmileFile;
+
+/// Optional XMILE functionality, as declared in the header `<options>` block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Conveyor stocks and leakage flows (`<uses_conveyor/>`).
+    Conveyor,
+    /// Queue stocks and overflow flows (`<uses_queue/>`).
+    Queue,
+    /// Arrayed variables and dimensions (`<uses_arrays/>`).
+    Arrays,
+    /// Modules and submodels (`<uses_submodels/>`).
+    Submodels,
+    /// Macro definitions (`<uses_macros/>`).
+    Macros,
+    /// Event posters on variables (`<uses_event_posters/>`).
+    EventPosters,
+    /// Stock and flow diagrams (`<has_model_view/>`).
+    ModelView,
+    /// Graphs, tables and other output objects (`<uses_outputs/>`).
+    Outputs,
+    /// Sliders, knobs and other input objects (`<uses_inputs/>`).
+    Inputs,
+    /// Text boxes, graphics frames and buttons (`<uses_annotation/>`).
+    Annotation,
+    /// MathML equations alongside XMILE equations (`<mathml>`).
+    MathML,
+}
+
+impl Feature {
+    /// Every feature, in the order they are listed in the specification.
+    pub const ALL: [Feature; 11] = [
+        Feature::Conveyor,
+        Feature::Queue,
+        Feature::Arrays,
+        Feature::Submodels,
+        Feature::Macros,
+        Feature::EventPosters,
+        Feature::ModelView,
+        Feature::Outputs,
+        Feature::Inputs,
+        Feature::Annotation,
+        Feature::MathML,
+    ];
+
+    /// The tag name used for this feature in the header `<options>` block.
+    ///
+    /// MathML has no option tag; its element name is returned instead.
+    pub fn option_tag(&self) -> &'static str {
+        match self {
+            Feature::Conveyor => "uses_conveyor",
+            Feature::Queue => "uses_queue",
+            Feature::Arrays => "uses_arrays",
+            Feature::Submodels => "uses_submodels",
+            Feature::Macros => "uses_macros",
+            Feature::EventPosters => "uses_event_posters",
+            Feature::ModelView => "has_model_view",
+            Feature::Outputs => "uses_outputs",
+            Feature::Inputs => "uses_inputs",
+            Feature::Annotation => "uses_annotation",
+            Feature::MathML => "mathml",
+        }
+    }
+
+    /// Whether this build of the crate supports the feature.
+    ///
+    /// Features gated behind Cargo features report `false` when the
+    /// corresponding Cargo feature is disabled.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Feature::Conveyor => cfg!(feature = "conveyors"),
+            Feature::Queue => cfg!(feature = "queues"),
+            Feature::Arrays => cfg!(feature = "arrays"),
+            Feature::Submodels => cfg!(feature = "submodels"),
+            Feature::Macros => cfg!(feature = "macros"),
+            Feature::MathML => cfg!(feature = "mathml"),
+            Feature::ModelView => cfg!(feature = "views"),
+            Feature::Outputs | Feature::Inputs | Feature::Annotation => {
+                cfg!(feature = "interface-objects")
+            }
+            Feature::EventPosters => true,
+        }
+    }
+
+    /// Returns the features a file declares in its header `<options>` block.
+    pub fn declared_by(options: &Options) -> Vec<Feature> {
+        let declared = [
+            (Feature::Conveyor, options.uses_conveyor.is_some()),
+            (Feature::Queue, options.uses_queue.is_some()),
+            (Feature::Arrays, options.uses_arrays.is_some()),
+            (Feature::Submodels, options.uses_submodels.is_some()),
+            (Feature::Macros, options.uses_macros.is_some()),
+            (Feature::EventPosters, options.uses_event_posters.is_some()),
+            (Feature::ModelView, options.has_model_view.is_some()),
+            (Feature::Outputs, options.uses_outputs.is_some()),
+            (Feature::Inputs, options.uses_inputs.is_some()),
+            (Feature::Annotation, options.uses_annotation.is_some()),
+        ];
+
+        declared
+            .into_iter()
+            .filter_map(|(feature, used)| used.then_some(feature))
+            .collect()
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.option_tag())
+    }
+}
+
+/// Returns the features supported by this build of the crate.
+pub fn supported_features() -> Vec<Feature> {
+    Feature::ALL
+        .into_iter()
+        .filter(Feature::is_supported)
+        .collect()
+}
+
+/// A known deviation from the XMILE specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownGap {
+    /// The feature the gap relates to, if it is specific to one.
+    pub feature: Option<Feature>,
+    /// The specification section the gap relates to.
+    pub section: &'static str,
+    /// A short description of what is missing.
+    pub description: &'static str,
+}
+
+const KNOWN_GAPS: &[KnownGap] = &[
+    KnownGap {
+        feature: None,
+        section: "2.11",
+        description: "Included files can be loaded but are not merged into the including file.",
+    },
+    KnownGap {
+        feature: None,
+        section: "3.1",
+        description: "Arrays, conveyors, queues, submodels and macros cannot be simulated, nor can builtins with internal state other than delays, smooths, TREND and FORCST (e.g. the pipeline DELAY).",
+    },
+];
+
+/// Returns the known deviations from the XMILE specification.
+///
+/// The list is static and describes the crate as a whole; gaps tied to a
+/// disabled Cargo feature are still reported.
+pub fn known_gaps() -> &'static [KnownGap] {
+    KNOWN_GAPS
+}
+
+/// The outcome of checking a single model for conformance.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConformanceReport {
+    /// A label for the checked model, usually its file name.
+    pub name: String,
+    /// Features declared in the model's header options.
+    pub declared_features: Vec<Feature>,
+    /// Declared features that this build does not support.
+    pub unsupported_features: Vec<Feature>,
+    /// The error raised while parsing, if any.
+    pub parse_error: Option<String>,
+    /// The error raised while validating, if any.
+    pub validation_error: Option<String>,
+    /// The error raised while serializing and re-parsing, if any.
+    pub round_trip_error: Option<String>,
+}
+
+impl ConformanceReport {
+    /// Returns true if the model parsed, validated and round-tripped.
+    pub fn is_conformant(&self) -> bool {
+        self.parse_error.is_none()
+            && self.validation_error.is_none()
+            && self.round_trip_error.is_none()
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_conformant() { "ok" } else { "FAILED" };
+        writeln!(f, "{}: {}", self.name, status)?;
+
+        if !self.unsupported_features.is_empty() {
+            let features = self
+                .unsupported_features
+                .iter()
+                .map(Feature::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "  unsupported features: {}", features)?;
+        }
+        if let Some(err) = &self.parse_error {
+            writeln!(f, "  parse: {}", err)?;
+        }
+        if let Some(err) = &self.validation_error {
+            writeln!(f, "  validate: {}", err)?;
+        }
+        if let Some(err) = &self.round_trip_error {
+            writeln!(f, "  round-trip: {}", err)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks a model for conformance.
+///
+/// The model is parsed, validated, serialized back to XML and parsed again;
+/// the two parsed files must compare equal. Checking stops at the first stage
+/// that fails.
+pub fn check_str(name: &str, xml: &str) -> ConformanceReport {
+    let mut report = ConformanceReport {
+        name: name.to_string(),
+        ..Default::default()
+    };
+
+    let file = match XmileFile::from_str(xml) {
+        Ok(file) => file,
+        Err(err) => {
+            report.parse_error = Some(err.to_string());
+            return report;
+        }
+    };
+
+    if let Some(options) = &file.header.options {
+        report.declared_features = Feature::declared_by(options);
+        report.unsupported_features = report
+            .declared_features
+            .iter()
+            .copied()
+            .filter(|feature| !feature.is_supported())
+            .collect();
+    }
+
+    if let Err(err) = file.validate() {
+        report.validation_error = Some(err.to_string());
+        return report;
+    }
+
+    report.round_trip_error = match file.to_xml_string() {
+        Ok(serialized) => match XmileFile::from_str(&serialized) {
+            Ok(reparsed) if reparsed == file => None,
+            Ok(_) => Some("re-parsed model differs from the original".to_string()),
+            Err(err) => Some(err.to_string()),
+        },
+        Err(err) => Some(err.to_string()),
+    };
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_always_supported_features() {
+        let supported = supported_features();
+        assert!(supported.contains(&Feature::EventPosters));
+    }
+
+    #[test]
+    fn test_feature_flags_reflected() {
+        assert_eq!(Feature::Arrays.is_supported(), cfg!(feature = "arrays"));
+        assert_eq!(Feature::Macros.is_supported(), cfg!(feature = "macros"));
+        assert_eq!(Feature::ModelView.is_supported(), cfg!(feature = "views"));
+        assert_eq!(
+            Feature::Inputs.is_supported(),
+            cfg!(feature = "interface-objects")
+        );
+    }
+
+    #[test]
+    fn test_check_reports_parse_error() {
+        let report = check_str("broken", "<xmile>");
+        assert!(!report.is_conformant());
+        assert!(report.parse_error.is_some());
+        assert!(report.to_string().starts_with("broken: FAILED"));
+    }
+}
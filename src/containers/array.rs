@@ -0,0 +1,505 @@
+//! Arrays: the values of arrayed variables while a model runs.
+//!
+//! An [`ArrayValue`] holds one number per element of its dimensions, in a
+//! single list ordered with the last dimension varying fastest, so that
+//! the container operations see every element. Elements are found by
+//! subscript, one index per dimension, each either an element name of a
+//! named dimension or a position counting from 1 (Section 3.7.1):
+//!
+//! ```rust
+//! use xmile::Container;
+//! use xmile::containers::array::ArrayValue;
+//! use xmile::dimensions::{Dimension, DimensionElement};
+//!
+//! let location = Dimension {
+//!     name: "Location".to_string(),
+//!     size: None,
+//!     elements: ["Boston", "Chicago"]
+//!         .map(|name| DimensionElement { name: name.to_string() })
+//!         .to_vec(),
+//! };
+//! let n = Dimension { name: "N".to_string(), size: Some(3), elements: Vec::new() };
+//!
+//! // Apply-to-all: every element starts with the same value
+//! let mut array = ArrayValue::filled(vec![location, n], 1.0);
+//! assert_eq!(array.len(), 6);
+//! // Non-apply-to-all: elements are set one subscript at a time
+//! array.set_subscript("Chicago, 2", 5.0).unwrap();
+//! assert_eq!(array.get(&["Chicago", "2"]).unwrap(), 5.0);
+//! assert_eq!(array.values(), [1.0, 1.0, 1.0, 1.0, 5.0, 1.0]);
+//! ```
+
+use std::ops::{Index, IndexMut};
+
+use thiserror::Error;
+
+use crate::dimensions::{Dimension, Dimensions};
+use crate::model::vars::array::VariableDimensions;
+
+use super::{Container, ContainerMut};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ArrayError {
+    #[error("Dimension '{0}' is not defined")]
+    UnknownDimension(String),
+    #[error("Array has {expected} dimensions, but {found} indices were given")]
+    IndexCount { expected: usize, found: usize },
+    #[error("'{index}' is not an index of dimension '{dimension}'")]
+    Index { dimension: String, index: String },
+    #[error("Array has {expected} elements, but {found} values were given")]
+    Length { expected: usize, found: usize },
+    #[error("Arrays of shape {left:?} and {right:?} cannot be combined")]
+    Shape { left: Vec<usize>, right: Vec<usize> },
+}
+
+/// The elements of one dimension a subscript picks out, by position
+/// counting from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// One element; the dimension is dropped from the result.
+    Position(usize),
+    /// Every element, as for a `*` wildcard.
+    All,
+    /// The elements from the first position to the second, inclusive.
+    Range(usize, usize),
+}
+
+/// The elements of an arrayed variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayValue {
+    dimensions: Vec<Dimension>,
+    /// How far apart in `values` consecutive indices of each dimension are.
+    strides: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl ArrayValue {
+    /// An array over `dimensions` with every element zero.
+    pub fn new(dimensions: Vec<Dimension>) -> Self {
+        ArrayValue::filled(dimensions, 0.0)
+    }
+
+    /// An array over `dimensions` with every element `value`, as for an
+    /// apply-to-all variable.
+    pub fn filled(dimensions: Vec<Dimension>, value: f64) -> Self {
+        let mut strides = vec![1; dimensions.len()];
+        for dimension in (0..dimensions.len().saturating_sub(1)).rev() {
+            strides[dimension] = strides[dimension + 1] * dimensions[dimension + 1].size();
+        }
+        let len = dimensions.iter().map(Dimension::size).product();
+        ArrayValue {
+            dimensions,
+            strides,
+            values: vec![value; len],
+        }
+    }
+
+    /// An array over `dimensions` holding `values`, last dimension fastest.
+    pub fn from_values(dimensions: Vec<Dimension>, values: Vec<f64>) -> Result<Self, ArrayError> {
+        let mut array = ArrayValue::new(dimensions);
+        if values.len() != array.values.len() {
+            return Err(ArrayError::Length {
+                expected: array.values.len(),
+                found: values.len(),
+            });
+        }
+        array.values = values;
+        Ok(array)
+    }
+
+    /// An array of zeros for a variable with `dimensions`, looking each up
+    /// among the model's `defined` dimensions.
+    pub fn for_variable(
+        dimensions: &VariableDimensions,
+        defined: &Dimensions,
+    ) -> Result<Self, ArrayError> {
+        let dimensions = dimensions
+            .dims
+            .iter()
+            .map(|dim| {
+                defined
+                    .dims
+                    .iter()
+                    .find(|defined| defined.name == dim.name)
+                    .cloned()
+                    .ok_or_else(|| ArrayError::UnknownDimension(dim.name.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ArrayValue::new(dimensions))
+    }
+
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.dimensions
+    }
+
+    /// The size of each dimension.
+    pub fn shape(&self) -> Vec<usize> {
+        self.dimensions.iter().map(Dimension::size).collect()
+    }
+
+    /// Where the element at `positions`, counting from 0 in each dimension,
+    /// is in the list of values.
+    pub fn offset_of(&self, positions: &[usize]) -> Option<usize> {
+        if positions.len() != self.dimensions.len() {
+            return None;
+        }
+        positions
+            .iter()
+            .zip(&self.dimensions)
+            .zip(&self.strides)
+            .try_fold(0, |offset, ((&position, dimension), stride)| {
+                (position < dimension.size()).then_some(offset + position * stride)
+            })
+    }
+
+    /// Where the element at `indices`, one per dimension, is in the list of
+    /// values.
+    pub fn offset<S: AsRef<str>>(&self, indices: &[S]) -> Result<usize, ArrayError> {
+        if indices.len() != self.dimensions.len() {
+            return Err(ArrayError::IndexCount {
+                expected: self.dimensions.len(),
+                found: indices.len(),
+            });
+        }
+        indices
+            .iter()
+            .zip(&self.dimensions)
+            .zip(&self.strides)
+            .try_fold(0, |offset, ((index, dimension), stride)| {
+                Ok(offset + position(dimension, index.as_ref())? * stride)
+            })
+    }
+
+    /// The element at `indices`.
+    pub fn get<S: AsRef<str>>(&self, indices: &[S]) -> Result<f64, ArrayError> {
+        Ok(self.values[self.offset(indices)?])
+    }
+
+    /// Sets the element at `indices`.
+    pub fn set<S: AsRef<str>>(&mut self, indices: &[S], value: f64) -> Result<(), ArrayError> {
+        let offset = self.offset(indices)?;
+        self.values[offset] = value;
+        Ok(())
+    }
+
+    /// Sets the element named by a comma-separated subscript, as given by
+    /// an `<element>` of a non-apply-to-all variable.
+    pub fn set_subscript(&mut self, subscript: &str, value: f64) -> Result<(), ArrayError> {
+        let indices: Vec<&str> = subscript.split(',').map(str::trim).collect();
+        self.set(&indices, value)
+    }
+
+    /// The position, counting from 0, of `index` in the dimension at
+    /// `dimension`.
+    pub fn position(&self, dimension: usize, index: &str) -> Result<usize, ArrayError> {
+        let found = self
+            .dimensions
+            .get(dimension)
+            .ok_or(ArrayError::IndexCount {
+                expected: self.dimensions.len(),
+                found: dimension + 1,
+            })?;
+        position(found, index)
+    }
+
+    /// The elements picked out by one selection per dimension, keeping the
+    /// dimensions selected by a wildcard or a range. Selecting a position
+    /// in every dimension gives an array with no dimensions and one
+    /// element.
+    pub fn select(&self, selections: &[Selection]) -> Result<ArrayValue, ArrayError> {
+        if selections.len() != self.dimensions.len() {
+            return Err(ArrayError::IndexCount {
+                expected: self.dimensions.len(),
+                found: selections.len(),
+            });
+        }
+        let mut base = 0;
+        // The stride, first position and length of each kept dimension
+        let mut kept = Vec::new();
+        let mut dimensions = Vec::new();
+        for ((selection, dimension), &stride) in
+            selections.iter().zip(&self.dimensions).zip(&self.strides)
+        {
+            let (first, last) = match *selection {
+                Selection::Position(position) => (position, position),
+                Selection::All => (0, dimension.size().saturating_sub(1)),
+                Selection::Range(first, last) => (first, last),
+            };
+            if first > last || last >= dimension.size() {
+                return Err(ArrayError::Index {
+                    dimension: dimension.name.clone(),
+                    index: format!("{}:{}", first + 1, last + 1),
+                });
+            }
+            if let Selection::Position(position) = selection {
+                base += position * stride;
+                continue;
+            }
+            kept.push((stride, first, last - first + 1));
+            dimensions.push(if dimension.elements.is_empty() {
+                Dimension {
+                    size: Some(last - first + 1),
+                    ..dimension.clone()
+                }
+            } else {
+                Dimension {
+                    elements: dimension.elements[first..=last].to_vec(),
+                    ..dimension.clone()
+                }
+            });
+        }
+
+        let mut selected = ArrayValue::new(dimensions);
+        let shape: Vec<usize> = kept.iter().map(|&(_, _, len)| len).collect();
+        for (value, positions) in selected.values.iter_mut().zip(Positions::new(shape)) {
+            let offset = positions
+                .iter()
+                .zip(&kept)
+                .map(|(position, (stride, first, _))| (first + position) * stride)
+                .sum::<usize>();
+            *value = self.values[base + offset];
+        }
+        Ok(selected)
+    }
+
+    /// The array with the order of its dimensions reversed, so that for a
+    /// two-dimensional array rows become columns.
+    pub fn transpose(&self) -> ArrayValue {
+        let mut transposed = ArrayValue::new(self.dimensions.iter().rev().cloned().collect());
+        let shape = transposed.shape();
+        for (value, positions) in transposed.values.iter_mut().zip(Positions::new(shape)) {
+            let offset = positions
+                .iter()
+                .zip(self.strides.iter().rev())
+                .map(|(position, stride)| position * stride)
+                .sum::<usize>();
+            *value = self.values[offset];
+        }
+        transposed
+    }
+
+    /// Applies `f` to every element.
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> ArrayValue {
+        ArrayValue {
+            values: self.values.iter().map(|&value| f(value)).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Combines the elements of two arrays of the same shape pairwise.
+    pub fn zip_with(
+        &self,
+        other: &ArrayValue,
+        f: impl Fn(f64, f64) -> f64,
+    ) -> Result<ArrayValue, ArrayError> {
+        if self.shape() != other.shape() {
+            return Err(ArrayError::Shape {
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+        Ok(ArrayValue {
+            values: self
+                .values
+                .iter()
+                .zip(&other.values)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            ..self.clone()
+        })
+    }
+}
+
+/// Every combination of positions within a shape, last dimension fastest.
+struct Positions {
+    shape: Vec<usize>,
+    next: Option<Vec<usize>>,
+}
+
+impl Positions {
+    fn new(shape: Vec<usize>) -> Self {
+        let next = shape
+            .iter()
+            .all(|&size| size > 0)
+            .then(|| vec![0; shape.len()]);
+        Positions { shape, next }
+    }
+}
+
+impl Iterator for Positions {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.next.take()?;
+        let mut next = current.clone();
+        for dimension in (0..next.len()).rev() {
+            next[dimension] += 1;
+            if next[dimension] < self.shape[dimension] {
+                self.next = Some(next);
+                break;
+            }
+            next[dimension] = 0;
+        }
+        Some(current)
+    }
+}
+
+/// The position, counting from 0, of `index` in `dimension`. Named
+/// dimensions also take positions counting from 1.
+fn position(dimension: &Dimension, index: &str) -> Result<usize, ArrayError> {
+    let named = dimension
+        .elements
+        .iter()
+        .position(|element| element.name.eq_ignore_ascii_case(index));
+    named
+        .or_else(|| {
+            let number = index.parse::<usize>().ok()?;
+            number
+                .checked_sub(1)
+                .filter(|&position| position < dimension.size())
+        })
+        .ok_or_else(|| ArrayError::Index {
+            dimension: dimension.name.clone(),
+            index: index.to_string(),
+        })
+}
+
+impl Container for ArrayValue {
+    fn values(&self) -> &[f64] {
+        &self.values
+    }
+}
+
+impl ContainerMut for ArrayValue {
+    fn values_mut(&mut self) -> &mut [f64] {
+        &mut self.values
+    }
+}
+
+impl Index<usize> for ArrayValue {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index]
+    }
+}
+
+impl IndexMut<usize> for ArrayValue {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.values[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dimensions::DimensionElement;
+    use crate::model::vars::array;
+
+    fn numbered(name: &str, size: usize) -> Dimension {
+        Dimension {
+            name: name.to_string(),
+            size: Some(size),
+            elements: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_strides() {
+        let array = ArrayValue::from_values(
+            vec![numbered("A", 2), numbered("B", 3)],
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+        )
+        .unwrap();
+        assert_eq!(array.shape(), [2, 3]);
+        assert_eq!(array.get(&["2", "1"]).unwrap(), 4.0);
+        assert_eq!(array.offset_of(&[1, 2]), Some(5));
+        assert_eq!(array.offset_of(&[2, 0]), None);
+        assert_eq!(array.mean(), Some(3.5));
+        assert!(matches!(
+            array.get(&["3", "1"]),
+            Err(ArrayError::Index { .. })
+        ));
+        assert!(matches!(
+            array.get(&["1"]),
+            Err(ArrayError::IndexCount { .. })
+        ));
+        assert!(matches!(
+            ArrayValue::from_values(vec![numbered("A", 2)], vec![1.0]),
+            Err(ArrayError::Length { .. })
+        ));
+    }
+
+    #[test]
+    fn test_select_and_transpose() {
+        // 1 2 3
+        // 4 5 6
+        let array = ArrayValue::from_values(
+            vec![numbered("A", 2), numbered("B", 3)],
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+        )
+        .unwrap();
+        let column = array
+            .select(&[Selection::All, Selection::Position(1)])
+            .unwrap();
+        assert_eq!(column.values(), [2.0, 5.0]);
+        assert_eq!(column.shape(), [2]);
+        let block = array
+            .select(&[Selection::Position(1), Selection::Range(1, 2)])
+            .unwrap();
+        assert_eq!(block.values(), [5.0, 6.0]);
+        let element = array
+            .select(&[Selection::Position(0), Selection::Position(2)])
+            .unwrap();
+        assert_eq!((element.shape().len(), element.values()), (0, &[3.0][..]));
+        assert!(matches!(
+            array.select(&[Selection::All, Selection::Range(2, 3)]),
+            Err(ArrayError::Index { .. })
+        ));
+
+        let transposed = array.transpose();
+        assert_eq!(transposed.shape(), [3, 2]);
+        assert_eq!(transposed.values(), [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert!(matches!(
+            array.zip_with(&transposed, |a, b| a + b),
+            Err(ArrayError::Shape { .. })
+        ));
+    }
+
+    #[test]
+    fn test_for_variable() {
+        let city = Dimension {
+            name: "City".to_string(),
+            size: None,
+            elements: vec![
+                DimensionElement {
+                    name: "Boston".to_string(),
+                },
+                DimensionElement {
+                    name: "LA".to_string(),
+                },
+            ],
+        };
+        let defined = Dimensions {
+            dims: vec![city, numbered("N", 2)],
+        };
+        let dims = |names: &[&str]| VariableDimensions {
+            dims: names
+                .iter()
+                .map(|name| array::Dimension {
+                    name: name.to_string(),
+                })
+                .collect(),
+        };
+
+        let mut array = ArrayValue::for_variable(&dims(&["City"]), &defined).unwrap();
+        array.set_subscript("la", 3.0).unwrap();
+        array.set(&["1"], 2.0).unwrap();
+        assert_eq!(array.values(), [2.0, 3.0]);
+        assert!(matches!(
+            ArrayValue::for_variable(&dims(&["Age"]), &defined),
+            Err(ArrayError::UnknownDimension(name)) if name == "Age"
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+//! Conveyors: stocks whose contents take a set time to pass through.
+//!
+//! A [`Conveyor`] divides its transit time into slats of one DT each.
+//! Every DT, material in a leak zone leaks, the slat at the exit is emptied
+//! into the outflow, every other slat moves one place along, and the inflow
+//! is put on the slat at the entrance. Element 0 of the container is the
+//! slat at the exit, so a conveyor lists its contents in the order they
+//! will leave:
+//!
+//! ```rust
+//! use xmile::Container;
+//! use xmile::containers::conveyor::Conveyor;
+//!
+//! let mut conveyor = Conveyor::new(3.0, 1.0).unwrap();
+//! assert_eq!(conveyor.len(), 3);
+//! assert_eq!(conveyor.advance(5.0).outflow, 0.0);
+//! assert_eq!(conveyor.values(), [0.0, 0.0, 5.0]);
+//! conveyor.advance(0.0);
+//! conveyor.advance(0.0);
+//! assert_eq!(conveyor.advance(0.0).outflow, 5.0);
+//! ```
+//!
+//! Leakage (Section 3.7.2) takes a fraction of the material passing
+//! through a zone of the conveyor. Linear leakage takes the same amount
+//! each DT, so that the fraction of what was put on the conveyor has gone
+//! by the end of the zone; exponential leakage takes the same fraction of
+//! what is left each DT. While a conveyor is arrested nothing moves: it
+//! takes no inflow, and nothing leaks or leaves.
+//!
+//! A [`Conveyor`] is a library piece only. Runs do not move conveyors:
+//! [`Simulator::new`] rejects models with conveyor stocks as
+//! [`SimulationError::Unsupported`], so callers advance a conveyor
+//! themselves, once per DT.
+//!
+//! [`Simulator::new`]: crate::simulation::Simulator::new
+//! [`SimulationError::Unsupported`]: crate::simulation::SimulationError::Unsupported
+
+use std::ops::{Index, IndexMut};
+
+use thiserror::Error;
+
+use crate::model::vars::flow::ConveyorLeakage;
+use crate::model::vars::stock::ConveyorStock;
+
+use super::{Container, ContainerMut};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ConveyorError {
+    #[error("Conveyor transit time must be positive, not {0}")]
+    TransitTime(f64),
+    #[error("DT must be positive, not {0}")]
+    Dt(f64),
+    #[error("Leak fraction {0} is not between 0 and 1")]
+    LeakFraction(f64),
+    #[error("Leak zone from {start} to {end} is not within the conveyor")]
+    LeakZone { start: f64, end: f64 },
+}
+
+/// A leak from part of a conveyor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Leakage {
+    /// The fraction of the material passing through the zone that leaks.
+    pub fraction: f64,
+    /// Where the zone starts, as a fraction of the conveyor from its
+    /// entrance.
+    pub start: f64,
+    /// Where the zone ends, as a fraction of the conveyor from its
+    /// entrance.
+    pub end: f64,
+}
+
+impl Leakage {
+    /// A leak of `fraction` along the whole conveyor.
+    pub fn new(fraction: f64) -> Self {
+        Leakage {
+            fraction,
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+
+    /// Limits the leak to the zone from `start` to `end`.
+    pub fn zone(self, start: f64, end: f64) -> Self {
+        Leakage { start, end, ..self }
+    }
+
+    /// The leak described by a leakage flow, if it gives a leak fraction.
+    pub fn from_flow(flow: &ConveyorLeakage) -> Option<Self> {
+        let leak = Leakage::new(flow.leak?);
+        Some(leak.zone(
+            flow.leak_start.unwrap_or(leak.start),
+            flow.leak_end.unwrap_or(leak.end),
+        ))
+    }
+}
+
+/// The amounts moved by one DT of a conveyor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConveyorStep {
+    /// The amount put on the conveyor.
+    pub inflow: f64,
+    /// The amount that left at the exit.
+    pub outflow: f64,
+    /// The amount taken by each leak, in the order they were added.
+    pub leaks: Vec<f64>,
+}
+
+/// A conveyor's slats and the leaks from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conveyor {
+    /// The material on each slat, from the exit to the entrance.
+    slats: Vec<f64>,
+    /// The material each slat held when it was put on, for linear leakage.
+    loaded: Vec<f64>,
+    transit_time: f64,
+    dt: f64,
+    leaks: Vec<Leakage>,
+    exponential: bool,
+    arrested: bool,
+}
+
+impl Conveyor {
+    /// An empty conveyor taking `transit_time` to pass through, moving
+    /// every `dt`. The transit time is rounded to a whole number of slats,
+    /// and is at least one.
+    pub fn new(transit_time: f64, dt: f64) -> Result<Self, ConveyorError> {
+        if !(dt > 0.0 && dt.is_finite()) {
+            return Err(ConveyorError::Dt(dt));
+        }
+        let slats = slat_count(transit_time, dt)?;
+        Ok(Conveyor {
+            slats: vec![0.0; slats],
+            loaded: vec![0.0; slats],
+            transit_time,
+            dt,
+            leaks: Vec::new(),
+            exponential: false,
+            arrested: false,
+        })
+    }
+
+    /// An empty conveyor for `stock`, whose length has been evaluated to
+    /// `transit_time`, losing material to the leakage flows `leaks`.
+    /// Leakage flows without a leak fraction are left out.
+    pub fn for_stock<'a>(
+        stock: &ConveyorStock,
+        transit_time: f64,
+        dt: f64,
+        leaks: impl IntoIterator<Item = &'a ConveyorLeakage>,
+    ) -> Result<Self, ConveyorError> {
+        let mut conveyor = Conveyor::new(transit_time, dt)?
+            .with_exponential_leakage(stock.exponential_leakage.unwrap_or(false));
+        for leak in leaks.into_iter().filter_map(Leakage::from_flow) {
+            conveyor = conveyor.with_leak(leak)?;
+        }
+        Ok(conveyor)
+    }
+
+    /// Adds a leak. Leaks are taken in the order they are added.
+    pub fn with_leak(mut self, leak: Leakage) -> Result<Self, ConveyorError> {
+        if !(0.0..=1.0).contains(&leak.fraction) {
+            return Err(ConveyorError::LeakFraction(leak.fraction));
+        }
+        if !((0.0..=leak.end).contains(&leak.start) && leak.end <= 1.0) {
+            return Err(ConveyorError::LeakZone {
+                start: leak.start,
+                end: leak.end,
+            });
+        }
+        self.leaks.push(leak);
+        Ok(self)
+    }
+
+    /// Makes leaks take a fraction of what is left each DT, rather than a
+    /// fixed amount.
+    pub fn with_exponential_leakage(mut self, exponential: bool) -> Self {
+        self.exponential = exponential;
+        self
+    }
+
+    pub fn transit_time(&self) -> f64 {
+        self.transit_time
+    }
+
+    /// The total material on the conveyor.
+    pub fn total(&self) -> f64 {
+        self.slats.iter().sum()
+    }
+
+    /// Spreads `total` evenly over the slats, replacing what was there, as
+    /// for a conveyor's initial value.
+    pub fn fill(&mut self, total: f64) {
+        let each = total / self.slats.len() as f64;
+        self.slats.fill(each);
+        self.loaded.fill(each);
+    }
+
+    pub fn is_arrested(&self) -> bool {
+        self.arrested
+    }
+
+    /// Stops or restarts the conveyor.
+    pub fn set_arrested(&mut self, arrested: bool) {
+        self.arrested = arrested;
+    }
+
+    /// Changes the transit time. Slats are added empty, or taken away, at
+    /// the entrance; the material on slats taken away is moved to the new
+    /// entrance slat, so none is lost.
+    pub fn set_transit_time(&mut self, transit_time: f64) -> Result<(), ConveyorError> {
+        let slats = slat_count(transit_time, self.dt)?;
+        if slats < self.slats.len() {
+            let removed: f64 = self.slats.drain(slats..).sum();
+            let removed_loaded: f64 = self.loaded.drain(slats..).sum();
+            self.slats[slats - 1] += removed;
+            self.loaded[slats - 1] += removed_loaded;
+        } else {
+            self.slats.resize(slats, 0.0);
+            self.loaded.resize(slats, 0.0);
+        }
+        self.transit_time = transit_time;
+        Ok(())
+    }
+
+    /// Moves the conveyor on by one DT, putting `inflow` (an amount, not a
+    /// rate) on at the entrance.
+    pub fn advance(&mut self, inflow: f64) -> ConveyorStep {
+        if self.arrested {
+            return ConveyorStep {
+                inflow: 0.0,
+                outflow: 0.0,
+                leaks: vec![0.0; self.leaks.len()],
+            };
+        }
+
+        let count = self.slats.len();
+        let mut leaks = Vec::with_capacity(self.leaks.len());
+        for leak in &self.leaks {
+            // A slat is in the zone if its middle is
+            let zone: Vec<usize> = (0..count)
+                .filter(|&slat| {
+                    let position = (count - slat) as f64 - 0.5;
+                    (leak.start..=leak.end).contains(&(position / count as f64))
+                })
+                .collect();
+            let mut leaked = 0.0;
+            for &slat in &zone {
+                let amount = if self.exponential {
+                    let kept = (1.0 - leak.fraction).powf(1.0 / zone.len() as f64);
+                    self.slats[slat] * (1.0 - kept)
+                } else {
+                    (self.loaded[slat] * leak.fraction / zone.len() as f64).min(self.slats[slat])
+                };
+                self.slats[slat] -= amount;
+                leaked += amount;
+            }
+            leaks.push(leaked);
+        }
+
+        let outflow = self.slats.remove(0);
+        self.loaded.remove(0);
+        self.slats.push(inflow);
+        self.loaded.push(inflow);
+        ConveyorStep {
+            inflow,
+            outflow,
+            leaks,
+        }
+    }
+}
+
+fn slat_count(transit_time: f64, dt: f64) -> Result<usize, ConveyorError> {
+    if !(transit_time > 0.0 && transit_time.is_finite()) {
+        return Err(ConveyorError::TransitTime(transit_time));
+    }
+    Ok((transit_time / dt).round().max(1.0) as usize)
+}
+
+impl Container for Conveyor {
+    fn values(&self) -> &[f64] {
+        &self.slats
+    }
+}
+
+impl ContainerMut for Conveyor {
+    /// Returns mutable access to the slats. Linear leakage still takes
+    /// its share of what each slat held when it was put on.
+    fn values_mut(&mut self) -> &mut [f64] {
+        &mut self.slats
+    }
+}
+
+impl Index<usize> for Conveyor {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.slats[index]
+    }
+}
+
+impl IndexMut<usize> for Conveyor {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.slats[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leakage() {
+        let run = |exponential: bool| {
+            let mut conveyor = Conveyor::new(4.0, 1.0)
+                .unwrap()
+                .with_leak(Leakage::new(0.75))
+                .unwrap()
+                .with_exponential_leakage(exponential);
+            let mut leaked = Vec::new();
+            let mut outflow = 0.0;
+            for inflow in [8.0, 0.0, 0.0, 0.0, 0.0] {
+                let step = conveyor.advance(inflow);
+                leaked.push(step.leaks[0]);
+                outflow += step.outflow;
+            }
+            (leaked, outflow)
+        };
+
+        // Linear leakage takes a quarter of 0.75 * 8 on each slat
+        let (leaked, outflow) = run(false);
+        assert_eq!(leaked, [0.0, 1.5, 1.5, 1.5, 1.5]);
+        assert_eq!(outflow, 2.0);
+        // Exponential leakage leaves the same amount, taking less each DT
+        let (leaked, outflow) = run(true);
+        assert!((outflow - 2.0).abs() < 1e-12);
+        assert!(leaked.windows(2).skip(1).all(|pair| pair[1] < pair[0]));
+
+        assert!(matches!(
+            Conveyor::new(4.0, 1.0)
+                .unwrap()
+                .with_leak(Leakage::new(0.5).zone(0.8, 0.2)),
+            Err(ConveyorError::LeakZone { .. })
+        ));
+        assert!(matches!(
+            Conveyor::new(0.0, 1.0),
+            Err(ConveyorError::TransitTime(_))
+        ));
+    }
+
+    #[test]
+    fn test_arrest_and_transit_time() {
+        let mut conveyor = Conveyor::new(2.0, 0.5).unwrap();
+        conveyor.fill(8.0);
+        assert_eq!(conveyor.values(), [2.0; 4]);
+
+        conveyor.set_arrested(true);
+        let step = conveyor.advance(3.0);
+        assert_eq!((step.inflow, step.outflow), (0.0, 0.0));
+        assert_eq!(conveyor.total(), 8.0);
+        conveyor.set_arrested(false);
+        assert_eq!(conveyor.advance(3.0).outflow, 2.0);
+        assert_eq!(conveyor.values(), [2.0, 2.0, 2.0, 3.0]);
+
+        // Shortening moves material from removed slats to the entrance
+        conveyor.set_transit_time(1.0).unwrap();
+        assert_eq!(conveyor.values(), [2.0, 7.0]);
+        conveyor.set_transit_time(1.5).unwrap();
+        assert_eq!(conveyor.values(), [2.0, 7.0, 0.0]);
+        assert_eq!(conveyor.transit_time(), 1.5);
+    }
+}
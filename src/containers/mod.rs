@@ -287,27 +287,20 @@
 //! - **Deterministic behaviour**: Operations produce consistent results
 //! - **Memory safety**: Rust's ownership system prevents data races
 //!
-//! ## Future Extensions
+//! ## Arrays
 //!
-//! The container system is designed to accommodate future XMILE container types:
+//! With the `arrays` feature, `array::ArrayValue` holds the elements of an
+//! arrayed variable, found by named or numbered subscripts.
 //!
-//! ```rust
-//! // Hypothetical future container types
-//! pub struct Array<const N: usize> {
-//!     data: [f64; N],
-//! }
+//! ## Conveyors
 //!
-//! pub struct Conveyor {
-//!     data: Vec<f64>,
-//!     length: f64,  // May change during simulation
-//! }
+//! With the `conveyors` feature, `conveyor::Conveyor` holds the slats of a
+//! conveyor stock, and moves them on each DT with its leaks and arrest.
 //!
-//! pub struct Queue {
-//!     data: std::collections::VecDeque<f64>,  // Changes during simulation
-//! }
+//! ## Queues
 //!
-//! // All would implement the same Container trait for uniform access
-//! ```
+//! With the `queues` feature, `queue::Queue` holds the batches of a queue
+//! stock, first in, first out, turning away what does not fit to overflow.
 //!
 //! ## Integration with Model Validation
 //!
@@ -357,6 +350,13 @@
 //! This foundation enables robust, efficient, and XMILE-compliant implementations of
 //! system dynamics models with complex data structures and mathematical operations.
 
+#[cfg(feature = "arrays")]
+pub mod array;
+#[cfg(feature = "conveyors")]
+pub mod conveyor;
+#[cfg(feature = "queues")]
+pub mod queue;
+
 use std::ops::{Index, IndexMut};
 
 /// Core trait for all XMILE containers providing uniform access and operations.
@@ -0,0 +1,256 @@
+//! Queues: stocks that hold batches in the order they arrived.
+//!
+//! A [`Queue`] keeps each amount put into it as a separate batch, stamped
+//! with the time it arrived, and gives batches out first in, first out.
+//! Element 0 of the container is the batch at the front, so a queue lists
+//! its contents in the order they will leave:
+//!
+//! ```rust
+//! use xmile::Container;
+//! use xmile::containers::queue::Queue;
+//!
+//! let mut queue = Queue::new();
+//! queue.push(3.0, 0.0).unwrap();
+//! queue.push(5.0, 1.0).unwrap();
+//! assert_eq!(queue.values(), [3.0, 5.0]);
+//! assert_eq!(queue.pop().map(|batch| batch.amount), Some(3.0));
+//! assert_eq!(queue.total(), 5.0);
+//! ```
+//!
+//! A queue may be given a capacity. When the header's `<uses_queue>` sets
+//! `overflow`, the part of a batch that does not fit is turned away, for
+//! the queue's overflow flow to take; otherwise a batch that does not fit
+//! is refused whole.
+//!
+//! A [`Queue`] is a library piece only. Runs do not fill or drain queues:
+//! [`Simulator::new`] rejects models with queue stocks as
+//! [`SimulationError::Unsupported`], so callers push and pop batches
+//! themselves.
+//!
+//! [`Simulator::new`]: crate::simulation::Simulator::new
+//! [`SimulationError::Unsupported`]: crate::simulation::SimulationError::Unsupported
+
+use std::ops::{Index, IndexMut};
+
+use thiserror::Error;
+
+use crate::header::UsesQueue;
+use crate::model::vars::stock::QueueStock;
+
+use super::{Container, ContainerMut};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum QueueError {
+    #[error("Queue capacity must be positive, not {0}")]
+    Capacity(f64),
+    #[error("Batch of {0} cannot be queued; batches must be positive")]
+    Amount(f64),
+    #[error("Batch of {amount} does not fit in the queue, which has room for {room}")]
+    Full { amount: f64, room: f64 },
+}
+
+/// An amount that entered a queue together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Batch {
+    pub amount: f64,
+    /// The time the batch entered the queue.
+    pub time: f64,
+}
+
+/// A queue's batches, from the front to the back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Queue {
+    /// The amount of each batch, kept apart from the times so that the
+    /// amounts can be read as one slice.
+    amounts: Vec<f64>,
+    times: Vec<f64>,
+    capacity: Option<f64>,
+    overflow: bool,
+}
+
+impl Queue {
+    /// An empty queue without a capacity.
+    pub fn new() -> Self {
+        Queue::default()
+    }
+
+    /// A queue for `stock`, holding its initial value, evaluated to
+    /// `initial`, as one batch that entered at `time`. Overflow is allowed
+    /// if the header's `<uses_queue>` options say so and the stock has an
+    /// outflow to take it.
+    pub fn for_stock(
+        stock: &QueueStock,
+        options: Option<&UsesQueue>,
+        initial: f64,
+        time: f64,
+    ) -> Result<Self, QueueError> {
+        let overflow = options.and_then(|options| options.overflow) == Some(true)
+            && !stock.outflows.is_empty();
+        let mut queue = Queue::new().with_overflow(overflow);
+        if initial != 0.0 {
+            queue.push(initial, time)?;
+        }
+        Ok(queue)
+    }
+
+    /// Limits the total the queue can hold.
+    pub fn with_capacity_limit(mut self, capacity: f64) -> Result<Self, QueueError> {
+        if !(capacity > 0.0 && capacity.is_finite()) {
+            return Err(QueueError::Capacity(capacity));
+        }
+        self.capacity = Some(capacity);
+        Ok(self)
+    }
+
+    /// Lets batches that do not fit be split, turning away the excess.
+    pub fn with_overflow(mut self, overflow: bool) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn capacity_limit(&self) -> Option<f64> {
+        self.capacity
+    }
+
+    pub fn has_overflow(&self) -> bool {
+        self.overflow
+    }
+
+    /// The total held in the queue.
+    pub fn total(&self) -> f64 {
+        self.amounts.iter().sum()
+    }
+
+    /// How much more the queue can take, or `None` if it has no capacity.
+    pub fn room(&self) -> Option<f64> {
+        self.capacity
+            .map(|capacity| (capacity - self.total()).max(0.0))
+    }
+
+    /// Puts a batch of `amount` at the back of the queue, stamped with
+    /// `time`. Returns the amount turned away to overflow, which is zero
+    /// if the batch fits.
+    pub fn push(&mut self, amount: f64, time: f64) -> Result<f64, QueueError> {
+        if !(amount > 0.0 && amount.is_finite()) {
+            return Err(QueueError::Amount(amount));
+        }
+        let room = self.room().unwrap_or(f64::INFINITY);
+        let accepted = if amount <= room {
+            amount
+        } else if self.overflow {
+            room
+        } else {
+            return Err(QueueError::Full { amount, room });
+        };
+        if accepted > 0.0 {
+            self.amounts.push(accepted);
+            self.times.push(time);
+        }
+        Ok(amount - accepted)
+    }
+
+    /// Takes the batch at the front of the queue.
+    pub fn pop(&mut self) -> Option<Batch> {
+        if self.amounts.is_empty() {
+            return None;
+        }
+        Some(Batch {
+            amount: self.amounts.remove(0),
+            time: self.times.remove(0),
+        })
+    }
+
+    /// The batch at the front of the queue, without taking it.
+    pub fn peek(&self) -> Option<Batch> {
+        Some(Batch {
+            amount: *self.amounts.first()?,
+            time: *self.times.first()?,
+        })
+    }
+
+    /// The batches from the front to the back.
+    pub fn batches(&self) -> impl Iterator<Item = Batch> + '_ {
+        self.amounts
+            .iter()
+            .zip(&self.times)
+            .map(|(&amount, &time)| Batch { amount, time })
+    }
+}
+
+impl Container for Queue {
+    fn values(&self) -> &[f64] {
+        &self.amounts
+    }
+}
+
+impl ContainerMut for Queue {
+    /// Returns mutable access to the batch amounts. Capacity is not
+    /// checked against changes made this way.
+    fn values_mut(&mut self) -> &mut [f64] {
+        &mut self.amounts
+    }
+}
+
+impl Index<usize> for Queue {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.amounts[index]
+    }
+}
+
+impl IndexMut<usize> for Queue {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.amounts[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo_and_statistics() {
+        let mut queue = Queue::new();
+        assert_eq!(queue.pop(), None);
+        for (time, amount) in [2.0, 6.0, 4.0].into_iter().enumerate() {
+            assert_eq!(queue.push(amount, time as f64).unwrap(), 0.0);
+        }
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.mean(), Some(4.0));
+        assert_eq!(queue.range(), Some((2.0, 6.0)));
+        assert_eq!(queue[1], 6.0);
+
+        assert_eq!(
+            queue.peek(),
+            Some(Batch {
+                amount: 2.0,
+                time: 0.0
+            })
+        );
+        assert_eq!(queue.pop().map(|batch| batch.time), Some(0.0));
+        assert_eq!(queue.values(), [6.0, 4.0]);
+        assert!(matches!(queue.push(-1.0, 3.0), Err(QueueError::Amount(_))));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut queue = Queue::new().with_capacity_limit(10.0).unwrap();
+        queue.push(8.0, 0.0).unwrap();
+        assert!(matches!(
+            queue.push(5.0, 1.0),
+            Err(QueueError::Full { room, .. }) if room == 2.0
+        ));
+        assert_eq!(queue.values(), [8.0]);
+
+        let mut queue = queue.with_overflow(true);
+        assert_eq!(queue.push(5.0, 1.0).unwrap(), 3.0);
+        assert_eq!(queue.values(), [8.0, 2.0]);
+        assert_eq!(queue.room(), Some(0.0));
+        assert!(matches!(
+            Queue::new().with_capacity_limit(0.0),
+            Err(QueueError::Capacity(_))
+        ));
+    }
+}
@@ -0,0 +1,355 @@
+//! Batch processing of model libraries.
+//!
+//! Migrating a library of hundreds of models means running the same
+//! operation on every file and finding out which ones failed. [`batch`]
+//! expands directories into the model files they contain, processes the
+//! files in parallel and reports each file as it completes, then returns a
+//! [`BatchSummary`] with the outcome of every file, in path order:
+//!
+//! ```rust,no_run
+//! use xmile::convert::{self, BatchOperation, BatchOptions};
+//!
+//! let options = BatchOptions::new(BatchOperation::Validate);
+//! let summary = convert::batch(&["models/"], &options, |progress| {
+//!     eprintln!("[{}/{}] {}", progress.completed, progress.total, progress.path.display());
+//! });
+//! for outcome in summary.failed() {
+//!     eprintln!("{}: {}", outcome.path.display(), outcome.result.as_ref().unwrap_err());
+//! }
+//! ```
+//!
+//! A failure in one file never stops the others.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+use crate::Error;
+use crate::xml::{ParseError, SerializeOptions, XmileFile};
+
+/// The file extensions [`batch`] looks for in directories by default.
+pub const DEFAULT_EXTENSIONS: &[&str] = &["xmile", "xml", "stmx", "itmx"];
+
+/// What [`batch`] does with each file.
+#[derive(Debug, Clone)]
+pub enum BatchOperation {
+    /// Parse and validate each file.
+    Validate,
+    /// Rewrite each file in place, serialized with the given options, after
+    /// checking that it parses.
+    Format(SerializeOptions),
+    /// Write each file to the `output` directory, serialized with the given
+    /// options. Files keep their paths relative to the directory they were
+    /// found in; files given directly are written to `output` by name.
+    Convert {
+        output: PathBuf,
+        options: SerializeOptions,
+    },
+}
+
+/// Options for [`batch`].
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub operation: BatchOperation,
+    /// The number of files processed at once, or `0` to use the available
+    /// parallelism of the machine.
+    pub threads: usize,
+    /// Whether the subdirectories of directories are searched too.
+    pub recursive: bool,
+    /// The extensions, without the dot, of the files processed in
+    /// directories. Files given directly are always processed.
+    pub extensions: Vec<String>,
+}
+
+impl BatchOptions {
+    pub fn new(operation: BatchOperation) -> Self {
+        BatchOptions {
+            operation,
+            threads: 0,
+            recursive: true,
+            extensions: DEFAULT_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn with_extensions(mut self, extensions: &[&str]) -> Self {
+        self.extensions = extensions.iter().map(|ext| ext.to_string()).collect();
+        self
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.extensions
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(ext))
+            })
+    }
+
+    fn threads(&self) -> usize {
+        match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+    }
+}
+
+/// Reported to the progress callback of [`batch`] as each file completes.
+#[derive(Debug)]
+pub struct BatchProgress<'a> {
+    /// The number of files completed, including this one.
+    pub completed: usize,
+    pub total: usize,
+    pub path: &'a Path,
+    pub succeeded: bool,
+}
+
+/// The outcome of processing one file.
+#[derive(Debug)]
+pub struct FileOutcome {
+    pub path: PathBuf,
+    /// The file written, for [`BatchOperation::Convert`].
+    pub output: Option<PathBuf>,
+    pub result: Result<(), Error>,
+}
+
+impl FileOutcome {
+    pub fn is_success(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// The outcome of every file processed by [`batch`], in path order.
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    pub outcomes: Vec<FileOutcome>,
+}
+
+impl BatchSummary {
+    pub fn succeeded(&self) -> impl Iterator<Item = &FileOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.is_success())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &FileOutcome> {
+        self.outcomes.iter().filter(|outcome| !outcome.is_success())
+    }
+
+    /// Whether every file was processed successfully.
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(FileOutcome::is_success)
+    }
+}
+
+/// A file to process, with the directory it was found in, if any.
+struct Job {
+    path: PathBuf,
+    root: Option<PathBuf>,
+}
+
+/// Runs `options.operation` on every file in `paths`, and every model file
+/// in the directories in `paths`, calling `progress` as each file completes.
+///
+/// Directories that cannot be read are reported as failed outcomes.
+pub fn batch<P: AsRef<Path>>(
+    paths: &[P],
+    options: &BatchOptions,
+    mut progress: impl FnMut(&BatchProgress<'_>),
+) -> BatchSummary {
+    let mut jobs = Vec::new();
+    let mut outcomes = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            if let Err(error) = collect_directory(path, path, options, &mut jobs) {
+                outcomes.push(FileOutcome {
+                    path: path.to_path_buf(),
+                    output: None,
+                    result: Err(ParseError::Io(error).into()),
+                });
+            }
+        } else {
+            jobs.push(Job {
+                path: path.to_path_buf(),
+                root: None,
+            });
+        }
+    }
+
+    let total = jobs.len() + outcomes.len();
+    for (completed, outcome) in outcomes.iter().enumerate() {
+        progress(&BatchProgress {
+            completed: completed + 1,
+            total,
+            path: &outcome.path,
+            succeeded: false,
+        });
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..options.threads().min(jobs.len()) {
+            let sender = sender.clone();
+            let (jobs, next) = (&jobs, &next);
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+                    if sender.send(process(job, &options.operation)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for outcome in receiver {
+            progress(&BatchProgress {
+                completed: outcomes.len() + 1,
+                total,
+                path: &outcome.path,
+                succeeded: outcome.is_success(),
+            });
+            outcomes.push(outcome);
+        }
+    });
+
+    outcomes.sort_by(|a, b| a.path.cmp(&b.path));
+    BatchSummary { outcomes }
+}
+
+fn collect_directory(
+    root: &Path,
+    directory: &Path,
+    options: &BatchOptions,
+    jobs: &mut Vec<Job>,
+) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if options.recursive {
+                collect_directory(root, &path, options, jobs)?;
+            }
+        } else if options.matches(&path) {
+            jobs.push(Job {
+                path,
+                root: Some(root.to_path_buf()),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn process(job: &Job, operation: &BatchOperation) -> FileOutcome {
+    let mut output = None;
+    let result = (|| -> Result<(), Error> {
+        let file = XmileFile::from_file(&job.path)?;
+        match operation {
+            BatchOperation::Validate => file.validate()?,
+            BatchOperation::Format(options) => {
+                let xml = file.to_xml_string_with(options)?;
+                std::fs::write(&job.path, xml).map_err(ParseError::Io)?;
+            }
+            BatchOperation::Convert {
+                output: directory,
+                options,
+            } => {
+                let relative = match &job.root {
+                    Some(root) => job.path.strip_prefix(root).unwrap_or(&job.path),
+                    None => Path::new(job.path.file_name().unwrap_or_default()),
+                };
+                let target = directory.join(relative);
+                let xml = file.to_xml_string_with(options)?;
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent).map_err(ParseError::Io)?;
+                }
+                std::fs::write(&target, xml).map_err(ParseError::Io)?;
+                output = Some(target);
+            }
+        }
+        Ok(())
+    })();
+    FileOutcome {
+        path: job.path.clone(),
+        output,
+        result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+    <model><variables><aux name="Rate"><eqn>0.1</eqn></aux></variables></model>
+</xmile>"#;
+
+    #[test]
+    fn test_validate_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("good.xmile"), MODEL).unwrap();
+        std::fs::write(dir.path().join("nested/bad.xmile"), "<xmile>").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a model").unwrap();
+
+        let mut reported = Vec::new();
+        let options = BatchOptions::new(BatchOperation::Validate).with_threads(2);
+        let summary = batch(&[dir.path()], &options, |progress| {
+            reported.push((progress.completed, progress.total));
+        });
+
+        reported.sort();
+        assert_eq!(reported, [(1, 2), (2, 2)]);
+        assert_eq!(summary.outcomes.len(), 2);
+        assert!(!summary.is_success());
+        assert!(summary.outcomes[0].path.ends_with("good.xmile"));
+        let failed: Vec<_> = summary.failed().collect();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].path.ends_with("nested/bad.xmile"));
+    }
+
+    #[test]
+    fn test_convert_keeps_relative_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("in");
+        std::fs::create_dir_all(input.join("sector")).unwrap();
+        std::fs::write(input.join("sector/model.xmile"), MODEL).unwrap();
+
+        let operation = BatchOperation::Convert {
+            output: dir.path().join("out"),
+            options: SerializeOptions {
+                indent: Some(2),
+                ..SerializeOptions::default()
+            },
+        };
+        let summary = batch(&[&input], &BatchOptions::new(operation), |_| {});
+        assert!(summary.is_success(), "{summary:?}");
+
+        let written = dir.path().join("out/sector/model.xmile");
+        assert_eq!(
+            summary.outcomes[0].output.as_deref(),
+            Some(written.as_path())
+        );
+        let file = XmileFile::from_file(&written).unwrap();
+        assert_eq!(file.models.len(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+//! Apache Arrow and Parquet export of simulation results.
+//!
+//! Results become Arrow record batches with a `time` column followed by one
+//! `Float64` column per variable. Ensembles, such as the runs of a
+//! sensitivity analysis, are stacked into a single batch in long form with a
+//! leading `run` column, which is the layout pandas and polars expect for
+//! grouping. Batches can be written to Parquet with [`write_parquet`].
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, UInt32Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use thiserror::Error;
+
+use super::export::ExportData;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ArrowExportError {
+    #[error("Series '{name}' has {found} values but there are {expected} times")]
+    LengthMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Run {run} has no series named '{name}'")]
+    MissingSeries { run: usize, name: String },
+    #[error("Ensemble has no runs")]
+    EmptyEnsemble,
+    #[error("No record batches to write")]
+    NoBatches,
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] ParquetError),
+}
+
+impl ExportData {
+    /// Converts these results to a record batch.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowExportError> {
+        let mut fields = vec![Field::new("time", DataType::Float64, false)];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(Float64Array::from(self.times.clone()))];
+        for (name, values) in &self.series {
+            check_length(name, self.times.len(), values.len())?;
+            fields.push(Field::new(name, DataType::Float64, false));
+            columns.push(Arc::new(Float64Array::from(values.clone())));
+        }
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            columns,
+        )?)
+    }
+}
+
+/// Stacks the runs of an ensemble into one record batch with a `run` column.
+///
+/// Every run must have the series of the first run, which set the column
+/// order; series only present in later runs are ignored.
+pub fn ensemble_record_batch(runs: &[ExportData]) -> Result<RecordBatch, ArrowExportError> {
+    let first = runs.first().ok_or(ArrowExportError::EmptyEnsemble)?;
+    let rows: usize = runs.iter().map(|run| run.times.len()).sum();
+
+    let mut run_ids = Vec::with_capacity(rows);
+    let mut times = Vec::with_capacity(rows);
+    let mut series: Vec<Vec<f64>> = vec![Vec::with_capacity(rows); first.series.len()];
+    for (run, data) in runs.iter().enumerate() {
+        run_ids.extend(std::iter::repeat_n(run as u32, data.times.len()));
+        times.extend_from_slice(&data.times);
+        for ((name, _), column) in first.series.iter().zip(&mut series) {
+            let values = data
+                .series(name)
+                .ok_or_else(|| ArrowExportError::MissingSeries {
+                    run,
+                    name: name.clone(),
+                })?;
+            check_length(name, data.times.len(), values.len())?;
+            column.extend_from_slice(values);
+        }
+    }
+
+    let mut fields = vec![
+        Field::new("run", DataType::UInt32, false),
+        Field::new("time", DataType::Float64, false),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt32Array::from(run_ids)),
+        Arc::new(Float64Array::from(times)),
+    ];
+    for ((name, _), values) in first.series.iter().zip(series) {
+        fields.push(Field::new(name, DataType::Float64, false));
+        columns.push(Arc::new(Float64Array::from(values)));
+    }
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+/// Writes record batches sharing one schema to a Parquet file.
+pub fn write_parquet<W: Write + Send>(
+    batches: &[RecordBatch],
+    out: W,
+) -> Result<(), ArrowExportError> {
+    let first = batches.first().ok_or(ArrowExportError::NoBatches)?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(out, first.schema(), Some(properties))?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+fn check_length(name: &str, expected: usize, found: usize) -> Result<(), ArrowExportError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(ArrowExportError::LengthMismatch {
+            name: name.to_string(),
+            expected,
+            found,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, UInt32Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn run(scale: f64) -> ExportData {
+        ExportData::new(vec![0.0, 1.0, 2.0])
+            .with_series(
+                "Population",
+                vec![100.0 * scale, 110.0 * scale, 121.0 * scale],
+            )
+            .with_series("Birth Rate", vec![0.1, 0.1, 0.1])
+    }
+
+    #[test]
+    fn test_record_batch() {
+        let batch = run(1.0).to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        let names: Vec<&str> = batch
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        assert_eq!(names, ["time", "Population", "Birth Rate"]);
+
+        let bad = ExportData::new(vec![0.0, 1.0]).with_series("x", vec![1.0]);
+        assert!(matches!(
+            bad.to_record_batch(),
+            Err(ArrowExportError::LengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ensemble_parquet_round_trip() {
+        let batch = ensemble_record_batch(&[run(1.0), run(2.0)]).unwrap();
+        assert_eq!(batch.num_rows(), 6);
+
+        let mut file = tempfile::tempfile().unwrap();
+        write_parquet(std::slice::from_ref(&batch), &mut file).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 1);
+        let read = &batches[0];
+        let runs = read.column(0).as_primitive::<UInt32Type>();
+        assert_eq!(runs.values(), &[0, 0, 0, 1, 1, 1]);
+        let population = read.column(2).as_primitive::<Float64Type>();
+        assert_eq!(population.value(5), 242.0);
+
+        assert!(matches!(
+            ensemble_record_batch(&[]),
+            Err(ArrowExportError::EmptyEnsemble)
+        ));
+        let other = ExportData::new(vec![0.0]).with_series("Other", vec![1.0]);
+        assert!(matches!(
+            ensemble_record_batch(&[run(1.0), other]),
+            Err(ArrowExportError::MissingSeries { run: 1, .. })
+        ));
+    }
+}
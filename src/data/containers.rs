@@ -0,0 +1,200 @@
+//! Recorded contents of conveyors and queues.
+//!
+//! The saved values of a conveyor or queue are its totals, but desktop tools
+//! also show the material in transit: the amount on each slat of a conveyor
+//! and each element waiting in a queue. A [`ContainerRecorder`] keeps those
+//! contents for the containers a caller chooses to track, one snapshot per
+//! saved step, so a UI can draw the conveyor or queue at any time of a run:
+//!
+//! ```rust
+//! use xmile::data::{ContainerKind, ContainerRecorder};
+//!
+//! let mut recorder = ContainerRecorder::new();
+//! let line = recorder.track("Production Line", ContainerKind::Conveyor);
+//! recorder.record(line, 0.0, &[0.0, 0.0, 0.0]);
+//! recorder.record(line, 1.0, &[5.0, 0.0, 0.0]);
+//! recorder.record(line, 2.0, &[4.0, 5.0, 0.0]);
+//!
+//! let history = recorder.history("production_line").unwrap();
+//! assert_eq!(history.at(1.5).unwrap().contents, [5.0, 0.0, 0.0]);
+//! assert_eq!(history.totals(), [(0.0, 0.0), (1.0, 5.0), (2.0, 9.0)]);
+//! ```
+//!
+//! Runs do not fill a recorder: [`Simulator`](crate::simulation::Simulator)
+//! cannot run conveyors or queues yet, so callers record contents they
+//! compute themselves, for instance from a `Conveyor` they advance.
+
+use super::export::find_series;
+
+/// The kind of container whose contents are recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContainerKind {
+    /// Contents are the amounts on each slat, from the inflow end to the
+    /// outflow end.
+    Conveyor,
+    /// Contents are the amounts of each element, from the front of the queue
+    /// to the back.
+    Queue,
+}
+
+/// The contents of a container at one time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerSnapshot {
+    pub time: f64,
+    pub contents: Vec<f64>,
+}
+
+impl ContainerSnapshot {
+    /// The total amount in the container.
+    pub fn total(&self) -> f64 {
+        self.contents.iter().sum()
+    }
+}
+
+/// The recorded contents of one conveyor or queue, in time order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerHistory {
+    name: String,
+    kind: ContainerKind,
+    snapshots: Vec<ContainerSnapshot>,
+}
+
+impl ContainerHistory {
+    pub fn new(name: &str, kind: ContainerKind) -> Self {
+        ContainerHistory {
+            name: name.to_string(),
+            kind,
+            snapshots: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> ContainerKind {
+        self.kind
+    }
+
+    pub fn snapshots(&self) -> &[ContainerSnapshot] {
+        &self.snapshots
+    }
+
+    /// Records the contents at `time`, which should not be before the time
+    /// of the previous snapshot.
+    pub fn record(&mut self, time: f64, contents: &[f64]) {
+        self.snapshots.push(ContainerSnapshot {
+            time,
+            contents: contents.to_vec(),
+        });
+    }
+
+    /// The latest snapshot at or before `time`.
+    ///
+    /// Contents are not interpolated: material moves between slats and
+    /// elements leave queues at saved steps.
+    pub fn at(&self, time: f64) -> Option<&ContainerSnapshot> {
+        let after = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.time <= time);
+        after.checked_sub(1).map(|index| &self.snapshots[index])
+    }
+
+    /// The time and total amount of each snapshot.
+    pub fn totals(&self) -> Vec<(f64, f64)> {
+        self.snapshots
+            .iter()
+            .map(|snapshot| (snapshot.time, snapshot.total()))
+            .collect()
+    }
+
+    /// The most slats or elements in any snapshot, for sizing a display of
+    /// a conveyor whose length changes or of a queue.
+    pub fn max_len(&self) -> usize {
+        self.snapshots
+            .iter()
+            .map(|snapshot| snapshot.contents.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Records the contents of the conveyors and queues a caller tracks.
+///
+/// Nothing is recorded for containers that are not tracked, so runs that do
+/// not need the contents pay nothing for them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContainerRecorder {
+    histories: Vec<ContainerHistory>,
+}
+
+impl ContainerRecorder {
+    pub fn new() -> Self {
+        ContainerRecorder::default()
+    }
+
+    /// Starts tracking the named container, returning the index to record
+    /// its contents with.
+    pub fn track(&mut self, name: &str, kind: ContainerKind) -> usize {
+        self.histories.push(ContainerHistory::new(name, kind));
+        self.histories.len() - 1
+    }
+
+    /// Records the contents of the container tracked at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no container is tracked at `index`.
+    pub fn record(&mut self, index: usize, time: f64, contents: &[f64]) {
+        self.histories[index].record(time, contents);
+    }
+
+    /// The tracked containers, in the order they were tracked.
+    pub fn histories(&self) -> &[ContainerHistory] {
+        &self.histories
+    }
+
+    /// The history of the named container.
+    ///
+    /// Names are compared as XMILE identifiers, as in
+    /// [`ExportData::series`](super::ExportData::series).
+    pub fn history(&self, name: &str) -> Option<&ContainerHistory> {
+        let index = find_series(self.histories.iter().map(ContainerHistory::name), name)?;
+        Some(&self.histories[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_lookup() {
+        let mut history = ContainerHistory::new("Queue", ContainerKind::Queue);
+        assert!(history.at(0.0).is_none());
+        history.record(0.0, &[]);
+        history.record(1.0, &[3.0, 2.0]);
+        history.record(2.0, &[2.0]);
+
+        assert!(history.at(-1.0).is_none());
+        assert_eq!(history.at(0.5).unwrap().contents, Vec::<f64>::new());
+        assert_eq!(history.at(1.0).unwrap().total(), 5.0);
+        assert_eq!(history.at(10.0).unwrap().time, 2.0);
+        assert_eq!(history.max_len(), 2);
+    }
+
+    #[test]
+    fn test_recorder_tracks_by_name() {
+        let mut recorder = ContainerRecorder::new();
+        let conveyor = recorder.track("Pipeline", ContainerKind::Conveyor);
+        let queue = recorder.track("Waiting List", ContainerKind::Queue);
+        recorder.record(queue, 0.0, &[1.0]);
+        recorder.record(conveyor, 0.0, &[0.0, 2.0]);
+
+        let waiting = recorder.history("waiting_list").unwrap();
+        assert_eq!(waiting.kind(), ContainerKind::Queue);
+        assert_eq!(waiting.totals(), [(0.0, 1.0)]);
+        assert_eq!(recorder.histories()[conveyor].name(), "Pipeline");
+        assert!(recorder.history("Backlog").is_none());
+    }
+}
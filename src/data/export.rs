@@ -0,0 +1,622 @@
+//! Resolution of export settings and CSV output for `<export>` connections.
+//!
+//! An `<export>` takes its orientation and interval from its own attributes,
+//! unless it exports a table with `use_settings="true"`, in which case the
+//! table's orientation, interval and number formatting are used instead.
+
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Identifier;
+use crate::model::format::NumberFormat;
+use crate::model::object::{FormatOptions, Object};
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+use crate::types::{Validate, ValidationResult};
+use crate::xml::schema::{Model, XmileFile};
+use crate::xml::validation::get_variable_name;
+
+use super::{DataExport, TableExport};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ExportError {
+    #[error("Export has neither <all/> nor <table/>")]
+    MissingSource,
+    #[error("Invalid table uid: {0}")]
+    InvalidTableUid(String),
+    #[error("No model found for table uid: {0}")]
+    ModelNotFound(String),
+    #[error("No table found with uid: {0}")]
+    TableNotFound(String),
+    #[error("Invalid export orientation: {0}")]
+    InvalidOrientation(String),
+    #[error("Invalid export interval: {0}")]
+    InvalidInterval(String),
+    #[error("Horizontal exports cannot be streamed")]
+    HorizontalStream,
+    #[error("Streaming export has already finished")]
+    StreamFinished,
+    #[error("IO error writing export: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// The layout of exported data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportOrientation {
+    /// One row per variable, with times across the columns.
+    Horizontal,
+    /// One column per variable, with times down the rows.
+    #[default]
+    Vertical,
+}
+
+impl std::str::FromStr for ExportOrientation {
+    type Err = ExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(ExportOrientation::Horizontal),
+            "vertical" => Ok(ExportOrientation::Vertical),
+            _ => Err(ExportError::InvalidOrientation(s.to_string())),
+        }
+    }
+}
+
+/// How often, in model time, values are exported.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ExportInterval {
+    /// Export only once, at the end of the run (`interval="0"`).
+    #[default]
+    Once,
+    /// Export every DT (`interval="DT"`).
+    EveryDt,
+    /// Export every given amount of model time.
+    Every(f64),
+}
+
+impl ExportInterval {
+    /// Returns the indices of the saved steps at `times` that are exported.
+    ///
+    /// An `Every` interval exports the first step and then the first step at
+    /// or past each further multiple of the interval. Intervals that are not
+    /// positive export every step.
+    pub fn rows(&self, times: &[f64]) -> Vec<usize> {
+        if *self == ExportInterval::Once {
+            return times.len().checked_sub(1).into_iter().collect();
+        }
+        let mut cursor = IntervalCursor::new(*self);
+        (0..times.len())
+            .filter(|&row| cursor.accept(times[row]))
+            .collect()
+    }
+}
+
+/// Tracks which saved steps an interval exports as they arrive in time order.
+///
+/// `Once` accepts every step; callers keep only the last.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IntervalCursor {
+    step: Option<f64>,
+    start: Option<f64>,
+    multiple: f64,
+}
+
+impl IntervalCursor {
+    pub(crate) fn new(interval: ExportInterval) -> Self {
+        let step = match interval {
+            ExportInterval::Every(step) if step > 0.0 => Some(step),
+            _ => None,
+        };
+        IntervalCursor {
+            step,
+            start: None,
+            multiple: 0.0,
+        }
+    }
+
+    /// Returns whether the step saved at `time` is exported.
+    pub(crate) fn accept(&mut self, time: f64) -> bool {
+        let Some(step) = self.step else {
+            return true;
+        };
+        let start = *self.start.get_or_insert(time);
+        let tolerance = step * 1e-9;
+        if time + tolerance >= start + self.multiple * step {
+            self.multiple = ((time + tolerance - start) / step).floor() + 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl std::str::FromStr for ExportInterval {
+    type Err = ExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("dt") {
+            return Ok(ExportInterval::EveryDt);
+        }
+        match s.parse::<f64>() {
+            Ok(0.0) => Ok(ExportInterval::Once),
+            Ok(interval) if interval.is_finite() => Ok(ExportInterval::Every(interval)),
+            _ => Err(ExportError::InvalidInterval(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportInterval::Once => write!(f, "0"),
+            ExportInterval::EveryDt => write!(f, "DT"),
+            ExportInterval::Every(interval) => write!(f, "{}", interval),
+        }
+    }
+}
+
+impl Serialize for ExportInterval {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExportInterval {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Validate for ExportInterval {
+    fn validate(&self) -> ValidationResult {
+        match self {
+            ExportInterval::Every(interval) if *interval < 0.0 => ValidationResult::Invalid(
+                Vec::new(),
+                vec![format!("Export interval cannot be negative: {}", interval)],
+            ),
+            _ => ValidationResult::Valid(()),
+        }
+    }
+}
+
+/// A variable written by an export, with the format used for its values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportColumn {
+    /// The variable name, as it appears in the table or model.
+    pub name: String,
+    /// The format for values, or `None` to write them unformatted.
+    pub format: Option<NumberFormat>,
+}
+
+/// The effective settings of an export connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSettings {
+    /// The layout of the exported data.
+    pub orientation: ExportOrientation,
+    /// How often, in model time, values are exported.
+    pub interval: ExportInterval,
+    /// The exported variables, or `None` to export every variable.
+    pub columns: Option<Vec<ExportColumn>>,
+}
+
+impl TableExport {
+    /// Splits the qualified uid into the module name and the table uid.
+    ///
+    /// A uid in the root model is prefixed with `.`, e.g. `.7`; a uid in a
+    /// submodel is prefixed with its module name, e.g. `Hares.7`. An
+    /// unqualified uid is treated as belonging to the root model.
+    pub fn qualified_uid(&self) -> Result<(Option<&str>, i32), ExportError> {
+        let invalid = || ExportError::InvalidTableUid(self.uid.clone());
+        let (module, uid) = match self.uid.rsplit_once('.') {
+            Some(("", uid)) => (None, uid),
+            Some((module, uid)) => (Some(module), uid),
+            None => (None, self.uid.as_str()),
+        };
+        let uid = uid.trim().parse().map_err(|_| invalid())?;
+        Ok((module, uid))
+    }
+}
+
+impl DataExport {
+    /// Resolves the settings this export is written with.
+    ///
+    /// When the export names a table with `use_settings="true"`, orientation,
+    /// interval and number formatting come from the table, and the export's
+    /// own `orientation` and `interval` attributes are ignored.
+    pub fn settings(&self, file: &XmileFile) -> Result<ExportSettings, ExportError> {
+        let orientation = match &self.orientation {
+            Some(orientation) => orientation.parse()?,
+            None => ExportOrientation::default(),
+        };
+        let mut settings = ExportSettings {
+            orientation,
+            interval: self.interval.unwrap_or_default(),
+            columns: None,
+        };
+
+        match &self.table_uid {
+            Some(table) => apply_table(&mut settings, table, file)?,
+            None if self.export_all.is_some() => {}
+            None => return Err(ExportError::MissingSource),
+        }
+        Ok(settings)
+    }
+}
+
+#[cfg(feature = "interface-objects")]
+fn apply_table(
+    settings: &mut ExportSettings,
+    table: &TableExport,
+    file: &XmileFile,
+) -> Result<(), ExportError> {
+    use crate::view::{TableItemType, TableOrientation};
+
+    let (module, uid) = table.qualified_uid()?;
+    let model =
+        find_model(file, module).ok_or_else(|| ExportError::ModelNotFound(table.uid.clone()))?;
+    let object = model
+        .views
+        .iter()
+        .flat_map(|views| &views.views)
+        .flat_map(|view| &view.tables)
+        .find(|object| object.uid.value == uid)
+        .ok_or_else(|| ExportError::TableNotFound(table.uid.clone()))?;
+
+    let use_settings = table.use_settings.unwrap_or(false);
+    let formats = object.number_formats(|name| entity_format(model, name));
+    settings.columns = Some(
+        object
+            .items
+            .iter()
+            .zip(formats)
+            .filter(|(item, _)| item.item_type == TableItemType::Variable)
+            .filter_map(|(item, format)| {
+                let name = item.entity_name.clone()?;
+                let format = use_settings.then_some(format);
+                Some(ExportColumn { name, format })
+            })
+            .collect(),
+    );
+
+    if use_settings {
+        settings.orientation = match object.orientation {
+            TableOrientation::Horizontal => ExportOrientation::Horizontal,
+            TableOrientation::Vertical => ExportOrientation::Vertical,
+        };
+        settings.interval = object.interval.parse()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "interface-objects"))]
+fn apply_table(
+    _settings: &mut ExportSettings,
+    table: &TableExport,
+    _file: &XmileFile,
+) -> Result<(), ExportError> {
+    // Tables are only parsed with the interface-objects feature.
+    table.qualified_uid()?;
+    Err(ExportError::TableNotFound(table.uid.clone()))
+}
+
+/// Finds the root model, or the model for the named module.
+#[cfg_attr(not(feature = "interface-objects"), allow(dead_code))]
+fn find_model<'a>(file: &'a XmileFile, module: Option<&str>) -> Option<&'a Model> {
+    match module {
+        None => file
+            .models
+            .iter()
+            .find(|model| model.name.is_none())
+            .or_else(|| file.models.first()),
+        Some(module) => {
+            // Nested modules are qualified by each enclosing module in turn.
+            let name = module.rsplit('.').next().unwrap_or(module);
+            file.models
+                .iter()
+                .find(|model| model.name.as_deref() == Some(name))
+        }
+    }
+}
+
+/// Looks up the `<format>` of the named variable in `model`.
+#[cfg_attr(not(feature = "interface-objects"), allow(dead_code))]
+fn entity_format(model: &Model, name: &str) -> Option<FormatOptions> {
+    let name = Identifier::parse_from_attribute(name).ok()?;
+    let variable = model
+        .variables
+        .variables
+        .iter()
+        .find(|variable| get_variable_name(variable) == Some(&name))?;
+    let format = match variable {
+        Variable::Auxiliary(aux) => aux.format(),
+        Variable::Stock(stock) => match stock.as_ref() {
+            Stock::Basic(s) => s.format(),
+            Stock::Conveyor(s) => s.format(),
+            Stock::Queue(s) => s.format(),
+        },
+        Variable::Flow(flow) => flow.format(),
+        Variable::GraphicalFunction(gf) => gf.format(),
+        _ => None,
+    };
+    format.copied()
+}
+
+impl ExportSettings {
+    /// Writes `data` as CSV in the settings' orientation.
+    ///
+    /// Only the configured columns are written, in order; when exporting
+    /// every variable, all series in `data` are written. Series named in
+    /// the settings but missing from `data` are skipped. Only the saved
+    /// steps selected by the interval are written.
+    pub fn write_csv<W: Write>(&self, data: &ExportData, mut out: W) -> Result<(), ExportError> {
+        let columns: Vec<(&str, &[f64], Option<&NumberFormat>)> = match &self.columns {
+            Some(columns) => columns
+                .iter()
+                .filter_map(|column| {
+                    let values = data.series(&column.name)?;
+                    Some((column.name.as_str(), values, column.format.as_ref()))
+                })
+                .collect(),
+            None => data
+                .series
+                .iter()
+                .map(|(name, values)| (name.as_str(), values.as_slice(), None))
+                .collect(),
+        };
+        let rows = self.interval.rows(&data.times);
+        let cell = |values: &[f64], row: usize, format: Option<&NumberFormat>| {
+            values
+                .get(row)
+                .map_or_else(String::new, |value| csv_value(*value, format))
+        };
+
+        match self.orientation {
+            ExportOrientation::Vertical => {
+                let header: Vec<String> = std::iter::once("Time")
+                    .chain(columns.iter().map(|(name, _, _)| *name))
+                    .map(csv_field)
+                    .collect();
+                writeln!(out, "{}", header.join(","))?;
+                for &row in &rows {
+                    let record: Vec<String> = std::iter::once(data.times[row].to_string())
+                        .chain(
+                            columns
+                                .iter()
+                                .map(|(_, values, format)| cell(values, row, *format)),
+                        )
+                        .collect();
+                    writeln!(out, "{}", record.join(","))?;
+                }
+            }
+            ExportOrientation::Horizontal => {
+                let times: Vec<String> = std::iter::once("Time".to_string())
+                    .chain(rows.iter().map(|&row| data.times[row].to_string()))
+                    .collect();
+                writeln!(out, "{}", times.join(","))?;
+                for (name, values, format) in &columns {
+                    let record: Vec<String> = std::iter::once(csv_field(name))
+                        .chain(rows.iter().map(|&row| cell(values, row, *format)))
+                        .collect();
+                    writeln!(out, "{}", record.join(","))?;
+                }
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Simulation output to export: a time column and one series per variable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportData {
+    /// The time of each saved step.
+    pub times: Vec<f64>,
+    /// The number of each saved step, counting steps of DT from the start
+    /// time, where known. Empty for data not recorded from a run.
+    pub steps: Vec<usize>,
+    /// The value of each variable at each saved step.
+    pub series: Vec<(String, Vec<f64>)>,
+}
+
+impl ExportData {
+    /// Creates export data for the given save times.
+    pub fn new(times: Vec<f64>) -> Self {
+        ExportData {
+            times,
+            steps: Vec::new(),
+            series: Vec::new(),
+        }
+    }
+
+    /// Sets the number of each saved step.
+    pub fn with_steps(mut self, steps: Vec<usize>) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Adds the values of a variable.
+    pub fn with_series(mut self, name: &str, values: Vec<f64>) -> Self {
+        self.series.push((name.to_string(), values));
+        self
+    }
+
+    /// Returns the values of the named variable.
+    ///
+    /// Names are compared as XMILE identifiers, so `Birth_Rate` finds the
+    /// series for `birth rate`.
+    pub fn series(&self, name: &str) -> Option<&[f64]> {
+        let index = find_series(self.series.iter().map(|(series, _)| series.as_str()), name)?;
+        Some(&self.series[index].1)
+    }
+
+    /// Returns the value of the named variable at `time`, interpolating
+    /// linearly between saved times and holding the first and last values
+    /// beyond them.
+    pub fn value_at(&self, name: &str, time: f64) -> Option<f64> {
+        let values = self.series(name)?;
+        let count = self.times.len().min(values.len());
+        if count == 0 {
+            return None;
+        }
+        let after = self.times[..count].partition_point(|&saved| saved < time);
+        if after == 0 {
+            return Some(values[0]);
+        }
+        if after >= count {
+            return Some(values[count - 1]);
+        }
+        let (t0, t1) = (self.times[after - 1], self.times[after]);
+        let (v0, v1) = (values[after - 1], values[after]);
+        if t1 == t0 {
+            Some(v1)
+        } else {
+            Some(v0 + (v1 - v0) * (time - t0) / (t1 - t0))
+        }
+    }
+}
+
+/// Returns the position of `name` among `names`, comparing as identifiers.
+pub(crate) fn find_series<'a>(names: impl Iterator<Item = &'a str>, name: &str) -> Option<usize> {
+    let key = Identifier::parse_from_attribute(name).ok();
+    names.into_iter().position(|series| {
+        series == name
+            || key.as_ref().is_some_and(|key| {
+                Identifier::parse_from_attribute(series).is_ok_and(|series| &series == key)
+            })
+    })
+}
+
+/// Formats a value as a CSV field.
+pub(crate) fn csv_value(value: f64, format: Option<&NumberFormat>) -> String {
+    match format {
+        Some(format) => csv_field(&format.format(value)),
+        None => value.to_string(),
+    }
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote or line break.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(uid: &str) -> TableExport {
+        TableExport {
+            uid: uid.to_string(),
+            use_settings: None,
+        }
+    }
+
+    #[test]
+    fn test_qualified_uid() {
+        assert_eq!(table(".7").qualified_uid().unwrap(), (None, 7));
+        assert_eq!(table("7").qualified_uid().unwrap(), (None, 7));
+        assert_eq!(
+            table("Hares.12").qualified_uid().unwrap(),
+            (Some("Hares"), 12)
+        );
+        assert_eq!(
+            table("Outer.Inner.3").qualified_uid().unwrap(),
+            (Some("Outer.Inner"), 3)
+        );
+        assert!(matches!(
+            table("Hares.x").qualified_uid(),
+            Err(ExportError::InvalidTableUid(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_csv_orientations() {
+        let data = ExportData::new(vec![0.0, 1.0])
+            .with_series("Population", vec![100.0, 110.5])
+            .with_series("birth rate", vec![0.1, 0.1]);
+        let mut settings = ExportSettings {
+            orientation: ExportOrientation::Vertical,
+            interval: ExportInterval::EveryDt,
+            columns: None,
+        };
+
+        let mut out = Vec::new();
+        settings.write_csv(&data, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Time,Population,birth rate\n0,100,0.1\n1,110.5,0.1\n"
+        );
+
+        settings.orientation = ExportOrientation::Horizontal;
+        settings.columns = Some(vec![ExportColumn {
+            name: "Birth_Rate".to_string(),
+            format: Some(NumberFormat {
+                precision: Some(0.01),
+                ..NumberFormat::default()
+            }),
+        }]);
+        let mut out = Vec::new();
+        settings.write_csv(&data, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Time,0,1\nBirth_Rate,0.10,0.10\n"
+        );
+    }
+
+    #[test]
+    fn test_interval_parsing() {
+        assert_eq!(
+            "DT".parse::<ExportInterval>().unwrap(),
+            ExportInterval::EveryDt
+        );
+        assert_eq!(
+            "dt".parse::<ExportInterval>().unwrap(),
+            ExportInterval::EveryDt
+        );
+        assert_eq!("0".parse::<ExportInterval>().unwrap(), ExportInterval::Once);
+        assert_eq!(
+            " 0.25 ".parse::<ExportInterval>().unwrap(),
+            ExportInterval::Every(0.25)
+        );
+        assert!("weekly".parse::<ExportInterval>().is_err());
+        assert!("NaN".parse::<ExportInterval>().is_err());
+        assert_eq!(ExportInterval::Every(0.5).to_string(), "0.5");
+        assert_eq!(ExportInterval::EveryDt.to_string(), "DT");
+
+        let negative: ExportInterval = "-1".parse().unwrap();
+        assert!(negative.validate().is_invalid());
+        assert!(ExportInterval::Every(2.0).validate().is_valid());
+    }
+
+    #[test]
+    fn test_interval_rows() {
+        let times = [0.0, 0.25, 0.5, 0.75, 1.0, 1.25, 1.5];
+        assert_eq!(ExportInterval::Once.rows(&times), vec![6]);
+        assert_eq!(ExportInterval::EveryDt.rows(&times).len(), 7);
+        assert_eq!(ExportInterval::Every(0.5).rows(&times), vec![0, 2, 4, 6]);
+        assert_eq!(ExportInterval::Every(0.6).rows(&times), vec![0, 3, 5]);
+        assert_eq!(ExportInterval::Every(-1.0).rows(&times).len(), 7);
+        assert!(ExportInterval::Once.rows(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("1,234"), "\"1,234\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
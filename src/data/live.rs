@@ -0,0 +1,286 @@
+//! Live streaming of saved steps to a dashboard.
+//!
+//! [`channel`] pairs a [`LiveSink`], which the run writes its saved steps
+//! to through [`SaveStepSink`], with a [`LiveStream`] that receives them on
+//! another thread as they are computed. A server forwards each
+//! [`LiveUpdate`] to its clients, for instance as the JSON text of
+//! [`LiveStream::to_json`] in a WebSocket frame.
+//!
+//! When the run has a pause interval (`pause` in the simulation specs), the
+//! sink sends [`LiveUpdate::Paused`] after the step ending each interval and
+//! waits until the stream calls [`LiveStream::resume`]:
+//!
+//! ```rust
+//! use xmile::data::SaveStepSink;
+//! use xmile::data::live::{self, LiveUpdate};
+//!
+//! let (mut sink, stream) = live::channel(&["Population"], Some(5.0));
+//! let run = std::thread::spawn(move || {
+//!     for step in 0..=10 {
+//!         sink.save_step(step as f64, &[100.0 + step as f64]).unwrap();
+//!     }
+//!     sink.finish().unwrap();
+//! });
+//! let mut steps = 0;
+//! for update in &stream {
+//!     match update {
+//!         LiveUpdate::Step { .. } => steps += 1,
+//!         LiveUpdate::Paused { .. } => stream.resume(),
+//!         LiveUpdate::Finished => break,
+//!     }
+//! }
+//! run.join().unwrap();
+//! assert_eq!(steps, 11);
+//! ```
+//!
+//! A dashboard closing never stops the run: once the stream is dropped the
+//! sink discards steps and no longer pauses.
+
+use std::fmt::Write as _;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use super::export::ExportError;
+use super::stream::SaveStepSink;
+
+/// A message from a running simulation to a [`LiveStream`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveUpdate {
+    /// The values saved at `time`, in the order of [`LiveStream::names`].
+    Step { time: f64, values: Vec<f64> },
+    /// The run reached the end of a pause interval at `time`, and waits
+    /// for [`LiveStream::resume`].
+    Paused { time: f64 },
+    /// The run has finished.
+    Finished,
+}
+
+/// Creates a sink for steps whose values are given in `names` order, and
+/// the stream receiving them.
+///
+/// The run pauses every `pause` units of model time after its first saved
+/// step. Intervals that are not positive and finite never pause, as the
+/// specification's default of infinity.
+pub fn channel(names: &[&str], pause: Option<f64>) -> (LiveSink, LiveStream) {
+    let (updates, receiver) = mpsc::channel();
+    let (resume, resumed) = mpsc::channel();
+    let sink = LiveSink {
+        updates,
+        resumed,
+        pause: pause.filter(|pause| pause.is_finite() && *pause > 0.0),
+        next_pause: None,
+        connected: true,
+        finished: false,
+    };
+    let stream = LiveStream {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        updates: receiver,
+        resume,
+    };
+    (sink, stream)
+}
+
+/// Sends each saved step of a run to a [`LiveStream`].
+#[derive(Debug)]
+pub struct LiveSink {
+    updates: Sender<LiveUpdate>,
+    resumed: Receiver<()>,
+    pause: Option<f64>,
+    /// The time at which the current pause interval ends.
+    next_pause: Option<f64>,
+    /// Whether the stream still exists.
+    connected: bool,
+    finished: bool,
+}
+
+impl LiveSink {
+    fn send(&mut self, update: LiveUpdate) {
+        if self.connected && self.updates.send(update).is_err() {
+            self.connected = false;
+        }
+    }
+}
+
+impl SaveStepSink for LiveSink {
+    fn save_step(&mut self, time: f64, values: &[f64]) -> Result<(), ExportError> {
+        if self.finished {
+            return Err(ExportError::StreamFinished);
+        }
+        self.send(LiveUpdate::Step {
+            time,
+            values: values.to_vec(),
+        });
+
+        let Some(pause) = self.pause else {
+            return Ok(());
+        };
+        let next_pause = *self.next_pause.get_or_insert(time + pause);
+        // Allow for rounding in times accumulated from DT
+        if time + pause * 1e-9 >= next_pause {
+            let mut next = next_pause;
+            while next <= time + pause * 1e-9 {
+                next += pause;
+            }
+            self.next_pause = Some(next);
+            self.send(LiveUpdate::Paused { time });
+            if self.connected && self.resumed.recv().is_err() {
+                self.connected = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        if !self.finished {
+            self.finished = true;
+            self.send(LiveUpdate::Finished);
+        }
+        Ok(())
+    }
+}
+
+/// Receives the updates of a running simulation from a [`LiveSink`].
+///
+/// Iterating over a stream blocks until the next update, and ends when the
+/// sink is dropped.
+#[derive(Debug)]
+pub struct LiveStream {
+    names: Vec<String>,
+    updates: Receiver<LiveUpdate>,
+    resume: Sender<()>,
+}
+
+impl LiveStream {
+    /// The names of the variables, in the order of each step's values.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Waits for the next update, or returns `None` once the sink is gone.
+    pub fn recv(&self) -> Option<LiveUpdate> {
+        self.updates.recv().ok()
+    }
+
+    /// The next update if one is waiting.
+    pub fn try_recv(&self) -> Option<LiveUpdate> {
+        self.updates.try_recv().ok()
+    }
+
+    /// Lets a paused run continue. Does nothing if the run is not paused.
+    pub fn resume(&self) {
+        let _ = self.resume.send(());
+    }
+
+    /// Encodes `update` as a JSON object, with step values keyed by
+    /// variable name. Values that are not finite are written as `null`.
+    pub fn to_json(&self, update: &LiveUpdate) -> String {
+        match update {
+            LiveUpdate::Step { time, values } => {
+                let mut json = format!(r#"{{"type":"step","time":{},"values":{{"#, number(*time));
+                for (index, (name, value)) in self.names.iter().zip(values).enumerate() {
+                    if index > 0 {
+                        json.push(',');
+                    }
+                    let _ = write!(json, "{}:{}", string(name), number(*value));
+                }
+                json.push_str("}}");
+                json
+            }
+            LiveUpdate::Paused { time } => {
+                format!(r#"{{"type":"paused","time":{}}}"#, number(*time))
+            }
+            LiveUpdate::Finished => r#"{"type":"finished"}"#.to_string(),
+        }
+    }
+}
+
+impl Iterator for &LiveStream {
+    type Item = LiveUpdate;
+
+    fn next(&mut self) -> Option<LiveUpdate> {
+        self.recv()
+    }
+}
+
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pauses_until_resumed() {
+        let (mut sink, stream) = channel(&["a"], Some(1.0));
+        let run = std::thread::spawn(move || {
+            for step in 0..=8 {
+                sink.save_step(step as f64 * 0.25, &[step as f64]).unwrap();
+            }
+            sink.finish().unwrap();
+            sink.save_step(3.0, &[0.0]).unwrap_err();
+        });
+
+        let mut pauses = Vec::new();
+        let mut steps = 0;
+        for update in &stream {
+            match update {
+                LiveUpdate::Step { .. } => steps += 1,
+                LiveUpdate::Paused { time } => {
+                    // The run waits, so nothing follows until resumed
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                    assert_eq!(stream.try_recv(), None);
+                    pauses.push(time);
+                    stream.resume();
+                }
+                LiveUpdate::Finished => break,
+            }
+        }
+        run.join().unwrap();
+        assert_eq!(steps, 9);
+        assert_eq!(pauses, [1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_json_and_disconnect() {
+        let (mut sink, stream) = channel(&["Birth \"Rate\"", "b"], Some(1.0));
+        sink.save_step(0.0, &[0.5, f64::NAN]).unwrap();
+        let update = stream.recv().unwrap();
+        assert_eq!(
+            stream.to_json(&update),
+            r#"{"type":"step","time":0,"values":{"Birth \"Rate\"":0.5,"b":null}}"#
+        );
+        assert_eq!(
+            stream.to_json(&LiveUpdate::Paused { time: 1.5 }),
+            r#"{"type":"paused","time":1.5}"#
+        );
+
+        // Once the stream is dropped the run neither fails nor waits
+        drop(stream);
+        for step in 1..=3 {
+            sink.save_step(step as f64, &[0.0, 0.0]).unwrap();
+        }
+        sink.finish().unwrap();
+    }
+}
@@ -11,6 +11,27 @@
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod containers;
+pub mod export;
+pub mod live;
+pub mod retain;
+pub mod stream;
+pub use containers::{ContainerHistory, ContainerKind, ContainerRecorder, ContainerSnapshot};
+pub use export::{
+    ExportColumn, ExportData, ExportError, ExportInterval, ExportOrientation, ExportSettings,
+};
+pub use live::{LiveSink, LiveStream, LiveUpdate};
+pub use retain::{ResultRecorder, Retention};
+pub use stream::{CsvStreamWriter, FlushPolicy, SaveStepSink};
+
+#[cfg(feature = "arrow")]
+pub use arrow::{ArrowExportError, ensemble_record_batch, write_parquet};
+
+use crate::resource::{AsyncResourceReader, Resource, ResourceError, ResourceReader, ResourceRef};
+use crate::types::{Validate, ValidationResult};
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Data {
     /// A list of data import connections in the XMILE file.
@@ -24,53 +45,84 @@ pub struct Data {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DataImport {
     /// The type of the data import (e.g., CSV, Excel, XML).
-    #[serde(rename = "@type")]
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
     pub data_type: Option<String>,
     /// Indicates whether the data import is enabled.
-    #[serde(rename = "@enabled")]
+    #[serde(rename = "@enabled", skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
     /// The frequency of the data import (e.g., on_demand, automatic).
-    #[serde(rename = "@frequency")]
+    #[serde(rename = "@frequency", skip_serializing_if = "Option::is_none")]
     pub frequency: Option<String>,
     /// The orientation of the data import (e.g., horizontal, vertical).
-    #[serde(rename = "@orientation")]
+    #[serde(rename = "@orientation", skip_serializing_if = "Option::is_none")]
     pub orientation: Option<String>,
     /// The source location of the data import.
-    #[serde(rename = "@resource")]
-    pub resource: Option<String>,
+    #[serde(rename = "@resource", skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ResourceRef>,
     /// The worksheet name for Excel imports.
-    #[serde(rename = "@worksheet")]
+    #[serde(rename = "@worksheet", skip_serializing_if = "Option::is_none")]
     pub worksheet: Option<String>,
 }
 
+impl DataImport {
+    /// Loads the source of this import connection.
+    pub fn fetch(&self, reader: &impl ResourceReader) -> Result<Resource, ResourceError> {
+        let location = self
+            .resource
+            .as_ref()
+            .ok_or(ResourceError::MissingResource)?;
+        let contents = reader.read(location)?;
+        Ok(Resource {
+            location: location.clone(),
+            contents,
+        })
+    }
+
+    /// Loads the source of this import connection without blocking.
+    pub async fn fetch_async(
+        &self,
+        reader: &impl AsyncResourceReader,
+    ) -> Result<Resource, ResourceError> {
+        let location = self
+            .resource
+            .as_ref()
+            .ok_or(ResourceError::MissingResource)?;
+        let contents = reader.read(location).await?;
+        Ok(Resource {
+            location: location.clone(),
+            contents,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DataExport {
     /// The type of the data export (e.g., CSV, Excel, XML).
-    #[serde(rename = "@type")]
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
     pub data_type: Option<String>,
     /// Indicates whether the data export is enabled.
-    #[serde(rename = "@enabled")]
+    #[serde(rename = "@enabled", skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
     /// The frequency of the data export (e.g., on_demand, automatic).
-    #[serde(rename = "@frequency")]
+    #[serde(rename = "@frequency", skip_serializing_if = "Option::is_none")]
     pub frequency: Option<String>,
     /// The orientation of the data export (e.g., horizontal, vertical).
-    #[serde(rename = "@orientation")]
+    #[serde(rename = "@orientation", skip_serializing_if = "Option::is_none")]
     pub orientation: Option<String>,
     /// The destination location of the data export.
-    #[serde(rename = "@resource")]
-    pub resource: Option<String>,
+    #[serde(rename = "@resource", skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ResourceRef>,
     /// The worksheet name for Excel exports.
-    #[serde(rename = "@worksheet")]
+    #[serde(rename = "@worksheet", skip_serializing_if = "Option::is_none")]
     pub worksheet: Option<String>,
     /// The export interval in model time.
-    #[serde(rename = "@interval")]
-    pub interval: Option<String>,
+    #[serde(rename = "@interval", skip_serializing_if = "Option::is_none")]
+    pub interval: Option<ExportInterval>,
     /// Indicates whether to export all variables or a specific table.
-    #[serde(rename = "all")]
+    #[serde(rename = "all", skip_serializing_if = "Option::is_none")]
     pub export_all: Option<()>,
     /// The UID of the table to export (if not exporting all variables).
-    #[serde(rename = "table")]
+    #[serde(rename = "table", skip_serializing_if = "Option::is_none")]
     pub table_uid: Option<TableExport>,
 }
 
@@ -78,6 +130,39 @@ pub struct DataExport {
 pub struct TableExport {
     #[serde(rename = "@uid")]
     pub uid: String,
-    #[serde(rename = "@use_settings")]
+    #[serde(rename = "@use_settings", skip_serializing_if = "Option::is_none")]
     pub use_settings: Option<bool>,
 }
+
+impl Validate for DataExport {
+    fn validate(&self) -> ValidationResult {
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+
+        if let Some(interval) = &self.interval
+            && let ValidationResult::Invalid(_, errs) = interval.validate()
+        {
+            errors.extend(errs);
+        }
+
+        if self
+            .table_uid
+            .as_ref()
+            .is_some_and(|table| table.use_settings == Some(true))
+            && (self.orientation.is_some() || self.interval.is_some())
+        {
+            warnings.push(
+                "Export orientation and interval are ignored when the table's settings are used."
+                    .to_string(),
+            );
+        }
+
+        if !errors.is_empty() {
+            ValidationResult::Invalid(warnings, errors)
+        } else if !warnings.is_empty() {
+            ValidationResult::Warnings((), warnings)
+        } else {
+            ValidationResult::Valid(())
+        }
+    }
+}
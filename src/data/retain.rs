@@ -0,0 +1,316 @@
+//! Bounded storage of run results.
+//!
+//! A run saving millions of steps cannot keep every value in memory on a
+//! small machine. [`ResultRecorder`] receives each saved step through
+//! [`SaveStepSink`] and keeps only what its [`Retention`] allows, while
+//! optionally passing every step on to another sink, such as a
+//! [`CsvStreamWriter`](super::CsvStreamWriter), at full resolution:
+//!
+//! ```rust
+//! use xmile::data::{ResultRecorder, Retention, SaveStepSink};
+//!
+//! let mut recorder = ResultRecorder::new(&["Population"], Retention::EveryNth(10));
+//! for step in 0..=100 {
+//!     recorder.save_step(step as f64, &[step as f64 * 2.0]).unwrap();
+//! }
+//! recorder.finish().unwrap();
+//! assert_eq!(recorder.latest(), Some((100.0, &[200.0][..])));
+//! let data = recorder.into_data();
+//! assert_eq!(data.times.len(), 11);
+//! assert_eq!(data.series("population").unwrap()[1], 20.0);
+//! ```
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::ExportData;
+use super::export::ExportError;
+use super::stream::SaveStepSink;
+
+/// Which saved steps a [`ResultRecorder`] keeps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retention {
+    /// Keep every step.
+    All,
+    /// Keep the first step and every `n`th step after it, and the last step.
+    EveryNth(usize),
+    /// Keep a uniform random sample of at most `capacity` steps, in time
+    /// order. The same seed keeps the same steps.
+    Reservoir { capacity: usize, seed: u64 },
+    /// Replace each run of `n` consecutive steps by the mean of their
+    /// values, at the time of the first step of the run.
+    Mean(usize),
+}
+
+impl Retention {
+    /// Keeps every `n`th step, with `n` chosen so that `steps` saved steps
+    /// of `variables` values take at most `max_bytes` of memory.
+    pub fn for_budget(max_bytes: usize, variables: usize, steps: usize) -> Retention {
+        let row_bytes = (variables + 1) * size_of::<f64>();
+        let rows = (max_bytes / row_bytes).max(1);
+        match steps.div_ceil(rows) {
+            0 | 1 => Retention::All,
+            n => Retention::EveryNth(n),
+        }
+    }
+}
+
+/// A saved step kept by a recorder.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    step: usize,
+    time: f64,
+    values: Vec<f64>,
+}
+
+/// Stores the saved steps of a run, subject to a [`Retention`].
+pub struct ResultRecorder {
+    names: Vec<String>,
+    retention: Retention,
+    rows: Vec<Row>,
+    steps: usize,
+    latest: Option<Row>,
+    /// The running sum of the values of the current [`Retention::Mean`] run.
+    sums: Option<Row>,
+    rng: Option<StdRng>,
+    sink: Option<Box<dyn SaveStepSink>>,
+}
+
+impl ResultRecorder {
+    /// Creates a recorder for steps whose values are given in `names` order.
+    pub fn new(names: &[&str], retention: Retention) -> Self {
+        let rng = match retention {
+            Retention::Reservoir { seed, .. } => Some(StdRng::seed_from_u64(seed)),
+            _ => None,
+        };
+        ResultRecorder {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            retention,
+            rows: Vec::new(),
+            steps: 0,
+            latest: None,
+            sums: None,
+            rng,
+            sink: None,
+        }
+    }
+
+    /// Passes every step on to `sink` as well as recording it.
+    pub fn with_sink(mut self, sink: impl SaveStepSink + 'static) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    /// The sink steps are passed on to, if any.
+    pub fn sink_mut(&mut self) -> Option<&mut (dyn SaveStepSink + 'static)> {
+        self.sink.as_deref_mut()
+    }
+
+    /// The time and values of the most recent step, whether or not it was
+    /// kept.
+    pub fn latest(&self) -> Option<(f64, &[f64])> {
+        self.latest
+            .as_ref()
+            .map(|row| (row.time, row.values.as_slice()))
+    }
+
+    /// The number of steps saved so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The number of steps kept so far.
+    pub fn retained(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The kept steps, in time order.
+    pub fn data(&self) -> ExportData {
+        let mut rows: Vec<&Row> = self.rows.iter().collect();
+        rows.sort_by_key(|row| row.step);
+        let mut data = ExportData::new(rows.iter().map(|row| row.time).collect())
+            .with_steps(rows.iter().map(|row| row.step).collect());
+        for (index, name) in self.names.iter().enumerate() {
+            let values = rows
+                .iter()
+                .map(|row| row.values.get(index).copied().unwrap_or(f64::NAN))
+                .collect();
+            data = data.with_series(name, values);
+        }
+        data
+    }
+
+    pub fn into_data(self) -> ExportData {
+        self.data()
+    }
+
+    fn keep(&mut self, row: Row) {
+        match self.retention {
+            Retention::All => self.rows.push(row),
+            Retention::EveryNth(n) => {
+                if row.step.is_multiple_of(n.max(1)) {
+                    self.rows.push(row);
+                }
+            }
+            Retention::Reservoir { capacity, .. } => {
+                if self.rows.len() < capacity {
+                    self.rows.push(row);
+                } else if let Some(rng) = &mut self.rng {
+                    let slot = rng.gen_range(0..=row.step);
+                    if slot < capacity {
+                        self.rows[slot] = row;
+                    }
+                }
+            }
+            Retention::Mean(n) => {
+                let sums = self.sums.get_or_insert_with(|| Row {
+                    step: row.step,
+                    time: row.time,
+                    values: vec![0.0; row.values.len()],
+                });
+                for (sum, value) in sums.values.iter_mut().zip(&row.values) {
+                    *sum += value;
+                }
+                if row.step + 1 - sums.step >= n.max(1) {
+                    self.push_mean(row.step + 1);
+                }
+            }
+        }
+    }
+
+    /// Completes the current [`Retention::Mean`] run, which ends before
+    /// step `end`.
+    fn push_mean(&mut self, end: usize) {
+        if let Some(mut sums) = self.sums.take() {
+            let count = (end - sums.step) as f64;
+            for sum in &mut sums.values {
+                *sum /= count;
+            }
+            self.rows.push(sums);
+        }
+    }
+}
+
+impl SaveStepSink for ResultRecorder {
+    fn save_step(&mut self, time: f64, values: &[f64]) -> Result<(), ExportError> {
+        if let Some(sink) = &mut self.sink {
+            sink.save_step(time, values)?;
+        }
+        let row = Row {
+            step: self.steps,
+            time,
+            values: values.to_vec(),
+        };
+        self.steps += 1;
+        self.latest = Some(row.clone());
+        self.keep(row);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        match self.retention {
+            Retention::EveryNth(_) => {
+                if let Some(latest) = &self.latest
+                    && self.rows.last().is_none_or(|row| row.step != latest.step)
+                {
+                    self.rows.push(latest.clone());
+                }
+            }
+            Retention::Mean(_) => self.push_mean(self.steps),
+            Retention::All | Retention::Reservoir { .. } => {}
+        }
+        crate::trace::event!(
+            debug,
+            steps = self.steps,
+            retained = self.rows.len(),
+            "run.finish"
+        );
+        match &mut self.sink {
+            Some(sink) => sink.finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ResultRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResultRecorder")
+            .field("names", &self.names)
+            .field("retention", &self.retention)
+            .field("steps", &self.steps)
+            .field("retained", &self.rows.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn record(retention: Retention, steps: usize) -> ResultRecorder {
+        let mut recorder = ResultRecorder::new(&["a"], retention);
+        for step in 0..steps {
+            recorder.save_step(step as f64, &[step as f64]).unwrap();
+        }
+        recorder.finish().unwrap();
+        recorder
+    }
+
+    #[test]
+    fn test_retention_policies() {
+        let data = record(Retention::EveryNth(4), 10).into_data();
+        assert_eq!(data.times, [0.0, 4.0, 8.0, 9.0]);
+        assert_eq!(data.steps, [0, 4, 8, 9]);
+
+        let data = record(Retention::Mean(4), 10).into_data();
+        assert_eq!(data.times, [0.0, 4.0, 8.0]);
+        assert_eq!(data.series("a").unwrap(), [1.5, 5.5, 8.5]);
+
+        let recorder = record(
+            Retention::Reservoir {
+                capacity: 5,
+                seed: 7,
+            },
+            1000,
+        );
+        assert_eq!(recorder.retained(), 5);
+        assert_eq!(recorder.steps(), 1000);
+        let times = recorder.data().times;
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_budget_and_sink() {
+        assert_eq!(Retention::for_budget(1600, 1, 100), Retention::All);
+        assert_eq!(
+            Retention::for_budget(1600, 1, 1000),
+            Retention::EveryNth(10)
+        );
+
+        struct Count(Rc<Cell<usize>>);
+
+        impl SaveStepSink for Count {
+            fn save_step(&mut self, _time: f64, _values: &[f64]) -> Result<(), ExportError> {
+                self.0.set(self.0.get() + 1);
+                Ok(())
+            }
+
+            fn finish(&mut self) -> Result<(), ExportError> {
+                Ok(())
+            }
+        }
+
+        let count = Rc::new(Cell::new(0));
+        let mut recorder = ResultRecorder::new(&["a"], Retention::EveryNth(10))
+            .with_sink(Count(Rc::clone(&count)));
+        for step in 0..20 {
+            recorder.save_step(step as f64, &[1.0]).unwrap();
+        }
+        recorder.finish().unwrap();
+        assert_eq!(count.get(), 20);
+        assert_eq!(recorder.retained(), 3);
+    }
+}
@@ -0,0 +1,301 @@
+//! Streaming CSV export for long simulation runs.
+//!
+//! [`CsvStreamWriter`] receives each saved step through [`SaveStepSink`] as
+//! the run progresses, so memory stays flat no matter how many steps are
+//! saved. Rows are buffered and written out according to a [`FlushPolicy`];
+//! with [`FlushPolicy::Manual`] the caller decides when to flush, e.g. when
+//! the destination is ready for more data.
+
+use std::io::Write;
+
+use crate::model::format::NumberFormat;
+
+use super::export::{
+    ExportError, ExportInterval, ExportOrientation, ExportSettings, IntervalCursor, csv_field,
+    csv_value, find_series,
+};
+
+/// Receives each saved step of a simulation run as it is computed.
+pub trait SaveStepSink {
+    /// Handles the values saved at `time`, in the order the variables were
+    /// named when the sink was created.
+    fn save_step(&mut self, time: f64, values: &[f64]) -> Result<(), ExportError>;
+
+    /// Completes the output once the run has finished.
+    fn finish(&mut self) -> Result<(), ExportError>;
+}
+
+/// When buffered rows are written to the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Flush once this many rows are buffered.
+    Rows(usize),
+    /// Flush once this many bytes are buffered.
+    Bytes(usize),
+    /// Only flush when [`CsvStreamWriter::flush`] or `finish` is called.
+    Manual,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        FlushPolicy::Rows(1024)
+    }
+}
+
+/// Writes a vertical CSV export one saved step at a time.
+#[derive(Debug)]
+pub struct CsvStreamWriter<W: Write> {
+    out: W,
+    policy: FlushPolicy,
+    interval: ExportInterval,
+    cursor: IntervalCursor,
+    /// The index into each step's values and the format of each column.
+    columns: Vec<(usize, Option<NumberFormat>)>,
+    buffer: String,
+    pending_rows: usize,
+    /// The most recent row, kept back for exports written only once.
+    last_row: Option<String>,
+    rows_written: usize,
+    finished: bool,
+}
+
+impl<W: Write> CsvStreamWriter<W> {
+    /// Creates a writer for steps whose values are given in `names` order.
+    ///
+    /// The settings choose the exported columns, their formats and the
+    /// interval. Horizontal exports need every step before the first row can
+    /// be written, so they cannot be streamed.
+    pub fn new(settings: &ExportSettings, names: &[&str], out: W) -> Result<Self, ExportError> {
+        if settings.orientation == ExportOrientation::Horizontal {
+            return Err(ExportError::HorizontalStream);
+        }
+
+        let columns: Vec<(usize, &str, Option<NumberFormat>)> = match &settings.columns {
+            Some(columns) => columns
+                .iter()
+                .filter_map(|column| {
+                    let index = find_series(names.iter().copied(), &column.name)?;
+                    Some((index, column.name.as_str(), column.format.clone()))
+                })
+                .collect(),
+            None => names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (index, *name, None))
+                .collect(),
+        };
+
+        let header: Vec<String> = std::iter::once("Time")
+            .chain(columns.iter().map(|(_, name, _)| *name))
+            .map(csv_field)
+            .collect();
+        let mut buffer = header.join(",");
+        buffer.push('\n');
+
+        Ok(CsvStreamWriter {
+            out,
+            policy: FlushPolicy::default(),
+            interval: settings.interval,
+            cursor: IntervalCursor::new(settings.interval),
+            columns: columns
+                .into_iter()
+                .map(|(index, _, format)| (index, format))
+                .collect(),
+            buffer,
+            pending_rows: 0,
+            last_row: None,
+            rows_written: 0,
+            finished: false,
+        })
+    }
+
+    /// Sets when buffered rows are written to the destination.
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the number of rows buffered but not yet written.
+    pub fn pending_rows(&self) -> usize {
+        self.pending_rows
+    }
+
+    /// Returns the number of bytes buffered but not yet written.
+    pub fn pending_bytes(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of rows written to the destination so far.
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+
+    /// Writes all buffered rows to the destination and flushes it.
+    pub fn flush(&mut self) -> Result<(), ExportError> {
+        if !self.buffer.is_empty() {
+            self.out.write_all(self.buffer.as_bytes())?;
+            self.buffer.clear();
+            self.rows_written += self.pending_rows;
+            self.pending_rows = 0;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Finishes the export and returns the destination.
+    pub fn into_inner(mut self) -> Result<W, ExportError> {
+        self.finish()?;
+        Ok(self.out)
+    }
+
+    fn record(&self, time: f64, values: &[f64]) -> String {
+        let mut record = time.to_string();
+        for (index, format) in &self.columns {
+            record.push(',');
+            if let Some(value) = values.get(*index) {
+                record.push_str(&csv_value(*value, format.as_ref()));
+            }
+        }
+        record.push('\n');
+        record
+    }
+
+    fn should_flush(&self) -> bool {
+        match self.policy {
+            FlushPolicy::Rows(rows) => self.pending_rows >= rows.max(1),
+            FlushPolicy::Bytes(bytes) => self.buffer.len() >= bytes,
+            FlushPolicy::Manual => false,
+        }
+    }
+}
+
+impl<W: Write> SaveStepSink for CsvStreamWriter<W> {
+    fn save_step(&mut self, time: f64, values: &[f64]) -> Result<(), ExportError> {
+        if self.finished {
+            return Err(ExportError::StreamFinished);
+        }
+        if !self.cursor.accept(time) {
+            return Ok(());
+        }
+
+        let record = self.record(time, values);
+        if self.interval == ExportInterval::Once {
+            self.last_row = Some(record);
+            return Ok(());
+        }
+        self.buffer.push_str(&record);
+        self.pending_rows += 1;
+        if self.should_flush() {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        if self.finished {
+            return Ok(());
+        }
+        if let Some(record) = self.last_row.take() {
+            self.buffer.push_str(&record);
+            self.pending_rows += 1;
+        }
+        self.finished = true;
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::export::ExportColumn;
+
+    fn settings(interval: ExportInterval) -> ExportSettings {
+        ExportSettings {
+            orientation: ExportOrientation::Vertical,
+            interval,
+            columns: None,
+        }
+    }
+
+    fn run<W: Write>(writer: &mut CsvStreamWriter<W>, steps: usize) {
+        for step in 0..steps {
+            let time = step as f64 * 0.5;
+            writer.save_step(time, &[time * 2.0, 1.0]).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_stream_matches_interval() {
+        let mut writer = CsvStreamWriter::new(
+            &settings(ExportInterval::Every(1.0)),
+            &["a", "b"],
+            Vec::new(),
+        )
+        .unwrap();
+        run(&mut writer, 5);
+        assert_eq!(writer.rows_written(), 3);
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "Time,a,b\n0,0,1\n1,2,1\n2,4,1\n");
+
+        let mut writer =
+            CsvStreamWriter::new(&settings(ExportInterval::Once), &["a", "b"], Vec::new()).unwrap();
+        run(&mut writer, 5);
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "Time,a,b\n2,4,1\n");
+    }
+
+    #[test]
+    fn test_stream_columns_and_formats() {
+        let mut settings = settings(ExportInterval::EveryDt);
+        settings.columns = Some(vec![ExportColumn {
+            name: "B".to_string(),
+            format: Some(NumberFormat {
+                precision: Some(0.1),
+                ..NumberFormat::default()
+            }),
+        }]);
+        let mut writer = CsvStreamWriter::new(&settings, &["a", "b"], Vec::new()).unwrap();
+        run(&mut writer, 2);
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "Time,B\n0,1.0\n0.5,1.0\n");
+    }
+
+    #[test]
+    fn test_flush_policy() {
+        let mut writer =
+            CsvStreamWriter::new(&settings(ExportInterval::EveryDt), &["a", "b"], Vec::new())
+                .unwrap()
+                .with_flush_policy(FlushPolicy::Rows(2));
+        writer.save_step(0.0, &[0.0, 0.0]).unwrap();
+        assert_eq!(writer.pending_rows(), 1);
+        writer.save_step(1.0, &[0.0, 0.0]).unwrap();
+        assert_eq!(writer.pending_rows(), 0);
+        assert_eq!(writer.rows_written(), 2);
+
+        let mut writer = writer.with_flush_policy(FlushPolicy::Manual);
+        for step in 2..10 {
+            writer.save_step(step as f64, &[0.0, 0.0]).unwrap();
+        }
+        assert_eq!(writer.pending_rows(), 8);
+        assert!(writer.pending_bytes() > 0);
+        writer.flush().unwrap();
+        assert_eq!(writer.rows_written(), 10);
+
+        writer.finish().unwrap();
+        assert!(matches!(
+            writer.save_step(11.0, &[0.0, 0.0]),
+            Err(ExportError::StreamFinished)
+        ));
+    }
+
+    #[test]
+    fn test_horizontal_cannot_stream() {
+        let mut settings = settings(ExportInterval::EveryDt);
+        settings.orientation = ExportOrientation::Horizontal;
+        assert!(matches!(
+            CsvStreamWriter::new(&settings, &["a"], Vec::new()),
+            Err(ExportError::HorizontalStream)
+        ));
+    }
+}
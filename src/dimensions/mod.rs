@@ -22,49 +22,23 @@ pub struct Dimensions {
 
 impl Validate for Dimensions {
     fn validate(&self) -> ValidationResult<(), String, String> {
-        let mut warnings = Vec::new();
-        let mut errors = Vec::new();
         let mut dim_names = std::collections::HashSet::new();
 
-        for (idx, dim) in self.dims.iter().enumerate() {
-            if !dim_names.insert(&dim.name) {
-                errors.push(format!("Duplicate dimension name found: {}", dim.name));
-            }
-
-            match dim.validate() {
-                ValidationResult::Valid(_) => {}
-                ValidationResult::Warnings(_, ws) => {
-                    let mut warning = format!("Warnings in dimension at index {}\n", idx);
-                    ws.iter().for_each(|w| {
-                        warning.push_str(&format!(" - {}\n", w));
-                    });
-                    warnings.push(warning);
-                }
-                ValidationResult::Invalid(ws, es) => {
-                    let mut error = format!("Errors in dimension at index {}\n", idx);
-                    es.iter().for_each(|e| {
-                        error.push_str(&format!(" - {}\n", e));
-                    });
-                    errors.push(error);
-
-                    let mut warning = format!("Warnings in dimension at index {}\n", idx);
-                    ws.iter().for_each(|w| {
-                        warning.push_str(&format!(" - {}\n", w));
-                    });
-                    warnings.push(warning);
-                }
-            }
-        }
-
-        if !errors.is_empty() {
-            return ValidationResult::Invalid(warnings, errors);
-        }
-
-        if !warnings.is_empty() {
-            return ValidationResult::Warnings((), warnings);
-        }
-
-        ValidationResult::Valid(())
+        self.dims
+            .iter()
+            .map(|dim| {
+                let unique = if dim_names.insert(&dim.name) {
+                    ValidationResult::Valid(())
+                } else {
+                    ValidationResult::Invalid(
+                        Vec::new(),
+                        vec![format!("Duplicate dimension name found: {}", dim.name)],
+                    )
+                };
+                let context = |message: String| format!("Dimension '{}': {}", dim.name, message);
+                unique.merge(dim.validate().map_warnings(context).map_errors(context))
+            })
+            .collect()
     }
 }
 
@@ -74,7 +48,7 @@ pub struct Dimension {
     #[serde(rename = "@name")]
     pub name: String,
     /// The size of the dimension (if elements are not named).
-    #[serde(rename = "@size")]
+    #[serde(rename = "@size", skip_serializing_if = "Option::is_none")]
     pub size: Option<usize>,
     /// A list of element names for the dimension (if named).
     #[serde(rename = "elem", default)]
@@ -0,0 +1,293 @@
+//! Double-double arithmetic for checking sensitivity to rounding error.
+//!
+//! A [`DoubleDouble`] represents a number as the unevaluated sum of two
+//! `f64`s, giving about 32 significant decimal digits. Evaluating an equation
+//! over it as well as over `f64` shows how much of the `f64` result is
+//! rounding error, which matters for models that add small flows to large
+//! stocks, such as interest on balances in financial models.
+//!
+//! Addition, subtraction, multiplication, division, square roots, integer
+//! powers, `INT` and `MOD` are carried out at full double-double precision.
+//! Other functions are computed in `f64` from the leading part, so their
+//! results are only as precise as an `f64`.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! let (_, balance) = xmile::equation::parse::expression("(1e16 + 1) - 1e16").unwrap();
+//! assert_eq!(balance.rounding_error(&HashMap::new()), Some(1.0));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::evaluate::Scalar;
+use super::{Expression, Identifier};
+
+/// A number held as the sum of a leading `f64` and a much smaller trailing
+/// `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    pub fn new(value: f64) -> Self {
+        DoubleDouble { hi: value, lo: 0.0 }
+    }
+
+    /// The nearest `f64` to the number.
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    /// Normalizes `hi + lo` where `|hi| >= |lo|`.
+    fn quick_two_sum(hi: f64, lo: f64) -> Self {
+        let sum = hi + lo;
+        DoubleDouble {
+            hi: sum,
+            lo: lo - (sum - hi),
+        }
+    }
+
+    /// Adds two `f64`s exactly.
+    fn two_sum(a: f64, b: f64) -> (f64, f64) {
+        let sum = a + b;
+        let b_part = sum - a;
+        (sum, (a - (sum - b_part)) + (b - b_part))
+    }
+
+    /// Multiplies two `f64`s exactly.
+    fn two_prod(a: f64, b: f64) -> (f64, f64) {
+        let product = a * b;
+        (product, a.mul_add(b, -product))
+    }
+
+    /// Raises the number to an integer power by repeated squaring.
+    fn powi(self, exponent: i64) -> Self {
+        let mut result = DoubleDouble::new(1.0);
+        let mut base = self;
+        let mut n = exponent.unsigned_abs();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            n >>= 1;
+        }
+        if exponent < 0 {
+            DoubleDouble::new(1.0) / result
+        } else {
+            result
+        }
+    }
+
+    /// Applies an `f64` function to the leading part.
+    fn approximate(self, f: fn(f64) -> f64) -> Self {
+        DoubleDouble::new(f(self.hi))
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        DoubleDouble::new(value)
+    }
+}
+
+impl fmt::Display for DoubleDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.lo == 0.0 {
+            write!(f, "{}", self.hi)
+        } else {
+            write!(f, "{} {:+e}", self.hi, self.lo)
+        }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, rhs: DoubleDouble) -> DoubleDouble {
+        let (hi, hi_error) = DoubleDouble::two_sum(self.hi, rhs.hi);
+        let (lo, lo_error) = DoubleDouble::two_sum(self.lo, rhs.lo);
+        let partial = DoubleDouble::quick_two_sum(hi, hi_error + lo);
+        DoubleDouble::quick_two_sum(partial.hi, partial.lo + lo_error)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn sub(self, rhs: DoubleDouble) -> DoubleDouble {
+        self + -rhs
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, rhs: DoubleDouble) -> DoubleDouble {
+        let (product, error) = DoubleDouble::two_prod(self.hi, rhs.hi);
+        DoubleDouble::quick_two_sum(product, error + self.hi * rhs.lo + self.lo * rhs.hi)
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn div(self, rhs: DoubleDouble) -> DoubleDouble {
+        let first = self.hi / rhs.hi;
+        let remainder = self - rhs * DoubleDouble::new(first);
+        let second = remainder.hi / rhs.hi;
+        let remainder = remainder - rhs * DoubleDouble::new(second);
+        let third = remainder.hi / rhs.hi;
+        DoubleDouble::quick_two_sum(first, second) + DoubleDouble::new(third)
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn neg(self) -> DoubleDouble {
+        DoubleDouble {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Scalar for DoubleDouble {
+    fn constant(value: f64) -> Self {
+        DoubleDouble::new(value)
+    }
+
+    fn value(self) -> f64 {
+        self.to_f64()
+    }
+
+    fn powf(self, exponent: Self) -> Self {
+        let whole = exponent.to_f64();
+        if exponent.lo == 0.0 && whole.fract() == 0.0 && whole.abs() <= 1024.0 {
+            self.powi(whole as i64)
+        } else {
+            DoubleDouble::new(self.hi.powf(exponent.hi))
+        }
+    }
+
+    fn rem_euclid(self, divisor: Self) -> Self {
+        let divisor = divisor.abs();
+        self - divisor * (self / divisor).floor()
+    }
+
+    fn abs(self) -> Self {
+        if self.hi < 0.0 { -self } else { self }
+    }
+
+    fn floor(self) -> Self {
+        let hi = self.hi.floor();
+        if hi == self.hi {
+            DoubleDouble::quick_two_sum(hi, self.lo.floor())
+        } else {
+            DoubleDouble::new(hi)
+        }
+    }
+
+    fn sqrt(self) -> Self {
+        if self.hi <= 0.0 {
+            return self.approximate(f64::sqrt);
+        }
+        // One Newton step from the f64 root doubles its precision.
+        let root = DoubleDouble::new(self.hi.sqrt());
+        root + (self - root * root) / (root * DoubleDouble::new(2.0))
+    }
+
+    fn exp(self) -> Self {
+        self.approximate(f64::exp)
+    }
+
+    fn ln(self) -> Self {
+        self.approximate(f64::ln)
+    }
+
+    fn log10(self) -> Self {
+        self.approximate(f64::log10)
+    }
+
+    fn sin(self) -> Self {
+        self.approximate(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.approximate(f64::cos)
+    }
+
+    fn tan(self) -> Self {
+        self.approximate(f64::tan)
+    }
+
+    fn asin(self) -> Self {
+        self.approximate(f64::asin)
+    }
+
+    fn acos(self) -> Self {
+        self.approximate(f64::acos)
+    }
+
+    fn atan(self) -> Self {
+        self.approximate(f64::atan)
+    }
+}
+
+impl Expression {
+    /// Returns how far the `f64` value of the expression is from its
+    /// double-double value, with the variable values in `values`.
+    pub fn rounding_error(&self, values: &HashMap<Identifier, f64>) -> Option<f64> {
+        let single = self.evaluate::<f64>(&|name| values.get(name).copied())?;
+        let double =
+            self.evaluate::<DoubleDouble>(&|name| values.get(name).copied().map(Into::into))?;
+        Some((double - DoubleDouble::new(single)).to_f64().abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic_is_exact_beyond_f64() {
+        let tenth = DoubleDouble::new(1.0) / DoubleDouble::new(10.0);
+        let mut sum = DoubleDouble::default();
+        for _ in 0..10 {
+            sum = sum + tenth;
+        }
+        assert!((sum - DoubleDouble::new(1.0)).to_f64().abs() < 1e-30);
+
+        let two = DoubleDouble::new(2.0);
+        let root = two.sqrt();
+        assert!((root * root - two).to_f64().abs() < 1e-30);
+        assert_eq!(DoubleDouble::new(3.0).powf(DoubleDouble::new(-2.0)), {
+            DoubleDouble::new(1.0) / DoubleDouble::new(9.0)
+        });
+        assert_eq!(
+            DoubleDouble::new(-7.0).rem_euclid(DoubleDouble::new(3.0)),
+            DoubleDouble::new(2.0)
+        );
+    }
+
+    #[test]
+    fn test_rounding_error_of_equations() {
+        let (_, interest) =
+            crate::equation::parse::expression("balance + balance * rate - balance").unwrap();
+        let values = HashMap::from([
+            (Identifier::parse_default("balance").unwrap(), 1e15),
+            (Identifier::parse_default("rate").unwrap(), 1.1e-16),
+        ]);
+        let error = interest.rounding_error(&values).unwrap();
+        assert!(error > 0.01, "{error}");
+
+        let (_, exact) = crate::equation::parse::expression("balance * 2").unwrap();
+        assert_eq!(exact.rounding_error(&values), Some(0.0));
+    }
+}
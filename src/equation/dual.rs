@@ -0,0 +1,272 @@
+//! Dual numbers for forward-mode automatic differentiation.
+//!
+//! A [`Dual`] carries a value and its derivative with respect to one chosen
+//! input. Evaluating an equation over duals, with the chosen input seeded by
+//! [`Dual::variable`] and every other input by [`Dual::constant`], gives its
+//! exact local sensitivity to that input in a single evaluation, with no
+//! step size to tune as with finite differences.
+//! [`Simulator::sensitivity`](crate::simulation::Simulator::sensitivity)
+//! does the same for a whole run.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use xmile::equation::{Expression, Identifier};
+//!
+//! let (_, births) = xmile::equation::parse::expression("population * birth_rate").unwrap();
+//! let values = HashMap::from([
+//!     (Identifier::parse_default("population").unwrap(), 100.0),
+//!     (Identifier::parse_default("birth_rate").unwrap(), 0.02),
+//! ]);
+//! let rate = Identifier::parse_default("birth_rate").unwrap();
+//! assert_eq!(births.derivative(&values, &rate), Some((2.0, 100.0)));
+//! ```
+//!
+//! Comparisons and conditions act on values only, so the derivative of an
+//! `IF` is that of the branch taken, and `INT` has derivative zero.
+
+use std::collections::HashMap;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::evaluate::Scalar;
+use super::{Expression, Identifier};
+
+/// A value and its derivative with respect to one input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub derivative: f64,
+}
+
+impl Dual {
+    pub fn new(value: f64, derivative: f64) -> Self {
+        Dual { value, derivative }
+    }
+
+    /// The input being differentiated with respect to.
+    pub fn variable(value: f64) -> Self {
+        Dual::new(value, 1.0)
+    }
+
+    /// A value that does not depend on the input.
+    pub fn constant(value: f64) -> Self {
+        Dual::new(value, 0.0)
+    }
+
+    /// Applies a function with derivative `slope` at this value.
+    fn chain(self, value: f64, slope: f64) -> Self {
+        Dual::new(value, slope * self.derivative)
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, rhs: Dual) -> Dual {
+        Dual::new(self.value + rhs.value, self.derivative + rhs.derivative)
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, rhs: Dual) -> Dual {
+        Dual::new(self.value - rhs.value, self.derivative - rhs.derivative)
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, rhs: Dual) -> Dual {
+        Dual::new(
+            self.value * rhs.value,
+            self.derivative * rhs.value + self.value * rhs.derivative,
+        )
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, rhs: Dual) -> Dual {
+        Dual::new(
+            self.value / rhs.value,
+            (self.derivative * rhs.value - self.value * rhs.derivative) / (rhs.value * rhs.value),
+        )
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Dual {
+        Dual::new(-self.value, -self.derivative)
+    }
+}
+
+impl Scalar for Dual {
+    fn constant(value: f64) -> Self {
+        Dual::constant(value)
+    }
+
+    fn value(self) -> f64 {
+        self.value
+    }
+
+    fn powf(self, exponent: Self) -> Self {
+        let value = self.value.powf(exponent.value);
+        let mut derivative = 0.0;
+        if self.derivative != 0.0 {
+            derivative += exponent.value * self.value.powf(exponent.value - 1.0) * self.derivative;
+        }
+        // Only a varying exponent needs the logarithm, which is undefined
+        // for negative bases.
+        if exponent.derivative != 0.0 {
+            derivative += value * self.value.ln() * exponent.derivative;
+        }
+        Dual::new(value, derivative)
+    }
+
+    fn rem_euclid(self, divisor: Self) -> Self {
+        let quotient = (self.value / divisor.value).floor();
+        Dual::new(
+            self.value.rem_euclid(divisor.value),
+            self.derivative - quotient * divisor.derivative,
+        )
+    }
+
+    fn abs(self) -> Self {
+        self.chain(self.value.abs(), self.value.signum())
+    }
+
+    fn floor(self) -> Self {
+        Dual::constant(self.value.floor())
+    }
+
+    fn sqrt(self) -> Self {
+        let root = self.value.sqrt();
+        self.chain(root, 0.5 / root)
+    }
+
+    fn exp(self) -> Self {
+        let exp = self.value.exp();
+        self.chain(exp, exp)
+    }
+
+    fn ln(self) -> Self {
+        self.chain(self.value.ln(), 1.0 / self.value)
+    }
+
+    fn log10(self) -> Self {
+        self.chain(
+            self.value.log10(),
+            1.0 / (self.value * std::f64::consts::LN_10),
+        )
+    }
+
+    fn sin(self) -> Self {
+        self.chain(self.value.sin(), self.value.cos())
+    }
+
+    fn cos(self) -> Self {
+        self.chain(self.value.cos(), -self.value.sin())
+    }
+
+    fn tan(self) -> Self {
+        let cos = self.value.cos();
+        self.chain(self.value.tan(), 1.0 / (cos * cos))
+    }
+
+    fn asin(self) -> Self {
+        self.chain(
+            self.value.asin(),
+            1.0 / (1.0 - self.value * self.value).sqrt(),
+        )
+    }
+
+    fn acos(self) -> Self {
+        self.chain(
+            self.value.acos(),
+            -1.0 / (1.0 - self.value * self.value).sqrt(),
+        )
+    }
+
+    fn atan(self) -> Self {
+        self.chain(self.value.atan(), 1.0 / (1.0 + self.value * self.value))
+    }
+}
+
+impl Expression {
+    /// Evaluates the expression and its derivative with respect to
+    /// `parameter`, returning `(value, derivative)`.
+    ///
+    /// `values` gives the value of every variable the expression refers to;
+    /// a parameter the expression does not use has derivative zero.
+    pub fn derivative(
+        &self,
+        values: &HashMap<Identifier, f64>,
+        parameter: &Identifier,
+    ) -> Option<(f64, f64)> {
+        let lookup = |name: &Identifier| {
+            let value = *values.get(name)?;
+            Some(if name == parameter {
+                Dual::variable(value)
+            } else {
+                Dual::constant(value)
+            })
+        };
+        let result = self.evaluate(&lookup)?;
+        Some((result.value, result.derivative))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::parse_expression as parse;
+
+    #[test]
+    fn test_derivatives_match_finite_differences() {
+        let x = Identifier::parse_default("x").unwrap();
+        let equations = [
+            "x * x - 3 * x",
+            "x ^ 3 / (1 + x)",
+            "2 ^ x",
+            "x ^ x",
+            "EXP(-x) * SIN(x) + COS(x)",
+            "LN(x) + LOG10(x) + SQRT(x)",
+            "ARCTAN(x) + ARCSIN(x / 10) + ARCCOS(x / 10) + TAN(x / 10)",
+            "ABS(1 - x) + MAX(x, 2) + MIN(x, 1) + x MOD 1",
+            "IF x > 1 THEN x * x ELSE x",
+        ];
+        let h = 1e-6;
+        for equation in equations {
+            let expression = parse(equation);
+            let at = |value: f64| {
+                let values = HashMap::from([(x.clone(), value)]);
+                expression.evaluate::<f64>(&|name| values.get(name).copied())
+            };
+            let values = HashMap::from([(x.clone(), 1.7)]);
+            let (value, derivative) = expression.derivative(&values, &x).unwrap();
+            let estimate = (at(1.7 + h).unwrap() - at(1.7 - h).unwrap()) / (2.0 * h);
+            assert_eq!(Some(value), at(1.7), "{equation}");
+            assert!(
+                (derivative - estimate).abs() < 1e-5 * estimate.abs().max(1.0),
+                "{equation}: {derivative} vs {estimate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unused_and_unknown_inputs() {
+        let x = Identifier::parse_default("x").unwrap();
+        let y = Identifier::parse_default("y").unwrap();
+        let values = HashMap::from([(x.clone(), 2.0), (y.clone(), 3.0)]);
+        assert_eq!(parse("x * 4").derivative(&values, &y), Some((8.0, 0.0)));
+        assert_eq!(
+            parse("INT(x * y)").derivative(&values, &x),
+            Some((6.0, 0.0))
+        );
+        assert_eq!(parse("x * z").derivative(&values, &x), None);
+    }
+}
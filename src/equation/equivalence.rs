@@ -0,0 +1,440 @@
+//! Checks whether two equations compute the same thing.
+//!
+//! Equations are compared after normalization: parentheses and unary plus
+//! are dropped, constant subexpressions are folded, subtraction becomes
+//! addition of a negation, sums and products are flattened with their
+//! operands put in a canonical order, and `>`/`>=` become `<`/`<=` with
+//! their operands swapped. Identifiers compare under XMILE's rules, so
+//! `Birth_Rate` matches `birth rate`. Normalization does not apply
+//! distributivity, so `a * (b + c)` and `a * b + a * c` are only found
+//! equivalent by numeric testing.
+//!
+//! Numeric testing evaluates both equations with random values for the
+//! variables they refer to. It can find equivalences that normalization
+//! misses, but only probabilistically: equations that agree at every sample
+//! may still differ elsewhere.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::expression::function::FunctionTarget;
+use super::{Expression, Identifier, NumericConstant};
+
+/// Settings for [`Expression::is_equivalent_with`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquivalenceOptions {
+    /// The number of random samples to compare at when normalization does
+    /// not settle the question. Zero disables numeric testing.
+    pub samples: usize,
+    pub seed: u64,
+    /// The range values are drawn from. The default range is positive, so
+    /// square roots and logarithms of variables are defined.
+    pub range: (f64, f64),
+    /// The largest relative difference between results treated as equal.
+    pub tolerance: f64,
+}
+
+impl Default for EquivalenceOptions {
+    fn default() -> Self {
+        EquivalenceOptions {
+            samples: 32,
+            seed: 0,
+            range: (0.1, 10.0),
+            tolerance: 1e-9,
+        }
+    }
+}
+
+impl Expression {
+    /// Returns true if `self` and `other` are the same after normalization.
+    pub fn is_equivalent(&self, other: &Expression) -> bool {
+        self.normalized() == other.normalized()
+    }
+
+    /// Returns true if `self` and `other` are the same after normalization
+    /// or, failing that, agree at every sample of a numeric test.
+    ///
+    /// Numeric testing needs both equations to be evaluable, so it only
+    /// applies to equations made of arithmetic, comparisons, logic,
+    /// `IF`-`THEN`-`ELSE` and pure builtins such as `EXP` and `MIN`.
+    pub fn is_equivalent_with(&self, other: &Expression, options: &EquivalenceOptions) -> bool {
+        let (a, b) = (self.normalized(), other.normalized());
+        a == b || (options.samples > 0 && agree_numerically(&a, &b, options))
+    }
+
+    /// Returns the normal form of this expression used to compare equations.
+    pub fn normalized(&self) -> Expression {
+        normalize(self)
+    }
+}
+
+fn normalize(expression: &Expression) -> Expression {
+    use Expression as E;
+
+    let boxed = |e: &Expression| Box::new(normalize(e));
+    let normalized = match expression {
+        E::Parentheses(inner) | E::UnaryPlus(inner) => return normalize(inner),
+        E::UnaryMinus(inner) => match normalize(inner) {
+            E::UnaryMinus(inner) => *inner,
+            inner => E::UnaryMinus(Box::new(inner)),
+        },
+        E::Add(..) | E::Subtract(..) => {
+            let mut terms = Vec::new();
+            collect_terms(expression, false, &mut terms);
+            return combine(terms, E::Add, 0.0, |a, b| a + b);
+        }
+        E::Multiply(..) => {
+            let mut factors = Vec::new();
+            collect_factors(expression, &mut factors);
+            return combine(factors, E::Multiply, 1.0, |a, b| a * b);
+        }
+        E::GreaterThan(lhs, rhs) => E::LessThan(boxed(rhs), boxed(lhs)),
+        E::GreaterThanOrEq(lhs, rhs) => E::LessThanOrEq(boxed(rhs), boxed(lhs)),
+        E::Equal(lhs, rhs) => commutative(E::Equal, lhs, rhs),
+        E::NotEqual(lhs, rhs) => commutative(E::NotEqual, lhs, rhs),
+        E::And(lhs, rhs) => commutative(E::And, lhs, rhs),
+        E::Or(lhs, rhs) => commutative(E::Or, lhs, rhs),
+        E::Not(inner) => E::Not(boxed(inner)),
+        E::Transpose(inner) => E::Transpose(boxed(inner)),
+        E::Range(start, end) => E::Range(boxed(start), boxed(end)),
+        E::Exponentiation(lhs, rhs) => E::Exponentiation(boxed(lhs), boxed(rhs)),
+        E::Divide(lhs, rhs) => E::Divide(boxed(lhs), boxed(rhs)),
+        E::Modulo(lhs, rhs) => E::Modulo(boxed(lhs), boxed(rhs)),
+        E::LessThan(lhs, rhs) => E::LessThan(boxed(lhs), boxed(rhs)),
+        E::LessThanOrEq(lhs, rhs) => E::LessThanOrEq(boxed(lhs), boxed(rhs)),
+        E::IfElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => E::IfElse {
+            condition: boxed(condition),
+            then_branch: boxed(then_branch),
+            else_branch: boxed(else_branch),
+        },
+        E::Subscript(name, indices) => {
+            E::Subscript(name.clone(), indices.iter().map(normalize).collect())
+        }
+        E::FunctionCall { target, parameters } => E::FunctionCall {
+            target: target.clone(),
+            parameters: parameters.iter().map(normalize).collect(),
+        },
+        E::Constant(_) | E::Wildcard | E::InlineComment(_) => expression.clone(),
+    };
+    fold(normalized)
+}
+
+/// Collects the terms of a sum, negating those that are subtracted.
+fn collect_terms(expression: &Expression, negate: bool, terms: &mut Vec<Expression>) {
+    match expression {
+        Expression::Parentheses(inner) | Expression::UnaryPlus(inner) => {
+            collect_terms(inner, negate, terms)
+        }
+        Expression::UnaryMinus(inner) => collect_terms(inner, !negate, terms),
+        Expression::Add(lhs, rhs) => {
+            collect_terms(lhs, negate, terms);
+            collect_terms(rhs, negate, terms);
+        }
+        Expression::Subtract(lhs, rhs) => {
+            collect_terms(lhs, negate, terms);
+            collect_terms(rhs, !negate, terms);
+        }
+        _ => {
+            let term = normalize(expression);
+            terms.push(if negate { negate_term(term) } else { term });
+        }
+    }
+}
+
+fn negate_term(term: Expression) -> Expression {
+    match term {
+        Expression::Constant(NumericConstant(value)) => {
+            Expression::Constant(NumericConstant(-value))
+        }
+        Expression::UnaryMinus(inner) => *inner,
+        term => Expression::UnaryMinus(Box::new(term)),
+    }
+}
+
+fn collect_factors(expression: &Expression, factors: &mut Vec<Expression>) {
+    match expression {
+        Expression::Parentheses(inner) | Expression::UnaryPlus(inner) => {
+            collect_factors(inner, factors)
+        }
+        Expression::Multiply(lhs, rhs) => {
+            collect_factors(lhs, factors);
+            collect_factors(rhs, factors);
+        }
+        _ => factors.push(normalize(expression)),
+    }
+}
+
+/// Folds the constant operands of a sum or product into one, dropping it if
+/// it is the identity, and chains the operands in canonical order.
+fn combine(
+    operands: Vec<Expression>,
+    operator: fn(Box<Expression>, Box<Expression>) -> Expression,
+    identity: f64,
+    apply: fn(f64, f64) -> f64,
+) -> Expression {
+    let mut constant = identity;
+    let mut rest = Vec::new();
+    for operand in operands {
+        match operand {
+            Expression::Constant(NumericConstant(value)) => constant = apply(constant, value),
+            operand => rest.push(operand),
+        }
+    }
+    rest.sort_by(order);
+    if constant != identity || rest.is_empty() {
+        rest.push(Expression::Constant(NumericConstant(constant)));
+    }
+    let mut operands = rest.into_iter();
+    let first = operands.next().expect("at least one operand");
+    operands.fold(first, |chain, operand| {
+        operator(Box::new(chain), Box::new(operand))
+    })
+}
+
+fn commutative(
+    operator: fn(Box<Expression>, Box<Expression>) -> Expression,
+    lhs: &Expression,
+    rhs: &Expression,
+) -> Expression {
+    let (lhs, rhs) = (normalize(lhs), normalize(rhs));
+    if order(&lhs, &rhs) == Ordering::Greater {
+        operator(Box::new(rhs), Box::new(lhs))
+    } else {
+        operator(Box::new(lhs), Box::new(rhs))
+    }
+}
+
+/// Replaces an operation on constants with its result.
+fn fold(expression: Expression) -> Expression {
+    let value = match &expression {
+        Expression::UnaryMinus(inner) => constant(inner).map(|value| -value),
+        Expression::Not(inner) => constant(inner).map(|value| truth(value == 0.0)),
+        Expression::Exponentiation(lhs, rhs)
+        | Expression::Divide(lhs, rhs)
+        | Expression::Modulo(lhs, rhs)
+        | Expression::LessThan(lhs, rhs)
+        | Expression::LessThanOrEq(lhs, rhs)
+        | Expression::Equal(lhs, rhs)
+        | Expression::NotEqual(lhs, rhs)
+        | Expression::And(lhs, rhs)
+        | Expression::Or(lhs, rhs) => match (constant(lhs), constant(rhs)) {
+            (Some(_), Some(_)) => evaluate(&expression, &HashMap::new()),
+            _ => None,
+        },
+        Expression::IfElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            return match constant(condition) {
+                Some(value) if value != 0.0 => *then_branch.clone(),
+                Some(_) => *else_branch.clone(),
+                None => expression,
+            };
+        }
+        _ => None,
+    };
+    match value {
+        Some(value) if value.is_finite() => Expression::Constant(NumericConstant(value)),
+        _ => expression,
+    }
+}
+
+fn constant(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Constant(NumericConstant(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+fn truth(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+/// A total order on normalized expressions, used to sort the operands of
+/// commutative operators.
+fn order(a: &Expression, b: &Expression) -> Ordering {
+    use Expression as E;
+
+    let children = |a: &[&Expression], b: &[&Expression]| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| order(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    };
+    match (a, b) {
+        (E::Constant(a), E::Constant(b)) => a.0.total_cmp(&b.0),
+        (E::Subscript(a, a_indices), E::Subscript(b, b_indices)) => a.cmp(b).then_with(|| {
+            children(
+                &a_indices.iter().collect::<Vec<_>>(),
+                &b_indices.iter().collect::<Vec<_>>(),
+            )
+        }),
+        (
+            E::FunctionCall {
+                target: a,
+                parameters: a_parameters,
+            },
+            E::FunctionCall {
+                target: b,
+                parameters: b_parameters,
+            },
+        ) => target_order(a, b).then_with(|| {
+            children(
+                &a_parameters.iter().collect::<Vec<_>>(),
+                &b_parameters.iter().collect::<Vec<_>>(),
+            )
+        }),
+        (E::InlineComment(a), E::InlineComment(b)) => a.cmp(b),
+        _ => rank(a)
+            .cmp(&rank(b))
+            .then_with(|| children(&a.children(), &b.children())),
+    }
+}
+
+fn target_order(a: &FunctionTarget, b: &FunctionTarget) -> Ordering {
+    let split = |target: &FunctionTarget| match target {
+        FunctionTarget::Function(name) => (0, name.clone()),
+        FunctionTarget::GraphicalFunction(name) => (1, name.clone()),
+        FunctionTarget::Model(name) => (2, name.clone()),
+        FunctionTarget::Array(name) => (3, name.clone()),
+    };
+    split(a).cmp(&split(b))
+}
+
+/// The position of an expression's variant in the order of expressions.
+fn rank(expression: &Expression) -> u8 {
+    use Expression as E;
+
+    match expression {
+        E::Constant(_) => 0,
+        E::Subscript(..) => 1,
+        E::FunctionCall { .. } => 2,
+        E::UnaryMinus(_) => 3,
+        E::Not(_) => 4,
+        E::Exponentiation(..) => 5,
+        E::Multiply(..) => 6,
+        E::Divide(..) => 7,
+        E::Modulo(..) => 8,
+        E::Add(..) => 9,
+        E::Subtract(..) => 10,
+        E::LessThan(..) => 11,
+        E::LessThanOrEq(..) => 12,
+        E::GreaterThan(..) => 13,
+        E::GreaterThanOrEq(..) => 14,
+        E::Equal(..) => 15,
+        E::NotEqual(..) => 16,
+        E::And(..) => 17,
+        E::Or(..) => 18,
+        E::IfElse { .. } => 19,
+        E::Parentheses(_) => 20,
+        E::UnaryPlus(_) => 21,
+        E::InlineComment(_) => 22,
+        E::Wildcard => 23,
+        E::Range(..) => 24,
+        E::Transpose(_) => 25,
+    }
+}
+
+/// The operands of an operator or `IF`-`THEN`-`ELSE`.
+/// Compares `a` and `b` at random values of the variables they refer to.
+/// Samples where either is undefined are skipped; at least one sample must
+/// be defined for both.
+fn agree_numerically(a: &Expression, b: &Expression, options: &EquivalenceOptions) -> bool {
+    let mut names = Vec::new();
+    collect_names(a, &mut names);
+    collect_names(b, &mut names);
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let (low, high) = options.range;
+    let mut compared = 0;
+    for _ in 0..options.samples {
+        let values: HashMap<Identifier, f64> = names
+            .iter()
+            .map(|name| (name.clone(), rng.gen_range(low..=high)))
+            .collect();
+        let (Some(x), Some(y)) = (evaluate(a, &values), evaluate(b, &values)) else {
+            continue;
+        };
+        if !x.is_finite() || !y.is_finite() {
+            continue;
+        }
+        let scale = x.abs().max(y.abs()).max(1.0);
+        if (x - y).abs() > options.tolerance * scale {
+            return false;
+        }
+        compared += 1;
+    }
+    compared > 0
+}
+
+fn collect_names(expression: &Expression, names: &mut Vec<Identifier>) {
+    if let Expression::Subscript(name, indices) = expression
+        && indices.is_empty()
+        && !names.contains(name)
+    {
+        names.push(name.clone());
+    }
+    for child in expression.children() {
+        collect_names(child, names);
+    }
+}
+
+/// Evaluates `expression` with the given variable values.
+fn evaluate(expression: &Expression, values: &HashMap<Identifier, f64>) -> Option<f64> {
+    expression.evaluate::<f64>(&|name| values.get(name).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::parse_expression as parse;
+
+    #[test]
+    fn test_normalization() {
+        let equivalent = [
+            ("a + b", "b + a"),
+            ("a - b", "-b + a"),
+            ("(a * b) * c", "c * (b * a)"),
+            ("Birth_Rate * 2", "2 * birth_rate"),
+            ("x + 1 + 2", "3 + x"),
+            ("a > b", "b < a"),
+            ("a = b AND c", "c AND b = a"),
+            ("IF 1 > 0 THEN a ELSE b", "a"),
+            ("-(-a)", "a"),
+        ];
+        for (a, b) in equivalent {
+            assert!(parse(a).is_equivalent(&parse(b)), "{a} vs {b}");
+        }
+        let different = [("a - b", "b - a"), ("a / b", "b / a"), ("a > b", "a < b")];
+        for (a, b) in different {
+            assert!(!parse(a).is_equivalent(&parse(b)), "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn test_numeric_testing() {
+        let options = EquivalenceOptions::default();
+        let (a, b) = (parse("a * (b + c)"), parse("a * b + a * c"));
+        assert!(!a.is_equivalent(&b));
+        assert!(a.is_equivalent_with(&b, &options));
+
+        let (a, b) = (parse("EXP(LN(x))"), parse("x"));
+        assert!(a.is_equivalent_with(&b, &options));
+
+        let (a, b) = (parse("x ^ 2"), parse("2 * x"));
+        assert!(!a.is_equivalent_with(&b, &options));
+
+        // Functions that cannot be evaluated are never equivalent numerically
+        let (a, b) = (parse("SMTH1(x, 1)"), parse("x"));
+        assert!(!a.is_equivalent_with(&b, &options));
+    }
+}
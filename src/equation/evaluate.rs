@@ -0,0 +1,259 @@
+//! Evaluation of scalar equations over any number type.
+//!
+//! [`Expression::evaluate`] computes an equation from the values of the
+//! variables it refers to. It is generic over [`Scalar`], so the same
+//! equation can be evaluated with plain `f64`s or with
+//! [`Dual`](super::dual::Dual) numbers, which carry a derivative alongside
+//! each value.
+//!
+//! Only equations whose result depends on nothing but their inputs can be
+//! evaluated: arrays, graphical functions, submodels and builtins that need
+//! the simulation state, such as `TIME` or delays, evaluate to `None`.
+//! [`Expression::evaluate_with`] also takes a resolver for function calls,
+//! which is how the simulator provides graphical functions and the builtins
+//! that depend on the time.
+
+use std::ops::{Add, Div, Mul, Neg, RangeInclusive, Sub};
+
+use super::expression::function::FunctionTarget;
+use super::{Expression, Identifier};
+
+/// A number type equations can be evaluated over.
+///
+/// Comparisons, conditions and logical operators act on [`Scalar::value`];
+/// their results are constants.
+pub trait Scalar:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// A number that does not vary with any input.
+    fn constant(value: f64) -> Self;
+    /// The plain value of the number.
+    fn value(self) -> f64;
+
+    fn powf(self, exponent: Self) -> Self;
+    fn rem_euclid(self, divisor: Self) -> Self;
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn log10(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan(self) -> Self;
+}
+
+impl Scalar for f64 {
+    fn constant(value: f64) -> Self {
+        value
+    }
+
+    fn value(self) -> f64 {
+        self
+    }
+
+    fn powf(self, exponent: Self) -> Self {
+        f64::powf(self, exponent)
+    }
+
+    fn rem_euclid(self, divisor: Self) -> Self {
+        f64::rem_euclid(self, divisor)
+    }
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+
+    fn floor(self) -> Self {
+        f64::floor(self)
+    }
+
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+
+    fn ln(self) -> Self {
+        f64::ln(self)
+    }
+
+    fn log10(self) -> Self {
+        f64::log10(self)
+    }
+
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        f64::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        f64::tan(self)
+    }
+
+    fn asin(self) -> Self {
+        f64::asin(self)
+    }
+
+    fn acos(self) -> Self {
+        f64::acos(self)
+    }
+
+    fn atan(self) -> Self {
+        f64::atan(self)
+    }
+}
+
+impl Expression {
+    /// Evaluates the expression with the variable values given by `lookup`,
+    /// or returns `None` if it refers to an unknown variable or uses
+    /// something that cannot be evaluated from its inputs alone.
+    pub fn evaluate<S: Scalar>(&self, lookup: &dyn Fn(&Identifier) -> Option<S>) -> Option<S> {
+        self.evaluate_with(lookup, &|_, _| None)
+    }
+
+    /// Evaluates the expression as [`evaluate`](Self::evaluate) does, first
+    /// offering each function call, with its arguments evaluated, to
+    /// `functions`. Calls it returns `None` for are left to the builtins
+    /// whose result depends only on their arguments.
+    pub fn evaluate_with<S: Scalar>(
+        &self,
+        lookup: &dyn Fn(&Identifier) -> Option<S>,
+        functions: &dyn Fn(&Identifier, &[S]) -> Option<S>,
+    ) -> Option<S> {
+        use Expression as E;
+
+        let truth = |value: bool| S::constant(if value { 1.0 } else { 0.0 });
+        let eval = |e: &Expression| e.evaluate_with(lookup, functions);
+        let binary = |lhs: &Expression, rhs: &Expression| Some((eval(lhs)?, eval(rhs)?));
+        let compare = |lhs: &Expression, rhs: &Expression, holds: fn(f64, f64) -> bool| {
+            binary(lhs, rhs).map(|(a, b)| truth(holds(a.value(), b.value())))
+        };
+        match self {
+            E::Constant(constant) => Some(S::constant(constant.0)),
+            E::Subscript(name, indices) if indices.is_empty() => {
+                lookup(name).or_else(|| constant(name).map(S::constant))
+            }
+            E::Subscript(..)
+            | E::Wildcard
+            | E::Range(..)
+            | E::Transpose(_)
+            | E::InlineComment(_) => None,
+            E::Parentheses(inner) | E::UnaryPlus(inner) => eval(inner),
+            E::UnaryMinus(inner) => Some(-eval(inner)?),
+            E::Not(inner) => Some(truth(eval(inner)?.value() == 0.0)),
+            E::Exponentiation(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a.powf(b)),
+            E::Multiply(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a * b),
+            E::Divide(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a / b),
+            E::Modulo(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a.rem_euclid(b)),
+            E::Add(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a + b),
+            E::Subtract(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a - b),
+            E::LessThan(lhs, rhs) => compare(lhs, rhs, |a, b| a < b),
+            E::LessThanOrEq(lhs, rhs) => compare(lhs, rhs, |a, b| a <= b),
+            E::GreaterThan(lhs, rhs) => compare(lhs, rhs, |a, b| a > b),
+            E::GreaterThanOrEq(lhs, rhs) => compare(lhs, rhs, |a, b| a >= b),
+            E::Equal(lhs, rhs) => compare(lhs, rhs, |a, b| a == b),
+            E::NotEqual(lhs, rhs) => compare(lhs, rhs, |a, b| a != b),
+            E::And(lhs, rhs) => compare(lhs, rhs, |a, b| a != 0.0 && b != 0.0),
+            E::Or(lhs, rhs) => compare(lhs, rhs, |a, b| a != 0.0 || b != 0.0),
+            E::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if eval(condition)?.value() != 0.0 {
+                    eval(then_branch)
+                } else {
+                    eval(else_branch)
+                }
+            }
+            E::FunctionCall {
+                target: FunctionTarget::Function(name),
+                parameters,
+            } => {
+                let arguments = parameters.iter().map(eval).collect::<Option<Vec<S>>>()?;
+                functions(name, &arguments).or_else(|| call(name, &arguments))
+            }
+            E::FunctionCall {
+                target: FunctionTarget::GraphicalFunction(name),
+                parameters,
+            } => {
+                let arguments = parameters.iter().map(eval).collect::<Option<Vec<S>>>()?;
+                functions(name, &arguments)
+            }
+            E::FunctionCall { .. } => None,
+        }
+    }
+}
+
+/// The value of a builtin constant, such as `PI`.
+pub(crate) fn constant(name: &Identifier) -> Option<f64> {
+    match name.normalized().to_ascii_uppercase().as_str() {
+        "PI" => Some(std::f64::consts::PI),
+        "INF" => Some(f64::INFINITY),
+        _ => None,
+    }
+}
+
+/// The number of arguments a builtin whose result depends only on its
+/// arguments takes, or `None` if `builtin`, in upper case, is not one.
+pub(crate) fn arity(builtin: &str) -> Option<RangeInclusive<usize>> {
+    match builtin {
+        "ABS" | "ARCCOS" | "ARCSIN" | "ARCTAN" | "COS" | "SIN" | "TAN" | "EXP" | "LN" | "LOG10"
+        | "SQRT" | "INT" => Some(1..=1),
+        "MAX" | "MIN" => Some(1..=usize::MAX),
+        "SAFEDIV" => Some(2..=3),
+        _ => None,
+    }
+}
+
+/// Calls a builtin whose result depends only on its arguments.
+pub(super) fn call<S: Scalar>(name: &Identifier, arguments: &[S]) -> Option<S> {
+    let unary = |f: fn(S) -> S| match arguments {
+        [x] => Some(f(*x)),
+        _ => None,
+    };
+    match name.normalized().to_ascii_uppercase().as_str() {
+        "ABS" => unary(S::abs),
+        "ARCCOS" => unary(S::acos),
+        "ARCSIN" => unary(S::asin),
+        "ARCTAN" => unary(S::atan),
+        "COS" => unary(S::cos),
+        "SIN" => unary(S::sin),
+        "TAN" => unary(S::tan),
+        "EXP" => unary(S::exp),
+        "LN" => unary(S::ln),
+        "LOG10" => unary(S::log10),
+        "SQRT" => unary(S::sqrt),
+        "INT" => unary(S::floor),
+        "MAX" => arguments
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.value() > a.value() { b } else { a }),
+        "MIN" => arguments
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.value() < a.value() { b } else { a }),
+        "SAFEDIV" => match arguments {
+            [_, b, rest @ ..] if b.value() == 0.0 => {
+                Some(rest.first().copied().unwrap_or(S::constant(0.0)))
+            }
+            [a, b] | [a, b, _] => Some(*a / *b),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -20,6 +20,12 @@ pub enum Expression {
     Constant(NumericConstant),
     // Operators
     Subscript(Identifier, Vec<Expression>),
+    /// `*` in a subscript: every element of a dimension.
+    Wildcard,
+    /// `a:b` in a subscript: the elements from `a` to `b`, inclusive.
+    Range(Box<Expression>, Box<Expression>),
+    /// `a'`: an array with the order of its dimensions reversed.
+    Transpose(Box<Expression>),
     Parentheses(Box<Expression>),
     Exponentiation(Box<Expression>, Box<Expression>),
     UnaryPlus(Box<Expression>),
@@ -62,6 +68,18 @@ impl Expression {
         Expression::Subscript(identifier, params)
     }
 
+    pub fn wildcard() -> Self {
+        Expression::Wildcard
+    }
+
+    pub fn range(start: Expression, end: Expression) -> Self {
+        Expression::Range(Box::new(start), Box::new(end))
+    }
+
+    pub fn transpose(expr: Expression) -> Self {
+        Expression::Transpose(Box::new(expr))
+    }
+
     pub fn parentheses(expr: Expression) -> Self {
         Expression::Parentheses(Box::new(expr))
     }
@@ -157,6 +175,7 @@ impl Expression {
     pub fn top_operator(&self) -> Option<operator::Operator> {
         match self {
             Expression::Subscript(_, _) => Some(Operator::Subscript),
+            Expression::Transpose(_) => Some(Operator::Transpose),
             Expression::Parentheses(_) => Some(Operator::Paren),
             Expression::Exponentiation(_, _) => Some(Operator::Exponentiation),
             Expression::UnaryPlus(_) => Some(Operator::UnaryPlus),
@@ -175,7 +194,7 @@ impl Expression {
             Expression::NotEqual(_, _) => Some(Operator::NotEqual),
             Expression::And(_, _) => Some(Operator::And),
             Expression::Or(_, _) => Some(Operator::Or),
-            Expression::Constant(_) => None,
+            Expression::Constant(_) | Expression::Wildcard | Expression::Range(_, _) => None,
             Expression::FunctionCall { .. } => None,
             Expression::IfElse { .. } => None,
             Expression::InlineComment(_) => None,
@@ -183,62 +202,18 @@ impl Expression {
     }
 
     pub fn operators(&self) -> Vec<Operator> {
-        let mut acc = Vec::new();
-        self.operators_recursive(&mut acc);
-        acc
-    }
+        struct Operators(Vec<Operator>);
 
-    fn operators_recursive(&self, acc: &mut Vec<Operator>) {
-        if let Some(op) = self.top_operator() {
-            acc.push(op);
-        }
-        match self {
-            Expression::Subscript(_, params) => {
-                for param in params {
-                    param.operators_recursive(acc);
-                }
-            }
-            Expression::Parentheses(expr) => expr.operators_recursive(acc),
-            Expression::Exponentiation(base, exponent) => {
-                base.operators_recursive(acc);
-                exponent.operators_recursive(acc);
-            }
-            Expression::UnaryPlus(expr) | Expression::UnaryMinus(expr) | Expression::Not(expr) => {
-                expr.operators_recursive(acc)
-            }
-            Expression::Multiply(lhs, rhs)
-            | Expression::Divide(lhs, rhs)
-            | Expression::Modulo(lhs, rhs)
-            | Expression::Add(lhs, rhs)
-            | Expression::Subtract(lhs, rhs)
-            | Expression::LessThan(lhs, rhs)
-            | Expression::LessThanOrEq(lhs, rhs)
-            | Expression::GreaterThan(lhs, rhs)
-            | Expression::GreaterThanOrEq(lhs, rhs)
-            | Expression::Equal(lhs, rhs)
-            | Expression::NotEqual(lhs, rhs)
-            | Expression::And(lhs, rhs)
-            | Expression::Or(lhs, rhs) => {
-                lhs.operators_recursive(acc);
-                rhs.operators_recursive(acc);
-            }
-            Expression::FunctionCall { parameters, .. } => {
-                for param in parameters {
-                    param.operators_recursive(acc);
-                }
+        impl super::visit::ExpressionVisitor for Operators {
+            fn enter(&mut self, expression: &Expression) -> super::visit::Visit {
+                self.0.extend(expression.top_operator());
+                super::visit::Visit::Continue
             }
-            Expression::IfElse {
-                condition,
-                then_branch,
-                else_branch,
-            } => {
-                condition.operators_recursive(acc);
-                then_branch.operators_recursive(acc);
-                else_branch.operators_recursive(acc);
-            }
-            Expression::InlineComment(_) => {}
-            Expression::Constant(_) => {}
         }
+
+        let mut operators = Operators(Vec::new());
+        self.walk(&mut operators);
+        operators.0
     }
 
     /// Resolves function calls in this expression using macro, graphical function, and array registries.
@@ -312,7 +287,7 @@ impl Expression {
         #[cfg(not(feature = "arrays"))] _array_registry: Option<()>,
     ) -> Result<Expression, String> {
         match self {
-            Expression::Constant(_) => Ok(self.clone()),
+            Expression::Constant(_) | Expression::Wildcard => Ok(self.clone()),
             Expression::Subscript(id, params) => {
                 let resolved_params: Result<Vec<Expression>, String> = params
                     .iter()
@@ -358,7 +333,10 @@ impl Expression {
                     Box::new(resolved_rhs),
                 ))
             }
-            Expression::UnaryPlus(expr) | Expression::UnaryMinus(expr) | Expression::Not(expr) => {
+            Expression::UnaryPlus(expr)
+            | Expression::UnaryMinus(expr)
+            | Expression::Not(expr)
+            | Expression::Transpose(expr) => {
                 let resolved = expr.resolve_function_calls(
                     #[cfg(feature = "macros")]
                     macro_registry,
@@ -370,6 +348,7 @@ impl Expression {
                     Expression::UnaryPlus(_) => Expression::UnaryPlus(Box::new(resolved)),
                     Expression::UnaryMinus(_) => Expression::UnaryMinus(Box::new(resolved)),
                     Expression::Not(_) => Expression::Not(Box::new(resolved)),
+                    Expression::Transpose(_) => Expression::Transpose(Box::new(resolved)),
                     _ => unreachable!(),
                 })
             }
@@ -385,7 +364,8 @@ impl Expression {
             | Expression::Equal(lhs, rhs)
             | Expression::NotEqual(lhs, rhs)
             | Expression::And(lhs, rhs)
-            | Expression::Or(lhs, rhs) => {
+            | Expression::Or(lhs, rhs)
+            | Expression::Range(lhs, rhs) => {
                 let resolved_lhs = lhs.resolve_function_calls(
                     #[cfg(feature = "macros")]
                     macro_registry,
@@ -440,6 +420,9 @@ impl Expression {
                     Expression::Or(_, _) => {
                         Expression::Or(Box::new(resolved_lhs), Box::new(resolved_rhs))
                     }
+                    Expression::Range(_, _) => {
+                        Expression::Range(Box::new(resolved_lhs), Box::new(resolved_rhs))
+                    }
                     _ => unreachable!(),
                 })
             }
@@ -587,7 +570,7 @@ impl Expression {
         #[cfg(not(feature = "arrays"))] _array_registry: Option<()>,
     ) -> Result<Expression, String> {
         match self {
-            Expression::Constant(_) => Ok(self.clone()),
+            Expression::Constant(_) | Expression::Wildcard => Ok(self.clone()),
             Expression::Subscript(id, params) => {
                 let resolved_params: Result<Vec<Expression>, String> = params
                     .iter()
@@ -625,7 +608,10 @@ impl Expression {
                     Box::new(resolved_rhs),
                 ))
             }
-            Expression::UnaryPlus(expr) | Expression::UnaryMinus(expr) | Expression::Not(expr) => {
+            Expression::UnaryPlus(expr)
+            | Expression::UnaryMinus(expr)
+            | Expression::Not(expr)
+            | Expression::Transpose(expr) => {
                 let resolved = expr.resolve_function_calls(
                     gf_registry,
                     #[cfg(feature = "arrays")]
@@ -635,6 +621,7 @@ impl Expression {
                     Expression::UnaryPlus(_) => Expression::UnaryPlus(Box::new(resolved)),
                     Expression::UnaryMinus(_) => Expression::UnaryMinus(Box::new(resolved)),
                     Expression::Not(_) => Expression::Not(Box::new(resolved)),
+                    Expression::Transpose(_) => Expression::Transpose(Box::new(resolved)),
                     _ => unreachable!(),
                 })
             }
@@ -650,7 +637,8 @@ impl Expression {
             | Expression::Equal(lhs, rhs)
             | Expression::NotEqual(lhs, rhs)
             | Expression::And(lhs, rhs)
-            | Expression::Or(lhs, rhs) => {
+            | Expression::Or(lhs, rhs)
+            | Expression::Range(lhs, rhs) => {
                 let resolved_lhs = lhs.resolve_function_calls(
                     gf_registry,
                     #[cfg(feature = "arrays")]
@@ -701,6 +689,9 @@ impl Expression {
                     Expression::Or(_, _) => {
                         Expression::Or(Box::new(resolved_lhs), Box::new(resolved_rhs))
                     }
+                    Expression::Range(_, _) => {
+                        Expression::Range(Box::new(resolved_lhs), Box::new(resolved_rhs))
+                    }
                     _ => unreachable!(),
                 })
             }
@@ -874,7 +865,7 @@ impl Expression {
         errors: &mut Vec<String>,
     ) {
         match self {
-            Expression::Constant(_) | Expression::InlineComment(_) => {}
+            Expression::Constant(_) | Expression::Wildcard | Expression::InlineComment(_) => {}
             Expression::Subscript(_, params) => {
                 for param in params {
                     param.validate_resolved_impl(
@@ -938,7 +929,8 @@ impl Expression {
             | Expression::Exponentiation(expr, _)
             | Expression::UnaryPlus(expr)
             | Expression::UnaryMinus(expr)
-            | Expression::Not(expr) => {
+            | Expression::Not(expr)
+            | Expression::Transpose(expr) => {
                 expr.validate_resolved_impl(
                     macro_registry,
                     gf_registry,
@@ -959,7 +951,8 @@ impl Expression {
             | Expression::Equal(lhs, rhs)
             | Expression::NotEqual(lhs, rhs)
             | Expression::And(lhs, rhs)
-            | Expression::Or(lhs, rhs) => {
+            | Expression::Or(lhs, rhs)
+            | Expression::Range(lhs, rhs) => {
                 lhs.validate_resolved_impl(
                     macro_registry,
                     gf_registry,
@@ -1015,7 +1008,7 @@ impl Expression {
         errors: &mut Vec<String>,
     ) {
         match self {
-            Expression::Constant(_) | Expression::InlineComment(_) => {}
+            Expression::Constant(_) | Expression::Wildcard | Expression::InlineComment(_) => {}
             Expression::Subscript(_, params) => {
                 for param in params {
                     param.validate_resolved_impl(
@@ -1071,7 +1064,8 @@ impl Expression {
             | Expression::Exponentiation(expr, _)
             | Expression::UnaryPlus(expr)
             | Expression::UnaryMinus(expr)
-            | Expression::Not(expr) => {
+            | Expression::Not(expr)
+            | Expression::Transpose(expr) => {
                 expr.validate_resolved_impl(
                     None,
                     gf_registry,
@@ -1094,7 +1088,8 @@ impl Expression {
             | Expression::Equal(lhs, rhs)
             | Expression::NotEqual(lhs, rhs)
             | Expression::And(lhs, rhs)
-            | Expression::Or(lhs, rhs) => {
+            | Expression::Or(lhs, rhs)
+            | Expression::Range(lhs, rhs) => {
                 lhs.validate_resolved_impl(
                     None,
                     gf_registry,
@@ -1173,6 +1168,9 @@ impl fmt::Display for Expression {
                 }
                 write!(f, "]")
             }
+            Expression::Wildcard => write!(f, "*"),
+            Expression::Range(start, end) => write!(f, "{}:{}", start, end),
+            Expression::Transpose(expr) => write!(f, "{}'", expr),
             Expression::Parentheses(expr) => write!(f, "({})", expr),
             Expression::Exponentiation(base, exponent) => write!(f, "{} ^ {}", base, exponent),
             Expression::UnaryPlus(expr) => write!(f, "+{}", expr),
@@ -1191,12 +1189,16 @@ impl fmt::Display for Expression {
             Expression::NotEqual(lhs, rhs) => write!(f, "{} <> {}", lhs, rhs),
             Expression::And(lhs, rhs) => write!(f, "{} AND {}", lhs, rhs),
             Expression::Or(lhs, rhs) => write!(f, "{} OR {}", lhs, rhs),
-            Expression::FunctionCall {
-                target: _,
-                parameters,
-            } => {
-                write!(f, "{}(", parameters[0])?;
-                for (i, param) in parameters.iter().enumerate().skip(1) {
+            Expression::FunctionCall { target, parameters } => {
+                let name = match target {
+                    FunctionTarget::Function(id)
+                    | FunctionTarget::GraphicalFunction(id)
+                    | FunctionTarget::Model(id)
+                    | FunctionTarget::Array(id) => id.raw(),
+                };
+
+                write!(f, "{}(", name)?;
+                for (i, param) in parameters.iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
@@ -1262,7 +1264,7 @@ pub mod operator {
     //!
     //! | Operators | Precedence Group                 |
     //! |:---------:|:---------------------------------|
-    //! | [ ]       | Subscripts                       |
+    //! | [ ] '     | Subscripts, transposition        |
     //! | ( )       | Parentheses                      |
     //! | ^         | Exponentiation                   |
     //! | + – NOT   | Unary operators                  |
@@ -1297,6 +1299,7 @@ pub mod operator {
     #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     pub enum Operator {
         Subscript,
+        Transpose,
         Paren,
         Exponentiation,
         UnaryPlus,
@@ -1320,7 +1323,7 @@ pub mod operator {
     impl Operator {
         pub fn precedence(&self) -> u8 {
             match self {
-                Operator::Subscript => 0,
+                Operator::Subscript | Operator::Transpose => 0,
                 Operator::Paren => 1,
                 Operator::Exponentiation => 2,
                 Operator::UnaryPlus | Operator::UnaryMinus | Operator::Not => 3,
@@ -1342,6 +1345,7 @@ pub mod operator {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             let symbol = match self {
                 Operator::Subscript => "[]",
+                Operator::Transpose => "'",
                 Operator::Paren => "()",
                 Operator::Exponentiation => "^",
                 Operator::UnaryPlus => "+",
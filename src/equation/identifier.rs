@@ -80,6 +80,7 @@ use crate::Namespace;
 
 /// Errors that can occur during identifier parsing and processing.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum IdentifierError {
     /// Error occurred during string processing (Unicode normalization, case folding, etc.)
     #[error("String processing error: {0}")]
@@ -400,6 +401,25 @@ impl Identifier {
         self.quoted
     }
 
+    /// Checks if the name of this identifier, ignoring quotes and namespace,
+    /// is a reserved keyword or the name of a built-in function.
+    ///
+    /// Such names can only be used for variables when quoted, and cannot be
+    /// used for macros outside an explicit namespace.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xmile::Identifier;
+    ///
+    /// assert!(Identifier::parse_default("\"Time\"").unwrap().is_reserved_word());
+    /// assert!(Identifier::parse_default("\"pulse train\"").unwrap().is_reserved_word());
+    /// assert!(!Identifier::parse_default("Timer").unwrap().is_reserved_word());
+    /// ```
+    pub fn is_reserved_word(&self) -> bool {
+        Self::is_reserved(&self.normalized.replace(' ', "_"))
+    }
+
     /// Checks if this is a qualified identifier (contains namespace).
     ///
     /// Returns `true` if the identifier includes namespace qualification
@@ -1,10 +1,21 @@
+pub mod double_double;
+pub mod dual;
+pub mod equivalence;
+pub mod evaluate;
 pub mod expression;
 pub mod identifier;
 pub mod numeric;
 pub mod parse;
+#[cfg(feature = "arrays")]
+pub mod subscript;
 pub mod units;
 pub mod utils;
+pub mod visit;
 
+pub use double_double::DoubleDouble;
+pub use dual::Dual;
+pub use equivalence::EquivalenceOptions;
+pub use evaluate::Scalar;
 pub use expression::{Expression, operator::Operator};
 pub use identifier::{Identifier, IdentifierError};
 pub use numeric::{NumericConstant, NumericConstantError};
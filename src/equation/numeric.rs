@@ -155,6 +155,7 @@ use thiserror::Error;
 /// enabling precise error reporting and handling. Each variant includes
 /// contextual information about what went wrong and where.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum NumericConstantError {
     /// The input string is empty or contains only whitespace.
     ///
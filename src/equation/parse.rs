@@ -129,8 +129,16 @@ pub mod common {
     }
 
     /// Parse a numeric constant (integer or float)
+    ///
+    /// The textual forms `inf`, `infinity` and `nan` accepted by nom's `double`
+    /// are rejected so that identifiers such as `infectivity` are not split.
     pub fn numeric_constant(input: &str) -> IResult<&str, NumericConstant> {
-        map(double, NumericConstant).parse(input)
+        let finite_literal = verify(recognize(double), |s: &str| {
+            !s.chars()
+                .any(|c| c.is_ascii_alphabetic() && !matches!(c, 'e' | 'E'))
+        });
+
+        map(map_res(finite_literal, str::parse::<f64>), NumericConstant).parse(input)
     }
 
     /// Parse parentheses around an expression
@@ -160,9 +168,9 @@ pub mod expression {
         branch::alt,
         bytes::complete::{tag, tag_no_case, take_while1},
         character::complete::char,
-        combinator::{map, value},
+        combinator::{map, peek, value},
         multi::{separated_list0, separated_list1},
-        sequence::{delimited, pair, preceded},
+        sequence::{delimited, pair, preceded, terminated},
     };
 
     use crate::{Expression, Operator, equation::expression::function::FunctionTarget};
@@ -190,6 +198,23 @@ pub mod expression {
         .parse(input)
     }
 
+    /// Parse one index of a subscript: an expression, a range of indices
+    /// such as `1:5`, or a `*` wildcard
+    fn subscript_index(input: &str) -> IResult<&str, Expression> {
+        alt((
+            value(
+                Expression::Wildcard,
+                terminated(ws(char('*')), peek(alt((char(','), char(']'))))),
+            ),
+            map(
+                pair(expression, preceded(ws(char(':')), expression)),
+                |(start, end)| Expression::Range(Box::new(start), Box::new(end)),
+            ),
+            expression,
+        ))
+        .parse(input)
+    }
+
     /// Parse array subscript
     fn subscript(input: &str) -> IResult<&str, Expression> {
         map(
@@ -197,7 +222,7 @@ pub mod expression {
                 identifier,
                 delimited(
                     ws(char('[')),
-                    separated_list1(ws(char(',')), expression),
+                    separated_list1(ws(char(',')), subscript_index),
                     ws(char(']')),
                 ),
             ),
@@ -247,6 +272,16 @@ pub mod expression {
         .parse(input)
     }
 
+    /// Parse a primary expression followed by any transposition marks
+    fn transpose(input: &str) -> IResult<&str, Expression> {
+        let (mut input, mut expression) = primary(input)?;
+        while let Ok((rest, _)) = ws(char('\'')).parse(input) {
+            expression = Expression::Transpose(Box::new(expression));
+            input = rest;
+        }
+        Ok((input, expression))
+    }
+
     /// Parse unary expressions (unary operators)
     fn unary(input: &str) -> IResult<&str, Expression> {
         alt((
@@ -259,7 +294,7 @@ pub mod expression {
             map(preceded(ws(tag_no_case("not")), unary), |expr| {
                 Expression::Not(Box::new(expr))
             }),
-            primary,
+            transpose,
         ))
         .parse(input)
     }
@@ -433,8 +468,11 @@ pub mod expression {
 
 pub mod units {
     use nom::{
-        IResult, Parser, branch::alt, character::complete::char, combinator::map,
-        sequence::preceded,
+        IResult, Parser,
+        branch::alt,
+        character::complete::{char, digit1},
+        combinator::{map, map_res, opt, recognize},
+        sequence::{delimited, pair, preceded},
     };
 
     use crate::UnitEquation;
@@ -456,13 +494,33 @@ pub mod units {
         .parse(input)
     }
 
+    /// Parse an integer exponent, optionally negative or in parentheses
+    fn exponent(input: &str) -> IResult<&str, i32> {
+        let integer = || map_res(recognize(pair(opt(char('-')), digit1)), str::parse::<i32>);
+        alt((
+            ws(integer()),
+            delimited(ws(char('(')), ws(integer()), ws(char(')'))),
+        ))
+        .parse(input)
+    }
+
+    /// Parse exponentiation (left-associative, binds tighter than unary minus)
+    fn power(input: &str) -> IResult<&str, UnitEquation> {
+        let (mut remaining, mut base) = atomic(input)?;
+        while let Ok((new_input, exponent)) = preceded(ws(char('^')), exponent).parse(remaining) {
+            base = UnitEquation::Exponentiation(Box::new(base), exponent);
+            remaining = new_input;
+        }
+        Ok((remaining, base))
+    }
+
     /// Parse a unary expression (handles unary minus)
     fn unary(input: &str) -> IResult<&str, UnitEquation> {
         alt((
             map(preceded(ws(char('-')), unary), |expr| {
                 UnitEquation::UnaryMinus(Box::new(expr))
             }),
-            atomic,
+            power,
         ))
         .parse(input)
     }
@@ -543,6 +601,14 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_identifier_with_numeric_prefix() {
+            let result = expression("contact_rate * infectivity").unwrap();
+            assert_eq!(result.0, "");
+            let result = expression("nancy + 1").unwrap();
+            assert_eq!(result.0, "");
+        }
+
         #[test]
         fn test_function_call() {
             let result = expression("ABS(-5)").unwrap().1;
@@ -566,6 +632,30 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_subscript_ranges_and_wildcards() {
+            let result = expression("Pop[Region, *] + Pop[1:3, Age]'").unwrap();
+            assert_eq!(result.0, "");
+            let Expression::Add(lhs, rhs) = result.1 else {
+                panic!("Expected addition");
+            };
+            let Expression::Subscript(_, indices) = *lhs else {
+                panic!("Expected subscript");
+            };
+            assert_eq!(indices[1], Expression::Wildcard);
+            let Expression::Transpose(inner) = *rhs else {
+                panic!("Expected transposition");
+            };
+            let Expression::Subscript(_, indices) = *inner else {
+                panic!("Expected subscript");
+            };
+            assert!(matches!(indices[0], Expression::Range(_, _)));
+            assert_eq!(
+                expression("a[2:n, *]'").unwrap().1.to_string(),
+                "a[2:n, *]'"
+            );
+        }
+
         #[test]
         fn test_if_else() {
             let result = expression("if x > 0 then 1 else -1").unwrap().1;
@@ -680,6 +770,18 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_exponentiation() {
+            let (rest, equation) = unit_equation("miles^2 / hours ^ (-1)").unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(equation.to_string(), "miles^2/hours^-1");
+            assert_eq!(
+                unit_equation("-miles^2").unwrap().1.to_string(),
+                "-(miles^2)"
+            );
+            assert!(unit_equation("(miles * hours)^3").is_ok());
+        }
+
         #[test]
         fn test_complex_expression() {
             let result = unit_equation("miles * seconds / 1");
@@ -0,0 +1,366 @@
+//! Evaluation of arrayed equations.
+//!
+//! [`Expression::evaluate_arrayed`] computes an equation whose value may
+//! be an array. Subscripts pick elements out of arrays (Section 3.7.1):
+//!
+//! - an index is an element name or a position counting from 1;
+//! - `*`, or the name of the dimension itself, selects every element;
+//! - `a:b` selects the elements from `a` to `b`, inclusive;
+//! - the name of a dimension the equation is being evaluated for selects
+//!   the element the equation is computing, so `Pop[Region, *]` in the
+//!   equation for one region picks out that region's row.
+//!
+//! Operators apply element by element, and between an array and a number
+//! apply the number to every element. `a'` reverses the order of the
+//! dimensions of `a`. `SUM`, `MEAN`, `MIN`, `MAX`, `PROD` and `SIZE` of a
+//! single array reduce it to a number:
+//!
+//! ```rust
+//! use xmile::containers::array::ArrayValue;
+//! use xmile::dimensions::Dimension;
+//! use xmile::equation::subscript::{ArrayedValue, SubscriptScope};
+//!
+//! let dimension = |name: &str, size| Dimension {
+//!     name: name.to_string(),
+//!     size: Some(size),
+//!     elements: Vec::new(),
+//! };
+//! let pop = ArrayValue::from_values(
+//!     vec![dimension("Region", 2), dimension("Age", 3)],
+//!     vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+//! )
+//! .unwrap();
+//! let arrays = |name: &xmile::Identifier| (name.normalized() == "Pop").then_some(&pop);
+//! let scalars = |_: &xmile::Identifier| None;
+//!
+//! // The row of the second region
+//! let scope = SubscriptScope::new(&arrays, &scalars).at("Region", 1);
+//! let (_, row) = xmile::equation::parse::expression("SUM(Pop[Region, *])").unwrap();
+//! assert_eq!(row.evaluate_arrayed(&scope), Some(ArrayedValue::Scalar(15.0)));
+//! ```
+//!
+//! The evaluator is a library piece only: runs do not use it, and
+//! [`Simulator::new`] rejects models with arrays as
+//! [`SimulationError::Unsupported`]. Callers evaluating arrayed equations
+//! build the [`SubscriptScope`] and step through time themselves.
+//!
+//! [`Simulator::new`]: crate::simulation::Simulator::new
+//! [`SimulationError::Unsupported`]: crate::simulation::SimulationError::Unsupported
+
+use crate::containers::Container;
+use crate::containers::array::{ArrayValue, Selection};
+
+use super::evaluate::call;
+use super::expression::function::FunctionTarget;
+use super::{Expression, Identifier};
+
+/// The value of an arrayed equation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayedValue {
+    Scalar(f64),
+    Array(ArrayValue),
+}
+
+impl ArrayedValue {
+    /// A number, or an array with no dimensions, as a number.
+    pub fn as_scalar(&self) -> Option<f64> {
+        match self {
+            ArrayedValue::Scalar(value) => Some(*value),
+            ArrayedValue::Array(array) if array.dimensions().is_empty() => Some(array[0]),
+            ArrayedValue::Array(_) => None,
+        }
+    }
+
+    fn from_array(array: ArrayValue) -> Self {
+        if array.dimensions().is_empty() {
+            ArrayedValue::Scalar(array[0])
+        } else {
+            ArrayedValue::Array(array)
+        }
+    }
+
+    fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        match self {
+            ArrayedValue::Scalar(value) => ArrayedValue::Scalar(f(value)),
+            ArrayedValue::Array(array) => ArrayedValue::Array(array.map(f)),
+        }
+    }
+
+    fn combine(self, other: Self, f: impl Fn(f64, f64) -> f64) -> Option<Self> {
+        use ArrayedValue::{Array, Scalar};
+
+        Some(match (self, other) {
+            (Scalar(a), Scalar(b)) => Scalar(f(a, b)),
+            (Array(a), Scalar(b)) => Array(a.map(|a| f(a, b))),
+            (Scalar(a), Array(b)) => Array(b.map(|b| f(a, b))),
+            (Array(a), Array(b)) => Array(a.zip_with(&b, f).ok()?),
+        })
+    }
+}
+
+/// The variables an arrayed equation can refer to, and the element it is
+/// being evaluated for.
+pub struct SubscriptScope<'a> {
+    arrays: &'a dyn Fn(&Identifier) -> Option<&'a ArrayValue>,
+    scalars: &'a dyn Fn(&Identifier) -> Option<f64>,
+    /// The position, counting from 0, of the element being computed in
+    /// each dimension of the variable whose equation this is.
+    element: Vec<(Identifier, usize)>,
+}
+
+impl<'a> SubscriptScope<'a> {
+    /// A scope finding arrayed variables with `arrays` and other variables
+    /// with `scalars`.
+    pub fn new(
+        arrays: &'a dyn Fn(&Identifier) -> Option<&'a ArrayValue>,
+        scalars: &'a dyn Fn(&Identifier) -> Option<f64>,
+    ) -> Self {
+        SubscriptScope {
+            arrays,
+            scalars,
+            element: Vec::new(),
+        }
+    }
+
+    /// Evaluates for the element at `position`, counting from 0, of the
+    /// dimension named `dimension`, as for one element of an apply-to-all
+    /// equation.
+    pub fn at(mut self, dimension: &str, position: usize) -> Self {
+        if let Ok(dimension) = Identifier::parse_from_attribute(dimension) {
+            self.element.push((dimension, position));
+        }
+        self
+    }
+
+    /// The position of the element being computed in `dimension`.
+    fn position_in(&self, dimension: &Identifier) -> Option<usize> {
+        self.element
+            .iter()
+            .find(|(name, _)| name == dimension)
+            .map(|&(_, position)| position)
+    }
+}
+
+impl Expression {
+    /// Evaluates the expression in `scope`, or returns `None` if it refers
+    /// to an unknown variable, subscripts an array with an index it does
+    /// not have, combines arrays of different shapes, or uses something
+    /// that cannot be evaluated from its inputs alone.
+    pub fn evaluate_arrayed(&self, scope: &SubscriptScope) -> Option<ArrayedValue> {
+        use ArrayedValue::Scalar;
+        use Expression as E;
+
+        fn truth(value: bool) -> f64 {
+            if value { 1.0 } else { 0.0 }
+        }
+        let eval = |e: &Expression| e.evaluate_arrayed(scope);
+        let binary = |lhs: &Expression, rhs: &Expression, f: fn(f64, f64) -> f64| {
+            eval(lhs)?.combine(eval(rhs)?, f)
+        };
+        match self {
+            E::Constant(constant) => Some(Scalar(constant.0)),
+            E::Subscript(name, indices) if indices.is_empty() => match (scope.arrays)(name) {
+                Some(array) => Some(ArrayedValue::Array(array.clone())),
+                None => (scope.scalars)(name).map(Scalar),
+            },
+            E::Subscript(name, indices) => {
+                let array = (scope.arrays)(name)?;
+                let selections = indices
+                    .iter()
+                    .enumerate()
+                    .map(|(dimension, index)| select(scope, array, dimension, index))
+                    .collect::<Option<Vec<_>>>()?;
+                array.select(&selections).ok().map(ArrayedValue::from_array)
+            }
+            E::Transpose(inner) => match eval(inner)? {
+                ArrayedValue::Array(array) => Some(ArrayedValue::Array(array.transpose())),
+                scalar => Some(scalar),
+            },
+            E::Wildcard | E::Range(..) | E::InlineComment(_) => None,
+            E::Parentheses(inner) | E::UnaryPlus(inner) => eval(inner),
+            E::UnaryMinus(inner) => Some(eval(inner)?.map(|value| -value)),
+            E::Not(inner) => Some(eval(inner)?.map(|value| truth(value == 0.0))),
+            E::Exponentiation(lhs, rhs) => binary(lhs, rhs, f64::powf),
+            E::Multiply(lhs, rhs) => binary(lhs, rhs, |a, b| a * b),
+            E::Divide(lhs, rhs) => binary(lhs, rhs, |a, b| a / b),
+            E::Modulo(lhs, rhs) => binary(lhs, rhs, f64::rem_euclid),
+            E::Add(lhs, rhs) => binary(lhs, rhs, |a, b| a + b),
+            E::Subtract(lhs, rhs) => binary(lhs, rhs, |a, b| a - b),
+            E::LessThan(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a < b)),
+            E::LessThanOrEq(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a <= b)),
+            E::GreaterThan(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a > b)),
+            E::GreaterThanOrEq(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a >= b)),
+            E::Equal(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a == b)),
+            E::NotEqual(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a != b)),
+            E::And(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a != 0.0 && b != 0.0)),
+            E::Or(lhs, rhs) => binary(lhs, rhs, |a, b| truth(a != 0.0 || b != 0.0)),
+            E::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if eval(condition)?.as_scalar()? != 0.0 {
+                    eval(then_branch)
+                } else {
+                    eval(else_branch)
+                }
+            }
+            // A flat index into an array, counting from 1
+            E::FunctionCall {
+                target: FunctionTarget::Function(name) | FunctionTarget::Array(name),
+                parameters,
+            } if (scope.arrays)(name).is_some() => {
+                let array = (scope.arrays)(name)?;
+                let [index] = parameters.as_slice() else {
+                    return None;
+                };
+                let index = eval(index)?.as_scalar()?.round();
+                (1.0..=array.len() as f64)
+                    .contains(&index)
+                    .then(|| Scalar(array[index as usize - 1]))
+            }
+            E::FunctionCall {
+                target: FunctionTarget::Function(name),
+                parameters,
+            } => {
+                let arguments = parameters.iter().map(eval).collect::<Option<Vec<_>>>()?;
+                if let [ArrayedValue::Array(array)] = arguments.as_slice() {
+                    return reduce(name, array).map(Scalar);
+                }
+                let arguments = arguments
+                    .iter()
+                    .map(ArrayedValue::as_scalar)
+                    .collect::<Option<Vec<f64>>>()?;
+                call(name, &arguments).map(Scalar)
+            }
+            E::FunctionCall { .. } => None,
+        }
+    }
+}
+
+/// The elements of the dimension at `dimension` of `array` that `index`
+/// selects.
+fn select(
+    scope: &SubscriptScope,
+    array: &ArrayValue,
+    dimension: usize,
+    index: &Expression,
+) -> Option<Selection> {
+    match index {
+        Expression::Wildcard => Some(Selection::All),
+        Expression::Range(first, last) => Some(Selection::Range(
+            position(scope, array, dimension, first)?,
+            position(scope, array, dimension, last)?,
+        )),
+        Expression::Subscript(name, indices)
+            if indices.is_empty() && names_dimension(array, dimension, name) =>
+        {
+            Some(
+                scope
+                    .position_in(name)
+                    .map_or(Selection::All, Selection::Position),
+            )
+        }
+        _ => position(scope, array, dimension, index).map(Selection::Position),
+    }
+}
+
+/// The position, counting from 0, of the element `index` names or numbers
+/// in the dimension at `dimension` of `array`.
+fn position(
+    scope: &SubscriptScope,
+    array: &ArrayValue,
+    dimension: usize,
+    index: &Expression,
+) -> Option<usize> {
+    if let Expression::Subscript(name, indices) = index
+        && indices.is_empty()
+        && let Some(position) = array.dimensions()[dimension]
+            .elements
+            .iter()
+            .position(|element| {
+                Identifier::parse_from_attribute(&element.name)
+                    .is_ok_and(|element| &element == name)
+            })
+    {
+        return Some(position);
+    }
+    let number = index.evaluate_arrayed(scope)?.as_scalar()?.round();
+    let size = array.dimensions()[dimension].size();
+    (1.0..=size as f64)
+        .contains(&number)
+        .then(|| number as usize - 1)
+}
+
+/// Whether `name` is the name of the dimension at `dimension` of `array`.
+fn names_dimension(array: &ArrayValue, dimension: usize, name: &Identifier) -> bool {
+    Identifier::parse_from_attribute(&array.dimensions()[dimension].name)
+        .is_ok_and(|dimension| &dimension == name)
+}
+
+/// Reduces an array to a number with an array builtin.
+fn reduce(name: &Identifier, array: &ArrayValue) -> Option<f64> {
+    let values = array.values();
+    match name.normalized().to_ascii_uppercase().as_str() {
+        "SUM" => Some(values.iter().sum()),
+        "PROD" => Some(values.iter().product()),
+        "MEAN" => array.mean(),
+        "MIN" => array.min(),
+        "MAX" => array.max(),
+        "SIZE" => Some(values.len() as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dimensions::{Dimension, DimensionElement};
+    use crate::test_utils::parse_expression as expression;
+
+    #[test]
+    fn test_subscripts() {
+        let region = Dimension {
+            name: "Region".to_string(),
+            size: None,
+            elements: ["North", "South"]
+                .map(|name| DimensionElement {
+                    name: name.to_string(),
+                })
+                .to_vec(),
+        };
+        let age = Dimension {
+            name: "Age".to_string(),
+            size: Some(3),
+            elements: Vec::new(),
+        };
+        let pop =
+            ArrayValue::from_values(vec![region, age], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let arrays = |name: &Identifier| (name.normalized() == "Pop").then_some(&pop);
+        let scalars = |name: &Identifier| (name.normalized() == "k").then_some(2.0);
+        let scope = SubscriptScope::new(&arrays, &scalars);
+        let evaluate = |equation: &str| expression(equation).evaluate_arrayed(&scope);
+        let values = |equation: &str| match evaluate(equation) {
+            Some(ArrayedValue::Array(array)) => array.values().to_vec(),
+            other => panic!("{equation} gave {other:?}"),
+        };
+
+        assert_eq!(evaluate("Pop[South, 2]"), Some(ArrayedValue::Scalar(5.0)));
+        assert_eq!(evaluate("Pop[1, k + 1]"), Some(ArrayedValue::Scalar(3.0)));
+        assert_eq!(values("Pop[*, 1]"), [1.0, 4.0]);
+        assert_eq!(values("Pop[North, 2:3] * k"), [4.0, 6.0]);
+        assert_eq!(values("Pop[Region, Age]'"), [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert_eq!(evaluate("SUM(Pop[*, 3])"), Some(ArrayedValue::Scalar(9.0)));
+        assert_eq!(evaluate("Pop(6)"), Some(ArrayedValue::Scalar(6.0)));
+        assert_eq!(evaluate("Pop[East, 1]"), None);
+        assert_eq!(evaluate("Pop[*, *] + Pop'"), None);
+
+        // Dimension names map to the element being computed
+        let scope = SubscriptScope::new(&arrays, &scalars).at("Region", 1);
+        let row = expression("Pop[Region, *] - MEAN(Pop[Region, *])");
+        match row.evaluate_arrayed(&scope) {
+            Some(ArrayedValue::Array(array)) => assert_eq!(array.values(), [-1.0, 0.0, 1.0]),
+            other => panic!("gave {other:?}"),
+        }
+    }
+}
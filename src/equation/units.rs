@@ -51,6 +51,8 @@ pub enum UnitEquation {
     Multiplication(Box<UnitEquation>, Box<UnitEquation>),
     Division(Box<UnitEquation>, Box<UnitEquation>),
     Parentheses(Box<UnitEquation>),
+    /// Units raised to an integer power, such as `miles^2`.
+    Exponentiation(Box<UnitEquation>, i32),
 }
 
 impl UnitEquation {
@@ -77,6 +79,10 @@ impl UnitEquation {
     pub fn parentheses(inner: UnitEquation) -> Self {
         UnitEquation::Parentheses(Box::new(inner))
     }
+
+    pub fn exponentiation(base: UnitEquation, exponent: i32) -> Self {
+        UnitEquation::Exponentiation(Box::new(base), exponent)
+    }
 }
 
 impl<'de> Deserialize<'de> for UnitEquation {
@@ -130,6 +136,7 @@ impl fmt::Display for UnitEquation {
             UnitEquation::Multiplication(left, right) => write!(f, "{} * {}", left, right),
             UnitEquation::Division(left, right) => write!(f, "{}/{}", left, right),
             UnitEquation::Parentheses(inner) => write!(f, "({})", inner),
+            UnitEquation::Exponentiation(base, exponent) => write!(f, "{}^{}", base, exponent),
         }
     }
 }
@@ -48,6 +48,7 @@ use crate::types::WithWarnings;
 
 /// Errors that can occur during string processing operations.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ProcessingError {
     /// An invalid escape sequence was encountered in a quoted identifier
     #[error("Invalid escape sequence: {0}")]
@@ -0,0 +1,367 @@
+//! Traversal and rewriting of expression trees.
+//!
+//! An [`ExpressionVisitor`] is called on entering and on leaving each node of
+//! an expression, with a callback for each kind of node, so analyses such as
+//! dependency extraction only handle the nodes they care about. An
+//! [`ExpressionFolder`] rebuilds an expression bottom-up, for rewrites such
+//! as simplification or differentiation.
+//!
+//! ```rust
+//! use xmile::equation::Expression;
+//! use xmile::equation::visit::ExpressionFolder;
+//!
+//! let (_, expression) = xmile::equation::parse::expression("(a + 0) * b").unwrap();
+//! let mut drop_zero = |expression: Expression| match expression {
+//!     Expression::Add(lhs, rhs) if rhs.to_string() == "0" => *lhs,
+//!     Expression::Parentheses(inner) if matches!(*inner, Expression::Subscript(..)) => *inner,
+//!     expression => expression,
+//! };
+//! assert_eq!(drop_zero.fold_expression(expression).to_string(), "a * b");
+//! ```
+
+use std::mem;
+
+use super::expression::function::FunctionTarget;
+use super::{Expression, Identifier, NumericConstant, Operator};
+
+/// Whether to descend into the children of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue,
+    SkipChildren,
+}
+
+/// Callbacks for a depth-first walk over an expression.
+///
+/// [`enter`](ExpressionVisitor::enter) and [`exit`](ExpressionVisitor::exit)
+/// are called for every node, and by default dispatch to the callback for
+/// the kind of node. Variable references (with or without subscripts),
+/// operators, function calls and conditionals have both an `enter_*` and an
+/// `exit_*` callback; constants and comments have no children and are only
+/// visited.
+pub trait ExpressionVisitor {
+    fn enter(&mut self, expression: &Expression) -> Visit {
+        match expression {
+            Expression::Constant(value) => {
+                self.visit_constant(value);
+                Visit::Continue
+            }
+            Expression::InlineComment(comment) => {
+                self.visit_comment(comment);
+                Visit::Continue
+            }
+            Expression::Subscript(name, indices) => self.enter_reference(name, indices),
+            Expression::FunctionCall { target, parameters } => {
+                self.enter_function_call(target, parameters)
+            }
+            Expression::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => self.enter_if_else(condition, then_branch, else_branch),
+            _ => match expression.top_operator() {
+                Some(operator) => self.enter_operator(operator, expression),
+                None => Visit::Continue,
+            },
+        }
+    }
+
+    fn exit(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Constant(_) | Expression::InlineComment(_) => {}
+            Expression::Subscript(name, indices) => self.exit_reference(name, indices),
+            Expression::FunctionCall { target, parameters } => {
+                self.exit_function_call(target, parameters)
+            }
+            Expression::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => self.exit_if_else(condition, then_branch, else_branch),
+            _ => {
+                if let Some(operator) = expression.top_operator() {
+                    self.exit_operator(operator, expression);
+                }
+            }
+        }
+    }
+
+    fn visit_constant(&mut self, _value: &NumericConstant) {}
+
+    fn visit_comment(&mut self, _comment: &str) {}
+
+    /// Called for a variable reference; `indices` is empty unless the
+    /// reference is subscripted.
+    fn enter_reference(&mut self, _name: &Identifier, _indices: &[Expression]) -> Visit {
+        Visit::Continue
+    }
+
+    fn exit_reference(&mut self, _name: &Identifier, _indices: &[Expression]) {}
+
+    /// Called for parentheses and unary and binary operators.
+    fn enter_operator(&mut self, _operator: Operator, _expression: &Expression) -> Visit {
+        Visit::Continue
+    }
+
+    fn exit_operator(&mut self, _operator: Operator, _expression: &Expression) {}
+
+    fn enter_function_call(
+        &mut self,
+        _target: &FunctionTarget,
+        _parameters: &[Expression],
+    ) -> Visit {
+        Visit::Continue
+    }
+
+    fn exit_function_call(&mut self, _target: &FunctionTarget, _parameters: &[Expression]) {}
+
+    fn enter_if_else(
+        &mut self,
+        _condition: &Expression,
+        _then_branch: &Expression,
+        _else_branch: &Expression,
+    ) -> Visit {
+        Visit::Continue
+    }
+
+    fn exit_if_else(
+        &mut self,
+        _condition: &Expression,
+        _then_branch: &Expression,
+        _else_branch: &Expression,
+    ) {
+    }
+}
+
+/// Rebuilds an expression bottom-up.
+///
+/// [`fold_expression`](ExpressionFolder::fold_expression) folds the children
+/// of a node and then passes the node to
+/// [`rewrite`](ExpressionFolder::rewrite), so `rewrite` always sees children
+/// that are already rewritten. Closures taking and returning an
+/// [`Expression`] are folders that rewrite with the closure.
+pub trait ExpressionFolder {
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        let expression = fold_children(self, expression);
+        self.rewrite(expression)
+    }
+
+    fn rewrite(&mut self, expression: Expression) -> Expression {
+        expression
+    }
+}
+
+impl<F: FnMut(Expression) -> Expression> ExpressionFolder for F {
+    fn rewrite(&mut self, expression: Expression) -> Expression {
+        self(expression)
+    }
+}
+
+/// Walks `expression` depth-first, children in the order they are written.
+pub fn walk<V: ExpressionVisitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    if visitor.enter(expression) == Visit::Continue {
+        for child in expression.children() {
+            walk(visitor, child);
+        }
+    }
+    visitor.exit(expression);
+}
+
+/// Folds each child of `expression` in place, leaving the node itself as it
+/// is.
+pub fn fold_children<F: ExpressionFolder + ?Sized>(
+    folder: &mut F,
+    mut expression: Expression,
+) -> Expression {
+    for child in expression.children_mut() {
+        let folded = folder.fold_expression(mem::replace(child, placeholder()));
+        *child = folded;
+    }
+    expression
+}
+
+fn placeholder() -> Expression {
+    Expression::InlineComment(String::new())
+}
+
+impl Expression {
+    /// The direct subexpressions of this expression, in the order they are
+    /// written.
+    pub fn children(&self) -> Vec<&Expression> {
+        use Expression as E;
+
+        match self {
+            E::Parentheses(inner)
+            | E::UnaryPlus(inner)
+            | E::UnaryMinus(inner)
+            | E::Not(inner)
+            | E::Transpose(inner) => vec![inner],
+            E::Exponentiation(lhs, rhs)
+            | E::Multiply(lhs, rhs)
+            | E::Divide(lhs, rhs)
+            | E::Modulo(lhs, rhs)
+            | E::Add(lhs, rhs)
+            | E::Subtract(lhs, rhs)
+            | E::LessThan(lhs, rhs)
+            | E::LessThanOrEq(lhs, rhs)
+            | E::GreaterThan(lhs, rhs)
+            | E::GreaterThanOrEq(lhs, rhs)
+            | E::Equal(lhs, rhs)
+            | E::NotEqual(lhs, rhs)
+            | E::And(lhs, rhs)
+            | E::Or(lhs, rhs)
+            | E::Range(lhs, rhs) => vec![lhs, rhs],
+            E::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => vec![condition, then_branch, else_branch],
+            E::Subscript(_, indices) => indices.iter().collect(),
+            E::FunctionCall { parameters, .. } => parameters.iter().collect(),
+            E::Constant(_) | E::Wildcard | E::InlineComment(_) => Vec::new(),
+        }
+    }
+
+    /// The direct subexpressions of this expression, mutably.
+    pub fn children_mut(&mut self) -> Vec<&mut Expression> {
+        use Expression as E;
+
+        match self {
+            E::Parentheses(inner)
+            | E::UnaryPlus(inner)
+            | E::UnaryMinus(inner)
+            | E::Not(inner)
+            | E::Transpose(inner) => vec![inner],
+            E::Exponentiation(lhs, rhs)
+            | E::Multiply(lhs, rhs)
+            | E::Divide(lhs, rhs)
+            | E::Modulo(lhs, rhs)
+            | E::Add(lhs, rhs)
+            | E::Subtract(lhs, rhs)
+            | E::LessThan(lhs, rhs)
+            | E::LessThanOrEq(lhs, rhs)
+            | E::GreaterThan(lhs, rhs)
+            | E::GreaterThanOrEq(lhs, rhs)
+            | E::Equal(lhs, rhs)
+            | E::NotEqual(lhs, rhs)
+            | E::And(lhs, rhs)
+            | E::Or(lhs, rhs)
+            | E::Range(lhs, rhs) => vec![lhs, rhs],
+            E::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => vec![condition, then_branch, else_branch],
+            E::Subscript(_, indices) => indices.iter_mut().collect(),
+            E::FunctionCall { parameters, .. } => parameters.iter_mut().collect(),
+            E::Constant(_) | E::Wildcard | E::InlineComment(_) => Vec::new(),
+        }
+    }
+
+    /// Walks this expression with `visitor`. See [`walk`].
+    pub fn walk<V: ExpressionVisitor + ?Sized>(&self, visitor: &mut V) {
+        walk(visitor, self);
+    }
+
+    /// The names of the variables this expression refers to, each once, in
+    /// order of first use. Function names are not included.
+    pub fn references(&self) -> Vec<&Identifier> {
+        fn collect<'a>(expression: &'a Expression, names: &mut Vec<&'a Identifier>) {
+            if let Expression::Subscript(name, _) = expression
+                && !names.contains(&name)
+            {
+                names.push(name);
+            }
+            for child in expression.children() {
+                collect(child, names);
+            }
+        }
+
+        let mut names = Vec::new();
+        collect(self, &mut names);
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::parse_expression as parse;
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl ExpressionVisitor for Trace {
+        fn visit_constant(&mut self, value: &NumericConstant) {
+            self.0.push(value.to_string());
+        }
+
+        fn enter_reference(&mut self, name: &Identifier, _indices: &[Expression]) -> Visit {
+            self.0.push(format!("ref {name}"));
+            Visit::Continue
+        }
+
+        fn enter_operator(&mut self, operator: Operator, _expression: &Expression) -> Visit {
+            self.0.push(format!("enter {operator:?}"));
+            Visit::Continue
+        }
+
+        fn exit_operator(&mut self, operator: Operator, _expression: &Expression) {
+            self.0.push(format!("exit {operator:?}"));
+        }
+
+        fn enter_function_call(
+            &mut self,
+            _target: &FunctionTarget,
+            _parameters: &[Expression],
+        ) -> Visit {
+            self.0.push("call".to_string());
+            Visit::SkipChildren
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let mut trace = Trace::default();
+        parse("a * (b + 2) - MAX(c, d)").walk(&mut trace);
+        assert_eq!(
+            trace.0,
+            [
+                "enter Subtract",
+                "enter Multiply",
+                "ref a",
+                "enter Paren",
+                "enter Add",
+                "ref b",
+                "2",
+                "exit Add",
+                "exit Paren",
+                "exit Multiply",
+                "call",
+                "exit Subtract",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fold_and_references() {
+        let expression = parse("IF x > 0 THEN Birth_Rate * x ELSE birth_rate");
+        let references: Vec<&str> = expression
+            .references()
+            .into_iter()
+            .map(Identifier::normalized)
+            .collect();
+        assert_eq!(references, ["x", "Birth Rate"]);
+
+        let mut rename = |expression: Expression| match expression {
+            Expression::Subscript(name, indices) if name.normalized() == "x" => {
+                Expression::Subscript(Identifier::parse_default("y").unwrap(), indices)
+            }
+            expression => expression,
+        };
+        assert_eq!(
+            rename.fold_expression(expression),
+            parse("IF y > 0 THEN Birth_Rate * y ELSE birth_rate")
+        );
+    }
+}
@@ -0,0 +1,410 @@
+//! A single error type for the crate.
+//!
+//! Each module reports failures with its own error enum, which says exactly
+//! what went wrong. [`Error`] wraps all of them, so applications using
+//! several modules can propagate any of their errors with `?`, and sort them
+//! by [`ErrorCategory`] without matching every variant:
+//!
+//! ```rust
+//! use xmile::{Error, ErrorCategory, Identifier};
+//! use xmile::xml::XmileFile;
+//!
+//! fn load(xml: &str, name: &str) -> Result<usize, Error> {
+//!     let file = XmileFile::from_str(xml)?;
+//!     file.validate()?;
+//!     let name = Identifier::parse_default(name)?;
+//!     Ok(file.variables().filter(|(qualified, _)| qualified.name == name).count())
+//! }
+//!
+//! let error = load("<xmile>", "Population").unwrap_err();
+//! assert_eq!(error.category(), ErrorCategory::Syntax);
+//! assert_eq!(error.category().as_str(), "syntax");
+//! ```
+//!
+//! The wrapped error is displayed as it is, and its
+//! [`source`](std::error::Error::source) chain is preserved.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::analysis::{DesignError, EnsembleError, MassBalanceError, SensitivityError};
+use crate::equation::{IdentifierError, NumericConstantError};
+use crate::explain::ExplainError;
+use crate::import::ImportError;
+use crate::model::vars::flow::FlowConversionError;
+use crate::model::vars::gf::{GraphicalFunctionConversionError, GraphicalFunctionParseError};
+use crate::model::vars::stock::StockConversionError;
+use crate::project::ProjectError;
+use crate::render::RenderError;
+use crate::resource::ResourceError;
+use crate::scenario::ScenarioError;
+#[cfg(feature = "artifacts")]
+use crate::simulation::ArtifactError;
+use crate::simulation::SimulationError;
+use crate::template::TemplateError;
+use crate::testing::assertions::AssertionError;
+use crate::testing::differential::EngineError;
+use crate::xml::errors::XmileError;
+use crate::xml::{LimitError, ParseError};
+
+#[cfg(feature = "views")]
+use crate::cld::CldError;
+#[cfg(feature = "arrays")]
+use crate::containers::array::ArrayError;
+#[cfg(feature = "conveyors")]
+use crate::containers::conveyor::ConveyorError;
+#[cfg(feature = "queues")]
+use crate::containers::queue::QueueError;
+use crate::data::ExportError;
+#[cfg(feature = "arrow")]
+use crate::data::arrow::ArrowExportError;
+#[cfg(feature = "macros")]
+use crate::r#macro::library::LibraryError;
+#[cfg(feature = "modelica")]
+use crate::modelica::ModelicaError;
+#[cfg(feature = "plot")]
+use crate::plot::PlotError;
+#[cfg(feature = "packages")]
+use crate::resource::PackageError;
+#[cfg(feature = "views")]
+use crate::view::EditError;
+#[cfg(feature = "interface-objects")]
+use crate::view::media::MediaError;
+
+/// A `Result` with [`Error`] as the default error type.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Any error reported by the crate.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading or deserializing a document failed.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// Parsing with context or validating a file failed.
+    #[error(transparent)]
+    Xmile(#[from] XmileError),
+    #[error(transparent)]
+    Identifier(#[from] IdentifierError),
+    #[error(transparent)]
+    NumericConstant(#[from] NumericConstantError),
+    #[error(transparent)]
+    GraphicalFunction(#[from] GraphicalFunctionParseError),
+    #[error(transparent)]
+    GraphicalFunctionConversion(#[from] GraphicalFunctionConversionError),
+    #[error(transparent)]
+    StockConversion(#[from] StockConversionError),
+    #[error(transparent)]
+    FlowConversion(#[from] FlowConversionError),
+    #[cfg(feature = "arrays")]
+    #[error(transparent)]
+    Array(#[from] ArrayError),
+    #[cfg(feature = "conveyors")]
+    #[error(transparent)]
+    Conveyor(#[from] ConveyorError),
+    #[cfg(feature = "queues")]
+    #[error(transparent)]
+    Queue(#[from] QueueError),
+    #[error(transparent)]
+    Resource(#[from] ResourceError),
+    /// Reading a model written by another tool failed.
+    #[error(transparent)]
+    Import(#[from] ImportError),
+    #[cfg(feature = "packages")]
+    #[error(transparent)]
+    Package(#[from] PackageError),
+    #[cfg(feature = "interface-objects")]
+    #[error(transparent)]
+    Media(#[from] MediaError),
+    /// An edit to a view was rejected.
+    #[cfg(feature = "views")]
+    #[error(transparent)]
+    Edit(#[from] EditError),
+    /// A causal loop diagram could not be read or is inconsistent.
+    #[cfg(feature = "views")]
+    #[error(transparent)]
+    Cld(#[from] CldError),
+    #[error(transparent)]
+    Export(#[from] ExportError),
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    ArrowExport(#[from] ArrowExportError),
+    #[error(transparent)]
+    Scenario(#[from] ScenarioError),
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+    #[error(transparent)]
+    Project(#[from] ProjectError),
+    #[cfg(feature = "macros")]
+    #[error(transparent)]
+    Library(#[from] LibraryError),
+    #[error(transparent)]
+    Explain(#[from] ExplainError),
+    #[error(transparent)]
+    MassBalance(#[from] MassBalanceError),
+    #[error(transparent)]
+    Ensemble(#[from] EnsembleError),
+    #[error(transparent)]
+    Design(#[from] DesignError),
+    #[error(transparent)]
+    Sensitivity(#[from] SensitivityError),
+    #[error(transparent)]
+    Render(#[from] RenderError),
+    #[cfg(feature = "plot")]
+    #[error(transparent)]
+    Plot(#[from] PlotError),
+    #[cfg(feature = "modelica")]
+    #[error(transparent)]
+    Modelica(#[from] ModelicaError),
+    /// An external simulation engine failed.
+    #[error(transparent)]
+    Simulation(#[from] EngineError),
+    /// A model could not be prepared for running, or a run failed.
+    #[error(transparent)]
+    Simulator(#[from] SimulationError),
+    /// A saved simulator could not be written or loaded.
+    #[cfg(feature = "artifacts")]
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+}
+
+impl From<LimitError> for Error {
+    fn from(error: LimitError) -> Self {
+        Error::Parse(error.into())
+    }
+}
+
+/// The broad kind of an [`Error`].
+///
+/// Categories and their [`as_str`](ErrorCategory::as_str) names are stable,
+/// so they can be used in logs, metrics and status codes. New categories
+/// may be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// Reading or writing a file or stream failed.
+    Io,
+    /// Input is malformed: XML, expressions, identifiers, numbers, or
+    /// scenario, template, test and project manifest text.
+    Syntax,
+    /// Input exceeds the limits set for untrusted documents.
+    Limit,
+    /// Input is well formed but breaks the rules of XMILE.
+    Validation,
+    /// A variable or graphical function cannot be converted to the form
+    /// asked for.
+    Conversion,
+    /// A referenced resource or package is missing or unusable.
+    Resource,
+    /// Results could not be exported.
+    Export,
+    /// A simulation failed or produced unusable results.
+    Simulation,
+    /// The caller asked for something the model or results do not have,
+    /// such as an unknown variable.
+    Usage,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Io => "io",
+            ErrorCategory::Syntax => "syntax",
+            ErrorCategory::Limit => "limit",
+            ErrorCategory::Validation => "validation",
+            ErrorCategory::Conversion => "conversion",
+            ErrorCategory::Resource => "resource",
+            ErrorCategory::Export => "export",
+            ErrorCategory::Simulation => "simulation",
+            ErrorCategory::Usage => "usage",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Error {
+    pub fn category(&self) -> ErrorCategory {
+        use ErrorCategory as C;
+
+        match self {
+            Error::Parse(error) => parse_category(error),
+            Error::Xmile(error) => xmile_category(error),
+            Error::Identifier(_) | Error::NumericConstant(_) | Error::GraphicalFunction(_) => {
+                C::Syntax
+            }
+            Error::GraphicalFunctionConversion(_)
+            | Error::StockConversion(_)
+            | Error::FlowConversion(_) => C::Conversion,
+            #[cfg(feature = "arrays")]
+            Error::Array(_) => C::Validation,
+            #[cfg(feature = "conveyors")]
+            Error::Conveyor(_) => C::Validation,
+            #[cfg(feature = "queues")]
+            Error::Queue(_) => C::Validation,
+            Error::Resource(ResourceError::Io { .. }) => C::Io,
+            Error::Resource(_) => C::Resource,
+            Error::Import(error) => match error {
+                ImportError::Io(_) => C::Io,
+                ImportError::Parse(error) => parse_category(error),
+                ImportError::UnknownFormat(_) => C::Usage,
+                ImportError::Unsupported { .. } => C::Conversion,
+            },
+            #[cfg(feature = "packages")]
+            Error::Package(error) => match error {
+                PackageError::Io(_) => C::Io,
+                PackageError::Parse(error) => parse_category(error),
+                PackageError::Zip(_)
+                | PackageError::NoMainDocument
+                | PackageError::MissingEntry(_)
+                | PackageError::InvalidUtf8(_) => C::Resource,
+            },
+            #[cfg(feature = "interface-objects")]
+            Error::Media(MediaError::Resource(ResourceError::Io { .. })) => C::Io,
+            #[cfg(feature = "interface-objects")]
+            Error::Media(_) => C::Resource,
+            #[cfg(feature = "views")]
+            Error::Edit(_) => C::Usage,
+            #[cfg(feature = "views")]
+            Error::Cld(error) => match error {
+                CldError::Parse(error) => parse_category(error),
+                CldError::InvalidName(_) => C::Syntax,
+                _ => C::Validation,
+            },
+            Error::Export(ExportError::Io(_)) => C::Io,
+            Error::Export(_) => C::Export,
+            #[cfg(feature = "arrow")]
+            Error::ArrowExport(_) => C::Export,
+            Error::Scenario(error) => match error {
+                ScenarioError::Io { .. } => C::Io,
+                ScenarioError::Parse(_) => C::Syntax,
+                ScenarioError::Serialize(_) => C::Export,
+                ScenarioError::InvalidGraphicalFunction { .. } | ScenarioError::Resolve(_) => {
+                    C::Validation
+                }
+                _ => C::Usage,
+            },
+            Error::Template(error) => match error {
+                TemplateError::UnclosedPlaceholder { .. } | TemplateError::Parse { .. } => {
+                    C::Syntax
+                }
+                TemplateError::DuplicateVariable { .. } => C::Validation,
+                TemplateError::UnknownTemplate(_) | TemplateError::MissingParameter { .. } => {
+                    C::Usage
+                }
+            },
+            Error::Project(error) => match error {
+                ProjectError::Io { .. }
+                | ProjectError::Scenario {
+                    source: ScenarioError::Io { .. },
+                    ..
+                }
+                | ProjectError::Tests {
+                    source: AssertionError::Io { .. },
+                    ..
+                } => C::Io,
+                ProjectError::Model { source, .. } => parse_category(source),
+                ProjectError::Manifest(_)
+                | ProjectError::Scenario { .. }
+                | ProjectError::Tests { .. } => C::Syntax,
+                ProjectError::DuplicateModel(_)
+                | ProjectError::UnknownModel { .. }
+                | ProjectError::Macros { .. } => C::Validation,
+            },
+            #[cfg(feature = "macros")]
+            Error::Library(error) => match error {
+                LibraryError::Parse(error) => parse_category(error),
+                LibraryError::InvalidVersion { .. } => C::Syntax,
+                LibraryError::MissingDependency { .. }
+                | LibraryError::IncompatibleVersion { .. } => C::Resource,
+                _ => C::Validation,
+            },
+            Error::Explain(_)
+            | Error::MassBalance(_)
+            | Error::Ensemble(_)
+            | Error::Design(_)
+            | Error::Sensitivity(_)
+            | Error::Render(_) => C::Usage,
+            #[cfg(feature = "plot")]
+            Error::Plot(PlotError::Io(_)) => C::Io,
+            #[cfg(feature = "plot")]
+            Error::Plot(_) => C::Usage,
+            #[cfg(feature = "modelica")]
+            Error::Modelica(_) => C::Usage,
+            Error::Simulation(EngineError::Io { .. }) => C::Io,
+            Error::Simulation(_) => C::Simulation,
+            Error::Simulator(error) => match error {
+                SimulationError::Export(ExportError::Io(_)) => C::Io,
+                SimulationError::Export(_) => C::Export,
+                SimulationError::Unsupported { .. } => C::Simulation,
+                _ => C::Validation,
+            },
+            #[cfg(feature = "artifacts")]
+            Error::Artifact(_) => C::Resource,
+        }
+    }
+}
+
+fn parse_category(error: &ParseError) -> ErrorCategory {
+    match error {
+        ParseError::Io(_) => ErrorCategory::Io,
+        ParseError::Xml(_) | ParseError::Deserialize(_) => ErrorCategory::Syntax,
+        ParseError::Limit(_) => ErrorCategory::Limit,
+    }
+}
+
+/// The category of a combined error is that of its first error.
+fn xmile_category(error: &XmileError) -> ErrorCategory {
+    match error {
+        XmileError::Io(_) => ErrorCategory::Io,
+        XmileError::Xml { .. } | XmileError::Deserialize { .. } => ErrorCategory::Syntax,
+        XmileError::Validation(_) => ErrorCategory::Validation,
+        XmileError::Multiple(errors) => errors
+            .first()
+            .map_or(ErrorCategory::Validation, xmile_category),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn test_categories() {
+        let error: Error = crate::xml::XmileFile::from_str("<xmile>")
+            .unwrap_err()
+            .into();
+        assert_eq!(error.category(), ErrorCategory::Syntax);
+
+        let error: Error = LimitError::DocType.into();
+        assert_eq!(error.category(), ErrorCategory::Limit);
+
+        let error: Error = ExplainError::UnknownVariable("x".to_string()).into();
+        assert_eq!(error.category(), ErrorCategory::Usage);
+        assert_eq!(error.to_string(), "Unknown variable: x");
+
+        let error: Error = StockConversionError::MissingConveyorLength.into();
+        assert_eq!(error.category().to_string(), "conversion");
+    }
+
+    #[test]
+    fn test_source_chain_is_preserved() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing.xmile");
+        let error: Error = ResourceError::Io {
+            resource: "missing.xmile".to_string(),
+            source: io,
+        }
+        .into();
+        assert_eq!(error.category(), ErrorCategory::Io);
+        assert!(error.to_string().starts_with("IO error reading resource"));
+        let source = error.source().expect("the IO error is the source");
+        assert_eq!(source.to_string(), "missing.xmile");
+    }
+}
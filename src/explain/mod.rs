@@ -0,0 +1,546 @@
+//! Causal trace explanations of simulation results.
+//!
+//! [`explain`] answers questions such as "why is Population high at t = 20?"
+//! by walking the dependency graph of a model from one variable back through
+//! the variables its equation uses, reading each value from the stored run
+//! data at that step. Every input in the resulting [`Explanation`] tree
+//! carries its value and its contribution to the variable above it.
+//!
+//! Contributions are linearised: the contribution of an input `x` to `f` is
+//! `∂f/∂x · x`, estimated by central differences around the stored values.
+//! For additive equations this is exactly each term; for products each
+//! factor is attributed the whole value, so relative contributions (each
+//! contribution over the sum of their magnitudes) share it evenly. The inputs
+//! of a stock are its flows, and their contributions are to its net flow.
+//!
+//! Feedback loops are cut where a variable would appear twice on the same
+//! path, so every explanation is finite.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::data::ExportData;
+use crate::equation::expression::function::FunctionTarget;
+use crate::model::vars::Variable;
+use crate::model::vars::gf::GraphicalFunction;
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+use crate::{Expression, Identifier};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ExplainError {
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String),
+    #[error("No step was saved at time {0}")]
+    TimeNotSaved(f64),
+}
+
+/// A variable's value at one step and the inputs that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// The name of the variable.
+    pub name: String,
+    /// The stored value of the variable, if the run data includes it.
+    pub value: Option<f64>,
+    /// The linearised contribution to the parent variable, in its units.
+    pub contribution: Option<f64>,
+    /// The contribution as a share of all contributions to the parent,
+    /// between -1 and 1.
+    pub relative_contribution: Option<f64>,
+    /// The variables this variable's equation uses.
+    pub inputs: Vec<Explanation>,
+}
+
+impl Explanation {
+    /// Returns the inputs ordered by the magnitude of their contribution,
+    /// largest first.
+    pub fn ranked_inputs(&self) -> Vec<&Explanation> {
+        let mut inputs: Vec<&Explanation> = self.inputs.iter().collect();
+        inputs.sort_by(|a, b| {
+            let a = a.contribution.map_or(0.0, f64::abs);
+            let b = b.contribution.map_or(0.0, f64::abs);
+            b.total_cmp(&a)
+        });
+        inputs
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.name, indent = depth * 2)?;
+        match self.value {
+            Some(value) => write!(f, " = {value}")?,
+            None => write!(f, " = ?")?,
+        }
+        if let Some(share) = self.relative_contribution {
+            write!(f, " ({:+.1}%)", share * 100.0)?;
+        }
+        writeln!(f)?;
+        for input in &self.inputs {
+            input.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Explains the value of `variable` at `time` from the stored run `data`.
+///
+/// `time` must be one of the saved times of `data`. The explanation follows
+/// inputs until a feedback loop closes; use [`explain_to_depth`] to stop
+/// sooner.
+pub fn explain(
+    model: &Model,
+    data: &ExportData,
+    variable: &str,
+    time: f64,
+) -> Result<Explanation, ExplainError> {
+    explain_to_depth(model, data, variable, time, usize::MAX)
+}
+
+/// Explains the value of `variable` at `time`, following inputs at most
+/// `max_depth` levels deep.
+pub fn explain_to_depth(
+    model: &Model,
+    data: &ExportData,
+    variable: &str,
+    time: f64,
+    max_depth: usize,
+) -> Result<Explanation, ExplainError> {
+    let name = Identifier::parse_from_attribute(variable)
+        .map_err(|_| ExplainError::UnknownVariable(variable.to_string()))?;
+    if find_variable(model, &name).is_none() {
+        return Err(ExplainError::UnknownVariable(variable.to_string()));
+    }
+    let step = data
+        .times
+        .iter()
+        .position(|saved| (saved - time).abs() <= 1e-9 * time.abs().max(1.0))
+        .ok_or(ExplainError::TimeNotSaved(time))?;
+
+    let dt = model
+        .sim_specs
+        .as_ref()
+        .and_then(|specs| specs.dt)
+        .or_else(|| {
+            let next = data.times.get(step + 1).map(|next| next - data.times[step]);
+            let previous = step
+                .checked_sub(1)
+                .map(|previous| data.times[step] - data.times[previous]);
+            next.or(previous)
+        });
+    let tracer = Tracer {
+        model,
+        data,
+        step,
+        scope: Scope {
+            time,
+            dt,
+            start: data.times.first().copied().unwrap_or(time),
+            stop: data.times.last().copied().unwrap_or(time),
+        },
+    };
+    let mut path = Vec::new();
+    Ok(tracer.trace(&name, &mut path, max_depth))
+}
+
+/// The builtin values available to equations.
+#[derive(Debug, Clone, Copy)]
+struct Scope {
+    time: f64,
+    dt: Option<f64>,
+    start: f64,
+    stop: f64,
+}
+
+struct Tracer<'a> {
+    model: &'a Model,
+    data: &'a ExportData,
+    step: usize,
+    scope: Scope,
+}
+
+impl<'a> Tracer<'a> {
+    fn trace(&self, name: &Identifier, path: &mut Vec<Identifier>, depth: usize) -> Explanation {
+        let mut explanation = Explanation {
+            name: name.normalized().to_string(),
+            value: self.value(name),
+            contribution: None,
+            relative_contribution: None,
+            inputs: Vec::new(),
+        };
+        if depth == 0 || path.contains(name) {
+            return explanation;
+        }
+        let Some(equation) = find_variable(self.model, name).and_then(|v| self.equation(v)) else {
+            return explanation;
+        };
+
+        let mut inputs = Vec::new();
+        equation.references(self.model, &mut inputs);
+        let values: Vec<Option<f64>> = inputs.iter().map(|input| self.value(input)).collect();
+        let contributions = self.contributions(&equation, &inputs, &values);
+        let total: f64 = contributions.iter().flatten().map(|c| c.abs()).sum();
+
+        path.push(name.clone());
+        for (input, contribution) in inputs.iter().zip(contributions) {
+            let mut node = self.trace(input, path, depth - 1);
+            node.contribution = contribution;
+            node.relative_contribution =
+                contribution.map(|c| if total > 0.0 { c / total } else { 0.0 });
+            explanation.inputs.push(node);
+        }
+        path.pop();
+        explanation
+    }
+
+    /// Returns the stored value of a variable at the explained step.
+    fn value(&self, name: &Identifier) -> Option<f64> {
+        self.data
+            .series(name.normalized())
+            .and_then(|values| values.get(self.step).copied())
+    }
+
+    fn equation(&self, variable: &'a Variable) -> Option<Equation<'a>> {
+        match variable {
+            Variable::Auxiliary(aux) => Some(Equation::Expression(aux.equation.clone())),
+            Variable::Flow(flow) => flow.equation.clone().map(Equation::Expression),
+            Variable::Stock(stock) => {
+                Some(Equation::Expression(stock.net_flow_expression(self.model)))
+            }
+            Variable::GraphicalFunction(gf) => {
+                gf.equation.clone().map(|input| Equation::Lookup(gf, input))
+            }
+            #[cfg(feature = "submodels")]
+            Variable::Module(_) => None,
+            Variable::Group(_) => None,
+        }
+    }
+
+    /// Estimates `∂f/∂x · x` for each input by central differences.
+    fn contributions(
+        &self,
+        equation: &Equation,
+        inputs: &[Identifier],
+        values: &[Option<f64>],
+    ) -> Vec<Option<f64>> {
+        if values.iter().any(Option::is_none) {
+            return vec![None; inputs.len()];
+        }
+        let values: Vec<f64> = values.iter().flatten().copied().collect();
+        (0..inputs.len())
+            .map(|index| {
+                let value = values[index];
+                let h = 1e-6 * value.abs().max(1.0);
+                let at = |offset: f64| {
+                    let lookup = |name: &Identifier| {
+                        let position = inputs.iter().position(|input| input == name)?;
+                        let base = values[position];
+                        Some(if position == index {
+                            base + offset
+                        } else {
+                            base
+                        })
+                    };
+                    equation.evaluate(self.model, &self.scope, &lookup)
+                };
+                let slope = (at(h)? - at(-h)?) / (2.0 * h);
+                Some(slope * value).filter(|c| c.is_finite())
+            })
+            .collect()
+    }
+}
+
+/// How a variable's value is computed from its inputs.
+enum Equation<'a> {
+    Expression(Expression),
+    /// A graphical function applied to its input equation.
+    Lookup(&'a GraphicalFunction, Expression),
+}
+
+impl Equation<'_> {
+    fn references(&self, model: &Model, names: &mut Vec<Identifier>) {
+        match self {
+            Equation::Expression(expression) | Equation::Lookup(_, expression) => {
+                collect_references(expression, model, names)
+            }
+        }
+    }
+
+    fn evaluate(
+        &self,
+        model: &Model,
+        scope: &Scope,
+        lookup: &dyn Fn(&Identifier) -> Option<f64>,
+    ) -> Option<f64> {
+        let evaluator = Evaluator {
+            model,
+            scope,
+            lookup,
+        };
+        match self {
+            Equation::Expression(expression) => evaluator.evaluate(expression),
+            Equation::Lookup(gf, input) => Some(gf.evaluate(evaluator.evaluate(input)?)),
+        }
+    }
+}
+
+fn find_variable<'a>(model: &'a Model, name: &Identifier) -> Option<&'a Variable> {
+    model
+        .variables
+        .variables
+        .iter()
+        .find(|variable| get_variable_name(variable) == Some(name))
+}
+
+fn find_graphical_function<'a>(
+    model: &'a Model,
+    name: &Identifier,
+) -> Option<&'a GraphicalFunction> {
+    match find_variable(model, name)? {
+        Variable::GraphicalFunction(gf) => Some(gf),
+        _ => None,
+    }
+}
+
+/// Collects the model variables an expression refers to, in order of first
+/// use. Graphical functions called with an argument are not inputs
+/// themselves; the variables in the argument are.
+fn collect_references(expression: &Expression, model: &Model, names: &mut Vec<Identifier>) {
+    for name in expression.references() {
+        if find_variable(model, name).is_some() && !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+}
+
+/// Evaluates scalar equations at one step of a run.
+///
+/// Arrays, submodels and stateful builtins such as delays cannot be
+/// evaluated from a single step, so their value is unknown.
+struct Evaluator<'a> {
+    model: &'a Model,
+    scope: &'a Scope,
+    lookup: &'a dyn Fn(&Identifier) -> Option<f64>,
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&self, expression: &Expression) -> Option<f64> {
+        let truth = |value: bool| if value { 1.0 } else { 0.0 };
+        let binary =
+            |lhs: &Expression, rhs: &Expression| Some((self.evaluate(lhs)?, self.evaluate(rhs)?));
+        match expression {
+            Expression::Constant(constant) => Some(constant.0),
+            Expression::Subscript(name, indices) if indices.is_empty() => {
+                (self.lookup)(name).or_else(|| self.builtin_value(name))
+            }
+            Expression::Subscript(..)
+            | Expression::Wildcard
+            | Expression::Range(..)
+            | Expression::Transpose(_)
+            | Expression::InlineComment(_) => None,
+            Expression::Parentheses(inner) | Expression::UnaryPlus(inner) => self.evaluate(inner),
+            Expression::UnaryMinus(inner) => Some(-self.evaluate(inner)?),
+            Expression::Not(inner) => Some(truth(self.evaluate(inner)? == 0.0)),
+            Expression::Exponentiation(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a.powf(b)),
+            Expression::Multiply(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a * b),
+            Expression::Divide(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a / b),
+            Expression::Modulo(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a.rem_euclid(b)),
+            Expression::Add(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a + b),
+            Expression::Subtract(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| a - b),
+            Expression::LessThan(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a < b)),
+            Expression::LessThanOrEq(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a <= b)),
+            Expression::GreaterThan(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a > b)),
+            Expression::GreaterThanOrEq(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a >= b)),
+            Expression::Equal(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a == b)),
+            Expression::NotEqual(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a != b)),
+            Expression::And(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a != 0.0 && b != 0.0)),
+            Expression::Or(lhs, rhs) => binary(lhs, rhs).map(|(a, b)| truth(a != 0.0 || b != 0.0)),
+            Expression::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.evaluate(condition)? != 0.0 {
+                    self.evaluate(then_branch)
+                } else {
+                    self.evaluate(else_branch)
+                }
+            }
+            Expression::FunctionCall { target, parameters } => self.call(target, parameters),
+        }
+    }
+
+    fn builtin_value(&self, name: &Identifier) -> Option<f64> {
+        match name.normalized().to_ascii_uppercase().as_str() {
+            "TIME" => Some(self.scope.time),
+            "DT" => self.scope.dt,
+            "STARTTIME" => Some(self.scope.start),
+            "STOPTIME" => Some(self.scope.stop),
+            "PI" => Some(std::f64::consts::PI),
+            _ => None,
+        }
+    }
+
+    fn call(&self, target: &FunctionTarget, parameters: &[Expression]) -> Option<f64> {
+        let name = match target {
+            FunctionTarget::Function(name) | FunctionTarget::GraphicalFunction(name) => name,
+            FunctionTarget::Model(_) | FunctionTarget::Array(_) => return None,
+        };
+        let arguments = parameters
+            .iter()
+            .map(|parameter| self.evaluate(parameter))
+            .collect::<Option<Vec<f64>>>()?;
+
+        if let Some(gf) = find_graphical_function(self.model, name) {
+            return match arguments.as_slice() {
+                [x] => Some(gf.evaluate(*x)),
+                _ => None,
+            };
+        }
+        let unary = |f: fn(f64) -> f64| match arguments.as_slice() {
+            [x] => Some(f(*x)),
+            _ => None,
+        };
+        match name.normalized().to_ascii_uppercase().as_str() {
+            "ABS" => unary(f64::abs),
+            "ARCCOS" => unary(f64::acos),
+            "ARCSIN" => unary(f64::asin),
+            "ARCTAN" => unary(f64::atan),
+            "COS" => unary(f64::cos),
+            "SIN" => unary(f64::sin),
+            "TAN" => unary(f64::tan),
+            "EXP" => unary(f64::exp),
+            "LN" => unary(f64::ln),
+            "LOG10" => unary(f64::log10),
+            "SQRT" => unary(f64::sqrt),
+            "INT" => unary(f64::floor),
+            "MAX" => arguments.iter().copied().reduce(f64::max),
+            "MIN" => arguments.iter().copied().reduce(f64::min),
+            "SAFEDIV" => match arguments.as_slice() {
+                [a, b] => Some(if *b == 0.0 { 0.0 } else { a / b }),
+                [a, b, fallback] => Some(if *b == 0.0 { *fallback } else { a / b }),
+                _ => None,
+            },
+            "TIME" | "DT" | "STARTTIME" | "STOPTIME" | "PI" if arguments.is_empty() => {
+                self.builtin_value(name)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de::from_str;
+
+    fn model() -> Model {
+        from_str(
+            r#"
+            <model>
+                <variables>
+                    <stock name="Population">
+                        <eqn>100</eqn>
+                        <inflow>births</inflow>
+                        <outflow>deaths</outflow>
+                    </stock>
+                    <flow name="births">
+                        <eqn>Population * birth_rate</eqn>
+                    </flow>
+                    <flow name="deaths">
+                        <eqn>Population / lifetime</eqn>
+                    </flow>
+                    <aux name="birth rate">
+                        <eqn>0.1 * crowding(Population / 1000)</eqn>
+                    </aux>
+                    <aux name="lifetime">
+                        <eqn>20</eqn>
+                    </aux>
+                    <gf name="crowding">
+                        <xscale min="0" max="1"/>
+                        <ypts>1,0</ypts>
+                    </gf>
+                </variables>
+            </model>
+            "#,
+        )
+        .expect("Failed to parse model")
+    }
+
+    fn data() -> ExportData {
+        ExportData::new(vec![0.0, 1.0])
+            .with_series("Population", vec![100.0, 104.0])
+            .with_series("births", vec![9.0, 9.3184])
+            .with_series("deaths", vec![5.0, 5.2])
+            .with_series("birth rate", vec![0.09, 0.0896])
+            .with_series("lifetime", vec![20.0, 20.0])
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("Expected a value");
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn test_explain_stock() {
+        let explanation = explain(&model(), &data(), "Population", 1.0).unwrap();
+        assert_eq!(explanation.value, Some(104.0));
+        assert_eq!(explanation.contribution, None);
+
+        let [births, deaths] = explanation.inputs.as_slice() else {
+            panic!("Expected births and deaths");
+        };
+        assert_eq!(births.name, "births");
+        assert_close(births.contribution, 9.3184);
+        assert_close(deaths.contribution, -5.2);
+        assert_close(births.relative_contribution, 9.3184 / 14.5184);
+        assert_eq!(explanation.ranked_inputs()[0].name, "births");
+
+        // births = Population * birth rate attributes the whole value to both
+        let [population, birth_rate] = births.inputs.as_slice() else {
+            panic!("Expected Population and birth rate");
+        };
+        assert_eq!(population.name, "Population");
+        assert!(population.inputs.is_empty(), "The loop should be cut");
+        assert_close(population.relative_contribution, 0.5);
+        assert_close(birth_rate.contribution, 9.3184);
+
+        // The graphical function is applied, not an input
+        assert_eq!(birth_rate.inputs.len(), 1);
+        assert_eq!(birth_rate.inputs[0].name, "Population");
+    }
+
+    #[test]
+    fn test_explain_depth_and_errors() {
+        let explanation = explain_to_depth(&model(), &data(), "births", 0.0, 1).unwrap();
+        assert_eq!(explanation.value, Some(9.0));
+        assert!(
+            explanation
+                .inputs
+                .iter()
+                .all(|input| input.inputs.is_empty())
+        );
+        assert_eq!(
+            explanation.to_string(),
+            "births = 9\n  Population = 100 (+50.0%)\n  birth rate = 0.09 (+50.0%)\n"
+        );
+
+        assert!(matches!(
+            explain(&model(), &data(), "growth", 0.0),
+            Err(ExplainError::UnknownVariable(_))
+        ));
+        assert!(matches!(
+            explain(&model(), &data(), "births", 0.5),
+            Err(ExplainError::TimeNotSaved(_))
+        ));
+    }
+}
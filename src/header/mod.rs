@@ -65,6 +65,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::resource::ResourceRef;
+use crate::xml::serialize;
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Header {
     /// The vendor/company name.
@@ -72,32 +75,46 @@ pub struct Header {
     /// The product information (name, version, and language).
     pub product: Product,
     /// The options for the header.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<Options>,
     /// The name of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// The version information for the model.
+    #[serde(rename = "version", skip_serializing_if = "Option::is_none")]
     pub version_info: Option<String>,
     /// The caption for the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub caption: Option<String>,
     /// The image for the model.
-    pub image: Option<String>, // Resource path or Data URI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
     /// The author of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
     /// The affiliation of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub affiliation: Option<String>,
     /// The client of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client: Option<String>,
     /// The copyright information for the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub copyright: Option<String>,
     /// The contact information for the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contact: Option<Contact>,
     /// The creation date of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub created: Option<String>, // ISO 8601 format
     /// The last modified date of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub modified: Option<String>, // ISO 8601 format
     /// The universally unique ID of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uuid: Option<String>, // IETF RFC4122 format
     /// The list of included files or URLs.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub includes: Option<Includes>,
 }
 
@@ -115,7 +132,18 @@ pub struct Include {
     /// The resource path (URL, relative path, or absolute path).
     /// Can include wildcards (e.g., "macros/*.xml").
     #[serde(rename = "@resource")]
-    pub resource: String,
+    pub resource: ResourceRef,
+}
+
+/// A picture of the model from the <image> tag.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Image {
+    /// The resource the picture is loaded from (URL, relative path, or absolute path).
+    #[serde(rename = "@resource", skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ResourceRef>,
+    /// The picture embedded as a base64 Data URI.
+    #[serde(rename = "$text", skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
 }
 
 /// Product information from the <product> tag.
@@ -125,106 +153,165 @@ pub struct Product {
     #[serde(rename = "@version")]
     pub version: String,
     /// The language code (optional attribute).
-    #[serde(rename = "@lang")]
+    #[serde(rename = "@lang", skip_serializing_if = "Option::is_none")]
     pub lang: Option<String>,
     /// The product name (text content of the tag).
-    /// In serde-xml-rs, text content is typically the field name or can be accessed via #text
-    #[serde(rename = "#text")]
+    #[serde(rename = "$text")]
     pub name: String,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Options {
     /// The namespace for the options.
+    #[serde(rename = "@namespace", skip_serializing_if = "Option::is_none")]
     pub namespace: Option<String>,
     /// Indicates whether conveyors are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_conveyor: Option<UsesConveyor>,
     /// Indicates whether queues are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_queue: Option<UsesQueue>,
     /// Indicates whether arrays are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_arrays: Option<UsesArrays>,
     /// Indicates whether submodels are used.
+    #[serde(default, with = "flag", skip_serializing_if = "flag::is_unset")]
     pub uses_submodels: Option<bool>,
     /// Indicates whether macros are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_macros: Option<UsesMacros>,
     /// Indicates whether event posters are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_event_posters: Option<UsesEventPosters>,
     /// Indicates whether model views are present.
+    #[serde(default, with = "flag", skip_serializing_if = "flag::is_unset")]
     pub has_model_view: Option<bool>,
     /// Indicates whether outputs are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_outputs: Option<UsesOutputs>,
     /// Indicates whether inputs are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uses_inputs: Option<UsesInputs>,
     /// Indicates whether annotations are used.
+    #[serde(default, with = "flag", skip_serializing_if = "flag::is_unset")]
     pub uses_annotation: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesConveyor {
     /// Indicates whether arrest is used.
+    #[serde(rename = "@arrest", skip_serializing_if = "Option::is_none")]
     pub arrest: Option<bool>,
     /// Indicates whether leakages are used.
+    #[serde(rename = "@leak", skip_serializing_if = "Option::is_none")]
     pub leak: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesQueue {
     /// Indicates whether overflow is used.
+    #[serde(rename = "@overflow", skip_serializing_if = "serialize::skip_false")]
     pub overflow: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesArrays {
     /// The maximum dimensions used by any variable in the whole-model.
+    #[serde(rename = "@maximum_dimensions")]
     pub maximum_dimensions: usize,
     /// The value returned when an index is invalid.
+    #[serde(
+        rename = "@invalid_index_value",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub invalid_index_value: Option<String>, // NaN/0
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesMacros {
     /// Indicates whether recursive macros are used.
+    #[serde(rename = "@recursive_macros")]
     pub recursive_macros: bool,
     /// Indicates whether option filters are defined.
+    #[serde(rename = "@option_filters")]
     pub option_filters: bool,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesEventPosters {
     /// Indicates whether messages are used.
+    #[serde(rename = "@messages", skip_serializing_if = "Option::is_none")]
     pub messages: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesOutputs {
     /// Indicates whether numeric display is used.
+    #[serde(rename = "@numeric_display", skip_serializing_if = "Option::is_none")]
     pub numeric_display: Option<bool>,
     /// Indicates whether lamps are used.
+    #[serde(rename = "@lamp", skip_serializing_if = "Option::is_none")]
     pub lamp: Option<bool>,
     /// Indicates whether gauges are used.
+    #[serde(rename = "@gauge", skip_serializing_if = "Option::is_none")]
     pub gauge: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct UsesInputs {
     /// Indicates whether numeric input is used.
+    #[serde(rename = "@numeric_input", skip_serializing_if = "Option::is_none")]
     pub numeric_input: Option<bool>,
     /// Indicates whether list input is used.
+    #[serde(rename = "@list", skip_serializing_if = "Option::is_none")]
     pub list: Option<bool>,
     /// Indicates whether graphical input is used.
+    #[serde(rename = "@graphical_input", skip_serializing_if = "Option::is_none")]
     pub graphical_input: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Contact {
     /// The address of the contact.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<String>,
     /// The phone number of the contact.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub phone: Option<String>,
     /// The fax number of the contact.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fax: Option<String>,
     /// The email of the contact.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
     /// The website of the contact.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub website: Option<String>,
 }
+
+/// Serde adapter for presence-only options such as `<has_model_view/>`.
+///
+/// The tag carries no content, so its presence deserializes to `Some(true)` and
+/// `Some(true)` serializes back to an empty tag.
+mod flag {
+    use serde::{Deserialize, Deserializer, Serializer, de::IgnoredAny};
+
+    pub fn serialize<S>(_value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_unit()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        IgnoredAny::deserialize(deserializer)?;
+        Ok(Some(true))
+    }
+
+    pub fn is_unset(value: &Option<bool>) -> bool {
+        !value.unwrap_or(false)
+    }
+}
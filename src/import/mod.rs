@@ -0,0 +1,396 @@
+//! Importing models written by other tools.
+//!
+//! Each format is read by an [`Importer`], which reports how confident it
+//! is that it can read a file with [`Importer::can_import`]. An
+//! [`ImporterRegistry`] holds the importers available and hands each file
+//! to the most confident one, so a new format is supported by registering
+//! an importer for it:
+//!
+//! ```rust,no_run
+//! use xmile::import::ImporterRegistry;
+//!
+//! let registry = ImporterRegistry::with_defaults();
+//! let file = registry.import("models/population.stmx").unwrap();
+//! println!("{} models", file.models.len());
+//! ```
+//!
+//! [`ImporterRegistry::with_defaults`] registers importers for XMILE, for
+//! Powersim Studio XML and for Simile exports. The Powersim and Simile
+//! importers recognize their formats but cannot yet translate them, and
+//! report [`ImportError::Unsupported`].
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::xml::{ParseError, XmileFile};
+
+/// The namespace declared by XMILE 1.0 documents.
+const XMILE_NAMESPACE: &str = "http://docs.oasis-open.org/xmile/ns/XMILE/v1.0";
+
+/// The number of bytes at the start of a file read to recognize its format.
+pub const SNIFF_LENGTH: usize = 4096;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("No importer recognizes {}", .0.display())]
+    UnknownFormat(PathBuf),
+    #[error("Importing {format} files is not supported yet: {reason}")]
+    Unsupported { format: String, reason: String },
+}
+
+/// How confident an importer is that it can read a file.
+///
+/// Confidences are ordered, from [`Confidence::None`] to
+/// [`Confidence::Certain`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    /// The file is not in the importer's format.
+    None,
+    /// Only the extension of the file suggests the importer's format.
+    Low,
+    /// The content of the file resembles the importer's format.
+    Medium,
+    /// The file declares the importer's format, as by a namespace or a
+    /// signature.
+    Certain,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Confidence::None => "none",
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::Certain => "certain",
+        })
+    }
+}
+
+/// Reads models in one format into XMILE.
+pub trait Importer: Send + Sync {
+    /// The name of the format, such as `"Powersim"`.
+    fn format(&self) -> &str;
+
+    /// The extensions, without the dot, of files in the format.
+    fn extensions(&self) -> &[&str];
+
+    /// How confident the importer is that it can read the file at `path`.
+    ///
+    /// Probing reads at most the first [`SNIFF_LENGTH`] bytes of the file;
+    /// files that cannot be read are probed by their extension alone.
+    fn can_import(&self, path: &Path) -> Confidence;
+
+    /// Reads the file at `path` into an XMILE file.
+    fn import(&self, path: &Path) -> Result<XmileFile, ImportError>;
+}
+
+/// The importers available for reading models, in registration order.
+#[derive(Default)]
+pub struct ImporterRegistry {
+    importers: Vec<Box<dyn Importer>>,
+}
+
+impl ImporterRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry of the importers provided by the crate: XMILE, Powersim
+    /// and Simile.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(XmileImporter);
+        registry.register(PowersimImporter);
+        registry.register(SimileImporter);
+        registry
+    }
+
+    /// Adds `importer` to the registry. Of importers equally confident
+    /// about a file, the first registered is chosen.
+    pub fn register(&mut self, importer: impl Importer + 'static) {
+        self.importers.push(Box::new(importer));
+    }
+
+    pub fn importers(&self) -> impl Iterator<Item = &dyn Importer> {
+        self.importers.iter().map(|importer| importer.as_ref())
+    }
+
+    /// The importer most confident it can read the file at `path`, with its
+    /// confidence, or `None` if no importer recognizes the file.
+    pub fn probe(&self, path: impl AsRef<Path>) -> Option<(&dyn Importer, Confidence)> {
+        let path = path.as_ref();
+        let mut best: Option<(&dyn Importer, Confidence)> = None;
+        for importer in self.importers() {
+            let confidence = importer.can_import(path);
+            if confidence > best.map_or(Confidence::None, |(_, best)| best) {
+                best = Some((importer, confidence));
+            }
+        }
+        best
+    }
+
+    /// Reads the file at `path` with the importer most confident it can.
+    pub fn import(&self, path: impl AsRef<Path>) -> Result<XmileFile, ImportError> {
+        let path = path.as_ref();
+        let (importer, _) = self
+            .probe(path)
+            .ok_or_else(|| ImportError::UnknownFormat(path.to_path_buf()))?;
+        importer.import(path)
+    }
+}
+
+impl fmt::Debug for ImporterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.importers().map(Importer::format))
+            .finish()
+    }
+}
+
+/// Imports XMILE files, including Stella and iThink files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmileImporter;
+
+impl Importer for XmileImporter {
+    fn format(&self) -> &str {
+        "XMILE"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        crate::convert::DEFAULT_EXTENSIONS
+    }
+
+    fn can_import(&self, path: &Path) -> Confidence {
+        probe(path, self.extensions(), |head| {
+            if head.contains(XMILE_NAMESPACE) {
+                Confidence::Certain
+            } else if root_element(head) == Some("xmile") {
+                Confidence::Medium
+            } else {
+                Confidence::None
+            }
+        })
+    }
+
+    fn import(&self, path: &Path) -> Result<XmileFile, ImportError> {
+        Ok(XmileFile::from_file(path)?)
+    }
+}
+
+/// Recognizes models exported from Powersim Studio as XML.
+///
+/// Translation is not implemented yet: [`Importer::import`] reports
+/// [`ImportError::Unsupported`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PowersimImporter;
+
+impl Importer for PowersimImporter {
+    fn format(&self) -> &str {
+        "Powersim"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sip", "xml"]
+    }
+
+    fn can_import(&self, path: &Path) -> Confidence {
+        probe(path, self.extensions(), |head| {
+            let head = head.to_ascii_lowercase();
+            if root_element(&head).is_some_and(|root| root.starts_with("powersim")) {
+                Confidence::Certain
+            } else if head.contains("powersim") {
+                Confidence::Medium
+            } else {
+                Confidence::None
+            }
+        })
+    }
+
+    fn import(&self, _path: &Path) -> Result<XmileFile, ImportError> {
+        Err(unsupported(
+            self,
+            "Powersim documents are recognized but not translated",
+        ))
+    }
+}
+
+/// Recognizes models exported from Simile.
+///
+/// Translation is not implemented yet: [`Importer::import`] reports
+/// [`ImportError::Unsupported`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimileImporter;
+
+impl Importer for SimileImporter {
+    fn format(&self) -> &str {
+        "Simile"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sml", "pl"]
+    }
+
+    fn can_import(&self, path: &Path) -> Confidence {
+        probe(path, self.extensions(), |head| {
+            let head = head.to_ascii_lowercase();
+            if head.contains("simile") && head.contains("submodel") {
+                Confidence::Certain
+            } else if head.contains("simile") {
+                Confidence::Medium
+            } else {
+                Confidence::None
+            }
+        })
+    }
+
+    fn import(&self, _path: &Path) -> Result<XmileFile, ImportError> {
+        Err(unsupported(
+            self,
+            "Simile exports are recognized but not translated",
+        ))
+    }
+}
+
+fn unsupported(importer: &dyn Importer, reason: &str) -> ImportError {
+    ImportError::Unsupported {
+        format: importer.format().to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Probes the file at `path` by `content`, given the start of the file,
+/// and otherwise by whether its extension is one of `extensions`.
+fn probe(path: &Path, extensions: &[&str], content: impl Fn(&str) -> Confidence) -> Confidence {
+    let by_content = sniff(path).map_or(Confidence::None, |head| content(&head));
+    let by_extension = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext)
+            if extensions
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(ext)) =>
+        {
+            Confidence::Low
+        }
+        _ => Confidence::None,
+    };
+    by_content.max(by_extension)
+}
+
+/// Reads the first [`SNIFF_LENGTH`] bytes of the file at `path`.
+fn sniff(path: &Path) -> Option<String> {
+    let mut head = Vec::with_capacity(SNIFF_LENGTH);
+    File::open(path)
+        .ok()?
+        .take(SNIFF_LENGTH as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// The name of the first element in `head`, skipping the XML declaration,
+/// comments and processing instructions.
+fn root_element(head: &str) -> Option<&str> {
+    let mut rest = head;
+    loop {
+        let start = rest.find('<')?;
+        rest = &rest[start + 1..];
+        if rest.starts_with('?') || rest.starts_with('!') {
+            continue;
+        }
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        return Some(&rest[..end]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+    <model><variables><aux name="Rate"><eqn>0.1</eqn></aux></variables></model>
+</xmile>"#;
+
+    #[test]
+    fn test_probe_picks_most_confident() {
+        let dir = tempfile::tempdir().unwrap();
+        let xmile = dir.path().join("model.xml");
+        std::fs::write(&xmile, MODEL).unwrap();
+        let powersim = dir.path().join("export.xml");
+        std::fs::write(
+            &powersim,
+            r#"<?xml version="1.0"?><!-- export --><PowersimStudio version="10"/>"#,
+        )
+        .unwrap();
+        let simile = dir.path().join("forest.sml");
+        std::fs::write(&simile, "% Simile model file\n").unwrap();
+        let unknown = dir.path().join("notes.txt");
+        std::fs::write(&unknown, "not a model").unwrap();
+
+        let registry = ImporterRegistry::with_defaults();
+        let (importer, confidence) = registry.probe(&xmile).unwrap();
+        assert_eq!(
+            (importer.format(), confidence),
+            ("XMILE", Confidence::Certain)
+        );
+        let (importer, confidence) = registry.probe(&powersim).unwrap();
+        assert_eq!(
+            (importer.format(), confidence),
+            ("Powersim", Confidence::Certain)
+        );
+        let (importer, confidence) = registry.probe(&simile).unwrap();
+        assert_eq!(
+            (importer.format(), confidence),
+            ("Simile", Confidence::Medium)
+        );
+        assert!(registry.probe(&unknown).is_none());
+        // A missing file is probed by its extension
+        let (importer, confidence) = registry.probe(dir.path().join("gone.sip")).unwrap();
+        assert_eq!(
+            (importer.format(), confidence),
+            ("Powersim", Confidence::Low)
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.xmile");
+        std::fs::write(&path, MODEL).unwrap();
+        let registry = ImporterRegistry::with_defaults();
+        assert_eq!(registry.import(&path).unwrap().models.len(), 1);
+
+        let powersim = dir.path().join("model.sip");
+        std::fs::write(&powersim, "<PowersimStudio/>").unwrap();
+        assert!(matches!(
+            registry.import(&powersim),
+            Err(ImportError::Unsupported { format, .. }) if format == "Powersim"
+        ));
+        let unknown = dir.path().join("notes.txt");
+        std::fs::write(&unknown, "not a model").unwrap();
+        assert!(matches!(
+            registry.import(&unknown),
+            Err(ImportError::UnknownFormat(path)) if path == unknown
+        ));
+        assert!(matches!(
+            ImporterRegistry::new().import(&path),
+            Err(ImportError::UnknownFormat(_))
+        ));
+        assert_eq!(
+            format!("{registry:?}"),
+            r#"["XMILE", "Powersim", "Simile"]"#
+        );
+    }
+}
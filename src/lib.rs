@@ -1,17 +1,44 @@
+pub mod analysis;
 pub mod behavior;
+#[cfg(feature = "views")]
+pub mod cld;
+pub mod conformance;
 pub mod containers;
+pub mod convert;
 pub mod core;
 pub mod data;
 pub mod dimensions;
 pub mod equation;
+pub mod error;
+pub mod explain;
 pub mod header;
+pub mod import;
 pub mod r#macro;
 pub mod model;
+#[cfg(feature = "modelica")]
+pub mod modelica;
 pub mod namespace;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod profile;
+pub mod project;
+pub mod render;
+pub mod report;
+pub mod resource;
+pub mod scenario;
+pub mod simulation;
 pub mod specs;
+pub mod template;
+pub mod testing;
+mod trace;
+pub mod translation;
 pub mod units;
 pub mod validation_utils;
+#[cfg(feature = "views")]
 pub mod view;
+pub mod visit;
 
 pub mod types;
 pub mod xml;
@@ -24,6 +51,7 @@ pub use core::Uid;
 pub use equation::{
     Expression, Identifier, Measure, NumericConstant, Operator, UnitEquation, UnitOfMeasure,
 };
+pub use error::{Error, ErrorCategory};
 pub use model::vars::gf::{GraphicalFunction, GraphicalFunctionData, GraphicalFunctionType};
 pub use namespace::Namespace;
 
@@ -0,0 +1,542 @@
+//! Macro libraries: macro-only XMILE files with names, versions and
+//! dependencies.
+//!
+//! A library is an XMILE file whose header gives its name and version, and
+//! which defines macros, usually without any model:
+//!
+//! ```xml
+//! <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header>
+//!         <vendor>Example</vendor>
+//!         <product version="1.0">Example</product>
+//!         <name>supplychain</name>
+//!         <version>2.1.0</version>
+//!         <includes>
+//!             <include resource="macros/smoothing-1.2.xml"/>
+//!         </includes>
+//!     </header>
+//!     <macro name="ship">...</macro>
+//! </xmile>
+//! ```
+//!
+//! Dependencies are the libraries the header includes, named after the
+//! convention `name-version.xml` used for versioned macro files: the include
+//! above requires a `smoothing` library compatible with version 1.2, and
+//! `smoothing-*.xml` accepts any version. Other includes are not
+//! dependencies.
+//!
+//! A [`LibrarySet`] collects libraries, checks that every dependency is
+//! present in a compatible version, and that no two libraries define a
+//! macro with the same name in the same namespace:
+//!
+//! ```rust
+//! use xmile::r#macro::library::{LibrarySet, MacroLibrary};
+//! use xmile::xml::XmileFile;
+//!
+//! let library = |name: &str, version: &str, includes: &str| {
+//!     let xml = format!(
+//!         r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!             <header>
+//!                 <vendor>Example</vendor><product version="1.0">Example</product>
+//!                 <name>{name}</name><version>{version}</version>
+//!                 <includes>{includes}</includes>
+//!             </header>
+//!             <macro name="{name}_total"><parm>a</parm><eqn>a</eqn></macro>
+//!         </xmile>"#
+//!     );
+//!     MacroLibrary::from_file(&XmileFile::from_str(&xml).unwrap()).unwrap()
+//! };
+//!
+//! let mut set = LibrarySet::new();
+//! set.add(library("smoothing", "1.4.0", "")).unwrap();
+//! set.add(library("supplychain", "2.1", r#"<include resource="smoothing-1.2.xml"/>"#))
+//!     .unwrap();
+//! let order: Vec<_> = set.resolve().unwrap().iter().map(|lib| lib.name.as_str()).collect();
+//! assert_eq!(order, ["smoothing", "supplychain"]);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::Macro;
+use crate::Namespace;
+use crate::xml::{ParseError, XmileFile};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LibraryError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("Macro library has no <name> in its header")]
+    MissingName,
+    #[error("Macro library '{0}' has no <version> in its header")]
+    MissingVersion(String),
+    #[error("Macro library '{library}' has invalid version '{version}'")]
+    InvalidVersion { library: String, version: String },
+    #[error("Macro library '{0}' is added more than once")]
+    DuplicateLibrary(String),
+    #[error("Macro library '{library}' requires '{dependency}', which is missing")]
+    MissingDependency {
+        library: String,
+        dependency: Dependency,
+    },
+    #[error("Macro library '{library}' requires '{dependency}', but version {found} is present")]
+    IncompatibleVersion {
+        library: String,
+        dependency: Dependency,
+        found: Version,
+    },
+    #[error("Macro libraries depend on each other: {}", .0.join(" -> "))]
+    DependencyCycle(Vec<String>),
+    #[error(
+        "Macro '{name}' in namespace '{namespace}' is defined by both '{first}' and '{second}'"
+    )]
+    Conflict {
+        name: String,
+        namespace: String,
+        first: String,
+        second: String,
+    },
+}
+
+/// A `major.minor.patch` version. Missing minor and patch numbers are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+        let mut parts = s.split('.');
+        let mut number = |required: bool| -> Result<u64, String> {
+            match parts.next() {
+                Some(part) => part
+                    .parse()
+                    .map_err(|_| format!("'{part}' is not a version number")),
+                None if required => Err("Version is empty".to_string()),
+                None => Ok(0),
+            }
+        };
+        let version = Version::new(number(true)?, number(false)?, number(false)?);
+        match parts.next() {
+            Some(_) => Err(format!("Version '{s}' has more than three numbers")),
+            None => Ok(version),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The versions of a library a dependency accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionReq {
+    /// `*`: any version.
+    Any,
+    /// `=1.2.0`: exactly this version.
+    Exact(Version),
+    /// `>=1.2`: this version or any later one.
+    AtLeast(Version),
+    /// `^1.2` or `1.2`: this version or a later one with the same major
+    /// version, or for `0.x` versions the same minor version.
+    Compatible(Version),
+}
+
+impl VersionReq {
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            VersionReq::Any => true,
+            VersionReq::Exact(required) => version == required,
+            VersionReq::AtLeast(required) => version >= required,
+            VersionReq::Compatible(required) => {
+                version >= required
+                    && version.major == required.major
+                    && (required.major > 0 || version.minor == required.minor)
+            }
+        }
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "*" {
+            Ok(VersionReq::Any)
+        } else if let Some(version) = s.strip_prefix(">=") {
+            Ok(VersionReq::AtLeast(version.parse()?))
+        } else if let Some(version) = s.strip_prefix('=') {
+            Ok(VersionReq::Exact(version.parse()?))
+        } else {
+            Ok(VersionReq::Compatible(
+                s.strip_prefix('^').unwrap_or(s).parse()?,
+            ))
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionReq::Any => write!(f, "*"),
+            VersionReq::Exact(version) => write!(f, "={version}"),
+            VersionReq::AtLeast(version) => write!(f, ">={version}"),
+            VersionReq::Compatible(version) => write!(f, "^{version}"),
+        }
+    }
+}
+
+/// A library required by another, and the versions of it accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub requirement: VersionReq,
+}
+
+impl Dependency {
+    /// The dependency named by an include following the `name-version.xml`
+    /// convention, if it does.
+    pub fn from_include(resource: &str) -> Option<Self> {
+        let file = resource.rsplit(['/', '\\']).next()?;
+        let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+        let (name, version) = stem.rsplit_once('-')?;
+        if name.is_empty() {
+            return None;
+        }
+        let requirement = match version {
+            "*" => VersionReq::Any,
+            version => VersionReq::Compatible(version.parse().ok()?),
+        };
+        Some(Dependency {
+            name: name.to_string(),
+            requirement,
+        })
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.requirement)
+    }
+}
+
+/// The macros of a library file, with its name, version and dependencies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacroLibrary {
+    pub name: String,
+    pub version: Version,
+    pub dependencies: Vec<Dependency>,
+    pub macros: Vec<Macro>,
+    /// The namespace of macros without a `namespace` attribute: the header's
+    /// namespace option, if it names exactly one namespace.
+    pub default_namespace: Vec<Namespace>,
+}
+
+impl MacroLibrary {
+    /// Reads the library defined by `file`.
+    pub fn from_file(file: &XmileFile) -> Result<Self, LibraryError> {
+        let header = &file.header;
+        let name = header
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or(LibraryError::MissingName)?
+            .to_string();
+        let version = header
+            .version_info
+            .as_deref()
+            .ok_or_else(|| LibraryError::MissingVersion(name.clone()))?;
+        let version = version.parse().map_err(|_| LibraryError::InvalidVersion {
+            library: name.clone(),
+            version: version.to_string(),
+        })?;
+        let dependencies = header
+            .includes
+            .iter()
+            .flat_map(|includes| &includes.includes)
+            .filter_map(|include| Dependency::from_include(include.resource.as_ref()))
+            .collect();
+        let default_namespace = header
+            .options
+            .as_ref()
+            .and_then(|options| options.namespace.as_deref())
+            .filter(|namespaces| !namespaces.contains(','))
+            .map(|namespace| Namespace::from_parts_str(namespace.trim()))
+            .unwrap_or_default();
+
+        Ok(MacroLibrary {
+            name,
+            version,
+            dependencies,
+            macros: file.macros.clone(),
+            default_namespace,
+        })
+    }
+
+    /// Parses and reads the library file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LibraryError> {
+        MacroLibrary::from_file(&XmileFile::from_file(path)?)
+    }
+
+    /// The namespace a macro of this library is defined in.
+    pub fn namespace_of<'a>(&'a self, macro_def: &'a Macro) -> &'a [Namespace] {
+        macro_def
+            .namespace
+            .as_deref()
+            .unwrap_or(&self.default_namespace)
+    }
+}
+
+/// A set of macro libraries that can be used together.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibrarySet {
+    libraries: Vec<MacroLibrary>,
+}
+
+impl LibrarySet {
+    pub fn new() -> Self {
+        LibrarySet::default()
+    }
+
+    /// Adds `library`, which must not have the name of a library already in
+    /// the set.
+    pub fn add(&mut self, library: MacroLibrary) -> Result<(), LibraryError> {
+        if self.get(&library.name).is_some() {
+            return Err(LibraryError::DuplicateLibrary(library.name));
+        }
+        self.libraries.push(library);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MacroLibrary> {
+        self.libraries.iter().find(|library| library.name == name)
+    }
+
+    pub fn libraries(&self) -> &[MacroLibrary] {
+        &self.libraries
+    }
+
+    /// Checks the dependencies and macros of the libraries, returning them
+    /// with every library after the libraries it depends on.
+    pub fn resolve(&self) -> Result<Vec<&MacroLibrary>, LibraryError> {
+        for library in &self.libraries {
+            for dependency in &library.dependencies {
+                let Some(found) = self.get(&dependency.name) else {
+                    return Err(LibraryError::MissingDependency {
+                        library: library.name.clone(),
+                        dependency: dependency.clone(),
+                    });
+                };
+                if !dependency.requirement.matches(&found.version) {
+                    return Err(LibraryError::IncompatibleVersion {
+                        library: library.name.clone(),
+                        dependency: dependency.clone(),
+                        found: found.version,
+                    });
+                }
+            }
+        }
+
+        let mut order = Vec::new();
+        for library in &self.libraries {
+            self.visit(library, &mut Vec::new(), &mut order)?;
+        }
+
+        let mut defined: HashMap<(String, String), &str> = HashMap::new();
+        for library in &order {
+            for macro_def in &library.macros {
+                let namespace = library
+                    .namespace_of(macro_def)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(".");
+                let key = (namespace, macro_def.name.compare_key().to_string());
+                if let Some(first) = defined.insert(key.clone(), &library.name) {
+                    return Err(LibraryError::Conflict {
+                        name: macro_def.name.to_string(),
+                        namespace: key.0,
+                        first: first.to_string(),
+                        second: library.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// The macros of every library, in dependency order.
+    pub fn macros(&self) -> Result<Vec<Macro>, LibraryError> {
+        Ok(self
+            .resolve()?
+            .into_iter()
+            .flat_map(|library| library.macros.iter().cloned())
+            .collect())
+    }
+
+    /// Adds `library` to `order` after its dependencies, which are all in
+    /// the set.
+    fn visit<'a>(
+        &'a self,
+        library: &'a MacroLibrary,
+        visiting: &mut Vec<&'a str>,
+        order: &mut Vec<&'a MacroLibrary>,
+    ) -> Result<(), LibraryError> {
+        if order.iter().any(|done| done.name == library.name) {
+            return Ok(());
+        }
+        if let Some(start) = visiting.iter().position(|name| *name == library.name) {
+            let mut cycle: Vec<String> = visiting[start..]
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+            cycle.push(library.name.clone());
+            return Err(LibraryError::DependencyCycle(cycle));
+        }
+        visiting.push(&library.name);
+        for dependency in &library.dependencies {
+            if let Some(found) = self.get(&dependency.name) {
+                self.visit(found, visiting, order)?;
+            }
+        }
+        visiting.pop();
+        order.push(library);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(name: &str, version: &str, includes: &[&str], macros: &str) -> MacroLibrary {
+        let includes: String = includes
+            .iter()
+            .map(|resource| format!(r#"<include resource="{resource}"/>"#))
+            .collect();
+        let xml = format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor><product version="1.0">Test</product>
+        <options namespace="isee"/>
+        <name>{name}</name><version>{version}</version>
+        <includes>{includes}</includes>
+    </header>
+    {macros}
+</xmile>"#
+        );
+        MacroLibrary::from_file(&XmileFile::from_str(&xml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_versions_and_dependencies() {
+        assert_eq!("v1.2".parse::<Version>().unwrap(), Version::new(1, 2, 0));
+        assert!("1.2.3.4".parse::<Version>().is_err());
+        let req: VersionReq = "^1.2".parse().unwrap();
+        assert!(req.matches(&Version::new(1, 9, 0)));
+        assert!(!req.matches(&Version::new(2, 0, 0)));
+        assert!(
+            !"0.2"
+                .parse::<VersionReq>()
+                .unwrap()
+                .matches(&Version::new(0, 3, 0))
+        );
+        assert_eq!(
+            Dependency::from_include("macros/supplychain-2.0.xml")
+                .unwrap()
+                .to_string(),
+            "supplychain ^2.0.0"
+        );
+        assert_eq!(
+            Dependency::from_include("macros/supplychain-*.xml")
+                .unwrap()
+                .requirement,
+            VersionReq::Any
+        );
+        assert!(Dependency::from_include("common.xml").is_none());
+
+        let mut set = LibrarySet::new();
+        set.add(library("base", "1.0", &[], "")).unwrap();
+        set.add(library("top", "1.0", &["base-2.0.xml"], ""))
+            .unwrap();
+        assert!(matches!(
+            set.resolve(),
+            Err(LibraryError::IncompatibleVersion { found, .. }) if found == Version::new(1, 0, 0)
+        ));
+
+        let mut set = LibrarySet::new();
+        set.add(library("a", "1.0", &["b-1.xml"], "")).unwrap();
+        set.add(library("b", "1.0", &["a-1.xml"], "")).unwrap();
+        assert_eq!(
+            set.resolve().unwrap_err().to_string(),
+            "Macro libraries depend on each other: a -> b -> a"
+        );
+        assert!(set.add(library("a", "2.0", &[], "")).is_err());
+    }
+
+    #[test]
+    fn test_conflicting_macros() {
+        let first = library(
+            "first",
+            "1.0",
+            &[],
+            r#"<macro name="smooth"><parm>x</parm><eqn>x</eqn></macro>"#,
+        );
+        assert_eq!(first.default_namespace, [Namespace::Isee]);
+        let second = library(
+            "second",
+            "1.0",
+            &[],
+            r#"<macro name="Smooth"><parm>x</parm><eqn>x</eqn></macro>"#,
+        );
+        let mut set = LibrarySet::new();
+        set.add(first).unwrap();
+        set.add(second).unwrap();
+        assert_eq!(
+            set.resolve().unwrap_err().to_string(),
+            "Macro 'Smooth' in namespace 'isee' is defined by both 'first' and 'second'"
+        );
+
+        let other = library(
+            "other",
+            "1.0",
+            &[],
+            r#"<macro name="smooth" namespace="user"><parm>x</parm><eqn>x</eqn></macro>"#,
+        );
+        let mut set = LibrarySet::new();
+        set.add(library(
+            "first",
+            "1.0",
+            &[],
+            r#"<macro name="smooth"><eqn>1</eqn></macro>"#,
+        ))
+        .unwrap();
+        set.add(other).unwrap();
+        assert_eq!(set.macros().unwrap().len(), 2);
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[cfg(feature = "macros")]
+pub mod library;
+
 #[cfg(feature = "macros")]
 use std::collections::HashMap;
 
@@ -9,9 +12,11 @@ use crate::{
     namespace::Namespace,
     specs::SimulationSpecs,
     types::{Validate, ValidationResult},
-    view::View,
 };
 
+#[cfg(feature = "views")]
+use crate::view::View;
+
 /// Macros allow new built-in functions to be defined, which can also be used to
 /// translate built-in functions across vendor packages. They also provide a way
 /// to implement non-standard simulation behavior for stocks, flows, and
@@ -109,6 +114,7 @@ pub struct Macro {
     /// This must only appear in conjunction with a <variables> tag.
     /// This is an OPTIONAL property: <views><view>...</view></views>
     /// (default: no view)
+    #[cfg(feature = "views")]
     pub views: Option<View>,
 
     /// The namespace for the macro.
@@ -116,6 +122,11 @@ pub struct Macro {
     /// (default: single namespace specified in the header's <options> tag,
     /// or no namespace if either no namespaces or multiple namespaces are specified)
     pub namespace: Option<Vec<Namespace>>,
+
+    /// The option filter this macro implements, if any.
+    /// These are the OPTIONAL attributes filter="…" and applyto="…"
+    /// (default: not an option filter)
+    pub filter: Option<OptionFilter>,
 }
 
 /// Raw macro structure for deserialization from XML.
@@ -126,6 +137,10 @@ struct RawMacro {
     name: Identifier,
     #[serde(rename = "@namespace")]
     namespace: Option<String>,
+    #[serde(rename = "@filter")]
+    filter: Option<String>,
+    #[serde(rename = "@applyto")]
+    apply_to: Option<String>,
     #[serde(rename = "parm", default)]
     parameters: Vec<MacroParameter>,
     #[serde(rename = "eqn")]
@@ -138,10 +153,12 @@ struct RawMacro {
     sim_specs: Option<SimulationSpecs>,
     #[serde(rename = "variables")]
     variables: Option<crate::xml::schema::Variables>,
+    #[cfg(feature = "views")]
     #[serde(rename = "views")]
     views: Option<RawMacroViews>,
 }
 
+#[cfg(feature = "views")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct RawMacroViews {
     #[serde(rename = "view")]
@@ -162,7 +179,23 @@ impl<'de> Deserialize<'de> for Macro {
 
         let variables = raw.variables.map(|vars| vars.variables);
 
-        let views = raw.views.map(|v| v.view);
+        let filter = match raw.filter {
+            Some(filter) => Some(OptionFilter {
+                filter: filter.parse().map_err(serde::de::Error::custom)?,
+                apply_to: raw
+                    .apply_to
+                    .map(|apply_to| apply_to.parse())
+                    .transpose()
+                    .map_err(serde::de::Error::custom)?,
+            }),
+            None if raw.apply_to.is_some() => {
+                return Err(serde::de::Error::custom(format!(
+                    "Macro '{}' has applyto but no filter attribute",
+                    raw.name
+                )));
+            }
+            None => None,
+        };
 
         Ok(Macro {
             name: raw.name,
@@ -172,8 +205,10 @@ impl<'de> Deserialize<'de> for Macro {
             doc: raw.doc,
             sim_specs: raw.sim_specs,
             variables,
-            views,
+            #[cfg(feature = "views")]
+            views: raw.views.map(|v| v.view),
             namespace,
+            filter,
         })
     }
 }
@@ -184,15 +219,24 @@ impl Serialize for Macro {
         S: Serializer,
     {
         use serde::ser::SerializeStruct;
-        let field_count = 1 // name
+        #[allow(unused_mut)]
+        let mut field_count = 1 // name
             + if self.namespace.is_some() { 1 } else { 0 }
+            + match &self.filter {
+                Some(OptionFilter { apply_to: Some(_), .. }) => 2,
+                Some(_) => 1,
+                None => 0,
+            }
             + if !self.parameters.is_empty() { 1 } else { 0 }
             + 1 // eqn
             + if self.format.is_some() { 1 } else { 0 }
             + if self.doc.is_some() { 1 } else { 0 }
             + if self.sim_specs.is_some() { 1 } else { 0 }
-            + if self.variables.is_some() { 1 } else { 0 }
-            + if self.views.is_some() { 1 } else { 0 };
+            + if self.variables.is_some() { 1 } else { 0 };
+        #[cfg(feature = "views")]
+        if self.views.is_some() {
+            field_count += 1;
+        }
 
         let mut state = serializer.serialize_struct("macro", field_count)?;
 
@@ -206,6 +250,13 @@ impl Serialize for Macro {
             state.serialize_field("@namespace", &ns_str)?;
         }
 
+        if let Some(ref filter) = self.filter {
+            state.serialize_field("@filter", filter.filter.as_str())?;
+            if let Some(apply_to) = filter.apply_to {
+                state.serialize_field("@applyto", apply_to.as_str())?;
+            }
+        }
+
         if !self.parameters.is_empty() {
             state.serialize_field("parm", &self.parameters)?;
         }
@@ -234,6 +285,7 @@ impl Serialize for Macro {
             )?;
         }
 
+        #[cfg(feature = "views")]
         if let Some(ref view) = self.views {
             state.serialize_field("views", &RawMacroViews { view: view.clone() })?;
         }
@@ -256,17 +308,148 @@ pub struct MacroParameter {
     /// The name of the formal parameter within the macro.
     /// This must be a valid XMILE identifier.
     /// This is specified as the text content of the <parm> tag.
-    #[serde(rename = "#text")]
+    #[serde(rename = "$text")]
     pub name: Identifier,
 
     /// The default value for the parameter, specified as a valid XMILE expression.
     /// This expression can refer to any parameter already defined.
     /// This is an OPTIONAL attribute: default="…"
     /// (default: no default value, parameter is required)
-    #[serde(rename = "@default")]
+    #[serde(rename = "@default", skip_serializing_if = "Option::is_none")]
     pub default: Option<Expression>,
 }
 
+/// The kind of variable an option filter applies to, from filter="…".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterType {
+    Stock,
+    Flow,
+    Aux,
+}
+
+impl FilterType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterType::Stock => "stock",
+            FilterType::Flow => "flow",
+            FilterType::Aux => "aux",
+        }
+    }
+}
+
+impl std::str::FromStr for FilterType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "stock" => Ok(FilterType::Stock),
+            "flow" => Ok(FilterType::Flow),
+            "aux" => Ok(FilterType::Aux),
+            _ => Err(format!(
+                "Invalid filter type '{s}'; expected stock, flow or aux"
+            )),
+        }
+    }
+}
+
+/// Where an option filter is applied, from applyto="…".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApplyTo {
+    /// The inflows of a filtered stock.
+    Inflows,
+    /// The outflows of a filtered stock.
+    Outflows,
+    /// The stock a filtered flow drains.
+    Upstream,
+    /// The stock a filtered flow fills.
+    Downstream,
+}
+
+impl ApplyTo {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApplyTo::Inflows => "inflows",
+            ApplyTo::Outflows => "outflows",
+            ApplyTo::Upstream => "upstream",
+            ApplyTo::Downstream => "downstream",
+        }
+    }
+}
+
+impl std::str::FromStr for ApplyTo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "inflows" => Ok(ApplyTo::Inflows),
+            "outflows" => Ok(ApplyTo::Outflows),
+            "upstream" => Ok(ApplyTo::Upstream),
+            "downstream" => Ok(ApplyTo::Downstream),
+            _ => Err(format!(
+                "Invalid applyto '{s}'; expected inflows, outflows, upstream or downstream"
+            )),
+        }
+    }
+}
+
+/// A value passed to an option filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterArgument {
+    /// The value of the filtered variable.
+    Value,
+    /// The value of the option: one if it is on, zero if it is off.
+    Option,
+    /// The calculated value of the flow.
+    Flow,
+    /// The calculated value of the stock.
+    Stock,
+    /// The sum of the stock's inflows with higher priority than the flow.
+    InflowSum,
+    /// The sum of the stock's outflows with higher priority than the flow.
+    OutflowSum,
+}
+
+/// An option filter (Section 4.8.4): a macro run whenever a variable with
+/// the option named by the macro is evaluated, to modify its value or, with
+/// `applyto`, the values of its flows or stocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OptionFilter {
+    pub filter: FilterType,
+    pub apply_to: Option<ApplyTo>,
+}
+
+impl OptionFilter {
+    /// The values passed to the filter, in calling order.
+    pub fn arguments(&self) -> &'static [FilterArgument] {
+        use FilterArgument as A;
+
+        match self.apply_to {
+            None => &[A::Value, A::Option],
+            Some(ApplyTo::Inflows) => &[A::Flow, A::Option, A::Stock, A::InflowSum],
+            Some(ApplyTo::Outflows) => &[A::Flow, A::Option, A::Stock, A::OutflowSum],
+            Some(ApplyTo::Upstream | ApplyTo::Downstream) => &[A::Stock, A::Option, A::Flow],
+        }
+    }
+
+    /// Whether `applyto` is allowed for the filtered kind of variable: only
+    /// stocks redirect to their inflows and outflows, and only flows to
+    /// their upstream and downstream stocks.
+    pub fn is_consistent(&self) -> bool {
+        matches!(
+            (self.filter, self.apply_to),
+            (_, None)
+                | (
+                    FilterType::Stock,
+                    Some(ApplyTo::Inflows | ApplyTo::Outflows)
+                )
+                | (
+                    FilterType::Flow,
+                    Some(ApplyTo::Upstream | ApplyTo::Downstream)
+                )
+        )
+    }
+}
+
 impl Validate for Macro {
     fn validate(&self) -> ValidationResult {
         let warnings = Vec::new();
@@ -280,12 +463,24 @@ impl Validate for Macro {
         }
 
         // Validate that if views is present, variables must also be present
+        #[cfg(feature = "views")]
         if self.views.is_some() && self.variables.is_none() {
             errors.push(
                 "Macro views can only appear in conjunction with a variables tag.".to_string(),
             );
         }
 
+        // Macros named after a builtin shadow it unless they are placed in an
+        // explicit namespace
+        if self.name.is_reserved_word() && self.namespace.is_none() && !self.name.is_qualified() {
+            errors.push(format!(
+                "Macro '{}' shadows the built-in '{}'. Give it an explicit namespace, e.g. namespace=\"user\", or rename it, e.g. to '{}'.",
+                self.name,
+                self.name.to_string().to_uppercase(),
+                crate::xml::validation::suggest_rename(&self.name, "macro", &[])
+            ));
+        }
+
         // Validate parameter default values: once a parameter has a default,
         // all subsequent parameters must also have defaults
         let mut found_default = false;
@@ -301,6 +496,33 @@ impl Validate for Macro {
             }
         }
 
+        // Option filters are called with a fixed list of arguments
+        if let Some(filter) = &self.filter {
+            if !filter.is_consistent() {
+                errors.push(format!(
+                    "Option filter '{}' for {} variables cannot have applyto=\"{}\".",
+                    self.name,
+                    filter.filter.as_str(),
+                    filter.apply_to.map_or("", |apply_to| apply_to.as_str())
+                ));
+            } else {
+                let passed = filter.arguments().len();
+                let required = self
+                    .parameters
+                    .iter()
+                    .take_while(|p| p.default.is_none())
+                    .count();
+                if required > passed || self.parameters.len() < passed {
+                    errors.push(format!(
+                        "Option filter '{}' is passed {} values, but takes {} parameters.",
+                        self.name,
+                        passed,
+                        self.parameters.len()
+                    ));
+                }
+            }
+        }
+
         // Validate sim_specs if present (only start, stop, dt, and method are allowed)
         // Note: The spec says only start, stop, dt, and method are allowed.
         // We can't easily validate this at the struct level since SimulationSpecs
@@ -339,6 +561,9 @@ impl Validate for MacroParameter {
 pub struct MacroRegistry {
     /// Map from macro name (normalized) to macro definition
     macros: HashMap<Identifier, Macro>,
+    /// Option filters, which run when variables with their option are
+    /// evaluated and are not called by name.
+    filters: Vec<Macro>,
 }
 
 #[cfg(feature = "macros")]
@@ -347,6 +572,7 @@ impl MacroRegistry {
     pub fn new() -> Self {
         MacroRegistry {
             macros: HashMap::new(),
+            filters: Vec::new(),
         }
     }
 
@@ -369,11 +595,36 @@ impl MacroRegistry {
 
     /// Registers a macro in the registry.
     ///
+    /// Option filters are kept apart from callable macros, since a stock,
+    /// flow and aux filter may share the name of their option.
+    ///
     /// # Arguments
     ///
     /// * `macro_def` - The macro definition to register
     pub fn register(&mut self, macro_def: Macro) {
-        self.macros.insert(macro_def.name.clone(), macro_def);
+        if macro_def.filter.is_some() {
+            self.filters.push(macro_def);
+        } else {
+            self.macros.insert(macro_def.name.clone(), macro_def);
+        }
+    }
+
+    /// Looks up the option filter run for `option` on a variable of kind
+    /// `filter`, applied to the variable itself or redirected by `apply_to`.
+    pub fn option_filter(
+        &self,
+        option: &Identifier,
+        filter: FilterType,
+        apply_to: Option<ApplyTo>,
+    ) -> Option<&Macro> {
+        self.filters.iter().find(|macro_def| {
+            &macro_def.name == option && macro_def.filter == Some(OptionFilter { filter, apply_to })
+        })
+    }
+
+    /// The option filters, in registration order.
+    pub fn option_filters(&self) -> &[Macro] {
+        &self.filters
     }
 
     /// Looks up a macro by name.
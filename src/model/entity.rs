@@ -0,0 +1,208 @@
+//! A common interface over the identifiable parts of a file.
+//!
+//! Variables, groups, views and the display objects in views are different
+//! types, but generic tooling such as search, diffing or annotation only
+//! needs to know what each one is called, how it is identified and what it
+//! documents. [`Entity`] exposes those, so such tools can work over
+//! `&dyn Entity` rather than matching on every concrete type.
+//!
+//! ```rust
+//! use xmile::model::entity::{Entity, EntityKind};
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <model><variables>
+//!         <stock name="Adult_Population"><eqn>100</eqn><doc>People aged 18 and over</doc></stock>
+//!     </variables></model>
+//! </xmile>"#).unwrap();
+//! let variable = &file.models[0].variables.variables[0];
+//! assert_eq!(variable.kind(), EntityKind::Stock);
+//! assert_eq!(variable.name(), Some("Adult Population"));
+//! assert!(variable.doc().is_some());
+//! assert_eq!(variable.uid(), None);
+//! ```
+
+use std::fmt;
+
+use crate::Uid;
+use crate::model::groups::Group;
+use crate::model::object::{Document, Documentation};
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+
+/// What kind of object an [`Entity`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Stock,
+    Flow,
+    Aux,
+    GraphicalFunction,
+    Module,
+    Group,
+    View,
+    StockObject,
+    FlowObject,
+    AuxObject,
+    ModuleObject,
+    GroupObject,
+    ConnectorObject,
+    AliasObject,
+}
+
+impl EntityKind {
+    /// Whether entities of this kind are display objects in a view rather
+    /// than part of the model.
+    pub fn is_display_object(self) -> bool {
+        matches!(
+            self,
+            EntityKind::StockObject
+                | EntityKind::FlowObject
+                | EntityKind::AuxObject
+                | EntityKind::ModuleObject
+                | EntityKind::GroupObject
+                | EntityKind::ConnectorObject
+                | EntityKind::AliasObject
+        )
+    }
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EntityKind::Stock => "stock",
+            EntityKind::Flow => "flow",
+            EntityKind::Aux => "aux",
+            EntityKind::GraphicalFunction => "graphical function",
+            EntityKind::Module => "module",
+            EntityKind::Group => "group",
+            EntityKind::View => "view",
+            EntityKind::StockObject => "stock display object",
+            EntityKind::FlowObject => "flow display object",
+            EntityKind::AuxObject => "aux display object",
+            EntityKind::ModuleObject => "module display object",
+            EntityKind::GroupObject => "group display object",
+            EntityKind::ConnectorObject => "connector",
+            EntityKind::AliasObject => "alias",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A variable, group, view or display object.
+pub trait Entity {
+    /// The UID of the entity. Only views and display objects have UIDs.
+    fn uid(&self) -> Option<Uid>;
+
+    /// The name of the entity, if it has one. Names of model entities are
+    /// normalized, so `Birth_Rate` is named `Birth Rate`; names in views are
+    /// as written.
+    fn name(&self) -> Option<&str>;
+
+    fn kind(&self) -> EntityKind;
+
+    /// The documentation of the entity, if any.
+    fn doc(&self) -> Option<&Documentation> {
+        None
+    }
+}
+
+impl Entity for Variable {
+    fn uid(&self) -> Option<Uid> {
+        None
+    }
+
+    fn name(&self) -> Option<&str> {
+        crate::xml::validation::get_variable_name(self).map(|name| name.normalized())
+    }
+
+    fn kind(&self) -> EntityKind {
+        match self {
+            Variable::Auxiliary(_) => EntityKind::Aux,
+            Variable::Stock(_) => EntityKind::Stock,
+            Variable::Flow(_) => EntityKind::Flow,
+            Variable::GraphicalFunction(_) => EntityKind::GraphicalFunction,
+            #[cfg(feature = "submodels")]
+            Variable::Module(_) => EntityKind::Module,
+            Variable::Group(_) => EntityKind::Group,
+        }
+    }
+
+    fn doc(&self) -> Option<&Documentation> {
+        match self {
+            Variable::Auxiliary(aux) => aux.documentation(),
+            Variable::Stock(stock) => match stock.as_ref() {
+                Stock::Basic(stock) => stock.documentation(),
+                Stock::Conveyor(stock) => stock.documentation(),
+                Stock::Queue(stock) => stock.documentation(),
+            },
+            Variable::Flow(flow) => flow.documentation(),
+            Variable::GraphicalFunction(gf) => gf.documentation(),
+            #[cfg(feature = "submodels")]
+            Variable::Module(module) => module.documentation(),
+            Variable::Group(group) => group.documentation(),
+        }
+    }
+}
+
+impl Entity for Group {
+    fn uid(&self) -> Option<Uid> {
+        None
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(self.name.normalized())
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::Group
+    }
+
+    fn doc(&self) -> Option<&Documentation> {
+        self.documentation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(xml: &str) -> Vec<Variable> {
+        let model: crate::xml::schema::Model =
+            quick_xml::de::from_str(&format!("<model><variables>{xml}</variables></model>"))
+                .unwrap();
+        model.variables.variables
+    }
+
+    #[test]
+    fn test_variable_entities() {
+        let variables = variables(
+            r#"<stock name="Population"><eqn>100</eqn><doc>People</doc></stock>
+            <flow name="births"><eqn>1</eqn></flow>
+            <gf name="Effect"><xscale min="0" max="1"/><ypts>0,1</ypts></gf>"#,
+        );
+        let entities: Vec<(EntityKind, Option<&str>, bool)> = variables
+            .iter()
+            .map(|v| (v.kind(), v.name(), v.doc().is_some()))
+            .collect();
+        assert_eq!(
+            entities,
+            [
+                (EntityKind::Stock, Some("Population"), true),
+                (EntityKind::Flow, Some("births"), false),
+                (EntityKind::GraphicalFunction, Some("Effect"), false),
+            ]
+        );
+        assert!(variables.iter().all(|v| v.uid().is_none()));
+    }
+
+    #[test]
+    fn test_kind_display() {
+        assert_eq!(
+            EntityKind::GraphicalFunction.to_string(),
+            "graphical function"
+        );
+        assert!(EntityKind::AliasObject.is_display_object());
+        assert!(!EntityKind::Group.is_display_object());
+    }
+}
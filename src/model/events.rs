@@ -52,11 +52,11 @@ pub struct EventPoster {
 pub struct Threshold {
     #[serde(rename = "@value")]
     pub value: f64,
-    #[serde(rename = "@direction")]
+    #[serde(rename = "@direction", skip_serializing_if = "Option::is_none")]
     pub direction: Option<String>,
-    #[serde(rename = "@repeat")]
+    #[serde(rename = "@repeat", skip_serializing_if = "Option::is_none")]
     pub repeat: Option<String>,
-    #[serde(rename = "@interval")]
+    #[serde(rename = "@interval", skip_serializing_if = "Option::is_none")]
     pub interval: Option<f64>,
     #[serde(rename = "event", default)]
     pub events: Vec<Event>,
@@ -64,10 +64,10 @@ pub struct Threshold {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
-    #[serde(rename = "@sim_action")]
+    #[serde(rename = "@sim_action", skip_serializing_if = "Option::is_none")]
     pub sim_action: Option<String>,
     // Actions can be text content or child elements - for now, we'll handle as text
-    #[serde(rename = "#text", default)]
+    #[serde(rename = "$text", default)]
     pub actions: Vec<String>, // Actions to be taken when the event is triggered
 }
 
@@ -0,0 +1,314 @@
+//! Number formatting for values shown by numeric displays, tables and other
+//! output devices (Section 4.1.1).
+//!
+//! A [`NumberFormat`] is the fully-resolved form of the `<format>` tag
+//! attributes: unspecified attributes take their XMILE defaults. Devices
+//! MAY override the formatting of the entity they show, so device settings
+//! are usually combined with the entity's [`FormatOptions`] first:
+//!
+//! ```rust
+//! use xmile::model::object::{DisplayAs, FormatOptions};
+//!
+//! let entity = FormatOptions {
+//!     precision: Some(0.01),
+//!     scale_by: None,
+//!     display_as: Some(DisplayAs::Currency),
+//!     delimit_000s: Some(true),
+//! };
+//! let device = FormatOptions {
+//!     precision: Some(1.0),
+//!     ..FormatOptions::default()
+//! };
+//! let format = device.or(&entity).number_format();
+//! assert_eq!(format.format(1234567.891), "$1,234,568");
+//! ```
+
+use super::object::{DisplayAs, FormatOptions};
+
+/// Significant digits used when no precision is given.
+const DEFAULT_SIGNIFICANT_DIGITS: i32 = 6;
+
+/// A resolved number format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    /// The value of the least significant digit, e.g. 0.01 to round to the
+    /// hundredths place or 0.5 to round to the nearest half. `None` picks a
+    /// precision from the magnitude of each value.
+    pub precision: Option<f64>,
+    /// The factor values are divided by before display, e.g. 1000 to
+    /// display thousands.
+    pub scale_by: f64,
+    /// How the value is presented.
+    pub display_as: DisplayAs,
+    /// Whether to separate thousands in the integer part.
+    pub delimit_000s: bool,
+    /// The symbol placed before currency values.
+    pub currency_symbol: String,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            precision: None,
+            scale_by: 1.0,
+            display_as: DisplayAs::Number,
+            delimit_000s: false,
+            currency_symbol: "$".to_string(),
+        }
+    }
+}
+
+impl From<&FormatOptions> for NumberFormat {
+    fn from(options: &FormatOptions) -> Self {
+        let defaults = NumberFormat::default();
+        NumberFormat {
+            precision: options.precision,
+            scale_by: options.scale_by.unwrap_or(defaults.scale_by),
+            display_as: options.display_as.unwrap_or(defaults.display_as),
+            delimit_000s: options.delimit_000s.unwrap_or(defaults.delimit_000s),
+            currency_symbol: defaults.currency_symbol,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Uses `symbol` for currency values instead of `$`.
+    pub fn with_currency_symbol(mut self, symbol: &str) -> Self {
+        self.currency_symbol = symbol.to_string();
+        self
+    }
+
+    /// Formats `value` for display.
+    ///
+    /// The value is divided by `scale_by`, multiplied by 100 for percentages,
+    /// and then rounded to the precision, so the precision applies to the
+    /// number as displayed.
+    pub fn format(&self, value: f64) -> String {
+        if value.is_nan() {
+            return "NaN".to_string();
+        }
+        if value.is_infinite() {
+            let sign = if value < 0.0 { "-" } else { "" };
+            return format!("{}Infinity", sign);
+        }
+
+        let scale_by = if self.scale_by == 0.0 || !self.scale_by.is_finite() {
+            1.0
+        } else {
+            self.scale_by
+        };
+        let mut scaled = value / scale_by;
+        if self.display_as == DisplayAs::Percent {
+            scaled *= 100.0;
+        }
+
+        if self.display_as == DisplayAs::Scientific {
+            return self.format_scientific(scaled);
+        }
+
+        let (rounded, decimals) = self.round(scaled);
+        let negative = rounded < 0.0;
+        let digits = self.group(&format!("{:.*}", decimals, rounded.abs()));
+        let sign = if negative { "-" } else { "" };
+
+        match self.display_as {
+            DisplayAs::Currency => format!("{}{}{}", sign, self.currency_symbol, digits),
+            DisplayAs::Percent => format!("{}{}%", sign, digits),
+            _ => format!("{}{}", sign, digits),
+        }
+    }
+
+    /// Rounds `value` to the precision, returning it with the number of
+    /// decimal places to show.
+    fn round(&self, value: f64) -> (f64, usize) {
+        let (rounded, decimals) = match self.precision {
+            Some(precision) if precision > 0.0 && precision.is_finite() => (
+                (value / precision).round() * precision,
+                decimals_of(precision),
+            ),
+            _ => {
+                let magnitude = if value == 0.0 {
+                    0
+                } else {
+                    value.abs().log10().floor() as i32
+                };
+                let decimals = (DEFAULT_SIGNIFICANT_DIGITS - 1 - magnitude).clamp(0, 15) as usize;
+                let factor = 10f64.powi(decimals as i32);
+                let rounded = (value * factor).round() / factor;
+                // Drop trailing zeros, which carry no information here.
+                let text = format!("{:.*}", decimals, rounded);
+                let trimmed = text.trim_end_matches('0').trim_end_matches('.');
+                let decimals = trimmed.split_once('.').map_or(0, |(_, d)| d.len());
+                (rounded, decimals)
+            }
+        };
+        // Avoid displaying "-0".
+        if rounded == 0.0 {
+            (0.0, decimals)
+        } else {
+            (rounded, decimals)
+        }
+    }
+
+    fn format_scientific(&self, value: f64) -> String {
+        if value == 0.0 {
+            let (_, decimals) = self.round(0.0);
+            return format!("{:.*}e0", decimals, 0.0);
+        }
+        let mut exponent = value.abs().log10().floor() as i32;
+        let mut mantissa = value / 10f64.powi(exponent);
+        let (mut rounded, mut decimals) = self.round(mantissa);
+        // Rounding can carry the mantissa to 10, e.g. 9.99 to one decimal.
+        if rounded.abs() >= 10.0 {
+            exponent += 1;
+            mantissa = value / 10f64.powi(exponent);
+            (rounded, decimals) = self.round(mantissa);
+        }
+        format!("{:.*}e{}", decimals, rounded, exponent)
+    }
+
+    /// Inserts thousands separators into the integer part of `digits`.
+    fn group(&self, digits: &str) -> String {
+        if !self.delimit_000s {
+            return digits.to_string();
+        }
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let mut grouped = String::with_capacity(digits.len() + integer.len() / 3);
+        for (i, c) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push('.');
+            grouped.push_str(fraction);
+        }
+        grouped
+    }
+}
+
+/// Returns the number of decimal places needed to show multiples of `precision`.
+fn decimals_of(precision: f64) -> usize {
+    (0..15)
+        .find(|&decimals| {
+            let scaled = precision * 10f64.powi(decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-9 * scaled.abs().max(1.0)
+        })
+        .unwrap_or(15)
+}
+
+impl FormatOptions {
+    /// Fills attributes not set here from `fallback`, e.g. a device's
+    /// settings from the entity it shows.
+    pub fn or(&self, fallback: &FormatOptions) -> FormatOptions {
+        FormatOptions {
+            precision: self.precision.or(fallback.precision),
+            scale_by: self.scale_by.or(fallback.scale_by),
+            display_as: self.display_as.or(fallback.display_as),
+            delimit_000s: self.delimit_000s.or(fallback.delimit_000s),
+        }
+    }
+
+    /// Resolves these options to a [`NumberFormat`], applying defaults.
+    pub fn number_format(&self) -> NumberFormat {
+        NumberFormat::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(precision: Option<f64>, display_as: DisplayAs, delimit_000s: bool) -> NumberFormat {
+        NumberFormat {
+            precision,
+            display_as,
+            delimit_000s,
+            ..NumberFormat::default()
+        }
+    }
+
+    #[test]
+    fn test_precision() {
+        let f = |p: f64, v: f64| format(Some(p), DisplayAs::Number, false).format(v);
+        assert_eq!(f(0.01, 2.71234), "2.71");
+        assert_eq!(f(0.5, 3.3), "3.5");
+        assert_eq!(f(1.0, 2.5), "3");
+        assert_eq!(f(10.0, 1234.0), "1230");
+        assert_eq!(f(0.25, 1.1), "1.00");
+        assert_eq!(f(0.1, -0.04), "0.0");
+    }
+
+    #[test]
+    fn test_default_precision() {
+        let f = |v: f64| NumberFormat::default().format(v);
+        assert_eq!(f(0.0), "0");
+        assert_eq!(f(42.0), "42");
+        assert_eq!(f(1.0 / 3.0), "0.333333");
+        assert_eq!(f(123456.789), "123457");
+        assert_eq!(f(-2.5), "-2.5");
+        assert_eq!(f(f64::NAN), "NaN");
+        assert_eq!(f(f64::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn test_thousands_and_scale() {
+        let mut f = format(Some(0.01), DisplayAs::Number, true);
+        assert_eq!(f.format(1234567.891), "1,234,567.89");
+        assert_eq!(f.format(-999.0), "-999.00");
+        assert_eq!(f.format(-1000.0), "-1,000.00");
+
+        f.scale_by = 1000.0;
+        f.precision = Some(0.1);
+        assert_eq!(f.format(1234567.0), "1,234.6");
+    }
+
+    #[test]
+    fn test_currency_and_percent() {
+        let currency = format(Some(0.01), DisplayAs::Currency, true);
+        assert_eq!(currency.format(-1234.5), "-$1,234.50");
+        let euro = currency.with_currency_symbol("€");
+        assert_eq!(euro.format(2.0), "€2.00");
+
+        let percent = format(Some(0.1), DisplayAs::Percent, false);
+        assert_eq!(percent.format(0.1234), "12.3%");
+    }
+
+    #[test]
+    fn test_scientific() {
+        let f = |p: Option<f64>, v: f64| format(p, DisplayAs::Scientific, false).format(v);
+        assert_eq!(f(Some(0.01), 12345.0), "1.23e4");
+        assert_eq!(f(Some(0.1), 0.000999), "1.0e-3");
+        assert_eq!(f(Some(0.01), -0.5), "-5.00e-1");
+        assert_eq!(f(None, 6.02214076e23), "6.02214e23");
+        assert_eq!(f(Some(0.1), 0.0), "0.0e0");
+    }
+
+    #[test]
+    fn test_options_fallback() {
+        let device = FormatOptions {
+            precision: Some(0.1),
+            scale_by: None,
+            display_as: None,
+            delimit_000s: None,
+        };
+        let entity = FormatOptions {
+            precision: Some(0.01),
+            scale_by: Some(1000.0),
+            display_as: Some(DisplayAs::Percent),
+            delimit_000s: None,
+        };
+        let merged = device.or(&entity);
+        assert_eq!(merged.precision, Some(0.1));
+        assert_eq!(merged.scale_by, Some(1000.0));
+
+        let resolved = merged.number_format();
+        assert_eq!(resolved.display_as, DisplayAs::Percent);
+        assert!(!resolved.delimit_000s);
+    }
+}
@@ -24,7 +24,7 @@ fn default_false() -> bool {
 pub struct Group {
     #[serde(rename = "@name")]
     pub name: Identifier,
-    #[serde(rename = "doc", default)]
+    #[serde(rename = "doc", default, skip_serializing_if = "Option::is_none")]
     pub doc: Option<Documentation>,
     #[serde(rename = "entity", default)]
     pub entities: Vec<GroupEntity>,
@@ -1,5 +1,8 @@
+pub mod entity;
 pub mod events;
+pub mod format;
 pub mod groups;
 pub mod object;
+pub mod qualified;
 pub mod vars;
 pub mod xml;
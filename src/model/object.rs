@@ -65,14 +65,18 @@ impl Validate for DeviceRange {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DisplayAs {
+    #[default]
     #[serde(rename = "number")]
     Number,
     #[serde(rename = "currency")]
     Currency,
     #[serde(rename = "percent")]
     Percent,
+    /// Scientific notation (extension; not part of the XMILE specification).
+    #[serde(rename = "scientific")]
+    Scientific,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -98,13 +102,13 @@ impl DeviceScale {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct RawDeviceScale {
-    #[serde(rename = "@min")]
+    #[serde(rename = "@min", skip_serializing_if = "Option::is_none")]
     min: Option<f64>,
-    #[serde(rename = "@max")]
+    #[serde(rename = "@max", skip_serializing_if = "Option::is_none")]
     max: Option<f64>,
-    #[serde(rename = "@auto")]
+    #[serde(rename = "@auto", skip_serializing_if = "Option::is_none")]
     auto: Option<bool>,
-    #[serde(rename = "@group")]
+    #[serde(rename = "@group", skip_serializing_if = "Option::is_none")]
     group: Option<u32>,
 }
 
@@ -184,15 +188,15 @@ impl Serialize for DeviceScale {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FormatOptions {
-    #[serde(rename = "@precision")]
+    #[serde(rename = "@precision", skip_serializing_if = "Option::is_none")]
     pub precision: Option<f64>,
-    #[serde(rename = "@scale_by")]
+    #[serde(rename = "@scale_by", skip_serializing_if = "Option::is_none")]
     pub scale_by: Option<f64>,
-    #[serde(rename = "@display_as")]
+    #[serde(rename = "@display_as", skip_serializing_if = "Option::is_none")]
     pub display_as: Option<DisplayAs>,
-    #[serde(rename = "@delimit_000s")]
+    #[serde(rename = "@delimit_000s", skip_serializing_if = "Option::is_none")]
     pub delimit_000s: Option<bool>,
 }
 
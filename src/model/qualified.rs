@@ -0,0 +1,202 @@
+//! Names of variables qualified by the modules that contain them.
+//!
+//! A variable inside a submodel is referred to from the model using the
+//! submodel through the names of the modules on the way, as in
+//! `Sector.Plant.Capacity`. [`XmileFile::variables`](crate::xml::XmileFile::variables)
+//! yields every variable of a file with such a [`QualifiedName`].
+//!
+//! ```rust
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <model><variables>
+//!         <aux name="Rate"><eqn>0.1</eqn></aux>
+//!         <stock name="Population"><eqn>100</eqn></stock>
+//!     </variables></model>
+//! </xmile>"#).unwrap();
+//! let names: Vec<String> = file.variables().map(|(name, _)| name.to_string()).collect();
+//! assert_eq!(names, ["Rate", "Population"]);
+//! ```
+
+use std::fmt;
+
+use crate::Identifier;
+use crate::model::vars::Variable;
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+
+/// The name of a variable qualified by the modules leading to it from a
+/// model that is not itself used as a submodel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QualifiedName {
+    /// The name of the model the path starts from, or `None` for an unnamed
+    /// (root) model.
+    pub model: Option<String>,
+    /// The names of the modules from that model to the variable, outermost
+    /// first. Empty for variables of the model itself.
+    pub modules: Vec<Identifier>,
+    /// The name of the variable.
+    pub name: Identifier,
+}
+
+impl QualifiedName {
+    /// Whether the variable belongs to the model itself rather than to one of
+    /// its submodels.
+    pub fn is_top_level(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+impl fmt::Display for QualifiedName {
+    /// Writes the module names and variable name separated by dots, as the
+    /// variable is referred to from its model.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for module in &self.modules {
+            write!(f, "{}.", module)?;
+        }
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Every named variable of `models` with its qualified name.
+///
+/// Models not used as a submodel are visited in file order, and the
+/// variables of each in document order. The variables of a submodel follow
+/// the module that uses it. Modules referring to an external resource, and
+/// modules that would recurse into a model already being visited, are
+/// yielded without descending into them.
+pub(crate) fn qualified_variables(models: &[Model]) -> Vec<(QualifiedName, &Variable)> {
+    let mut variables = Vec::new();
+    for model in models {
+        if is_submodel(models, model) {
+            continue;
+        }
+        collect(models, model, &model.name, &[], &[model], &mut variables);
+    }
+    variables
+}
+
+fn collect<'a>(
+    models: &'a [Model],
+    model: &'a Model,
+    root: &Option<String>,
+    modules: &[Identifier],
+    visiting: &[&'a Model],
+    variables: &mut Vec<(QualifiedName, &'a Variable)>,
+) {
+    for variable in &model.variables.variables {
+        let Some(name) = get_variable_name(variable) else {
+            continue;
+        };
+        variables.push((
+            QualifiedName {
+                model: root.clone(),
+                modules: modules.to_vec(),
+                name: name.clone(),
+            },
+            variable,
+        ));
+
+        #[cfg(feature = "submodels")]
+        if let Variable::Module(module) = variable
+            && module.resource.is_none()
+            && let Some(submodel) = find_model(models, &module.name)
+            && !visiting.iter().any(|m| std::ptr::eq(*m, submodel))
+        {
+            let modules = [modules, std::slice::from_ref(&module.name)].concat();
+            let visiting = [visiting, &[submodel]].concat();
+            collect(models, submodel, root, &modules, &visiting, variables);
+        }
+    }
+    #[cfg(not(feature = "submodels"))]
+    let _ = (models, visiting);
+}
+
+/// Whether a module of some model instantiates `model`.
+#[cfg(feature = "submodels")]
+fn is_submodel(models: &[Model], model: &Model) -> bool {
+    models
+        .iter()
+        .flat_map(|m| &m.variables.variables)
+        .any(|variable| match variable {
+            Variable::Module(module) => {
+                module.resource.is_none()
+                    && find_model(models, &module.name).is_some_and(|m| std::ptr::eq(m, model))
+            }
+            _ => false,
+        })
+}
+
+#[cfg(not(feature = "submodels"))]
+fn is_submodel(_models: &[Model], _model: &Model) -> bool {
+    false
+}
+
+#[cfg(feature = "submodels")]
+fn find_model<'a>(models: &'a [Model], name: &Identifier) -> Option<&'a Model> {
+    models.iter().find(|model| {
+        model
+            .name
+            .as_deref()
+            .and_then(|n| Identifier::parse_from_attribute(n).ok())
+            .is_some_and(|n| &n == name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xml::XmileFile;
+
+    fn file(models: &str) -> XmileFile {
+        quick_xml::de::from_str(&format!(
+            r#"<xmile version="1.0"><header><vendor>Test</vendor><product version="1.0">Test</product></header>{models}</xmile>"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unqualified_variables() {
+        let file = file(
+            r#"<model><variables><aux name="a"><eqn>1</eqn></aux></variables></model>
+            <model name="Other"><variables><aux name="b"><eqn>2</eqn></aux></variables></model>"#,
+        );
+        let names: Vec<_> = qualified_variables(&file.models)
+            .into_iter()
+            .map(|(name, _)| (name.model.clone(), name.to_string()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (None, "a".to_string()),
+                (Some("Other".to_string()), "b".to_string())
+            ]
+        );
+    }
+
+    #[cfg(feature = "submodels")]
+    #[test]
+    fn test_module_variables_qualified() {
+        let file = file(
+            r#"<model><variables><aux name="Rate"><eqn>1</eqn></aux><module name="Sector"/></variables></model>
+            <model name="Sector"><variables><module name="Plant"/><aux name="Output"><eqn>2</eqn></aux></variables></model>
+            <model name="Plant"><variables><aux name="Capacity"><eqn>3</eqn></aux><module name="Sector"/></variables></model>"#,
+        );
+        let names: Vec<String> = qualified_variables(&file.models)
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Rate",
+                "Sector",
+                "Sector.Plant",
+                "Sector.Plant.Capacity",
+                "Sector.Plant.Sector",
+                "Sector.Output"
+            ]
+        );
+    }
+}
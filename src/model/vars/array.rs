@@ -131,7 +131,7 @@ mod tests {
     #[test]
     fn test_dimension_deserialization() {
         let xml = r#"<dim name="Length" />"#;
-        let dimension: Dimension = serde_xml_rs::from_str(xml).unwrap();
+        let dimension: Dimension = quick_xml::de::from_str(xml).unwrap();
         assert_eq!(dimension.name, "Length");
     }
 
@@ -140,10 +140,7 @@ mod tests {
         let dimension = Dimension {
             name: "Length".to_string(),
         };
-        let xml = serde_xml_rs::to_string(&dimension).unwrap();
-        assert_eq!(
-            xml,
-            r#"<?xml version="1.0" encoding="UTF-8"?><dim name="Length" />"#
-        );
+        let xml = quick_xml::se::to_string(&dimension).unwrap();
+        assert_eq!(xml, r#"<dim name="Length"/>"#);
     }
 }
@@ -19,23 +19,29 @@ use super::Var;
 pub struct Auxiliary {
     #[serde(rename = "@name")]
     pub name: Identifier,
-    #[serde(rename = "@access")]
+    #[serde(rename = "@access", skip_serializing_if = "Option::is_none")]
     pub access: Option<AccessType>,
-    #[serde(rename = "@autoexport")]
+    #[serde(rename = "@autoexport", skip_serializing_if = "Option::is_none")]
     pub autoexport: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub documentation: Option<Documentation>,
     #[serde(rename = "eqn")]
     pub equation: Expression,
     #[cfg(feature = "mathml")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mathml_equation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub units: Option<UnitEquation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub range: Option<DeviceRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub scale: Option<DeviceScale>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<FormatOptions>,
 
     /// The dimensions for this auxiliary variable (if it's an array).
     #[cfg(feature = "arrays")]
-    #[serde(rename = "dimensions")]
+    #[serde(rename = "dimensions", skip_serializing_if = "Option::is_none")]
     pub dimensions: Option<VariableDimensions>,
 
     /// Array elements for non-apply-to-all arrays.
@@ -44,7 +50,7 @@ pub struct Auxiliary {
     pub elements: Vec<ArrayElement>,
 
     /// Optional event poster for triggering events based on auxiliary values.
-    #[serde(rename = "event_poster")]
+    #[serde(rename = "event_poster", skip_serializing_if = "Option::is_none")]
     pub event_poster: Option<EventPoster>,
 }
 
@@ -6,8 +6,9 @@ use crate::{
     model::{
         events::EventPoster,
         object::{DeviceRange, DeviceScale, Document, Documentation, FormatOptions, Object},
-        vars::{AccessType, NonNegativeContent},
+        vars::{AccessType, NonNegativeContent, Variable, stock::Stock},
     },
+    xml::schema::Model,
 };
 
 #[cfg(feature = "arrays")]
@@ -16,6 +17,7 @@ use crate::model::vars::array::{ArrayElement, VariableDimensions};
 use super::Var;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum FlowConversionError {
     #[error("Missing leakage for conveyor flow")]
     MissingLeakage,
@@ -33,52 +35,52 @@ struct RawFlow {
     // Flow fields
     #[serde(rename = "@name")]
     name: Identifier,
-    #[serde(rename = "@access")]
+    #[serde(rename = "@access", skip_serializing_if = "Option::is_none")]
     access: Option<AccessType>,
-    #[serde(rename = "@autoexport")]
+    #[serde(rename = "@autoexport", skip_serializing_if = "Option::is_none")]
     autoexport: Option<bool>,
-    #[serde(rename = "@leak_start")]
+    #[serde(rename = "@leak_start", skip_serializing_if = "Option::is_none")]
     leak_start: Option<f64>,
-    #[serde(rename = "@leak_end")]
+    #[serde(rename = "@leak_end", skip_serializing_if = "Option::is_none")]
     leak_end: Option<f64>,
-    #[serde(rename = "eqn")]
+    #[serde(rename = "eqn", skip_serializing_if = "Option::is_none")]
     equation: Option<Expression>,
-    #[serde(rename = "mathml")]
+    #[serde(rename = "mathml", skip_serializing_if = "Option::is_none")]
     mathml_equation: Option<String>,
-    #[serde(rename = "multiplier")]
+    #[serde(rename = "multiplier", skip_serializing_if = "Option::is_none")]
     multiplier: Option<f64>,
     // Non-negative content
-    #[serde(rename = "non_negative")]
+    #[serde(rename = "non_negative", skip_serializing_if = "Option::is_none")]
     non_negative: Option<NonNegativeContent>,
     // QueueOverflow specific fields
-    #[serde(rename = "queue_overflow")]
+    #[serde(rename = "queue_overflow", skip_serializing_if = "Option::is_none")]
     queue_overflow: Option<OverflowFlag>,
     // ConveyorLeakage specific fields
-    #[serde(rename = "leak")]
+    #[serde(rename = "leak", skip_serializing_if = "Option::is_none")]
     leak: Option<LeakContent>,
-    #[serde(rename = "leak_integers")]
+    #[serde(rename = "leak_integers", skip_serializing_if = "Option::is_none")]
     leak_integers: Option<LeakIntegersFlag>,
     // Common fields
-    #[serde(rename = "units")]
+    #[serde(rename = "units", skip_serializing_if = "Option::is_none")]
     units: Option<UnitEquation>,
-    #[serde(rename = "doc")]
+    #[serde(rename = "doc", skip_serializing_if = "Option::is_none")]
     documentation: Option<Documentation>,
-    #[serde(rename = "range")]
+    #[serde(rename = "range", skip_serializing_if = "Option::is_none")]
     range: Option<DeviceRange>,
-    #[serde(rename = "scale")]
+    #[serde(rename = "scale", skip_serializing_if = "Option::is_none")]
     scale: Option<DeviceScale>,
-    #[serde(rename = "format")]
+    #[serde(rename = "format", skip_serializing_if = "Option::is_none")]
     format: Option<FormatOptions>,
 
     #[cfg(feature = "arrays")]
-    #[serde(rename = "dimensions")]
+    #[serde(rename = "dimensions", skip_serializing_if = "Option::is_none")]
     dimensions: Option<VariableDimensions>,
 
     #[cfg(feature = "arrays")]
     #[serde(rename = "element", default)]
     elements: Vec<ArrayElement>,
 
-    #[serde(rename = "event_poster")]
+    #[serde(rename = "event_poster", skip_serializing_if = "Option::is_none")]
     event_poster: Option<EventPoster>,
 }
 
@@ -141,7 +143,7 @@ struct OverflowFlag;
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 struct LeakContent {
-    #[serde(rename = "#text")]
+    #[serde(rename = "$text", skip_serializing_if = "Option::is_none")]
     fraction: Option<f64>,
 }
 
@@ -427,6 +429,61 @@ impl From<RawFlow> for BasicFlow {
     }
 }
 
+/// One end of a flow: a stock, or a cloud outside the model boundary.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FlowEndpoint {
+    Cloud,
+    Stock(Identifier),
+}
+
+/// Where a flow takes material from and where it puts it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlowEndpoints {
+    pub from: FlowEndpoint,
+    pub to: FlowEndpoint,
+}
+
+impl FlowEndpoints {
+    /// Whether the flow moves material between two stocks, so that it
+    /// leaves the total held in stocks unchanged.
+    pub fn is_internal(&self) -> bool {
+        matches!(
+            (&self.from, &self.to),
+            (FlowEndpoint::Stock(_), FlowEndpoint::Stock(_))
+        )
+    }
+
+    /// Whether the flow crosses the model boundary at one end or both.
+    pub fn is_boundary(&self) -> bool {
+        !self.is_internal()
+    }
+}
+
+impl BasicFlow {
+    /// The ends of this flow in `model`: the stock that lists it as an
+    /// outflow and the stock that lists it as an inflow. An end that no
+    /// stock lists is a cloud.
+    pub fn endpoints(&self, model: &Model) -> FlowEndpoints {
+        let end = |listed: fn(&Stock) -> &[Identifier]| {
+            model
+                .variables
+                .variables
+                .iter()
+                .find_map(|variable| match variable {
+                    Variable::Stock(stock) if listed(stock).contains(&self.name) => {
+                        Some(FlowEndpoint::Stock(stock.name().clone()))
+                    }
+                    _ => None,
+                })
+                .unwrap_or(FlowEndpoint::Cloud)
+        };
+        FlowEndpoints {
+            from: end(Stock::outflows),
+            to: end(Stock::inflows),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct QueueOverflow {
     pub name: Identifier,
@@ -640,7 +697,7 @@ impl TryFrom<RawFlow> for ConveyorLeakage {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_xml_rs::from_str;
+    use quick_xml::de::from_str;
 
     #[test]
     fn test_basic_flow() {
@@ -662,6 +719,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_flow_endpoints() {
+        let model: Model = from_str(
+            r#"<model>
+                <variables>
+                    <stock name="Susceptible"><eqn>99</eqn><outflow>infection</outflow></stock>
+                    <stock name="Infected">
+                        <eqn>1</eqn>
+                        <inflow>infection</inflow>
+                        <outflow>deaths</outflow>
+                    </stock>
+                    <flow name="infection"><eqn>0.1</eqn></flow>
+                    <flow name="deaths"><eqn>0.01</eqn></flow>
+                    <flow name="unused"><eqn>0</eqn></flow>
+                </variables>
+            </model>"#,
+        )
+        .unwrap();
+        let endpoints: Vec<FlowEndpoints> = model
+            .variables
+            .variables
+            .iter()
+            .filter_map(|variable| match variable {
+                Variable::Flow(flow) => Some(flow.endpoints(&model)),
+                _ => None,
+            })
+            .collect();
+        let stock = |name: &str| FlowEndpoint::Stock(Identifier::parse_default(name).unwrap());
+
+        assert_eq!(endpoints[0].from, stock("Susceptible"));
+        assert_eq!(endpoints[0].to, stock("Infected"));
+        assert!(endpoints[0].is_internal());
+        assert_eq!(
+            endpoints[1],
+            FlowEndpoints {
+                from: stock("Infected"),
+                to: FlowEndpoint::Cloud,
+            }
+        );
+        assert!(endpoints[1].is_boundary());
+        assert_eq!(endpoints[2].from, FlowEndpoint::Cloud);
+        assert_eq!(endpoints[2].to, FlowEndpoint::Cloud);
+    }
+
     #[test]
     fn test_flow_with_multiplier() {
         let xml = r#"<flow name="unit_converter">
@@ -914,7 +1015,7 @@ mod tests {
 
     #[test]
     fn test_flow_serialization_roundtrip() {
-        use serde_xml_rs::to_string;
+        use quick_xml::se::to_string_with_root;
 
         let original_xml = r#"<flow name="test_flow">
    <eqn>x + y</eqn>
@@ -923,7 +1024,7 @@ mod tests {
 </flow>"#;
 
         let flow: Flow = from_str(original_xml).expect("Failed to parse flow");
-        let serialized = to_string(&flow).expect("Failed to serialize flow");
+        let serialized = to_string_with_root("flow", &flow).expect("Failed to serialize flow");
         let reparsed: Flow = from_str(&serialized).expect("Failed to reparse flow");
 
         // Verify the roundtrip preserves the data
@@ -56,9 +56,9 @@ use crate::{
 #[cfg(feature = "arrays")]
 use crate::model::vars::array::{ArrayElement, VariableDimensions};
 
-pub use data::GraphicalFunctionData;
+pub use data::{GraphicalFunctionConversionError, GraphicalFunctionData, GraphicalFunctionTable};
 pub use function_type::GraphicalFunctionType;
-pub use points::GraphicalFunctionPoints;
+pub use points::{GraphicalFunctionPoints, PointsParseError};
 pub use scale::GraphicalFunctionScale;
 
 /// XMILE graphical function with metadata and interpolation behaviour.
@@ -311,6 +311,25 @@ impl GraphicalFunction {
             GraphicalFunctionType::Discrete => self.data.evaluate_discrete(x),
         }
     }
+
+    /// Prepares the function for repeated evaluation. See
+    /// [`GraphicalFunctionTable`].
+    pub fn table(&self) -> GraphicalFunctionTable {
+        self.data.table(self.function_type())
+    }
+
+    /// Evaluates the function at each of `xs`, writing the results to `ys`.
+    ///
+    /// Gives the same results as calling [`evaluate`](Self::evaluate) for
+    /// each value, but prepares the function once rather than for every
+    /// value. Keep a [`table`](Self::table) instead when evaluating
+    /// repeatedly.
+    ///
+    /// # Panics
+    /// Panics if `xs` and `ys` have different lengths.
+    pub fn evaluate_many(&self, xs: &[f64], ys: &mut [f64]) {
+        self.table().evaluate_many(xs, ys);
+    }
 }
 
 // VARIABLE IMPLEMENTATIONS
@@ -496,40 +515,44 @@ impl IndexMut<usize> for GraphicalFunction {
 
 // XML SERIALIZATION AND DESERIALIZATION
 
+fn skip_type(function_type: &Option<String>) -> bool {
+    crate::xml::serialize::skip_keyword(function_type.as_deref(), "continuous")
+}
+
 /// Helper struct for deserializing the raw XML structure
 #[derive(Debug, Serialize, Deserialize)]
 struct RawGraphicalFunction {
-    #[serde(rename = "@name")]
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
     name: Option<String>,
-    #[serde(rename = "@type")]
+    #[serde(rename = "@type", skip_serializing_if = "skip_type")]
     r#type: Option<String>,
-    #[serde(rename = "eqn")]
+    #[serde(rename = "eqn", skip_serializing_if = "Option::is_none")]
     equation: Option<Expression>,
-    #[serde(rename = "mathml")]
+    #[serde(rename = "mathml", skip_serializing_if = "Option::is_none")]
     mathml_equation: Option<String>,
-    #[serde(rename = "units")]
+    #[serde(rename = "units", skip_serializing_if = "Option::is_none")]
     units: Option<UnitEquation>,
-    #[serde(rename = "doc")]
+    #[serde(rename = "doc", skip_serializing_if = "Option::is_none")]
     documentation: Option<Documentation>,
-    #[serde(rename = "range")]
+    #[serde(rename = "range", skip_serializing_if = "Option::is_none")]
     range: Option<DeviceRange>,
-    #[serde(rename = "scale")]
+    #[serde(rename = "scale", skip_serializing_if = "Option::is_none")]
     scale: Option<DeviceScale>,
-    #[serde(rename = "format")]
+    #[serde(rename = "format", skip_serializing_if = "Option::is_none")]
     format: Option<FormatOptions>,
-    // serde-xml-rs doesn't support flattening the below enum directly,
+    // Serde can't flatten the below enum into an XML element directly,
     // so we deserialize it as additional fields
-    #[serde(rename = "xscale")]
+    #[serde(rename = "xscale", skip_serializing_if = "Option::is_none")]
     x_scale: Option<GraphicalFunctionScale>,
-    #[serde(rename = "yscale")]
+    #[serde(rename = "yscale", skip_serializing_if = "Option::is_none")]
     y_scale: Option<GraphicalFunctionScale>,
-    #[serde(rename = "ypts")]
+    #[serde(rename = "ypts", skip_serializing_if = "Option::is_none")]
     y_pts: Option<GraphicalFunctionPoints>,
-    #[serde(rename = "xpts")]
+    #[serde(rename = "xpts", skip_serializing_if = "Option::is_none")]
     x_pts: Option<GraphicalFunctionPoints>,
 
     #[cfg(feature = "arrays")]
-    #[serde(rename = "dimensions")]
+    #[serde(rename = "dimensions", skip_serializing_if = "Option::is_none")]
     dimensions: Option<VariableDimensions>,
 
     #[cfg(feature = "arrays")]
@@ -558,6 +581,7 @@ impl From<GraphicalFunctionData> for GraphicalFunction {
 
 /// Error types for parsing graphical functions from XML.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum GraphicalFunctionParseError {
     /// Error parsing the function name as an Identifier.
     #[error("Invalid name: {0}")]
@@ -660,7 +684,7 @@ impl From<&GraphicalFunction> for RawGraphicalFunction {
             ),
         };
         RawGraphicalFunction {
-            name: gf.name.as_ref().map(|n| n.to_string()),
+            name: gf.name.as_ref().map(|n| n.raw().to_string()),
             r#type: gf.r#type.as_ref().map(|t| t.to_string()),
             equation: gf.equation.clone(),
             mathml_equation: gf.mathml_equation.clone(),
@@ -1332,6 +1356,198 @@ pub mod data {
         }
     }
 
+    // BATCH EVALUATION
+
+    /// A graphical function prepared for repeated evaluation.
+    ///
+    /// The segment containing an x-value is found by index arithmetic for a
+    /// uniform scale and by binary search over the x-values of x-y pairs,
+    /// rather than by a linear scan, and the gradients used for extrapolation
+    /// are computed once. Results are the same as those of
+    /// [`GraphicalFunctionData::evaluate`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use xmile::{GraphicalFunction, GraphicalFunctionData};
+    ///
+    /// let gf: GraphicalFunction =
+    ///     GraphicalFunctionData::xy_pairs(vec![0.0, 1.0, 4.0], vec![0.0, 1.0, 2.5], None).into();
+    /// let table = gf.table();
+    /// let mut ys = [0.0; 3];
+    /// table.evaluate_many(&[0.5, 2.0, 9.0], &mut ys);
+    /// assert_eq!(ys, [0.5, 1.5, 2.5]);
+    /// ```
+    #[cfg_attr(feature = "artifacts", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct GraphicalFunctionTable {
+        function_type: GraphicalFunctionType,
+        x: TableX,
+        y_values: Vec<f64>,
+        left_gradient: f64,
+        right_gradient: f64,
+    }
+
+    #[cfg_attr(feature = "artifacts", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Debug, Clone, PartialEq)]
+    enum TableX {
+        /// A uniform scale, with the step between points or `None` if the
+        /// points coincide.
+        Uniform {
+            min: f64,
+            max: f64,
+            step: Option<f64>,
+        },
+        Pairs(Vec<f64>),
+    }
+
+    impl GraphicalFunctionData {
+        /// Prepares this data for repeated evaluation as a function of the
+        /// given type.
+        pub fn table(&self, function_type: GraphicalFunctionType) -> GraphicalFunctionTable {
+            let (x, left_gradient, right_gradient) = match self {
+                GraphicalFunctionData::UniformScale {
+                    x_scale, y_values, ..
+                } => {
+                    let delta = x_scale.delta();
+                    let step = delta / (y_values.len().max(1) - 1) as f64;
+                    let step =
+                        (delta.abs() >= f64::EPSILON && step.abs() >= f64::EPSILON).then_some(step);
+                    let x = TableX::Uniform {
+                        min: x_scale.min,
+                        max: x_scale.max,
+                        step,
+                    };
+                    (x, self.left_gradient(), self.right_gradient())
+                }
+                GraphicalFunctionData::XYPairs {
+                    x_values, y_values, ..
+                } => {
+                    let (left, right) = if x_values.len() >= 2 {
+                        let len = x_values.len();
+                        (
+                            Some((y_values[1] - y_values[0]) / (x_values[1] - x_values[0])),
+                            Some(
+                                (y_values[len - 1] - y_values[len - 2])
+                                    / (x_values[len - 1] - x_values[len - 2]),
+                            ),
+                        )
+                    } else {
+                        (None, None)
+                    };
+                    (TableX::Pairs(x_values.values.clone()), left, right)
+                }
+            };
+            let y_values = match self {
+                GraphicalFunctionData::UniformScale { y_values, .. }
+                | GraphicalFunctionData::XYPairs { y_values, .. } => y_values.values.clone(),
+            };
+            GraphicalFunctionTable {
+                function_type,
+                x,
+                y_values,
+                left_gradient: left_gradient.unwrap_or(0.0),
+                right_gradient: right_gradient.unwrap_or(0.0),
+            }
+        }
+    }
+
+    impl GraphicalFunctionTable {
+        /// Evaluates the function at `x`.
+        pub fn evaluate(&self, x: f64) -> f64 {
+            let y = &self.y_values;
+            let Some(&last_y) = y.last() else {
+                return 0.0;
+            };
+            let extrapolate = self.function_type == GraphicalFunctionType::Extrapolate;
+
+            let (first_x, last_x) = match &self.x {
+                TableX::Uniform { min, max, .. } => (*min, *max),
+                TableX::Pairs(x_values) => match (x_values.first(), x_values.last()) {
+                    (Some(first), Some(last)) => (*first, *last),
+                    _ => return 0.0,
+                },
+            };
+            let can_extrapolate = match &self.x {
+                TableX::Uniform { .. } => true,
+                TableX::Pairs(x_values) => x_values.len() >= 2,
+            };
+            if x == first_x {
+                return y[0];
+            }
+            if x == last_x {
+                return last_y;
+            }
+            if x < first_x {
+                return if extrapolate && can_extrapolate {
+                    y[0] + self.left_gradient * (x - first_x)
+                } else {
+                    y[0]
+                };
+            }
+            if x > last_x {
+                return if extrapolate && can_extrapolate {
+                    last_y + self.right_gradient * (x - last_x)
+                } else {
+                    last_y
+                };
+            }
+
+            let (lower, t) = match &self.x {
+                TableX::Uniform { min, step, .. } => {
+                    let Some(step) = step else {
+                        return y[0];
+                    };
+                    let exact_index = (x - min) / step;
+                    let lower = exact_index.floor() as usize;
+                    if lower >= y.len() - 1 {
+                        return y[lower.min(y.len() - 1)];
+                    }
+                    (lower, exact_index - lower as f64)
+                }
+                TableX::Pairs(x_values) => {
+                    // NaN compares less than no x-value, so falls past the last
+                    if x.is_nan() {
+                        return last_y;
+                    }
+                    let upper = x_values.partition_point(|&x_value| x_value <= x);
+                    if upper == 0 {
+                        return y[0];
+                    }
+                    let lower = upper - 1;
+                    if lower >= y.len() - 1 {
+                        return last_y;
+                    }
+                    let dx = x_values[upper] - x_values[lower];
+                    if dx.abs() < f64::EPSILON {
+                        return y[lower];
+                    }
+                    (lower, (x - x_values[lower]) / dx)
+                }
+            };
+
+            match self.function_type {
+                GraphicalFunctionType::Discrete => y[lower],
+                _ => f64::interpolate_between(y[lower], y[lower + 1], t),
+            }
+        }
+
+        /// Evaluates the function at each of `xs`, writing the results to
+        /// `ys`.
+        ///
+        /// # Panics
+        /// Panics if `xs` and `ys` have different lengths.
+        pub fn evaluate_many(&self, xs: &[f64], ys: &mut [f64]) {
+            assert_eq!(
+                xs.len(),
+                ys.len(),
+                "x-values and results must have the same length"
+            );
+            for (x, y) in xs.iter().zip(ys.iter_mut()) {
+                *y = self.evaluate(*x);
+            }
+        }
+    }
+
     // VALIDATION LOGIC
 
     impl Validate for GraphicalFunctionData {
@@ -1351,6 +1567,11 @@ pub mod data {
                 } => {
                     validation_utils::_chain(Self::validate_y_values(y_values), w, e);
                     validation_utils::_chain(Self::validate_x_scale(&Some(*x_scale)), w, e);
+                    validation_utils::_chain(
+                        Self::validate_point_count(x_scale, y_values.len()),
+                        w,
+                        e,
+                    );
                     validation_utils::_chain(Self::validate_y_scale(y_scale), w, e);
                 }
                 GraphicalFunctionData::XYPairs {
@@ -1372,6 +1593,158 @@ pub mod data {
         }
     }
 
+    /// Errors converting graphical function data between representations.
+    #[derive(Debug, Clone, PartialEq, Error)]
+    #[non_exhaustive]
+    pub enum GraphicalFunctionConversionError {
+        #[error("Graphical function data has no points")]
+        Empty,
+        #[error("x-value {found} at index {index} is not evenly spaced; expected {expected}")]
+        NonUniformSpacing {
+            index: usize,
+            expected: f64,
+            found: f64,
+        },
+    }
+
+    impl GraphicalFunctionData {
+        /// The x-value of each point, in order.
+        pub fn x_values(&self) -> Vec<f64> {
+            match self {
+                GraphicalFunctionData::UniformScale {
+                    x_scale, y_values, ..
+                } => {
+                    let len = y_values.len();
+                    if len == 1 {
+                        return vec![x_scale.min];
+                    }
+                    let step = x_scale.delta() / (len - 1) as f64;
+                    (0..len)
+                        .map(|index| {
+                            if index == len - 1 {
+                                x_scale.max
+                            } else {
+                                x_scale.min + step * index as f64
+                            }
+                        })
+                        .collect()
+                }
+                GraphicalFunctionData::XYPairs { x_values, .. } => x_values.values.clone(),
+            }
+        }
+
+        /// The same points as explicit x-y pairs.
+        pub fn to_xy_pairs(&self) -> GraphicalFunctionData {
+            match self {
+                GraphicalFunctionData::UniformScale {
+                    y_scale, y_values, ..
+                } => GraphicalFunctionData::XYPairs {
+                    y_scale: *y_scale,
+                    x_values: GraphicalFunctionPoints::new(
+                        self.x_values(),
+                        y_values.separator.clone(),
+                    ),
+                    y_values: y_values.clone(),
+                },
+                GraphicalFunctionData::XYPairs { .. } => self.clone(),
+            }
+        }
+
+        /// The same points as a uniform scale.
+        ///
+        /// Fails if an x-value is further than `tolerance` from where evenly
+        /// spaced points between the first and last x-values would put it;
+        /// see [`resample_uniform`](GraphicalFunctionData::resample_uniform)
+        /// for unevenly spaced points.
+        pub fn to_uniform_scale(
+            &self,
+            tolerance: f64,
+        ) -> Result<GraphicalFunctionData, GraphicalFunctionConversionError> {
+            let (y_scale, x_values, y_values) = match self {
+                GraphicalFunctionData::UniformScale { .. } => return Ok(self.clone()),
+                GraphicalFunctionData::XYPairs {
+                    y_scale,
+                    x_values,
+                    y_values,
+                } => (y_scale, x_values, y_values),
+            };
+            let (Some(&first), Some(&last)) = (x_values.first(), x_values.last()) else {
+                return Err(GraphicalFunctionConversionError::Empty);
+            };
+
+            let step = if x_values.len() > 1 {
+                (last - first) / (x_values.len() - 1) as f64
+            } else {
+                0.0
+            };
+            for (index, &found) in x_values.iter().enumerate() {
+                let expected = first + step * index as f64;
+                if (found - expected).abs() > tolerance {
+                    return Err(GraphicalFunctionConversionError::NonUniformSpacing {
+                        index,
+                        expected,
+                        found,
+                    });
+                }
+            }
+
+            Ok(GraphicalFunctionData::UniformScale {
+                x_scale: GraphicalFunctionScale::new(first, last),
+                y_scale: *y_scale,
+                y_values: y_values.clone(),
+            })
+        }
+
+        /// Evaluates the function at `points` evenly spaced x-values between
+        /// the first and last x-values, as a uniform scale.
+        ///
+        /// Points between the original x-values are interpolated as
+        /// `function_type` evaluates them, so resampling a discrete function
+        /// keeps its steps at the resolution of the new points.
+        ///
+        /// # Panics
+        /// Panics if `points` is zero.
+        pub fn resample_uniform(
+            &self,
+            function_type: GraphicalFunctionType,
+            points: usize,
+        ) -> GraphicalFunctionData {
+            assert!(points > 0, "cannot resample to zero points");
+            let x_values = self.x_values();
+            let first = x_values.first().copied().unwrap_or(0.0);
+            let last = if points > 1 {
+                x_values.last().copied().unwrap_or(first)
+            } else {
+                first
+            };
+            let mut resampled = GraphicalFunctionData::UniformScale {
+                x_scale: GraphicalFunctionScale::new(first, last),
+                y_scale: self.y_scale_explicit(),
+                y_values: vec![0.0; points].into(),
+            };
+
+            let table = self.table(function_type);
+            let x_values = resampled.x_values();
+            if let GraphicalFunctionData::UniformScale { y_values, .. } = &mut resampled {
+                for (y, x) in y_values.iter_mut().zip(x_values) {
+                    *y = table.evaluate(x);
+                }
+                y_values.separator = match self {
+                    GraphicalFunctionData::UniformScale { y_values, .. }
+                    | GraphicalFunctionData::XYPairs { y_values, .. } => y_values.separator.clone(),
+                };
+            }
+            resampled
+        }
+
+        fn y_scale_explicit(&self) -> Option<GraphicalFunctionScale> {
+            match self {
+                GraphicalFunctionData::UniformScale { y_scale, .. }
+                | GraphicalFunctionData::XYPairs { y_scale, .. } => *y_scale,
+            }
+        }
+    }
+
     impl GraphicalFunctionData {
         fn validate_x_values(x_values: &GraphicalFunctionPoints, y_len: usize) -> ValidationResult {
             let mut warnings = Vec::new();
@@ -1382,7 +1755,11 @@ pub mod data {
 
             validation_utils::_chain(x_values.validate(), w, e);
             validation_utils::_chain(validation_utils::validate_length(x_values, y_len), w, e);
-            validation_utils::_chain(validation_utils::validate_ascending(x_values), w, e);
+            validation_utils::_chain(
+                validation_utils::validate_strictly_ascending(x_values),
+                w,
+                e,
+            );
 
             validation_utils::_return(warnings, errors)
         }
@@ -1400,6 +1777,32 @@ pub mod data {
             validation_utils::_return(warnings, errors)
         }
 
+        /// Checks that a uniform x-scale can spread `y_len` points: two or more
+        /// points need a non-zero range, and a single point needs a zero
+        /// range.
+        fn validate_point_count(
+            x_scale: &GraphicalFunctionScale,
+            y_len: usize,
+        ) -> ValidationResult {
+            let warnings = Vec::new();
+            let mut errors = Vec::new();
+
+            let zero_range = validation_utils::_float_equals(x_scale.min, x_scale.max);
+            if y_len == 1 && !zero_range {
+                errors.push(format!(
+                    "A single y-value needs a zero-range x-scale, but the x-scale is {} to {}.",
+                    x_scale.min, x_scale.max
+                ));
+            } else if y_len >= 2 && zero_range {
+                errors.push(format!(
+                    "{} y-values need an x-scale with min < max, but both are {}.",
+                    y_len, x_scale.min
+                ));
+            }
+
+            validation_utils::_return(warnings, errors)
+        }
+
         fn validate_x_scale(x_scale: &Option<GraphicalFunctionScale>) -> ValidationResult {
             let mut warnings = Vec::new();
             let mut errors = Vec::new();
@@ -1428,18 +1831,19 @@ pub mod data {
     /// Graphical Function Data XML representation.
     #[derive(Debug, Serialize, Deserialize)]
     pub(super) struct RawGraphicalFunctionData {
-        #[serde(rename = "xscale")]
+        #[serde(rename = "xscale", skip_serializing_if = "Option::is_none")]
         pub(super) x_scale: Option<GraphicalFunctionScale>,
-        #[serde(rename = "yscale")]
+        #[serde(rename = "yscale", skip_serializing_if = "Option::is_none")]
         pub(super) y_scale: Option<GraphicalFunctionScale>,
-        #[serde(rename = "ypts")]
+        #[serde(rename = "ypts", skip_serializing_if = "Option::is_none")]
         pub(super) y_pts: Option<GraphicalFunctionPoints>,
-        #[serde(rename = "xpts")]
+        #[serde(rename = "xpts", skip_serializing_if = "Option::is_none")]
         pub(super) x_pts: Option<GraphicalFunctionPoints>,
     }
 
     /// Error types for parsing graphical function data from XML.
     #[derive(Debug, Error)]
+    #[non_exhaustive]
     pub enum GraphicalFunctionDataParseError {
         #[error("ypts is required for graphical function data")]
         MissingYPoints,
@@ -1858,9 +2262,11 @@ pub mod scale {
 ///
 /// The original separator is preserved for round-trip XML serialization.
 pub mod points {
+    use std::fmt;
     use std::ops::{Deref, DerefMut, Index, IndexMut};
 
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use thiserror::Error;
 
     use crate::{
         types::{Validate, ValidationResult},
@@ -1894,6 +2300,74 @@ pub mod points {
         pub fn separator(&self) -> Option<&str> {
             self.separator.as_deref()
         }
+
+        /// Parses the text of an `<xpts>` or `<ypts>` element, whose values
+        /// are separated by `separator` (a comma if `None`).
+        ///
+        /// Whitespace around values and a trailing separator are ignored. A
+        /// separator of only whitespace, such as a space or a tab, matches
+        /// any run of whitespace. The separator is kept, so
+        /// [`to_string`](ToString::to_string) writes the points back the
+        /// same way.
+        ///
+        /// ```rust
+        /// use xmile::model::vars::gf::GraphicalFunctionPoints;
+        ///
+        /// let points = GraphicalFunctionPoints::parse(" 0; 0.5 ;1; ", Some(";")).unwrap();
+        /// assert_eq!(points.values, [0.0, 0.5, 1.0]);
+        /// assert_eq!(points.to_string(), "0;0.5;1");
+        /// ```
+        pub fn parse(text: &str, separator: Option<&str>) -> Result<Self, PointsParseError> {
+            let sep = separator.unwrap_or(DEFAULT_SEPARATOR);
+            let mut parts: Vec<&str> = if sep.trim().is_empty() {
+                text.split_whitespace().collect()
+            } else {
+                text.split(sep).map(str::trim).collect()
+            };
+            if parts.last().is_some_and(|part| part.is_empty()) {
+                parts.pop();
+            }
+
+            let values = parts
+                .into_iter()
+                .enumerate()
+                .map(|(index, part)| {
+                    part.parse::<f64>().map_err(|_| PointsParseError {
+                        index,
+                        value: part.to_string(),
+                    })
+                })
+                .collect::<Result<Vec<f64>, _>>()?;
+            Ok(GraphicalFunctionPoints::new(
+                values,
+                separator.map(str::to_string),
+            ))
+        }
+    }
+
+    const DEFAULT_SEPARATOR: &str = ",";
+
+    /// A value in the text of points that is not a number.
+    #[derive(Debug, Clone, PartialEq, Eq, Error)]
+    #[error("Invalid point '{value}' at index {index}")]
+    pub struct PointsParseError {
+        pub index: usize,
+        pub value: String,
+    }
+
+    impl fmt::Display for GraphicalFunctionPoints {
+        /// Writes the values separated by the separator, formatted with the
+        /// current [`SerializeOptions`](crate::xml::SerializeOptions).
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let sep = self.separator().unwrap_or(DEFAULT_SEPARATOR);
+            for (index, value) in self.values.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(sep)?;
+                }
+                f.write_str(&crate::xml::serialize::format_point(*value))?;
+            }
+            Ok(())
+        }
     }
 
     // VALIDATION LOGIC
@@ -1918,28 +2392,18 @@ pub mod points {
     /// Points XML representation.
     #[derive(Debug, Serialize, Deserialize)]
     struct RawGraphicalFunctionPoints {
-        #[serde(rename = "@sep")]
+        #[serde(rename = "@sep", skip_serializing_if = "Option::is_none")]
         separator: Option<String>,
-        #[serde(rename = "#text")]
+        #[serde(rename = "$text")]
         data: String,
     }
 
     impl TryFrom<RawGraphicalFunctionPoints> for GraphicalFunctionPoints {
-        type Error = String;
+        type Error = PointsParseError;
 
         /// Converts a RawGraphicalFunctionPoints into GraphicalFunctionPoints.
         fn try_from(raw: RawGraphicalFunctionPoints) -> Result<Self, Self::Error> {
-            let sep = raw.separator.as_deref().unwrap_or(",");
-            raw.data
-                .split(sep)
-                .map(|val_str| {
-                    val_str
-                        .trim()
-                        .parse::<f64>()
-                        .map_err(|_| val_str.to_string())
-                })
-                .collect::<Result<Vec<f64>, _>>()
-                .map(|vals| GraphicalFunctionPoints::new(vals, raw.separator))
+            GraphicalFunctionPoints::parse(&raw.data, raw.separator.as_deref())
         }
     }
 
@@ -1951,9 +2415,9 @@ pub mod points {
         {
             let raw: RawGraphicalFunctionPoints =
                 RawGraphicalFunctionPoints::deserialize(deserializer)?;
-            GraphicalFunctionPoints::try_from(raw).map_err(|invalid_float| {
+            GraphicalFunctionPoints::try_from(raw).map_err(|error| {
                 serde::de::Error::invalid_value(
-                    serde::de::Unexpected::Str(invalid_float.as_str()),
+                    serde::de::Unexpected::Str(&error.value),
                     &"a valid f64 value (e.g. '1.0', '2.5', '-3.14')",
                 )
             })
@@ -1966,15 +2430,9 @@ pub mod points {
         where
             S: Serializer,
         {
-            let sep = self.separator.as_deref().unwrap_or(",");
             let raw = RawGraphicalFunctionPoints {
                 separator: self.separator.clone(),
-                data: self
-                    .values
-                    .iter()
-                    .map(|v| v.to_string())
-                    .collect::<Vec<_>>()
-                    .join(sep),
+                data: self.to_string(),
             };
             raw.serialize(serializer)
         }
@@ -2146,6 +2604,73 @@ mod tests {
             GraphicalFunctionData::xy_pairs(vec![0.0, 0.5], vec![0.0, 0.3, 1.0], None);
         }
 
+        #[test]
+        fn test_x_values_strictly_increasing() {
+            let repeated = GraphicalFunctionData::xy_pairs(
+                vec![0.0, 1.0, 1.0, 2.0],
+                vec![0.0, 5.0, 5.0, 10.0],
+                None,
+            );
+            assert!(repeated.validate().is_invalid());
+
+            let nan = GraphicalFunctionData::xy_pairs(vec![0.0, f64::NAN], vec![0.0, 1.0], None);
+            assert!(nan.validate().is_invalid());
+        }
+
+        #[test]
+        fn test_uniform_scale_point_count() {
+            let single = |x_scale| GraphicalFunctionData::uniform_scale(x_scale, vec![0.5], None);
+            assert!(single((5.0, 5.0)).validate().is_valid());
+            assert!(single((0.0, 1.0)).validate().is_invalid());
+
+            let flat = GraphicalFunctionData::uniform_scale((5.0, 5.0), vec![0.0, 1.0], None);
+            assert!(flat.validate().is_invalid());
+        }
+
+        #[test]
+        fn test_table_matches_evaluate() {
+            let xs: Vec<f64> = (-20..=60).map(|i| i as f64 * 0.1).collect();
+            let data = [
+                GraphicalFunctionData::uniform_scale(
+                    (0.0, 4.0),
+                    vec![0.0, 0.3, 0.9, 1.0, 1.0],
+                    None,
+                ),
+                GraphicalFunctionData::uniform_scale((1.0, 1.0), vec![2.0], None),
+                GraphicalFunctionData::xy_pairs(
+                    vec![0.0, 0.5, 2.0, 3.5],
+                    vec![1.0, 0.2, 0.7, 0.7],
+                    None,
+                ),
+            ];
+            for data in data {
+                for function_type in [
+                    GraphicalFunctionType::Continuous,
+                    GraphicalFunctionType::Extrapolate,
+                    GraphicalFunctionType::Discrete,
+                ] {
+                    let mut ys = vec![0.0; xs.len()];
+                    data.table(function_type.clone())
+                        .evaluate_many(&xs, &mut ys);
+                    for (x, y) in xs.iter().zip(&ys) {
+                        assert_eq!(
+                            *y,
+                            data.evaluate(function_type.clone(), *x),
+                            "{function_type} at {x}"
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "x-values and results must have the same length")]
+        fn test_evaluate_many_mismatched_lengths() {
+            let gf: GraphicalFunction =
+                GraphicalFunctionData::uniform_scale((0.0, 1.0), vec![0.0, 1.0], None).into();
+            gf.evaluate_many(&[0.0, 0.5], &mut [0.0]);
+        }
+
         #[test]
         fn test_y_scale_inference() {
             let data = GraphicalFunctionData::uniform_scale((0.0, 1.0), vec![0.2, 0.8, 0.5], None);
@@ -2167,6 +2692,52 @@ mod tests {
             assert_eq!(scale.min, 0.0);
             assert_eq!(scale.max, 1.0);
         }
+
+        #[test]
+        fn test_convert_between_representations() {
+            let uniform = GraphicalFunctionData::uniform_scale(
+                (0.0, 1.0),
+                vec![0.0, 0.4, 0.9],
+                Some((0.0, 1.0)),
+            );
+            let pairs = uniform.to_xy_pairs();
+            assert_eq!(
+                pairs,
+                GraphicalFunctionData::xy_pairs(
+                    vec![0.0, 0.5, 1.0],
+                    vec![0.0, 0.4, 0.9],
+                    Some((0.0, 1.0))
+                )
+            );
+            assert_eq!(pairs.to_uniform_scale(1e-9), Ok(uniform));
+
+            let uneven =
+                GraphicalFunctionData::xy_pairs(vec![0.0, 0.2, 1.0], vec![0.0, 0.4, 0.9], None);
+            assert_eq!(
+                uneven.to_uniform_scale(0.1),
+                Err(data::GraphicalFunctionConversionError::NonUniformSpacing {
+                    index: 1,
+                    expected: 0.5,
+                    found: 0.2
+                })
+            );
+        }
+
+        #[test]
+        fn test_resample_uniform() {
+            let uneven =
+                GraphicalFunctionData::xy_pairs(vec![0.0, 0.2, 1.0], vec![0.0, 0.4, 0.8], None);
+            let resampled = uneven.resample_uniform(GraphicalFunctionType::Continuous, 5);
+            assert_eq!(resampled.x_values(), [0.0, 0.25, 0.5, 0.75, 1.0]);
+            for x in [0.0, 0.25, 0.5, 1.0] {
+                let expected = uneven.evaluate(GraphicalFunctionType::Continuous, x);
+                let found = resampled.evaluate(GraphicalFunctionType::Continuous, x);
+                assert!(
+                    (expected - found).abs() < 1e-12,
+                    "{x}: {expected} != {found}"
+                );
+            }
+        }
     }
 
     mod function_type {
@@ -2266,6 +2837,54 @@ mod tests {
             points[1] = 0.7;
             assert_eq!(points[1], 0.7);
         }
+
+        #[test]
+        fn test_points_parse_tolerates_whitespace_and_trailing_separator() {
+            let points = GraphicalFunctionPoints::parse("\n  0, 0.5 ,\t1,\n", None).unwrap();
+            assert_eq!(points.values, [0.0, 0.5, 1.0]);
+            assert_eq!(points.to_string(), "0,0.5,1");
+
+            let points = GraphicalFunctionPoints::parse(" 0   0.5\n1 ", Some(" ")).unwrap();
+            assert_eq!(points.values, [0.0, 0.5, 1.0]);
+            assert_eq!(points.to_string(), "0 0.5 1");
+
+            assert_eq!(
+                GraphicalFunctionPoints::parse("0,,1", None),
+                Err(PointsParseError {
+                    index: 1,
+                    value: String::new()
+                })
+            );
+            let parsed: GraphicalFunctionPoints =
+                quick_xml::de::from_str(r#"<ypts sep="|">0| 2 |</ypts>"#).unwrap();
+            assert_eq!(
+                parsed,
+                GraphicalFunctionPoints::new(vec![0.0, 2.0], Some("|".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_points_serialization_precision() {
+            use crate::xml::serialize::{FloatFormat, SerializeOptions, with_options};
+
+            let points = GraphicalFunctionPoints::new(vec![0.0, 1.0 / 3.0, 0.1 + 0.2], None);
+            let shortest = quick_xml::se::to_string_with_root("ypts", &points).unwrap();
+            assert_eq!(
+                shortest,
+                "<ypts>0,0.3333333333333333,0.30000000000000004</ypts>"
+            );
+            let parsed: GraphicalFunctionPoints = quick_xml::de::from_str(&shortest).unwrap();
+            assert_eq!(parsed, points);
+
+            let options = SerializeOptions {
+                point_format: FloatFormat::Significant(3),
+                ..SerializeOptions::default()
+            };
+            let rounded = with_options(&options, || {
+                quick_xml::se::to_string_with_root("ypts", &points).unwrap()
+            });
+            assert_eq!(rounded, "<ypts>0,0.333,0.3</ypts>");
+        }
     }
 
     mod edge_case_tests {
@@ -2544,7 +3163,7 @@ mod tests {
                 <ypts>0,0.1,0.5,0.9,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 assert_eq!(
                     function.name,
@@ -2575,7 +3194,7 @@ mod tests {
                 <ypts>0,0.3,0.55,0.7,0.83,0.9,0.95,0.98,0.99,0.995,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 assert_eq!(
                     function.name,
@@ -2611,7 +3230,7 @@ mod tests {
                 <ypts>0,0.3,0.55,0.7,0.83,0.9,0.95,0.98,0.99,0.995,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 assert_eq!(
                     function.name,
@@ -2649,7 +3268,7 @@ mod tests {
                 <ypts>0,0.3,0.55,0.7,0.83,0.9,0.95,0.98,0.99,0.995,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 assert!(function.name.is_none()); // Anonymous/embedded
                 assert!(function.r#type.is_none()); // Should default
@@ -2682,7 +3301,7 @@ mod tests {
                 <ypts>0.05,0.1,0.2,0.25,0.3,0.33</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -2696,7 +3315,7 @@ mod tests {
                 <ypts>0.05,0.1,0.2,0.25,0.3,0.33</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -2709,7 +3328,7 @@ mod tests {
                 <ypts>0.05,0.1,0.2,0.25</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 // This should fail during validation, but may parse successfully initially
                 // The validation would catch the unordered x-values
                 if let Ok(function) = result {
@@ -2727,7 +3346,7 @@ mod tests {
                 <ypts>0.05,0.1,0.2,0.25,0.3,0.33</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
         }
@@ -2758,7 +3377,7 @@ mod tests {
                 <scale min="1.0" max="2.0"></scale>
                 <format precision="0.01" scale_by="1000" display_as="percent" delimit_000s="true" />
             </gf>"#;
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
                 println!("{:#?}", function);
 
                 assert_eq!(
@@ -2782,8 +3401,8 @@ mod tests {
                     _ => panic!("Expected UniformScale variant"),
                 }
 
-                let expected_equation = Expression::add(
-                    Expression::add(
+                let expected_equation = Expression::binary_add(
+                    Expression::binary_add(
                         Expression::exponentiation(
                             Expression::subscript(Identifier::parse_default("x").unwrap(), vec![]),
                             Expression::constant(NumericConstant::from(2.0)),
@@ -2859,11 +3478,11 @@ mod tests {
             </gf>"#;
 
                 let continuous_func: GraphicalFunction =
-                    serde_xml_rs::from_str(continuous_xml).unwrap();
+                    quick_xml::de::from_str(continuous_xml).unwrap();
                 let extrapolate_func: GraphicalFunction =
-                    serde_xml_rs::from_str(extrapolate_xml).unwrap();
+                    quick_xml::de::from_str(extrapolate_xml).unwrap();
                 let discrete_func: GraphicalFunction =
-                    serde_xml_rs::from_str(discrete_xml).unwrap();
+                    quick_xml::de::from_str(discrete_xml).unwrap();
 
                 assert_eq!(
                     continuous_func.r#type,
@@ -2883,7 +3502,7 @@ mod tests {
                 <ypts>0,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
                 assert_eq!(function.r#type, Some(GraphicalFunctionType::Continuous));
             }
 
@@ -2895,7 +3514,7 @@ mod tests {
                 <ypts>0,0.5,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale { y_scale, .. } => {
@@ -2912,7 +3531,7 @@ mod tests {
                 <ypts>-1,-0.5,0,0.5,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale {
@@ -2933,7 +3552,7 @@ mod tests {
                 <ypts>42</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale {
@@ -2954,7 +3573,7 @@ mod tests {
                 <ypts>0.01,0.25,0.75,0.99</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale {
@@ -2975,7 +3594,7 @@ mod tests {
                 <ypts>1e-6,1e-3,1e0,1e3,1e6</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale {
@@ -3000,7 +3619,7 @@ mod tests {
                 <ypts>10,50,90</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::XYPairs {
@@ -3023,7 +3642,7 @@ mod tests {
                 <ypts>0,0.2,0.3,0.4,0.7,0.9,1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::XYPairs {
@@ -3048,7 +3667,7 @@ mod tests {
                 <ypts sep=";">0;0.25;0.5;0.75;1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale { y_values, .. } => {
@@ -3066,7 +3685,7 @@ mod tests {
                 <ypts sep="|">10|50|90</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::XYPairs {
@@ -3088,7 +3707,7 @@ mod tests {
                 <ypts sep=" ">0 1 4</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale { y_values, .. } => {
@@ -3106,7 +3725,7 @@ mod tests {
                 <ypts sep="	">0	0.5	1</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale { y_values, .. } => {
@@ -3125,7 +3744,7 @@ mod tests {
                 <ypts sep=",">10,50,90</ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::XYPairs {
@@ -3151,7 +3770,7 @@ mod tests {
                 <xscale min="0" max="1"/>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3161,7 +3780,7 @@ mod tests {
                 <ypts>0,0.5,1</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3172,7 +3791,7 @@ mod tests {
                 <ypts></ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3183,7 +3802,7 @@ mod tests {
                 <ypts>0,invalid,1</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3194,7 +3813,7 @@ mod tests {
                 <ypts>0,1</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3206,7 +3825,7 @@ mod tests {
                 <ypts>0,0.5,1</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3217,7 +3836,7 @@ mod tests {
                 <ypts>0,0.5</ypts>
             </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
 
@@ -3229,7 +3848,7 @@ mod tests {
             </gf>"#;
 
                 // This should fail because separator is "," but values use ";"
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(xml);
                 assert!(result.is_err());
             }
         }
@@ -3245,7 +3864,7 @@ mod tests {
                 <ypts> 0 , 0.25 , 0.5 , 0.75 , 1 </ypts>
             </gf>"#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale { y_values, .. } => {
@@ -3271,7 +3890,7 @@ mod tests {
             </gf>
             "#;
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale {
@@ -3303,7 +3922,7 @@ mod tests {
                     ypts_str
                 );
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(&xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(&xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::UniformScale { y_values, .. } => {
@@ -3337,7 +3956,7 @@ mod tests {
                     y_values.join(",")
                 );
 
-                let function: GraphicalFunction = serde_xml_rs::from_str(&xml).unwrap();
+                let function: GraphicalFunction = quick_xml::de::from_str(&xml).unwrap();
 
                 match function.data {
                     GraphicalFunctionData::XYPairs {
@@ -3365,7 +3984,7 @@ mod tests {
             <ypts>0,1
         </gf>"#;
 
-                let result1: Result<GraphicalFunction, _> = serde_xml_rs::from_str(malformed_xml1);
+                let result1: Result<GraphicalFunction, _> = quick_xml::de::from_str(malformed_xml1);
                 assert!(
                     result1.is_err(),
                     "Should fail gracefully with malformed XML"
@@ -3378,7 +3997,7 @@ mod tests {
             </xscale>
         </gf>"#;
 
-                let result2: Result<GraphicalFunction, _> = serde_xml_rs::from_str(malformed_xml2);
+                let result2: Result<GraphicalFunction, _> = quick_xml::de::from_str(malformed_xml2);
                 assert!(
                     result2.is_err(),
                     "Should fail with incorrectly nested elements"
@@ -3394,7 +4013,7 @@ mod tests {
             <ypts>0,1,2</ypts>
         </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(mixed_xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(mixed_xml);
                 assert!(
                     result.is_err(),
                     "Should fail when scale has invalid numeric values"
@@ -3407,7 +4026,7 @@ mod tests {
         </gf>"#;
 
                 let result2: Result<GraphicalFunction, _> =
-                    serde_xml_rs::from_str(mixed_points_xml);
+                    quick_xml::de::from_str(mixed_points_xml);
                 assert!(
                     result2.is_err(),
                     "Should fail when points contain invalid numeric values"
@@ -3431,7 +4050,7 @@ mod tests {
                     long_name, long_values
                 );
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(&xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(&xml);
                 match result {
                     Ok(gf) => {
                         assert_eq!(gf.name.as_ref().unwrap().to_string(), long_name);
@@ -3454,7 +4073,7 @@ mod tests {
 
                 // Should parse successfully, ignoring unexpected attributes
                 let result: Result<GraphicalFunction, _> =
-                    serde_xml_rs::from_str(xml_with_extra_attrs);
+                    quick_xml::de::from_str(xml_with_extra_attrs);
                 match result {
                     Ok(gf) => {
                         assert_eq!(gf.name.as_ref().unwrap().to_string(), "test");
@@ -3474,7 +4093,7 @@ mod tests {
             <ypts><![CDATA[0,0.5,1]]></ypts>
         </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(cdata_xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(cdata_xml);
                 // Should either parse correctly or fail gracefully
                 match result {
                     Ok(gf) => match gf.data {
@@ -3497,7 +4116,7 @@ mod tests {
             <ypts>0,1</ypts>
         </gf>"#;
 
-                let result: Result<GraphicalFunction, _> = serde_xml_rs::from_str(unicode_xml);
+                let result: Result<GraphicalFunction, _> = quick_xml::de::from_str(unicode_xml);
                 assert_eq!(result.unwrap().name.as_ref().unwrap().raw(), "函数_测试_🧪");
             }
         }
@@ -114,7 +114,7 @@ impl Serialize for NonNegativeContent {
         use serde::ser::SerializeStruct;
         let mut state = serializer.serialize_struct("non_negative", 1)?;
         // Always serialize #text field, even if None, to match deserializer expectations
-        state.serialize_field("#text", &self.value)?;
+        state.serialize_field("$text", &self.value)?;
         state.end()
     }
 }
@@ -127,7 +127,7 @@ impl<'de> Deserialize<'de> for NonNegativeContent {
         // Try to deserialize as a struct with #text field
         #[derive(Deserialize)]
         struct Helper {
-            #[serde(rename = "#text", default)]
+            #[serde(rename = "$text", default)]
             value: Option<bool>,
         }
 
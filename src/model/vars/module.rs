@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     Identifier,
     model::object::{Document, Documentation, Object},
+    resource::ResourceRef,
 };
 
 use super::Var;
@@ -21,8 +22,8 @@ pub struct Module {
     pub name: Identifier,
 
     /// Optional resource reference to the submodel's file (URL, relative, or absolute path).
-    #[serde(rename = "@resource")]
-    pub resource: Option<String>,
+    #[serde(rename = "@resource", skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ResourceRef>,
 
     /// Connections between this module and the parent model.
     /// Each connection maps a submodel input (to) to a submodel output (from).
@@ -30,7 +31,7 @@ pub struct Module {
     pub connections: Vec<ModuleConnection>,
 
     /// Optional documentation for the module.
-    #[serde(rename = "doc")]
+    #[serde(rename = "doc", skip_serializing_if = "Option::is_none")]
     pub documentation: Option<Documentation>,
 }
 
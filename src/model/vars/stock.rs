@@ -1,14 +1,18 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::xml::serialize;
+
 use crate::{
-    Expression, Identifier, Measure, UnitEquation,
+    Expression, Identifier, Measure, NumericConstant, UnitEquation,
+    equation::{expression::function::FunctionTarget, identifier::IdentifierOptions},
     model::{
         events::EventPoster,
         object::{DeviceRange, DeviceScale, Document, Documentation, FormatOptions, Object},
-        vars::{AccessType, NonNegativeContent},
+        vars::{AccessType, NonNegativeContent, Variable},
     },
     types::{Validate, ValidationResult},
+    xml::schema::Model,
 };
 
 #[cfg(feature = "arrays")]
@@ -17,6 +21,7 @@ use crate::model::vars::array::{ArrayElement, VariableDimensions};
 use super::Var;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum StockConversionError {
     #[error("Missing conveyor length in stock definition")]
     MissingConveyorLength,
@@ -50,10 +55,10 @@ struct RawStock {
     #[serde(rename = "@name")]
     name: Identifier,
 
-    #[serde(rename = "@access")]
+    #[serde(rename = "@access", skip_serializing_if = "Option::is_none")]
     access: Option<AccessType>,
 
-    #[serde(rename = "@autoexport")]
+    #[serde(rename = "@autoexport", skip_serializing_if = "Option::is_none")]
     autoexport: Option<bool>,
 
     #[serde(rename = "inflow")]
@@ -67,62 +72,71 @@ struct RawStock {
     initial_equation: Expression,
 
     #[cfg(feature = "mathml")]
-    #[serde(rename = "mathml")]
+    #[serde(rename = "mathml", skip_serializing_if = "Option::is_none")]
     mathml_equation: Option<String>,
 
-    #[serde(rename = "non_negative")]
+    #[serde(rename = "non_negative", skip_serializing_if = "Option::is_none")]
     non_negative: Option<NonNegativeContent>,
 
-    #[serde(rename = "conveyor")]
+    #[serde(rename = "conveyor", skip_serializing_if = "Option::is_none")]
     conveyor: Option<RawConveyor>,
-    #[serde(rename = "queue")]
+    #[serde(rename = "queue", skip_serializing_if = "Option::is_none")]
     queue: Option<RawQueue>,
 
-    #[serde(rename = "units")]
+    #[serde(rename = "units", skip_serializing_if = "Option::is_none")]
     units: Option<UnitEquation>,
 
-    #[serde(rename = "doc")]
+    #[serde(rename = "doc", skip_serializing_if = "Option::is_none")]
     documentation: Option<Documentation>,
 
-    #[serde(rename = "range")]
+    #[serde(rename = "range", skip_serializing_if = "Option::is_none")]
     range: Option<DeviceRange>,
-    #[serde(rename = "scale")]
+    #[serde(rename = "scale", skip_serializing_if = "Option::is_none")]
     scale: Option<DeviceScale>,
-    #[serde(rename = "format")]
+    #[serde(rename = "format", skip_serializing_if = "Option::is_none")]
     format: Option<FormatOptions>,
 
     #[cfg(feature = "arrays")]
-    #[serde(rename = "dimensions")]
+    #[serde(rename = "dimensions", skip_serializing_if = "Option::is_none")]
     dimensions: Option<VariableDimensions>,
 
     #[cfg(feature = "arrays")]
     #[serde(rename = "element", default)]
     elements: Vec<ArrayElement>,
 
-    #[serde(rename = "event_poster")]
+    #[serde(rename = "event_poster", skip_serializing_if = "Option::is_none")]
     event_poster: Option<EventPoster>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct RawConveyor {
     // Conveyor-specific fields
-    #[serde(rename = "len")]
+    #[serde(rename = "len", skip_serializing_if = "Option::is_none")]
     length: Option<Expression>, // required for conveyor stocks
-    #[serde(rename = "capacity")]
+    #[serde(rename = "capacity", skip_serializing_if = "Option::is_none")]
     capacity: Option<Expression>,
-    #[serde(rename = "in_limit")]
+    #[serde(rename = "in_limit", skip_serializing_if = "Option::is_none")]
     inflow_limit: Option<Expression>,
-    #[serde(rename = "sample")]
+    #[serde(rename = "sample", skip_serializing_if = "Option::is_none")]
     sample: Option<Expression>,
-    #[serde(rename = "arrest")]
+    #[serde(rename = "arrest", skip_serializing_if = "Option::is_none")]
     arrest_value: Option<Expression>,
-    #[serde(rename = "@discrete")]
+    #[serde(rename = "@discrete", skip_serializing_if = "serialize::skip_false")]
     discrete: Option<bool>,
-    #[serde(rename = "@batch_integrity")]
+    #[serde(
+        rename = "@batch_integrity",
+        skip_serializing_if = "serialize::skip_false"
+    )]
     batch_integrity: Option<bool>,
-    #[serde(rename = "@one_at_a_time")]
+    #[serde(
+        rename = "@one_at_a_time",
+        skip_serializing_if = "serialize::skip_false"
+    )]
     one_at_a_time: Option<bool>,
-    #[serde(rename = "@exponential_leak")]
+    #[serde(
+        rename = "@exponential_leak",
+        skip_serializing_if = "serialize::skip_false"
+    )]
     exponential_leakage: Option<bool>,
 }
 
@@ -358,6 +372,114 @@ impl Serialize for Stock {
     }
 }
 
+impl Stock {
+    /// Returns the name of the stock.
+    pub fn name(&self) -> &Identifier {
+        match self {
+            Stock::Basic(stock) => &stock.name,
+            Stock::Conveyor(stock) => &stock.name,
+            Stock::Queue(stock) => &stock.name,
+        }
+    }
+
+    /// Returns the inflows to the stock.
+    pub fn inflows(&self) -> &[Identifier] {
+        match self {
+            Stock::Basic(stock) => &stock.inflows,
+            Stock::Conveyor(stock) => &stock.inflows,
+            Stock::Queue(stock) => &stock.inflows,
+        }
+    }
+
+    /// Returns the outflows from the stock.
+    pub fn outflows(&self) -> &[Identifier] {
+        match self {
+            Stock::Basic(stock) => &stock.outflows,
+            Stock::Conveyor(stock) => &stock.outflows,
+            Stock::Queue(stock) => &stock.outflows,
+        }
+    }
+
+    /// Builds the expression for the stock's rate of change, i.e. the sum of
+    /// its inflows less the sum of its outflows.
+    ///
+    /// Flows in `model` marked non-negative (uniflows) contribute
+    /// `MAX(0, flow)`. For a non-negative stock the net flow is wrapped as
+    /// `MAX(net, -stock / DT)`, so that a step never takes the stock below
+    /// zero. A stock with no flows has a net flow of `0`.
+    pub fn net_flow_expression(&self, model: &Model) -> Expression {
+        let flow = |name: &Identifier| {
+            let reference = Expression::Subscript(name.clone(), vec![]);
+            if is_uniflow(model, name) {
+                builtin_call(
+                    "MAX",
+                    vec![Expression::Constant(NumericConstant(0.0)), reference],
+                )
+            } else {
+                reference
+            }
+        };
+
+        let mut net = self
+            .inflows()
+            .iter()
+            .map(flow)
+            .reduce(|sum, inflow| Expression::Add(Box::new(sum), Box::new(inflow)));
+        for outflow in self.outflows() {
+            net = Some(match net {
+                Some(sum) => Expression::Subtract(Box::new(sum), Box::new(flow(outflow))),
+                None => Expression::UnaryMinus(Box::new(flow(outflow))),
+            });
+        }
+        let net = net.unwrap_or(Expression::Constant(NumericConstant(0.0)));
+
+        match self {
+            Stock::Basic(stock) if matches!(stock.non_negative, Some(None | Some(true))) => {
+                let floor = Expression::Divide(
+                    Box::new(Expression::UnaryMinus(Box::new(Expression::Subscript(
+                        stock.name.clone(),
+                        vec![],
+                    )))),
+                    Box::new(Expression::Subscript(builtin("DT"), vec![])),
+                );
+                builtin_call("MAX", vec![net, floor])
+            }
+            _ => net,
+        }
+    }
+}
+
+/// Returns whether the named flow in `model` is non-negative.
+fn is_uniflow(model: &Model, name: &Identifier) -> bool {
+    model
+        .variables
+        .variables
+        .iter()
+        .any(|variable| match variable {
+            Variable::Flow(flow) => {
+                flow.name == *name && matches!(flow.non_negative, Some(None | Some(true)))
+            }
+            _ => false,
+        })
+}
+
+/// Returns the identifier of a builtin, which are otherwise reserved.
+fn builtin(name: &str) -> Identifier {
+    let options = IdentifierOptions {
+        allow_dollar: false,
+        allow_digit: false,
+        allow_reserved: true,
+    };
+    Identifier::parse(name, options).expect("builtin names are valid identifiers")
+}
+
+fn builtin_call(name: &str, parameters: Vec<Expression>) -> Expression {
+    Expression::FunctionCall {
+        target: FunctionTarget::Function(builtin(name)),
+        parameters,
+    }
+}
+
 /// A basic stock variable with inflows, outflows, and an initial value equation.
 #[derive(Debug, Clone, PartialEq)]
 pub struct BasicStock {
@@ -815,7 +937,74 @@ impl QueueStock {}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_xml_rs::from_str;
+    use quick_xml::de::from_str;
+
+    fn model() -> Model {
+        from_str(
+            r#"
+            <model>
+                <variables>
+                    <stock name="Population">
+                        <eqn>100</eqn>
+                        <inflow>births</inflow>
+                        <inflow>immigration</inflow>
+                        <outflow>deaths</outflow>
+                        <non_negative/>
+                    </stock>
+                    <stock name="Debt">
+                        <eqn>0</eqn>
+                        <outflow>repayment</outflow>
+                    </stock>
+                    <stock name="Constant">
+                        <eqn>1</eqn>
+                    </stock>
+                    <flow name="births">
+                        <eqn>Population * 0.1</eqn>
+                    </flow>
+                    <flow name="immigration">
+                        <eqn>5</eqn>
+                        <non_negative/>
+                    </flow>
+                    <flow name="deaths">
+                        <eqn>Population * 0.05</eqn>
+                        <non_negative>false</non_negative>
+                    </flow>
+                    <flow name="repayment">
+                        <eqn>10</eqn>
+                    </flow>
+                </variables>
+            </model>
+            "#,
+        )
+        .expect("Failed to parse model")
+    }
+
+    #[test]
+    fn test_net_flow_expression() {
+        let model = model();
+        let stocks: Vec<&Stock> = model
+            .variables
+            .variables
+            .iter()
+            .filter_map(|variable| match variable {
+                Variable::Stock(stock) => Some(stock.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            stocks[0].net_flow_expression(&model).to_string(),
+            r#"MAX("births" + MAX(0, "immigration") - "deaths", -"Population" / DT)"#
+        );
+        assert_eq!(
+            stocks[1].net_flow_expression(&model).to_string(),
+            r#"-"repayment""#
+        );
+        assert_eq!(
+            stocks[2].net_flow_expression(&model),
+            Expression::Constant(NumericConstant(0.0))
+        );
+    }
 
     #[test]
     fn test_basic_stock() {
@@ -0,0 +1,561 @@
+//! Export of stock and flow models to Modelica.
+//!
+//! [`export`] writes the root model of a file as a Modelica `model` class,
+//! so it can be simulated, linearized or verified with Modelica tools:
+//!
+//! - each stock becomes a state, with `der(stock)` equal to its net flow
+//!   and its initial value as its start value or an initial equation;
+//! - each auxiliary or flow becomes a variable with its equation, or a
+//!   `parameter` if its equation is a constant;
+//! - the run length and DT become the `experiment` annotation.
+//!
+//! Names are kept where they are valid Modelica identifiers once spaces
+//! are written as underscores, and quoted (`'Birth Rate%'`) otherwise.
+//!
+//! Constructs without a continuous counterpart cannot be translated:
+//! conveyors and queues, arrays, graphical functions, submodels, and
+//! builtins that keep state or act at discrete times, such as `DELAY1`,
+//! `SMTH1` or `PULSE`. Variables using them are left out of the class and
+//! listed in [`ModelicaExport::untranslated`], which should be checked
+//! before the output is used.
+//!
+//! ```rust
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><name>Growth</name><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <sim_specs><start>0</start><stop>10</stop><dt>0.5</dt></sim_specs>
+//!     <model><variables>
+//!         <stock name="Population"><eqn>100</eqn><inflow>Births</inflow></stock>
+//!         <flow name="Births"><eqn>Population * Birth_Rate</eqn></flow>
+//!         <aux name="Birth_Rate"><eqn>0.1</eqn></aux>
+//!     </variables></model>
+//! </xmile>"#).unwrap();
+//! let export = xmile::modelica::export(&file).unwrap();
+//! assert!(export.source.contains("der(Population) = Births;"));
+//! assert!(export.untranslated.is_empty());
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::{self, Write as _};
+
+use thiserror::Error;
+
+use crate::equation::Expression;
+use crate::equation::expression::function::FunctionTarget;
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+use crate::xml::{Model, XmileFile};
+use crate::{Identifier, NumericConstant};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ModelicaError {
+    #[error("The file has no model to export")]
+    NoModel,
+}
+
+/// A model written as Modelica source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelicaExport {
+    /// The Modelica `model` class.
+    pub source: String,
+    /// The variables left out of [`source`](Self::source), in model order.
+    pub untranslated: Vec<Untranslated>,
+}
+
+/// A variable that could not be written as Modelica.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untranslated {
+    pub variable: String,
+    /// The construct that could not be translated.
+    pub reason: String,
+}
+
+impl fmt::Display for Untranslated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.variable, self.reason)
+    }
+}
+
+/// Builtins with a Modelica function of the same meaning.
+const FUNCTIONS: &[(&str, &str)] = &[
+    ("ABS", "abs"),
+    ("SQRT", "sqrt"),
+    ("EXP", "exp"),
+    ("LN", "log"),
+    ("LOG10", "log10"),
+    ("SIN", "sin"),
+    ("COS", "cos"),
+    ("TAN", "tan"),
+    ("ARCSIN", "asin"),
+    ("ARCCOS", "acos"),
+    ("ARCTAN", "atan"),
+    ("INT", "floor"),
+    ("MIN", "min"),
+    ("MAX", "max"),
+];
+
+/// Reserved words of Modelica, which must be quoted as names.
+const KEYWORDS: &[&str] = &[
+    "algorithm",
+    "and",
+    "annotation",
+    "block",
+    "break",
+    "class",
+    "connect",
+    "connector",
+    "constant",
+    "constrainedby",
+    "der",
+    "discrete",
+    "each",
+    "else",
+    "elseif",
+    "elsewhen",
+    "encapsulated",
+    "end",
+    "enumeration",
+    "equation",
+    "expandable",
+    "extends",
+    "external",
+    "false",
+    "final",
+    "flow",
+    "for",
+    "function",
+    "if",
+    "import",
+    "impure",
+    "in",
+    "initial",
+    "inner",
+    "input",
+    "loop",
+    "model",
+    "not",
+    "operator",
+    "or",
+    "outer",
+    "output",
+    "package",
+    "parameter",
+    "partial",
+    "protected",
+    "public",
+    "pure",
+    "record",
+    "redeclare",
+    "replaceable",
+    "return",
+    "stream",
+    "then",
+    "time",
+    "true",
+    "type",
+    "when",
+    "while",
+    "within",
+];
+
+/// Writes the root model of `file` as a Modelica class named after the
+/// file's header, or `Model` if it has no name.
+///
+/// The root model is the first model without a name, or the first model
+/// if every model is named.
+pub fn export(file: &XmileFile) -> Result<ModelicaExport, ModelicaError> {
+    let model = file
+        .models
+        .iter()
+        .find(|model| model.name.is_none())
+        .or_else(|| file.models.first())
+        .ok_or(ModelicaError::NoModel)?;
+    let name = file.header.name.as_deref().unwrap_or("Model");
+    let specs = model.sim_specs.as_ref().or(file.sim_specs.as_ref());
+    let (start, stop, dt) = specs.map_or((0.0, 10.0, 1.0), |specs| {
+        (specs.start, specs.stop, specs.dt.unwrap_or(1.0))
+    });
+
+    let mut translator = Translator {
+        variables: model
+            .variables
+            .variables
+            .iter()
+            .filter_map(variable_name)
+            .map(|name| name.normalized().to_lowercase())
+            .collect(),
+        start,
+        stop,
+        uses_dt: false,
+    };
+    let mut declarations = Vec::new();
+    let mut initial = Vec::new();
+    let mut equations = Vec::new();
+    let mut untranslated = Vec::new();
+    for variable in &model.variables.variables {
+        if let Err(reason) = translator.variable(
+            model,
+            variable,
+            &mut declarations,
+            &mut initial,
+            &mut equations,
+        ) && let Some(variable) = variable_name(variable)
+        {
+            declarations.push(format!("  // Not translated: {variable} ({reason})"));
+            untranslated.push(Untranslated {
+                variable: variable.to_string(),
+                reason,
+            });
+        }
+    }
+    if translator.uses_dt {
+        declarations.insert(0, format!("  parameter Real DT = {};", number(dt)));
+    }
+
+    let class = identifier(name);
+    let mut source = format!("model {class}\n");
+    for line in &declarations {
+        let _ = writeln!(source, "{line}");
+    }
+    if !initial.is_empty() {
+        source.push_str("initial equation\n");
+        for line in &initial {
+            let _ = writeln!(source, "{line}");
+        }
+    }
+    source.push_str("equation\n");
+    for line in &equations {
+        let _ = writeln!(source, "{line}");
+    }
+    let _ = writeln!(
+        source,
+        "  annotation(experiment(StartTime = {}, StopTime = {}, Interval = {}));",
+        number(start),
+        number(stop),
+        number(dt)
+    );
+    let _ = writeln!(source, "end {class};");
+    Ok(ModelicaExport {
+        source,
+        untranslated,
+    })
+}
+
+/// Translates the variables and equations of a model.
+struct Translator {
+    /// The names of the model's variables, lowercased, which take
+    /// precedence over builtins of the same name.
+    variables: HashSet<String>,
+    start: f64,
+    stop: f64,
+    uses_dt: bool,
+}
+
+impl Translator {
+    /// Adds the declarations and equations of `variable`, or returns why it
+    /// cannot be translated.
+    fn variable(
+        &mut self,
+        model: &Model,
+        variable: &Variable,
+        declarations: &mut Vec<String>,
+        initial: &mut Vec<String>,
+        equations: &mut Vec<String>,
+    ) -> Result<(), String> {
+        let (name, equation) = match variable {
+            Variable::Stock(stock) => {
+                let Stock::Basic(basic) = stock.as_ref() else {
+                    return Err(match stock.as_ref() {
+                        Stock::Conveyor(_) => "conveyor",
+                        _ => "queue",
+                    }
+                    .to_string());
+                };
+                if is_arrayed(variable) {
+                    return Err("arrays".to_string());
+                }
+                let name = identifier(basic.name.normalized());
+                let rate = self.expression(&stock.net_flow_expression(model))?;
+                let declaration = match &basic.initial_equation {
+                    Expression::Constant(NumericConstant(value)) => {
+                        format!("  Real {name}(start = {}, fixed = true);", number(*value))
+                    }
+                    expression => {
+                        let value = self.expression(expression)?;
+                        initial.push(format!("  {name} = {value};"));
+                        format!("  Real {name};")
+                    }
+                };
+                declarations.push(declaration);
+                equations.push(format!("  der({name}) = {rate};"));
+                return Ok(());
+            }
+            Variable::Auxiliary(aux) => (&aux.name, Some(&aux.equation)),
+            Variable::Flow(flow) => (&flow.name, flow.equation.as_ref()),
+            Variable::GraphicalFunction(_) => return Err("graphical function".to_string()),
+            #[cfg(feature = "submodels")]
+            Variable::Module(_) => return Err("submodel".to_string()),
+            Variable::Group(_) => return Ok(()),
+        };
+        if is_arrayed(variable) {
+            return Err("arrays".to_string());
+        }
+        let equation = equation.ok_or_else(|| "no equation".to_string())?;
+        let name = identifier(name.normalized());
+        match equation {
+            Expression::Constant(NumericConstant(value)) => {
+                declarations.push(format!("  parameter Real {name} = {};", number(*value)));
+            }
+            expression => {
+                let value = self.expression(expression)?;
+                declarations.push(format!("  Real {name};"));
+                equations.push(format!("  {name} = {value};"));
+            }
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Result<String, String> {
+        use Expression as E;
+
+        let mut binary = |lhs: &Expression, op: &str, rhs: &Expression| {
+            Ok(format!(
+                "{} {op} {}",
+                self.expression(lhs)?,
+                self.expression(rhs)?
+            ))
+        };
+        match expression {
+            E::Constant(NumericConstant(value)) => Ok(number(*value)),
+            E::Subscript(name, indices) if indices.is_empty() => self.name(name),
+            E::Subscript(..) | E::Wildcard | E::Range(..) | E::Transpose(_) => {
+                Err("arrays".to_string())
+            }
+            E::Parentheses(inner) => Ok(format!("({})", self.expression(inner)?)),
+            E::UnaryPlus(inner) => self.expression(inner),
+            E::UnaryMinus(inner) => Ok(format!("-{}", self.expression(inner)?)),
+            // Modelica's `not` binds more loosely than comparisons
+            E::Not(inner) => Ok(format!("not ({})", self.expression(inner)?)),
+            E::Exponentiation(lhs, rhs) => binary(lhs, "^", rhs),
+            E::Multiply(lhs, rhs) => binary(lhs, "*", rhs),
+            E::Divide(lhs, rhs) => binary(lhs, "/", rhs),
+            E::Modulo(lhs, rhs) => Ok(format!(
+                "mod({}, {})",
+                self.expression(lhs)?,
+                self.expression(rhs)?
+            )),
+            E::Add(lhs, rhs) => binary(lhs, "+", rhs),
+            E::Subtract(lhs, rhs) => binary(lhs, "-", rhs),
+            E::LessThan(lhs, rhs) => binary(lhs, "<", rhs),
+            E::LessThanOrEq(lhs, rhs) => binary(lhs, "<=", rhs),
+            E::GreaterThan(lhs, rhs) => binary(lhs, ">", rhs),
+            E::GreaterThanOrEq(lhs, rhs) => binary(lhs, ">=", rhs),
+            E::Equal(lhs, rhs) => binary(lhs, "==", rhs),
+            E::NotEqual(lhs, rhs) => binary(lhs, "<>", rhs),
+            E::And(lhs, rhs) => binary(lhs, "and", rhs),
+            E::Or(lhs, rhs) => binary(lhs, "or", rhs),
+            E::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => Ok(format!(
+                "(if {} then {} else {})",
+                self.expression(condition)?,
+                self.expression(then_branch)?,
+                self.expression(else_branch)?
+            )),
+            E::FunctionCall {
+                target: FunctionTarget::Function(name),
+                parameters,
+            } => self.call(name, parameters),
+            E::FunctionCall {
+                target: FunctionTarget::GraphicalFunction(_),
+                ..
+            } => Err("graphical function".to_string()),
+            E::FunctionCall {
+                target: FunctionTarget::Model(_),
+                ..
+            } => Err("submodel".to_string()),
+            E::FunctionCall {
+                target: FunctionTarget::Array(_),
+                ..
+            } => Err("arrays".to_string()),
+            E::InlineComment(_) => Err("comment in place of an equation".to_string()),
+        }
+    }
+
+    /// Translates a reference to a variable or a builtin constant.
+    fn name(&mut self, name: &Identifier) -> Result<String, String> {
+        if self.variables.contains(&name.normalized().to_lowercase()) {
+            return Ok(identifier(name.normalized()));
+        }
+        match name.normalized().to_ascii_uppercase().as_str() {
+            "TIME" => Ok("time".to_string()),
+            "DT" => {
+                self.uses_dt = true;
+                Ok("DT".to_string())
+            }
+            "STARTTIME" => Ok(number(self.start)),
+            "STOPTIME" => Ok(number(self.stop)),
+            "PI" => Ok("Modelica.Constants.pi".to_string()),
+            "INF" => Ok("Modelica.Constants.inf".to_string()),
+            _ => Ok(identifier(name.normalized())),
+        }
+    }
+
+    fn call(&mut self, name: &Identifier, parameters: &[Expression]) -> Result<String, String> {
+        let builtin = name.normalized().to_ascii_uppercase();
+        let arguments = parameters
+            .iter()
+            .map(|parameter| self.expression(parameter))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some((_, function)) = FUNCTIONS.iter().find(|(xmile, _)| *xmile == builtin) {
+            return Ok(format!("{function}({})", arguments.join(", ")));
+        }
+        match (builtin.as_str(), arguments.as_slice()) {
+            ("STEP", [height, start]) => Ok(format!("(if time >= {start} then {height} else 0.0)")),
+            _ => Err(builtin),
+        }
+    }
+}
+
+fn variable_name(variable: &Variable) -> Option<&Identifier> {
+    match variable {
+        Variable::Auxiliary(aux) => Some(&aux.name),
+        Variable::Stock(stock) => Some(match stock.as_ref() {
+            Stock::Basic(stock) => &stock.name,
+            Stock::Conveyor(stock) => &stock.name,
+            Stock::Queue(stock) => &stock.name,
+        }),
+        Variable::Flow(flow) => Some(&flow.name),
+        Variable::GraphicalFunction(gf) => gf.name.as_ref(),
+        #[cfg(feature = "submodels")]
+        Variable::Module(module) => Some(&module.name),
+        Variable::Group(_) => None,
+    }
+}
+
+#[cfg(feature = "arrays")]
+fn is_arrayed(variable: &Variable) -> bool {
+    match variable {
+        Variable::Auxiliary(aux) => aux.dimensions.is_some() || !aux.elements.is_empty(),
+        Variable::Flow(flow) => flow.dimensions.is_some() || !flow.elements.is_empty(),
+        Variable::Stock(stock) => match stock.as_ref() {
+            Stock::Basic(stock) => stock.dimensions.is_some() || !stock.elements.is_empty(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(not(feature = "arrays"))]
+fn is_arrayed(_variable: &Variable) -> bool {
+    false
+}
+
+/// Writes `name` as a Modelica identifier, quoting it if needed.
+fn identifier(name: &str) -> String {
+    let plain = name.replace(' ', "_");
+    let valid = plain
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && plain.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid && !KEYWORDS.contains(&plain.as_str()) {
+        plain
+    } else {
+        format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+/// Writes `value` as a Modelica literal.
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{value:?}")
+    } else if value.is_nan() {
+        "0.0 / 0.0".to_string()
+    } else if value > 0.0 {
+        "Modelica.Constants.inf".to_string()
+    } else {
+        "-Modelica.Constants.inf".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(variables: &str) -> XmileFile {
+        XmileFile::from_str(&format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+                <sim_specs><start>1</start><stop>21</stop><dt>0.25</dt></sim_specs>
+                <model><variables>{variables}</variables></model>
+            </xmile>"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_export_state_space() {
+        let file = file(
+            r#"<stock name="Tank"><eqn>Capacity / 2</eqn><inflow>Fill</inflow><outflow>Drain</outflow><non_negative/></stock>
+               <flow name="Fill"><eqn>IF TIME &lt; 5 THEN 2 ELSE 0</eqn></flow>
+               <flow name="Drain"><eqn>Tank * MAX(0.1, Rate MOD 1)</eqn></flow>
+               <aux name="Rate"><eqn>STEP(0.5, 10) + ABS(-0.2)</eqn></aux>
+               <aux name="Capacity"><eqn>100</eqn></aux>
+               <aux name="end"><eqn>Capacity</eqn></aux>"#,
+        );
+        let export = export(&file).unwrap();
+        assert!(export.untranslated.is_empty(), "{:?}", export.untranslated);
+        let source = &export.source;
+        assert!(source.starts_with("model Model\n  parameter Real DT = 0.25;\n"));
+        assert!(source.contains("  Real Tank;\n"));
+        assert!(source.contains("initial equation\n  Tank = Capacity / 2.0;\n"));
+        assert!(source.contains("  der(Tank) = max(Fill - Drain, -Tank / DT);\n"));
+        assert!(source.contains("  Fill = (if time < 5.0 then 2.0 else 0.0);\n"));
+        assert!(source.contains("  Drain = Tank * max(0.1, mod(Rate, 1.0));\n"));
+        assert!(source.contains("  Rate = (if time >= 10.0 then 0.5 else 0.0) + abs(-0.2);\n"));
+        assert!(source.contains("  parameter Real Capacity = 100.0;\n"));
+        assert!(source.contains("  'end' = Capacity;\n"));
+        assert!(source.contains(
+            "annotation(experiment(StartTime = 1.0, StopTime = 21.0, Interval = 0.25));\nend Model;\n"
+        ));
+    }
+
+    #[test]
+    fn test_report_untranslatable() {
+        let file = file(
+            r#"<stock name="Stock"><eqn>0</eqn><inflow>Smoothed</inflow></stock>
+               <flow name="Smoothed"><eqn>SMTH1(Input, 3)</eqn></flow>
+               <aux name="Input"><eqn>Table(TIME)</eqn></aux>
+               <gf name="Table"><xscale min="0" max="1"/><ypts>0,1</ypts></gf>"#,
+        );
+        let export = export(&file).unwrap();
+        let reasons: Vec<String> = export.untranslated.iter().map(|u| u.to_string()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "Smoothed: SMTH1",
+                "Input: graphical function",
+                "Table: graphical function"
+            ]
+        );
+        assert!(
+            export
+                .source
+                .contains("  // Not translated: Smoothed (SMTH1)\n")
+        );
+        assert!(
+            export
+                .source
+                .contains("  Real Stock(start = 0.0, fixed = true);\n")
+        );
+        assert!(matches!(
+            super::export(&XmileFile::from_str(r#"<xmile version="1.0"><header><vendor>T</vendor><product version="1">T</product></header></xmile>"#).unwrap()),
+            Err(ModelicaError::NoModel)
+        ));
+    }
+}
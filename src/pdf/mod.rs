@@ -0,0 +1,645 @@
+//! Printable PDF reports of XMILE models.
+//!
+//! A report holds every view of every model, split into pages, followed by
+//! a listing of each variable's equation, units and documentation. Views are
+//! split using their own `page_width`, `page_height` and `page_sequence`
+//! (Section 6.1), so a report prints the pages a modeller laid out.
+//!
+//! The writer is self-contained: it produces PDF 1.4 using the standard
+//! Helvetica fonts, which every reader provides, so no fonts are embedded.
+//!
+//! ```rust
+//! use xmile::pdf::{PdfOptions, render};
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Example</vendor><product version="1.0">Example</product></header>
+//!     <model><variables><aux name="rate"><eqn>0.1</eqn></aux></variables></model>
+//! </xmile>"#).unwrap();
+//! let pdf = render(&file, &PdfOptions::default());
+//! assert!(pdf.starts_with(b"%PDF-1.4"));
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::model::object::Documentation;
+use crate::model::vars::stock::Stock;
+use crate::model::vars::{Var, Variable};
+use crate::view::geometry::Outline;
+use crate::view::{PageSequence, Point, Pointer, View, ViewEntity};
+use crate::xml::schema::{Model, XmileFile};
+use crate::{Expression, Identifier, UnitEquation};
+
+/// Average width of a Helvetica character, as a fraction of the font size,
+/// used to wrap text without font metrics.
+const AVERAGE_CHAR_WIDTH: f64 = 0.5;
+
+/// Radius of the circle drawn for a cloud, in view coordinates.
+const CLOUD_RADIUS: f64 = 6.0;
+
+/// Layout of a PDF report. Lengths are in points (1/72 inch).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfOptions {
+    pub page_width: f64,
+    pub page_height: f64,
+    pub margin: f64,
+    pub font_size: f64,
+    /// Whether to include each model's views.
+    pub views: bool,
+    /// Whether to include the equation and documentation listing.
+    pub listing: bool,
+}
+
+impl Default for PdfOptions {
+    /// A4 portrait with half-inch margins.
+    fn default() -> Self {
+        PdfOptions {
+            page_width: 595.0,
+            page_height: 842.0,
+            margin: 36.0,
+            font_size: 10.0,
+            views: true,
+            listing: true,
+        }
+    }
+}
+
+/// Renders a report of `file` as PDF bytes.
+pub fn render(file: &XmileFile, options: &PdfOptions) -> Vec<u8> {
+    let mut pages = Vec::new();
+    let title = file
+        .header
+        .name
+        .clone()
+        .unwrap_or_else(|| "Untitled model".to_string());
+    for (index, model) in file.models.iter().enumerate() {
+        let model_title = match &model.name {
+            Some(name) => format!("{title} / {name}"),
+            None if index > 0 => format!("{title} / model {}", index + 1),
+            None => title.clone(),
+        };
+        if options.views
+            && let Some(views) = &model.views
+        {
+            for view in &views.views {
+                pages.extend(view_pages(view, &model_title, options));
+            }
+        }
+        if options.listing {
+            pages.extend(listing_pages(model, &model_title, options));
+        }
+    }
+    if pages.is_empty() {
+        pages.push(Canvas::new(options));
+    }
+    write_document(&pages, options)
+}
+
+/// Writes a report of `file` as PDF to `writer`.
+pub fn write_pdf<W: Write>(
+    file: &XmileFile,
+    options: &PdfOptions,
+    mut writer: W,
+) -> io::Result<()> {
+    writer.write_all(&render(file, options))
+}
+
+/// The content stream of one page being drawn.
+struct Canvas {
+    content: String,
+    page_height: f64,
+}
+
+impl Canvas {
+    fn new(options: &PdfOptions) -> Self {
+        Canvas {
+            content: String::new(),
+            page_height: options.page_height,
+        }
+    }
+
+    /// Converts a distance from the top of the page to a PDF y coordinate.
+    fn flip(&self, y: f64) -> f64 {
+        self.page_height - y
+    }
+
+    fn op(&mut self, op: &str) {
+        self.content.push_str(op);
+        self.content.push('\n');
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64) {
+        let Some((first, rest)) = points.split_first() else {
+            return;
+        };
+        let _ = write!(
+            self.content,
+            "{} w {} {} m",
+            number(width),
+            number(first.0),
+            number(self.flip(first.1))
+        );
+        for (x, y) in rest {
+            let _ = write!(self.content, " {} {} l", number(*x), number(self.flip(*y)));
+        }
+        self.op(" S");
+    }
+
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let _ = writeln!(
+            self.content,
+            "1 w {} {} {} {} re S",
+            number(x),
+            number(self.flip(y + height)),
+            number(width),
+            number(height)
+        );
+    }
+
+    fn circle(&mut self, x: f64, y: f64, radius: f64) {
+        // Four Bezier quarter circles
+        let k = 0.552_284_75 * radius;
+        let y = self.flip(y);
+        let _ = writeln!(
+            self.content,
+            "1 w {} {} m {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c S",
+            number(x + radius),
+            number(y),
+            number(x + radius),
+            number(y + k),
+            number(x + k),
+            number(y + radius),
+            number(x),
+            number(y + radius),
+            number(x - k),
+            number(y + radius),
+            number(x - radius),
+            number(y + k),
+            number(x - radius),
+            number(y),
+            number(x - radius),
+            number(y - k),
+            number(x - k),
+            number(y - radius),
+            number(x),
+            number(y - radius),
+            number(x + k),
+            number(y - radius),
+            number(x + radius),
+            number(y - k),
+            number(x + radius),
+            number(y),
+        );
+    }
+
+    /// Draws `text` with its baseline at `y`, starting at `x`.
+    fn text(&mut self, x: f64, y: f64, text: &str, size: f64, bold: bool) {
+        let font = if bold { "F2" } else { "F1" };
+        let _ = writeln!(
+            self.content,
+            "BT /{font} {} Tf {} {} Td ({}) Tj ET",
+            number(size),
+            number(x),
+            number(self.flip(y)),
+            escape(text)
+        );
+    }
+
+    /// Draws `text` centred horizontally on `x`.
+    fn centered_text(&mut self, x: f64, y: f64, text: &str, size: f64) {
+        let width = text_width(text, size);
+        self.text(x - width / 2.0, y, text, size, false);
+    }
+}
+
+/// Splits `view` into pages and draws each one.
+fn view_pages(view: &View, title: &str, options: &PdfOptions) -> Vec<Canvas> {
+    let (tile_width, tile_height) = view.page_size();
+    if tile_width <= 0.0 || tile_height <= 0.0 {
+        return Vec::new();
+    }
+    let (columns, rows) = view.page_grid();
+    let tiles: Vec<(usize, usize)> = match view.page_sequence {
+        PageSequence::Row => (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .collect(),
+        PageSequence::Column => (0..columns)
+            .flat_map(|column| (0..rows).map(move |row| (row, column)))
+            .collect(),
+    };
+
+    // Fit each tile in the area below the page heading
+    let header = options.font_size * 2.0;
+    let area_width = options.page_width - 2.0 * options.margin;
+    let area_height = options.page_height - 2.0 * options.margin - header;
+    let scale = (area_width / tile_width).min(area_height / tile_height);
+    let top = options.margin + header;
+
+    let count = tiles.len();
+    tiles
+        .into_iter()
+        .enumerate()
+        .map(|(index, (row, column))| {
+            let mut canvas = Canvas::new(options);
+            canvas.text(
+                options.margin,
+                options.margin + options.font_size,
+                &format!(
+                    "{title}: view {} (page {} of {count})",
+                    view.uid.value,
+                    index + 1
+                ),
+                options.font_size,
+                true,
+            );
+            let origin = (column as f64 * tile_width, row as f64 * tile_height);
+            let place = |point: &Point| {
+                (
+                    options.margin + (point.x - origin.0) * scale,
+                    top + (point.y - origin.1) * scale,
+                )
+            };
+            // Clip drawing to the tile
+            canvas.op("q");
+            let _ = writeln!(
+                canvas.content,
+                "{} {} {} {} re W n",
+                number(options.margin),
+                number(canvas.flip(top + tile_height * scale)),
+                number(tile_width * scale),
+                number(tile_height * scale)
+            );
+            draw_view(&mut canvas, view, &place, scale, options.font_size);
+            canvas.op("Q");
+            canvas
+        })
+        .collect()
+}
+
+/// Draws the stock and flow diagram objects of `view`.
+fn draw_view(
+    canvas: &mut Canvas,
+    view: &View,
+    place: &dyn Fn(&Point) -> (f64, f64),
+    scale: f64,
+    font_size: f64,
+) {
+    let label_size = (font_size * 0.8 * scale).max(4.0);
+
+    for connector in &view.connectors {
+        let points: Vec<_> = view
+            .connector_path(connector)
+            .flatten()
+            .iter()
+            .map(place)
+            .collect();
+        canvas.polyline(&points, 0.5);
+    }
+    for flow in &view.flows {
+        let points: Vec<_> = flow.pts.iter().map(place).collect();
+        canvas.polyline(&points, 2.0);
+    }
+    for cloud in view.clouds() {
+        let (x, y) = place(cloud.position);
+        canvas.circle(x, y, CLOUD_RADIUS * scale);
+    }
+
+    let draw_outline = |canvas: &mut Canvas, outline: &Outline, label: &str| {
+        let (x, y) = place(outline.center());
+        let bottom = match outline {
+            Outline::Rectangle { width, height, .. } => {
+                canvas.rectangle(
+                    x - width * scale / 2.0,
+                    y - height * scale / 2.0,
+                    width * scale,
+                    height * scale,
+                );
+                y + height * scale / 2.0
+            }
+            Outline::Circle { radius, .. } => {
+                canvas.circle(x, y, radius * scale);
+                y + radius * scale
+            }
+        };
+        for (line, text) in label.lines().enumerate() {
+            canvas.centered_text(
+                x,
+                bottom + label_size * (line as f64 + 1.1),
+                text,
+                label_size,
+            );
+        }
+    };
+
+    let entities = view
+        .stocks
+        .iter()
+        .map(|stock| stock as &dyn ViewEntity)
+        .chain(view.flows.iter().map(|flow| flow as &dyn ViewEntity))
+        .chain(view.auxes.iter().map(|aux| aux as &dyn ViewEntity))
+        .chain(view.modules.iter().map(|module| module as &dyn ViewEntity));
+    for entity in entities {
+        if let Some(outline) = entity.outline() {
+            draw_outline(canvas, &outline, &entity.label());
+        }
+    }
+    for alias in &view.aliases {
+        if let Some(outline) = view.outline_of(&Pointer::Alias(alias.uid)) {
+            canvas.op("[2 2] 0 d");
+            draw_outline(canvas, &outline, &view.alias_label(alias));
+            canvas.op("[] 0 d");
+        }
+    }
+}
+
+/// Lays out the equation and documentation listing of `model`.
+fn listing_pages(model: &Model, title: &str, options: &PdfOptions) -> Vec<Canvas> {
+    let size = options.font_size;
+    let line_height = size * 1.3;
+    let max_chars =
+        ((options.page_width - 2.0 * options.margin) / (size * AVERAGE_CHAR_WIDTH)) as usize;
+
+    // Each line is its text, indentation and whether it is bold
+    let mut lines: Vec<(String, f64, bool)> = vec![(format!("{title}: equations"), 0.0, true)];
+    for variable in &model.variables.variables {
+        let Some(entry) = ListingEntry::of(variable) else {
+            continue;
+        };
+        lines.push((String::new(), 0.0, false));
+        lines.push((
+            format!("{} ({})", entry.name.normalized(), entry.kind),
+            0.0,
+            true,
+        ));
+        let indent = size * 2.0;
+        let wrap_at = max_chars.saturating_sub(4).max(20);
+        if let Some(equation) = entry.equation {
+            let prefix = if entry.kind == "stock" {
+                "INIT = "
+            } else {
+                "= "
+            };
+            for line in wrap(&format!("{prefix}{equation}"), wrap_at) {
+                lines.push((line, indent, false));
+            }
+        }
+        if let Some(units) = entry.units {
+            lines.push((format!("Units: {units}"), indent, false));
+        }
+        if let Some(documentation) = entry.documentation {
+            for paragraph in documentation_text(documentation).lines() {
+                for line in wrap(paragraph, wrap_at) {
+                    lines.push((line, indent, false));
+                }
+            }
+        }
+    }
+
+    let mut pages = Vec::new();
+    let mut canvas = Canvas::new(options);
+    let mut y = options.margin;
+    for (text, indent, bold) in lines {
+        if y + line_height > options.page_height - options.margin {
+            pages.push(std::mem::replace(&mut canvas, Canvas::new(options)));
+            y = options.margin;
+        }
+        y += line_height;
+        if !text.is_empty() {
+            canvas.text(options.margin + indent, y, &text, size, bold);
+        }
+    }
+    pages.push(canvas);
+    pages
+}
+
+/// What the listing shows for one variable.
+struct ListingEntry<'a> {
+    name: &'a Identifier,
+    kind: &'static str,
+    equation: Option<&'a Expression>,
+    units: Option<&'a UnitEquation>,
+    documentation: Option<&'a Documentation>,
+}
+
+impl<'a> ListingEntry<'a> {
+    fn of(variable: &'a Variable) -> Option<Self> {
+        fn entry<'a, V: Var<'a>>(var: &'a V, kind: &'static str) -> Option<ListingEntry<'a>> {
+            Some(ListingEntry {
+                name: var.name()?,
+                kind,
+                equation: var.equation(),
+                units: var.units(),
+                documentation: var.documentation(),
+            })
+        }
+        match variable {
+            Variable::Auxiliary(aux) => entry(aux, "aux"),
+            Variable::Stock(stock) => match stock.as_ref() {
+                Stock::Basic(stock) => entry(stock, "stock"),
+                Stock::Conveyor(stock) => entry(stock.as_ref(), "stock"),
+                Stock::Queue(stock) => entry(stock, "stock"),
+            },
+            Variable::Flow(flow) => entry(flow, "flow"),
+            Variable::GraphicalFunction(gf) => entry(gf, "graphical function"),
+            #[cfg(feature = "submodels")]
+            Variable::Module(module) => entry(module, "module"),
+            Variable::Group(_) => None,
+        }
+    }
+}
+
+/// The readable text of documentation, with HTML tags removed and plain text
+/// escapes expanded.
+fn documentation_text(documentation: &Documentation) -> String {
+    match documentation {
+        Documentation::PlainText(text) => text.replace("\\n", "\n").replace("\\t", "    "),
+        Documentation::Html(html) => {
+            let mut text = String::new();
+            let mut in_tag = false;
+            for c in html.chars() {
+                match c {
+                    '<' => in_tag = true,
+                    '>' => in_tag = false,
+                    c if !in_tag => text.push(c),
+                    _ => {}
+                }
+            }
+            text.replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&nbsp;", " ")
+                .replace("&amp;", "&")
+        }
+    }
+}
+
+/// Wraps `text` at spaces into lines of at most `width` characters, breaking
+/// longer words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn text_width(text: &str, size: f64) -> f64 {
+    text.chars().count() as f64 * size * AVERAGE_CHAR_WIDTH
+}
+
+/// Escapes `text` as a PDF string in the fonts' WinAnsi encoding. Characters
+/// outside Latin-1 are replaced by `?`.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{c}"),
+            c if (c as u32) < 0x20 => " ".to_string(),
+            c if (c as u32) < 0x7f => c.to_string(),
+            c if (c as u32) <= 0xff => format!("\\{:03o}", c as u32),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+/// Formats a coordinate with at most two decimals.
+fn number(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    if rounded == 0.0 {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+/// Assembles the pages into a PDF file.
+fn write_document(pages: &[Canvas], options: &PdfOptions) -> Vec<u8> {
+    // Objects 1-4 are the catalog, page tree and fonts; each page then takes
+    // two objects, the page and its content stream
+    let page_ids: Vec<usize> = (0..pages.len()).map(|index| 5 + 2 * index).collect();
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{id} 0 R"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            number(options.page_width),
+            number(options.page_height),
+            id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            page.content.len(),
+            page.content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{object}\nendobj\n", index + 1);
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builders::synthetic_model_xml;
+
+    fn report(options: &PdfOptions) -> String {
+        let file = XmileFile::from_str(&synthetic_model_xml()).unwrap();
+        String::from_utf8(render(&file, options)).unwrap()
+    }
+
+    #[test]
+    fn test_report_structure() {
+        let pdf = report(&PdfOptions::default());
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+
+        // The cross-reference table points at each object
+        let xref: usize = pdf
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|offset| offset.parse().ok())
+            .unwrap();
+        assert!(pdf[xref..].starts_with("xref\n"));
+        for (index, entry) in pdf[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .enumerate()
+        {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", index + 1)));
+        }
+
+        assert!(pdf.contains("(Population) Tj"));
+        assert!(pdf.contains("(Population \\(stock\\)) Tj"));
+        assert!(pdf.contains("(INIT = 100) Tj"));
+    }
+
+    #[test]
+    fn test_views_are_paginated() {
+        let listing_only = report(&PdfOptions {
+            views: false,
+            ..PdfOptions::default()
+        });
+        let full = report(&PdfOptions::default());
+        let pages = |pdf: &str| pdf.matches("/Type /Page ").count();
+        assert_eq!(pages(&listing_only), 1);
+        assert!(pages(&full) > pages(&listing_only));
+        assert!(full.contains("page 1 of "));
+    }
+
+    #[test]
+    fn test_wrap_and_escape() {
+        assert_eq!(wrap("a bb ccc dddd", 6), vec!["a bb", "ccc", "dddd"]);
+        assert_eq!(wrap("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(escape("f(x) \\ é ∑"), "f\\(x\\) \\\\ \\351 ?");
+    }
+}
@@ -0,0 +1,356 @@
+//! Charts comparing simulation results across runs.
+//!
+//! [`compare`] draws one small chart per variable, each with a line for
+//! every run, so the effect of a scenario on several variables can be seen
+//! at a glance. Runs are the [`ExportData`] of each run, named for the
+//! legend, and variables are found in them as in
+//! [`ExportData::series`]. Charts are written as a single self-contained SVG
+//! document:
+//!
+//! ```rust
+//! use xmile::data::ExportData;
+//! use xmile::plot::{self, PlotOptions};
+//!
+//! let base = ExportData::new(vec![0.0, 1.0, 2.0]).with_series("Population", vec![100.0, 110.0, 121.0]);
+//! let policy = ExportData::new(vec![0.0, 1.0, 2.0]).with_series("Population", vec![100.0, 105.0, 110.0]);
+//! let svg = plot::compare_svg(&[("base", &base), ("policy", &policy)], &["Population"], &PlotOptions::default()).unwrap();
+//! assert_eq!(svg.matches("<polyline").count(), 2);
+//! ```
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::data::ExportData;
+
+/// Colours of the run lines, in run order, repeating after the last.
+pub const PALETTE: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+];
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PlotError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("No runs to compare")]
+    NoRuns,
+    #[error("No run has values for {0}")]
+    UnknownVariable(String),
+}
+
+/// Layout of comparison charts. Lengths are in SVG user units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotOptions {
+    /// The width of each chart.
+    pub chart_width: f64,
+    /// The height of each chart.
+    pub chart_height: f64,
+    /// The number of charts in each row.
+    pub columns: usize,
+    pub font_size: f64,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions {
+            chart_width: 320.0,
+            chart_height: 200.0,
+            columns: 3,
+            font_size: 11.0,
+        }
+    }
+}
+
+/// Space around the plot area of a chart, for its title and axis labels.
+const INSET_TOP: f64 = 24.0;
+const INSET_BOTTOM: f64 = 20.0;
+const INSET_LEFT: f64 = 56.0;
+const INSET_RIGHT: f64 = 12.0;
+/// The height of the legend row above the charts.
+const LEGEND_HEIGHT: f64 = 24.0;
+
+/// Writes charts of `variables` across `runs` to an SVG file at `path`,
+/// with the default [`PlotOptions`].
+pub fn compare(
+    runs: &[(&str, &ExportData)],
+    variables: &[&str],
+    path: impl AsRef<Path>,
+) -> Result<(), PlotError> {
+    let svg = compare_svg(runs, variables, &PlotOptions::default())?;
+    std::fs::write(path, svg)?;
+    Ok(())
+}
+
+/// Draws charts of `variables` across `runs` as an SVG document.
+///
+/// A run without values for a variable has no line in its chart, but every
+/// variable must have values in at least one run.
+pub fn compare_svg(
+    runs: &[(&str, &ExportData)],
+    variables: &[&str],
+    options: &PlotOptions,
+) -> Result<String, PlotError> {
+    if runs.is_empty() {
+        return Err(PlotError::NoRuns);
+    }
+    for variable in variables {
+        if runs.iter().all(|(_, data)| data.series(variable).is_none()) {
+            return Err(PlotError::UnknownVariable(variable.to_string()));
+        }
+    }
+
+    let columns = options.columns.clamp(1, variables.len().max(1));
+    let rows = variables.len().div_ceil(columns);
+    let width = columns as f64 * options.chart_width;
+    let height = LEGEND_HEIGHT + rows as f64 * options.chart_height;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="{}">"#,
+        options.font_size
+    );
+
+    svg.push_str("<g class=\"legend\">\n");
+    let mut x = INSET_LEFT;
+    for (index, (name, _)) in runs.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x}" y1="12" x2="{}" y2="12" stroke="{}" stroke-width="2"/><text x="{}" y="16">{}</text>"#,
+            x + 16.0,
+            color(index),
+            x + 20.0,
+            escape(name)
+        );
+        x += 28.0 + name.chars().count() as f64 * options.font_size * 0.6;
+    }
+    svg.push_str("</g>\n");
+
+    for (index, variable) in variables.iter().enumerate() {
+        let left = (index % columns) as f64 * options.chart_width;
+        let top = LEGEND_HEIGHT + (index / columns) as f64 * options.chart_height;
+        chart(&mut svg, runs, variable, left, top, options);
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Draws the chart of one variable with its top-left corner at (`left`,
+/// `top`).
+fn chart(
+    svg: &mut String,
+    runs: &[(&str, &ExportData)],
+    variable: &str,
+    left: f64,
+    top: f64,
+    options: &PlotOptions,
+) {
+    let lines: Vec<(usize, Vec<(f64, f64)>)> = runs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, data))| {
+            let values = data.series(variable)?;
+            let points = data
+                .times
+                .iter()
+                .copied()
+                .zip(values.iter().copied())
+                .collect();
+            Some((index, points))
+        })
+        .collect();
+    let finite = || {
+        lines
+            .iter()
+            .flat_map(|(_, points)| points)
+            .filter(|(time, value)| time.is_finite() && value.is_finite())
+    };
+    let (mut x_min, mut x_max) = range(finite().map(|(time, _)| *time));
+    let (mut y_min, mut y_max) = range(finite().map(|(_, value)| *value));
+    if x_max <= x_min {
+        (x_min, x_max) = (x_min - 1.0, x_max + 1.0);
+    }
+    if y_max <= y_min {
+        let pad = y_min.abs().max(1.0) * 0.1;
+        (y_min, y_max) = (y_min - pad, y_max + pad);
+    }
+
+    let plot_left = left + INSET_LEFT;
+    let plot_right = left + options.chart_width - INSET_RIGHT;
+    let plot_top = top + INSET_TOP;
+    let plot_bottom = top + options.chart_height - INSET_BOTTOM;
+    let to_x = |time: f64| plot_left + (time - x_min) / (x_max - x_min) * (plot_right - plot_left);
+    let to_y =
+        |value: f64| plot_bottom - (value - y_min) / (y_max - y_min) * (plot_bottom - plot_top);
+
+    let _ = writeln!(svg, r#"<g class="chart">"#);
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-weight="bold">{}</text>"#,
+        number(left + options.chart_width / 2.0),
+        number(top + INSET_TOP - 8.0),
+        escape(variable)
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#999"/>"##,
+        number(plot_left),
+        number(plot_top),
+        number(plot_right - plot_left),
+        number(plot_bottom - plot_top)
+    );
+    for (value, y) in [(y_max, plot_top), (y_min, plot_bottom)] {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+            number(plot_left - 4.0),
+            number(y),
+            label(value)
+        );
+    }
+    for (time, anchor) in [(x_min, "start"), (x_max, "end")] {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="{anchor}">{}</text>"#,
+            number(to_x(time)),
+            number(plot_bottom + options.font_size + 2.0),
+            label(time)
+        );
+    }
+
+    for (index, points) in &lines {
+        // Values that are not finite break the line
+        for segment in points.split(|(time, value)| !(time.is_finite() && value.is_finite())) {
+            if segment.is_empty() {
+                continue;
+            }
+            let coordinates: Vec<String> = segment
+                .iter()
+                .map(|(time, value)| format!("{},{}", number(to_x(*time)), number(to_y(*value))))
+                .collect();
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+                coordinates.join(" "),
+                color(*index)
+            );
+        }
+    }
+    svg.push_str("</g>\n");
+}
+
+fn range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if min.is_finite() {
+        (min, max)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+fn color(run: usize) -> &'static str {
+    PALETTE[run % PALETTE.len()]
+}
+
+/// Formats an axis label with at most four significant digits.
+fn label(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-3..1e5).contains(&magnitude) {
+        return format!("{value:.2e}");
+    }
+    let decimals = match magnitude {
+        m if m >= 1000.0 => 0,
+        m if m >= 100.0 => 1,
+        m if m >= 10.0 => 2,
+        _ => 3,
+    };
+    let text = format!("{value:.decimals$}");
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Rounds `value` to two decimals for output.
+fn number(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs() -> (ExportData, ExportData) {
+        let times = vec![0.0, 1.0, 2.0, 3.0];
+        let base = ExportData::new(times.clone())
+            .with_series("Population", vec![100.0, 110.0, 121.0, 133.1])
+            .with_series("Birth_Rate", vec![0.1; 4]);
+        let policy = ExportData::new(times)
+            .with_series("Population", vec![100.0, 105.0, f64::NAN, 115.0])
+            .with_series("Birth_Rate", vec![0.05; 4]);
+        (base, policy)
+    }
+
+    #[test]
+    fn test_small_multiples() {
+        let (base, policy) = runs();
+        let runs = [("base", &base), ("policy & more", &policy)];
+        let options = PlotOptions {
+            columns: 1,
+            ..PlotOptions::default()
+        };
+        let svg = compare_svg(&runs, &["Population", "birth rate"], &options).unwrap();
+        assert_eq!(svg.matches(r#"<g class="chart">"#).count(), 2);
+        assert!(
+            svg.contains(r#"height="424""#),
+            "charts stack in one column"
+        );
+        // The missing value splits the policy line in two
+        assert_eq!(svg.matches("<polyline").count(), 5);
+        assert!(svg.contains(">policy &amp; more</text>"));
+        assert!(svg.contains(">133.1</text>"));
+        assert_eq!(label(0.05), "0.05");
+        assert_eq!(label(250000.0), "2.50e5");
+
+        assert!(matches!(
+            compare_svg(&runs, &["Deaths"], &options),
+            Err(PlotError::UnknownVariable(name)) if name == "Deaths"
+        ));
+        assert!(matches!(
+            compare_svg(&[], &["Population"], &options),
+            Err(PlotError::NoRuns)
+        ));
+    }
+
+    #[test]
+    fn test_compare_writes_file() {
+        let (base, policy) = runs();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("compare.svg");
+        compare(
+            &[("base", &base), ("policy", &policy)],
+            &["Population"],
+            &path,
+        )
+        .unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
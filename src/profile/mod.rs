@@ -0,0 +1,223 @@
+//! Opt-in profiling of equation evaluation.
+//!
+//! A [`Profiler`] accumulates the time spent evaluating each variable's
+//! equation, and each graphical function lookup, over a run. Its
+//! [`report`](Profiler::report) lists the most expensive ones, so modellers
+//! can find the constructs slowing their runs:
+//!
+//! ```rust
+//! use xmile::GraphicalFunctionData;
+//! use xmile::profile::{ProfileKind, Profiler};
+//!
+//! let effect = GraphicalFunctionData::uniform_scale((0.0, 1.0), vec![0.0, 0.5, 1.0], None).into();
+//! let (_, equation) = xmile::equation::parse::expression("Population * 0.1").unwrap();
+//!
+//! let mut profiler = Profiler::new();
+//! for step in 0..100 {
+//!     let population = step as f64;
+//!     profiler.evaluate("births", &equation, &|_| Some(population));
+//!     profiler.lookup("Effect", &effect, 0.25);
+//! }
+//! let report = profiler.report(5);
+//! assert_eq!(report.entries.len(), 2);
+//! assert!(report.entries.iter().any(|e| e.kind == ProfileKind::Lookup && e.calls == 100));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::equation::{Expression, Identifier};
+use crate::model::vars::gf::GraphicalFunction;
+
+/// What was timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProfileKind {
+    /// The equation of a variable.
+    Equation,
+    /// A graphical function lookup.
+    Lookup,
+}
+
+impl fmt::Display for ProfileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileKind::Equation => write!(f, "equation"),
+            ProfileKind::Lookup => write!(f, "lookup"),
+        }
+    }
+}
+
+/// The time spent in one equation or lookup over a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileEntry {
+    pub name: String,
+    pub kind: ProfileKind,
+    pub calls: u64,
+    pub total: Duration,
+}
+
+impl ProfileEntry {
+    /// The mean time per call.
+    pub fn mean(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total.div_f64(self.calls as f64)
+        }
+    }
+}
+
+/// Accumulates evaluation times by name.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    entries: Vec<ProfileEntry>,
+    index: HashMap<(ProfileKind, String), usize>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler::default()
+    }
+
+    /// Adds `elapsed` to the time spent in `name`.
+    pub fn record(&mut self, name: &str, kind: ProfileKind, elapsed: Duration) {
+        let key = (kind, key(name));
+        let index = *self.index.entry(key).or_insert_with(|| {
+            self.entries.push(ProfileEntry {
+                name: name.to_string(),
+                kind,
+                calls: 0,
+                total: Duration::ZERO,
+            });
+            self.entries.len() - 1
+        });
+        let entry = &mut self.entries[index];
+        entry.calls += 1;
+        entry.total += elapsed;
+    }
+
+    /// Runs `f`, recording the time it takes against `name`.
+    pub fn time<R>(&mut self, name: &str, kind: ProfileKind, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record(name, kind, start.elapsed());
+        result
+    }
+
+    /// Evaluates the equation of the variable `name`, timing it.
+    pub fn evaluate(
+        &mut self,
+        name: &str,
+        equation: &Expression,
+        lookup: &dyn Fn(&Identifier) -> Option<f64>,
+    ) -> Option<f64> {
+        self.time(name, ProfileKind::Equation, || equation.evaluate(lookup))
+    }
+
+    /// Looks up `x` in the graphical function `name`, timing it.
+    pub fn lookup(&mut self, name: &str, gf: &GraphicalFunction, x: f64) -> f64 {
+        self.time(name, ProfileKind::Lookup, || gf.evaluate(x))
+    }
+
+    /// The `top` entries with the most total time, most expensive first.
+    pub fn report(&self, top: usize) -> ProfileReport {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+        let total = self.entries.iter().map(|entry| entry.total).sum();
+        entries.truncate(top);
+        ProfileReport { total, entries }
+    }
+
+    /// Forgets everything recorded so far.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+}
+
+/// Compares names as identifiers, so `Birth_Rate` and `birth rate` share an
+/// entry, falling back to the name as written.
+fn key(name: &str) -> String {
+    Identifier::parse_default(name)
+        .map(|identifier| identifier.compare_key().to_string())
+        .unwrap_or_else(|_| name.to_string())
+}
+
+/// The most expensive equations and lookups of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileReport {
+    /// The time spent in everything profiled, not only the listed entries.
+    pub total: Duration,
+    pub entries: Vec<ProfileEntry>,
+}
+
+impl fmt::Display for ProfileReport {
+    /// Writes one line per entry with its share of the total time.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total.as_secs_f64();
+        for entry in &self.entries {
+            let share = if total > 0.0 {
+                100.0 * entry.total.as_secs_f64() / total
+            } else {
+                0.0
+            };
+            writeln!(
+                f,
+                "{:>5.1}%  {:>10.3?}  {:>8} calls  {:>10.3?}/call  {} ({})",
+                share,
+                entry.total,
+                entry.calls,
+                entry.mean(),
+                entry.name,
+                entry.kind
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_orders_by_total_time() {
+        let mut profiler = Profiler::new();
+        profiler.record(
+            "Birth_Rate",
+            ProfileKind::Equation,
+            Duration::from_millis(2),
+        );
+        profiler.record(
+            "birth rate",
+            ProfileKind::Equation,
+            Duration::from_millis(2),
+        );
+        profiler.record("Effect", ProfileKind::Lookup, Duration::from_millis(5));
+        profiler.record("deaths", ProfileKind::Equation, Duration::from_millis(1));
+
+        let report = profiler.report(2);
+        assert_eq!(report.total, Duration::from_millis(10));
+        let names: Vec<(&str, u64)> = report
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.calls))
+            .collect();
+        assert_eq!(names, [("Effect", 1), ("Birth_Rate", 2)]);
+        assert_eq!(report.entries[1].mean(), Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_report_display() {
+        let mut profiler = Profiler::new();
+        profiler.record("Effect", ProfileKind::Lookup, Duration::from_millis(3));
+        profiler.record("births", ProfileKind::Equation, Duration::from_millis(1));
+        let text = profiler.report(10).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(" 75.0%"), "{text}");
+        assert!(lines[0].ends_with("Effect (lookup)"), "{text}");
+        assert!(lines[1].ends_with("births (equation)"), "{text}");
+    }
+}
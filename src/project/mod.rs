@@ -0,0 +1,462 @@
+//! Projects: several models with their shared macros, scenarios and tests.
+//!
+//! `<includes>` lets one XMILE file pull in others, but says nothing about
+//! the scenarios and regression tests that go with a model. A project
+//! manifest, `xmile.toml`, lists all of them, with paths relative to the
+//! manifest:
+//!
+//! ```toml
+//! name = "Predator-prey"
+//! description = "Hares, lynxes and their habitat"
+//! # XMILE files whose macros every model can call
+//! macros = ["lib/smoothing.xmile"]
+//!
+//! [[models]]
+//! name = "hares"
+//! path = "models/hares.xmile"
+//!
+//! [[models]]
+//! name = "habitat"
+//! path = "models/habitat.xmile"
+//!
+//! [[scenarios]]
+//! name = "drought"
+//! path = "scenarios/drought.toml"
+//! model = "habitat"
+//!
+//! [[tests]]
+//! path = "tests/hares.tests"
+//! model = "hares"
+//! ```
+//!
+//! Scenarios without a `model` apply to every model. [`Project::load`]
+//! reads the manifest and every file it lists, failing on the first file
+//! that cannot be read, so a loaded project is complete.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::scenario::{Scenario, ScenarioError};
+use crate::testing::assertions::{AssertionError, TestSuite};
+use crate::xml::{ParseError, XmileFile};
+
+/// The file name of a project manifest.
+pub const MANIFEST_FILE: &str = "xmile.toml";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ProjectError {
+    #[error("IO error reading {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid project manifest: {0}")]
+    Manifest(#[from] toml::de::Error),
+    #[error("Model '{0}' is listed more than once")]
+    DuplicateModel(String),
+    #[error("{entry} refers to unknown model '{model}'")]
+    UnknownModel { entry: String, model: String },
+    #[error("Failed to load {path}: {source}")]
+    Model {
+        path: String,
+        #[source]
+        source: ParseError,
+    },
+    #[error("Failed to load scenario {path}: {source}")]
+    Scenario {
+        path: String,
+        #[source]
+        source: ScenarioError,
+    },
+    #[error("Failed to load tests {path}: {source}")]
+    Tests {
+        path: String,
+        #[source]
+        source: AssertionError,
+    },
+    #[error("Error resolving macro calls in model '{model}': {message}")]
+    Macros { model: String, message: String },
+}
+
+/// The contents of an `xmile.toml` manifest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// XMILE files whose macros are available to every model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub macros: Vec<PathBuf>,
+    #[serde(default)]
+    pub models: Vec<ModelEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scenarios: Vec<ScenarioEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<TestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelEntry {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioEntry {
+    /// The name of the scenario, if not the one given in the scenario file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub path: PathBuf,
+    /// The model the scenario applies to, or every model if not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestEntry {
+    pub path: PathBuf,
+    /// The model whose runs the tests check.
+    pub model: String,
+}
+
+impl std::str::FromStr for Manifest {
+    type Err = ProjectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let manifest: Manifest = toml::from_str(s)?;
+        manifest.check()?;
+        Ok(manifest)
+    }
+}
+
+impl Manifest {
+    /// Checks that model names are unique and that scenarios and tests refer
+    /// to listed models.
+    fn check(&self) -> Result<(), ProjectError> {
+        for (index, model) in self.models.iter().enumerate() {
+            if self.models[..index]
+                .iter()
+                .any(|other| other.name == model.name)
+            {
+                return Err(ProjectError::DuplicateModel(model.name.clone()));
+            }
+        }
+        let references = self
+            .scenarios
+            .iter()
+            .filter_map(|scenario| Some((&scenario.path, scenario.model.as_ref()?)))
+            .chain(self.tests.iter().map(|tests| (&tests.path, &tests.model)));
+        for (path, model) in references {
+            if !self.models.iter().any(|entry| &entry.name == model) {
+                return Err(ProjectError::UnknownModel {
+                    entry: path.display().to_string(),
+                    model: model.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A model of a project, parsed, with the project's macros available to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub file: XmileFile,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectScenario {
+    pub name: Option<String>,
+    pub path: PathBuf,
+    pub model: Option<String>,
+    pub scenario: Scenario,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectTests {
+    pub path: PathBuf,
+    pub model: String,
+    pub suite: TestSuite,
+}
+
+/// A loaded project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    /// The directory containing the manifest, which paths are relative to.
+    pub root: PathBuf,
+    pub manifest: Manifest,
+    pub models: Vec<ProjectModel>,
+    /// The macro library files, in manifest order.
+    pub macros: Vec<XmileFile>,
+    pub scenarios: Vec<ProjectScenario>,
+    pub tests: Vec<ProjectTests>,
+}
+
+impl Project {
+    /// Loads the project whose manifest is at `path`, or in the directory
+    /// `path`, and every file the manifest lists.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ProjectError> {
+        let mut path = path.as_ref().to_path_buf();
+        if path.is_dir() {
+            path.push(MANIFEST_FILE);
+        }
+        let contents = fs::read_to_string(&path).map_err(|source| ProjectError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let manifest: Manifest = contents.parse()?;
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Project::resolve(root, manifest)
+    }
+
+    /// Loads every file listed in `manifest`, relative to `root`.
+    pub fn resolve(root: PathBuf, manifest: Manifest) -> Result<Self, ProjectError> {
+        manifest.check()?;
+        let parse = |path: &Path| {
+            let path = root.join(path);
+            XmileFile::from_file(&path).map_err(|source| ProjectError::Model {
+                path: path.display().to_string(),
+                source,
+            })
+        };
+
+        let macros = manifest
+            .macros
+            .iter()
+            .map(|path| parse(path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut models = Vec::new();
+        for entry in &manifest.models {
+            #[allow(unused_mut)]
+            let mut file = parse(&entry.path)?;
+            #[cfg(feature = "macros")]
+            add_library_macros(&entry.name, &mut file, &macros)?;
+            models.push(ProjectModel {
+                name: entry.name.clone(),
+                path: root.join(&entry.path),
+                file,
+            });
+        }
+
+        let scenarios = manifest
+            .scenarios
+            .iter()
+            .map(|entry| {
+                let path = root.join(&entry.path);
+                let scenario = Scenario::load(&path).map_err(|source| ProjectError::Scenario {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+                Ok(ProjectScenario {
+                    name: entry.name.clone().or_else(|| scenario.name.clone()),
+                    path,
+                    model: entry.model.clone(),
+                    scenario,
+                })
+            })
+            .collect::<Result<Vec<_>, ProjectError>>()?;
+
+        let tests = manifest
+            .tests
+            .iter()
+            .map(|entry| {
+                let path = root.join(&entry.path);
+                let suite = TestSuite::from_file(&path).map_err(|source| ProjectError::Tests {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+                Ok(ProjectTests {
+                    path,
+                    model: entry.model.clone(),
+                    suite,
+                })
+            })
+            .collect::<Result<Vec<_>, ProjectError>>()?;
+
+        Ok(Project {
+            root,
+            manifest,
+            models,
+            macros,
+            scenarios,
+            tests,
+        })
+    }
+
+    /// The model with the given manifest name.
+    pub fn model(&self, name: &str) -> Option<&ProjectModel> {
+        self.models.iter().find(|model| model.name == name)
+    }
+
+    /// The scenarios that apply to the named model.
+    pub fn scenarios_for<'a>(
+        &'a self,
+        model: &'a str,
+    ) -> impl Iterator<Item = &'a ProjectScenario> + 'a {
+        self.scenarios
+            .iter()
+            .filter(move |scenario| scenario.model.as_deref().is_none_or(|name| name == model))
+    }
+
+    /// The test suites for the named model.
+    pub fn tests_for<'a>(&'a self, model: &'a str) -> impl Iterator<Item = &'a ProjectTests> + 'a {
+        self.tests.iter().filter(move |tests| tests.model == model)
+    }
+}
+
+/// Adds the macros of `libraries` that `file` does not define itself, and
+/// resolves the file's function calls against them.
+#[cfg(feature = "macros")]
+fn add_library_macros(
+    name: &str,
+    file: &mut XmileFile,
+    libraries: &[XmileFile],
+) -> Result<(), ProjectError> {
+    let mut added = false;
+    for library in libraries {
+        for library_macro in &library.macros {
+            if !file
+                .macros
+                .iter()
+                .any(|existing| existing.name == library_macro.name)
+            {
+                file.macros.push(library_macro.clone());
+                added = true;
+            }
+        }
+    }
+    if added {
+        file.resolve_all_expressions()
+            .map_err(|errors| ProjectError::Macros {
+                model: name.to_string(),
+                message: errors.join("; "),
+            })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+    <model><variables><aux name="Rate"><eqn>0.1</eqn></aux></variables></model>
+</xmile>"#;
+
+    #[test]
+    fn test_load_project() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("models")).unwrap();
+        fs::write(dir.path().join("models/a.xmile"), MODEL).unwrap();
+        fs::write(dir.path().join("models/b.xmile"), MODEL).unwrap();
+        fs::write(dir.path().join("fast.toml"), "name = \"Fast\"\n").unwrap();
+        fs::write(dir.path().join("a.tests"), "assert Rate always >= 0\n").unwrap();
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            r#"name = "Demo"
+
+[[models]]
+name = "a"
+path = "models/a.xmile"
+
+[[models]]
+name = "b"
+path = "models/b.xmile"
+
+[[scenarios]]
+path = "fast.toml"
+
+[[tests]]
+path = "a.tests"
+model = "a"
+"#,
+        )
+        .unwrap();
+
+        let project = Project::load(dir.path()).unwrap();
+        assert_eq!(project.manifest.name.as_deref(), Some("Demo"));
+        assert_eq!(project.models.len(), 2);
+        assert_eq!(project.model("b").unwrap().file.models.len(), 1);
+        assert_eq!(project.scenarios_for("b").count(), 1);
+        assert_eq!(
+            project.scenarios[0].name.as_deref(),
+            Some("Fast"),
+            "the scenario file's name is used"
+        );
+        assert_eq!(project.tests_for("a").count(), 1);
+        assert_eq!(project.tests_for("b").count(), 0);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn test_library_macros_are_added_to_models() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("lib.xmile"),
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+    <macro name="add"><parm>a</parm><parm>b</parm><eqn>a + b</eqn></macro>
+    <model><variables/></model>
+</xmile>"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("model.xmile"),
+            MODEL.replace("<eqn>0.1</eqn>", "<eqn>add(0.1, 1)</eqn>"),
+        )
+        .unwrap();
+        let manifest: Manifest = r#"
+macros = ["lib.xmile"]
+
+[[models]]
+name = "model"
+path = "model.xmile"
+"#
+        .parse()
+        .unwrap();
+
+        let project = Project::resolve(dir.path().to_path_buf(), manifest).unwrap();
+        let file = &project.model("model").unwrap().file;
+        assert_eq!(file.macros.len(), 1);
+        assert!(file.validate().is_ok(), "{:?}", file.validate());
+    }
+
+    #[test]
+    fn test_manifest_references_are_checked() {
+        let error = r#"
+[[models]]
+name = "a"
+path = "a.xmile"
+
+[[tests]]
+path = "b.tests"
+model = "b"
+"#
+        .parse::<Manifest>()
+        .unwrap_err();
+        assert!(matches!(error, ProjectError::UnknownModel { ref model, .. } if model == "b"));
+
+        let error = r#"
+[[models]]
+name = "a"
+path = "a.xmile"
+
+[[models]]
+name = "a"
+path = "b.xmile"
+"#
+        .parse::<Manifest>()
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Model 'a' is listed more than once");
+    }
+}
@@ -0,0 +1,335 @@
+//! Diagrams drawn from the structure of a model rather than its views.
+//!
+//! [`context_diagram`] draws one variable with the variables it depends on
+//! and the variables that depend on it, as SVG. It needs no stored view, so
+//! it works for models built or imported without diagrams, and makes a
+//! quick picture for documentation or for debugging an equation:
+//!
+//! ```rust
+//! use xmile::render;
+//! use xmile::xml::schema::Model;
+//!
+//! let model: Model = quick_xml::de::from_str(
+//!     r#"<model><variables>
+//!         <aux name="Birth_Rate"><eqn>0.03</eqn></aux>
+//!         <flow name="births"><eqn>Population * Birth_Rate</eqn></flow>
+//!         <stock name="Population"><eqn>100</eqn><inflow>births</inflow></stock>
+//!     </variables></model>"#,
+//! )
+//! .unwrap();
+//! let svg = render::context_diagram(&model, "births", 1).unwrap();
+//! assert!(svg.starts_with("<svg"));
+//! ```
+//!
+//! Causes are laid out in columns to the left of the variable, nearest
+//! first, and effects to the right. A variable that is both, in a feedback
+//! loop, is placed on the side where it is nearer, and causes win ties.
+//! Flows into and out of a stock are drawn as thick pipes, and other
+//! dependencies as thin arrows.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::Identifier;
+use crate::model::vars::Variable;
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+
+/// Horizontal distance between the centres of neighbouring columns.
+const COLUMN_SPACING: f64 = 170.0;
+/// Vertical distance between the centres of nodes in a column.
+const ROW_SPACING: f64 = 60.0;
+const NODE_WIDTH: f64 = 120.0;
+const NODE_HEIGHT: f64 = 36.0;
+const MARGIN: f64 = 20.0;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RenderError {
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String),
+}
+
+/// The kind of a node, which decides its shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Stock,
+    Flow,
+    Aux,
+}
+
+/// Draws `variable` with its causes and effects up to `radius` steps away
+/// in the dependency graph of `model`, as an SVG document.
+///
+/// The causes of a stock are its inflows and outflows; the causes of other
+/// variables are the variables their equations use.
+pub fn context_diagram(
+    model: &Model,
+    variable: &str,
+    radius: usize,
+) -> Result<String, RenderError> {
+    let unknown = || RenderError::UnknownVariable(variable.to_string());
+    let name = Identifier::parse_from_attribute(variable).map_err(|_| unknown())?;
+    let variables: Vec<(&Identifier, &Variable)> = model
+        .variables
+        .variables
+        .iter()
+        .filter_map(|variable| Some((get_variable_name(variable)?, variable)))
+        .filter(|(_, variable)| !matches!(variable, Variable::Group(_)))
+        .collect();
+    let center = variables
+        .iter()
+        .position(|(other, _)| **other == name)
+        .ok_or_else(unknown)?;
+
+    let index_of = |name: &Identifier| variables.iter().position(|(other, _)| *other == name);
+    let causes: Vec<Vec<usize>> = variables
+        .iter()
+        .map(|(_, variable)| {
+            let mut causes: Vec<usize> = Vec::new();
+            for cause in inputs(variable).into_iter().filter_map(index_of) {
+                if !causes.contains(&cause) {
+                    causes.push(cause);
+                }
+            }
+            causes
+        })
+        .collect();
+    let mut effects: Vec<Vec<usize>> = vec![Vec::new(); variables.len()];
+    for (effect, causes) in causes.iter().enumerate() {
+        for &cause in causes {
+            effects[cause].push(effect);
+        }
+    }
+
+    // Each shown variable's column: negative for causes, positive for effects
+    let upstream = distances(&causes, center, radius);
+    let downstream = distances(&effects, center, radius);
+    let mut columns: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for index in 0..variables.len() {
+        let column = match (upstream[index], downstream[index]) {
+            _ if index == center => 0,
+            (Some(up), Some(down)) if down < up => down as i64,
+            (Some(up), _) => -(up as i64),
+            (None, Some(down)) => down as i64,
+            (None, None) => continue,
+        };
+        columns.entry(column).or_default().push(index);
+    }
+
+    let first = *columns.keys().next().unwrap_or(&0);
+    let tallest = columns.values().map(Vec::len).max().unwrap_or(1);
+    let width = columns.len() as f64 * COLUMN_SPACING - COLUMN_SPACING + NODE_WIDTH + 2.0 * MARGIN;
+    let height = tallest as f64 * ROW_SPACING - ROW_SPACING + NODE_HEIGHT + 2.0 * MARGIN;
+    let mut positions: Vec<Option<(f64, f64)>> = vec![None; variables.len()];
+    for (column, members) in &columns {
+        let x = MARGIN + NODE_WIDTH / 2.0 + (column - first) as f64 * COLUMN_SPACING;
+        let top = (height - (members.len() as f64 - 1.0) * ROW_SPACING) / 2.0;
+        for (row, &index) in members.iter().enumerate() {
+            positions[index] = Some((x, top + row as f64 * ROW_SPACING));
+        }
+    }
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="12">"#
+    );
+    svg.push_str(
+        r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#,
+    );
+    svg.push_str("\n<g class=\"edges\" stroke=\"black\" fill=\"none\">\n");
+    for (effect, causes) in causes.iter().enumerate() {
+        let Some(to) = positions[effect] else {
+            continue;
+        };
+        for &cause in causes {
+            let Some(from) = positions[cause] else {
+                continue;
+            };
+            let pipe = kind_of(variables[effect].1) == NodeKind::Stock
+                && kind_of(variables[cause].1) == NodeKind::Flow;
+            let ((x1, y1), (x2, y2)) = edge_ends(from, to);
+            let (class, stroke) = if pipe { ("flow", 3) } else { ("link", 1) };
+            let _ = writeln!(
+                svg,
+                r#"<line class="{class}" x1="{}" y1="{}" x2="{}" y2="{}" stroke-width="{stroke}" marker-end="url(#arrow)"/>"#,
+                number(x1),
+                number(y1),
+                number(x2),
+                number(y2)
+            );
+        }
+    }
+    svg.push_str("</g>\n<g class=\"nodes\" stroke=\"black\">\n");
+    for (index, (name, variable)) in variables.iter().enumerate() {
+        let Some((x, y)) = positions[index] else {
+            continue;
+        };
+        let kind = kind_of(variable);
+        let (class, fill, stroke) = if index == center {
+            ("selected", "#fff3c4", 2.5)
+        } else {
+            ("", "white", 1.0)
+        };
+        let (left, top) = (number(x - NODE_WIDTH / 2.0), number(y - NODE_HEIGHT / 2.0));
+        let shape = match kind {
+            NodeKind::Stock => format!(
+                r#"<rect x="{left}" y="{top}" width="{NODE_WIDTH}" height="{NODE_HEIGHT}"/>"#
+            ),
+            NodeKind::Flow => format!(
+                r#"<rect x="{left}" y="{top}" width="{NODE_WIDTH}" height="{NODE_HEIGHT}" rx="{}"/>"#,
+                NODE_HEIGHT / 2.0
+            ),
+            NodeKind::Aux => format!(
+                r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"/>"#,
+                number(x),
+                number(y),
+                NODE_WIDTH / 2.0,
+                NODE_HEIGHT / 2.0
+            ),
+        };
+        let kind_class = match kind {
+            NodeKind::Stock => "stock",
+            NodeKind::Flow => "flow",
+            NodeKind::Aux => "aux",
+        };
+        let _ = writeln!(
+            svg,
+            r#"<g class="{}" fill="{fill}" stroke-width="{stroke}">{shape}<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle" fill="black" stroke="none">{}</text></g>"#,
+            format!("{kind_class} {class}").trim_end(),
+            number(x),
+            number(y),
+            escape(&name.to_string())
+        );
+    }
+    svg.push_str("</g>\n</svg>\n");
+    Ok(svg)
+}
+
+/// The distance from `start` of every variable reachable along `edges`
+/// within `radius` steps.
+fn distances(edges: &[Vec<usize>], start: usize, radius: usize) -> Vec<Option<usize>> {
+    let mut distances = vec![None; edges.len()];
+    distances[start] = Some(0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(index) = queue.pop_front() {
+        let distance = distances[index].unwrap_or(0);
+        if distance == radius {
+            continue;
+        }
+        for &next in &edges[index] {
+            if distances[next].is_none() {
+                distances[next] = Some(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// The names of the variables `variable` depends on.
+fn inputs(variable: &Variable) -> Vec<&Identifier> {
+    match variable {
+        Variable::Stock(stock) => stock.inflows().iter().chain(stock.outflows()).collect(),
+        Variable::Auxiliary(aux) => aux.equation.references(),
+        Variable::Flow(flow) => flow
+            .equation
+            .as_ref()
+            .map(|equation| equation.references())
+            .unwrap_or_default(),
+        Variable::GraphicalFunction(gf) => gf
+            .equation
+            .as_ref()
+            .map(|equation| equation.references())
+            .unwrap_or_default(),
+        #[cfg(feature = "submodels")]
+        Variable::Module(_) => Vec::new(),
+        Variable::Group(_) => Vec::new(),
+    }
+}
+
+fn kind_of(variable: &Variable) -> NodeKind {
+    match variable {
+        Variable::Stock(_) => NodeKind::Stock,
+        Variable::Flow(_) => NodeKind::Flow,
+        _ => NodeKind::Aux,
+    }
+}
+
+/// The ends of an edge between nodes centred at `from` and `to`: the facing
+/// sides of nodes in different columns, or the facing top and bottom of
+/// nodes in the same column.
+fn edge_ends(from: (f64, f64), to: (f64, f64)) -> ((f64, f64), (f64, f64)) {
+    let half_width = NODE_WIDTH / 2.0;
+    let half_height = NODE_HEIGHT / 2.0;
+    if (from.0 - to.0).abs() > f64::EPSILON {
+        let side = (to.0 - from.0).signum();
+        (
+            (from.0 + side * half_width, from.1),
+            (to.0 - side * half_width, to.1),
+        )
+    } else {
+        let side = (to.1 - from.1).signum();
+        (
+            (from.0, from.1 + side * half_height),
+            (to.0, to.1 - side * half_height),
+        )
+    }
+}
+
+/// Rounds `value` to two decimals for output.
+fn number(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> Model {
+        quick_xml::de::from_str(
+            r#"<model><variables>
+                <aux name="Fertility"><eqn>0.03</eqn></aux>
+                <aux name="Birth_Rate"><eqn>Fertility * 1</eqn></aux>
+                <flow name="births"><eqn>Population * Birth_Rate</eqn></flow>
+                <stock name="Population"><eqn>100</eqn><inflow>births</inflow></stock>
+                <aux name="Density"><eqn>Population / 10</eqn></aux>
+            </variables></model>"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_radius_limits_the_diagram() {
+        let model = model();
+        let svg = context_diagram(&model, "births", 1).unwrap();
+        assert!(svg.contains(r#"<g class="flow selected""#));
+        assert!(svg.contains(">Birth Rate</text>"));
+        assert!(svg.contains(">Population</text>"));
+        assert!(!svg.contains(">Fertility</text>"));
+        assert!(!svg.contains(">Density</text>"));
+        // births feeds Population, which feeds back into births
+        assert_eq!(svg.matches(r#"class="flow" x1"#).count(), 1);
+        assert_eq!(svg.matches(r#"class="link""#).count(), 2);
+
+        let svg = context_diagram(&model, "births", 2).unwrap();
+        assert!(svg.contains(">Fertility</text>"));
+        assert!(svg.contains(">Density</text>"));
+    }
+
+    #[test]
+    fn test_unknown_variable() {
+        let error = context_diagram(&model(), "Deaths", 1).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown variable: Deaths");
+    }
+}
@@ -0,0 +1,160 @@
+//! JUnit XML reports of checks and test runs.
+
+use crate::conformance::ConformanceReport;
+use crate::testing::assertions::TestReport;
+
+use super::{Finding, Level, escape_xml};
+
+/// A test case and why it failed, if it did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub name: String,
+    /// The group the case belongs to, usually the file checked.
+    pub classname: String,
+    pub failure: Option<String>,
+}
+
+/// A named group of test cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestSuite {
+    pub name: String,
+    pub cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+    /// One case per model, failing with the problems found in it.
+    pub fn from_conformance(name: &str, reports: &[ConformanceReport]) -> Self {
+        let cases = reports
+            .iter()
+            .map(|report| {
+                let errors: Vec<String> = Finding::from_conformance(report)
+                    .into_iter()
+                    .filter(|finding| finding.level == Level::Error)
+                    .map(|finding| format!("{}: {}", finding.rule, finding.message))
+                    .collect();
+                TestCase {
+                    name: "conformance".to_string(),
+                    classname: report.name.clone(),
+                    failure: (!errors.is_empty()).then(|| errors.join("\n")),
+                }
+            })
+            .collect();
+        TestSuite {
+            name: name.to_string(),
+            cases,
+        }
+    }
+
+    /// One case per assertion of a test run.
+    pub fn from_tests(name: &str, report: &TestReport) -> Self {
+        let cases = report
+            .outcomes
+            .iter()
+            .map(|outcome| TestCase {
+                name: format!("line {}: {}", outcome.assertion.line, outcome.assertion),
+                classname: name.to_string(),
+                failure: outcome.failure.clone(),
+            })
+            .collect();
+        TestSuite {
+            name: name.to_string(),
+            cases,
+        }
+    }
+
+    /// One case per file, failing with the errors found in it. Files are
+    /// those named by `files`, so files without findings pass.
+    pub fn from_findings(name: &str, files: &[&str], findings: &[Finding]) -> Self {
+        let cases = files
+            .iter()
+            .map(|file| {
+                let errors: Vec<&str> = findings
+                    .iter()
+                    .filter(|f| f.level == Level::Error && f.file.as_deref() == Some(*file))
+                    .map(|f| f.message.as_str())
+                    .collect();
+                TestCase {
+                    name: name.to_string(),
+                    classname: file.to_string(),
+                    failure: (!errors.is_empty()).then(|| errors.join("\n")),
+                }
+            })
+            .collect();
+        TestSuite {
+            name: name.to_string(),
+            cases,
+        }
+    }
+
+    pub fn failures(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.failure.is_some())
+            .count()
+    }
+}
+
+/// Writes `suites` as a JUnit XML `<testsuites>` document.
+pub fn to_string(suites: &[TestSuite]) -> String {
+    let tests: usize = suites.iter().map(|suite| suite.cases.len()).sum();
+    let failures: usize = suites.iter().map(TestSuite::failures).sum();
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"{tests}\" failures=\"{failures}\">\n"
+    );
+    for suite in suites {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            escape_xml(&suite.name),
+            suite.cases.len(),
+            suite.failures()
+        ));
+        for case in &suite.cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape_xml(&case.name),
+                escape_xml(&case.classname)
+            );
+            match &case.failure {
+                None => xml.push_str(&format!("{open}/>\n")),
+                Some(failure) => {
+                    let message = failure.lines().next().unwrap_or_default();
+                    xml.push_str(&format!(
+                        "{open}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                        escape_xml(message),
+                        escape_xml(failure)
+                    ));
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::check_str;
+
+    #[test]
+    fn test_junit_report() {
+        let broken = check_str("broken.xmile", "<xmile");
+        let suite = TestSuite::from_conformance("conformance", &[broken]);
+        assert_eq!(suite.failures(), 1);
+
+        let passing = TestSuite {
+            name: "validation".to_string(),
+            cases: vec![TestCase {
+                name: "a < b".to_string(),
+                classname: "model.xmile".to_string(),
+                failure: None,
+            }],
+        };
+        let xml = to_string(&[suite, passing]);
+        assert!(xml.contains(r#"<testsuites tests="2" failures="1">"#));
+        assert!(xml.contains(r#"<testcase name="conformance" classname="broken.xmile">"#));
+        assert!(xml.contains(r#"<failure message="parse: "#));
+        assert!(xml.contains(r#"<testcase name="a &lt; b" classname="model.xmile"/>"#));
+    }
+}
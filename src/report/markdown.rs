@@ -0,0 +1,520 @@
+//! Markdown reports of analyses, for wikis and pull requests.
+//!
+//! A [`MarkdownReport`] is built up section by section: sensitivity
+//! indices and elementary effects as tables ranked by importance with a
+//! bar chart, ensemble statistics as a table over time with a chart of the
+//! mean and its bands, and validation findings as a table. Charts are SVG
+//! images embedded as data URLs, so the report is a single file:
+//!
+//! ```rust
+//! use xmile::analysis::SobolIndex;
+//! use xmile::report::markdown::MarkdownReport;
+//!
+//! let indices = [SobolIndex { parameter: "birth rate".to_string(), first_order: 0.7, total: 0.8 }];
+//! let report = MarkdownReport::new("Population study")
+//!     .with_sobol_indices("Population at 2050", &indices)
+//!     .to_markdown();
+//! assert!(report.contains("| birth rate | 0.700 | 0.800 |"));
+//! ```
+//!
+//! Some viewers, GitHub among them, do not show images from data URLs.
+//! [`MarkdownReport::write_linked`] instead writes each chart to an SVG
+//! file beside the report, linked by its relative path.
+
+use std::fmt::{self, Write as _};
+use std::io;
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::analysis::{ElementaryEffects, Ensemble, SobolIndex};
+
+use super::{Finding, escape_xml};
+
+/// The most rows an ensemble table has; longer runs are thinned to evenly
+/// spaced saved times.
+const MAX_ROWS: usize = 21;
+
+/// Colour of bars, lines and bands.
+const COLOR: &str = "#1f77b4";
+
+/// A Markdown document of analysis results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownReport {
+    title: String,
+    sections: Vec<Vec<Block>>,
+}
+
+/// Part of a section: Markdown, or a chart whose link depends on where it
+/// is written.
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Markdown(String),
+    Image { caption: String, svg: String },
+}
+
+fn image(caption: &str, svg: String) -> Block {
+    Block::Image {
+        caption: caption.to_string(),
+        svg,
+    }
+}
+
+impl MarkdownReport {
+    pub fn new(title: &str) -> Self {
+        MarkdownReport {
+            title: title.to_string(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Adds a paragraph of text, written as Markdown.
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.sections
+            .push(vec![Block::Markdown(format!("{}\n", text.trim_end()))]);
+        self
+    }
+
+    /// Adds an SVG image, such as a chart from [`crate::plot`], with a
+    /// caption.
+    pub fn with_chart(mut self, caption: &str, svg: &str) -> Self {
+        self.sections.push(vec![image(caption, svg.to_string())]);
+        self
+    }
+
+    /// Adds the Sobol indices of `output`, in the order given, which
+    /// [`sobol_indices`](crate::analysis::sensitivity::sobol_indices) ranks
+    /// by total effect.
+    pub fn with_sobol_indices(mut self, output: &str, indices: &[SobolIndex]) -> Self {
+        let rows: Vec<Vec<String>> = indices
+            .iter()
+            .map(|index| {
+                vec![
+                    index.parameter.clone(),
+                    format!("{:.3}", index.first_order),
+                    format!("{:.3}", index.total),
+                ]
+            })
+            .collect();
+        let bars: Vec<(&str, f64)> = indices
+            .iter()
+            .map(|index| (index.parameter.as_str(), index.total))
+            .collect();
+        self.sections.push(vec![
+            Block::Markdown(format!(
+                "## Sensitivity of {}\n\n{}\n",
+                output,
+                table(&["Parameter", "First order", "Total effect"], &rows),
+            )),
+            image(&format!("Total effects on {output}"), bar_chart(&bars)),
+        ]);
+        self
+    }
+
+    /// Adds the elementary effects of the parameters on `output`, in the
+    /// order given, which
+    /// [`morris_effects`](crate::analysis::sensitivity::morris_effects)
+    /// ranks by `mu_star`.
+    pub fn with_elementary_effects(mut self, output: &str, effects: &[ElementaryEffects]) -> Self {
+        let rows: Vec<Vec<String>> = effects
+            .iter()
+            .map(|effect| {
+                vec![
+                    effect.parameter.clone(),
+                    number(effect.mu_star),
+                    number(effect.mu),
+                    number(effect.sigma),
+                ]
+            })
+            .collect();
+        let bars: Vec<(&str, f64)> = effects
+            .iter()
+            .map(|effect| (effect.parameter.as_str(), effect.mu_star))
+            .collect();
+        self.sections.push(vec![
+            Block::Markdown(format!(
+                "## Screening of {}\n\n{}\n",
+                output,
+                table(&["Parameter", "μ*", "μ", "σ"], &rows),
+            )),
+            image(
+                &format!("Mean absolute effects on {output}"),
+                bar_chart(&bars),
+            ),
+        ]);
+        self
+    }
+
+    /// Adds the statistics of `variable` over the runs of `ensemble`, as a
+    /// table of the mean, standard deviation, envelope and percentiles over
+    /// time and a chart of the mean within its envelope and its widest
+    /// percentile band. Nothing is added if the ensemble has no statistics
+    /// for the variable.
+    pub fn with_ensemble(mut self, variable: &str, ensemble: &Ensemble) -> Self {
+        let (Some(mean), Some(sd), Some((min, max))) = (
+            ensemble.mean(variable),
+            ensemble.std_dev(variable),
+            ensemble.envelope(variable),
+        ) else {
+            return self;
+        };
+        let percentiles: Vec<(f64, Vec<f64>)> = ensemble
+            .percentiles()
+            .iter()
+            .filter_map(|&p| Some((p, ensemble.percentile(variable, p)?)))
+            .collect();
+
+        let mut headers: Vec<String> = ["Time", "Mean", "SD", "Min"].map(String::from).into();
+        headers.extend(percentiles.iter().map(|(p, _)| percent(*p)));
+        headers.push("Max".to_string());
+        let times = ensemble.times();
+        let rows: Vec<Vec<String>> = thinned(times.len())
+            .map(|row| {
+                let mut cells = vec![
+                    number(times[row]),
+                    number(mean[row]),
+                    number(sd[row]),
+                    number(min[row]),
+                ];
+                cells.extend(percentiles.iter().map(|(_, values)| number(values[row])));
+                cells.push(number(max[row]));
+                cells
+            })
+            .collect();
+        let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+
+        let band = match (percentiles.first(), percentiles.last()) {
+            (Some((low, lower)), Some((high, upper))) if low < high => {
+                Some((lower.as_slice(), upper.as_slice()))
+            }
+            _ => None,
+        };
+        self.sections.push(vec![
+            Block::Markdown(format!(
+                "## {} over {} runs\n\n{}\n",
+                variable,
+                ensemble.runs(),
+                table(&headers, &rows),
+            )),
+            image(
+                &format!("{variable} over {} runs", ensemble.runs()),
+                band_chart(times, &mean, (min.as_slice(), max.as_slice()), band),
+            ),
+        ]);
+        self
+    }
+
+    /// Adds `findings` under `heading`, or a note that there were none.
+    pub fn with_findings(mut self, heading: &str, findings: &[Finding]) -> Self {
+        let body = if findings.is_empty() {
+            "No problems found.\n".to_string()
+        } else {
+            let rows: Vec<Vec<String>> = findings
+                .iter()
+                .map(|finding| {
+                    let location = match (&finding.file, finding.line) {
+                        (Some(file), Some(line)) => format!("{file}:{line}"),
+                        (Some(file), None) => file.clone(),
+                        (None, Some(line)) => format!("line {line}"),
+                        (None, None) => String::new(),
+                    };
+                    vec![
+                        finding.level.to_string(),
+                        finding.rule.clone(),
+                        location,
+                        finding.message.clone(),
+                    ]
+                })
+                .collect();
+            table(&["Level", "Rule", "Location", "Message"], &rows)
+        };
+        self.sections
+            .push(vec![Block::Markdown(format!("## {heading}\n\n{body}"))]);
+        self
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.to_string()
+    }
+
+    /// The report with its charts linked rather than embedded, and the
+    /// charts to write beside it: `{stem}-1.svg`, `{stem}-2.svg` and so on,
+    /// in the order they appear.
+    pub fn to_markdown_linked(&self, stem: &str) -> (String, Vec<(String, String)>) {
+        let mut files = Vec::new();
+        let markdown = self.render(&mut |caption, svg| {
+            let file = format!("{stem}-{}.svg", files.len() + 1);
+            let link = format!("![{}]({})\n", alt(caption), file.replace(' ', "%20"));
+            files.push((file, svg.to_string()));
+            link
+        });
+        (markdown, files)
+    }
+
+    /// Writes the report to `path` and each of its charts to an SVG file
+    /// beside it, named after the report as
+    /// [`to_markdown_linked`](Self::to_markdown_linked) names them, so that
+    /// viewers which do not show data URLs still show the charts.
+    pub fn write_linked(&self, path: &Path) -> io::Result<()> {
+        let stem = path
+            .file_stem()
+            .map_or("report".into(), |stem| stem.to_string_lossy());
+        let (markdown, files) = self.to_markdown_linked(&stem);
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for (file, svg) in files {
+            std::fs::write(directory.join(file), svg)?;
+        }
+        std::fs::write(path, markdown)
+    }
+
+    /// The Markdown of the report, with each chart written by `image`.
+    fn render(&self, image: &mut dyn FnMut(&str, &str) -> String) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        for section in &self.sections {
+            markdown.push('\n');
+            for block in section {
+                match block {
+                    Block::Markdown(text) => markdown.push_str(text),
+                    Block::Image { caption, svg } => markdown.push_str(&image(caption, svg)),
+                }
+            }
+        }
+        markdown
+    }
+}
+
+impl fmt::Display for MarkdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&mut embedded))
+    }
+}
+
+/// A Markdown table, escaping pipes and line breaks in cells.
+fn table<S: AsRef<str>>(headers: &[&str], rows: &[Vec<S>]) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+    let mut table = line(headers.iter().map(|header| cell(header)).collect());
+    table.push_str(&line(headers.iter().map(|_| "---".to_string()).collect()));
+    for row in rows {
+        table.push_str(&line(row.iter().map(|text| cell(text.as_ref())).collect()));
+    }
+    table
+}
+
+/// An SVG embedded as a Markdown image.
+fn embedded(caption: &str, svg: &str) -> String {
+    format!(
+        "![{}](data:image/svg+xml;base64,{})\n",
+        alt(caption),
+        STANDARD.encode(svg)
+    )
+}
+
+/// A caption as the alternative text of an image.
+fn alt(caption: &str) -> String {
+    caption.replace(['[', ']'], "")
+}
+
+/// A number with at most four decimal places, without trailing zeros.
+fn number(value: f64) -> String {
+    let text = format!("{value:.4}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// The column heading of a percentile, such as `p95`.
+fn percent(p: f64) -> String {
+    format!("p{}", (p * 100.0 * 1e6).round() / 1e6)
+}
+
+/// At most [`MAX_ROWS`] evenly spaced rows of `count`, always including
+/// the first and the last.
+fn thinned(count: usize) -> impl Iterator<Item = usize> {
+    let shown = count.min(MAX_ROWS);
+    (0..shown).map(move |i| match shown {
+        1 => 0,
+        _ => i * (count - 1) / (shown - 1),
+    })
+}
+
+/// A horizontal bar chart of labelled values, scaled to the largest.
+fn bar_chart(bars: &[(&str, f64)]) -> String {
+    const LABEL: f64 = 140.0;
+    const BAR: f64 = 260.0;
+    const ROW: f64 = 22.0;
+    let largest = bars
+        .iter()
+        .map(|(_, value)| value.abs())
+        .fold(0.0, f64::max);
+    let height = ROW * bars.len() as f64 + 8.0;
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{height}" font-family="sans-serif" font-size="11">"#,
+        LABEL + BAR + 60.0
+    );
+    svg.push('\n');
+    for (index, (label, value)) in bars.iter().enumerate() {
+        let y = 4.0 + index as f64 * ROW;
+        let width = if largest > 0.0 {
+            value.abs() / largest * BAR
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="end">{}</text><rect x="{LABEL}" y="{y}" width="{width}" height="{}" fill="{COLOR}"/><text x="{}" y="{}">{}</text>"#,
+            LABEL - 6.0,
+            y + 14.0,
+            escape_xml(label),
+            ROW - 6.0,
+            LABEL + width + 4.0,
+            y + 14.0,
+            number(*value),
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// A chart of `mean` over `times`, within its `envelope` and an optional
+/// percentile band.
+fn band_chart(
+    times: &[f64],
+    mean: &[f64],
+    envelope: (&[f64], &[f64]),
+    band: Option<(&[f64], &[f64])>,
+) -> String {
+    const WIDTH: f64 = 480.0;
+    const HEIGHT: f64 = 240.0;
+    const INSET: f64 = 40.0;
+    let (t0, t1) = (times.first().copied(), times.last().copied());
+    let (low, high) = (
+        envelope.0.iter().copied().fold(f64::INFINITY, f64::min),
+        envelope.1.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+    );
+    let span = |a: f64, b: f64| if b > a { b - a } else { 1.0 };
+    let (t0, t1) = (t0.unwrap_or(0.0), t1.unwrap_or(1.0));
+    let x = |t: f64| INSET + (t - t0) / span(t0, t1) * (WIDTH - 2.0 * INSET);
+    let y = |v: f64| HEIGHT - INSET - (v - low) / span(low, high) * (HEIGHT - 2.0 * INSET);
+    let points = |values: &mut dyn Iterator<Item = (f64, f64)>| {
+        values
+            .map(|(t, v)| format!("{:.2},{:.2}", x(t), y(v)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let area = |lower: &[f64], upper: &[f64]| {
+        let forward = times.iter().copied().zip(upper.iter().copied());
+        let back = times.iter().copied().zip(lower.iter().copied()).rev();
+        points(&mut forward.chain(back))
+    };
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="11">"#
+    );
+    svg.push('\n');
+    let _ = writeln!(
+        svg,
+        r#"<polygon points="{}" fill="{COLOR}" fill-opacity="0.15"/>"#,
+        area(envelope.0, envelope.1)
+    );
+    if let Some((lower, upper)) = band {
+        let _ = writeln!(
+            svg,
+            r#"<polygon points="{}" fill="{COLOR}" fill-opacity="0.3"/>"#,
+            area(lower, upper)
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r#"<polyline points="{}" fill="none" stroke="{COLOR}" stroke-width="2"/>"#,
+        points(&mut times.iter().copied().zip(mean.iter().copied()))
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="4" y="{}">{}</text><text x="4" y="{}">{}</text><text x="{INSET}" y="{}">{}</text><text x="{}" y="{}" text-anchor="end">{}</text>"#,
+        INSET,
+        number(high),
+        HEIGHT - INSET,
+        number(low),
+        HEIGHT - INSET + 16.0,
+        number(t0),
+        WIDTH - INSET,
+        HEIGHT - INSET + 16.0,
+        number(t1),
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ExportData;
+
+    #[test]
+    fn test_report() {
+        let mut ensemble = Ensemble::new().with_percentiles(&[0.1, 0.9]).unwrap();
+        for scale in [1.0, 2.0, 3.0] {
+            let run = ExportData::new(vec![0.0, 1.0, 2.0])
+                .with_series("Stock", vec![0.0, scale, 2.0 * scale]);
+            ensemble.add(&run).unwrap();
+        }
+        let effects = [ElementaryEffects {
+            parameter: "rate | growth".to_string(),
+            mu: -1.5,
+            mu_star: 1.5,
+            sigma: 0.25,
+        }];
+        let report = MarkdownReport::new("Study")
+            .with_text("Three runs.")
+            .with_elementary_effects("Stock", &effects)
+            .with_ensemble("Stock", &ensemble)
+            .with_ensemble("Unknown", &ensemble)
+            .with_findings(
+                "Validation",
+                &[Finding::error("units", "Bad units").in_file("a.xmile")],
+            )
+            .to_markdown();
+
+        assert!(report.starts_with("# Study\n\nThree runs.\n\n## Screening of Stock\n"));
+        assert!(report.contains("| rate \\| growth | 1.5 | -1.5 | 0.25 |"));
+        assert!(report.contains("| Time | Mean | SD | Min | p10 | p90 | Max |\n"));
+        assert!(report.contains("| 2 | 4 | 2 | 2 | 2.4 | 5.6 | 6 |\n"));
+        assert!(report.contains("| error | units | a.xmile | Bad units |"));
+        assert_eq!(report.matches("## ").count(), 3);
+        assert_eq!(report.matches("data:image/svg+xml;base64,").count(), 2);
+    }
+
+    #[test]
+    fn test_linked_charts() {
+        let report = MarkdownReport::new("Study")
+            .with_chart("First [chart]", "<svg/>")
+            .with_text("Between.")
+            .with_chart("Second", "<svg></svg>");
+        let (markdown, files) = report.to_markdown_linked("my study");
+        assert_eq!(
+            markdown,
+            "# Study\n\n![First chart](my%20study-1.svg)\n\nBetween.\n\n![Second](my%20study-2.svg)\n"
+        );
+        assert_eq!(
+            files,
+            [
+                ("my study-1.svg".to_string(), "<svg/>".to_string()),
+                ("my study-2.svg".to_string(), "<svg></svg>".to_string()),
+            ]
+        );
+        // Embedding stays the default
+        assert!(!report.to_markdown().contains(".svg"));
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("my study.md");
+        report.write_linked(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), markdown);
+        let chart = std::fs::read_to_string(directory.path().join("my study-2.svg")).unwrap();
+        assert_eq!(chart, "<svg></svg>");
+    }
+}
@@ -0,0 +1,228 @@
+//! Machine-readable reports of validation, conformance and test results.
+//!
+//! Problems found in models are collected as [`Finding`]s, which
+//! [`sarif::to_string`] writes as a SARIF 2.1.0 log for code scanning
+//! tools such as GitHub checks. Outcomes of checks and test runs are
+//! collected as [`junit::TestSuite`]s, which [`junit::to_string`] writes as
+//! JUnit XML for CI test reports such as GitLab's. Analysis results are
+//! written as Markdown for wikis and pull requests by
+//! [`markdown::MarkdownReport`].
+//!
+//! ```rust
+//! use xmile::report::{Finding, sarif};
+//!
+//! let findings = vec![Finding::error("validation", "Duplicate variable name: x").in_file("model.xmile")];
+//! let log = sarif::to_string("xmile", "0.1.0", &findings);
+//! assert!(log.contains(r#""ruleId": "validation""#));
+//! ```
+
+use std::fmt;
+
+use crate::conformance::ConformanceReport;
+use crate::testing::assertions::TestReport;
+use crate::types::ValidationResult;
+use crate::xml::{ErrorContext, XmileError};
+
+pub mod junit;
+pub mod markdown;
+pub mod sarif;
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Error => write!(f, "error"),
+            Level::Warning => write!(f, "warning"),
+            Level::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A problem found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// A short identifier for the kind of problem, e.g. `validation`.
+    pub rule: String,
+    pub level: Level,
+    pub message: String,
+    pub file: Option<String>,
+    /// The line of the file the problem is on, from 1.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl Finding {
+    pub fn new(rule: impl Into<String>, level: Level, message: impl Into<String>) -> Self {
+        Finding {
+            rule: rule.into(),
+            level,
+            message: message.into(),
+            file: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn error(rule: impl Into<String>, message: impl Into<String>) -> Self {
+        Finding::new(rule, Level::Error, message)
+    }
+
+    pub fn warning(rule: impl Into<String>, message: impl Into<String>) -> Self {
+        Finding::new(rule, Level::Warning, message)
+    }
+
+    pub fn in_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Lists the warnings and errors of a validation result.
+    pub fn from_validation<T>(result: &ValidationResult<T>, file: Option<&str>) -> Vec<Finding> {
+        let (warnings, errors): (&[String], &[String]) = match result {
+            ValidationResult::Valid(_) => (&[], &[]),
+            ValidationResult::Warnings(_, warnings) => (warnings, &[]),
+            ValidationResult::Invalid(warnings, errors) => (warnings, errors),
+        };
+        let located = |finding: Finding| match file {
+            Some(file) => finding.in_file(file),
+            None => finding,
+        };
+        errors
+            .iter()
+            .map(|error| located(Finding::error("validation", error)))
+            .chain(
+                warnings
+                    .iter()
+                    .map(|warning| located(Finding::warning("validation", warning))),
+            )
+            .collect()
+    }
+
+    /// Lists the problems behind a parse or validation error, with the file
+    /// and line they occurred at where known.
+    pub fn from_error(error: &XmileError) -> Vec<Finding> {
+        let located = |finding: Finding, context: &ErrorContext| Finding {
+            file: context
+                .file_path
+                .as_ref()
+                .map(|path| path.display().to_string()),
+            line: context.line,
+            column: context.column,
+            ..finding
+        };
+        match error {
+            XmileError::Io(error) => vec![Finding::error("io", error.to_string())],
+            XmileError::Xml { message, context } => {
+                vec![located(Finding::error("xml", message.as_str()), context)]
+            }
+            XmileError::Deserialize { message, context } => {
+                vec![located(Finding::error("parse", message.as_str()), context)]
+            }
+            XmileError::Validation(validation) => {
+                let mut findings: Vec<Finding> = validation
+                    .errors
+                    .iter()
+                    .map(|error| located(Finding::error("validation", error), &validation.context))
+                    .chain(validation.warnings.iter().map(|warning| {
+                        located(Finding::warning("validation", warning), &validation.context)
+                    }))
+                    .collect();
+                if findings.is_empty() {
+                    findings.push(located(
+                        Finding::error("validation", validation.message.as_str()),
+                        &validation.context,
+                    ));
+                }
+                findings
+            }
+            XmileError::Multiple(errors) => errors.iter().flat_map(Finding::from_error).collect(),
+        }
+    }
+
+    /// Lists the failed stages and unsupported features of a conformance
+    /// check, located in the checked file.
+    pub fn from_conformance(report: &ConformanceReport) -> Vec<Finding> {
+        let stages = [
+            ("parse", &report.parse_error),
+            ("validation", &report.validation_error),
+            ("round-trip", &report.round_trip_error),
+        ];
+        stages
+            .into_iter()
+            .filter_map(|(rule, error)| {
+                error
+                    .as_ref()
+                    .map(|error| Finding::error(rule, error.as_str()).in_file(&report.name))
+            })
+            .chain(report.unsupported_features.iter().map(|feature| {
+                Finding::warning(
+                    "unsupported-feature",
+                    format!(
+                        "The model uses {}, which this build does not support",
+                        feature
+                    ),
+                )
+                .in_file(&report.name)
+            }))
+            .collect()
+    }
+
+    /// Lists the failed assertions of a test run, located in the test file.
+    pub fn from_tests(report: &TestReport, test_file: &str) -> Vec<Finding> {
+        report
+            .failures()
+            .map(|outcome| {
+                Finding::error(
+                    "assertion",
+                    format!(
+                        "{}: {}",
+                        outcome.assertion,
+                        outcome.failure.as_deref().unwrap_or_default()
+                    ),
+                )
+                .in_file(test_file)
+                .at_line(outcome.assertion.line)
+            })
+            .collect()
+    }
+}
+
+/// Escapes text for XML content and attribute values.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Writes `text` as a JSON string, with quotes.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,83 @@
+//! SARIF 2.1.0 logs of findings.
+
+use std::collections::BTreeSet;
+
+use super::{Finding, Level, json_string};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Writes `findings` as a SARIF log of one run of the tool `tool` at
+/// `version`. Every rule used by a finding is listed in the tool's rules.
+pub fn to_string(tool: &str, version: &str, findings: &[Finding]) -> String {
+    let rules: BTreeSet<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+    let rules: Vec<String> = rules
+        .into_iter()
+        .map(|rule| format!("            {{ \"id\": {} }}", json_string(rule)))
+        .collect();
+    let results: Vec<String> = findings.iter().map(result).collect();
+
+    format!(
+        "{{\n  \"$schema\": {},\n  \"version\": \"2.1.0\",\n  \"runs\": [\n    {{\n      \"tool\": {{\n        \"driver\": {{\n          \"name\": {},\n          \"version\": {},\n          \"rules\": [\n{}\n          ]\n        }}\n      }},\n      \"results\": [\n{}\n      ]\n    }}\n  ]\n}}\n",
+        json_string(SCHEMA),
+        json_string(tool),
+        json_string(version),
+        rules.join(",\n"),
+        results.join(",\n"),
+    )
+}
+
+fn result(finding: &Finding) -> String {
+    let level = match finding.level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note => "note",
+    };
+    let mut result = format!(
+        "        {{\n          \"ruleId\": {},\n          \"level\": \"{}\",\n          \"message\": {{ \"text\": {} }}",
+        json_string(&finding.rule),
+        level,
+        json_string(&finding.message),
+    );
+    if let Some(file) = &finding.file {
+        let mut region = Vec::new();
+        if let Some(line) = finding.line {
+            region.push(format!("\"startLine\": {line}"));
+        }
+        if let Some(column) = finding.column {
+            region.push(format!("\"startColumn\": {column}"));
+        }
+        let region = if region.is_empty() {
+            String::new()
+        } else {
+            format!(", \"region\": {{ {} }}", region.join(", "))
+        };
+        result.push_str(&format!(
+            ",\n          \"locations\": [\n            {{ \"physicalLocation\": {{ \"artifactLocation\": {{ \"uri\": {} }}{} }} }}\n          ]",
+            json_string(&file.replace('\\', "/")),
+            region,
+        ));
+    }
+    result.push_str("\n        }");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_log() {
+        let findings = vec![
+            Finding::error("validation", "Duplicate \"x\"")
+                .in_file("models\\pop.xmile")
+                .at_line(12),
+            Finding::warning("unsupported-feature", "Uses conveyors"),
+        ];
+        let log = to_string("xmile", "1.0", &findings);
+        assert!(log.contains(r#""message": { "text": "Duplicate \"x\"" }"#));
+        assert!(log.contains(r#""uri": "models/pop.xmile""#));
+        assert!(log.contains(r#""region": { "startLine": 12 }"#));
+        assert!(log.contains(r#"{ "id": "unsupported-feature" }"#));
+        assert_eq!(log.matches("\"ruleId\"").count(), 2);
+    }
+}
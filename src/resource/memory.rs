@@ -0,0 +1,244 @@
+//! In-memory resources, keyed by `/`-separated path.
+
+use std::collections::BTreeMap;
+
+use super::{
+    Location, ResourceError, ResourceKind, ResourceLocator, ResourceReader, ResourceRef,
+    wildcard_match,
+};
+
+/// Serves resources from memory.
+///
+/// Entries are keyed by `/`-separated path. Relative references are resolved
+/// against a base directory within the store and absolute paths fall back to
+/// their final file name under it, mirroring [`FileReader`](super::FileReader).
+/// URLs are served if an entry was inserted under the full URL.
+///
+/// ```rust
+/// use xmile::resource::{MemoryResources, ResourceReader};
+///
+/// let resources = MemoryResources::new()
+///     .with("models/data/input.csv", "time,x\n0,1\n")
+///     .with_base("models");
+/// assert_eq!(resources.read(&"data/input.csv".into()).unwrap(), b"time,x\n0,1\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryResources {
+    base: String,
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryResources {
+    /// Creates an empty store resolving relative references from its root.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves relative references against the directory `base`.
+    pub fn with_base(mut self, base: &str) -> Self {
+        self.base = normalize(base).unwrap_or_default();
+        self
+    }
+
+    /// Adds the entry `name`, returning the store.
+    pub fn with(mut self, name: &str, contents: impl Into<Vec<u8>>) -> Self {
+        self.insert(name, contents);
+        self
+    }
+
+    /// Adds or replaces the entry `name`.
+    pub fn insert(&mut self, name: &str, contents: impl Into<Vec<u8>>) {
+        self.entries.insert(name.to_string(), contents.into());
+    }
+
+    /// The directory relative references are resolved against.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The contents of the entry `name`, if present.
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.entries.get(name).map(Vec::as_slice)
+    }
+
+    /// Returns true if the store has an entry `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// The names of all entries, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Joins `relative` to the base directory and normalizes the result.
+    fn join(&self, relative: &str, resource: &ResourceRef) -> Result<String, ResourceError> {
+        let joined = if self.base.is_empty() {
+            relative.to_string()
+        } else {
+            format!("{}/{}", self.base, relative)
+        };
+        normalize(&joined).ok_or_else(|| ResourceError::NotFound(resource.to_string()))
+    }
+}
+
+impl ResourceLocator for MemoryResources {
+    fn locate(&self, resource: &ResourceRef) -> Result<Location, ResourceError> {
+        match resource.kind() {
+            ResourceKind::Url => Ok(Location::Url(resource.to_string())),
+            ResourceKind::RelativePath => {
+                Ok(Location::Entry(self.join(resource.as_str(), resource)?))
+            }
+            ResourceKind::AbsolutePath => {
+                let path = resource.absolute_path().unwrap_or_default();
+                match normalize(&path) {
+                    Some(name) if self.entries.contains_key(&name) => Ok(Location::Entry(name)),
+                    _ => Ok(Location::Entry(self.join(resource.file_name(), resource)?)),
+                }
+            }
+        }
+    }
+}
+
+impl ResourceReader for MemoryResources {
+    fn read(&self, resource: &ResourceRef) -> Result<Vec<u8>, ResourceError> {
+        let name = match self.locate(resource)? {
+            Location::Url(url) => url,
+            Location::Entry(name) => name,
+            Location::Path(_) => return Err(ResourceError::Unsupported(resource.to_string())),
+        };
+        self.entries
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| ResourceError::NotFound(resource.to_string()))
+    }
+
+    fn expand(&self, resource: &ResourceRef) -> Result<Vec<ResourceRef>, ResourceError> {
+        if !resource.has_wildcard() {
+            return Ok(vec![resource.clone()]);
+        }
+
+        let (dir, pattern) = resource.split_wildcard();
+        let dir = ResourceRef::new(format!("{}/", dir.unwrap_or(".")));
+        let mut prefix = match self.locate(&dir)? {
+            Location::Entry(name) => name,
+            _ => return Err(ResourceError::Unsupported(resource.to_string())),
+        };
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+
+        Ok(self
+            .entries
+            .keys()
+            .filter_map(|name| name.strip_prefix(&prefix))
+            .filter(|name| !name.contains('/') && wildcard_match(pattern, name))
+            .map(|name| resource.with_file_name(name))
+            .collect())
+    }
+}
+
+/// Collapses `.` and `..` segments, returning `None` if the path escapes the root.
+fn normalize(path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resources(base: &str, names: &[&str]) -> MemoryResources {
+        names
+            .iter()
+            .fold(MemoryResources::new(), |resources, name| {
+                resources.with(name, name.as_bytes())
+            })
+            .with_base(base)
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("a/./b/../c.csv").as_deref(), Some("a/c.csv"));
+        assert_eq!(normalize("a/../../c.csv"), None);
+    }
+
+    #[test]
+    fn test_locate_relative_to_base() {
+        let resources = resources(
+            "model",
+            &["model/main.xmile", "model/data/a.csv", "shared/lib.xml"],
+        );
+        let locate = |s: &str| resources.locate(&s.into()).unwrap();
+        assert_eq!(
+            locate("data/a.csv"),
+            Location::Entry("model/data/a.csv".into())
+        );
+        assert_eq!(
+            locate("../shared/lib.xml"),
+            Location::Entry("shared/lib.xml".into())
+        );
+        assert_eq!(
+            locate("/shared/lib.xml"),
+            Location::Entry("shared/lib.xml".into())
+        );
+        assert_eq!(
+            locate("/abs/path/a.csv"),
+            Location::Entry("model/a.csv".into())
+        );
+        assert_eq!(
+            resources.read(&"data/a.csv".into()).unwrap(),
+            b"model/data/a.csv"
+        );
+        assert!(matches!(
+            resources.read(&"../../escape.csv".into()),
+            Err(ResourceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_urls() {
+        let resources = MemoryResources::new().with("https://example.com/a.csv", "a");
+        assert_eq!(
+            resources.read(&"https://example.com/a.csv".into()).unwrap(),
+            b"a"
+        );
+        assert!(matches!(
+            resources.read(&"https://example.com/b.csv".into()),
+            Err(ResourceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_expand_wildcards() {
+        let resources = resources(
+            "",
+            &[
+                "main.xmile",
+                "macros/a-1.xml",
+                "macros/a-2.xml",
+                "macros/b.xml",
+            ],
+        );
+        assert_eq!(
+            resources.expand(&"macros/a-*.xml".into()).unwrap(),
+            vec![
+                ResourceRef::from("macros/a-1.xml"),
+                ResourceRef::from("macros/a-2.xml")
+            ]
+        );
+        assert_eq!(
+            resources.expand(&"*.xmile".into()).unwrap(),
+            vec![ResourceRef::from("main.xmile")]
+        );
+    }
+}
@@ -0,0 +1,533 @@
+//! # Resources
+//!
+//! Loading of external resources referenced from an XMILE file: the model
+//! image in the header, files listed under `<includes>` (Section 2.11),
+//! submodel files, data import and export connections (Section 2.10) and
+//! graphics frame media.
+//!
+//! Every `resource="…"` attribute is held as a [`ResourceRef`]. A reference
+//! can be a URL, a path relative to the XMILE file or an absolute path
+//! starting with `/` or `file://`. Relative and absolute paths MAY use `*` as
+//! a wildcard in the final path segment.
+//!
+//! A [`ResourceLocator`] resolves references to concrete [`Location`]s and a
+//! [`ResourceReader`] reads them. [`FileReader`] resolves against a base
+//! directory on the local filesystem and [`MemoryResources`] serves resources
+//! from memory, which is mostly useful in tests. URL support is left to the
+//! application, which can plug in its own reader. Applications that must not
+//! block can implement [`AsyncResourceReader`] instead and use the `_async`
+//! loading methods:
+//!
+//! ```rust,no_run
+//! use xmile::resource::FileReader;
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_file("models/main.xmile").unwrap();
+//! let reader = FileReader::for_file("models/main.xmile");
+//! for include in file.resolve_includes(&reader).unwrap() {
+//!     println!("{} ({} bytes)", include.location, include.contents.len());
+//! }
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+mod memory;
+pub use memory::MemoryResources;
+
+#[cfg(feature = "packages")]
+mod package;
+#[cfg(feature = "packages")]
+pub use package::{Package, PackageError};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ResourceError {
+    #[error("Resource not found: {0}")]
+    NotFound(String),
+    #[error("Unsupported resource location: {0}")]
+    Unsupported(String),
+    #[error("Data connection has no resource attribute")]
+    MissingResource,
+    #[error("Resource is not valid UTF-8: {0}")]
+    InvalidUtf8(String),
+    #[error("IO error reading resource {resource}: {source}")]
+    Io {
+        resource: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// The form of a resource reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    /// A URL other than a `file://` URL.
+    Url,
+    /// An absolute path, starting with `/` or `file://`.
+    AbsolutePath,
+    /// A path relative to the referencing XMILE file.
+    RelativePath,
+}
+
+/// The value of a `resource="…"` attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ResourceRef(String);
+
+impl ResourceRef {
+    pub fn new(resource: impl Into<String>) -> Self {
+        ResourceRef(resource.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the form of this reference.
+    pub fn kind(&self) -> ResourceKind {
+        if self.0.starts_with("file://") || self.0.starts_with('/') {
+            ResourceKind::AbsolutePath
+        } else if is_url(&self.0) {
+            ResourceKind::Url
+        } else {
+            ResourceKind::RelativePath
+        }
+    }
+
+    /// Returns the path of an absolute reference, with any `file://` prefix
+    /// replaced by a leading `/`.
+    pub fn absolute_path(&self) -> Option<String> {
+        match self.0.strip_prefix("file://") {
+            Some(path) => Some(format!("/{}", path.trim_start_matches('/'))),
+            None if self.0.starts_with('/') => Some(self.0.clone()),
+            None => None,
+        }
+    }
+
+    /// Returns the final path segment.
+    pub fn file_name(&self) -> &str {
+        self.0.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Returns true if the final path segment contains a `*` wildcard.
+    pub fn has_wildcard(&self) -> bool {
+        self.kind() != ResourceKind::Url && self.file_name().contains('*')
+    }
+
+    /// Splits a wildcard reference into its directory (if any) and pattern.
+    fn split_wildcard(&self) -> (Option<&str>, &str) {
+        match self.0.rsplit_once('/') {
+            Some((dir, pattern)) => (Some(dir), pattern),
+            None => (None, &self.0),
+        }
+    }
+
+    /// Replaces the final path segment with `name`.
+    fn with_file_name(&self, name: &str) -> ResourceRef {
+        match self.split_wildcard() {
+            (Some(dir), _) => ResourceRef(format!("{}/{}", dir, name)),
+            (None, _) => ResourceRef(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ResourceRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for ResourceRef {
+    fn from(resource: &str) -> Self {
+        ResourceRef(resource.to_string())
+    }
+}
+
+impl From<String> for ResourceRef {
+    fn from(resource: String) -> Self {
+        ResourceRef(resource)
+    }
+}
+
+impl AsRef<str> for ResourceRef {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for ResourceRef {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ResourceRef {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// The concrete location a [`ResourceRef`] resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// A URL, to be fetched by an application-provided reader.
+    Url(String),
+    /// A file on the local filesystem.
+    Path(PathBuf),
+    /// An entry in an in-memory store or package, by normalized path.
+    Entry(String),
+}
+
+/// Resolves resource references to concrete locations.
+pub trait ResourceLocator {
+    /// Resolves `resource` to the location it refers to.
+    ///
+    /// Resolution does not check that the location exists.
+    fn locate(&self, resource: &ResourceRef) -> Result<Location, ResourceError>;
+}
+
+/// A resource loaded through a [`ResourceReader`] or [`AsyncResourceReader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    /// The reference the resource was read from, after wildcard expansion.
+    pub location: ResourceRef,
+    /// The raw contents of the resource.
+    pub contents: Vec<u8>,
+}
+
+impl Resource {
+    /// Returns the contents as text.
+    pub fn as_str(&self) -> Result<&str, ResourceError> {
+        std::str::from_utf8(&self.contents)
+            .map_err(|_| ResourceError::InvalidUtf8(self.location.to_string()))
+    }
+}
+
+/// Reads resources by reference.
+pub trait ResourceReader {
+    /// Reads the resource `resource` refers to.
+    fn read(&self, resource: &ResourceRef) -> Result<Vec<u8>, ResourceError>;
+
+    /// Expands wildcards in `resource` to the matching references.
+    ///
+    /// The default implementation performs no expansion.
+    fn expand(&self, resource: &ResourceRef) -> Result<Vec<ResourceRef>, ResourceError> {
+        Ok(vec![resource.clone()])
+    }
+}
+
+/// Reads resources by reference without blocking.
+///
+/// This is the asynchronous counterpart of [`ResourceReader`], for use with
+/// async HTTP clients or file APIs. It is runtime-agnostic: the returned
+/// futures are driven by whichever executor the application uses.
+pub trait AsyncResourceReader {
+    /// Reads the resource `resource` refers to.
+    fn read(
+        &self,
+        resource: &ResourceRef,
+    ) -> impl Future<Output = Result<Vec<u8>, ResourceError>> + Send;
+
+    /// Expands wildcards in `resource` to the matching references.
+    ///
+    /// The default implementation performs no expansion.
+    fn expand(
+        &self,
+        resource: &ResourceRef,
+    ) -> impl Future<Output = Result<Vec<ResourceRef>, ResourceError>> + Send {
+        let expanded = vec![resource.clone()];
+        async move { Ok(expanded) }
+    }
+}
+
+/// Adapts a [`ResourceReader`] to [`AsyncResourceReader`].
+///
+/// Reads are performed synchronously when the future is created, so this is
+/// only suitable for readers that do not block for long, such as in-memory
+/// readers or local files in tests.
+#[derive(Debug, Clone)]
+pub struct Blocking<R>(pub R);
+
+impl<R: ResourceReader> AsyncResourceReader for Blocking<R> {
+    fn read(
+        &self,
+        resource: &ResourceRef,
+    ) -> impl Future<Output = Result<Vec<u8>, ResourceError>> + Send {
+        let result = self.0.read(resource);
+        async move { result }
+    }
+
+    fn expand(
+        &self,
+        resource: &ResourceRef,
+    ) -> impl Future<Output = Result<Vec<ResourceRef>, ResourceError>> + Send {
+        let result = self.0.expand(resource);
+        async move { result }
+    }
+}
+
+/// Reads resources from the local filesystem.
+///
+/// Relative paths are resolved against a base directory, normally the
+/// directory containing the XMILE file. Absolute paths that do not resolve
+/// to a file fall back to their final file name, searched for relative to the
+/// base directory. URLs are located but cannot be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReader {
+    base_dir: PathBuf,
+}
+
+impl FileReader {
+    /// Creates a reader resolving relative paths against `base_dir`.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        FileReader {
+            base_dir: base_dir.into(),
+        }
+    }
+
+    /// Creates a reader for resources referenced by the XMILE file at `path`.
+    pub fn for_file(path: impl AsRef<Path>) -> Self {
+        let base_dir = path
+            .as_ref()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        FileReader { base_dir }
+    }
+
+    /// The directory relative paths are resolved against.
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    fn locate_path(&self, resource: &ResourceRef) -> Result<PathBuf, ResourceError> {
+        match self.locate(resource)? {
+            Location::Path(path) => Ok(path),
+            _ => Err(ResourceError::Unsupported(resource.to_string())),
+        }
+    }
+}
+
+impl ResourceLocator for FileReader {
+    fn locate(&self, resource: &ResourceRef) -> Result<Location, ResourceError> {
+        match resource.kind() {
+            ResourceKind::Url => Ok(Location::Url(resource.to_string())),
+            ResourceKind::RelativePath => Ok(Location::Path(self.base_dir.join(resource.as_str()))),
+            ResourceKind::AbsolutePath => {
+                let path = PathBuf::from(resource.absolute_path().unwrap_or_default());
+                if path.exists() {
+                    return Ok(Location::Path(path));
+                }
+                match path.file_name() {
+                    Some(name) => Ok(Location::Path(self.base_dir.join(name))),
+                    None => Err(ResourceError::NotFound(resource.to_string())),
+                }
+            }
+        }
+    }
+}
+
+impl ResourceReader for FileReader {
+    fn read(&self, resource: &ResourceRef) -> Result<Vec<u8>, ResourceError> {
+        let path = self.locate_path(resource)?;
+        fs::read(&path).map_err(|err| io_error(resource, err))
+    }
+
+    fn expand(&self, resource: &ResourceRef) -> Result<Vec<ResourceRef>, ResourceError> {
+        if !resource.has_wildcard() {
+            return Ok(vec![resource.clone()]);
+        }
+
+        let (dir, pattern) = resource.split_wildcard();
+        let dir_path = match dir {
+            Some(dir) => self.locate_path(&ResourceRef(format!("{}/", dir)))?,
+            None => self.base_dir.clone(),
+        };
+        let entries = fs::read_dir(&dir_path).map_err(|err| io_error(resource, err))?;
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|err| io_error(resource, err))?;
+            if !entry.path().is_file() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str()
+                && wildcard_match(pattern, name)
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+
+        Ok(names
+            .iter()
+            .map(|name| resource.with_file_name(name))
+            .collect())
+    }
+}
+
+/// Loads every resource matching `resource`, expanding wildcards.
+pub fn load_all(
+    reader: &impl ResourceReader,
+    resource: &ResourceRef,
+) -> Result<Vec<Resource>, ResourceError> {
+    reader
+        .expand(resource)?
+        .into_iter()
+        .map(|location| {
+            let contents = reader.read(&location)?;
+            Ok(Resource { location, contents })
+        })
+        .collect()
+}
+
+/// Loads every resource matching `resource`, expanding wildcards.
+pub async fn load_all_async(
+    reader: &impl AsyncResourceReader,
+    resource: &ResourceRef,
+) -> Result<Vec<Resource>, ResourceError> {
+    let mut resources = Vec::new();
+    for location in reader.expand(resource).await? {
+        let contents = reader.read(&location).await?;
+        resources.push(Resource { location, contents });
+    }
+    Ok(resources)
+}
+
+/// Returns true if `location` is a URL other than a `file://` URL.
+fn is_url(location: &str) -> bool {
+    match location.split_once("://") {
+        Some((scheme, _)) => {
+            !scheme.is_empty()
+                && scheme != "file"
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+fn io_error(resource: &ResourceRef, err: io::Error) -> ResourceError {
+    if err.kind() == io::ErrorKind::NotFound {
+        ResourceError::NotFound(resource.to_string())
+    } else {
+        ResourceError::Io {
+            resource: resource.to_string(),
+            source: err,
+        }
+    }
+}
+
+/// Matches `name` against a pattern where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcard: the prefix must be the whole name.
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_kind() {
+        let kind = |s: &str| ResourceRef::from(s).kind();
+        assert_eq!(
+            kind("http://systemdynamics.org/xmile/macros/standard-1.0.xml"),
+            ResourceKind::Url
+        );
+        assert_eq!(kind("https://example.com/data.csv"), ResourceKind::Url);
+        assert_eq!(
+            kind("file://library/my-macro-library.xml"),
+            ResourceKind::AbsolutePath
+        );
+        assert_eq!(
+            kind("/library/my-macro-library.xml"),
+            ResourceKind::AbsolutePath
+        );
+        assert_eq!(
+            kind("macros/my-macro-library.xml"),
+            ResourceKind::RelativePath
+        );
+    }
+
+    #[test]
+    fn test_resource_ref_paths() {
+        let resource = ResourceRef::from("file://library/my-macro-library.xml");
+        assert_eq!(
+            resource.absolute_path().as_deref(),
+            Some("/library/my-macro-library.xml")
+        );
+        assert_eq!(resource.file_name(), "my-macro-library.xml");
+        assert!(!resource.has_wildcard());
+        assert!(ResourceRef::from("macros/supplychain-*.xml").has_wildcard());
+        assert_eq!(ResourceRef::from("macros/lib.xml").absolute_path(), None);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "anything.xml"));
+        assert!(wildcard_match("supplychain-*.xml", "supplychain-1.0.xml"));
+        assert!(wildcard_match("*-*.xml", "standard-1.0.xml"));
+        assert!(!wildcard_match(
+            "supplychain-*.xml",
+            "supplychain-1.0.xmile"
+        ));
+        assert!(!wildcard_match("supplychain-*.xml", "standard-1.0.xml"));
+        assert!(wildcard_match("exact.xml", "exact.xml"));
+        assert!(!wildcard_match("exact.xml", "exact.xml.bak"));
+    }
+
+    #[test]
+    fn test_locate_paths() {
+        let reader = FileReader::new("/models");
+        assert_eq!(
+            reader.locate(&"macros/lib.xml".into()).unwrap(),
+            Location::Path(PathBuf::from("/models/macros/lib.xml"))
+        );
+        // Absolute paths that do not exist fall back to the file name.
+        assert_eq!(
+            reader
+                .locate(&"file://no-such-library/lib.xml".into())
+                .unwrap(),
+            Location::Path(PathBuf::from("/models/lib.xml"))
+        );
+        assert_eq!(
+            reader.locate(&"http://example.com/lib.xml".into()).unwrap(),
+            Location::Url("http://example.com/lib.xml".to_string())
+        );
+        assert!(matches!(
+            reader.read(&"http://example.com/lib.xml".into()),
+            Err(ResourceError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_for_file_uses_parent_directory() {
+        let reader = FileReader::for_file("/models/main.xmile");
+        assert_eq!(reader.base_dir(), Path::new("/models"));
+    }
+}
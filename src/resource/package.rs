@@ -0,0 +1,255 @@
+//! Packaged models: zip archives holding an XMILE document together with the
+//! resources it references, and gzip-compressed XMILE documents.
+//!
+//! A [`Package`] holds the entries of a zip archive in [`MemoryResources`]
+//! based at the directory of the main document, so includes and data imports inside a package load the same way
+//! as they do from the filesystem.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use thiserror::Error;
+
+use super::{
+    Location, MemoryResources, ResourceError, ResourceKind, ResourceLocator, ResourceReader,
+    ResourceRef,
+};
+use crate::xml::{ParseError, XmileFile};
+
+/// File extensions recognised as XMILE documents, in order of preference.
+const DOCUMENT_EXTENSIONS: [&str; 4] = ["xmile", "stmx", "itmx", "xml"];
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PackageError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Package contains no XMILE document")]
+    NoMainDocument,
+    #[error("Package entry not found: {0}")]
+    MissingEntry(String),
+    #[error("Package entry is not valid UTF-8: {0}")]
+    InvalidUtf8(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// The contents of a zip-packaged model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    resources: MemoryResources,
+    main: String,
+}
+
+impl Package {
+    /// Opens the zip archive at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PackageError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a zip archive from `reader`.
+    ///
+    /// The main document is the XMILE document closest to the root of the
+    /// archive, preferring `.xmile` files over `.stmx`, `.itmx` and `.xml`.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Self, PackageError> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+        let mut resources = MemoryResources::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            if entry.is_dir() {
+                continue;
+            }
+            let mut contents = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut contents)?;
+            resources.insert(entry.name(), contents);
+        }
+
+        let main = find_main_document(resources.names()).ok_or(PackageError::NoMainDocument)?;
+        Ok(Package::new(resources, main))
+    }
+
+    fn new(resources: MemoryResources, main: String) -> Self {
+        let base = main.rsplit_once('/').map_or("", |(dir, _)| dir);
+        Package {
+            resources: resources.with_base(base),
+            main,
+        }
+    }
+
+    /// Uses the entry `name` as the main document instead of the detected one.
+    pub fn with_main(self, name: &str) -> Result<Self, PackageError> {
+        if !self.resources.contains(name) {
+            return Err(PackageError::MissingEntry(name.to_string()));
+        }
+        Ok(Package::new(self.resources, name.to_string()))
+    }
+
+    /// The name of the main document entry.
+    pub fn main_name(&self) -> &str {
+        &self.main
+    }
+
+    /// The text of the main document.
+    pub fn main_document(&self) -> Result<&str, PackageError> {
+        let contents = self.resources.get(&self.main).unwrap_or_default();
+        std::str::from_utf8(contents).map_err(|_| PackageError::InvalidUtf8(self.main.clone()))
+    }
+
+    /// Parses the main document.
+    pub fn parse(&self) -> Result<XmileFile, PackageError> {
+        Ok(XmileFile::from_str(self.main_document()?)?)
+    }
+
+    /// The names of all file entries, sorted by path.
+    pub fn entry_names(&self) -> impl Iterator<Item = &str> {
+        self.resources.names()
+    }
+
+    /// The contents of the entry `name`, if present.
+    pub fn entry(&self, name: &str) -> Option<&[u8]> {
+        self.resources.get(name)
+    }
+
+    /// The entries of the package as resources.
+    pub fn resources(&self) -> &MemoryResources {
+        &self.resources
+    }
+
+    /// Resolves a resource reference to an entry name.
+    ///
+    /// Relative references are resolved against the directory of the main
+    /// document. Absolute paths fall back to their final file name, as they
+    /// would on the filesystem when the absolute path does not exist.
+    pub fn resolve(&self, resource: &ResourceRef) -> Result<String, ResourceError> {
+        match self.locate(resource)? {
+            Location::Entry(name) => Ok(name),
+            _ => Err(ResourceError::Unsupported(resource.to_string())),
+        }
+    }
+}
+
+impl ResourceLocator for Package {
+    fn locate(&self, resource: &ResourceRef) -> Result<Location, ResourceError> {
+        self.resources.locate(resource)
+    }
+}
+
+impl ResourceReader for Package {
+    fn read(&self, resource: &ResourceRef) -> Result<Vec<u8>, ResourceError> {
+        if resource.kind() == ResourceKind::Url {
+            return Err(ResourceError::Unsupported(resource.to_string()));
+        }
+        self.resources.read(resource)
+    }
+
+    fn expand(&self, resource: &ResourceRef) -> Result<Vec<ResourceRef>, ResourceError> {
+        self.resources.expand(resource)
+    }
+}
+
+impl XmileFile {
+    /// Parse the main document of the zip-packaged model at `path`.
+    ///
+    /// The returned [`Package`] serves the other entries of the archive as
+    /// resources, e.g. for [`XmileFile::resolve_includes`] or
+    /// [`DataImport::fetch`](crate::data::DataImport::fetch).
+    pub fn from_zip<P: AsRef<Path>>(path: P) -> Result<(Self, Package), PackageError> {
+        let package = Package::open(path)?;
+        let file = package.parse()?;
+        Ok((file, package))
+    }
+
+    /// Parse a gzip-compressed XMILE file from a reader.
+    pub fn from_gzip_reader<R: Read>(reader: R) -> Result<Self, ParseError> {
+        Self::from_reader(GzDecoder::new(reader))
+    }
+
+    /// Parse a gzip-compressed XMILE file from a file path.
+    pub fn from_gzip<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        Self::from_gzip_reader(BufReader::new(File::open(path)?))
+    }
+}
+
+fn find_main_document<'a>(names: impl Iterator<Item = &'a str>) -> Option<String> {
+    names
+        .filter_map(|name| {
+            let (_, extension) = name.rsplit_once('.')?;
+            let rank = DOCUMENT_EXTENSIONS
+                .iter()
+                .position(|ext| extension.eq_ignore_ascii_case(ext))?;
+            // Skip metadata written by macOS archivers.
+            if name.starts_with("__MACOSX/") {
+                return None;
+            }
+            Some((name.matches('/').count(), rank, name))
+        })
+        .min()
+        .map(|(_, _, name)| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(main: &str, names: &[&str]) -> Package {
+        let resources = names
+            .iter()
+            .fold(MemoryResources::new(), |resources, name| {
+                resources.with(name, name.as_bytes())
+            });
+        Package::new(resources, main.to_string())
+    }
+
+    #[test]
+    fn test_find_main_document() {
+        let names = ["model/data.xml", "model/main.xmile", "readme.txt"];
+        assert_eq!(
+            find_main_document(names.into_iter()).as_deref(),
+            Some("model/main.xmile")
+        );
+
+        let names = ["lib/macros.xmile", "main.stmx"];
+        assert_eq!(
+            find_main_document(names.into_iter()).as_deref(),
+            Some("main.stmx")
+        );
+
+        assert_eq!(find_main_document(["data.csv"].into_iter()), None);
+    }
+
+    #[test]
+    fn test_resolve_relative_to_main() {
+        let package = package(
+            "model/main.xmile",
+            &["model/main.xmile", "model/data/a.csv", "shared/lib.xml"],
+        );
+        let resolve = |s: &str| package.resolve(&s.into()).unwrap();
+        assert_eq!(resolve("data/a.csv"), "model/data/a.csv");
+        assert_eq!(resolve("../shared/lib.xml"), "shared/lib.xml");
+        assert_eq!(resolve("/abs/path/a.csv"), "model/a.csv");
+        assert_eq!(
+            package.read(&"data/a.csv".into()).unwrap(),
+            b"model/data/a.csv"
+        );
+        assert!(matches!(
+            package.read(&"https://example.com/a.csv".into()),
+            Err(ResourceError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_with_main_rebases_resources() {
+        let package = package(
+            "a/main.xmile",
+            &["a/main.xmile", "b/other.xmile", "b/x.csv"],
+        )
+        .with_main("b/other.xmile")
+        .unwrap();
+        assert_eq!(package.main_name(), "b/other.xmile");
+        assert_eq!(package.read(&"x.csv".into()).unwrap(), b"b/x.csv");
+    }
+}
@@ -0,0 +1,399 @@
+//! Scenario files: declarative parameter, graphical function and sim_specs
+//! overrides for batch experiments.
+//!
+//! A scenario is a small TOML document that can be shared alongside a model
+//! and applied to it before a run:
+//!
+//! ```toml
+//! name = "High growth"
+//! description = "Doubles the birth rate and runs for longer"
+//!
+//! [sim_specs]
+//! stop = 200
+//! dt = 0.25
+//!
+//! [parameters]
+//! "birth rate" = 0.2
+//! "initial population" = "1000 * scale"
+//! "Hares.area" = 500
+//!
+//! [[graphical_functions]]
+//! name = "effect of crowding"
+//! xscale = [0, 1]
+//! ypts = [1, 0.8, 0.5, 0.2, 0]
+//! ```
+//!
+//! Parameters replace the equation of an auxiliary or flow, or the initial
+//! value of a stock, with a number or an equation. Names are looked up in the
+//! root model unless qualified with the name of a submodel.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+use crate::xml::XmileFile;
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+use crate::{Expression, GraphicalFunctionData, Identifier, NumericConstant};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ScenarioError {
+    #[error("IO error reading scenario {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid scenario: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Failed to write scenario: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String),
+    #[error("Variable '{0}' has no equation to override")]
+    NotOverridable(String),
+    #[error("Unknown graphical function: {0}")]
+    UnknownGraphicalFunction(String),
+    #[error("Invalid graphical function '{name}': {reason}")]
+    InvalidGraphicalFunction { name: String, reason: String },
+    #[error("Scenario changes sim_specs, but the file has none and no start and stop are given")]
+    MissingSimSpecs,
+    #[error("Error resolving function calls: {0}")]
+    Resolve(String),
+}
+
+/// A named set of overrides applied to a model before a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// A short name for the scenario.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// A description of what the scenario explores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Changes to the simulation specifications.
+    #[serde(default, skip_serializing_if = "SimSpecsOverrides::is_empty")]
+    pub sim_specs: SimSpecsOverrides,
+    /// New values for parameters, by (optionally qualified) variable name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, ParameterValue>,
+    /// Replacement data for graphical functions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graphical_functions: Vec<GraphicalFunctionOverride>,
+}
+
+/// Changes to the `<sim_specs>` of a file. Unset fields are left as they are.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimSpecsOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_units: Option<String>,
+}
+
+impl SimSpecsOverrides {
+    /// Returns true if no field is overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == SimSpecsOverrides::default()
+    }
+}
+
+/// The new value of a parameter: a number or an XMILE equation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterValue {
+    Number(f64),
+    Equation(Expression),
+}
+
+impl ParameterValue {
+    /// Returns the value as an expression.
+    pub fn to_expression(&self) -> Expression {
+        match self {
+            ParameterValue::Number(value) => Expression::Constant(NumericConstant(*value)),
+            ParameterValue::Equation(expression) => expression.clone(),
+        }
+    }
+}
+
+/// Replacement points for a graphical function.
+///
+/// Points are given either as `ypts` spread evenly over `xscale`, or as
+/// matching `xpts` and `ypts`. The function keeps its interpolation type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicalFunctionOverride {
+    /// The (optionally qualified) name of the graphical function.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xscale: Option<[f64; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xpts: Option<Vec<f64>>,
+    pub ypts: Vec<f64>,
+}
+
+impl GraphicalFunctionOverride {
+    /// Converts the points to graphical function data.
+    pub fn to_data(&self) -> Result<GraphicalFunctionData, ScenarioError> {
+        let invalid = |reason: &str| ScenarioError::InvalidGraphicalFunction {
+            name: self.name.clone(),
+            reason: reason.to_string(),
+        };
+        if self.ypts.is_empty() {
+            return Err(invalid("ypts cannot be empty"));
+        }
+        match (&self.xscale, &self.xpts) {
+            (Some([min, max]), None) => {
+                if min.is_nan() || max.is_nan() || min >= max {
+                    return Err(invalid("xscale min must be less than max"));
+                }
+                Ok(GraphicalFunctionData::uniform_scale(
+                    (*min, *max),
+                    self.ypts.clone(),
+                    None,
+                ))
+            }
+            (None, Some(xpts)) => {
+                if xpts.len() != self.ypts.len() {
+                    return Err(invalid("xpts and ypts must have the same length"));
+                }
+                if xpts.windows(2).any(|pair| pair[0] > pair[1]) {
+                    return Err(invalid("xpts must be in ascending order"));
+                }
+                Ok(GraphicalFunctionData::xy_pairs(
+                    xpts.clone(),
+                    self.ypts.clone(),
+                    None,
+                ))
+            }
+            (Some(_), Some(_)) => Err(invalid("cannot have both xscale and xpts")),
+            (None, None) => Err(invalid("either xscale or xpts must be provided")),
+        }
+    }
+}
+
+impl std::str::FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+impl Scenario {
+    /// Loads a scenario from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| ScenarioError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        contents.parse()
+    }
+
+    /// Writes the scenario as TOML.
+    pub fn to_toml(&self) -> Result<String, ScenarioError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Applies the scenario's overrides to `file`.
+    ///
+    /// Either every override is applied or, on error, `file` is left
+    /// unchanged.
+    pub fn apply(&self, file: &mut XmileFile) -> Result<(), ScenarioError> {
+        let mut updated = file.clone();
+        self.apply_sim_specs(&mut updated)?;
+
+        for (name, value) in &self.parameters {
+            let (model, local) = find_model(&mut updated, name);
+            let model = model.ok_or_else(|| ScenarioError::UnknownVariable(name.clone()))?;
+            let variable = find_variable(model, local)
+                .ok_or_else(|| ScenarioError::UnknownVariable(name.clone()))?;
+            let expression = value.to_expression();
+            match variable {
+                Variable::Auxiliary(aux) => aux.equation = expression,
+                Variable::Flow(flow) => flow.equation = Some(expression),
+                Variable::Stock(stock) => match stock.as_mut() {
+                    Stock::Basic(s) => s.initial_equation = expression,
+                    Stock::Conveyor(s) => s.initial_equation = expression,
+                    Stock::Queue(s) => s.initial_equation = expression,
+                },
+                _ => return Err(ScenarioError::NotOverridable(name.clone())),
+            }
+        }
+
+        for replacement in &self.graphical_functions {
+            let data = replacement.to_data()?;
+            let unknown = || ScenarioError::UnknownGraphicalFunction(replacement.name.clone());
+            let (model, local) = find_model(&mut updated, &replacement.name);
+            let variable = find_variable(model.ok_or_else(unknown)?, local).ok_or_else(unknown)?;
+            match variable {
+                Variable::GraphicalFunction(gf) => gf.data = data,
+                _ => return Err(unknown()),
+            }
+        }
+
+        if !self.parameters.is_empty() {
+            updated
+                .resolve_all_expressions()
+                .map_err(|errors| ScenarioError::Resolve(errors.join("; ")))?;
+        }
+        *file = updated;
+        Ok(())
+    }
+
+    fn apply_sim_specs(&self, file: &mut XmileFile) -> Result<(), ScenarioError> {
+        let overrides = &self.sim_specs;
+        if overrides.is_empty() {
+            return Ok(());
+        }
+
+        let sim_specs = match &mut file.sim_specs {
+            Some(sim_specs) => sim_specs,
+            None => match (overrides.start, overrides.stop) {
+                (Some(start), Some(stop)) => file.sim_specs.insert(crate::specs::SimulationSpecs {
+                    start,
+                    stop,
+                    dt: None,
+                    method: None,
+                    time_units: None,
+                    pause: None,
+                    run_by: None,
+                }),
+                _ => return Err(ScenarioError::MissingSimSpecs),
+            },
+        };
+        if let Some(start) = overrides.start {
+            sim_specs.start = start;
+        }
+        if let Some(stop) = overrides.stop {
+            sim_specs.stop = stop;
+        }
+        if let Some(dt) = overrides.dt {
+            sim_specs.dt = Some(dt);
+        }
+        if let Some(method) = &overrides.method {
+            sim_specs.method = Some(method.clone());
+        }
+        if let Some(time_units) = &overrides.time_units {
+            sim_specs.time_units = Some(time_units.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Splits a possibly qualified name into its model and local name.
+///
+/// A leading qualifier is only treated as a model name when a model with
+/// that name exists; otherwise the whole name is looked up in the root model.
+fn find_model<'a, 'n>(file: &'a mut XmileFile, name: &'n str) -> (Option<&'a mut Model>, &'n str) {
+    let qualified = name.split_once('.').and_then(|(model, local)| {
+        let index = file
+            .models
+            .iter()
+            .position(|m| m.name.as_deref() == Some(model))?;
+        Some((index, local))
+    });
+    match qualified {
+        Some((index, local)) => (file.models.get_mut(index), local),
+        None => {
+            let root = file
+                .models
+                .iter()
+                .position(|m| m.name.is_none())
+                .unwrap_or(0);
+            (file.models.get_mut(root), name)
+        }
+    }
+}
+
+fn find_variable<'a>(model: &'a mut Model, name: &str) -> Option<&'a mut Variable> {
+    let name = Identifier::parse_from_attribute(name).ok()?;
+    model
+        .variables
+        .variables
+        .iter_mut()
+        .find(|variable| get_variable_name(variable) == Some(&name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario: Scenario = r#"
+            name = "High growth"
+
+            [sim_specs]
+            stop = 200
+            dt = 0.25
+
+            [parameters]
+            "birth rate" = 0.2
+            "initial population" = "1000 * scale"
+            area = 5
+
+            [[graphical_functions]]
+            name = "effect of crowding"
+            xpts = [0, 0.5, 1]
+            ypts = [1, 0.5, 0]
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(scenario.name.as_deref(), Some("High growth"));
+        assert_eq!(scenario.sim_specs.stop, Some(200.0));
+        assert_eq!(scenario.sim_specs.start, None);
+        assert_eq!(
+            scenario.parameters["birth rate"],
+            ParameterValue::Number(0.2)
+        );
+        assert_eq!(scenario.parameters["area"], ParameterValue::Number(5.0));
+        assert!(matches!(
+            scenario.parameters["initial population"],
+            ParameterValue::Equation(Expression::Multiply(_, _))
+        ));
+        assert!(scenario.graphical_functions[0].to_data().is_ok());
+
+        let reparsed: Scenario = scenario.to_toml().unwrap().parse().unwrap();
+        assert_eq!(reparsed, scenario);
+    }
+
+    #[test]
+    fn test_invalid_graphical_function() {
+        let replacement = GraphicalFunctionOverride {
+            name: "f".to_string(),
+            xscale: Some([0.0, 1.0]),
+            xpts: Some(vec![0.0, 1.0]),
+            ypts: vec![0.0, 1.0],
+        };
+        assert!(matches!(
+            replacement.to_data(),
+            Err(ScenarioError::InvalidGraphicalFunction { .. })
+        ));
+
+        let replacement = GraphicalFunctionOverride {
+            xscale: None,
+            xpts: Some(vec![1.0, 0.0]),
+            ..replacement
+        };
+        assert!(replacement.to_data().is_err());
+
+        assert!(matches!(
+            "parameters = 3".parse::<Scenario>(),
+            Err(ScenarioError::Parse(_))
+        ));
+    }
+}
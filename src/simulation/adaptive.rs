@@ -0,0 +1,168 @@
+//! Adaptive steps with the Runge-Kutta-Fehlberg method.
+//!
+//! Each step estimates the stocks with fourth- and fifth-order formulas
+//! sharing six evaluations of the net flows, and takes their difference as
+//! the error of the step. Steps whose error is within the tolerance are
+//! kept, moving on with the fifth-order estimate; either way the next step
+//! is sized from the error. Values at save times falling within a step are
+//! interpolated from the stocks and net flows at its ends with cubic
+//! Hermite polynomials, so saving never shortens a step.
+
+use crate::equation::evaluate::Scalar;
+
+use super::{Save, SimulationError, Simulator};
+
+/// The times of the stages, as fractions of the step.
+const C: [f64; 6] = [0.0, 1.0 / 4.0, 3.0 / 8.0, 12.0 / 13.0, 1.0, 1.0 / 2.0];
+
+/// The weights of the earlier stages' net flows in each stage.
+const A: [&[f64]; 6] = [
+    &[],
+    &[1.0 / 4.0],
+    &[3.0 / 32.0, 9.0 / 32.0],
+    &[1932.0 / 2197.0, -7200.0 / 2197.0, 7296.0 / 2197.0],
+    &[439.0 / 216.0, -8.0, 3680.0 / 513.0, -845.0 / 4104.0],
+    &[
+        -8.0 / 27.0,
+        2.0,
+        -3544.0 / 2565.0,
+        1859.0 / 4104.0,
+        -11.0 / 40.0,
+    ],
+];
+
+/// The weights of the fifth-order estimate.
+const B: [f64; 6] = [
+    16.0 / 135.0,
+    0.0,
+    6656.0 / 12825.0,
+    28561.0 / 56430.0,
+    -9.0 / 50.0,
+    2.0 / 55.0,
+];
+
+/// The fifth-order weights less the fourth-order ones.
+const E: [f64; 6] = [
+    1.0 / 360.0,
+    0.0,
+    -128.0 / 4275.0,
+    -2197.0 / 75240.0,
+    1.0 / 50.0,
+    2.0 / 55.0,
+];
+
+/// The most one step may grow or shrink the next by.
+const MAX_GROWTH: f64 = 5.0;
+const MAX_SHRINK: f64 = 0.2;
+
+impl Simulator {
+    /// Runs from the initial `values` to the stop time with adaptive steps,
+    /// passing each step of DT to `save`. Returns the sizes of the steps
+    /// kept.
+    pub(super) fn run_adaptive<S: Scalar>(
+        &self,
+        values: &mut [S],
+        save: &mut Save<S>,
+    ) -> Result<Vec<f64>, SimulationError> {
+        let stop = self.time(self.steps);
+        // Steps this short are kept whatever their error, so that a run
+        // always finishes
+        let min_step = self.dt * 1e-9;
+        let mut sizes = Vec::new();
+        let mut time = self.start;
+        let mut h = self.dt;
+        let mut stocks: Vec<S> = self.stocks.iter().map(|&slot| values[slot]).collect();
+        self.compute(values, time);
+        save(time, values)?;
+        let mut rates = self.net_flows(values, time);
+
+        let mut next = 1;
+        while next <= self.steps {
+            h = h.min(stop - time);
+            let mut k = vec![rates.clone()];
+            for (weights, c) in A.iter().zip(C).skip(1) {
+                let weighted = combine(weights, &k);
+                k.push(self.stage(values, &stocks, &weighted, h, time + c * h));
+            }
+            let increment = combine(&B, &k);
+            let error = combine(&E, &k)
+                .iter()
+                .zip(&stocks)
+                .zip(&increment)
+                .map(|((error, start), rate)| {
+                    let (start, rate) = (start.value(), rate.value());
+                    let scale = start.abs().max((start + rate * h).abs()).max(1.0);
+                    (error.value() * h).abs() / (self.tolerance * scale)
+                })
+                .fold(0.0, f64::max);
+
+            if error <= 1.0 || h <= min_step {
+                let end = time + h;
+                let ends: Vec<S> = stocks
+                    .iter()
+                    .zip(&increment)
+                    .map(|(&start, &rate)| start + rate * S::constant(h))
+                    .collect();
+                for (&slot, &value) in self.stocks.iter().zip(&ends) {
+                    values[slot] = value;
+                }
+                self.compute(values, end);
+                let end_rates = self.net_flows(values, end);
+
+                while next <= self.steps && self.time(next) <= end + min_step {
+                    let at = self.time(next);
+                    let theta = (at - time) / h;
+                    let mut sample = values.to_vec();
+                    for (stock, &slot) in self.stocks.iter().enumerate() {
+                        sample[slot] = hermite(
+                            theta,
+                            h,
+                            (stocks[stock], rates[stock]),
+                            (ends[stock], end_rates[stock]),
+                        );
+                    }
+                    self.compute(&mut sample, at);
+                    save(at, &sample)?;
+                    next += 1;
+                }
+
+                sizes.push(h);
+                time = end;
+                stocks = ends;
+                rates = end_rates;
+            }
+            let growth = if error > 0.0 {
+                0.9 * error.powf(-0.2)
+            } else {
+                MAX_GROWTH
+            };
+            h *= growth.clamp(MAX_SHRINK, MAX_GROWTH);
+        }
+        Ok(sizes)
+    }
+}
+
+/// The weighted sums of each stock's net flows over the stages.
+fn combine<S: Scalar>(weights: &[f64], stages: &[Vec<S>]) -> Vec<S> {
+    (0..stages[0].len())
+        .map(|stock| {
+            weights
+                .iter()
+                .zip(stages)
+                .fold(S::constant(0.0), |sum, (&weight, rates)| {
+                    sum + S::constant(weight) * rates[stock]
+                })
+        })
+        .collect()
+}
+
+/// The value a fraction `theta` of the way through a step of `h` between
+/// two (value, slope) pairs, on the cubic through both.
+fn hermite<S: Scalar>(theta: f64, h: f64, (y0, f0): (S, S), (y1, f1): (S, S)) -> S {
+    let (t2, t3) = (theta * theta, theta * theta * theta);
+    let weight = S::constant;
+    weight(2.0 * t3 - 3.0 * t2 + 1.0) * y0
+        + weight((t3 - 2.0 * t2 + theta) * h) * f0
+        + weight(3.0 * t2 - 2.0 * t3) * y1
+        + weight((t3 - t2) * h) * f1
+}
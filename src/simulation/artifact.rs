@@ -0,0 +1,515 @@
+//! Prepared simulators saved as artifacts.
+//!
+//! Preparing a simulator means parsing the document, resolving every
+//! equation, expanding stateful builtins into hidden stocks, and ordering
+//! the equations. [`Simulator::to_artifact`] saves the result, and
+//! [`Simulator::from_artifact`] loads it again without doing any of that,
+//! for services that start often and always run the same model.
+//!
+//! An artifact starts with a header naming the version of the crate that
+//! wrote it and a hash of the source document. Loading refuses artifacts
+//! from another version, since the prepared form may change between
+//! versions, and artifacts prepared from a document other than the one
+//! given, so an edited model is never run from a stale artifact. The rest
+//! is the simulator, encoded with `bincode`.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Identifier;
+use crate::equation::expression::function::FunctionTarget;
+use crate::equation::identifier::IdentifierOptions;
+use crate::equation::{Expression, IdentifierError, NumericConstant};
+use crate::model::vars::gf::GraphicalFunctionTable;
+use crate::specs::{IntegrationMethod, SimulationSpecs};
+
+use super::time::Ratio;
+use super::{Equation, Simulator, Slot};
+
+/// The bytes every artifact starts with.
+const MAGIC: &[u8; 8] = b"XMILESIM";
+
+/// The version of the crate, which artifacts must have been written by.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// An error saving or loading an artifact.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ArtifactError {
+    #[error("Not a simulator artifact")]
+    NotAnArtifact,
+    #[error("The artifact was written by xmile {0}, not {VERSION}")]
+    Version(String),
+    #[error("The artifact was prepared from a different document")]
+    Stale,
+    #[error("Invalid artifact: {0}")]
+    Encoding(#[from] bincode::Error),
+    #[error("Invalid artifact: {0}")]
+    Identifier(#[from] IdentifierError),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: String,
+    /// The FNV-1a hash of the source document.
+    source: u64,
+}
+
+/// A [`Simulator`], with its equations and names in forms that can be
+/// encoded.
+#[derive(Serialize, Deserialize)]
+struct Prepared {
+    specs: Specs,
+    start: f64,
+    stop: f64,
+    dt: f64,
+    steps: usize,
+    method: IntegrationMethod,
+    ratio: Option<Ratio>,
+    tolerance: f64,
+    slots: Vec<PreparedSlot>,
+    names: Vec<String>,
+    index: Vec<(String, usize)>,
+    functions: Vec<GraphicalFunctionTable>,
+    function_index: Vec<(String, usize)>,
+    stocks: Vec<usize>,
+    initial_order: Vec<usize>,
+    order: Vec<usize>,
+}
+
+/// The fields of [`SimulationSpecs`], whose own serde implementation
+/// follows the XML form.
+#[derive(Serialize, Deserialize)]
+struct Specs {
+    start: f64,
+    stop: f64,
+    dt: Option<f64>,
+    method: Option<String>,
+    time_units: Option<String>,
+    pause: Option<f64>,
+    run_by: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PreparedSlot {
+    name: String,
+    equation: PreparedEquation,
+    non_negative: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+enum PreparedEquation {
+    Stock { initial: Node, net: Node },
+    Expression(Node),
+    Lookup(usize, Node),
+}
+
+/// An [`Expression`], whose own serde implementation writes equation text.
+/// Equations built for hidden stocks have no parentheses to keep their
+/// precedence in text, so they are kept as trees.
+#[derive(Serialize, Deserialize)]
+enum Node {
+    Constant(f64),
+    Subscript(String, Vec<Node>),
+    Wildcard,
+    Range(Box<Node>, Box<Node>),
+    Transpose(Box<Node>),
+    Parentheses(Box<Node>),
+    Exponentiation(Box<Node>, Box<Node>),
+    UnaryPlus(Box<Node>),
+    UnaryMinus(Box<Node>),
+    Not(Box<Node>),
+    Multiply(Box<Node>, Box<Node>),
+    Divide(Box<Node>, Box<Node>),
+    Modulo(Box<Node>, Box<Node>),
+    Add(Box<Node>, Box<Node>),
+    Subtract(Box<Node>, Box<Node>),
+    LessThan(Box<Node>, Box<Node>),
+    LessThanOrEq(Box<Node>, Box<Node>),
+    GreaterThan(Box<Node>, Box<Node>),
+    GreaterThanOrEq(Box<Node>, Box<Node>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    FunctionCall {
+        target: Target,
+        name: String,
+        parameters: Vec<Node>,
+    },
+    IfElse(Box<Node>, Box<Node>, Box<Node>),
+    InlineComment(String),
+}
+
+#[derive(Serialize, Deserialize)]
+enum Target {
+    Function,
+    GraphicalFunction,
+    Model,
+    Array,
+}
+
+impl Simulator {
+    /// Saves this simulator as an artifact of `source`, the document its
+    /// model was read from.
+    pub fn to_artifact(&self, source: &str) -> Result<Vec<u8>, ArtifactError> {
+        let mut artifact = MAGIC.to_vec();
+        let header = Header {
+            version: VERSION.to_string(),
+            source: fnv1a(source),
+        };
+        bincode::serialize_into(&mut artifact, &header)?;
+        bincode::serialize_into(&mut artifact, &Prepared::from(self))?;
+        Ok(artifact)
+    }
+
+    /// Loads a simulator saved by [`to_artifact`](Self::to_artifact),
+    /// checking that it was prepared from `source` by this version of the
+    /// crate.
+    pub fn from_artifact(artifact: &[u8], source: &str) -> Result<Simulator, ArtifactError> {
+        let mut rest = artifact
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or(ArtifactError::NotAnArtifact)?;
+        let header: Header = bincode::deserialize_from(&mut rest)?;
+        if header.version != VERSION {
+            return Err(ArtifactError::Version(header.version));
+        }
+        if header.source != fnv1a(source) {
+            return Err(ArtifactError::Stale);
+        }
+        let prepared: Prepared = bincode::deserialize(rest)?;
+        prepared.try_into()
+    }
+}
+
+/// The 64-bit FNV-1a hash of `text`, which unlike the standard library's
+/// hashers is the same in every build.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Names are kept as written, and read back as equations read them, which
+/// allows reserved names such as `TIME`.
+fn name(raw: &str) -> Result<Identifier, IdentifierError> {
+    Identifier::parse(
+        raw,
+        IdentifierOptions {
+            allow_dollar: true,
+            allow_digit: true,
+            allow_reserved: true,
+        },
+    )
+}
+
+fn entries(index: &std::collections::HashMap<Identifier, usize>) -> Vec<(String, usize)> {
+    index
+        .iter()
+        .map(|(name, &slot)| (name.raw().to_string(), slot))
+        .collect()
+}
+
+impl From<&Simulator> for Prepared {
+    fn from(simulator: &Simulator) -> Self {
+        let specs = &simulator.specs;
+        Prepared {
+            specs: Specs {
+                start: specs.start,
+                stop: specs.stop,
+                dt: specs.dt,
+                method: specs.method.clone(),
+                time_units: specs.time_units.clone(),
+                pause: specs.pause,
+                run_by: specs.run_by.clone(),
+            },
+            start: simulator.start,
+            stop: simulator.stop,
+            dt: simulator.dt,
+            steps: simulator.steps,
+            method: simulator.method,
+            ratio: simulator.ratio,
+            tolerance: simulator.tolerance,
+            slots: simulator
+                .slots
+                .iter()
+                .map(|slot| PreparedSlot {
+                    name: slot.name.raw().to_string(),
+                    equation: match &slot.equation {
+                        Equation::Stock { initial, net } => PreparedEquation::Stock {
+                            initial: initial.into(),
+                            net: net.into(),
+                        },
+                        Equation::Expression(expression) => {
+                            PreparedEquation::Expression(expression.into())
+                        }
+                        Equation::Lookup(function, input) => {
+                            PreparedEquation::Lookup(*function, input.into())
+                        }
+                    },
+                    non_negative: slot.non_negative,
+                })
+                .collect(),
+            names: simulator.names.clone(),
+            index: entries(&simulator.index),
+            functions: simulator.functions.clone(),
+            function_index: entries(&simulator.function_index),
+            stocks: simulator.stocks.clone(),
+            initial_order: simulator.initial_order.clone(),
+            order: simulator.order.clone(),
+        }
+    }
+}
+
+impl TryFrom<Prepared> for Simulator {
+    type Error = ArtifactError;
+
+    fn try_from(prepared: Prepared) -> Result<Self, Self::Error> {
+        let index = |entries: Vec<(String, usize)>| {
+            entries
+                .into_iter()
+                .map(|(raw, slot)| Ok((name(&raw)?, slot)))
+                .collect::<Result<_, IdentifierError>>()
+        };
+        let slots = prepared
+            .slots
+            .into_iter()
+            .map(|slot| {
+                let equation = match slot.equation {
+                    PreparedEquation::Stock { initial, net } => Equation::Stock {
+                        initial: initial.try_into()?,
+                        net: net.try_into()?,
+                    },
+                    PreparedEquation::Expression(node) => Equation::Expression(node.try_into()?),
+                    PreparedEquation::Lookup(function, input) => {
+                        Equation::Lookup(function, input.try_into()?)
+                    }
+                };
+                Ok(Slot {
+                    name: name(&slot.name)?,
+                    equation,
+                    non_negative: slot.non_negative,
+                })
+            })
+            .collect::<Result<_, IdentifierError>>()?;
+        let specs = prepared.specs;
+        Ok(Simulator {
+            specs: SimulationSpecs {
+                start: specs.start,
+                stop: specs.stop,
+                dt: specs.dt,
+                method: specs.method,
+                time_units: specs.time_units,
+                pause: specs.pause,
+                run_by: specs.run_by,
+            },
+            start: prepared.start,
+            stop: prepared.stop,
+            dt: prepared.dt,
+            steps: prepared.steps,
+            method: prepared.method,
+            ratio: prepared.ratio,
+            tolerance: prepared.tolerance,
+            slots,
+            names: prepared.names,
+            index: index(prepared.index)?,
+            functions: prepared.functions,
+            function_index: index(prepared.function_index)?,
+            stocks: prepared.stocks,
+            initial_order: prepared.initial_order,
+            order: prepared.order,
+        })
+    }
+}
+
+impl From<&Expression> for Node {
+    fn from(expression: &Expression) -> Self {
+        let node = |expression: &Expression| Box::new(Node::from(expression));
+        let nodes = |expressions: &[Expression]| expressions.iter().map(Node::from).collect();
+        match expression {
+            Expression::Constant(NumericConstant(value)) => Node::Constant(*value),
+            Expression::Subscript(name, indices) => {
+                Node::Subscript(name.raw().to_string(), nodes(indices))
+            }
+            Expression::Wildcard => Node::Wildcard,
+            Expression::Range(from, to) => Node::Range(node(from), node(to)),
+            Expression::Transpose(inner) => Node::Transpose(node(inner)),
+            Expression::Parentheses(inner) => Node::Parentheses(node(inner)),
+            Expression::Exponentiation(lhs, rhs) => Node::Exponentiation(node(lhs), node(rhs)),
+            Expression::UnaryPlus(inner) => Node::UnaryPlus(node(inner)),
+            Expression::UnaryMinus(inner) => Node::UnaryMinus(node(inner)),
+            Expression::Not(inner) => Node::Not(node(inner)),
+            Expression::Multiply(lhs, rhs) => Node::Multiply(node(lhs), node(rhs)),
+            Expression::Divide(lhs, rhs) => Node::Divide(node(lhs), node(rhs)),
+            Expression::Modulo(lhs, rhs) => Node::Modulo(node(lhs), node(rhs)),
+            Expression::Add(lhs, rhs) => Node::Add(node(lhs), node(rhs)),
+            Expression::Subtract(lhs, rhs) => Node::Subtract(node(lhs), node(rhs)),
+            Expression::LessThan(lhs, rhs) => Node::LessThan(node(lhs), node(rhs)),
+            Expression::LessThanOrEq(lhs, rhs) => Node::LessThanOrEq(node(lhs), node(rhs)),
+            Expression::GreaterThan(lhs, rhs) => Node::GreaterThan(node(lhs), node(rhs)),
+            Expression::GreaterThanOrEq(lhs, rhs) => Node::GreaterThanOrEq(node(lhs), node(rhs)),
+            Expression::Equal(lhs, rhs) => Node::Equal(node(lhs), node(rhs)),
+            Expression::NotEqual(lhs, rhs) => Node::NotEqual(node(lhs), node(rhs)),
+            Expression::And(lhs, rhs) => Node::And(node(lhs), node(rhs)),
+            Expression::Or(lhs, rhs) => Node::Or(node(lhs), node(rhs)),
+            Expression::FunctionCall { target, parameters } => {
+                let (target, name) = match target {
+                    FunctionTarget::Function(name) => (Target::Function, name),
+                    FunctionTarget::GraphicalFunction(name) => (Target::GraphicalFunction, name),
+                    FunctionTarget::Model(name) => (Target::Model, name),
+                    FunctionTarget::Array(name) => (Target::Array, name),
+                };
+                Node::FunctionCall {
+                    target,
+                    name: name.raw().to_string(),
+                    parameters: nodes(parameters),
+                }
+            }
+            Expression::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => Node::IfElse(node(condition), node(then_branch), node(else_branch)),
+            Expression::InlineComment(comment) => Node::InlineComment(comment.clone()),
+        }
+    }
+}
+
+impl TryFrom<Node> for Expression {
+    type Error = IdentifierError;
+
+    fn try_from(node: Node) -> Result<Self, Self::Error> {
+        let expression = |node: Box<Node>| Expression::try_from(*node).map(Box::new);
+        let expressions = |nodes: Vec<Node>| {
+            nodes
+                .into_iter()
+                .map(Expression::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match node {
+            Node::Constant(value) => Expression::Constant(NumericConstant(value)),
+            Node::Subscript(raw, indices) => {
+                Expression::Subscript(name(&raw)?, expressions(indices)?)
+            }
+            Node::Wildcard => Expression::Wildcard,
+            Node::Range(from, to) => Expression::Range(expression(from)?, expression(to)?),
+            Node::Transpose(inner) => Expression::Transpose(expression(inner)?),
+            Node::Parentheses(inner) => Expression::Parentheses(expression(inner)?),
+            Node::Exponentiation(lhs, rhs) => {
+                Expression::Exponentiation(expression(lhs)?, expression(rhs)?)
+            }
+            Node::UnaryPlus(inner) => Expression::UnaryPlus(expression(inner)?),
+            Node::UnaryMinus(inner) => Expression::UnaryMinus(expression(inner)?),
+            Node::Not(inner) => Expression::Not(expression(inner)?),
+            Node::Multiply(lhs, rhs) => Expression::Multiply(expression(lhs)?, expression(rhs)?),
+            Node::Divide(lhs, rhs) => Expression::Divide(expression(lhs)?, expression(rhs)?),
+            Node::Modulo(lhs, rhs) => Expression::Modulo(expression(lhs)?, expression(rhs)?),
+            Node::Add(lhs, rhs) => Expression::Add(expression(lhs)?, expression(rhs)?),
+            Node::Subtract(lhs, rhs) => Expression::Subtract(expression(lhs)?, expression(rhs)?),
+            Node::LessThan(lhs, rhs) => Expression::LessThan(expression(lhs)?, expression(rhs)?),
+            Node::LessThanOrEq(lhs, rhs) => {
+                Expression::LessThanOrEq(expression(lhs)?, expression(rhs)?)
+            }
+            Node::GreaterThan(lhs, rhs) => {
+                Expression::GreaterThan(expression(lhs)?, expression(rhs)?)
+            }
+            Node::GreaterThanOrEq(lhs, rhs) => {
+                Expression::GreaterThanOrEq(expression(lhs)?, expression(rhs)?)
+            }
+            Node::Equal(lhs, rhs) => Expression::Equal(expression(lhs)?, expression(rhs)?),
+            Node::NotEqual(lhs, rhs) => Expression::NotEqual(expression(lhs)?, expression(rhs)?),
+            Node::And(lhs, rhs) => Expression::And(expression(lhs)?, expression(rhs)?),
+            Node::Or(lhs, rhs) => Expression::Or(expression(lhs)?, expression(rhs)?),
+            Node::FunctionCall {
+                target,
+                name: raw,
+                parameters,
+            } => {
+                let name = name(&raw)?;
+                Expression::FunctionCall {
+                    target: match target {
+                        Target::Function => FunctionTarget::Function(name),
+                        Target::GraphicalFunction => FunctionTarget::GraphicalFunction(name),
+                        Target::Model => FunctionTarget::Model(name),
+                        Target::Array => FunctionTarget::Array(name),
+                    },
+                    parameters: expressions(parameters)?,
+                }
+            }
+            Node::IfElse(condition, then_branch, else_branch) => Expression::IfElse {
+                condition: expression(condition)?,
+                then_branch: expression(then_branch)?,
+                else_branch: expression(else_branch)?,
+            },
+            Node::InlineComment(comment) => Expression::InlineComment(comment),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xml::XmileFile;
+
+    const SOURCE: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+        <sim_specs method="rk4"><start>0</start><stop>10</stop><dt>0.25</dt></sim_specs>
+        <model><variables>
+            <stock name="Population"><eqn>100</eqn><inflow>Births</inflow><outflow>Deaths</outflow></stock>
+            <flow name="Births"><eqn>Population * birth_rate(TIME)</eqn></flow>
+            <flow name="Deaths"><eqn>DELAY3(Births, 4)</eqn></flow>
+            <gf name="birth_rate"><xscale min="0" max="10"/><ypts>0.1,0.2,0.05</ypts></gf>
+            <aux name="Smoothed"><eqn>SMTH1(Population - 2 * Deaths, 3)</eqn></aux>
+        </variables></model>
+    </xmile>"#;
+
+    fn simulator() -> Simulator {
+        let file = XmileFile::from_str(SOURCE).unwrap();
+        Simulator::new(&file.models[0], file.sim_specs.as_ref().unwrap())
+            .unwrap()
+            .with_tolerance(1e-3)
+    }
+
+    #[test]
+    fn test_artifact_round_trip() {
+        let simulator = simulator();
+        let artifact = simulator.to_artifact(SOURCE).unwrap();
+        let loaded = Simulator::from_artifact(&artifact, SOURCE).unwrap();
+        assert_eq!(loaded.names(), simulator.names());
+        assert_eq!(loaded.tolerance, simulator.tolerance);
+        assert_eq!(loaded.specs, simulator.specs);
+        assert_eq!(loaded.run().unwrap(), simulator.run().unwrap());
+    }
+
+    #[test]
+    fn test_artifact_checks() {
+        let artifact = simulator().to_artifact(SOURCE).unwrap();
+
+        let edited = SOURCE.replace("0.05", "0.06");
+        assert!(matches!(
+            Simulator::from_artifact(&artifact, &edited),
+            Err(ArtifactError::Stale)
+        ));
+        assert!(matches!(
+            Simulator::from_artifact(SOURCE.as_bytes(), SOURCE),
+            Err(ArtifactError::NotAnArtifact)
+        ));
+        assert!(matches!(
+            Simulator::from_artifact(&artifact[..artifact.len() / 2], SOURCE),
+            Err(ArtifactError::Encoding(_))
+        ));
+
+        // An artifact from another version of the crate
+        let mut old = MAGIC.to_vec();
+        let header = Header {
+            version: "0.0.1".to_string(),
+            source: fnv1a(SOURCE),
+        };
+        bincode::serialize_into(&mut old, &header).unwrap();
+        bincode::serialize_into(&mut old, &Prepared::from(&simulator())).unwrap();
+        assert!(matches!(
+            Simulator::from_artifact(&old, SOURCE),
+            Err(ArtifactError::Version(version)) if version == "0.0.1"
+        ));
+    }
+}
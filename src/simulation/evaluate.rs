@@ -0,0 +1,133 @@
+//! Evaluation of equations at one step of a run.
+//!
+//! Equations are evaluated with [`Expression::evaluate_with`], over any
+//! [`Scalar`]; a [`Context`] resolves what that needs the run for: the
+//! values of variables, `TIME`, `DT`, `STARTTIME` and `STOPTIME`, graphical
+//! functions, and the builtins `STEP`, `PULSE` and `RAMP`, which depend on
+//! the time.
+
+use std::ops::RangeInclusive;
+
+use crate::Identifier;
+use crate::equation::Expression;
+use crate::equation::evaluate::{self, Scalar};
+
+use super::Simulator;
+
+/// The values known at one step of a run.
+pub(super) struct Context<'a, S> {
+    pub simulator: &'a Simulator,
+    pub values: &'a [S],
+    pub time: f64,
+}
+
+/// Whether `name` is a builtin that is used without arguments.
+pub(super) fn is_builtin(name: &Identifier) -> bool {
+    evaluate::constant(name).is_some()
+        || matches!(
+            name.normalized().to_ascii_uppercase().as_str(),
+            "TIME" | "DT" | "STARTTIME" | "STOPTIME"
+        )
+}
+
+/// The number of arguments a builtin takes, or `None` if it cannot be
+/// simulated.
+pub(super) fn arity(builtin: &str) -> Option<RangeInclusive<usize>> {
+    match builtin {
+        "STEP" => Some(2..=2),
+        "PULSE" | "RAMP" => Some(2..=3),
+        _ => evaluate::arity(builtin),
+    }
+}
+
+impl<S: Scalar> Context<'_, S> {
+    /// Evaluates an equation checked by [`Simulator::check`].
+    pub fn evaluate(&self, expression: &Expression) -> S {
+        expression
+            .evaluate_with(&|name| self.value(name), &|name, arguments| {
+                self.call(name, arguments)
+            })
+            .unwrap_or(S::constant(f64::NAN))
+    }
+
+    fn value(&self, name: &Identifier) -> Option<S> {
+        let simulator = self.simulator;
+        if let Some(&slot) = simulator.index.get(name) {
+            return Some(self.values[slot]);
+        }
+        let value = match name.normalized().to_ascii_uppercase().as_str() {
+            "TIME" => self.time,
+            "DT" => simulator.dt,
+            "STARTTIME" => simulator.start,
+            "STOPTIME" => simulator.stop,
+            _ => return None,
+        };
+        Some(S::constant(value))
+    }
+
+    /// Calls a graphical function, or a builtin that depends on the time.
+    /// Graphical functions are applied to the value of their input, so
+    /// their results are constants.
+    fn call(&self, name: &Identifier, arguments: &[S]) -> Option<S> {
+        if let Some(&function) = self.simulator.function_index.get(name) {
+            let input = arguments.first()?.value();
+            return Some(S::constant(
+                self.simulator.functions[function].evaluate(input),
+            ));
+        }
+
+        let zero = S::constant(0.0);
+        let dt = self.simulator.dt;
+        let time = self.time;
+        match (name.normalized().to_ascii_uppercase().as_str(), arguments) {
+            ("STEP", [height, start]) => {
+                let started = match self.simulator.discrete_step(time) {
+                    Some(step) => step >= self.simulator.first_step_from(start.value()),
+                    None => time >= start.value(),
+                };
+                Some(if started { *height } else { zero })
+            }
+            // The magnitude is spread over one DT, so a stock the pulse
+            // flows into changes by the magnitude
+            ("PULSE", [magnitude, first, rest @ ..]) => {
+                let first = first.value();
+                let interval = rest.first().map_or(0.0, |interval| interval.value());
+                // Discrete runs pulse on whole steps
+                let pulses = if let Some(step) = self.simulator.discrete_step(time) {
+                    let since = step - self.simulator.first_step_from(first);
+                    since >= 0.0
+                        && if interval > 0.0 {
+                            since % self.simulator.steps_in(interval) == 0.0
+                        } else {
+                            since == 0.0
+                        }
+                } else {
+                    let since = time - first;
+                    let nearest = if interval > 0.0 {
+                        since - (since / interval).round().max(0.0) * interval
+                    } else {
+                        since
+                    };
+                    since > -dt / 2.0 && nearest.abs() < dt / 2.0
+                };
+                Some(if pulses {
+                    *magnitude / S::constant(dt)
+                } else {
+                    zero
+                })
+            }
+            ("RAMP", [slope, start, rest @ ..]) => {
+                if time > start.value() {
+                    let end = match rest.first() {
+                        Some(end) if end.value() < time => *end,
+                        _ => S::constant(time),
+                    };
+                    Some(*slope * (end - *start))
+                } else {
+                    Some(zero)
+                }
+            }
+            _ => None,
+        }
+    }
+}
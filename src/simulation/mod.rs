@@ -0,0 +1,1178 @@
+//! Simulation of stock and flow models.
+//!
+//! A [`Simulator`] prepares a [`Model`] for running with a set of
+//! [`SimulationSpecs`]: it checks that every equation can be evaluated,
+//! and orders the auxiliaries and flows so that each is computed after the
+//! variables it uses. Stocks break feedback loops, since their values are
+//! known at the start of each step.
+//!
+//! Each step of a run computes the auxiliaries and flows from the stocks,
+//! saves every value, and then moves each stock over DT with the
+//! [`IntegrationMethod`] the specs name: by its net flow times DT (Euler's
+//! method, the default), or by a weighted average of net flows computed
+//! part-way through the step (second- or fourth-order Runge-Kutta):
+//!
+//! ```rust
+//! use xmile::simulation::Simulator;
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <sim_specs><start>0</start><stop>2</stop><dt>1</dt></sim_specs>
+//!     <model><variables>
+//!         <stock name="Population"><eqn>100</eqn><inflow>Births</inflow></stock>
+//!         <flow name="Births"><eqn>Population * Birth_Rate</eqn></flow>
+//!         <aux name="Birth_Rate"><eqn>0.25</eqn></aux>
+//!     </variables></model>
+//! </xmile>"#).unwrap();
+//! let simulator = Simulator::new(&file.models[0], file.sim_specs.as_ref().unwrap()).unwrap();
+//! let run = simulator.run().unwrap();
+//! assert_eq!(run.times, [0.0, 1.0, 2.0]);
+//! assert_eq!(run.series("Population").unwrap(), [100.0, 125.0, 156.25]);
+//! assert_eq!(run.series("Births").unwrap(), [25.0, 31.25, 39.0625]);
+//! ```
+//!
+//! The adaptive Runge-Kutta-Fehlberg method (`rk45`) instead chooses the
+//! size of each step to keep its estimated error within a
+//! [tolerance](Simulator::with_tolerance), and interpolates the values it
+//! saves every DT. The `discrete` method runs the equations as difference
+//! equations: stocks move as with Euler's method, but `STEP` and `PULSE`
+//! act on the step their times fall on.
+//!
+//! Each saved time is the start time plus a whole number of steps of DT,
+//! with DT held as a fraction where it is one, so times do not drift and
+//! are exact for DTs such as 0.1 or a DT given by its reciprocal. Runs
+//! record the number of each saved step alongside its time.
+//!
+//! Runs can also be written to any [`SaveStepSink`] as they progress with
+//! [`Simulator::run_with`], for instance to a
+//! [`ResultRecorder`] that keeps only some steps.
+//!
+//! Equations are evaluated with [`Expression::evaluate_with`], the same
+//! evaluator used outside runs, so [`Simulator::run_over`] can carry out a
+//! run over any [`Scalar`] number type, such as double-double numbers to
+//! see how much of it is rounding error, or over dual numbers with
+//! [`Simulator::sensitivity`] to find how every value depends on a
+//! constant.
+//!
+//! A run can also be advanced a step at a time from a [`Run`], and the
+//! model under it swapped for an edited one with
+//! [`Simulator::swap_model`], which keeps the values of the stocks the two
+//! models share.
+//!
+//! With the `artifacts` feature, a prepared simulator can be saved with
+//! `Simulator::to_artifact` and loaded again without parsing the model.
+//!
+//! Delays and smooths (`DELAY1`, `DELAY3`, `DELAYN`, `SMTH1`, `SMTH3`,
+//! `SMTHN`), `TREND` and `FORCST` keep state between steps: each call is
+//! given hidden stocks of its own, which start with the model's stocks and
+//! are integrated with them. Their values are not saved.
+//!
+//! Arrays, conveyors, queues, submodels and other builtins that keep state
+//! between steps, such as the pipeline `DELAY`, cannot be simulated yet;
+//! [`Simulator::new`] rejects models using them with
+//! [`SimulationError::Unsupported`]. Nor are option filters applied:
+//! [`Simulator::from_file`] rejects files that use them in the same way.
+
+mod adaptive;
+#[cfg(feature = "artifacts")]
+mod artifact;
+mod evaluate;
+mod reload;
+mod sensitivity;
+mod stateful;
+mod time;
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::data::{ExportData, ExportError, ResultRecorder, Retention, SaveStepSink};
+use crate::equation::Expression;
+use crate::equation::evaluate::Scalar;
+use crate::equation::expression::function::FunctionTarget;
+use crate::model::vars::Variable;
+use crate::model::vars::gf::GraphicalFunctionTable;
+use crate::model::vars::stock::Stock;
+use crate::specs::{IntegrationMethod, SimulationSpecs};
+use crate::xml::XmileFile;
+use crate::xml::schema::Model;
+use crate::{Identifier, trace};
+
+#[cfg(feature = "artifacts")]
+pub use artifact::ArtifactError;
+use evaluate::Context;
+pub use reload::{Migration, Run};
+use stateful::Expander;
+use time::Ratio;
+
+/// The default tolerance of adaptive steps.
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SimulationError {
+    #[error("Invalid simulation specs: {0}")]
+    InvalidSpecs(String),
+    #[error("{variable} refers to unknown variable {name}")]
+    UnknownVariable { variable: String, name: String },
+    #[error("{variable} cannot be simulated: {construct} is not supported")]
+    Unsupported { variable: String, construct: String },
+    #[error("{variable} calls {function} with {count} arguments")]
+    Arity {
+        variable: String,
+        function: String,
+        count: usize,
+    },
+    #[error("The file has no model to simulate")]
+    NoModel,
+    #[error("{0} is not a constant of the model")]
+    NotConstant(String),
+    #[error("Circular dependency: {}", .0.join(" -> "))]
+    Circular(Vec<String>),
+    #[error(transparent)]
+    Export(#[from] ExportError),
+}
+
+#[cfg(feature = "tracing")]
+thread_local! {
+    /// The equations evaluated by the run on this thread, reported when it
+    /// finishes.
+    static EVALUATIONS: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+}
+
+/// Counts `count` equation evaluations towards the current run.
+#[inline]
+fn count_evaluations(count: usize) {
+    #[cfg(feature = "tracing")]
+    EVALUATIONS.with(|evaluations| evaluations.set(evaluations.get() + count as u64));
+    #[cfg(not(feature = "tracing"))]
+    let _ = count;
+}
+
+/// Receives the values of every slot at each saved time.
+type Save<'a, S> = dyn FnMut(f64, &[S]) -> Result<(), SimulationError> + 'a;
+
+/// How a variable's value is computed at each step.
+#[derive(Debug, Clone)]
+enum Equation {
+    /// A stock, moved by its net flow.
+    Stock {
+        initial: Expression,
+        net: Expression,
+    },
+    Expression(Expression),
+    /// A graphical function applied to its input equation.
+    Lookup(usize, Expression),
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    name: Identifier,
+    equation: Equation,
+    /// Whether negative values are replaced by zero (non-negative flows).
+    non_negative: bool,
+}
+
+/// A model prepared for running.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    specs: SimulationSpecs,
+    start: f64,
+    stop: f64,
+    dt: f64,
+    steps: usize,
+    method: IntegrationMethod,
+    /// DT as a fraction, if it is one with a denominator of at most a
+    /// million.
+    ratio: Option<Ratio>,
+    tolerance: f64,
+    /// The model's variables, followed by the hidden stocks of stateful
+    /// builtins.
+    slots: Vec<Slot>,
+    /// The names of the model's variables, whose values are saved.
+    names: Vec<String>,
+    index: HashMap<Identifier, usize>,
+    functions: Vec<GraphicalFunctionTable>,
+    function_index: HashMap<Identifier, usize>,
+    /// The slots of the stocks, in model order, then the hidden stocks.
+    stocks: Vec<usize>,
+    /// Every slot, in the order initial values are computed.
+    initial_order: Vec<usize>,
+    /// The auxiliaries and flows, in the order they are computed each step.
+    order: Vec<usize>,
+}
+
+impl Simulator {
+    /// Prepares the first model of `file`, its root model, for running with
+    /// `specs`, as [`new`](Self::new) does.
+    ///
+    /// Fails with [`SimulationError::Unsupported`] if the file uses option
+    /// filters, which runs do not apply, and with
+    /// [`SimulationError::NoModel`] if it has no model.
+    pub fn from_file(
+        file: &XmileFile,
+        specs: &SimulationSpecs,
+    ) -> Result<Simulator, SimulationError> {
+        let declared = file
+            .header
+            .options
+            .as_ref()
+            .and_then(|options| options.uses_macros.as_ref())
+            .is_some_and(|uses_macros| uses_macros.option_filters);
+        #[cfg(feature = "macros")]
+        if let Some(filter) = file
+            .macros
+            .iter()
+            .find(|macro_def| macro_def.filter.is_some())
+        {
+            return Err(unsupported(&filter.name, "option filter"));
+        }
+        if declared {
+            return Err(SimulationError::Unsupported {
+                variable: "The file".to_string(),
+                construct: "<uses_macros option_filters=\"true\"/>".to_string(),
+            });
+        }
+        let model = file.models.first().ok_or(SimulationError::NoModel)?;
+        Simulator::new(model, specs)
+    }
+
+    /// Prepares `model` for running with `specs`.
+    ///
+    /// Fails if the specs are invalid, if an equation refers to an unknown
+    /// variable or uses something that cannot be simulated, or if
+    /// auxiliaries and flows depend on each other in a loop with no stock.
+    /// Adaptive steps are invalid for models with conveyors, queues or
+    /// `PULSE`, which act at discrete times a step could pass over.
+    pub fn new(model: &Model, specs: &SimulationSpecs) -> Result<Simulator, SimulationError> {
+        let dt = specs.dt.unwrap_or(1.0);
+        if !(dt > 0.0 && dt.is_finite()) {
+            return Err(SimulationError::InvalidSpecs(format!(
+                "DT must be positive, not {dt}"
+            )));
+        }
+        if !(specs.start.is_finite() && specs.stop.is_finite() && specs.stop >= specs.start) {
+            return Err(SimulationError::InvalidSpecs(format!(
+                "stop time {} is before start time {}",
+                specs.stop, specs.start
+            )));
+        }
+        // Allow for rounding in (stop - start) / dt, so that a run from 0 to
+        // 1 with DT 0.1 has ten steps
+        let steps = ((specs.stop - specs.start) / dt + 1e-6).floor() as usize;
+        let method = specs
+            .integration_method()
+            .map_err(SimulationError::InvalidSpecs)?;
+        // Times are exact wherever DT is a fraction, as a DT given by its
+        // reciprocal always is; discrete runs need them to be
+        let ratio = Ratio::approximate(dt);
+        if method == IntegrationMethod::Discrete && ratio.is_none() {
+            return Err(SimulationError::InvalidSpecs(format!(
+                "DT {dt} is not a fraction with a denominator of at most a million"
+            )));
+        }
+        if method == IntegrationMethod::Rk45
+            && let Some(construct) = discrete_construct(model)
+        {
+            return Err(SimulationError::InvalidSpecs(format!(
+                "{method} steps cannot be used with {construct}, which acts at discrete times"
+            )));
+        }
+
+        let mut functions = Vec::new();
+        let mut function_index = HashMap::new();
+        for variable in &model.variables.variables {
+            if let Variable::GraphicalFunction(gf) = variable
+                && let Some(name) = &gf.name
+            {
+                function_index.insert(name.clone(), functions.len());
+                functions.push(gf.table());
+            }
+        }
+
+        let mut slots = Vec::new();
+        let mut stocks = Vec::new();
+        for variable in &model.variables.variables {
+            let slot =
+                match variable {
+                    Variable::Stock(stock) => {
+                        let Stock::Basic(basic) = stock.as_ref() else {
+                            return Err(unsupported(
+                                stock.name(),
+                                match stock.as_ref() {
+                                    Stock::Conveyor(_) => "conveyor",
+                                    _ => "queue",
+                                },
+                            ));
+                        };
+                        stocks.push(slots.len());
+                        Slot {
+                            name: basic.name.clone(),
+                            equation: Equation::Stock {
+                                initial: basic.initial_equation.clone(),
+                                net: stock.net_flow_expression(model),
+                            },
+                            non_negative: false,
+                        }
+                    }
+                    Variable::Flow(flow) => Slot {
+                        name: flow.name.clone(),
+                        equation: Equation::Expression(flow.equation.clone().ok_or_else(|| {
+                            unsupported(&flow.name, "a flow without an equation")
+                        })?),
+                        non_negative: matches!(flow.non_negative, Some(None | Some(true))),
+                    },
+                    Variable::Auxiliary(aux) => Slot {
+                        name: aux.name.clone(),
+                        equation: Equation::Expression(aux.equation.clone()),
+                        non_negative: false,
+                    },
+                    Variable::GraphicalFunction(gf) => {
+                        let (Some(name), Some(input)) = (&gf.name, &gf.equation) else {
+                            continue;
+                        };
+                        Slot {
+                            name: name.clone(),
+                            equation: Equation::Lookup(function_index[name], input.clone()),
+                            non_negative: false,
+                        }
+                    }
+                    #[cfg(feature = "submodels")]
+                    Variable::Module(module) => return Err(unsupported(&module.name, "submodel")),
+                    Variable::Group(_) => continue,
+                };
+            if is_arrayed(variable) {
+                return Err(unsupported(&slot.name, "arrays"));
+            }
+            slots.push(slot);
+        }
+
+        // Give each call of a builtin that keeps state its own stocks
+        let names = slots.iter().map(|slot| slot.name.to_string()).collect();
+        let mut expander =
+            Expander::new(&function_index, slots.iter().map(|slot| slot.name.clone()));
+        for slot in &mut slots {
+            let name = &slot.name;
+            match &mut slot.equation {
+                Equation::Stock { initial, .. } => {
+                    *initial = expander.expand(name, initial.clone())?;
+                }
+                Equation::Expression(expression) | Equation::Lookup(_, expression) => {
+                    *expression = expander.expand(name, expression.clone())?;
+                }
+            }
+        }
+        for hidden in expander.stocks {
+            stocks.push(slots.len());
+            slots.push(Slot {
+                name: hidden.name,
+                equation: Equation::Stock {
+                    initial: hidden.initial,
+                    net: hidden.net,
+                },
+                non_negative: false,
+            });
+        }
+
+        let index: HashMap<Identifier, usize> = slots
+            .iter()
+            .enumerate()
+            .map(|(position, slot)| (slot.name.clone(), position))
+            .collect();
+        let mut simulator = Simulator {
+            specs: specs.clone(),
+            start: specs.start,
+            stop: specs.stop,
+            dt,
+            steps,
+            method,
+            ratio,
+            tolerance: DEFAULT_TOLERANCE,
+            names,
+            slots,
+            index,
+            functions,
+            function_index,
+            stocks,
+            initial_order: Vec::new(),
+            order: Vec::new(),
+        };
+
+        // Initial values may use any variable; afterwards stocks are known
+        // at the start of each step, so only auxiliaries and flows depend
+        // on each other
+        let mut initial_dependencies: Vec<Vec<usize>> = Vec::with_capacity(simulator.slots.len());
+        let mut dependencies: Vec<Vec<usize>> = Vec::with_capacity(simulator.slots.len());
+        for slot in &simulator.slots {
+            let (initial, each_step) = match &slot.equation {
+                Equation::Stock { initial, net } => {
+                    simulator.check(&slot.name, net)?;
+                    (initial, None)
+                }
+                Equation::Expression(expression) | Equation::Lookup(_, expression) => {
+                    (expression, Some(expression))
+                }
+            };
+            simulator.check(&slot.name, initial)?;
+            initial_dependencies.push(simulator.references(initial));
+            dependencies.push(
+                each_step
+                    .map(|expression| {
+                        simulator
+                            .references(expression)
+                            .into_iter()
+                            .filter(|dependency| !simulator.stocks.contains(dependency))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            );
+        }
+        let all: Vec<usize> = (0..simulator.slots.len()).collect();
+        let computed: Vec<usize> = all
+            .iter()
+            .copied()
+            .filter(|slot| !simulator.stocks.contains(slot))
+            .collect();
+        simulator.initial_order = simulator.sort(&all, &initial_dependencies)?;
+        simulator.order = simulator.sort(&computed, &dependencies)?;
+        Ok(simulator)
+    }
+
+    /// The names of the variables, in the order their values are saved.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The number of steps of DT in a run. A run saves one more step than
+    /// this, for its start time.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// The method stocks are integrated with.
+    pub fn method(&self) -> IntegrationMethod {
+        self.method
+    }
+
+    /// Sets the tolerance of adaptive steps: the error allowed in each
+    /// step, relative to the stocks, or absolute for stocks smaller than
+    /// one. Fixed-step methods ignore it.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// The time at the given step.
+    ///
+    /// Times are computed as the start time plus the step count times DT,
+    /// rather than by adding DT repeatedly, so they do not drift over long
+    /// runs. When DT is a fraction, such as 0.1 or a DT given by its
+    /// reciprocal, the step count is multiplied by the fraction, so that
+    /// step 3 of DT 0.1 is 0.3 exactly rather than 0.30000000000000004.
+    pub fn time(&self, step: usize) -> f64 {
+        match self.ratio {
+            Some(ratio) => self.start + ratio.times(step),
+            None => self.start + step as f64 * self.dt,
+        }
+    }
+
+    /// The step a time falls on, the inverse of [`time`](Self::time), or
+    /// `None` if it is not within a billionth of a step of one in the run.
+    pub fn step(&self, time: f64) -> Option<usize> {
+        let steps = (time - self.start) / self.dt;
+        let step = steps.round();
+        ((steps - step).abs() < time::ON_STEP && (0.0..=self.steps as f64).contains(&step))
+            .then_some(step as usize)
+    }
+
+    /// Runs the model, keeping every saved step.
+    pub fn run(&self) -> Result<ExportData, SimulationError> {
+        let names: Vec<&str> = self.names.iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        self.run_with(&mut recorder)?;
+        Ok(recorder.into_data())
+    }
+
+    /// Runs the model, passing each saved step to `sink` with the values
+    /// in [`names`](Self::names) order, and finishing the sink at the end.
+    pub fn run_with(&self, sink: &mut dyn SaveStepSink) -> Result<(), SimulationError> {
+        self.run_with_step_sizes(sink).map(|_| ())
+    }
+
+    /// Runs the model as [`run_with`](Self::run_with) does, returning the
+    /// sizes of the integration steps taken: DT for each step with a
+    /// fixed-step method, or each step kept by adaptive steps.
+    pub fn run_with_step_sizes(
+        &self,
+        sink: &mut dyn SaveStepSink,
+    ) -> Result<Vec<f64>, SimulationError> {
+        self.run_over::<f64>(sink)
+    }
+
+    /// Runs the model as [`run_with_step_sizes`](Self::run_with_step_sizes)
+    /// does, computing every value over the number type `S`, such as
+    /// [`DoubleDouble`](crate::equation::double_double::DoubleDouble) to
+    /// see how much of a run is rounding error. The plain value of each is
+    /// saved.
+    pub fn run_over<S: Scalar>(
+        &self,
+        sink: &mut dyn SaveStepSink,
+    ) -> Result<Vec<f64>, SimulationError> {
+        trace::enter_span!("xmile.run", steps = self.steps);
+        #[cfg(feature = "tracing")]
+        EVALUATIONS.with(|evaluations| evaluations.set(0));
+        let mut values = vec![S::constant(0.0); self.slots.len()];
+        self.initialize(&mut values, self.start, &[]);
+        let sizes = self.run_from(&mut values, &mut |time, values| {
+            self.save(sink, time, values)
+        })?;
+        sink.finish()?;
+        trace::event!(
+            info,
+            steps = sizes.len(),
+            evaluations = EVALUATIONS.with(std::cell::Cell::get),
+            "ran"
+        );
+        Ok(sizes)
+    }
+
+    /// Runs from the initial `values`, passing each saved step to `save`,
+    /// and returns the sizes of the integration steps taken.
+    fn run_from<S: Scalar>(
+        &self,
+        values: &mut [S],
+        save: &mut Save<S>,
+    ) -> Result<Vec<f64>, SimulationError> {
+        if self.method == IntegrationMethod::Rk45 {
+            return self.run_adaptive(values, save);
+        }
+        for step in 0..=self.steps {
+            let time = self.time(step);
+            self.compute(values, time);
+            save(time, values)?;
+            if step == self.steps {
+                break;
+            }
+            self.integrate(values, time);
+        }
+        Ok(vec![self.dt; self.steps])
+    }
+
+    /// Computes the initial value of every slot but those in `known`,
+    /// whose values are already in `values`, at `time`.
+    fn initialize<S: Scalar>(&self, values: &mut [S], time: f64, known: &[usize]) {
+        for &slot in &self.initial_order {
+            if known.contains(&slot) {
+                continue;
+            }
+            values[slot] = match &self.slots[slot].equation {
+                Equation::Stock { initial, .. } => {
+                    count_evaluations(1);
+                    self.context(values, time).evaluate(initial)
+                }
+                _ => self.value(slot, values, time),
+            };
+        }
+    }
+
+    /// Passes the plain values of the model's variables to `sink`.
+    fn save<S: Scalar>(
+        &self,
+        sink: &mut dyn SaveStepSink,
+        time: f64,
+        values: &[S],
+    ) -> Result<(), SimulationError> {
+        let saved: Vec<f64> = values[..self.names.len()]
+            .iter()
+            .map(|value| value.value())
+            .collect();
+        sink.save_step(time, &saved)?;
+        Ok(())
+    }
+
+    /// Moves the stocks in `values`, whose auxiliaries and flows have been
+    /// computed at `time`, to `time + DT`. The auxiliaries and flows are
+    /// left as they were computed for the last stage.
+    fn integrate<S: Scalar>(&self, values: &mut [S], time: f64) {
+        let dt = self.dt;
+        let start: Vec<S> = self.stocks.iter().map(|&slot| values[slot]).collect();
+        let k1 = self.net_flows(values, time);
+        let rates = match self.method {
+            IntegrationMethod::Euler | IntegrationMethod::Discrete => k1,
+            IntegrationMethod::Rk2 => {
+                let k2 = self.stage(values, &start, &k1, dt, time + dt);
+                k1.iter()
+                    .zip(&k2)
+                    .map(|(&k1, &k2)| (k1 + k2) / S::constant(2.0))
+                    .collect()
+            }
+            IntegrationMethod::Rk4 => {
+                let k2 = self.stage(values, &start, &k1, dt / 2.0, time + dt / 2.0);
+                let k3 = self.stage(values, &start, &k2, dt / 2.0, time + dt / 2.0);
+                let k4 = self.stage(values, &start, &k3, dt, time + dt);
+                (0..k1.len())
+                    .map(|stock| {
+                        (k1[stock] + S::constant(2.0) * (k2[stock] + k3[stock]) + k4[stock])
+                            / S::constant(6.0)
+                    })
+                    .collect()
+            }
+            IntegrationMethod::Rk45 => unreachable!("adaptive runs choose their own steps"),
+        };
+        for ((&slot, &start), rate) in self.stocks.iter().zip(&start).zip(rates) {
+            values[slot] = start + rate * S::constant(dt);
+        }
+    }
+
+    /// The net flows at `time` with each stock moved from `start` by its
+    /// rate in `rates` for `h`.
+    fn stage<S: Scalar>(
+        &self,
+        values: &mut [S],
+        start: &[S],
+        rates: &[S],
+        h: f64,
+        time: f64,
+    ) -> Vec<S> {
+        for ((&slot, &start), &rate) in self.stocks.iter().zip(start).zip(rates) {
+            values[slot] = start + rate * S::constant(h);
+        }
+        self.compute(values, time);
+        self.net_flows(values, time)
+    }
+
+    /// Computes the auxiliaries and flows at `time` from the stocks.
+    fn compute<S: Scalar>(&self, values: &mut [S], time: f64) {
+        for &slot in &self.order {
+            values[slot] = self.value(slot, values, time);
+        }
+    }
+
+    /// The net flow of each stock at `time`.
+    fn net_flows<S: Scalar>(&self, values: &[S], time: f64) -> Vec<S> {
+        count_evaluations(self.stocks.len());
+        let context = self.context(values, time);
+        self.stocks
+            .iter()
+            .map(|&slot| match &self.slots[slot].equation {
+                Equation::Stock { net, .. } => context.evaluate(net),
+                _ => unreachable!("only stocks have net flows"),
+            })
+            .collect()
+    }
+
+    /// Computes the value of an auxiliary, flow or graphical function.
+    fn value<S: Scalar>(&self, slot: usize, values: &[S], time: f64) -> S {
+        count_evaluations(1);
+        let context = self.context(values, time);
+        let slot = &self.slots[slot];
+        let value = match &slot.equation {
+            Equation::Expression(expression) => context.evaluate(expression),
+            Equation::Lookup(function, input) => {
+                S::constant(self.functions[*function].evaluate(context.evaluate(input).value()))
+            }
+            Equation::Stock { .. } => unreachable!("stocks are integrated, not computed"),
+        };
+        // Negative and undefined values of non-negative flows are zero
+        if slot.non_negative && (value.value() < 0.0 || value.value().is_nan()) {
+            S::constant(0.0)
+        } else {
+            value
+        }
+    }
+
+    fn context<'a, S>(&'a self, values: &'a [S], time: f64) -> Context<'a, S> {
+        Context {
+            simulator: self,
+            values,
+            time,
+        }
+    }
+
+    /// The slots of the variables `expression` refers to.
+    fn references(&self, expression: &Expression) -> Vec<usize> {
+        expression
+            .references()
+            .into_iter()
+            .filter_map(|name| self.index.get(name).copied())
+            .collect()
+    }
+
+    /// Checks that `expression`, the equation of `variable`, can be
+    /// evaluated.
+    fn check(&self, variable: &Identifier, expression: &Expression) -> Result<(), SimulationError> {
+        match expression {
+            Expression::Subscript(name, indices) => {
+                if !indices.is_empty() {
+                    return Err(unsupported(variable, "arrays"));
+                }
+                if !self.index.contains_key(name) && !evaluate::is_builtin(name) {
+                    return Err(SimulationError::UnknownVariable {
+                        variable: variable.to_string(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+            Expression::FunctionCall { target, parameters } => {
+                let function = match target {
+                    FunctionTarget::Function(name) | FunctionTarget::GraphicalFunction(name) => {
+                        name
+                    }
+                    FunctionTarget::Model(_) => return Err(unsupported(variable, "submodel")),
+                    FunctionTarget::Array(_) => return Err(unsupported(variable, "arrays")),
+                };
+                let arity = if self.function_index.contains_key(function) {
+                    1..=1
+                } else if let FunctionTarget::GraphicalFunction(name) = target {
+                    return Err(SimulationError::UnknownVariable {
+                        variable: variable.to_string(),
+                        name: name.to_string(),
+                    });
+                } else {
+                    let builtin = function.normalized().to_ascii_uppercase();
+                    evaluate::arity(&builtin).ok_or_else(|| unsupported(variable, &builtin))?
+                };
+                if !arity.contains(&parameters.len()) {
+                    return Err(SimulationError::Arity {
+                        variable: variable.to_string(),
+                        function: function.to_string(),
+                        count: parameters.len(),
+                    });
+                }
+            }
+            Expression::Wildcard | Expression::Range(..) | Expression::Transpose(_) => {
+                return Err(unsupported(variable, "arrays"));
+            }
+            Expression::InlineComment(_) => {
+                return Err(unsupported(variable, "a comment in place of an equation"));
+            }
+            _ => {}
+        }
+        expression
+            .children()
+            .into_iter()
+            .try_for_each(|child| self.check(variable, child))
+    }
+
+    /// Orders `nodes` so that each comes after its dependencies among them.
+    fn sort(
+        &self,
+        nodes: &[usize],
+        dependencies: &[Vec<usize>],
+    ) -> Result<Vec<usize>, SimulationError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Open,
+            Done,
+        }
+
+        fn visit(
+            node: usize,
+            dependencies: &[Vec<usize>],
+            marks: &mut [Mark],
+            path: &mut Vec<usize>,
+            order: &mut Vec<usize>,
+        ) -> Result<(), Vec<usize>> {
+            match marks[node] {
+                Mark::Done => return Ok(()),
+                Mark::Open => {
+                    let start = path.iter().position(|&open| open == node).unwrap_or(0);
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(node);
+                    return Err(cycle);
+                }
+                Mark::New => {}
+            }
+            marks[node] = Mark::Open;
+            path.push(node);
+            for &dependency in &dependencies[node] {
+                visit(dependency, dependencies, marks, path, order)?;
+            }
+            path.pop();
+            marks[node] = Mark::Done;
+            order.push(node);
+            Ok(())
+        }
+
+        let mut marks = vec![Mark::New; dependencies.len()];
+        let mut path = Vec::new();
+        let mut order = Vec::with_capacity(nodes.len());
+        for &node in nodes {
+            visit(node, dependencies, &mut marks, &mut path, &mut order).map_err(|cycle| {
+                SimulationError::Circular(
+                    cycle
+                        .iter()
+                        .map(|&slot| self.slots[slot].name.to_string())
+                        .collect(),
+                )
+            })?;
+        }
+        Ok(order)
+    }
+}
+
+/// The first conveyor, queue or use of `PULSE` in `model`, described for
+/// an error message.
+fn discrete_construct(model: &Model) -> Option<String> {
+    fn pulses(expression: &Expression) -> bool {
+        matches!(
+            expression,
+            Expression::FunctionCall { target: FunctionTarget::Function(name), .. }
+                if name.normalized().eq_ignore_ascii_case("PULSE")
+        ) || expression.children().into_iter().any(pulses)
+    }
+
+    model.variables.variables.iter().find_map(|variable| {
+        let (name, equation) = match variable {
+            Variable::Stock(stock) => match stock.as_ref() {
+                Stock::Basic(basic) => (&basic.name, Some(&basic.initial_equation)),
+                Stock::Conveyor(_) => return Some(format!("conveyor {}", stock.name())),
+                _ => return Some(format!("queue {}", stock.name())),
+            },
+            Variable::Flow(flow) => (&flow.name, flow.equation.as_ref()),
+            Variable::Auxiliary(aux) => (&aux.name, Some(&aux.equation)),
+            Variable::GraphicalFunction(gf) => (gf.name.as_ref()?, gf.equation.as_ref()),
+            _ => return None,
+        };
+        equation
+            .is_some_and(pulses)
+            .then(|| format!("PULSE in {name}"))
+    })
+}
+
+fn unsupported(variable: &Identifier, construct: &str) -> SimulationError {
+    SimulationError::Unsupported {
+        variable: variable.to_string(),
+        construct: construct.to_string(),
+    }
+}
+
+#[cfg(feature = "arrays")]
+fn is_arrayed(variable: &Variable) -> bool {
+    match variable {
+        Variable::Auxiliary(aux) => aux.dimensions.is_some() || !aux.elements.is_empty(),
+        Variable::Flow(flow) => flow.dimensions.is_some() || !flow.elements.is_empty(),
+        Variable::Stock(stock) => match stock.as_ref() {
+            Stock::Basic(stock) => stock.dimensions.is_some() || !stock.elements.is_empty(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(not(feature = "arrays"))]
+fn is_arrayed(_variable: &Variable) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equation::double_double::DoubleDouble;
+    use crate::xml::XmileFile;
+
+    fn simulator(specs: &str, variables: &str) -> Result<Simulator, SimulationError> {
+        let file = XmileFile::from_str(&format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+                {specs}
+                <model><variables>{variables}</variables></model>
+            </xmile>"#
+        ))
+        .unwrap();
+        Simulator::new(&file.models[0], file.sim_specs.as_ref().unwrap())
+    }
+
+    #[test]
+    fn test_run_orders_equations() {
+        // Listed so that each auxiliary comes before the ones it uses
+        let simulator = simulator(
+            "<sim_specs><start>0</start><stop>1</stop><dt>0.25</dt></sim_specs>",
+            r#"<flow name="Drain"><eqn>Tank * Fraction</eqn></flow>
+               <aux name="Fraction"><eqn>Half / Capacity</eqn></aux>
+               <aux name="Half"><eqn>Capacity / 2</eqn></aux>
+               <aux name="Capacity"><eqn>Initial</eqn></aux>
+               <stock name="Tank"><eqn>Initial</eqn><outflow>Drain</outflow></stock>
+               <aux name="Initial"><eqn>IF TIME &gt;= 0.5 THEN 1 ELSE 2</eqn></aux>"#,
+        )
+        .unwrap();
+        assert_eq!(simulator.steps(), 4);
+        let run = simulator.run().unwrap();
+        assert_eq!(run.times, [0.0, 0.25, 0.5, 0.75, 1.0]);
+        // The tank starts at the initial value at TIME = 0, and loses half
+        // of itself per unit of time
+        assert_eq!(
+            run.series("Tank").unwrap(),
+            [2.0, 1.75, 1.53125, 1.33984375, 1.17236328125]
+        );
+        assert_eq!(run.series("Fraction").unwrap()[2], 0.5);
+        assert_eq!(run.series("Drain").unwrap()[0], 1.0);
+    }
+
+    #[test]
+    fn test_builtins_and_graphical_functions() {
+        let simulator = simulator(
+            "<sim_specs><start>0</start><stop>4</stop><dt>0.5</dt></sim_specs>",
+            r#"<stock name="Received"><eqn>0</eqn><inflow>Delivery</inflow><non_negative/></stock>
+               <flow name="Delivery"><eqn>PULSE(10, 1, 2) - STEP(1, 3)</eqn><non_negative/></flow>
+               <aux name="Effect"><eqn>Curve(Received)</eqn></aux>
+               <gf name="Curve"><xscale min="0" max="20"/><ypts>0,1</ypts></gf>
+               <gf name="Growth"><eqn>TIME</eqn><xscale min="0" max="4"/><ypts>0,2</ypts></gf>
+               <aux name="Ramp"><eqn>RAMP(2, 1, 2) + SAFEDIV(1, 0, 5) + MIN(PI, 3)</eqn></aux>"#,
+        )
+        .unwrap();
+        let run = simulator.run().unwrap();
+        // Pulses at 1 and 3 of 10 / DT for one DT, less the step from 3
+        assert_eq!(
+            run.series("Delivery").unwrap(),
+            [0.0, 0.0, 20.0, 0.0, 0.0, 0.0, 19.0, 0.0, 0.0]
+        );
+        assert_eq!(run.series("Received").unwrap()[8], 19.5);
+        assert!((run.series("Effect").unwrap()[8] - 0.975).abs() < 1e-12);
+        assert!((run.series("Growth").unwrap()[3] - 0.75).abs() < 1e-12);
+        assert_eq!(
+            run.series("Ramp").unwrap(),
+            [8.0, 8.0, 8.0, 9.0, 10.0, 10.0, 10.0, 10.0, 10.0]
+        );
+    }
+
+    #[test]
+    fn test_integration_methods() {
+        // Exponential decay over one step of 0.5, towards exp(-0.5)
+        let decay = |method: &str| {
+            let simulator = simulator(
+                &format!(r#"<sim_specs method="{method}"><start>0</start><stop>0.5</stop><dt>0.5</dt></sim_specs>"#),
+                r#"<stock name="Y"><eqn>1</eqn><outflow>Decay</outflow></stock>
+                   <flow name="Decay"><eqn>Y</eqn></flow>"#,
+            )
+            .unwrap();
+            let run = simulator.run().unwrap();
+            (simulator.method(), run.series("Y").unwrap()[1])
+        };
+        assert_eq!(decay("euler"), (IntegrationMethod::Euler, 0.5));
+        assert_eq!(decay("RK2"), (IntegrationMethod::Rk2, 0.625));
+        let (method, y) = decay("Runge-Kutta 4");
+        assert_eq!(method, IntegrationMethod::Rk4);
+        assert!((y - 0.606_770_833_333_333_3).abs() < 1e-12);
+        assert!((y - (-0.5f64).exp()).abs() < 3e-4);
+
+        assert!(matches!(
+            simulator(
+                r#"<sim_specs method="gear"><start>0</start><stop>1</stop></sim_specs>"#,
+                r#"<aux name="A"><eqn>1</eqn></aux>"#
+            ),
+            Err(SimulationError::InvalidSpecs(_))
+        ));
+    }
+
+    #[test]
+    fn test_run_over_double_doubles() {
+        let simulator = simulator(
+            r#"<sim_specs method="rk4"><start>0</start><stop>4</stop><dt>1</dt></sim_specs>"#,
+            r#"<stock name="Balance"><eqn>1e16</eqn><inflow>Interest</inflow></stock>
+               <flow name="Interest"><eqn>STEP(1, 0) + RAMP(0, 0)</eqn></flow>"#,
+        )
+        .unwrap();
+        // Each unit of interest is lost to rounding in f64
+        assert_eq!(simulator.run().unwrap().series("Balance").unwrap()[4], 1e16);
+        let names: Vec<&str> = simulator.names().iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        simulator.run_over::<DoubleDouble>(&mut recorder).unwrap();
+        let run = recorder.into_data();
+        assert_eq!(run.series("Balance").unwrap()[4], 1e16 + 4.0);
+    }
+
+    #[test]
+    fn test_adaptive_steps() {
+        let adaptive = simulator(
+            r#"<sim_specs method="rk45"><start>0</start><stop>2</stop><dt>0.5</dt></sim_specs>"#,
+            r#"<stock name="Y"><eqn>1</eqn><outflow>Decay</outflow></stock>
+               <flow name="Decay"><eqn>Y</eqn></flow>"#,
+        )
+        .unwrap();
+        assert_eq!(adaptive.tolerance(), DEFAULT_TOLERANCE);
+        let names: Vec<&str> = adaptive.names().iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        let sizes = adaptive.run_with_step_sizes(&mut recorder).unwrap();
+        let run = recorder.into_data();
+
+        // Values are saved every DT, whatever steps were taken
+        assert_eq!(run.times, [0.0, 0.5, 1.0, 1.5, 2.0]);
+        for (time, y) in run.times.iter().zip(run.series("Y").unwrap()) {
+            assert!((y - (-time).exp()).abs() < 1e-4, "{y} at {time}");
+        }
+        assert!(sizes.iter().all(|&size| size > 0.0));
+        assert!((sizes.iter().sum::<f64>() - 2.0).abs() < 1e-9);
+        // A tighter tolerance takes more, shorter steps
+        let tight = adaptive.clone().with_tolerance(1e-10);
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        assert!(tight.run_with_step_sizes(&mut recorder).unwrap().len() > sizes.len());
+
+        assert!(matches!(
+            simulator(
+                r#"<sim_specs method="rk45"><start>0</start><stop>1</stop></sim_specs>"#,
+                r#"<aux name="A"><eqn>PULSE(1, 0)</eqn></aux>"#
+            ),
+            Err(SimulationError::InvalidSpecs(message)) if message.contains("PULSE in A")
+        ));
+    }
+
+    #[test]
+    fn test_discrete_steps() {
+        let run = |method: &str| {
+            let simulator = simulator(
+                &format!(r#"<sim_specs method="{method}"><start>0</start><stop>1.8</stop><dt>0.3</dt></sim_specs>"#),
+                r#"<aux name="Switch"><eqn>STEP(1, 0.9)</eqn></aux>
+                   <stock name="Received"><eqn>0</eqn><inflow>Delivery</inflow></stock>
+                   <flow name="Delivery"><eqn>PULSE(3, 0.9, 0.6)</eqn></flow>"#,
+            )
+            .unwrap();
+            simulator.run().unwrap()
+        };
+
+        // 3 * 0.3 is just under 0.9, but times are exact, so Euler's method
+        // switches on time too
+        let euler = run("euler");
+        assert_eq!(euler.times[3], 0.9);
+        assert_eq!(euler.series("Switch").unwrap()[3], 1.0);
+
+        let discrete = run("discrete");
+        assert_eq!(discrete.times, [0.0, 0.3, 0.6, 0.9, 1.2, 1.5, 1.8]);
+        assert_eq!(
+            discrete.series("Switch").unwrap(),
+            [0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]
+        );
+        let delivery = discrete.series("Delivery").unwrap();
+        assert_eq!(delivery[3], 10.0);
+        assert_eq!(delivery[5], 10.0);
+        assert_eq!(delivery.iter().filter(|&&rate| rate != 0.0).count(), 2);
+        assert!((discrete.series("Received").unwrap()[6] - 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_exact_times() {
+        let simulator = simulator(
+            r#"<sim_specs><start>0</start><stop>1</stop><dt reciprocal="true">10</dt></sim_specs>"#,
+            r#"<aux name="Switch"><eqn>STEP(1, 0.7)</eqn></aux>"#,
+        )
+        .unwrap();
+        assert_eq!(simulator.dt(), 0.1);
+        let run = simulator.run().unwrap();
+        // 7 * 0.1 is 0.7000000000000001
+        assert_eq!(run.times[7], 0.7);
+        assert_eq!(run.series("Switch").unwrap()[7], 1.0);
+        assert_eq!(run.steps, (0..=10).collect::<Vec<_>>());
+        assert_eq!(simulator.step(0.7), Some(7));
+        assert_eq!(simulator.step(0.75), None);
+        assert_eq!(simulator.step(1.1), None);
+    }
+
+    #[test]
+    fn test_stateful_builtins() {
+        let stateful = simulator(
+            "<sim_specs><start>0</start><stop>3</stop><dt>1</dt></sim_specs>",
+            r#"<aux name="Input"><eqn>10</eqn></aux>
+               <aux name="Smoothed"><eqn>SMTH1(Input, 1, 0) + SMTH1(Input, 2, 0)</eqn></aux>
+               <aux name="Delayed"><eqn>DELAY1(Input, 2, 0)</eqn></aux>
+               <aux name="Chained"><eqn>SMTHN(Input, 2, 2, 0)</eqn></aux>
+               <aux name="Settled"><eqn>DELAY3(Input, 3)</eqn></aux>
+               <aux name="Trend"><eqn>TREND(Input, 2, 0.1)</eqn></aux>
+               <aux name="Forecast"><eqn>FORCST(Input, 2, 5, 0.1)</eqn></aux>"#,
+        )
+        .unwrap();
+        // Hidden stocks are integrated but not saved
+        assert_eq!(stateful.names().len(), 7);
+        let run = stateful.run().unwrap();
+        // Each call site keeps its own state
+        assert_eq!(run.series("Smoothed").unwrap(), [0.0, 15.0, 17.5, 18.75]);
+        assert_eq!(run.series("Delayed").unwrap(), [0.0, 5.0, 7.5, 8.75]);
+        assert_eq!(run.series("Chained").unwrap(), [0.0, 0.0, 10.0, 10.0]);
+        // A delay started at its input stays in equilibrium
+        assert_eq!(run.series("Settled").unwrap(), [10.0; 4]);
+        assert!((run.series("Trend").unwrap()[0] - 0.1).abs() < 1e-12);
+        assert!((run.series("Forecast").unwrap()[0] - 15.0).abs() < 1e-12);
+
+        assert!(matches!(
+            simulator(
+                "<sim_specs><start>0</start><stop>1</stop></sim_specs>",
+                r#"<aux name="A"><eqn>DELAYN(TIME, 2, A)</eqn></aux>"#
+            ),
+            Err(SimulationError::Unsupported { construct, .. }) if construct.starts_with("DELAYN")
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_models() {
+        let specs = "<sim_specs><start>0</start><stop>1</stop></sim_specs>";
+        assert!(matches!(
+            simulator(specs, r#"<aux name="A"><eqn>B + 1</eqn></aux><aux name="B"><eqn>A</eqn></aux>"#),
+            Err(SimulationError::Circular(names)) if names == ["A", "B", "A"]
+        ));
+        assert!(matches!(
+            simulator(specs, r#"<aux name="A"><eqn>Missing</eqn></aux>"#),
+            Err(SimulationError::UnknownVariable { name, .. }) if name == "Missing"
+        ));
+        assert!(matches!(
+            simulator(specs, r#"<aux name="A"><eqn>DELAY(TIME, 2)</eqn></aux>"#),
+            Err(SimulationError::Unsupported { construct, .. }) if construct == "DELAY"
+        ));
+        assert!(matches!(
+            simulator(specs, r#"<aux name="A"><eqn>ABS(1, 2)</eqn></aux>"#),
+            Err(SimulationError::Arity { count: 2, .. })
+        ));
+        let model: Model = quick_xml::de::from_str(
+            r#"<model><variables><aux name="A"><eqn>1</eqn></aux></variables></model>"#,
+        )
+        .unwrap();
+        let mut invalid = SimulationSpecs {
+            start: 1.0,
+            stop: 0.0,
+            dt: None,
+            method: None,
+            time_units: None,
+            pause: None,
+            run_by: None,
+        };
+        assert!(matches!(
+            Simulator::new(&model, &invalid),
+            Err(SimulationError::InvalidSpecs(_))
+        ));
+        invalid.stop = 2.0;
+        invalid.dt = Some(0.0);
+        assert!(matches!(
+            Simulator::new(&model, &invalid),
+            Err(SimulationError::InvalidSpecs(_))
+        ));
+        // Option filters are not applied by runs
+        let file = XmileFile::from_str(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                <header>
+                    <vendor>Test</vendor><product version="1.0">Test</product>
+                    <options><uses_macros recursive_macros="false" option_filters="true"/></options>
+                </header>
+                <model><variables><aux name="A"><eqn>1</eqn></aux></variables></model>
+            </xmile>"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            Simulator::from_file(&file, &invalid),
+            Err(SimulationError::Unsupported { construct, .. }) if construct.contains("option_filters")
+        ));
+        // A stock breaks the loop between its outflow and its initial value
+        simulator(
+            specs,
+            r#"<stock name="S"><eqn>F</eqn><outflow>F</outflow></stock><flow name="F"><eqn>1</eqn></flow>"#,
+        )
+        .unwrap();
+    }
+}
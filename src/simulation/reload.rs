@@ -0,0 +1,227 @@
+//! Runs advanced a step at a time, and hot reloading of their models.
+//!
+//! A [`Run`] holds the values of a run between steps. While it is in
+//! progress, [`Simulator::swap_model`] can replace the model under it with
+//! an edited one: stocks present in both models keep their values, stocks
+//! only in the new model start from their initial equations evaluated at
+//! the current time, and stocks only in the old model are dropped. Stocks
+//! are matched by name, as identifiers, so renaming `Work_In_Progress` to
+//! `work in progress` keeps its value.
+
+use crate::data::SaveStepSink;
+use crate::specs::IntegrationMethod;
+use crate::xml::schema::Model;
+
+use super::{Equation, SimulationError, Simulator};
+
+/// A run in progress, started by [`Simulator::begin`].
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// The next step to save.
+    step: usize,
+    /// The value of every slot, with the stocks at the next step.
+    values: Vec<f64>,
+}
+
+impl Run {
+    /// The next step to save, which is one past the last step of the run
+    /// once it has finished.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+}
+
+/// How the stocks of a run were carried over to a new model, by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Migration {
+    /// The stocks in both models, which kept their values.
+    pub kept: Vec<String>,
+    /// The stocks only in the new model, which were given their initial
+    /// values.
+    pub initialized: Vec<String>,
+    /// The stocks only in the old model, whose values were dropped.
+    pub dropped: Vec<String>,
+}
+
+impl Simulator {
+    /// Starts a run, to be advanced with [`advance`](Self::advance).
+    ///
+    /// Fails for adaptive steps, which do not move a step of DT at a time.
+    pub fn begin(&self) -> Result<Run, SimulationError> {
+        if self.method == IntegrationMethod::Rk45 {
+            return Err(SimulationError::InvalidSpecs(format!(
+                "{} steps cannot be taken one at a time",
+                self.method
+            )));
+        }
+        let mut values = vec![0.0; self.slots.len()];
+        self.initialize(&mut values, self.start, &[]);
+        Ok(Run { step: 0, values })
+    }
+
+    /// Saves the next step of `run` to `sink`, as
+    /// [`run_with`](Self::run_with) does, and moves its stocks on to the
+    /// step after. Returns whether there are steps left; the sink is
+    /// finished after the last.
+    pub fn advance(
+        &self,
+        run: &mut Run,
+        sink: &mut dyn SaveStepSink,
+    ) -> Result<bool, SimulationError> {
+        if run.step > self.steps {
+            return Ok(false);
+        }
+        let time = self.time(run.step);
+        self.compute(&mut run.values, time);
+        self.save(sink, time, &run.values)?;
+        run.step += 1;
+        if run.step > self.steps {
+            sink.finish()?;
+            return Ok(false);
+        }
+        self.integrate(&mut run.values, time);
+        Ok(true)
+    }
+
+    /// Replaces the model under `run` with `model`, run with the same specs
+    /// and tolerance, and reports what happened to each of the stocks.
+    ///
+    /// Stocks present in both models keep their values. The initial values
+    /// of new stocks are computed at the time of the next step, so they may
+    /// depend on the stocks carried over. The hidden stocks of delays and
+    /// smooths are carried over in the same way, and are not reported. The
+    /// simulator and run are left unchanged if `model` cannot be
+    /// simulated.
+    pub fn swap_model(
+        &mut self,
+        model: &Model,
+        run: &mut Run,
+    ) -> Result<Migration, SimulationError> {
+        let swapped = Simulator::new(model, &self.specs)?.with_tolerance(self.tolerance);
+        let time = swapped.time(run.step.min(swapped.steps));
+
+        let mut migration = Migration::default();
+        let mut values = vec![0.0; swapped.slots.len()];
+        let mut kept = Vec::new();
+        for &slot in &swapped.stocks {
+            let name = &swapped.slots[slot].name;
+            let saved = slot < swapped.names.len();
+            match self.index.get(name) {
+                Some(&old) if self.is_stock(old) => {
+                    values[slot] = run.values[old];
+                    kept.push(slot);
+                    if saved {
+                        migration.kept.push(name.to_string());
+                    }
+                }
+                _ if saved => migration.initialized.push(name.to_string()),
+                _ => {}
+            }
+        }
+        migration.dropped = self
+            .stocks
+            .iter()
+            .filter(|&&slot| slot < self.names.len())
+            .map(|&slot| &self.slots[slot].name)
+            .filter(|name| {
+                !swapped
+                    .index
+                    .get(*name)
+                    .is_some_and(|&slot| swapped.is_stock(slot))
+            })
+            .map(ToString::to_string)
+            .collect();
+        swapped.initialize(&mut values, time, &kept);
+
+        *self = swapped;
+        run.values = values;
+        Ok(migration)
+    }
+
+    fn is_stock(&self, slot: usize) -> bool {
+        matches!(self.slots[slot].equation, Equation::Stock { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ResultRecorder, Retention};
+    use crate::xml::XmileFile;
+
+    fn file(variables: &str) -> XmileFile {
+        XmileFile::from_str(&format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+                <sim_specs><start>0</start><stop>4</stop><dt>1</dt></sim_specs>
+                <model><variables>{variables}</variables></model>
+            </xmile>"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_advance_matches_run() {
+        let file = file(
+            r#"<stock name="Population"><eqn>100</eqn><inflow>Births</inflow></stock>
+               <flow name="Births"><eqn>Population * 0.25</eqn></flow>"#,
+        );
+        let simulator = Simulator::new(&file.models[0], file.sim_specs.as_ref().unwrap()).unwrap();
+        let names: Vec<&str> = simulator.names().iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        let mut run = simulator.begin().unwrap();
+        while simulator.advance(&mut run, &mut recorder).unwrap() {}
+        assert_eq!(run.step(), 5);
+        assert!(!simulator.advance(&mut run, &mut recorder).unwrap());
+        assert_eq!(recorder.into_data(), simulator.run().unwrap());
+    }
+
+    #[test]
+    fn test_swap_model() {
+        let old = file(
+            r#"<stock name="Population"><eqn>100</eqn><inflow>Births</inflow></stock>
+               <flow name="Births"><eqn>Population * 0.5</eqn></flow>
+               <stock name="Removed"><eqn>1</eqn></stock>"#,
+        );
+        let specs = old.sim_specs.as_ref().unwrap();
+        let mut simulator = Simulator::new(&old.models[0], specs).unwrap();
+        let names: Vec<&str> = simulator.names().iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        let mut run = simulator.begin().unwrap();
+        simulator.advance(&mut run, &mut recorder).unwrap();
+        simulator.advance(&mut run, &mut recorder).unwrap();
+
+        // Halve the birth rate two steps in, and start counting adults at
+        // a tenth of the population
+        let new = file(
+            r#"<stock name="population"><eqn>1</eqn><inflow>Births</inflow></stock>
+               <flow name="Births"><eqn>Population * 0.25</eqn></flow>
+               <stock name="Adults"><eqn>Population / 10</eqn></stock>"#,
+        );
+        let migration = simulator.swap_model(&new.models[0], &mut run).unwrap();
+        assert_eq!(
+            migration,
+            Migration {
+                kept: vec!["population".to_string()],
+                initialized: vec!["Adults".to_string()],
+                dropped: vec!["Removed".to_string()],
+            }
+        );
+
+        let names: Vec<&str> = simulator.names().iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        while simulator.advance(&mut run, &mut recorder).unwrap() {}
+        let rest = recorder.into_data();
+        assert_eq!(rest.times, [2.0, 3.0, 4.0]);
+        assert_eq!(
+            rest.series("population").unwrap(),
+            [225.0, 281.25, 351.5625]
+        );
+        assert_eq!(rest.series("Adults").unwrap(), [22.5; 3]);
+
+        // A model that cannot be simulated leaves the run as it was
+        let broken = file(r#"<aux name="A"><eqn>Missing</eqn></aux>"#);
+        assert!(simulator.swap_model(&broken.models[0], &mut run).is_err());
+        assert_eq!(simulator.names(), ["population", "Births", "Adults"]);
+    }
+}
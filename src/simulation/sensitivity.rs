@@ -0,0 +1,127 @@
+//! Local sensitivities of a run to one of its constants.
+//!
+//! A run over [`Dual`] numbers carries, alongside every value, its
+//! derivative with respect to whichever input was seeded with
+//! [`Dual::variable`]. [`Simulator::sensitivity`] seeds a constant of the
+//! model and keeps it seeded at every step, so one run gives the exact
+//! derivative of every saved value with respect to that constant, with no
+//! step size to tune as with finite differences.
+
+use crate::Identifier;
+use crate::data::{ExportData, ResultRecorder, Retention, SaveStepSink};
+use crate::equation::dual::Dual;
+
+use super::{Equation, SimulationError, Simulator};
+
+impl Simulator {
+    /// Runs the model as [`run`](Self::run) does, returning instead of each
+    /// saved value its derivative with respect to `parameter`, an auxiliary
+    /// whose equation is a constant.
+    ///
+    /// Fails with [`SimulationError::NotConstant`] if the model has no such
+    /// auxiliary.
+    pub fn sensitivity(&self, parameter: &str) -> Result<ExportData, SimulationError> {
+        let not_constant = || SimulationError::NotConstant(parameter.to_string());
+        let name = Identifier::parse_from_attribute(parameter).map_err(|_| not_constant())?;
+        let slot = *self.index.get(&name).ok_or_else(not_constant)?;
+        let value = match &self.slots[slot].equation {
+            Equation::Expression(expression) => expression.evaluate::<f64>(&|_| None),
+            _ => None,
+        }
+        .ok_or_else(not_constant)?;
+
+        // The parameter is seeded once, and left out of the equations
+        // computed each step so that it stays seeded
+        let mut seeded = self.clone();
+        seeded.order.retain(|&other| other != slot);
+        let mut values = vec![Dual::constant(0.0); self.slots.len()];
+        values[slot] = Dual::variable(value);
+        seeded.initialize(&mut values, self.start, &[slot]);
+
+        let names: Vec<&str> = self.names.iter().map(String::as_str).collect();
+        let mut recorder = ResultRecorder::new(&names, Retention::All);
+        seeded.run_from(&mut values, &mut |time, values| {
+            let derivatives: Vec<f64> = values[..names.len()]
+                .iter()
+                .map(|value| value.derivative)
+                .collect();
+            recorder.save_step(time, &derivatives)?;
+            Ok(())
+        })?;
+        recorder.finish()?;
+        Ok(recorder.into_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::{SimulationError, Simulator};
+    use crate::xml::XmileFile;
+
+    fn simulator(rate: f64) -> Simulator {
+        let file = XmileFile::from_str(&format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+                <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+                <sim_specs method="rk4"><start>0</start><stop>5</stop><dt>0.5</dt></sim_specs>
+                <model><variables>
+                    <stock name="Population"><eqn>100</eqn><inflow>Births</inflow></stock>
+                    <flow name="Births"><eqn>Population * Birth_Rate</eqn></flow>
+                    <aux name="Birth_Rate"><eqn>{rate}</eqn></aux>
+                    <aux name="Doubled"><eqn>2 * Population</eqn></aux>
+                </variables></model>
+            </xmile>"#
+        ))
+        .unwrap();
+        Simulator::new(&file.models[0], file.sim_specs.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sensitivity_to_a_constant() {
+        let rate = 0.1;
+        let sensitivity = simulator(rate).sensitivity("birth rate").unwrap();
+        let population = sensitivity.series("Population").unwrap();
+        assert_eq!(sensitivity.times.len(), 11);
+        assert_eq!(population[0], 0.0);
+
+        // Population grows as 100 * exp(rate * t), so its derivative with
+        // respect to the rate is 100 * t * exp(rate * t)
+        for (&time, &derivative) in sensitivity.times.iter().zip(population) {
+            let exact = 100.0 * time * (rate * time).exp();
+            assert!((derivative - exact).abs() < 1e-3 * exact.max(1.0), "{time}");
+        }
+
+        // And matches a central difference of two runs
+        let h = 1e-6;
+        let run = |rate| simulator(rate).run().unwrap();
+        let (above, below) = (run(rate + h), run(rate - h));
+        let difference = |name: &str| {
+            let (above, below) = (above.series(name).unwrap(), below.series(name).unwrap());
+            above
+                .iter()
+                .zip(below)
+                .map(|(a, b)| (a - b) / (2.0 * h))
+                .collect::<Vec<_>>()
+        };
+        for name in ["Population", "Births", "Doubled"] {
+            for (derivative, difference) in sensitivity
+                .series(name)
+                .unwrap()
+                .iter()
+                .zip(difference(name))
+            {
+                assert!(
+                    (derivative - difference).abs() < 1e-4 * difference.abs().max(1.0),
+                    "{name}"
+                );
+            }
+        }
+        assert_eq!(sensitivity.series("Birth_Rate").unwrap(), [1.0; 11]);
+
+        for parameter in ["Population", "Doubled", "Missing"] {
+            assert!(matches!(
+                simulator(rate).sensitivity(parameter),
+                Err(SimulationError::NotConstant(name)) if name == parameter
+            ));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Builtins that keep state between steps.
+//!
+//! Delays (`DELAY1`, `DELAY3`, `DELAYN`), smooths (`SMTH1`, `SMTH3`,
+//! `SMTHN`), `TREND` and `FORCST` are expanded before a run into stocks of
+//! their own. Each call site gets its own chain of hidden stocks, which are
+//! initialised with the model's stocks and integrated every DT alongside
+//! them, and the call is replaced by an equation reading the chain:
+//!
+//! - an nth-order smooth is a chain of n first-order smooths, each over
+//!   1/n of the averaging time, starting at the initial value;
+//! - an nth-order delay is a chain of n stocks, each draining over 1/n of
+//!   the delay time and starting with the initial value's worth of material
+//!   in transit;
+//! - `TREND` compares the input with a first-order smooth of it, started so
+//!   that the trend is the given initial trend, and `FORCST` extrapolates
+//!   the input by the trend over the horizon.
+//!
+//! The order of `DELAYN` and `SMTHN` fixes the number of stocks, so it must
+//! be a number.
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::ops::RangeInclusive;
+
+use crate::Identifier;
+use crate::equation::expression::function::FunctionTarget;
+use crate::equation::{Expression, NumericConstant};
+
+use super::{SimulationError, unsupported};
+
+/// A stock added for a call site of a stateful builtin.
+#[derive(Debug, Clone)]
+pub(super) struct HiddenStock {
+    pub name: Identifier,
+    pub initial: Expression,
+    pub net: Expression,
+}
+
+/// The number of arguments a stateful builtin takes, or `None` if `builtin`
+/// keeps no state.
+pub(super) fn arity(builtin: &str) -> Option<RangeInclusive<usize>> {
+    match builtin {
+        "DELAY1" | "DELAY3" | "SMTH1" | "SMTH3" | "TREND" => Some(2..=3),
+        "DELAYN" | "SMTHN" | "FORCST" => Some(3..=4),
+        _ => None,
+    }
+}
+
+/// Replaces the stateful builtins in a model's equations with hidden stocks.
+pub(super) struct Expander<'a> {
+    /// Graphical functions, which take precedence over builtins.
+    functions: &'a HashMap<Identifier, usize>,
+    /// Names in use, by model variables or hidden stocks.
+    taken: HashSet<Identifier>,
+    pub stocks: Vec<HiddenStock>,
+}
+
+impl<'a> Expander<'a> {
+    pub fn new(
+        functions: &'a HashMap<Identifier, usize>,
+        taken: impl IntoIterator<Item = Identifier>,
+    ) -> Self {
+        Expander {
+            functions,
+            taken: taken.into_iter().collect(),
+            stocks: Vec::new(),
+        }
+    }
+
+    /// Expands the stateful builtins in `expression`, the equation of
+    /// `variable`, innermost first.
+    pub fn expand(
+        &mut self,
+        variable: &Identifier,
+        mut expression: Expression,
+    ) -> Result<Expression, SimulationError> {
+        for child in expression.children_mut() {
+            let placeholder = Expression::Constant(NumericConstant(0.0));
+            *child = self.expand(variable, mem::replace(child, placeholder))?;
+        }
+
+        let builtin = match &expression {
+            Expression::FunctionCall {
+                target: FunctionTarget::Function(name),
+                ..
+            } if !self.functions.contains_key(name) => name.normalized().to_ascii_uppercase(),
+            _ => return Ok(expression),
+        };
+        let Some(arity) = arity(&builtin) else {
+            return Ok(expression);
+        };
+        let Expression::FunctionCall {
+            target: FunctionTarget::Function(name),
+            parameters,
+        } = expression
+        else {
+            unreachable!("only function calls name builtins");
+        };
+        if !arity.contains(&parameters.len()) {
+            return Err(SimulationError::Arity {
+                variable: variable.to_string(),
+                function: name.to_string(),
+                count: parameters.len(),
+            });
+        }
+
+        let [input, time, rest @ ..] = parameters.as_slice() else {
+            unreachable!("stateful builtins take at least two arguments");
+        };
+        let (input, time) = (input.clone(), time.clone());
+        let zero = || Expression::Constant(NumericConstant(0.0));
+        Ok(match builtin.as_str() {
+            "SMTH1" | "SMTH3" | "DELAY1" | "DELAY3" => {
+                let order = if builtin.ends_with('1') { 1 } else { 3 };
+                let initial = rest.first().cloned().unwrap_or_else(|| input.clone());
+                if builtin.starts_with("SMTH") {
+                    self.smooth(&builtin, input, time, order, initial)
+                } else {
+                    self.delay(&builtin, input, time, order, initial)
+                }
+            }
+            "SMTHN" | "DELAYN" => {
+                let order = order(&rest[0]).ok_or_else(|| {
+                    unsupported(
+                        variable,
+                        &format!("{builtin} with an order that is not a number"),
+                    )
+                })?;
+                let initial = rest.get(1).cloned().unwrap_or_else(|| input.clone());
+                if builtin == "SMTHN" {
+                    self.smooth(&builtin, input, time, order, initial)
+                } else {
+                    self.delay(&builtin, input, time, order, initial)
+                }
+            }
+            "TREND" => {
+                let trend = rest.first().cloned().unwrap_or_else(zero);
+                self.trend(input, time, trend)
+            }
+            _ => {
+                let horizon = rest[0].clone();
+                let trend = rest.get(1).cloned().unwrap_or_else(zero);
+                let trend = self.trend(input.clone(), time, trend);
+                // input * (1 + trend * horizon)
+                Expression::multiply(
+                    input,
+                    Expression::binary_add(
+                        Expression::Constant(NumericConstant(1.0)),
+                        Expression::multiply(trend, horizon),
+                    ),
+                )
+            }
+        })
+    }
+
+    /// A chain of `order` first-order smooths of `input`, returning the
+    /// output of the last.
+    fn smooth(
+        &mut self,
+        builtin: &str,
+        input: Expression,
+        time: Expression,
+        order: usize,
+        initial: Expression,
+    ) -> Expression {
+        let stage_time = stage_time(time, order);
+        let mut output = input;
+        for _ in 0..order {
+            let level = self.stock(builtin, initial.clone(), |level| {
+                // (output - level) / stage time
+                Expression::divide(Expression::subtract(output, level), stage_time.clone())
+            });
+            output = level;
+        }
+        output
+    }
+
+    /// A chain of `order` stocks each draining into the next, returning the
+    /// outflow of the last.
+    fn delay(
+        &mut self,
+        builtin: &str,
+        input: Expression,
+        time: Expression,
+        order: usize,
+        initial: Expression,
+    ) -> Expression {
+        let stage_time = stage_time(time, order);
+        let in_transit = Expression::multiply(initial, stage_time.clone());
+        let mut output = input;
+        for _ in 0..order {
+            let level = self.stock(builtin, in_transit.clone(), |level| {
+                Expression::subtract(output, Expression::divide(level, stage_time.clone()))
+            });
+            output = Expression::divide(level, stage_time.clone());
+        }
+        output
+    }
+
+    /// The fractional rate of change of `input` over `time`.
+    fn trend(&mut self, input: Expression, time: Expression, trend: Expression) -> Expression {
+        // Started at input / (1 + trend * time), so the trend starts at trend
+        let initial = Expression::divide(
+            input.clone(),
+            Expression::binary_add(
+                Expression::Constant(NumericConstant(1.0)),
+                Expression::multiply(trend, time.clone()),
+            ),
+        );
+        let average = self.smooth("TREND", input.clone(), time.clone(), 1, initial);
+        // (input - average) / (average * time)
+        Expression::divide(
+            Expression::subtract(input, average.clone()),
+            Expression::multiply(average, time),
+        )
+    }
+
+    /// Adds a hidden stock with the net flow `net` builds from a reference
+    /// to it, and returns the reference.
+    fn stock(
+        &mut self,
+        builtin: &str,
+        initial: Expression,
+        net: impl FnOnce(Expression) -> Expression,
+    ) -> Expression {
+        let name = (self.stocks.len() + 1..)
+            .map(|n| {
+                Identifier::parse_from_attribute(&format!("#{builtin} {n}"))
+                    .expect("hidden stock names are valid identifiers")
+            })
+            .find(|name| !self.taken.contains(name))
+            .expect("some hidden stock name is free");
+        self.taken.insert(name.clone());
+        let level = Expression::Subscript(name.clone(), vec![]);
+        self.stocks.push(HiddenStock {
+            name,
+            initial,
+            net: net(level.clone()),
+        });
+        level
+    }
+}
+
+/// The time constant of each of `order` stages sharing `time`.
+fn stage_time(time: Expression, order: usize) -> Expression {
+    if order == 1 {
+        time
+    } else {
+        Expression::divide(time, Expression::Constant(NumericConstant(order as f64)))
+    }
+}
+
+/// The order of `DELAYN` or `SMTHN`, if `expression` is a number. Orders
+/// are rounded, and are at least one.
+fn order(expression: &Expression) -> Option<usize> {
+    match expression {
+        Expression::Constant(constant) if constant.0.is_finite() => {
+            Some(constant.0.round().max(1.0) as usize)
+        }
+        Expression::Parentheses(inner) | Expression::UnaryPlus(inner) => order(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,122 @@
+//! Exact times.
+//!
+//! Multiplying a step count by a DT such as 0.3 gives times like
+//! 0.8999999999999999 rather than 0.9, which would put an event at 0.9 a
+//! step late. Runs instead hold DT as a fraction where they can, such as
+//! a DT given by its reciprocal, and compute each time from the whole
+//! number of steps taken, rounding once.
+//!
+//! A discrete run treats its equations as difference equations, so events
+//! such as `STEP` and `PULSE` must happen at exactly the step they name;
+//! it compares event times by the step they fall on.
+
+use crate::specs::IntegrationMethod;
+
+use super::Simulator;
+
+/// How far from a whole number of steps an event time may be and still
+/// count as falling on that step.
+pub(super) const ON_STEP: f64 = 1e-9;
+
+/// A positive fraction.
+#[cfg_attr(feature = "artifacts", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Ratio {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Ratio {
+    /// The largest denominator tried when approximating a number.
+    const MAX_DENOMINATOR: u64 = 1_000_000;
+
+    /// The fraction with the smallest denominator that rounds to `value`,
+    /// found from the continued fraction of `value`. Fails for values that
+    /// are not positive, or that need a denominator over a million.
+    pub fn approximate(value: f64) -> Option<Ratio> {
+        if !(value > 0.0 && value.is_finite()) {
+            return None;
+        }
+        // The last two convergents, starting from 0/1 and 1/0
+        let (mut numerators, mut denominators) = ((0u64, 1u64), (1u64, 0u64));
+        let mut rest = value;
+        loop {
+            let whole = rest.floor();
+            if whole > u32::MAX as f64 {
+                return None;
+            }
+            let whole = whole as u64;
+            let numerator = whole.checked_mul(numerators.1)?.checked_add(numerators.0)?;
+            let denominator = whole
+                .checked_mul(denominators.1)?
+                .checked_add(denominators.0)?;
+            if denominator > Self::MAX_DENOMINATOR {
+                return None;
+            }
+            let ratio = Ratio {
+                numerator,
+                denominator,
+            };
+            if (ratio.value() - value).abs() <= value * f64::EPSILON {
+                return Some(ratio);
+            }
+            numerators = (numerators.1, numerator);
+            denominators = (denominators.1, denominator);
+            rest = 1.0 / (rest - whole as f64);
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// `count` times this fraction, rounded once.
+    pub fn times(&self, count: usize) -> f64 {
+        count as f64 * self.numerator as f64 / self.denominator as f64
+    }
+}
+
+impl Simulator {
+    /// The step a discrete run is at when the time is `time`, or `None` if
+    /// the run is not discrete.
+    pub(super) fn discrete_step(&self, time: f64) -> Option<f64> {
+        (self.method == IntegrationMethod::Discrete)
+            .then(|| ((time - self.start) / self.dt).round())
+    }
+
+    /// The first step at or after `time`.
+    pub(super) fn first_step_from(&self, time: f64) -> f64 {
+        let steps = (time - self.start) / self.dt;
+        if (steps - steps.round()).abs() < ON_STEP {
+            steps.round()
+        } else {
+            steps.ceil()
+        }
+    }
+
+    /// The whole number of steps nearest `interval`, at least one.
+    pub(super) fn steps_in(&self, interval: f64) -> f64 {
+        (interval / self.dt).round().max(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approximate() {
+        let ratio = Ratio::approximate(0.3).unwrap();
+        assert_eq!((ratio.numerator, ratio.denominator), (3, 10));
+        assert_eq!(ratio.times(3), 0.9);
+        assert_ne!(3.0 * 0.3, 0.9);
+        let third = Ratio::approximate(1.0 / 3.0).unwrap();
+        assert_eq!((third.numerator, third.denominator), (1, 3));
+        assert_eq!(
+            Ratio::approximate(2.5).map(|ratio| ratio.value()),
+            Some(2.5)
+        );
+        assert_eq!(Ratio::approximate(std::f64::consts::PI), None);
+        assert_eq!(Ratio::approximate(0.0), None);
+    }
+}
@@ -10,7 +10,12 @@
 // ·         Pause interval:  pause="…" w/interval (default: infinity – can be ignored)
 // ·         Run selected groups or modules:  <run by="…"> with run type either:  all, group, or module (default: all, i.e., run whole-model).  Which groups or modules to run are identified by run attributes on the group or model.
 
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::xml::serialize;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SimulationSpecs {
@@ -18,14 +23,206 @@ pub struct SimulationSpecs {
     pub start: f64,
     /// The stop time of the simulation.
     pub stop: f64,
-    /// The step size (DT) of the simulation.
+    /// The step size (DT) of the simulation. A DT given as its reciprocal,
+    /// with `reciprocal="true"`, is read as one over the value given.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_dt",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub dt: Option<f64>,
     /// The integration method used in the simulation.
+    #[serde(rename = "@method", skip_serializing_if = "skip_method")]
     pub method: Option<String>,
-    /// The unit of time for the simulation.
+    /// The unit of time for the simulation, from the `time_units`
+    /// attribute. A `<time_units>` element, as pre-standard files have, is
+    /// also read.
+    #[serde(
+        rename = "@time_units",
+        alias = "time_units",
+        skip_serializing_if = "skip_time_units"
+    )]
     pub time_units: Option<String>,
     /// The pause interval for the simulation.
+    #[serde(rename = "@pause", skip_serializing_if = "Option::is_none")]
     pub pause: Option<f64>,
     /// The run type for the simulation (e.g., all, group, module).
+    #[serde(rename = "@run_by", skip_serializing_if = "skip_run_by")]
     pub run_by: Option<String>,
 }
+
+impl SimulationSpecs {
+    /// The integration method named by `method`, Euler if none is given.
+    ///
+    /// Fails for methods that are not supported, such as the XMILE name
+    /// `gear`.
+    pub fn integration_method(&self) -> Result<IntegrationMethod, String> {
+        self.method.as_deref().map_or(
+            Ok(IntegrationMethod::Euler),
+            str::parse::<IntegrationMethod>,
+        )
+    }
+}
+
+fn skip_method(method: &Option<String>) -> bool {
+    serialize::skip_keyword(method.as_deref(), "euler")
+}
+
+fn skip_time_units(time_units: &Option<String>) -> bool {
+    serialize::skip_keyword(time_units.as_deref(), "")
+}
+
+fn skip_run_by(run_by: &Option<String>) -> bool {
+    serialize::skip_keyword(run_by.as_deref(), "all")
+}
+
+/// Reads `<dt>`, taking the reciprocal of its value if it has
+/// `reciprocal="true"`.
+fn deserialize_dt<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct RawDt {
+        #[serde(rename = "@reciprocal", default)]
+        reciprocal: Option<bool>,
+        #[serde(rename = "$text")]
+        value: f64,
+    }
+
+    let raw = RawDt::deserialize(deserializer)?;
+    Ok(Some(if raw.reciprocal == Some(true) {
+        1.0 / raw.value
+    } else {
+        raw.value
+    }))
+}
+
+/// A method of moving stocks over each step of DT.
+#[cfg_attr(feature = "artifacts", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IntegrationMethod {
+    /// Each stock moves by its net flow at the start of the step.
+    #[default]
+    Euler,
+    /// Second-order Runge-Kutta: the average of the net flows at the start
+    /// of the step and at its end, estimated with Euler's method (Heun's
+    /// method).
+    Rk2,
+    /// The classical fourth-order Runge-Kutta method.
+    Rk4,
+    /// The Runge-Kutta-Fehlberg method, which adapts its step size to keep
+    /// the estimated error within a tolerance. DT sets the save interval
+    /// and the first step, and values at save times are interpolated.
+    Rk45,
+    /// Difference equations: each stock moves by its net flow times DT, as
+    /// with Euler's method, but times are exact multiples of DT and events
+    /// such as `STEP` and `PULSE` happen on the step they name.
+    Discrete,
+}
+
+impl IntegrationMethod {
+    /// The XMILE name of the method.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntegrationMethod::Euler => "euler",
+            IntegrationMethod::Rk2 => "rk2",
+            IntegrationMethod::Rk4 => "rk4",
+            IntegrationMethod::Rk45 => "rk45",
+            IntegrationMethod::Discrete => "discrete",
+        }
+    }
+}
+
+impl fmt::Display for IntegrationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IntegrationMethod {
+    type Err = String;
+
+    /// Parses an XMILE method name, or a synonym used by other tools such
+    /// as `Runge-Kutta 4` or Vensim's `RK4 Auto` and `RK4 Fixed`. Case,
+    /// spaces, hyphens and underscores are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "euler" => Ok(IntegrationMethod::Euler),
+            "rk2" | "rk2auto" | "rk2fixed" | "rungekutta2" | "heun" => Ok(IntegrationMethod::Rk2),
+            "rk4" | "rk4auto" | "rk4fixed" | "rungekutta4" | "rungekutta" => {
+                Ok(IntegrationMethod::Rk4)
+            }
+            "rk45" | "rkf45" | "rungekuttafehlberg" => Ok(IntegrationMethod::Rk45),
+            "discrete" | "difference" => Ok(IntegrationMethod::Discrete),
+            _ => Err(format!(
+                "Unsupported integration method '{s}'; expected euler, rk2, rk4, rk45 or discrete"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integration_method_names() {
+        assert_eq!("euler".parse(), Ok(IntegrationMethod::Euler));
+        assert_eq!("RK2".parse(), Ok(IntegrationMethod::Rk2));
+        assert_eq!("Runge-Kutta 4".parse(), Ok(IntegrationMethod::Rk4));
+        assert_eq!("RK4 Auto".parse(), Ok(IntegrationMethod::Rk4));
+        assert_eq!("RKF45".parse(), Ok(IntegrationMethod::Rk45));
+        assert_eq!("Discrete".parse(), Ok(IntegrationMethod::Discrete));
+        assert!("gear".parse::<IntegrationMethod>().is_err());
+        assert_eq!(IntegrationMethod::Rk4.to_string(), "rk4");
+
+        let mut specs = SimulationSpecs {
+            start: 0.0,
+            stop: 10.0,
+            dt: None,
+            method: None,
+            time_units: None,
+            pause: None,
+            run_by: None,
+        };
+        assert_eq!(specs.integration_method(), Ok(IntegrationMethod::Euler));
+        specs.method = Some("gear".to_string());
+        assert_eq!(
+            specs.integration_method(),
+            Err(
+                "Unsupported integration method 'gear'; expected euler, rk2, rk4, rk45 or discrete"
+                    .to_string()
+            )
+        );
+
+        // The method, pause interval and run type are attributes
+        let specs: SimulationSpecs = quick_xml::de::from_str(
+            r#"<sim_specs method="RK4" pause="5" run_by="group"><start>0</start><stop>1</stop></sim_specs>"#,
+        )
+        .unwrap();
+        assert_eq!(specs.integration_method(), Ok(IntegrationMethod::Rk4));
+        assert_eq!(specs.pause, Some(5.0));
+        assert_eq!(specs.run_by.as_deref(), Some("group"));
+    }
+
+    #[test]
+    fn test_reciprocal_dt() {
+        let dt = |xml: &str| {
+            let specs: SimulationSpecs = quick_xml::de::from_str(&format!(
+                "<sim_specs><start>0</start><stop>1</stop>{xml}</sim_specs>"
+            ))
+            .unwrap();
+            specs.dt
+        };
+        assert_eq!(dt(r#"<dt reciprocal="true">4</dt>"#), Some(0.25));
+        assert_eq!(dt(r#"<dt reciprocal="false">4</dt>"#), Some(4.0));
+        assert_eq!(dt("<dt>0.5</dt>"), Some(0.5));
+        assert_eq!(dt(""), None);
+    }
+}
@@ -0,0 +1,351 @@
+//! Templates for common stock and flow structures, generated from a few
+//! arguments.
+//!
+//! Each generator returns a [`Template`] whose names start with the
+//! `{{prefix}}` placeholder, so a structure can be instantiated several
+//! times in one model. Views lay the structure out from the top left of the
+//! diagram, with [`STAGE_SPACING`] units between neighbouring stocks.
+
+use super::Template;
+
+/// The horizontal distance between the centres of neighbouring stocks.
+pub const STAGE_SPACING: f64 = 150.0;
+
+const STOCK_WIDTH: f64 = 45.0;
+const STOCK_HEIGHT: f64 = 35.0;
+const ORIGIN_X: f64 = 150.0;
+const ORIGIN_Y: f64 = 100.0;
+
+/// Builds a chain of `n_stages` stocks that material passes through in
+/// turn, taking `transit_time_expr` in total to pass through the chain.
+///
+/// Stages are named from `names`, falling back to `Stage_1`, `Stage_2`, ...
+/// for stages without a name. Material enters the first stage through
+/// `<first>_Inflow`, moves from stage to stage through `<stage>_to_<next>`
+/// flows and leaves the last stage through `<last>_Outflow`. Each stage
+/// drains at its level divided by its share of the transit time, making the
+/// chain an `n_stages`-order delay.
+///
+/// The template has two parameters, both defaulting to `0`: `initial`, the
+/// initial value of every stage, and `inflow`, the equation of the inflow.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use xmile::template::generators::aging_chain;
+/// use xmile::xml::Model;
+///
+/// let chain = aging_chain(3, &["Children", "Adults", "Elderly"], "75");
+/// let mut model: Model = quick_xml::de::from_str("<model><variables/></model>").unwrap();
+/// chain.instantiate(&mut model, "", &HashMap::new()).unwrap();
+/// assert_eq!(model.variables.variables.len(), 7);
+/// ```
+pub fn aging_chain(n_stages: usize, names: &[&str], transit_time_expr: &str) -> Template {
+    let stages: Vec<String> = (0..n_stages.max(1))
+        .map(|index| match names.get(index) {
+            Some(name) => format!("{{{{prefix}}}}{}", identifier(name)),
+            None => format!("{{{{prefix}}}}Stage_{}", index + 1),
+        })
+        .collect();
+    let stage_time = format!(
+        "(({}) / {})",
+        super::escape(transit_time_expr),
+        stages.len()
+    );
+
+    let inflow = format!("{}_Inflow", stages[0]);
+    let outflow = format!("{}_Outflow", stages[stages.len() - 1]);
+    let transfers: Vec<String> = stages
+        .windows(2)
+        .map(|pair| format!("{}_to_{}", pair[0], strip_prefix(&pair[1])))
+        .collect();
+
+    let mut variables = String::new();
+    for (index, stage) in stages.iter().enumerate() {
+        let incoming = if index == 0 {
+            &inflow
+        } else {
+            &transfers[index - 1]
+        };
+        let outgoing = transfers.get(index).unwrap_or(&outflow);
+        variables.push_str(&format!(
+            r#"<stock name="{stage}"><eqn>{{{{initial}}}}</eqn><inflow>{incoming}</inflow><outflow>{outgoing}</outflow></stock>"#
+        ));
+    }
+    variables.push_str(&format!(
+        r#"<flow name="{inflow}"><eqn>{{{{inflow}}}}</eqn></flow>"#
+    ));
+    for (stage, flow) in stages.iter().zip(transfers.iter().chain([&outflow])) {
+        variables.push_str(&format!(
+            r#"<flow name="{flow}"><eqn>{stage} / {stage_time}</eqn></flow>"#
+        ));
+    }
+
+    let mut objects = String::new();
+    let mut uid = 2;
+    let half_width = STOCK_WIDTH / 2.0;
+    for (index, stage) in stages.iter().enumerate() {
+        objects.push_str(&stock_object(uid, stage, stage_x(index), ORIGIN_Y));
+        uid += 1;
+    }
+    let last = stages.len() - 1;
+    let flows = std::iter::once((
+        &inflow,
+        stage_x(0) - STAGE_SPACING + half_width,
+        stage_x(0) - half_width,
+    ))
+    .chain(transfers.iter().enumerate().map(|(index, flow)| {
+        (
+            flow,
+            stage_x(index) + half_width,
+            stage_x(index + 1) - half_width,
+        )
+    }))
+    .chain(std::iter::once((
+        &outflow,
+        stage_x(last) + half_width,
+        stage_x(last + 1) - half_width,
+    )));
+    for (flow, from, to) in flows {
+        objects.push_str(&flow_object(uid, flow, (from, ORIGIN_Y), (to, ORIGIN_Y)));
+        uid += 1;
+    }
+
+    Template::new(
+        "aging_chain",
+        format!(
+            "<model><variables>{variables}</variables>{}</model>",
+            view(
+                &objects,
+                stage_x(stages.len()) + STAGE_SPACING,
+                ORIGIN_Y * 2.0
+            ),
+        ),
+    )
+    .with_default("initial", "0")
+    .with_default("inflow", "0")
+}
+
+/// Builds a co-flow: a stock accumulating `attribute_name` for the material
+/// in `parent_stock`, so the attribute's average follows material as it
+/// flows in and out of the parent.
+///
+/// The structure is the standard one. `Total_<attribute>` holds the
+/// attribute summed over the material in the parent. `<attribute>_Added`
+/// brings in the attribute of entering material, `<attribute>_Gained`
+/// accrues it while material is in the parent, and `<attribute>_Lost`
+/// removes the average attribute with leaving material.
+/// `Average_<attribute>` divides the total by the parent, falling back to
+/// the initial average while the parent is empty.
+///
+/// The parent stock is referred to as it is named in the model; only the
+/// new variables are prefixed. The template has these parameters:
+///
+/// - `inflow`: the rate material enters the parent, usually the name of
+///   its inflow
+/// - `outflow`: the rate material leaves the parent
+/// - `incoming`: the attribute per unit of entering material
+/// - `gain`: the attribute gained per unit of material per time unit
+///   (default `0`)
+/// - `initial`: the initial average attribute (default `0`)
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use xmile::template::generators::coflow;
+/// use xmile::xml::Model;
+///
+/// let experience = coflow("Employees", "Experience");
+/// let mut model: Model = quick_xml::de::from_str("<model><variables/></model>").unwrap();
+/// let params = HashMap::from([
+///     ("inflow".to_string(), "hiring".to_string()),
+///     ("outflow".to_string(), "quitting".to_string()),
+///     ("incoming".to_string(), "2".to_string()),
+///     ("gain".to_string(), "1".to_string()),
+/// ]);
+/// experience.instantiate(&mut model, "", &params).unwrap();
+/// assert_eq!(model.variables.variables.len(), 5);
+/// ```
+pub fn coflow(parent_stock: &str, attribute_name: &str) -> Template {
+    let parent = identifier(parent_stock);
+    let attribute = identifier(attribute_name);
+    let total = format!("{{{{prefix}}}}Total_{attribute}");
+    let average = format!("{{{{prefix}}}}Average_{attribute}");
+    let added = format!("{{{{prefix}}}}{attribute}_Added");
+    let gained = format!("{{{{prefix}}}}{attribute}_Gained");
+    let lost = format!("{{{{prefix}}}}{attribute}_Lost");
+
+    let variables = [
+        format!(
+            r#"<stock name="{total}"><eqn>{parent} * ({{{{initial}}}})</eqn><inflow>{added}</inflow><inflow>{gained}</inflow><outflow>{lost}</outflow></stock>"#
+        ),
+        format!(r#"<flow name="{added}"><eqn>({{{{inflow}}}}) * ({{{{incoming}}}})</eqn></flow>"#),
+        format!(r#"<flow name="{gained}"><eqn>{parent} * ({{{{gain}}}})</eqn></flow>"#),
+        format!(r#"<flow name="{lost}"><eqn>({{{{outflow}}}}) * {average}</eqn></flow>"#),
+        format!(
+            r#"<aux name="{average}"><eqn>IF {parent} &gt; 0 THEN {total} / {parent} ELSE {{{{initial}}}}</eqn></aux>"#
+        ),
+    ]
+    .concat();
+
+    let (x, y) = (ORIGIN_X, ORIGIN_Y);
+    let (half_width, half_height) = (STOCK_WIDTH / 2.0, STOCK_HEIGHT / 2.0);
+    let objects = [
+        stock_object(2, &total, x, y),
+        flow_object(
+            3,
+            &added,
+            (x - STAGE_SPACING + half_width, y),
+            (x - half_width, y),
+        ),
+        flow_object(
+            4,
+            &gained,
+            (x, y - ORIGIN_Y + half_height),
+            (x, y - half_height),
+        ),
+        flow_object(
+            5,
+            &lost,
+            (x + half_width, y),
+            (x + STAGE_SPACING - half_width, y),
+        ),
+        format!(
+            r#"<aux uid="6" name="{average}" x="{x}" y="{}"/>"#,
+            y + ORIGIN_Y / 2.0
+        ),
+    ]
+    .concat();
+
+    Template::new(
+        "coflow",
+        format!(
+            "<model><variables>{variables}</variables>{}</model>",
+            view(&objects, x + STAGE_SPACING * 2.0, ORIGIN_Y * 2.0)
+        ),
+    )
+    .with_default("gain", "0")
+    .with_default("initial", "0")
+}
+
+/// The x coordinate of the centre of the stock at `index`.
+fn stage_x(index: usize) -> f64 {
+    ORIGIN_X + STAGE_SPACING * index as f64
+}
+
+fn stock_object(uid: i32, name: &str, x: f64, y: f64) -> String {
+    format!(
+        r#"<stock uid="{uid}" name="{name}" x="{x}" y="{y}" width="{STOCK_WIDTH}" height="{STOCK_HEIGHT}"/>"#
+    )
+}
+
+/// A straight flow from `from` to `to`, with its valve halfway along.
+fn flow_object(uid: i32, name: &str, from: (f64, f64), to: (f64, f64)) -> String {
+    format!(
+        r#"<flow uid="{uid}" name="{name}" x="{x}" y="{y}" width="18" height="18"><pts><pt x="{}" y="{}"/><pt x="{}" y="{}"/></pts></flow>"#,
+        from.0,
+        from.1,
+        to.0,
+        to.1,
+        x = (from.0 + to.0) / 2.0,
+        y = (from.1 + to.1) / 2.0,
+    )
+}
+
+/// Wraps display objects in a view large enough to hold them.
+fn view(objects: &str, width: f64, height: f64) -> String {
+    format!(
+        r#"<views><view uid="1" width="{width}" height="{height}" page_width="{width}" page_height="{height}">{objects}</view></views>"#
+    )
+}
+
+/// Writes `name` as it appears in equations and `name` attributes.
+fn identifier(name: &str) -> String {
+    super::escape(&name.trim().replace(' ', "_"))
+}
+
+fn strip_prefix(name: &str) -> &str {
+    name.strip_prefix("{{prefix}}").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::model::vars::Variable;
+    use crate::model::vars::stock::Stock;
+    use crate::xml::schema::Model;
+
+    fn model() -> Model {
+        quick_xml::de::from_str("<model><variables/></model>").unwrap()
+    }
+
+    #[test]
+    fn test_aging_chain() {
+        let chain = aging_chain(3, &["Young", "Middle Aged"], "life_expectancy");
+        let mut model = model();
+        let params = HashMap::from([("inflow".to_string(), "births".to_string())]);
+        chain.instantiate(&mut model, "Pop_", &params).unwrap();
+
+        let variables = &model.variables.variables;
+        assert_eq!(variables.len(), 7);
+        let Variable::Stock(stock) = &variables[1] else {
+            panic!("expected a stock");
+        };
+        let Stock::Basic(stock) = stock.as_ref() else {
+            panic!("expected a basic stock");
+        };
+        assert_eq!(stock.name.to_string(), "Pop Middle Aged");
+        assert_eq!(stock.inflows[0].to_string(), "Pop Young to Middle Aged");
+        assert_eq!(stock.outflows[0].to_string(), "Pop Middle Aged to Stage 3");
+
+        let Variable::Flow(flow) = &variables[6] else {
+            panic!("expected a flow");
+        };
+        assert_eq!(flow.name.to_string(), "Pop Stage 3 Outflow");
+        assert_eq!(
+            flow.equation.as_ref().unwrap().to_string(),
+            "Pop_Stage_3 / ((life_expectancy) / 3)"
+        );
+
+        #[cfg(feature = "views")]
+        {
+            let view = &model.views.as_ref().unwrap().views[0];
+            assert_eq!(view.stocks.len(), 3);
+            assert_eq!(view.flows.len(), 4);
+            assert_eq!(view.flows[1].x, Some(225.0));
+        }
+    }
+
+    #[test]
+    fn test_coflow() {
+        let template = coflow("Workforce", "Skill Level");
+        assert_eq!(
+            template.parameters().into_iter().collect::<Vec<_>>(),
+            vec!["gain", "incoming", "inflow", "initial", "outflow"]
+        );
+        let mut model = model();
+        let params = HashMap::from([
+            ("inflow".to_string(), "hiring".to_string()),
+            ("outflow".to_string(), "attrition".to_string()),
+            ("incoming".to_string(), "0.5".to_string()),
+        ]);
+        template.instantiate(&mut model, "", &params).unwrap();
+
+        let variables = &model.variables.variables;
+        let Variable::Flow(lost) = &variables[3] else {
+            panic!("expected a flow");
+        };
+        assert_eq!(lost.name.to_string(), "Skill Level Lost");
+        assert_eq!(
+            lost.equation.as_ref().unwrap().to_string(),
+            "(attrition) * Average_Skill_Level"
+        );
+        let Variable::Auxiliary(average) = &variables[4] else {
+            panic!("expected an auxiliary");
+        };
+        assert_eq!(
+            average.equation.to_string(),
+            "IF Workforce > 0 THEN Total_Skill_Level / Workforce ELSE 0"
+        );
+    }
+}
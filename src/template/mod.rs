@@ -0,0 +1,413 @@
+//! Parameterized model fragments ("molecules") that can be stamped into a
+//! model.
+//!
+//! A [`Template`] is the XMILE text of a `<model>` element whose text may
+//! contain `{{placeholder}}`s. Instantiating it fills the placeholders from
+//! the given parameters, parses the result and merges its variables and
+//! views into a model. The `{{prefix}}` placeholder is filled with the name
+//! prefix of the instance, so writing every name as `{{prefix}}Name` lets
+//! one template be instantiated several times in the same model:
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use xmile::template::Template;
+//! use xmile::xml::Model;
+//!
+//! let decay = Template::new(
+//!     "decay",
+//!     r#"<model>
+//!         <variables>
+//!             <stock name="{{prefix}}Amount">
+//!                 <eqn>{{initial}}</eqn>
+//!                 <outflow>{{prefix}}Loss</outflow>
+//!             </stock>
+//!             <flow name="{{prefix}}Loss">
+//!                 <eqn>{{prefix}}Amount / {{lifetime}}</eqn>
+//!             </flow>
+//!         </variables>
+//!     </model>"#,
+//! )
+//! .with_default("lifetime", "10");
+//!
+//! let mut model: Model = quick_xml::de::from_str("<model><variables/></model>").unwrap();
+//! let params = HashMap::from([("initial".to_string(), "100".to_string())]);
+//! decay.instantiate(&mut model, "Carbon_", &params).unwrap();
+//! decay.instantiate(&mut model, "Nitrogen_", &params).unwrap();
+//! assert_eq!(model.variables.variables.len(), 4);
+//! ```
+//!
+//! Display objects keep their positions, which may themselves be
+//! placeholders, and have their uids renumbered past those already used in
+//! the model's views. The views of a template are merged into the model's
+//! views with the same index, and added where the model has no such view.
+
+use std::collections::{BTreeSet, HashMap};
+
+use thiserror::Error;
+
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+
+pub mod generators;
+
+#[cfg(feature = "views")]
+use crate::view::View;
+#[cfg(feature = "views")]
+use crate::xml::schema::Views;
+
+/// The placeholder filled with the name prefix of an instance.
+pub const PREFIX_PLACEHOLDER: &str = "prefix";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TemplateError {
+    #[error("Unknown template: {0}")]
+    UnknownTemplate(String),
+    #[error("Template '{template}' needs a value for '{parameter}'")]
+    MissingParameter { template: String, parameter: String },
+    #[error("Template '{template}' has an unclosed placeholder")]
+    UnclosedPlaceholder { template: String },
+    #[error("Template '{template}' does not produce a valid model: {reason}")]
+    Parse { template: String, reason: String },
+    #[error("Template '{template}' defines '{variable}', which the model already has")]
+    DuplicateVariable { template: String, variable: String },
+}
+
+/// A named model fragment with placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    /// The XMILE text of a `<model>` element, with placeholders.
+    pub source: String,
+    /// Values used for parameters that are not given when instantiating.
+    pub defaults: HashMap<String, String>,
+}
+
+impl Template {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Template {
+            name: name.into(),
+            source: source.into(),
+            defaults: HashMap::new(),
+        }
+    }
+
+    pub fn with_default(mut self, parameter: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defaults.insert(parameter.into(), value.into());
+        self
+    }
+
+    /// The names of the placeholders in the template, other than `prefix`.
+    pub fn parameters(&self) -> BTreeSet<&str> {
+        placeholders(&self.source)
+            .filter(|name| *name != PREFIX_PLACEHOLDER)
+            .collect()
+    }
+
+    /// Fills the placeholders of the template, returning the XMILE text of
+    /// the fragment. Values are escaped for use in XML.
+    pub fn render(
+        &self,
+        prefix: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<String, TemplateError> {
+        let mut text = String::with_capacity(self.source.len());
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find("{{") {
+            text.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(TemplateError::UnclosedPlaceholder {
+                    template: self.name.clone(),
+                });
+            };
+            let name = rest[start + 2..start + end].trim();
+            let value = if name == PREFIX_PLACEHOLDER {
+                prefix
+            } else {
+                params
+                    .get(name)
+                    .or_else(|| self.defaults.get(name))
+                    .ok_or_else(|| TemplateError::MissingParameter {
+                        template: self.name.clone(),
+                        parameter: name.to_string(),
+                    })?
+            };
+            text.push_str(&escape(value));
+            rest = &rest[start + end + 2..];
+        }
+        text.push_str(rest);
+        Ok(text)
+    }
+
+    /// Fills the placeholders of the template and merges the resulting
+    /// variables and views into `model`. The model is left unchanged if the
+    /// template cannot be instantiated, including when it would define a
+    /// variable the model already has.
+    pub fn instantiate(
+        &self,
+        model: &mut Model,
+        prefix: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<(), TemplateError> {
+        #[allow(unused_mut)]
+        let mut text = self.render(prefix, params)?;
+        #[cfg(feature = "views")]
+        {
+            let used = model
+                .views
+                .iter()
+                .flat_map(|views| &views.views)
+                .map(max_uid)
+                .max()
+                .unwrap_or(0);
+            text = renumber_uids(&text, used);
+        }
+        let fragment: Model =
+            quick_xml::de::from_str(&text).map_err(|error| TemplateError::Parse {
+                template: self.name.clone(),
+                reason: error.to_string(),
+            })?;
+
+        for variable in &fragment.variables.variables {
+            let Some(name) = get_variable_name(variable) else {
+                continue;
+            };
+            let exists = model
+                .variables
+                .variables
+                .iter()
+                .any(|existing| get_variable_name(existing) == Some(name));
+            if exists {
+                return Err(TemplateError::DuplicateVariable {
+                    template: self.name.clone(),
+                    variable: name.normalized().to_string(),
+                });
+            }
+        }
+
+        model
+            .variables
+            .variables
+            .extend(fragment.variables.variables);
+        #[cfg(feature = "views")]
+        if let Some(fragment_views) = fragment.views {
+            let views = model.views.get_or_insert_with(|| Views {
+                visible_view: None,
+                views: Vec::new(),
+                #[cfg(feature = "style")]
+                style: None,
+            });
+            for (index, view) in fragment_views.views.into_iter().enumerate() {
+                match views.views.get_mut(index) {
+                    Some(existing) => append_objects(existing, view),
+                    None => views.views.push(view),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A set of templates, by name.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateLibrary {
+    templates: HashMap<String, Template>,
+}
+
+impl TemplateLibrary {
+    pub fn new() -> Self {
+        TemplateLibrary::default()
+    }
+
+    /// Adds `template`, replacing any template with the same name.
+    pub fn insert(&mut self, template: Template) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+
+    /// The names of the templates, sorted.
+    pub fn names(&self) -> BTreeSet<&str> {
+        self.templates.keys().map(String::as_str).collect()
+    }
+
+    /// Instantiates the template called `name` into `model`.
+    pub fn instantiate(
+        &self,
+        model: &mut Model,
+        name: &str,
+        prefix: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<(), TemplateError> {
+        self.get(name)
+            .ok_or_else(|| TemplateError::UnknownTemplate(name.to_string()))?
+            .instantiate(model, prefix, params)
+    }
+}
+
+/// The names of the placeholders in `source`, in order of appearance.
+fn placeholders(source: &str) -> impl Iterator<Item = &str> {
+    source.split("{{").skip(1).filter_map(|rest| {
+        let end = rest.find("}}")?;
+        Some(rest[..end].trim())
+    })
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Calls `visit` with the value of each `uid="..."` attribute in `text`,
+/// replacing the value with the result.
+#[cfg(feature = "views")]
+fn map_uids(text: &str, mut visit: impl FnMut(i32) -> i32) -> String {
+    const ATTRIBUTE: &str = "uid=\"";
+    let mut mapped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(ATTRIBUTE) {
+        let value_start = start + ATTRIBUTE.len();
+        mapped.push_str(&rest[..value_start]);
+        rest = &rest[value_start..];
+        let is_attribute = mapped[..mapped.len() - ATTRIBUTE.len()].ends_with(char::is_whitespace);
+        let end = rest.find('"').unwrap_or(rest.len());
+        match rest[..end].trim().parse::<i32>() {
+            Ok(uid) if is_attribute => mapped.push_str(&visit(uid).to_string()),
+            _ => mapped.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    mapped.push_str(rest);
+    mapped
+}
+
+/// Shifts every uid in `text` by `offset`.
+#[cfg(feature = "views")]
+fn renumber_uids(text: &str, offset: i32) -> String {
+    map_uids(text, |uid| uid + offset)
+}
+
+/// The largest uid used in `view`, including the view itself.
+#[cfg(feature = "views")]
+fn max_uid(view: &View) -> i32 {
+    let mut max = view.uid.value;
+    if let Ok(text) = quick_xml::se::to_string(view) {
+        map_uids(&text, |uid| {
+            max = max.max(uid);
+            uid
+        });
+    }
+    max
+}
+
+/// Moves the display objects of `from` into `into`.
+#[cfg(feature = "views")]
+fn append_objects(into: &mut View, from: View) {
+    into.stocks.extend(from.stocks);
+    into.flows.extend(from.flows);
+    into.auxes.extend(from.auxes);
+    into.modules.extend(from.modules);
+    into.groups.extend(from.groups);
+    into.connectors.extend(from.connectors);
+    into.aliases.extend(from.aliases);
+    into.stacked_containers.extend(from.stacked_containers);
+    #[cfg(feature = "interface-objects")]
+    {
+        into.sliders.extend(from.sliders);
+        into.knobs.extend(from.knobs);
+        into.switches.extend(from.switches);
+        into.options.extend(from.options);
+        into.numeric_inputs.extend(from.numeric_inputs);
+        into.list_inputs.extend(from.list_inputs);
+        into.graphical_inputs.extend(from.graphical_inputs);
+        into.numeric_displays.extend(from.numeric_displays);
+        into.lamps.extend(from.lamps);
+        into.gauges.extend(from.gauges);
+        into.graphs.extend(from.graphs);
+        into.tables.extend(from.tables);
+        into.text_boxes.extend(from.text_boxes);
+        into.graphics_frames.extend(from.graphics_frames);
+        into.buttons.extend(from.buttons);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stock_template() -> Template {
+        Template::new(
+            "stock",
+            r#"<model>
+                <variables>
+                    <stock name="{{prefix}}Level"><eqn>{{initial}}</eqn></stock>
+                </variables>
+                <views>
+                    <view uid="1" width="800" height="600" page_width="800" page_height="600">
+                        <stock uid="2" name="{{prefix}}Level" x="{{x}}" y="100" width="45" height="35"/>
+                    </view>
+                </views>
+            </model>"#,
+        )
+        .with_default("x", "100")
+    }
+
+    #[cfg(feature = "views")]
+    fn model() -> Model {
+        quick_xml::de::from_str("<model><variables/></model>").unwrap()
+    }
+
+    #[test]
+    fn test_parameters_and_render() {
+        let template = stock_template();
+        assert_eq!(
+            template.parameters().into_iter().collect::<Vec<_>>(),
+            vec!["initial", "x"]
+        );
+        let error = template.render("A_", &HashMap::new()).unwrap_err();
+        assert!(matches!(
+            error,
+            TemplateError::MissingParameter { parameter, .. } if parameter == "initial"
+        ));
+        let params = HashMap::from([("initial".to_string(), "a < b".to_string())]);
+        let text = template.render("A_", &params).unwrap();
+        assert!(text.contains(r#"<stock name="A_Level"><eqn>a &lt; b</eqn>"#));
+        assert!(text.contains(r#"x="100""#));
+    }
+
+    #[cfg(feature = "views")]
+    #[test]
+    fn test_instantiate_twice() {
+        let mut library = TemplateLibrary::new();
+        library.insert(stock_template());
+        let mut model = model();
+        let params = HashMap::from([("initial".to_string(), "10".to_string())]);
+        library
+            .instantiate(&mut model, "stock", "A_", &params)
+            .unwrap();
+        library
+            .instantiate(&mut model, "stock", "B_", &params)
+            .unwrap();
+        assert_eq!(model.variables.variables.len(), 2);
+
+        let views = &model.views.as_ref().unwrap().views;
+        assert_eq!(views.len(), 1);
+        let uids: Vec<i32> = views[0].stocks.iter().map(|s| s.uid.value).collect();
+        assert_eq!(uids, vec![2, 4]);
+
+        let error = library
+            .instantiate(&mut model, "stock", "A_", &params)
+            .unwrap_err();
+        assert!(matches!(error, TemplateError::DuplicateVariable { .. }));
+        assert_eq!(model.variables.variables.len(), 2);
+        assert!(matches!(
+            library.instantiate(&mut model, "missing", "C_", &params),
+            Err(TemplateError::UnknownTemplate(_))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::Expression;
+
 // Helper function to assert floating point equality with tolerance
 pub fn assert_float_eq(a: f64, b: f64, tolerance: f64) {
     assert!(
@@ -8,3 +10,10 @@ pub fn assert_float_eq(a: f64, b: f64, tolerance: f64) {
         tolerance
     );
 }
+
+/// Parses an equation, failing the test if any of it is left unparsed.
+pub fn parse_expression(equation: &str) -> Expression {
+    let (rest, expression) = crate::equation::parse::expression(equation).unwrap();
+    assert!(rest.trim().is_empty(), "unparsed input: {rest}");
+    expression
+}
@@ -0,0 +1,497 @@
+//! A small language of assertions about simulation results, for regression
+//! testing models.
+//!
+//! A test file has one assertion per line; blank lines and lines starting
+//! with `#` are ignored. Each assertion names a variable, quoted if its name
+//! has spaces, and says what must hold of its values:
+//!
+//! ```text
+//! # Values at every saved step, or at some step
+//! assert "Population" always >= 0
+//! assert "Population" never > 1e6
+//! assert Inventory eventually < 10
+//!
+//! # Values at a time, interpolating between saved steps, or at the end
+//! assert "GDP" at 2050 within 5% of 1.2e13
+//! assert "GDP" at 2050 within 1e11 of 1.2e13
+//! assert Backlog at 10 == 0
+//! assert Backlog final <= 5
+//! ```
+//!
+//! Comparisons are `<`, `<=`, `>`, `>=`, `==` and `!=`. Variable names are
+//! compared as XMILE identifiers, so `Birth_Rate` finds `birth rate`.
+//!
+//! ```rust
+//! use xmile::data::export::ExportData;
+//! use xmile::testing::assertions::TestSuite;
+//!
+//! let suite = TestSuite::parse(r#"assert "Population" always >= 0"#).unwrap();
+//! let run = ExportData::new(vec![0.0, 1.0]).with_series("Population", vec![10.0, 12.0]);
+//! assert!(suite.check(&run).passed());
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::data::export::ExportData;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AssertionError {
+    #[error("IO error reading tests {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid assertion on line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn holds(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessOrEqual => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterOrEqual => lhs >= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "<" => Ok(Comparison::Less),
+            "<=" => Ok(Comparison::LessOrEqual),
+            ">" => Ok(Comparison::Greater),
+            ">=" => Ok(Comparison::GreaterOrEqual),
+            "==" => Ok(Comparison::Equal),
+            "!=" => Ok(Comparison::NotEqual),
+            _ => Err(format!("expected a comparison, found '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+/// How far a value may be from its expected value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    Absolute(f64),
+    /// A percentage of the expected value.
+    Percent(f64),
+}
+
+/// Where in a run an assertion looks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Check {
+    /// The comparison holds at every saved step.
+    Always(Comparison, f64),
+    /// The comparison holds at no saved step.
+    Never(Comparison, f64),
+    /// The comparison holds at some saved step.
+    Eventually(Comparison, f64),
+    /// The comparison holds at a time.
+    At(f64, Comparison, f64),
+    /// The value at a time is within a tolerance of an expected value.
+    Within {
+        time: f64,
+        tolerance: Tolerance,
+        expected: f64,
+    },
+    /// The comparison holds at the last saved step.
+    Final(Comparison, f64),
+}
+
+/// One assertion about the values of a variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    pub variable: String,
+    pub check: Check,
+    /// The line of the test file the assertion is on, from 1.
+    pub line: usize,
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "assert \"{}\" ", self.variable)?;
+        match self.check {
+            Check::Always(comparison, value) => write!(f, "always {comparison} {value}"),
+            Check::Never(comparison, value) => write!(f, "never {comparison} {value}"),
+            Check::Eventually(comparison, value) => {
+                write!(f, "eventually {comparison} {value}")
+            }
+            Check::At(time, comparison, value) => write!(f, "at {time} {comparison} {value}"),
+            Check::Within {
+                time,
+                tolerance,
+                expected,
+            } => match tolerance {
+                Tolerance::Absolute(tolerance) => {
+                    write!(f, "at {time} within {tolerance} of {expected}")
+                }
+                Tolerance::Percent(percent) => {
+                    write!(f, "at {time} within {percent}% of {expected}")
+                }
+            },
+            Check::Final(comparison, value) => write!(f, "final {comparison} {value}"),
+        }
+    }
+}
+
+impl Assertion {
+    /// Checks the assertion against `run`, returning a description of the
+    /// failure if it does not hold.
+    pub fn check(&self, run: &ExportData) -> Result<(), String> {
+        let Some(values) = run.series(&self.variable) else {
+            return Err(format!("No results for '{}'", self.variable));
+        };
+        let value_at = |time: f64| {
+            run.value_at(&self.variable, time)
+                .ok_or_else(|| format!("No results for '{}'", self.variable))
+        };
+        let steps = || run.times.iter().zip(values);
+        match self.check {
+            Check::Always(comparison, bound) => {
+                match steps().find(|(_, value)| !comparison.holds(**value, bound)) {
+                    Some((time, value)) => Err(format!(
+                        "'{}' was {} at time {}, but must always be {} {}",
+                        self.variable, value, time, comparison, bound
+                    )),
+                    None => Ok(()),
+                }
+            }
+            Check::Never(comparison, bound) => {
+                match steps().find(|(_, value)| comparison.holds(**value, bound)) {
+                    Some((time, value)) => Err(format!(
+                        "'{}' was {} at time {}, but must never be {} {}",
+                        self.variable, value, time, comparison, bound
+                    )),
+                    None => Ok(()),
+                }
+            }
+            Check::Eventually(comparison, bound) => {
+                if steps().any(|(_, value)| comparison.holds(*value, bound)) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "'{}' was never {} {}",
+                        self.variable, comparison, bound
+                    ))
+                }
+            }
+            Check::At(time, comparison, bound) => {
+                let value = value_at(time)?;
+                if comparison.holds(value, bound) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "'{}' was {} at time {}, but must be {} {}",
+                        self.variable, value, time, comparison, bound
+                    ))
+                }
+            }
+            Check::Within {
+                time,
+                tolerance,
+                expected,
+            } => {
+                let value = value_at(time)?;
+                let allowed = match tolerance {
+                    Tolerance::Absolute(tolerance) => tolerance,
+                    Tolerance::Percent(percent) => expected.abs() * percent / 100.0,
+                };
+                if (value - expected).abs() <= allowed {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "'{}' was {} at time {}, more than {} from {}",
+                        self.variable, value, time, allowed, expected
+                    ))
+                }
+            }
+            Check::Final(comparison, bound) => match values.last() {
+                Some(value) if comparison.holds(*value, bound) => Ok(()),
+                Some(value) => Err(format!(
+                    "'{}' ended at {}, but must end {} {}",
+                    self.variable, value, comparison, bound
+                )),
+                None => Err(format!("No results for '{}'", self.variable)),
+            },
+        }
+    }
+}
+
+/// The assertions of a test file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestSuite {
+    pub assertions: Vec<Assertion>,
+}
+
+impl TestSuite {
+    pub fn parse(text: &str) -> Result<Self, AssertionError> {
+        let mut assertions = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let number = index + 1;
+            assertions.push(parse_assertion(line, number).map_err(|message| {
+                AssertionError::Parse {
+                    line: number,
+                    message,
+                }
+            })?);
+        }
+        Ok(TestSuite { assertions })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AssertionError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| AssertionError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        TestSuite::parse(&text)
+    }
+
+    /// Checks every assertion against `run`.
+    pub fn check(&self, run: &ExportData) -> TestReport {
+        TestReport {
+            outcomes: self
+                .assertions
+                .iter()
+                .map(|assertion| AssertionOutcome {
+                    assertion: assertion.clone(),
+                    failure: assertion.check(run).err(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Whether an assertion held, and why not if it did not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertionOutcome {
+    pub assertion: Assertion,
+    pub failure: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestReport {
+    pub outcomes: Vec<AssertionOutcome>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> bool {
+        self.outcomes
+            .iter()
+            .all(|outcome| outcome.failure.is_none())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &AssertionOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.failure.is_some())
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for outcome in &self.outcomes {
+            match &outcome.failure {
+                None => writeln!(
+                    f,
+                    "ok    line {}: {}",
+                    outcome.assertion.line, outcome.assertion
+                )?,
+                Some(failure) => writeln!(
+                    f,
+                    "FAIL  line {}: {}\n      {}",
+                    outcome.assertion.line, outcome.assertion, failure
+                )?,
+            }
+        }
+        let failed = self.failures().count();
+        write!(
+            f,
+            "{} passed, {} failed",
+            self.outcomes.len() - failed,
+            failed
+        )
+    }
+}
+
+fn parse_assertion(line: &str, number: usize) -> Result<Assertion, String> {
+    let rest = line
+        .strip_prefix("assert")
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+        .ok_or("expected 'assert'")?
+        .trim_start();
+    let (variable, rest) = if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find('"').ok_or("unclosed quote")?;
+        (quoted[..end].to_string(), &quoted[end + 1..])
+    } else {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        (rest[..end].to_string(), &rest[end..])
+    };
+    if variable.is_empty() {
+        return Err("expected a variable name".to_string());
+    }
+
+    let mut words = rest.split_whitespace();
+    let mut next = |expected: &str| words.next().ok_or_else(|| format!("expected {expected}"));
+    let number_of = |word: &str| {
+        word.parse::<f64>()
+            .map_err(|_| format!("expected a number, found '{word}'"))
+    };
+    let comparison = |word: &str| word.parse::<Comparison>();
+
+    let check = match next("'always', 'never', 'eventually', 'at' or 'final'")? {
+        "always" => Check::Always(
+            comparison(next("a comparison")?)?,
+            number_of(next("a number")?)?,
+        ),
+        "never" => Check::Never(
+            comparison(next("a comparison")?)?,
+            number_of(next("a number")?)?,
+        ),
+        "eventually" => Check::Eventually(
+            comparison(next("a comparison")?)?,
+            number_of(next("a number")?)?,
+        ),
+        "final" => Check::Final(
+            comparison(next("a comparison")?)?,
+            number_of(next("a number")?)?,
+        ),
+        "at" => {
+            let time = number_of(next("a time")?)?;
+            match next("'within' or a comparison")? {
+                "within" => {
+                    let amount = next("a tolerance")?;
+                    let tolerance = match amount.strip_suffix('%') {
+                        Some(percent) => Tolerance::Percent(number_of(percent)?),
+                        None => Tolerance::Absolute(number_of(amount)?),
+                    };
+                    if next("'of'")? != "of" {
+                        return Err("expected 'of'".to_string());
+                    }
+                    Check::Within {
+                        time,
+                        tolerance,
+                        expected: number_of(next("a number")?)?,
+                    }
+                }
+                word => Check::At(time, comparison(word)?, number_of(next("a number")?)?),
+            }
+        }
+        word => return Err(format!("unknown check '{word}'")),
+    };
+    if let Some(extra) = words.next() {
+        return Err(format!("unexpected '{extra}'"));
+    }
+    Ok(Assertion {
+        variable,
+        check,
+        line: number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTS: &str = r#"
+        # Population must stay sensible
+        assert "Population" always >= 0
+        assert Population never > 1e6
+        assert "GDP" at 2050 within 5% of 1.2e13
+        assert GDP at 2040 > 1.2e13
+        assert GDP final <= 2e13
+        assert GDP eventually > 1.3e13
+    "#;
+
+    fn run() -> ExportData {
+        ExportData::new(vec![2030.0, 2040.0, 2060.0])
+            .with_series("Population", vec![10.0, 20.0, 30.0])
+            .with_series("GDP", vec![1.0e13, 1.1e13, 1.3e13])
+    }
+
+    #[test]
+    fn test_parse_and_check() {
+        let suite = TestSuite::parse(TESTS).unwrap();
+        assert_eq!(suite.assertions.len(), 6);
+        assert_eq!(
+            suite.assertions[2].check,
+            Check::Within {
+                time: 2050.0,
+                tolerance: Tolerance::Percent(5.0),
+                expected: 1.2e13
+            }
+        );
+        assert_eq!(suite.assertions[2].line, 5);
+
+        let report = suite.check(&run());
+        let failures: Vec<usize> = report.failures().map(|f| f.assertion.line).collect();
+        assert_eq!(failures, vec![6, 8]);
+        assert!(!report.passed());
+        assert!(report.to_string().ends_with("4 passed, 2 failed"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (text, line) in [
+            ("assert Population sometimes > 0", 1),
+            ("\nassert Population at 10 within 5 from 3", 2),
+            ("assert \"Population always > 0", 1),
+            ("assert Population always > 0 extra", 1),
+        ] {
+            match TestSuite::parse(text) {
+                Err(AssertionError::Parse { line: found, .. }) => assert_eq!(found, line),
+                other => panic!("expected a parse error for {text:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_variable() {
+        let suite = TestSuite::parse("assert Inventory always >= 0").unwrap();
+        let report = suite.check(&run());
+        assert_eq!(
+            report.outcomes[0].failure.as_deref(),
+            Some("No results for 'Inventory'")
+        );
+    }
+}
@@ -0,0 +1,427 @@
+//! Synthetic views exercising every display object variant.
+//!
+//! Each [`ObjectKind`] has a small XML fixture and a way to count matching
+//! objects in a parsed [`View`]. Round-tripping a view built from every kind
+//! and checking that each count survives turns a serializer or deserializer
+//! that drops objects into a failing test:
+//!
+//! ```rust
+//! use xmile::testing::builders::{ObjectKind, ViewBuilder};
+//!
+//! let view = ViewBuilder::new(1).with(ObjectKind::Stock).build().unwrap();
+//! assert_eq!(ObjectKind::Stock.count(&view), 1);
+//! ```
+
+use std::fmt::Write;
+
+#[cfg(feature = "interface-objects")]
+use crate::view::GraphicsFrameContent;
+use crate::view::{Pointer, Shape, View};
+
+/// A display object variant that can appear in a view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Stock,
+    /// A stock drawn with a rectangle `<shape>`.
+    StockRectangle,
+    Flow,
+    Aux,
+    /// An aux drawn with a circle `<shape>`.
+    AuxCircle,
+    Module,
+    Group,
+    /// A connector drawn as a straight line from its angle.
+    Connector,
+    /// A connector drawn as an arc through an anchor point.
+    ConnectorArc,
+    /// A connector starting at an alias.
+    ConnectorFromAlias,
+    Alias,
+    /// An alias drawn with a name only `<shape>`.
+    AliasNameOnly,
+    StackedContainer,
+    #[cfg(feature = "interface-objects")]
+    Slider,
+    #[cfg(feature = "interface-objects")]
+    Knob,
+    #[cfg(feature = "interface-objects")]
+    Switch,
+    #[cfg(feature = "interface-objects")]
+    Options,
+    #[cfg(feature = "interface-objects")]
+    NumericInput,
+    #[cfg(feature = "interface-objects")]
+    ListInput,
+    #[cfg(feature = "interface-objects")]
+    GraphicalInput,
+    #[cfg(feature = "interface-objects")]
+    NumericDisplay,
+    #[cfg(feature = "interface-objects")]
+    Lamp,
+    #[cfg(feature = "interface-objects")]
+    Gauge,
+    #[cfg(feature = "interface-objects")]
+    Graph,
+    #[cfg(feature = "interface-objects")]
+    Table,
+    #[cfg(feature = "interface-objects")]
+    TextBox,
+    /// A graphics frame showing an image.
+    #[cfg(feature = "interface-objects")]
+    GraphicsFrameImage,
+    /// A graphics frame showing a video.
+    #[cfg(feature = "interface-objects")]
+    GraphicsFrameVideo,
+    #[cfg(feature = "interface-objects")]
+    Button,
+}
+
+impl ObjectKind {
+    /// Every object kind available with the enabled features.
+    pub const ALL: &[ObjectKind] = &[
+        ObjectKind::Stock,
+        ObjectKind::StockRectangle,
+        ObjectKind::Flow,
+        ObjectKind::Aux,
+        ObjectKind::AuxCircle,
+        ObjectKind::Module,
+        ObjectKind::Group,
+        ObjectKind::Connector,
+        ObjectKind::ConnectorArc,
+        ObjectKind::ConnectorFromAlias,
+        ObjectKind::Alias,
+        ObjectKind::AliasNameOnly,
+        ObjectKind::StackedContainer,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Slider,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Knob,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Switch,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Options,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::NumericInput,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::ListInput,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::GraphicalInput,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::NumericDisplay,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Lamp,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Gauge,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Graph,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Table,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::TextBox,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::GraphicsFrameImage,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::GraphicsFrameVideo,
+        #[cfg(feature = "interface-objects")]
+        ObjectKind::Button,
+    ];
+
+    /// Returns the XML of one object of this kind with the given uid.
+    ///
+    /// Objects refer to the variables of [`synthetic_model_xml`]; the alias
+    /// connector points at the alias with uid `alias_uid`.
+    pub fn fixture(self, uid: i32, alias_uid: i32) -> String {
+        match self {
+            ObjectKind::Stock => format!(
+                r#"<stock uid="{uid}" name="Population" x="100" y="100" width="45" height="35"/>"#
+            ),
+            ObjectKind::StockRectangle => format!(
+                r#"<stock uid="{uid}" name="Population" x="100" y="200" width="45" height="35"><shape type="rectangle" width="45" height="35" corner_radius="5"/></stock>"#
+            ),
+            ObjectKind::Flow => format!(
+                r#"<flow uid="{uid}" name="births" x="50" y="100" width="18" height="18"><pts><pt x="0" y="100"/><pt x="77" y="100"/></pts></flow>"#
+            ),
+            ObjectKind::Aux => {
+                format!(r#"<aux uid="{uid}" name="birth rate" x="50" y="50"/>"#)
+            }
+            ObjectKind::AuxCircle => format!(
+                r#"<aux uid="{uid}" name="lifetime" x="150" y="50"><shape type="circle" radius="9"/></aux>"#
+            ),
+            ObjectKind::Module => format!(
+                r#"<module uid="{uid}" name="Hares" x="300" y="100" width="45" height="35"/>"#
+            ),
+            ObjectKind::Group => format!(
+                r#"<group uid="{uid}" name="Demographics" x="20" y="20" locked="false"><item uid="1"/></group>"#
+            ),
+            ObjectKind::Connector => format!(
+                r#"<connector uid="{uid}" x="50" y="50" angle="270"><from>birth rate</from><to>births</to></connector>"#
+            ),
+            ObjectKind::ConnectorArc => format!(
+                r#"<connector uid="{uid}" x="100" y="100" angle="135" polarity="+"><from>Population</from><to>births</to><pts><pt x="100" y="100"/><pt x="70" y="80"/><pt x="50" y="100"/></pts></connector>"#
+            ),
+            ObjectKind::ConnectorFromAlias => format!(
+                r#"<connector uid="{uid}" x="200" y="50" angle="180" delay_mark="true"><from><alias uid="{alias_uid}"/></from><to>births</to></connector>"#
+            ),
+            ObjectKind::Alias => {
+                format!(r#"<alias uid="{uid}" x="200" y="50"><of>birth rate</of></alias>"#)
+            }
+            ObjectKind::AliasNameOnly => format!(
+                r#"<alias uid="{uid}" x="250" y="50"><shape type="name_only" width="40" height="12"/><of>lifetime</of></alias>"#
+            ),
+            ObjectKind::StackedContainer => format!(
+                r#"<stacked_container uid="{uid}" x="400" y="100" width="200" height="150" visible_index="0"/>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Slider => format!(
+                r#"<slider uid="{uid}" x="10" y="300" width="197" height="43" min="0" max="1"><entity name="birth rate"/><reset_to after="one_time_unit">0.1</reset_to></slider>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Knob => format!(
+                r#"<knob uid="{uid}" x="220" y="300" width="60" height="60" min="0" max="1000"><entity name="Population"/></knob>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Switch => format!(
+                r#"<switch uid="{uid}" x="300" y="300" width="40" height="40" show_name="true" switch_style="Toggle" clicking_sound="false" entity_name="policy" entity_value="1"/>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Options => format!(
+                r#"<options uid="{uid}" x="360" y="300" width="100" height="60" layout="Vertical" horizontal_spacing="5" vertical_spacing="5"><entity name="policy">0</entity><entity name="policy">1</entity></options>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::NumericInput => format!(
+                r#"<numeric_input uid="{uid}" x="10" y="360" width="80" height="20" entity_name="lifetime" min="1" max="100" value="20"/>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::ListInput => format!(
+                r#"<list_input uid="{uid}" x="100" y="360" width="120" height="60" name="Inputs" column_width="60"><numeric_input uid="{}" x="100" y="360" width="60" height="20" entity_name="birth rate" min="0" max="1" value="0.1"/></list_input>"#,
+                uid + 1000
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::GraphicalInput => format!(
+                r#"<graphical_input uid="{uid}" x="240" y="360" width="120" height="80" entity_name="crowding"/>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::NumericDisplay => format!(
+                r#"<numeric_display uid="{uid}" x="380" y="360" width="80" height="20" entity_name="Population" precision="0.1"/>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Lamp => format!(
+                r#"<lamp uid="{uid}" x="480" y="360" width="20" height="20"><entity name="Population"/><zones><zone type="normal" min="0" max="500" color="green"/><zone type="panic" min="500" max="1000" color="red"/></zones></lamp>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Gauge => format!(
+                r#"<gauge uid="{uid}" x="520" y="360" width="80" height="80"><entity name="Population"/><zones><zone type="normal" min="0" max="1000" color="green"/></zones></gauge>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Graph => format!(
+                r#"<graph uid="{uid}" x="10" y="460" width="300" height="200" graph_type="TimeSeries" show_grid="true" num_x_grid_lines="5" num_y_grid_lines="5" num_x_labels="5" num_y_labels="5" right_axis_auto_scale="true" right_axis_multi_scale="false" left_axis_auto_scale="true" left_axis_multi_scale="false" plot_numbers="false" comparative="false"><plot index="0" pen_width="1" pen_style="Solid" show_y_axis="true" title="Population" right_axis="false" entity_name="Population"/></graph>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Table => format!(
+                r#"<table uid="{uid}" x="320" y="460" width="300" height="200" orientation="vertical" column_width="80" interval="1" report_balances="beginning" report_flows="instantaneous" comparative="false" wrap_text="false"><item type="time"/><item type="variable" entity_name="Population" precision="1"/></table>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::TextBox => format!(
+                r#"<text_box uid="{uid}" x="640" y="460" width="120" height="40" appearance="Transparent">Births depend on the population</text_box>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::GraphicsFrameImage => format!(
+                r#"<graphics_frame uid="{uid}" x="640" y="300" width="120" height="80"><image size_to_parent="true">images/logo.png</image></graphics_frame>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::GraphicsFrameVideo => format!(
+                r#"<graphics_frame uid="{uid}" x="640" y="400" width="120" height="80"><video width="640" height="480">media/intro.mp4</video></graphics_frame>"#
+            ),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Button => format!(
+                r#"<button uid="{uid}" x="780" y="300" width="80" height="30" appearance="Opaque" style="Rounded" label="Run" clicking_sound="true"/>"#
+            ),
+        }
+    }
+
+    /// Counts the objects of this kind in `view`.
+    pub fn count(self, view: &View) -> usize {
+        fn count<T>(objects: &[T], matches: impl Fn(&T) -> bool) -> usize {
+            objects.iter().filter(|object| matches(object)).count()
+        }
+        match self {
+            ObjectKind::Stock => count(&view.stocks, |stock| stock.shape.is_none()),
+            ObjectKind::StockRectangle => count(&view.stocks, |stock| {
+                matches!(stock.shape, Some(Shape::Rectangle { .. }))
+            }),
+            ObjectKind::Flow => count(&view.flows, |flow| !flow.pts.is_empty()),
+            ObjectKind::Aux => count(&view.auxes, |aux| aux.shape.is_none()),
+            ObjectKind::AuxCircle => count(&view.auxes, |aux| {
+                matches!(aux.shape, Some(Shape::Circle { .. }))
+            }),
+            ObjectKind::Module => view.modules.len(),
+            ObjectKind::Group => count(&view.groups, |group| !group.items.is_empty()),
+            ObjectKind::Connector => count(&view.connectors, |connector| {
+                connector.pts.is_empty() && matches!(connector.from, Pointer::Name(_))
+            }),
+            ObjectKind::ConnectorArc => {
+                count(&view.connectors, |connector| !connector.pts.is_empty())
+            }
+            ObjectKind::ConnectorFromAlias => count(&view.connectors, |connector| {
+                matches!(connector.from, Pointer::Alias(_))
+            }),
+            ObjectKind::Alias => count(&view.aliases, |alias| alias.shape.is_none()),
+            ObjectKind::AliasNameOnly => count(&view.aliases, |alias| {
+                matches!(alias.shape, Some(Shape::NameOnly { .. }))
+            }),
+            ObjectKind::StackedContainer => view.stacked_containers.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Slider => view.sliders.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Knob => view.knobs.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Switch => view.switches.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Options => count(&view.options, |options| !options.entities.is_empty()),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::NumericInput => view.numeric_inputs.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::ListInput => {
+                count(&view.list_inputs, |list| !list.numeric_inputs.is_empty())
+            }
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::GraphicalInput => view.graphical_inputs.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::NumericDisplay => view.numeric_displays.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Lamp => count(&view.lamps, |lamp| !lamp.zones.is_empty()),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Gauge => count(&view.gauges, |gauge| !gauge.zones.is_empty()),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Graph => count(&view.graphs, |graph| !graph.plots.is_empty()),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Table => count(&view.tables, |table| !table.items.is_empty()),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::TextBox => view.text_boxes.len(),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::GraphicsFrameImage => count(&view.graphics_frames, |frame| {
+                matches!(frame.content, GraphicsFrameContent::Image(_))
+            }),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::GraphicsFrameVideo => count(&view.graphics_frames, |frame| {
+                matches!(frame.content, GraphicsFrameContent::Video(_))
+            }),
+            #[cfg(feature = "interface-objects")]
+            ObjectKind::Button => view.buttons.len(),
+        }
+    }
+}
+
+/// Builds the XML of a view from object fixtures.
+#[derive(Debug, Clone)]
+pub struct ViewBuilder {
+    uid: i32,
+    view_type: String,
+    kinds: Vec<ObjectKind>,
+}
+
+impl ViewBuilder {
+    /// Creates an empty stock and flow view.
+    pub fn new(uid: i32) -> Self {
+        ViewBuilder {
+            uid,
+            view_type: "stock_flow".to_string(),
+            kinds: Vec::new(),
+        }
+    }
+
+    /// Sets the `type` attribute of the view.
+    pub fn view_type(mut self, view_type: &str) -> Self {
+        self.view_type = view_type.to_string();
+        self
+    }
+
+    /// Adds one object of the given kind.
+    pub fn with(mut self, kind: ObjectKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    /// Adds one object of every kind.
+    pub fn with_all(mut self) -> Self {
+        self.kinds.extend_from_slice(ObjectKind::ALL);
+        self
+    }
+
+    /// Returns the XML of the view.
+    ///
+    /// Objects are numbered from uid 1 in the order they were added.
+    pub fn to_xml(&self) -> String {
+        let alias_uid = self
+            .kinds
+            .iter()
+            .position(|kind| matches!(kind, ObjectKind::Alias | ObjectKind::AliasNameOnly))
+            .map_or(0, |index| index as i32 + 1);
+        let mut xml = format!(
+            r#"<view uid="{}" type="{}" width="1000" height="800" page_width="1000" page_height="800">"#,
+            self.uid, self.view_type
+        );
+        for (index, kind) in self.kinds.iter().enumerate() {
+            let _ = write!(xml, "{}", kind.fixture(index as i32 + 1, alias_uid));
+        }
+        xml.push_str("</view>");
+        xml
+    }
+
+    /// Parses the XML of the view.
+    pub fn build(&self) -> Result<View, quick_xml::DeError> {
+        quick_xml::de::from_str(&self.to_xml())
+    }
+}
+
+/// Returns the XML of a view with one object of every kind.
+pub fn synthetic_view_xml() -> String {
+    ViewBuilder::new(1).with_all().to_xml()
+}
+
+/// Returns a complete XMILE document whose model defines every variable the
+/// fixtures refer to and whose `<views>` holds a view with one object of
+/// every kind.
+pub fn synthetic_model_xml() -> String {
+    format!(
+        r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>xmile</vendor>
+        <product version="1.0">xmile</product>
+    </header>
+    <sim_specs>
+        <start>0</start>
+        <stop>100</stop>
+        <dt>1</dt>
+    </sim_specs>
+    <model>
+        <variables>
+            <stock name="Population">
+                <eqn>100</eqn>
+                <inflow>births</inflow>
+            </stock>
+            <flow name="births">
+                <eqn>Population * birth_rate * crowding(Population / 1000)</eqn>
+            </flow>
+            <aux name="birth rate">
+                <eqn>0.1</eqn>
+            </aux>
+            <aux name="lifetime">
+                <eqn>20</eqn>
+            </aux>
+            <aux name="policy">
+                <eqn>0</eqn>
+            </aux>
+            <gf name="crowding">
+                <xscale min="0" max="1"/>
+                <ypts>1,0.5,0</ypts>
+            </gf>
+        </variables>
+        <views>
+            {}
+        </views>
+    </model>
+</xmile>"#,
+        synthetic_view_xml()
+    )
+}
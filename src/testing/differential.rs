@@ -0,0 +1,400 @@
+//! Differential testing of simulation results against another engine.
+//!
+//! An [`ExternalEngine`] simulates a model file and returns its results.
+//! [`Harness::check`] runs a model in the reference engine and compares the
+//! result with a run from this crate, reporting every variable and time at
+//! which the two diverge by more than the tolerance.
+//!
+//! [`CommandEngine`] runs a reference engine as a subprocess that writes
+//! its results to standard output as CSV, with time in the first column.
+//! For PySD, a small script that loads the model, runs it and prints
+//! `model.run().to_csv()` will do.
+//!
+//! ```rust,no_run
+//! use std::path::Path;
+//! use xmile::data::export::ExportData;
+//! use xmile::testing::differential::{CommandEngine, Harness};
+//!
+//! let pysd = CommandEngine::new("pysd", "python3").arg("run_pysd.py").arg("{model}");
+//! let ours = ExportData::new(vec![0.0, 1.0]).with_series("Population", vec![100.0, 102.0]);
+//! let report = Harness::new(&pysd).check(Path::new("population.xmile"), &ours).unwrap();
+//! println!("{report}");
+//! ```
+
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+use thiserror::Error;
+
+use crate::data::export::ExportData;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EngineError {
+    #[error("Failed to start {engine}: {source}")]
+    Io {
+        engine: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{engine} failed to simulate the model: {message}")]
+    Failed { engine: String, message: String },
+    #[error("Invalid results from {engine}: {message}")]
+    Output { engine: String, message: String },
+}
+
+/// A simulation engine that can run a model file.
+pub trait ExternalEngine {
+    /// A name for the engine, used in reports.
+    fn name(&self) -> &str;
+
+    /// Simulates the model in `model` and returns its saved steps.
+    fn run(&self, model: &Path) -> Result<ExportData, EngineError>;
+}
+
+/// An engine run as a command that writes CSV results to standard output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandEngine {
+    name: String,
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandEngine {
+    /// Placeholder in arguments replaced by the path of the model.
+    pub const MODEL_PLACEHOLDER: &'static str = "{model}";
+
+    pub fn new(name: &str, program: &str) -> Self {
+        CommandEngine {
+            name: name.to_string(),
+            program: program.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Adds an argument. If no argument contains
+    /// [`Self::MODEL_PLACEHOLDER`], the model path is passed last.
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+}
+
+impl ExternalEngine for CommandEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, model: &Path) -> Result<ExportData, EngineError> {
+        let path = model.display().to_string();
+        let mut command = Command::new(&self.program);
+        if self
+            .args
+            .iter()
+            .any(|arg| arg.contains(Self::MODEL_PLACEHOLDER))
+        {
+            command.args(
+                self.args
+                    .iter()
+                    .map(|arg| arg.replace(Self::MODEL_PLACEHOLDER, &path)),
+            );
+        } else {
+            command.args(&self.args).arg(&path);
+        }
+
+        let output = command.output().map_err(|source| EngineError::Io {
+            engine: self.name.clone(),
+            source,
+        })?;
+        if !output.status.success() {
+            return Err(EngineError::Failed {
+                engine: self.name.clone(),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        read_csv(&String::from_utf8_lossy(&output.stdout)).map_err(|message| EngineError::Output {
+            engine: self.name.clone(),
+            message,
+        })
+    }
+}
+
+/// Reads results written as CSV with a header row, time in the first column
+/// and one column per variable.
+pub fn read_csv(text: &str) -> Result<ExportData, String> {
+    let mut rows = text.lines().filter(|line| !line.trim().is_empty());
+    let header = split_row(rows.next().ok_or("no header row")?);
+    if header.len() < 2 {
+        return Err("expected a time column and at least one variable".to_string());
+    }
+
+    let mut times = Vec::new();
+    let mut columns = vec![Vec::new(); header.len() - 1];
+    for (index, row) in rows.enumerate() {
+        let fields = split_row(row);
+        if fields.len() != header.len() {
+            return Err(format!(
+                "row {} has {} fields, expected {}",
+                index + 2,
+                fields.len(),
+                header.len()
+            ));
+        }
+        let mut values = fields.iter().map(|field| {
+            field
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("row {}: '{}' is not a number", index + 2, field))
+        });
+        times.push(values.next().unwrap()?);
+        for column in &mut columns {
+            column.push(values.next().unwrap()?);
+        }
+    }
+
+    Ok(header
+        .iter()
+        .skip(1)
+        .zip(columns)
+        .fold(ExportData::new(times), |data, (name, values)| {
+            data.with_series(name.trim(), values)
+        }))
+}
+
+/// Splits a CSV row into fields, unquoting quoted fields.
+fn split_row(row: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = row.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// How far values from the two engines may differ: by `absolute` plus
+/// `relative` times the size of the reference value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifferentialTolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl Default for DifferentialTolerance {
+    fn default() -> Self {
+        DifferentialTolerance {
+            absolute: 1e-6,
+            relative: 1e-4,
+        }
+    }
+}
+
+impl DifferentialTolerance {
+    pub fn accepts(&self, value: f64, reference: f64) -> bool {
+        (value.is_nan() && reference.is_nan())
+            || (value - reference).abs() <= self.absolute + self.relative * reference.abs()
+    }
+}
+
+/// A value that differs between the two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub variable: String,
+    pub time: f64,
+    pub value: f64,
+    pub reference: f64,
+}
+
+/// The differences between a run and a reference run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DifferentialReport {
+    /// The name of the reference engine.
+    pub engine: String,
+    /// Variables compared at each saved time of the reference run.
+    pub compared: Vec<String>,
+    /// Variables of the reference run with no results in the run.
+    pub missing: Vec<String>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl DifferentialReport {
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty() && self.divergences.is_empty()
+    }
+
+    /// Returns the divergences of one variable, in time order.
+    pub fn divergences_of<'a>(&'a self, variable: &'a str) -> impl Iterator<Item = &'a Divergence> {
+        self.divergences
+            .iter()
+            .filter(move |divergence| divergence.variable == variable)
+    }
+}
+
+impl fmt::Display for DifferentialReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for variable in &self.compared {
+            let mut divergences = self.divergences_of(variable).peekable();
+            let Some(first) = divergences.peek().copied() else {
+                writeln!(f, "ok    {variable}")?;
+                continue;
+            };
+            let (count, worst) = divergences.fold((0, first), |(count, worst), divergence| {
+                let error = (divergence.value - divergence.reference).abs();
+                let worst_error = (worst.value - worst.reference).abs();
+                (
+                    count + 1,
+                    if error > worst_error {
+                        divergence
+                    } else {
+                        worst
+                    },
+                )
+            });
+            writeln!(
+                f,
+                "DIFF  {variable}: {count} divergences from t = {}, largest at t = {} ({} vs {} from {})",
+                first.time, worst.time, worst.value, worst.reference, self.engine
+            )?;
+        }
+        for variable in &self.missing {
+            writeln!(
+                f,
+                "MISS  {variable}: no results to compare with {}",
+                self.engine
+            )?;
+        }
+        let diverged = self
+            .compared
+            .iter()
+            .filter(|variable| self.divergences_of(variable.as_str()).next().is_some())
+            .count();
+        write!(
+            f,
+            "{} matched, {} diverged, {} missing",
+            self.compared.len() - diverged,
+            diverged,
+            self.missing.len()
+        )
+    }
+}
+
+/// Compares runs with the results of a reference engine.
+pub struct Harness<'a> {
+    engine: &'a dyn ExternalEngine,
+    tolerance: DifferentialTolerance,
+}
+
+impl<'a> Harness<'a> {
+    pub fn new(engine: &'a dyn ExternalEngine) -> Self {
+        Harness {
+            engine,
+            tolerance: DifferentialTolerance::default(),
+        }
+    }
+
+    pub fn with_tolerance(mut self, tolerance: DifferentialTolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Runs `model` in the reference engine and compares the results with
+    /// `run`.
+    pub fn check(&self, model: &Path, run: &ExportData) -> Result<DifferentialReport, EngineError> {
+        let reference = self.engine.run(model)?;
+        Ok(self.compare(run, &reference))
+    }
+
+    /// Compares `run` with `reference` at each saved time of the reference,
+    /// interpolating between the saved times of `run`. Variables are
+    /// matched as XMILE identifiers.
+    pub fn compare(&self, run: &ExportData, reference: &ExportData) -> DifferentialReport {
+        let mut report = DifferentialReport {
+            engine: self.engine.name().to_string(),
+            ..Default::default()
+        };
+        for (variable, values) in &reference.series {
+            if run.series(variable).is_none() {
+                report.missing.push(variable.clone());
+                continue;
+            }
+            report.compared.push(variable.clone());
+            for (&time, &expected) in reference.times.iter().zip(values) {
+                let value = run.value_at(variable, time).unwrap_or(f64::NAN);
+                if !self.tolerance.accepts(value, expected) {
+                    report.divergences.push(Divergence {
+                        variable: variable.clone(),
+                        time,
+                        value,
+                        reference: expected,
+                    });
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(ExportData);
+
+    impl ExternalEngine for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn run(&self, _: &Path) -> Result<ExportData, EngineError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_harness_reports_divergences() {
+        let reference = read_csv(
+            "time,\"Population\",Births,Deaths\n0,100,10,5\n1,105,10.5,5.25\n2,110.25,11.025,5.5125\n",
+        )
+        .unwrap();
+        assert_eq!(reference.times, vec![0.0, 1.0, 2.0]);
+        let engine = Fixed(reference);
+
+        let run = ExportData::new(vec![0.0, 0.5, 1.0, 1.5, 2.0])
+            .with_series("population", vec![100.0, 102.5, 105.0, 107.6, 110.25])
+            .with_series("births", vec![10.0, 10.25, 10.5, 10.8, 11.2]);
+        let report = Harness::new(&engine)
+            .check(Path::new("model.xmile"), &run)
+            .unwrap();
+
+        assert_eq!(report.compared, vec!["Population", "Births"]);
+        assert_eq!(report.missing, vec!["Deaths"]);
+        let births: Vec<f64> = report.divergences_of("Births").map(|d| d.time).collect();
+        assert_eq!(births, vec![2.0]);
+        assert_eq!(report.divergences_of("Population").count(), 0);
+        assert!(!report.passed());
+        assert!(
+            report
+                .to_string()
+                .ends_with("1 matched, 1 diverged, 1 missing")
+        );
+    }
+
+    #[test]
+    fn test_read_csv_errors() {
+        assert!(read_csv("").is_err());
+        assert!(read_csv("time\n0\n").is_err());
+        assert!(read_csv("time,x\n0,1,2\n").is_err());
+        assert!(read_csv("time,x\n0,one\n").is_err());
+    }
+}
@@ -0,0 +1,8 @@
+//! Helpers for testing code that reads and writes XMILE files, and for
+//! testing models against their simulation results.
+
+pub mod assertions;
+pub mod differential;
+
+#[cfg(feature = "views")]
+pub mod builders;
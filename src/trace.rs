@@ -0,0 +1,38 @@
+//! Instrumentation with [`tracing`](https://docs.rs/tracing), behind the
+//! `tracing` feature.
+//!
+//! Parsing, validation and runs open spans, and report counts in events, so
+//! services embedding the crate can see which phase of a request is slow or
+//! failing:
+//!
+//! - `xmile.parse`: one span per parsed document, with `deserialize` and
+//!   `resolve` child spans, and an event with the number of models and
+//!   variables parsed.
+//! - `xmile.validate`: one span per validated file, with a `model.validate`
+//!   child span per model, and events with the number of warnings and errors.
+//! - `xmile.run`: one span per run of a
+//!   [`Simulator`](crate::simulation::Simulator), with the number of steps,
+//!   and a `ran` event with the integration steps taken and the equations
+//!   evaluated.
+//! - `run.finish`: an event with the number of steps saved and kept when a
+//!   [`ResultRecorder`](crate::data::ResultRecorder) finishes.
+//!
+//! Without the feature the macros below expand to nothing.
+
+/// Enters an `info` span that lasts until the end of the enclosing block.
+macro_rules! enter_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+/// Emits an event at the given level.
+macro_rules! event {
+    ($level:ident, $($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($args)*);
+    };
+}
+
+pub(crate) use {enter_span, event};
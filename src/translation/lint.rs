@@ -0,0 +1,289 @@
+//! Lints over the human-readable text in a model.
+//!
+//! A [`TextLint`] checks one piece of text, such as a variable's
+//! documentation or the label drawn for a stock, and reports what it finds.
+//! [`lint`] applies lints to every entry of a file's [`Catalog`], and
+//! [`validate_text`] reports the findings as validation warnings, so that a
+//! team's spelling and terminology rules are checked alongside the model.
+//!
+//! Two lints are provided: [`Dictionary`], which flags words missing from a
+//! word list, and [`Terminology`], which flags discouraged terms and suggests
+//! the preferred ones. Any `Fn(&str) -> Vec<LintFinding>` is also a lint.
+//!
+//! ```rust
+//! use xmile::translation::lint::{Terminology, TextLint};
+//!
+//! let terminology = Terminology::new().prefer("inflow", &["input flow", "influx"]);
+//! let findings = terminology.check("Influx of new customers");
+//! assert_eq!(findings[0].suggestion.as_deref(), Some("inflow"));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::types::ValidationResult;
+use crate::xml::XmileFile;
+
+use super::{Catalog, TextSource};
+
+/// Something a lint found in a piece of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub message: String,
+    /// Replacement text for what was found, if the lint has one.
+    pub suggestion: Option<String>,
+}
+
+/// Checks a piece of human-readable text.
+pub trait TextLint {
+    fn check(&self, text: &str) -> Vec<LintFinding>;
+}
+
+impl<F> TextLint for F
+where
+    F: Fn(&str) -> Vec<LintFinding>,
+{
+    fn check(&self, text: &str) -> Vec<LintFinding> {
+        self(text)
+    }
+}
+
+/// A finding and the text it was found in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextFinding {
+    pub source: TextSource,
+    pub finding: LintFinding,
+}
+
+/// Applies `lints` to every piece of text in `file`, in document order.
+pub fn lint(file: &XmileFile, lints: &[&dyn TextLint]) -> Vec<TextFinding> {
+    let mut findings = Vec::new();
+    for entry in Catalog::extract(file).entries {
+        for lint in lints {
+            findings.extend(
+                lint.check(&entry.text)
+                    .into_iter()
+                    .map(|finding| TextFinding {
+                        source: entry.source.clone(),
+                        finding,
+                    }),
+            );
+        }
+    }
+    findings
+}
+
+/// Applies `lints` to every piece of text in `file`, reporting each finding
+/// as a warning.
+pub fn validate_text(file: &XmileFile, lints: &[&dyn TextLint]) -> ValidationResult {
+    let warnings: Vec<String> = lint(file, lints)
+        .into_iter()
+        .map(|TextFinding { source, finding }| match finding.suggestion {
+            Some(suggestion) => format!(
+                "In the {}: {} Consider '{}'.",
+                source, finding.message, suggestion
+            ),
+            None => format!("In the {}: {}", source, finding.message),
+        })
+        .collect();
+    if warnings.is_empty() {
+        ValidationResult::Valid(())
+    } else {
+        ValidationResult::Warnings((), warnings)
+    }
+}
+
+/// Flags words that are not in a word list. Words are compared ignoring
+/// case, and words containing digits are not checked.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Dictionary {
+            words: words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Reads a word list with one word per line.
+    pub fn from_word_list(list: &str) -> Self {
+        Dictionary::new(list.lines().map(str::trim).filter(|line| !line.is_empty()))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+}
+
+impl TextLint for Dictionary {
+    fn check(&self, text: &str) -> Vec<LintFinding> {
+        let mut reported = HashSet::new();
+        words(text)
+            .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+            .filter(|word| !self.contains(word))
+            .filter(|word| reported.insert(word.to_lowercase()))
+            .map(|word| LintFinding {
+                message: format!("Unknown word '{}'.", word),
+                suggestion: None,
+            })
+            .collect()
+    }
+}
+
+/// Flags discouraged terms, suggesting the preferred term for each. Terms
+/// may be several words long and are matched as whole words, ignoring case.
+#[derive(Debug, Clone, Default)]
+pub struct Terminology {
+    /// Discouraged terms, as lowercase words, with their preferred term.
+    discouraged: HashMap<Vec<String>, String>,
+}
+
+impl Terminology {
+    pub fn new() -> Self {
+        Terminology::default()
+    }
+
+    /// Prefers `preferred` over each of the `discouraged` terms.
+    pub fn prefer(mut self, preferred: &str, discouraged: &[&str]) -> Self {
+        for term in discouraged {
+            let term: Vec<String> = words(term).map(str::to_lowercase).collect();
+            if !term.is_empty() {
+                self.discouraged.insert(term, preferred.to_string());
+            }
+        }
+        self
+    }
+}
+
+impl TextLint for Terminology {
+    fn check(&self, text: &str) -> Vec<LintFinding> {
+        let words: Vec<&str> = words(text).collect();
+        let lowercase: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+        let mut findings = Vec::new();
+        let mut start = 0;
+        while start < words.len() {
+            let found = self
+                .discouraged
+                .iter()
+                .filter(|(term, _)| lowercase[start..].starts_with(term))
+                .max_by_key(|(term, _)| term.len());
+            match found {
+                Some((term, preferred)) => {
+                    findings.push(LintFinding {
+                        message: format!(
+                            "'{}' is a discouraged term.",
+                            words[start..start + term.len()].join(" ")
+                        ),
+                        suggestion: Some(preferred.clone()),
+                    });
+                    start += term.len();
+                }
+                None => start += 1,
+            }
+        }
+        findings
+    }
+}
+
+/// The words of `text`, skipping markup between angle brackets so HTML
+/// documentation is checked by its text. Apostrophes within words are kept.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let mut in_tag = false;
+    text.split(move |c: char| {
+        match c {
+            '<' => in_tag = true,
+            '>' => {
+                in_tag = false;
+                return true;
+            }
+            _ => {}
+        }
+        in_tag || !(c.is_alphanumeric() || c == '\'')
+    })
+    .map(|word| word.trim_matches('\''))
+    .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Example</vendor>
+            <product version="1.0">Example</product>
+            <name>Customers</name>
+        </header>
+        <model>
+            <variables>
+                <stock name="Customers">
+                    <eqn>100</eqn>
+                    <inflow>acquisition</inflow>
+                    <doc><![CDATA[<p>Number of <b>custmers</b></p>]]></doc>
+                </stock>
+                <flow name="acquisition">
+                    <eqn>10</eqn>
+                    <doc>Input flow of new customers</doc>
+                </flow>
+            </variables>
+        </model>
+    </xmile>"#;
+
+    #[test]
+    fn test_lint_documentation() {
+        let file = XmileFile::from_str(MODEL).unwrap();
+        let dictionary = Dictionary::from_word_list("customers\nnumber\nof\nnew\ninput\nflow\n");
+        let terminology = Terminology::new().prefer("inflow", &["input flow", "influx"]);
+
+        let findings = lint(&file, &[&dictionary, &terminology]);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].finding.message, "Unknown word 'custmers'.");
+        assert_eq!(
+            findings[1].source,
+            TextSource::Documentation {
+                model: 0,
+                variable: "acquisition".to_string()
+            }
+        );
+        assert_eq!(findings[1].finding.suggestion.as_deref(), Some("inflow"));
+
+        let ValidationResult::Warnings(_, warnings) = validate_text(&file, &[&terminology]) else {
+            panic!("expected warnings");
+        };
+        assert_eq!(
+            warnings,
+            vec![
+                "In the documentation of 'acquisition' in model 0: \
+                 'Input flow' is a discouraged term. Consider 'inflow'."
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closure_lint() {
+        let shouting = |text: &str| {
+            if text.chars().any(char::is_lowercase) {
+                Vec::new()
+            } else {
+                vec![LintFinding {
+                    message: "Text is all capitals.".to_string(),
+                    suggestion: None,
+                }]
+            }
+        };
+        let file =
+            XmileFile::from_str(&MODEL.replace("Customers</name>", "CUSTOMERS</name>")).unwrap();
+        let findings = lint(&file, &[&shouting]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].source, TextSource::ModelName);
+    }
+}
@@ -0,0 +1,510 @@
+//! Catalogs of the human-readable text in a model, for translation.
+//!
+//! [`Catalog::extract`] lists every piece of text a reader of the model
+//! sees: the model's name and caption, variable documentation, and the
+//! titles, labels and text of interface objects. [`localize`] then applies a
+//! map from original to translated text, producing a localized copy of the
+//! file that can be distributed alongside the original.
+//!
+//! Variable names are listed as they are drawn on diagrams so translators see
+//! them in context, but are never replaced: XMILE draws a variable's label
+//! from its name, which equations and display objects refer to.
+//!
+//! The [`lint`] module checks the same text against spelling and terminology
+//! rules.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use xmile::translation::{Catalog, localize};
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Example</vendor><product version="1.0">Example</product><caption>Population growth</caption></header>
+//!     <model><variables><aux name="rate"><eqn>0.1</eqn><doc>Births per person per year</doc></aux></variables></model>
+//! </xmile>"#).unwrap();
+//!
+//! let catalog = Catalog::extract(&file);
+//! assert!(catalog.messages().contains(&"Population growth"));
+//!
+//! let translations = HashMap::from([(
+//!     "Population growth".to_string(),
+//!     "Croissance démographique".to_string(),
+//! )]);
+//! let localized = localize(&file, &translations);
+//! assert_eq!(localized.header.caption.as_deref(), Some("Croissance démographique"));
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::model::object::Documentation;
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+use crate::xml::XmileFile;
+
+#[cfg(feature = "views")]
+use crate::Uid;
+
+pub mod lint;
+
+/// Where a piece of text appears in a file. Models are identified by their
+/// index in the file and view objects by their uid within a view.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextSource {
+    /// The `<name>` in the header.
+    ModelName,
+    /// The `<caption>` in the header.
+    Caption,
+    /// A variable's `<doc>`.
+    Documentation { model: usize, variable: String },
+    /// The label drawn for a stock, flow, auxiliary or module.
+    #[cfg(feature = "views")]
+    DisplayName {
+        model: usize,
+        view: Uid,
+        object: Uid,
+    },
+    /// The title of a graph or table.
+    #[cfg(feature = "interface-objects")]
+    Title {
+        model: usize,
+        view: Uid,
+        object: Uid,
+    },
+    /// The documentation of a graph or table.
+    #[cfg(feature = "interface-objects")]
+    ObjectDoc {
+        model: usize,
+        view: Uid,
+        object: Uid,
+    },
+    /// The title of one of a graph's plots.
+    #[cfg(feature = "interface-objects")]
+    PlotTitle {
+        model: usize,
+        view: Uid,
+        object: Uid,
+        plot: u32,
+    },
+    /// The text of a text box, or of the text box a button pops up.
+    #[cfg(feature = "interface-objects")]
+    TextBox {
+        model: usize,
+        view: Uid,
+        object: Uid,
+    },
+    /// The label of a button.
+    #[cfg(feature = "interface-objects")]
+    ButtonLabel {
+        model: usize,
+        view: Uid,
+        object: Uid,
+    },
+}
+
+impl TextSource {
+    /// Whether [`localize`] replaces text from this source.
+    pub fn is_replaceable(&self) -> bool {
+        #[cfg(feature = "views")]
+        if let TextSource::DisplayName { .. } = self {
+            return false;
+        }
+        true
+    }
+}
+
+impl fmt::Display for TextSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "views")]
+        let object =
+            |f: &mut fmt::Formatter<'_>, what: &str, model: &usize, view: &Uid, object: &Uid| {
+                write!(
+                    f,
+                    "{} of object {} in view {} of model {}",
+                    what, object.value, view.value, model
+                )
+            };
+        match self {
+            TextSource::ModelName => write!(f, "model name"),
+            TextSource::Caption => write!(f, "caption"),
+            TextSource::Documentation { model, variable } => {
+                write!(f, "documentation of '{}' in model {}", variable, model)
+            }
+            #[cfg(feature = "views")]
+            TextSource::DisplayName {
+                model,
+                view,
+                object: uid,
+            } => object(f, "display name", model, view, uid),
+            #[cfg(feature = "interface-objects")]
+            TextSource::Title {
+                model,
+                view,
+                object: uid,
+            } => object(f, "title", model, view, uid),
+            #[cfg(feature = "interface-objects")]
+            TextSource::ObjectDoc {
+                model,
+                view,
+                object: uid,
+            } => object(f, "documentation", model, view, uid),
+            #[cfg(feature = "interface-objects")]
+            TextSource::PlotTitle {
+                model,
+                view,
+                object: uid,
+                plot,
+            } => object(f, &format!("title of plot {}", plot), model, view, uid),
+            #[cfg(feature = "interface-objects")]
+            TextSource::TextBox {
+                model,
+                view,
+                object: uid,
+            } => object(f, "text", model, view, uid),
+            #[cfg(feature = "interface-objects")]
+            TextSource::ButtonLabel {
+                model,
+                view,
+                object: uid,
+            } => object(f, "label", model, view, uid),
+        }
+    }
+}
+
+/// A piece of text and where it appears.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    pub source: TextSource,
+    pub text: String,
+}
+
+/// Every piece of human-readable text in a file, in document order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Catalog {
+    pub entries: Vec<CatalogEntry>,
+}
+
+impl Catalog {
+    /// Lists the text in `file`, skipping empty text.
+    pub fn extract(file: &XmileFile) -> Self {
+        let mut entries = Vec::new();
+        let mut file = file.clone();
+        visit_text(&mut file, &mut |source, text| {
+            if !text.trim().is_empty() {
+                entries.push(CatalogEntry {
+                    source,
+                    text: text.clone(),
+                });
+            }
+        });
+        Catalog { entries }
+    }
+
+    /// The distinct texts to translate, sorted. Text that is only used for
+    /// display names is included, as it is shown to readers.
+    pub fn messages(&self) -> BTreeSet<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.text.as_str())
+            .collect()
+    }
+
+    /// The replaceable texts that `translations` has no translation for.
+    pub fn untranslated<'a>(&'a self, translations: &HashMap<String, String>) -> BTreeSet<&'a str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.source.is_replaceable())
+            .map(|entry| entry.text.as_str())
+            .filter(|text| !translations.contains_key(*text))
+            .collect()
+    }
+}
+
+/// Returns a copy of `file` with every replaceable text found in
+/// `translations` replaced by its translation. Text without a translation is
+/// left as it is.
+pub fn localize(file: &XmileFile, translations: &HashMap<String, String>) -> XmileFile {
+    let mut localized = file.clone();
+    visit_text(&mut localized, &mut |source, text| {
+        if source.is_replaceable()
+            && let Some(translation) = translations.get(text.as_str())
+        {
+            *text = translation.clone();
+        }
+    });
+    localized
+}
+
+/// Calls `visit` with each piece of text in `file`. Display names are passed
+/// as a copy, so changes to them are discarded.
+fn visit_text(file: &mut XmileFile, visit: &mut dyn FnMut(TextSource, &mut String)) {
+    if let Some(name) = &mut file.header.name {
+        visit(TextSource::ModelName, name);
+    }
+    if let Some(caption) = &mut file.header.caption {
+        visit(TextSource::Caption, caption);
+    }
+
+    for (model, content) in file.models.iter_mut().enumerate() {
+        for variable in &mut content.variables.variables {
+            let Some((name, documentation)) = documentation_of(variable) else {
+                continue;
+            };
+            let text = match documentation {
+                Documentation::PlainText(text) | Documentation::Html(text) => text,
+            };
+            visit(
+                TextSource::Documentation {
+                    model,
+                    variable: name,
+                },
+                text,
+            );
+        }
+
+        #[cfg(feature = "views")]
+        if let Some(views) = &mut content.views {
+            for view in &mut views.views {
+                visit_view(model, view, visit);
+            }
+        }
+    }
+}
+
+/// The name and documentation of a variable that has documentation.
+fn documentation_of(variable: &mut Variable) -> Option<(String, &mut Documentation)> {
+    let (name, documentation) = match variable {
+        Variable::Auxiliary(aux) => (&aux.name, &mut aux.documentation),
+        Variable::Stock(stock) => match stock.as_mut() {
+            Stock::Basic(stock) => (&stock.name, &mut stock.documentation),
+            Stock::Conveyor(stock) => (&stock.name, &mut stock.documentation),
+            Stock::Queue(stock) => (&stock.name, &mut stock.documentation),
+        },
+        Variable::Flow(flow) => (&flow.name, &mut flow.documentation),
+        Variable::GraphicalFunction(gf) => (gf.name.as_ref()?, &mut gf.documentation),
+        #[cfg(feature = "submodels")]
+        Variable::Module(module) => (&module.name, &mut module.documentation),
+        Variable::Group(group) => (&group.name, &mut group.doc),
+    };
+    let name = name.normalized().to_string();
+    documentation
+        .as_mut()
+        .map(|documentation| (name, documentation))
+}
+
+#[cfg(feature = "views")]
+fn visit_view(
+    model: usize,
+    view: &mut crate::view::View,
+    visit: &mut dyn FnMut(TextSource, &mut String),
+) {
+    use crate::view::entity::display_label;
+
+    let view_uid = view.uid;
+    let names = view
+        .stocks
+        .iter()
+        .map(|stock| (stock.uid, &stock.name))
+        .chain(view.flows.iter().map(|flow| (flow.uid, &flow.name)))
+        .chain(view.auxes.iter().map(|aux| (aux.uid, &aux.name)))
+        .chain(view.modules.iter().map(|module| (module.uid, &module.name)));
+    for (object, name) in names {
+        visit(
+            TextSource::DisplayName {
+                model,
+                view: view_uid,
+                object,
+            },
+            &mut display_label(name),
+        );
+    }
+
+    #[cfg(feature = "interface-objects")]
+    visit_interface(model, view, visit);
+}
+
+#[cfg(feature = "interface-objects")]
+fn visit_interface(
+    model: usize,
+    view: &mut crate::view::View,
+    visit: &mut dyn FnMut(TextSource, &mut String),
+) {
+    use crate::view::PopupContent;
+
+    let view_uid = view.uid;
+    let at = |object: Uid| (model, view_uid, object);
+    for graph in &mut view.graphs {
+        let (model, view, object) = at(graph.uid);
+        if let Some(title) = &mut graph.title {
+            visit(
+                TextSource::Title {
+                    model,
+                    view,
+                    object,
+                },
+                title,
+            );
+        }
+        if let Some(doc) = &mut graph.doc {
+            visit(
+                TextSource::ObjectDoc {
+                    model,
+                    view,
+                    object,
+                },
+                doc,
+            );
+        }
+        for plot in &mut graph.plots {
+            visit(
+                TextSource::PlotTitle {
+                    model,
+                    view,
+                    object,
+                    plot: plot.index,
+                },
+                &mut plot.title,
+            );
+        }
+    }
+    for table in &mut view.tables {
+        let (model, view, object) = at(table.uid);
+        if let Some(title) = &mut table.title {
+            visit(
+                TextSource::Title {
+                    model,
+                    view,
+                    object,
+                },
+                title,
+            );
+        }
+        if let Some(doc) = &mut table.doc {
+            visit(
+                TextSource::ObjectDoc {
+                    model,
+                    view,
+                    object,
+                },
+                doc,
+            );
+        }
+    }
+    for text_box in &mut view.text_boxes {
+        let (model, view, object) = at(text_box.uid);
+        visit(
+            TextSource::TextBox {
+                model,
+                view,
+                object,
+            },
+            &mut text_box.content,
+        );
+    }
+    for button in &mut view.buttons {
+        let (model, view, object) = at(button.uid);
+        if let Some(label) = &mut button.label {
+            visit(
+                TextSource::ButtonLabel {
+                    model,
+                    view,
+                    object,
+                },
+                label,
+            );
+        }
+        if let Some(PopupContent::TextBox(text_box)) = &mut button.popup {
+            visit(
+                TextSource::TextBox {
+                    model,
+                    view,
+                    object,
+                },
+                &mut text_box.content,
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "interface-objects"))]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Example</vendor>
+            <product version="1.0">Example</product>
+            <name>Rabbits</name>
+        </header>
+        <model>
+            <variables>
+                <stock name="Rabbit_Population">
+                    <eqn>100</eqn>
+                    <doc>Number of rabbits</doc>
+                </stock>
+                <aux name="rate"><eqn>0.1</eqn></aux>
+            </variables>
+            <views>
+                <view uid="1" width="800" height="600" page_width="800" page_height="600">
+                    <stock uid="2" name="Rabbit_Population" x="100" y="100" width="45" height="35"/>
+                    <text_box uid="3" x="10" y="10" width="200" height="40" appearance="Transparent">Number of rabbits</text_box>
+                    <button uid="4" x="300" y="10" width="80" height="30" appearance="Opaque" style="Rounded" label="Run" clicking_sound="false"/>
+                </view>
+            </views>
+        </model>
+    </xmile>"#;
+
+    #[test]
+    fn test_extract_catalog() {
+        let file = XmileFile::from_str(MODEL).unwrap();
+        let catalog = Catalog::extract(&file);
+        let sources: Vec<_> = catalog.entries.iter().map(|e| &e.source).collect();
+        assert_eq!(sources[0], &TextSource::ModelName);
+        assert_eq!(
+            sources[1],
+            &TextSource::Documentation {
+                model: 0,
+                variable: "Rabbit Population".to_string()
+            }
+        );
+        assert_eq!(
+            catalog.messages().into_iter().collect::<Vec<_>>(),
+            ["Number of rabbits", "Rabbit Population", "Rabbits", "Run"]
+        );
+    }
+
+    #[test]
+    fn test_localize() {
+        let file = XmileFile::from_str(MODEL).unwrap();
+        let translations = HashMap::from([
+            (
+                "Number of rabbits".to_string(),
+                "Nombre de lapins".to_string(),
+            ),
+            (
+                "Rabbit Population".to_string(),
+                "Population de lapins".to_string(),
+            ),
+            ("Run".to_string(), "Lancer".to_string()),
+        ]);
+        let catalog = Catalog::extract(&file);
+        assert_eq!(
+            catalog
+                .untranslated(&translations)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ["Rabbits"]
+        );
+
+        let localized = localize(&file, &translations);
+        let view = &localized.models[0].views.as_ref().unwrap().views[0];
+        assert_eq!(view.text_boxes[0].content, "Nombre de lapins");
+        assert_eq!(view.buttons[0].label.as_deref(), Some("Lancer"));
+        // Names are identifiers, so they are kept
+        assert_eq!(view.stocks[0].name, "Rabbit_Population");
+
+        let localized = Catalog::extract(&localized);
+        assert!(localized.messages().contains("Nombre de lapins"));
+        assert!(!localized.messages().contains("Number of rabbits"));
+    }
+}
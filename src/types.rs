@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
 /// A result type that can contain warnings alongside the successful result.
 ///
@@ -143,8 +143,217 @@ impl<T, W, E: Debug> ValidationResult<T, W, E> {
             ValidationResult::Invalid(_, errors) => Err(errors),
         }
     }
+
+    /// Checks if the validation passed, with or without warnings.
+    pub fn is_ok_with_warnings(&self) -> bool {
+        !self.is_invalid()
+    }
+
+    /// Runs a further validation on the valid data, keeping the warnings of
+    /// both.
+    ///
+    /// `f` is not called if this result is invalid.
+    pub fn and_then<U, F>(self, f: F) -> ValidationResult<U, W, E>
+    where
+        F: FnOnce(T) -> ValidationResult<U, W, E>,
+    {
+        match self {
+            ValidationResult::Valid(data) => f(data),
+            ValidationResult::Warnings(data, mut warnings) => match f(data) {
+                ValidationResult::Valid(data) => ValidationResult::Warnings(data, warnings),
+                ValidationResult::Warnings(data, more) => {
+                    warnings.extend(more);
+                    ValidationResult::Warnings(data, warnings)
+                }
+                ValidationResult::Invalid(more, errors) => {
+                    warnings.extend(more);
+                    ValidationResult::Invalid(warnings, errors)
+                }
+            },
+            ValidationResult::Invalid(warnings, errors) => {
+                ValidationResult::Invalid(warnings, errors)
+            }
+        }
+    }
+
+    /// Adds the warnings and errors of `other` to this result, keeping this
+    /// result's data.
+    ///
+    /// Unlike [`and_then`](ValidationResult::and_then), both validations have
+    /// already run, so the errors of both are reported.
+    pub fn merge(self, other: ValidationResult<(), W, E>) -> ValidationResult<T, W, E> {
+        let (data, mut warnings, mut errors) = self.into_parts();
+        let (_, more_warnings, more_errors) = other.into_parts();
+        warnings.extend(more_warnings);
+        errors.extend(more_errors);
+        match data {
+            Some(data) if errors.is_empty() => ValidationResult::from_parts(data, warnings, errors),
+            _ => ValidationResult::Invalid(warnings, errors),
+        }
+    }
+
+    /// Transforms each warning, such as to add the path of the element
+    /// validated.
+    pub fn map_warnings<W2, F>(self, f: F) -> ValidationResult<T, W2, E>
+    where
+        F: FnMut(W) -> W2,
+    {
+        match self {
+            ValidationResult::Valid(data) => ValidationResult::Valid(data),
+            ValidationResult::Warnings(data, warnings) => {
+                ValidationResult::Warnings(data, warnings.into_iter().map(f).collect())
+            }
+            ValidationResult::Invalid(warnings, errors) => {
+                ValidationResult::Invalid(warnings.into_iter().map(f).collect(), errors)
+            }
+        }
+    }
+
+    /// Transforms each error, such as to add the path of the element
+    /// validated.
+    pub fn map_errors<E2: Debug, F>(self, f: F) -> ValidationResult<T, W, E2>
+    where
+        F: FnMut(E) -> E2,
+    {
+        match self {
+            ValidationResult::Valid(data) => ValidationResult::Valid(data),
+            ValidationResult::Warnings(data, warnings) => {
+                ValidationResult::Warnings(data, warnings)
+            }
+            ValidationResult::Invalid(warnings, errors) => {
+                ValidationResult::Invalid(warnings, errors.into_iter().map(f).collect())
+            }
+        }
+    }
+
+    /// Builds a result from valid data and the diagnostics found, which is
+    /// invalid if there are any errors.
+    pub fn from_parts(data: T, warnings: Vec<W>, errors: Vec<E>) -> Self {
+        if !errors.is_empty() {
+            ValidationResult::Invalid(warnings, errors)
+        } else if !warnings.is_empty() {
+            ValidationResult::Warnings(data, warnings)
+        } else {
+            ValidationResult::Valid(data)
+        }
+    }
+
+    /// Splits the result into its data, if valid, its warnings and its
+    /// errors.
+    pub fn into_parts(self) -> (Option<T>, Vec<W>, Vec<E>) {
+        match self {
+            ValidationResult::Valid(data) => (Some(data), Vec::new(), Vec::new()),
+            ValidationResult::Warnings(data, warnings) => (Some(data), warnings, Vec::new()),
+            ValidationResult::Invalid(warnings, errors) => (None, warnings, errors),
+        }
+    }
+}
+
+/// Combines the results of many validations, reporting the warnings and
+/// errors of all of them.
+impl<W, E: Debug> FromIterator<ValidationResult<(), W, E>> for ValidationResult<(), W, E> {
+    fn from_iter<I: IntoIterator<Item = ValidationResult<(), W, E>>>(results: I) -> Self {
+        results
+            .into_iter()
+            .fold(ValidationResult::Valid(()), ValidationResult::merge)
+    }
+}
+
+/// The warnings and errors of a failed validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationErrors<W = String, E = String> {
+    pub warnings: Vec<W>,
+    pub errors: Vec<E>,
+}
+
+impl<W, E: fmt::Display> fmt::Display for ValidationErrors<W, E> {
+    /// Writes the errors, one per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Debug, E: Debug + fmt::Display> std::error::Error for ValidationErrors<W, E> {}
+
+impl<T, W, E: Debug> From<ValidationResult<T, W, E>> for Result<T, ValidationErrors<W, E>> {
+    /// Converts a result to a `Result`, so it can be propagated with `?`.
+    /// The warnings of a passed validation are discarded.
+    fn from(result: ValidationResult<T, W, E>) -> Self {
+        match result {
+            ValidationResult::Valid(data) | ValidationResult::Warnings(data, _) => Ok(data),
+            ValidationResult::Invalid(warnings, errors) => {
+                Err(ValidationErrors { warnings, errors })
+            }
+        }
+    }
 }
 
 pub trait Validate<T = (), W = String, E: Debug = String> {
     fn validate(&self) -> ValidationResult<T, W, E>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(error: &str) -> ValidationResult {
+        ValidationResult::Invalid(Vec::new(), vec![error.to_string()])
+    }
+
+    fn warning(warning: &str) -> ValidationResult {
+        ValidationResult::Warnings((), vec![warning.to_string()])
+    }
+
+    #[test]
+    fn test_combinators() {
+        let result =
+            warning("a").and_then(|_| ValidationResult::Warnings(2, vec!["b".to_string()]));
+        assert!(result.is_ok_with_warnings());
+        assert_eq!(result.ok(), Ok(2));
+
+        let mut called = false;
+        let result = invalid("x").and_then(|_| {
+            called = true;
+            ValidationResult::Valid(())
+        });
+        assert!(result.is_invalid() && !called);
+
+        let result: ValidationResult = [
+            warning("a"),
+            invalid("x"),
+            ValidationResult::Valid(()),
+            invalid("y"),
+        ]
+        .into_iter()
+        .collect();
+        let (data, warnings, errors) = result
+            .map_errors(|error| format!("dim: {error}"))
+            .into_parts();
+        assert_eq!(data, None);
+        assert_eq!(warnings, ["a"]);
+        assert_eq!(errors, ["dim: x", "dim: y"]);
+
+        let result: ValidationResult = [ValidationResult::Valid(()), warning("a")]
+            .into_iter()
+            .collect();
+        assert!(result.has_warnings());
+    }
+
+    #[test]
+    fn test_into_result() {
+        fn check(result: ValidationResult) -> Result<(), ValidationErrors> {
+            Result::from(result)
+        }
+
+        assert_eq!(check(warning("a")), Ok(()));
+        let errors = check(warning("a").merge(invalid("x")).merge(invalid("y"))).unwrap_err();
+        assert_eq!(errors.warnings, ["a"]);
+        assert_eq!(errors.to_string(), "x\ny");
+    }
+}
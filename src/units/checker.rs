@@ -0,0 +1,489 @@
+//! Dimensional analysis of every equation in a model.
+//!
+//! [`check_units`] works out the units of each equation from the declared
+//! units of the variables it uses and compares them with the declared units
+//! of the variable it defines. Units are compared after
+//! [substitution](super::substitution), so model unit aliases and equations
+//! apply, and disabled units are left out.
+//!
+//! Within an equation:
+//!
+//! - terms that are added, subtracted, compared or chosen between by
+//!   `IF ... THEN ... ELSE`, and the arguments of `MIN` and `MAX`, must
+//!   have the same units
+//! - products, quotients and whole powers combine units, and `SQRT` halves
+//!   their exponents
+//! - `EXP`, `LN`, `LOG10` and the trigonometric functions take and give
+//!   dimensionless values, as do exponents
+//! - `TIME`, `DT`, `STARTTIME` and `STOPTIME` are in the model's time units
+//! - numbers take whatever units they are added to or compared with
+//!
+//! Variables without declared units, and functions not listed here, have
+//! unknown units, which match anything. Each mismatch is reported with the
+//! sub-expression whose units do not agree.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::equation::expression::function::FunctionTarget;
+use crate::model::vars::stock::Stock;
+use crate::model::vars::{Var, Variable};
+use crate::types::ValidationResult;
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+use crate::{Expression, Identifier};
+
+use super::ModelUnits;
+use super::consistency::variable_units;
+use super::substitution::{ReducedUnits, UnitError, UnitTable};
+
+/// What is wrong with the units of a sub-expression.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MismatchKind {
+    /// The equation's units are not the variable's declared units.
+    Declared {
+        declared: ReducedUnits,
+        found: ReducedUnits,
+    },
+    /// Operands that must have the same units do not.
+    Operands {
+        left: ReducedUnits,
+        right: ReducedUnits,
+    },
+    /// A value that must be dimensionless has units.
+    Dimensionless(ReducedUnits),
+    /// Units raised to a power that is not a whole number, or whose square
+    /// root has fractional exponents.
+    Power(ReducedUnits),
+}
+
+/// A sub-expression of a variable's equation whose units do not agree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitMismatch {
+    pub variable: Identifier,
+    pub expression: Expression,
+    pub kind: MismatchKind,
+}
+
+impl fmt::Display for UnitMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "In the equation of '{}', ", self.variable)?;
+        match &self.kind {
+            MismatchKind::Declared { declared, found } => write!(
+                f,
+                "'{}' has units '{}', but the variable is declared in '{}'.",
+                self.expression, found, declared
+            ),
+            MismatchKind::Operands { left, right } => write!(
+                f,
+                "'{}' combines units '{}' and '{}', which must be the same.",
+                self.expression, left, right
+            ),
+            MismatchKind::Dimensionless(units) => write!(
+                f,
+                "'{}' has units '{}', but must be dimensionless.",
+                self.expression, units
+            ),
+            MismatchKind::Power(units) => write!(
+                f,
+                "'{}' takes a fractional power of units '{}'.",
+                self.expression, units
+            ),
+        }
+    }
+}
+
+/// The outcome of checking the units of a model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnitReport {
+    /// The units of each variable, declared or, failing that, worked out
+    /// from its equation, in the order of the model. Variables whose units
+    /// are unknown are left out.
+    pub units: Vec<(Identifier, ReducedUnits)>,
+    pub mismatches: Vec<UnitMismatch>,
+    /// Declared units that could not be reduced to primary units.
+    pub errors: Vec<UnitError>,
+}
+
+impl UnitReport {
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty() && self.errors.is_empty()
+    }
+
+    /// The units of the named variable.
+    pub fn units_of(&self, name: &Identifier) -> Option<&ReducedUnits> {
+        self.units
+            .iter()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, units)| units)
+    }
+
+    /// The mismatches and errors as a validation result.
+    pub fn to_validation_result(&self) -> ValidationResult {
+        let errors = self
+            .errors
+            .iter()
+            .map(ToString::to_string)
+            .chain(self.mismatches.iter().map(ToString::to_string))
+            .collect();
+        ValidationResult::from_parts((), Vec::new(), errors)
+    }
+}
+
+/// Checks the units of every equation of `model`, run in `time_units`,
+/// with the baseline units overridden by `model_units`.
+pub fn check_units(
+    model: &Model,
+    time_units: Option<&str>,
+    model_units: Option<&ModelUnits>,
+) -> UnitReport {
+    let mut report = UnitReport::default();
+    let table = UnitTable::new(model_units);
+    let time = time_units
+        .filter(|units| !units.trim().is_empty())
+        .and_then(|units| match table.reduce_str(units) {
+            Ok(time) => Some(time),
+            Err(error) => {
+                report.errors.push(error);
+                None
+            }
+        });
+
+    let variables = &model.variables.variables;
+    let mut declared = HashMap::new();
+    for variable in variables {
+        let (Some(name), Some(units)) = (get_variable_name(variable), variable_units(variable))
+        else {
+            continue;
+        };
+        match table.reduce(units) {
+            Ok(units) => {
+                declared.insert(name.clone(), units);
+            }
+            Err(error) => report.errors.push(error),
+        }
+    }
+
+    let checker = Checker {
+        declared: &declared,
+        time: time.as_ref(),
+    };
+    for variable in variables {
+        let Some(name) = get_variable_name(variable) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        let inferred = equation_of(variable).map(|equation| {
+            let inferred = checker.infer(equation, &mut found);
+            if let (Some(expected), Inferred::Known(units)) = (declared.get(name), &inferred)
+                && expected != units
+            {
+                found.push((
+                    equation.clone(),
+                    MismatchKind::Declared {
+                        declared: expected.clone(),
+                        found: units.clone(),
+                    },
+                ));
+            }
+            inferred
+        });
+        report
+            .mismatches
+            .extend(found.into_iter().map(|(expression, kind)| UnitMismatch {
+                variable: name.clone(),
+                expression,
+                kind,
+            }));
+        match (declared.get(name), inferred) {
+            (Some(units), _) => report.units.push((name.clone(), units.clone())),
+            (None, Some(Inferred::Known(units))) => report.units.push((name.clone(), units)),
+            _ => {}
+        }
+    }
+    report
+}
+
+/// The equation of a variable; for stocks, their initial value.
+fn equation_of(variable: &Variable) -> Option<&Expression> {
+    match variable {
+        Variable::Auxiliary(aux) => aux.equation(),
+        Variable::Stock(stock) => match stock.as_ref() {
+            Stock::Basic(stock) => stock.equation(),
+            Stock::Conveyor(stock) => stock.equation(),
+            Stock::Queue(stock) => stock.equation(),
+        },
+        Variable::Flow(flow) => flow.equation(),
+        Variable::GraphicalFunction(gf) => gf.equation(),
+        _ => None,
+    }
+}
+
+/// The units of an expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Inferred {
+    Known(ReducedUnits),
+    /// A number, which takes the units of what it is combined with.
+    Number,
+    Unknown,
+}
+
+struct Checker<'a> {
+    declared: &'a HashMap<Identifier, ReducedUnits>,
+    time: Option<&'a ReducedUnits>,
+}
+
+type Found = Vec<(Expression, MismatchKind)>;
+
+impl Checker<'_> {
+    fn infer(&self, expression: &Expression, found: &mut Found) -> Inferred {
+        use Expression as E;
+
+        match expression {
+            E::Constant(_) => Inferred::Number,
+            E::Subscript(name, _) => self.reference(name),
+            E::Parentheses(inner)
+            | E::UnaryPlus(inner)
+            | E::UnaryMinus(inner)
+            | E::Transpose(inner) => self.infer(inner, found),
+            E::Not(inner) => {
+                self.infer(inner, found);
+                Inferred::Number
+            }
+            E::Multiply(lhs, rhs) => product(self.infer(lhs, found), self.infer(rhs, found), false),
+            E::Divide(lhs, rhs) => product(self.infer(lhs, found), self.infer(rhs, found), true),
+            E::Add(lhs, rhs) | E::Subtract(lhs, rhs) | E::Modulo(lhs, rhs) => {
+                self.same(expression, &[&**lhs, &**rhs], found)
+            }
+            E::LessThan(lhs, rhs)
+            | E::LessThanOrEq(lhs, rhs)
+            | E::GreaterThan(lhs, rhs)
+            | E::GreaterThanOrEq(lhs, rhs)
+            | E::Equal(lhs, rhs)
+            | E::NotEqual(lhs, rhs) => {
+                self.same(expression, &[&**lhs, &**rhs], found);
+                Inferred::Number
+            }
+            E::And(lhs, rhs) | E::Or(lhs, rhs) => {
+                self.infer(lhs, found);
+                self.infer(rhs, found);
+                Inferred::Number
+            }
+            E::Exponentiation(base, exponent) => {
+                let base_units = self.infer(base, found);
+                self.dimensionless(exponent, found);
+                match base_units {
+                    Inferred::Known(units) if !units.is_dimensionless() => {
+                        match exponent.evaluate::<f64>(&|_| None) {
+                            Some(power) if power.fract() == 0.0 => {
+                                Inferred::Known(units.powi(power as i32))
+                            }
+                            _ => {
+                                found.push((expression.clone(), MismatchKind::Power(units)));
+                                Inferred::Unknown
+                            }
+                        }
+                    }
+                    other => other,
+                }
+            }
+            E::IfElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.infer(condition, found);
+                self.same(expression, &[&**then_branch, &**else_branch], found)
+            }
+            E::FunctionCall { target, parameters } => {
+                self.call(expression, target, parameters, found)
+            }
+            E::Wildcard | E::Range(..) | E::InlineComment(_) => Inferred::Unknown,
+        }
+    }
+
+    /// The units of a variable or a builtin used by name.
+    fn reference(&self, name: &Identifier) -> Inferred {
+        match name.normalized().to_ascii_uppercase().as_str() {
+            "TIME" | "DT" | "STARTTIME" | "STOPTIME" => self
+                .time
+                .map_or(Inferred::Unknown, |time| Inferred::Known(time.clone())),
+            "PI" => Inferred::Number,
+            _ => self
+                .declared
+                .get(name)
+                .map_or(Inferred::Unknown, |units| Inferred::Known(units.clone())),
+        }
+    }
+
+    /// The units shared by `operands`, reporting `whole` if they differ.
+    fn same(&self, whole: &Expression, operands: &[&Expression], found: &mut Found) -> Inferred {
+        let mut shared = Inferred::Number;
+        for operand in operands {
+            shared = match (shared, self.infer(operand, found)) {
+                (Inferred::Known(left), Inferred::Known(right)) if left != right => {
+                    found.push((whole.clone(), MismatchKind::Operands { left, right }));
+                    return Inferred::Unknown;
+                }
+                (Inferred::Known(units), _) | (_, Inferred::Known(units)) => Inferred::Known(units),
+                (Inferred::Unknown, _) | (_, Inferred::Unknown) => Inferred::Unknown,
+                (Inferred::Number, Inferred::Number) => Inferred::Number,
+            };
+        }
+        shared
+    }
+
+    /// Checks that `expression` is dimensionless.
+    fn dimensionless(&self, expression: &Expression, found: &mut Found) {
+        if let Inferred::Known(units) = self.infer(expression, found)
+            && !units.is_dimensionless()
+        {
+            found.push((expression.clone(), MismatchKind::Dimensionless(units)));
+        }
+    }
+
+    fn call(
+        &self,
+        whole: &Expression,
+        target: &FunctionTarget,
+        parameters: &[Expression],
+        found: &mut Found,
+    ) -> Inferred {
+        let name = match target {
+            FunctionTarget::Function(name) | FunctionTarget::GraphicalFunction(name) => name,
+            FunctionTarget::Array(name) => return self.reference(name),
+            FunctionTarget::Model(_) => return Inferred::Unknown,
+        };
+        // A graphical function, or another variable called like one
+        if let Some(units) = self.declared.get(name) {
+            for parameter in parameters {
+                self.infer(parameter, found);
+            }
+            return Inferred::Known(units.clone());
+        }
+        let operands: Vec<&Expression> = parameters.iter().collect();
+        match name.normalized().to_ascii_uppercase().as_str() {
+            "MIN" | "MAX" => self.same(whole, &operands, found),
+            "ABS" | "INT" | "ROUND" | "DELAY" | "DELAY1" | "DELAY3" | "DELAYN" | "SMTH1"
+            | "SMTH3" | "SMTHN" | "INIT" | "PREVIOUS" => {
+                let inferred = parameters
+                    .iter()
+                    .map(|parameter| self.infer(parameter, found))
+                    .collect::<Vec<_>>();
+                inferred.into_iter().next().unwrap_or(Inferred::Unknown)
+            }
+            "SAFEDIV" => match parameters {
+                [numerator, denominator, ..] => product(
+                    self.infer(numerator, found),
+                    self.infer(denominator, found),
+                    true,
+                ),
+                _ => Inferred::Unknown,
+            },
+            "SQRT" => match parameters {
+                [argument] => match self.infer(argument, found) {
+                    Inferred::Known(units) => match units.sqrt() {
+                        Some(root) => Inferred::Known(root),
+                        None => {
+                            found.push((whole.clone(), MismatchKind::Power(units)));
+                            Inferred::Unknown
+                        }
+                    },
+                    other => other,
+                },
+                _ => Inferred::Unknown,
+            },
+            "EXP" | "LN" | "LOG10" | "SIN" | "COS" | "TAN" | "ARCSIN" | "ARCCOS" | "ARCTAN" => {
+                for parameter in parameters {
+                    self.dimensionless(parameter, found);
+                }
+                Inferred::Known(ReducedUnits::default())
+            }
+            "TIME" | "DT" | "STARTTIME" | "STOPTIME" | "PI" if parameters.is_empty() => {
+                self.reference(name)
+            }
+            _ => {
+                for parameter in parameters {
+                    self.infer(parameter, found);
+                }
+                Inferred::Unknown
+            }
+        }
+    }
+}
+
+/// The units of a product, or of a quotient if `divide` is set.
+fn product(left: Inferred, right: Inferred, divide: bool) -> Inferred {
+    match (left, right) {
+        (Inferred::Unknown, _) | (_, Inferred::Unknown) => Inferred::Unknown,
+        (Inferred::Number, Inferred::Number) => Inferred::Number,
+        (Inferred::Known(units), Inferred::Number) => Inferred::Known(units),
+        (Inferred::Number, Inferred::Known(units)) if divide => {
+            Inferred::Known(ReducedUnits::default().divide(&units))
+        }
+        (Inferred::Number, Inferred::Known(units)) => Inferred::Known(units),
+        (Inferred::Known(left), Inferred::Known(right)) if divide => {
+            Inferred::Known(left.divide(&right))
+        }
+        (Inferred::Known(left), Inferred::Known(right)) => Inferred::Known(left.multiply(&right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de::from_str;
+
+    fn model(variables: &str) -> Model {
+        from_str(&format!(
+            "<model><variables>{variables}</variables></model>"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_consistent_model() {
+        let model = model(
+            r#"<stock name="Population"><eqn>100</eqn><inflow>Births</inflow><units>people</units></stock>
+            <flow name="Births"><eqn>Population * birth_rate + 2</eqn><units>persons/year</units></flow>
+            <aux name="birth rate"><eqn>0.5 * EXP(-TIME / horizon) / horizon</eqn><units>per_year</units></aux>
+            <aux name="horizon"><eqn>10</eqn><units>years</units></aux>
+            <aux name="area"><eqn>SQRT(land) * 2</eqn></aux>
+            <aux name="land"><eqn>4</eqn><units>plots * plots</units></aux>"#,
+        );
+        let units: ModelUnits = from_str(
+            r#"<model_units><unit name="people"><alias>persons</alias></unit></model_units>"#,
+        )
+        .unwrap();
+        let report = check_units(&model, Some("years"), Some(&units));
+        assert!(report.is_consistent(), "{:?}", report.mismatches);
+        let area = Identifier::parse_default("area").unwrap();
+        assert_eq!(report.units_of(&area).unwrap().to_string(), "plots");
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let model = model(
+            r#"<stock name="Population"><eqn>100</eqn><units>people</units></stock>
+            <flow name="Births"><eqn>Population * 0.1</eqn><units>people/year</units></flow>
+            <aux name="total"><eqn>IF TIME > 5 THEN Population + Births ELSE 0</eqn></aux>
+            <aux name="growth"><eqn>LN(Population)</eqn></aux>"#,
+        );
+        let report = check_units(&model, Some("years"), None);
+        let found: Vec<_> = report
+            .mismatches
+            .iter()
+            .map(|mismatch| (mismatch.variable.to_string(), &mismatch.kind))
+            .collect();
+        assert_eq!(found.len(), 3, "{found:?}");
+        assert!(matches!(found[0], (ref name, MismatchKind::Declared { .. }) if name == "Births"));
+        assert!(matches!(found[1], (_, MismatchKind::Operands { .. })));
+        assert_eq!(
+            report.mismatches[1].expression.to_string(),
+            "Population + Births"
+        );
+        assert!(matches!(found[2], (_, MismatchKind::Dimensionless(_))));
+        assert!(report.to_validation_result().is_invalid());
+    }
+}
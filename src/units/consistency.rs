@@ -0,0 +1,198 @@
+//! Checks that the units of stocks, flows and conveyor transit times agree
+//! with the model's unit of time.
+//!
+//! A flow changes its stock by its value every unit of time, so a flow's
+//! units must be its stock's units per time unit: a stock of `people` in a
+//! model run in `years` has flows in `people/year`. The transit time of a
+//! conveyor must be in time units.
+//!
+//! Units are compared after [substitution](super::substitution), using the
+//! baseline units and the file's model units, so `people/year`,
+//! `people * per_year` and `persons/years` all match when `persons` is an
+//! alias of `people`. Variables without declared units are
+//! not checked, nor are conveyors whose transit time is not the name of a
+//! single variable with units.
+//!
+//! [`XmileFile::validate`](crate::xml::XmileFile::validate) runs these
+//! checks on every model, with the model's or the file's time units.
+
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+use crate::types::ValidationResult;
+use crate::xml::schema::Model;
+use crate::xml::validation::get_variable_name;
+use crate::{Expression, Identifier, Measure, UnitEquation};
+
+use super::ModelUnits;
+use super::substitution::{UnitTable, parse_units};
+
+/// Validate that every flow's units are its stock's units per time unit, and
+/// that conveyor transit times are in time units
+///
+/// Nothing is checked when the model has no time units.
+pub fn validate_stock_flow_units(
+    model: &Model,
+    time_units: Option<&str>,
+    model_units: Option<&ModelUnits>,
+) -> ValidationResult {
+    let warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    let Some(time_units) = time_units.filter(|units| !units.trim().is_empty()) else {
+        return ValidationResult::Valid(());
+    };
+    let units = UnitTable::new(model_units);
+    let time = match parse_units(time_units).map(|time| units.reduce(&time)) {
+        Some(Ok(time)) => time,
+        Some(Err(error)) => return ValidationResult::Invalid(warnings, vec![error.to_string()]),
+        None => {
+            return ValidationResult::Invalid(
+                warnings,
+                vec![format!(
+                    "Time units '{}' are not a valid unit equation.",
+                    time_units
+                )],
+            );
+        }
+    };
+
+    let variables = &model.variables.variables;
+    let declared_units = |name: &Identifier| {
+        variables
+            .iter()
+            .find(|variable| get_variable_name(variable) == Some(name))
+            .and_then(variable_units)
+    };
+
+    for variable in variables {
+        let Variable::Stock(stock) = variable else {
+            continue;
+        };
+        let Some(stock_units) = stock_units(stock) else {
+            continue;
+        };
+        let expected = match units.reduce(stock_units) {
+            Ok(reduced) => reduced.divide(&time),
+            Err(error) => {
+                errors.push(error.to_string());
+                continue;
+            }
+        };
+
+        for flow in stock.inflows().iter().chain(stock.outflows()) {
+            let Some(flow_units) = declared_units(flow) else {
+                continue;
+            };
+            match units.reduce(flow_units) {
+                Ok(reduced) if reduced == expected => {}
+                Ok(_) => errors.push(format!(
+                    "Flow '{}' has units '{}', but flows of stock '{}' must have its units '{}' per time unit '{}'.",
+                    flow,
+                    flow_units,
+                    stock.name(),
+                    stock_units,
+                    time_units
+                )),
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+
+        if let Stock::Conveyor(conveyor) = stock.as_ref()
+            && let Expression::Subscript(name, indices) = &conveyor.length
+            && indices.is_empty()
+            && let Some(length_units) = declared_units(name)
+        {
+            match units.reduce(length_units) {
+                Ok(reduced) if reduced == time => {}
+                Ok(_) => errors.push(format!(
+                    "Transit time '{}' of conveyor '{}' has units '{}', but transit times must be in the model's time units '{}'.",
+                    name,
+                    conveyor.name,
+                    length_units,
+                    time_units
+                )),
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        ValidationResult::Valid(())
+    } else {
+        ValidationResult::Invalid(warnings, errors)
+    }
+}
+
+fn stock_units(stock: &Stock) -> Option<&UnitEquation> {
+    match stock {
+        Stock::Basic(stock) => stock.units(),
+        Stock::Conveyor(stock) => stock.units(),
+        Stock::Queue(stock) => stock.units(),
+    }
+}
+
+pub(super) fn variable_units(variable: &Variable) -> Option<&UnitEquation> {
+    match variable {
+        Variable::Auxiliary(aux) => aux.units(),
+        Variable::Stock(stock) => stock_units(stock),
+        Variable::Flow(flow) => flow.units(),
+        Variable::GraphicalFunction(gf) => gf.units(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de::from_str;
+
+    fn model(variables: &str) -> Model {
+        from_str(&format!(
+            "<model><variables>{variables}</variables></model>"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flow_units_per_time() {
+        let model = model(
+            r#"<stock name="Population"><eqn>100</eqn><inflow>Births</inflow><outflow>Deaths</outflow><outflow>Emigration</outflow><units>people</units></stock>
+            <flow name="Births"><eqn>5</eqn><units>persons/yr</units></flow>
+            <flow name="Deaths"><eqn>2</eqn><units>people * per_year</units></flow>
+            <flow name="Emigration"><eqn>1</eqn><units>people/month</units></flow>"#,
+        );
+        let units: ModelUnits = from_str(
+            r#"<model_units><unit name="people"><alias>persons</alias></unit></model_units>"#,
+        )
+        .unwrap();
+
+        match validate_stock_flow_units(&model, Some("years"), Some(&units)) {
+            ValidationResult::Invalid(_, errors) => {
+                assert_eq!(errors.len(), 1, "{errors:?}");
+                assert!(errors[0].starts_with("Flow 'Emigration' has units"));
+            }
+            _ => panic!("expected Emigration to be reported"),
+        }
+        assert!(validate_stock_flow_units(&model, None, Some(&units)).is_valid());
+    }
+
+    #[test]
+    fn test_conveyor_transit_time_and_cycles() {
+        let model = model(
+            r#"<stock name="Pipeline"><eqn>0</eqn><conveyor><len>Delay</len></conveyor><units>widgets</units></stock>
+            <aux name="Delay"><eqn>3</eqn><units>widgets</units></aux>"#,
+        );
+        match validate_stock_flow_units(&model, Some("days"), None) {
+            ValidationResult::Invalid(_, errors) => {
+                assert!(errors[0].starts_with("Transit time 'Delay' of conveyor 'Pipeline'"));
+            }
+            _ => panic!("expected the transit time to be reported"),
+        }
+
+        let units: ModelUnits = from_str(
+            r#"<model_units><unit name="widgets"><eqn>gadgets</eqn></unit><unit name="gadgets"><eqn>widgets</eqn></unit></model_units>"#,
+        )
+        .unwrap();
+        assert!(validate_stock_flow_units(&model, Some("days"), Some(&units)).is_invalid());
+    }
+}
@@ -0,0 +1,169 @@
+//! A standard library of unit definitions.
+//!
+//! The specification asks vendors to make the unit definitions they provide
+//! available in the `<model_units>` format, and files only define the units
+//! they add or override. This library defines common units beyond the
+//! baseline time units: people, money, dimensionless ratios and the SI base
+//! and derived units, with their usual aliases, so models that define few
+//! units still have `person`, `persons` and `people` checked as one unit.
+//!
+//! [`UnitTable::standard`] adds the library to the baseline units, and a
+//! file's model units extend or override it, or disable its units. With the
+//! `unit-library` feature, [`UnitTable::new`] and so
+//! [`XmileFile::validate`](crate::xml::XmileFile::validate) start from the
+//! standard table instead of the baseline units.
+//!
+//! Unit equations have no scale factors, so the library only relates units
+//! of the same size: `kilometers` is a primary unit, not `1000 * meters`.
+
+use std::sync::OnceLock;
+
+use super::ModelUnits;
+#[cfg(doc)]
+use super::substitution::UnitTable;
+
+/// The standard unit library, in the `<model_units>` format.
+pub const STANDARD_UNITS_XML: &str = r#"<model_units>
+    <unit name="people">
+        <alias>person</alias>
+        <alias>persons</alias>
+        <alias>peoples</alias>
+    </unit>
+    <unit name="dollars">
+        <alias>dollar</alias>
+        <alias>USD</alias>
+    </unit>
+    <unit name="euros">
+        <alias>euro</alias>
+        <alias>EUR</alias>
+    </unit>
+    <unit name="pounds_sterling">
+        <alias>GBP</alias>
+    </unit>
+    <unit name="percent">
+        <eqn>1</eqn>
+        <alias>pct</alias>
+    </unit>
+    <unit name="fraction">
+        <eqn>1</eqn>
+        <alias>fractions</alias>
+    </unit>
+    <unit name="meters">
+        <alias>m</alias>
+        <alias>meter</alias>
+        <alias>metres</alias>
+        <alias>metre</alias>
+    </unit>
+    <unit name="kilograms">
+        <alias>kg</alias>
+        <alias>kilogram</alias>
+    </unit>
+    <unit name="amperes">
+        <alias>A</alias>
+        <alias>ampere</alias>
+        <alias>amps</alias>
+    </unit>
+    <unit name="kelvin">
+        <alias>K</alias>
+    </unit>
+    <unit name="moles">
+        <alias>mol</alias>
+        <alias>mole</alias>
+    </unit>
+    <unit name="candela">
+        <alias>cd</alias>
+    </unit>
+    <unit name="square_meters">
+        <eqn>meters * meters</eqn>
+        <alias>m2</alias>
+    </unit>
+    <unit name="cubic_meters">
+        <eqn>meters * meters * meters</eqn>
+        <alias>m3</alias>
+    </unit>
+    <unit name="meters_per_second">
+        <eqn>meters/seconds</eqn>
+    </unit>
+    <unit name="newtons">
+        <eqn>kilograms * meters/(seconds * seconds)</eqn>
+        <alias>N</alias>
+        <alias>newton</alias>
+    </unit>
+    <unit name="joules">
+        <eqn>newtons * meters</eqn>
+        <alias>J</alias>
+        <alias>joule</alias>
+    </unit>
+    <unit name="watts">
+        <eqn>joules/seconds</eqn>
+        <alias>W</alias>
+        <alias>watt</alias>
+    </unit>
+    <unit name="pascals">
+        <eqn>newtons/(meters * meters)</eqn>
+        <alias>Pa</alias>
+        <alias>pascal</alias>
+    </unit>
+    <unit name="coulombs">
+        <eqn>amperes * seconds</eqn>
+        <alias>C</alias>
+        <alias>coulomb</alias>
+    </unit>
+    <unit name="volts">
+        <eqn>watts/amperes</eqn>
+        <alias>V</alias>
+        <alias>volt</alias>
+    </unit>
+    <unit name="hertz">
+        <eqn>1/seconds</eqn>
+        <alias>Hz</alias>
+    </unit>
+</model_units>"#;
+
+/// The standard unit library.
+pub fn standard_units() -> &'static ModelUnits {
+    static UNITS: OnceLock<ModelUnits> = OnceLock::new();
+    UNITS.get_or_init(|| {
+        quick_xml::de::from_str(STANDARD_UNITS_XML).expect("The standard unit library is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Validate;
+    use crate::units::substitution::UnitTable;
+
+    #[test]
+    fn test_library_is_valid() {
+        let table = UnitTable::standard();
+        assert!(table.validate().is_valid());
+        assert_eq!(
+            table.reduce_str("J/s").unwrap(),
+            table.reduce_str("watts").unwrap()
+        );
+        assert_eq!(
+            table.reduce_str("persons * pct").unwrap().to_string(),
+            "people"
+        );
+    }
+
+    #[test]
+    fn test_model_units_override_library() {
+        let units: ModelUnits = quick_xml::de::from_str(
+            r#"<model_units>
+                <unit name="joules" disabled="true"/>
+                <unit name="dollars"><eqn>euros</eqn></unit>
+            </model_units>"#,
+        )
+        .unwrap();
+        let mut table = UnitTable::standard();
+        table.extend(&units);
+        assert_eq!(table.reduce_str("USD").unwrap().to_string(), "euros");
+        assert_eq!(table.reduce_str("J").unwrap().to_string(), "J");
+        assert_eq!(
+            table.reduce_str("newtons * meters").unwrap().to_string(),
+            "kilograms * meters^2/seconds^2"
+        );
+    }
+}
@@ -20,10 +20,15 @@
 
 use serde::{Deserialize, Serialize};
 
+pub mod checker;
+pub mod consistency;
+pub mod library;
+pub mod substitution;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelUnits {
     /// A list of unit definitions in the XMILE file.
-    #[serde(rename = "unit")]
+    #[serde(rename = "unit", default)]
     pub units: Vec<UnitDefinition>,
 }
 
@@ -33,11 +38,12 @@ pub struct UnitDefinition {
     #[serde(rename = "@name")]
     pub name: String,
     /// An optional equation defining the unit.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub eqn: Option<String>,
     /// A list of aliases for the unit.
     #[serde(rename = "alias", default)]
     pub aliases: Vec<String>,
     /// Indicates whether the unit is disabled.
-    #[serde(rename = "@disabled")]
+    #[serde(rename = "@disabled", skip_serializing_if = "Option::is_none")]
     pub disabled: Option<bool>,
 }
@@ -0,0 +1,512 @@
+//! The unit substitution process.
+//!
+//! Units in a unit equation may be defined by equations of other units, and
+//! may be known by several aliases. Substitution replaces each alias by the
+//! name of its unit and each defined unit by its equation until only primary
+//! units, those with no equation, remain. Units are then compared by the
+//! exponent of each primary unit, so `people/year`, `people * per_year` and
+//! `persons/years` are the same units when `persons` is an alias of
+//! `people`, and `miles^2/miles` and `miles` are the same units. The
+//! reduced units are written back as the simplest equivalent equation.
+//!
+//! A [`UnitTable`] holds the definitions used: the baseline units of the
+//! specification, overridden by the file's model units. Units marked
+//! `disabled` are left out of substitution, with their aliases, so a model
+//! can remove a built-in definition:
+//!
+//! ```rust
+//! use xmile::units::ModelUnits;
+//! use xmile::units::substitution::UnitTable;
+//!
+//! let units: ModelUnits = quick_xml::de::from_str(
+//!     r#"<model_units>
+//!         <unit name="people"><alias>persons</alias></unit>
+//!         <unit name="people_per_year"><eqn>people/year</eqn></unit>
+//!     </model_units>"#,
+//! ).unwrap();
+//! let table = UnitTable::new(Some(&units));
+//! let a = table.reduce_str("persons * per_year").unwrap();
+//! let b = table.reduce_str("people_per_year").unwrap();
+//! assert_eq!(a, b);
+//! assert_eq!(a.to_string(), "people/years");
+//! assert!(table.equivalent("people^2 / persons", "people").unwrap());
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use thiserror::Error;
+
+use super::ModelUnits;
+use super::library::standard_units;
+use crate::equation::parse::unit_equation;
+use crate::equation::units::baseline::baseline_units;
+use crate::types::{Validate, ValidationResult};
+use crate::{Identifier, UnitEquation, UnitOfMeasure};
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum UnitError {
+    #[error("The definition of unit '{0}' refers to itself.")]
+    Cycle(Identifier),
+    #[error("Units '{0}' are not a valid unit equation.")]
+    InvalidEquation(String),
+}
+
+/// Units as the exponent of each primary unit they are made of.
+///
+/// Dimensionless units have no primary units.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ReducedUnits {
+    exponents: BTreeMap<Identifier, i32>,
+}
+
+impl ReducedUnits {
+    /// The exponent of each primary unit, omitting zero exponents.
+    pub fn exponents(&self) -> &BTreeMap<Identifier, i32> {
+        &self.exponents
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.exponents.is_empty()
+    }
+
+    pub fn multiply(&self, other: &ReducedUnits) -> ReducedUnits {
+        self.combine(other, 1)
+    }
+
+    pub fn divide(&self, other: &ReducedUnits) -> ReducedUnits {
+        self.combine(other, -1)
+    }
+
+    /// The units raised to the power `exponent`.
+    pub fn powi(&self, exponent: i32) -> ReducedUnits {
+        let mut exponents = self.exponents.clone();
+        for power in exponents.values_mut() {
+            *power *= exponent;
+        }
+        exponents.retain(|_, power| *power != 0);
+        ReducedUnits { exponents }
+    }
+
+    /// The square root of the units, if every exponent is even.
+    pub fn sqrt(&self) -> Option<ReducedUnits> {
+        let mut exponents = self.exponents.clone();
+        for power in exponents.values_mut() {
+            if *power % 2 != 0 {
+                return None;
+            }
+            *power /= 2;
+        }
+        Some(ReducedUnits { exponents })
+    }
+
+    /// The simplest unit equation of the units: the primary units with
+    /// positive exponents, in order, over those with negative exponents,
+    /// such as `kilograms * meters^2/seconds^2`, or `1` if dimensionless.
+    pub fn to_equation(&self) -> UnitEquation {
+        let product = |positive: bool| {
+            self.exponents
+                .iter()
+                .filter(|(_, exponent)| (**exponent > 0) == positive)
+                .map(|(unit, exponent)| match exponent.abs() {
+                    1 => UnitEquation::Alias(unit.clone()),
+                    power => UnitEquation::exponentiation(UnitEquation::Alias(unit.clone()), power),
+                })
+                .reduce(UnitEquation::multiplication)
+        };
+        let numerator = product(true).unwrap_or(UnitEquation::Integer(1));
+        match product(false) {
+            None => numerator,
+            Some(denominator @ UnitEquation::Multiplication(..)) => {
+                UnitEquation::division(numerator, UnitEquation::parentheses(denominator))
+            }
+            Some(denominator) => UnitEquation::division(numerator, denominator),
+        }
+    }
+
+    fn combine(&self, other: &ReducedUnits, sign: i32) -> ReducedUnits {
+        let mut exponents = self.exponents.clone();
+        for (unit, exponent) in &other.exponents {
+            *exponents.entry(unit.clone()).or_default() += sign * exponent;
+        }
+        exponents.retain(|_, exponent| *exponent != 0);
+        ReducedUnits { exponents }
+    }
+}
+
+impl fmt::Display for ReducedUnits {
+    /// Writes the units as their simplest unit equation, such as
+    /// `people/years`, `1/days` or `meters^2`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_equation())
+    }
+}
+
+/// A unit's name and defining equation, looked up by the name or an alias.
+#[derive(Debug, Clone, PartialEq)]
+struct Definition {
+    name: Identifier,
+    equation: Option<UnitEquation>,
+}
+
+/// The unit definitions used for substitution, by name and alias.
+///
+/// Names defined nowhere are treated as primary units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitTable {
+    definitions: HashMap<Identifier, Definition>,
+    /// Model unit equations that could not be parsed.
+    invalid: Vec<(String, String)>,
+}
+
+impl UnitTable {
+    /// The baseline units, overridden by `model_units`.
+    ///
+    /// With the `unit-library` feature, the [standard units](super::library)
+    /// are used instead of the baseline units.
+    pub fn new(model_units: Option<&ModelUnits>) -> Self {
+        #[cfg(feature = "unit-library")]
+        let mut table = UnitTable::standard();
+        #[cfg(not(feature = "unit-library"))]
+        let mut table = UnitTable::baseline();
+        if let Some(model_units) = model_units {
+            table.extend(model_units);
+        }
+        table
+    }
+
+    /// The units the specification recommends vendors provide.
+    pub fn baseline() -> Self {
+        let mut table = UnitTable::empty();
+        for unit in baseline_units() {
+            table.define(&unit);
+        }
+        table
+    }
+
+    /// The baseline units and the [standard unit library](super::library).
+    pub fn standard() -> Self {
+        let mut table = UnitTable::baseline();
+        table.extend(standard_units());
+        table
+    }
+
+    /// A table with no definitions, where every unit is primary.
+    pub fn empty() -> Self {
+        UnitTable {
+            definitions: HashMap::new(),
+            invalid: Vec::new(),
+        }
+    }
+
+    /// Defines `unit`, replacing any definition of its name or aliases.
+    ///
+    /// Aliases of an earlier definition of the unit are kept, and refer to
+    /// the new definition.
+    pub fn define(&mut self, unit: &UnitOfMeasure) {
+        for definition in self.definitions.values_mut() {
+            if definition.name == unit.name {
+                definition.equation = unit.equation.clone();
+            }
+        }
+        for name in std::iter::once(&unit.name).chain(&unit.aliases) {
+            self.definitions.insert(
+                name.clone(),
+                Definition {
+                    name: unit.name.clone(),
+                    equation: unit.equation.clone(),
+                },
+            );
+        }
+    }
+
+    /// Removes the unit `name` from substitution, with every alias of it.
+    pub fn disable(&mut self, name: &Identifier) {
+        let primary = self.primary_name(name).clone();
+        self.definitions
+            .retain(|alias, definition| alias != name && definition.name != primary);
+    }
+
+    /// Adds the definitions of `model_units`, which override those already
+    /// in the table. Disabled units are removed instead.
+    pub fn extend(&mut self, model_units: &ModelUnits) {
+        for unit in &model_units.units {
+            let Ok(name) = Identifier::parse_unit_name(&unit.name) else {
+                continue;
+            };
+            if unit.disabled == Some(true) {
+                self.disable(&name);
+                for alias in &unit.aliases {
+                    if let Ok(alias) = Identifier::parse_unit_name(alias) {
+                        self.disable(&alias);
+                    }
+                }
+                continue;
+            }
+            let equation = match unit.eqn.as_deref() {
+                Some(text) => match parse_units(text) {
+                    Some(equation) => Some(equation),
+                    None => {
+                        self.invalid.push((unit.name.clone(), text.to_string()));
+                        None
+                    }
+                },
+                None => None,
+            };
+            let aliases = unit
+                .aliases
+                .iter()
+                .filter_map(|alias| Identifier::parse_unit_name(alias).ok())
+                .collect();
+            self.define(&UnitOfMeasure {
+                name,
+                equation,
+                aliases,
+            });
+        }
+    }
+
+    /// The name of the unit `name` is an alias of, or `name` itself.
+    pub fn primary_name<'a>(&'a self, name: &'a Identifier) -> &'a Identifier {
+        self.definitions
+            .get(name)
+            .map_or(name, |definition| &definition.name)
+    }
+
+    /// Replaces aliases by unit names and defined units by their equations,
+    /// until only primary units remain.
+    pub fn substitute(&self, equation: &UnitEquation) -> Result<UnitEquation, UnitError> {
+        self.substitute_in(equation, &mut Vec::new())
+    }
+
+    fn substitute_in(
+        &self,
+        equation: &UnitEquation,
+        expanding: &mut Vec<Identifier>,
+    ) -> Result<UnitEquation, UnitError> {
+        let boxed = |equation: Result<UnitEquation, UnitError>| equation.map(Box::new);
+        Ok(match equation {
+            UnitEquation::Integer(value) => UnitEquation::Integer(*value),
+            UnitEquation::Alias(name) => match self.definitions.get(name) {
+                None => UnitEquation::Alias(name.clone()),
+                Some(Definition {
+                    name,
+                    equation: None,
+                }) => UnitEquation::Alias(name.clone()),
+                Some(Definition {
+                    name,
+                    equation: Some(definition),
+                }) => {
+                    if expanding.contains(name) {
+                        return Err(UnitError::Cycle(name.clone()));
+                    }
+                    expanding.push(name.clone());
+                    let substituted = self.substitute_in(definition, expanding)?;
+                    expanding.pop();
+                    match substituted {
+                        UnitEquation::Integer(_)
+                        | UnitEquation::Alias(_)
+                        | UnitEquation::Parentheses(_) => substituted,
+                        substituted => UnitEquation::Parentheses(Box::new(substituted)),
+                    }
+                }
+            },
+            UnitEquation::UnaryMinus(inner) => {
+                UnitEquation::UnaryMinus(boxed(self.substitute_in(inner, expanding))?)
+            }
+            UnitEquation::Parentheses(inner) => {
+                UnitEquation::Parentheses(boxed(self.substitute_in(inner, expanding))?)
+            }
+            UnitEquation::Multiplication(left, right) => UnitEquation::Multiplication(
+                boxed(self.substitute_in(left, expanding))?,
+                boxed(self.substitute_in(right, expanding))?,
+            ),
+            UnitEquation::Division(left, right) => UnitEquation::Division(
+                boxed(self.substitute_in(left, expanding))?,
+                boxed(self.substitute_in(right, expanding))?,
+            ),
+            UnitEquation::Exponentiation(base, exponent) => {
+                UnitEquation::Exponentiation(boxed(self.substitute_in(base, expanding))?, *exponent)
+            }
+        })
+    }
+
+    /// Reduces `equation` to the exponents of its primary units.
+    pub fn reduce(&self, equation: &UnitEquation) -> Result<ReducedUnits, UnitError> {
+        let mut exponents = BTreeMap::new();
+        accumulate(&self.substitute(equation)?, 1, &mut exponents);
+        exponents.retain(|_, exponent| *exponent != 0);
+        Ok(ReducedUnits { exponents })
+    }
+
+    /// Parses and reduces a unit equation.
+    pub fn reduce_str(&self, equation: &str) -> Result<ReducedUnits, UnitError> {
+        let parsed = parse_units(equation)
+            .ok_or_else(|| UnitError::InvalidEquation(equation.to_string()))?;
+        self.reduce(&parsed)
+    }
+
+    /// Rewrites `equation` as the simplest equation of the same primary
+    /// units, cancelling units and collecting exponents.
+    pub fn simplify(&self, equation: &UnitEquation) -> Result<UnitEquation, UnitError> {
+        Ok(self.reduce(equation)?.to_equation())
+    }
+
+    /// Whether two unit equations reduce to the same units.
+    pub fn equivalent(&self, left: &str, right: &str) -> Result<bool, UnitError> {
+        Ok(self.reduce_str(left)? == self.reduce_str(right)?)
+    }
+}
+
+impl Default for UnitTable {
+    fn default() -> Self {
+        UnitTable::baseline()
+    }
+}
+
+impl Validate for UnitTable {
+    /// Checks that every unit equation could be parsed and that no unit is
+    /// defined in terms of itself.
+    fn validate(&self) -> ValidationResult {
+        let mut errors: Vec<String> = self
+            .invalid
+            .iter()
+            .map(|(name, equation)| {
+                format!(
+                    "Unit '{name}' has equation '{equation}', which is not a valid unit equation."
+                )
+            })
+            .collect();
+
+        let mut names: Vec<&Identifier> = self
+            .definitions
+            .values()
+            .map(|definition| &definition.name)
+            .collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            if let Err(UnitError::Cycle(cycle)) =
+                self.substitute(&UnitEquation::Alias(name.clone()))
+                && &cycle == name
+            {
+                errors.push(UnitError::Cycle(cycle).to_string());
+            }
+        }
+
+        ValidationResult::from_parts((), Vec::new(), errors)
+    }
+}
+
+pub(crate) fn parse_units(text: &str) -> Option<UnitEquation> {
+    match unit_equation(text) {
+        Ok((rest, equation)) if rest.trim().is_empty() => Some(equation),
+        _ => None,
+    }
+}
+
+fn accumulate(equation: &UnitEquation, sign: i32, exponents: &mut BTreeMap<Identifier, i32>) {
+    match equation {
+        UnitEquation::Integer(_) => {}
+        UnitEquation::Alias(name) => *exponents.entry(name.clone()).or_default() += sign,
+        UnitEquation::UnaryMinus(inner) | UnitEquation::Parentheses(inner) => {
+            accumulate(inner, sign, exponents)
+        }
+        UnitEquation::Multiplication(left, right) => {
+            accumulate(left, sign, exponents);
+            accumulate(right, sign, exponents);
+        }
+        UnitEquation::Division(left, right) => {
+            accumulate(left, sign, exponents);
+            accumulate(right, -sign, exponents);
+        }
+        UnitEquation::Exponentiation(base, exponent) => {
+            accumulate(base, sign * exponent, exponents)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_units(xml: &str) -> ModelUnits {
+        quick_xml::de::from_str(&format!("<model_units>{xml}</model_units>")).unwrap()
+    }
+
+    #[test]
+    fn test_substitution_and_disabled_units() {
+        let units = model_units(
+            r#"<unit name="rabbits"><alias>rabbit</alias></unit>
+            <unit name="rabbits_per_day"><eqn>rabbit/day</eqn></unit>
+            <unit name="days" disabled="true"/>"#,
+        );
+        let table = UnitTable::new(Some(&units));
+        let substituted = table
+            .substitute(&parse_units("rabbits_per_day * days").unwrap())
+            .unwrap();
+        assert_eq!(substituted.to_string(), "(rabbits/day) * days");
+        assert_eq!(
+            table
+                .reduce_str("rabbits_per_day * days")
+                .unwrap()
+                .to_string(),
+            "days * rabbits/day"
+        );
+
+        let table = UnitTable::new(None);
+        assert!(
+            table
+                .reduce_str("per_day * day")
+                .unwrap()
+                .is_dimensionless()
+        );
+        assert_eq!(
+            table.reduce_str("Dmnl/years").unwrap().to_string(),
+            "1/years"
+        );
+    }
+
+    #[test]
+    fn test_exponents_and_simplification() {
+        let units = model_units(
+            r#"<unit name="acres"><eqn>furlongs^2</eqn><alias>acre</alias></unit>
+            <unit name="yield"><eqn>bushels/acre</eqn></unit>"#,
+        );
+        let table = UnitTable::new(Some(&units));
+        let simplified = table
+            .simplify(&parse_units("yield * acres^2 / (furlongs * Dmnl)").unwrap())
+            .unwrap();
+        assert_eq!(simplified.to_string(), "bushels * furlongs");
+        assert_eq!(parse_units(&simplified.to_string()), Some(simplified));
+        assert_eq!(
+            table.reduce_str("1/(acre * days^-1)").unwrap().to_string(),
+            "days/furlongs^2"
+        );
+        assert!(table.equivalent("yield^-1", "furlongs^2/bushels").unwrap());
+        assert!(!table.equivalent("acres", "furlongs").unwrap());
+        assert!(table.equivalent("1", "Dimensionless").unwrap());
+    }
+
+    #[test]
+    fn test_cycles_and_invalid_equations() {
+        let units = model_units(
+            r#"<unit name="widgets"><eqn>gadgets</eqn></unit>
+            <unit name="gadgets"><eqn>widgets * 1</eqn></unit>
+            <unit name="broken"><eqn>a +</eqn></unit>"#,
+        );
+        let table = UnitTable::new(Some(&units));
+        assert!(matches!(
+            table.reduce_str("widgets"),
+            Err(UnitError::Cycle(_))
+        ));
+        match table.validate() {
+            ValidationResult::Invalid(_, errors) => {
+                assert_eq!(errors.len(), 3, "{errors:?}");
+                assert!(errors[0].starts_with("Unit 'broken' has equation 'a +'"));
+            }
+            _ => panic!("expected the cycle and the invalid equation to be reported"),
+        }
+    }
+}
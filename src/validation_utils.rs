@@ -50,6 +50,30 @@ pub fn validate_ascending<V: PartialOrd + fmt::Display>(points: &[V]) -> Validat
     _return(warnings, errors)
 }
 
+pub fn validate_strictly_ascending<V: PartialOrd + fmt::Display>(points: &[V]) -> ValidationResult {
+    let warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    // Equal neighbours would make the segment between them zero-width
+    for i in 1..points.len() {
+        if points[i] < points[i - 1] {
+            errors.push(format!(
+                "values are not in ascending order: {} > {} at index {}",
+                points[i - 1],
+                points[i],
+                i
+            ));
+        } else if points[i] == points[i - 1] {
+            errors.push(format!(
+                "values are not strictly increasing: {} repeated at index {}",
+                points[i], i
+            ));
+        }
+    }
+
+    _return(warnings, errors)
+}
+
 pub fn validate_non_empty(points: &[f64]) -> ValidationResult {
     let warnings = Vec::new();
     let mut errors = Vec::new();
@@ -0,0 +1,779 @@
+//! Editing operations on views.
+//!
+//! Display objects are plain structs, and changing them one field at a time
+//! easily leaves a diagram inconsistent: a stock moves but the pipes drawn
+//! into it stay behind, or a connector keeps the points it had between the
+//! objects it used to join. The methods here make the changes a diagram
+//! editor makes, identifying objects by uid, and keep the geometry that
+//! depends on them consistent:
+//!
+//! - the ends of flow pipes stay on the edges of the stocks they touch, and
+//!   pipes keep their right angles, gaining a bend when one is needed;
+//! - flow valves stay on their pipes;
+//! - connectors with stored points follow the objects at their ends;
+//! - groups stay centred on their items.
+//!
+//! A flow end is attached to a stock when it lies on or inside the stock's
+//! outline; other ends are clouds, which move with the pipe.
+//!
+//! Every operation checks its arguments before changing anything, so a
+//! view is left unchanged when an operation fails.
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use super::View;
+use super::entity::same_name;
+use super::geometry::{Bounds, EPSILON, Outline, angle_between, lerp};
+use super::objects::{Point, Pointer, Shape, StockObject};
+use crate::Uid;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum EditError {
+    #[error("No display object has uid {}", .0.value)]
+    UnknownObject(Uid),
+    #[error("Display object {} has no position", .0.value)]
+    Unpositioned(Uid),
+    #[error("Display object {} cannot be moved", .0.value)]
+    NotMovable(Uid),
+    #[error("Display object {} cannot be resized", .0.value)]
+    NotResizable(Uid),
+    #[error("Display object {} is not a flow", .0.value)]
+    NotAFlow(Uid),
+    #[error("Display object {} is not a connector", .0.value)]
+    NotAConnector(Uid),
+    #[error("Display object {} is not a stock", .0.value)]
+    NotAStock(Uid),
+    #[error("Invalid size {width} x {height}")]
+    InvalidSize { width: f64, height: f64 },
+    #[error("Invalid grid size {0}")]
+    InvalidGridSize(f64),
+    #[error("Flow {} needs at least two points", .0.value)]
+    TooFewPoints(Uid),
+    #[error("The points of flow {} do not form right angles", .0.value)]
+    NotOrthogonal(Uid),
+    #[error("{} is not in this view", describe(.0))]
+    UnknownEndpoint(Pointer),
+    #[error("Connectors cannot point to alias {}", .0.value)]
+    ConnectorToAlias(Uid),
+}
+
+fn describe(pointer: &Pointer) -> String {
+    match pointer {
+        Pointer::Name(name) => name.clone(),
+        Pointer::Alias(uid) => format!("Alias {}", uid.value),
+    }
+}
+
+/// A display object of a view, by its index in the list of its kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum Target {
+    Stock(usize),
+    Flow(usize),
+    Aux(usize),
+    Module(usize),
+    Group(usize),
+    Connector(usize),
+    Alias(usize),
+    Container(usize),
+}
+
+impl View {
+    /// Moves the display object with `uid` by (`dx`, `dy`).
+    ///
+    /// - Moving a stock carries the ends of the pipes attached to it.
+    /// - A flow moves as a whole when neither end is attached to a stock;
+    ///   otherwise its valve slides along its pipe toward the new position.
+    /// - Moving a group moves its items.
+    /// - Moving a connector moves the points between its ends, so only
+    ///   connectors drawn through three or more points can be moved.
+    pub fn move_object(&mut self, uid: Uid, dx: f64, dy: f64) -> Result<(), EditError> {
+        let target = self.target(uid)?;
+        let positioned = match target {
+            Target::Stock(index) => {
+                self.stocks[index].x.is_some() && self.stocks[index].y.is_some()
+            }
+            Target::Flow(index) => self.flows[index].x.is_some() && self.flows[index].y.is_some(),
+            Target::Aux(index) => self.auxes[index].x.is_some() && self.auxes[index].y.is_some(),
+            _ => true,
+        };
+        if !positioned {
+            return Err(EditError::Unpositioned(uid));
+        }
+
+        match target {
+            Target::Connector(index) => {
+                let points = &self.connectors[index].pts;
+                if points.len() <= 2 {
+                    return Err(EditError::NotMovable(uid));
+                }
+                self.edit(|view| {
+                    let points = &mut view.connectors[index].pts;
+                    let last = points.len() - 1;
+                    for point in &mut points[1..last] {
+                        shift(point, dx, dy);
+                    }
+                });
+            }
+            Target::Flow(index) if self.attachments()[index].iter().any(Option::is_some) => {
+                self.edit(|view| {
+                    let flow = &mut view.flows[index];
+                    if let (Some(x), Some(y)) = (&mut flow.x, &mut flow.y) {
+                        let valve = nearest_on_path(
+                            &flow.pts,
+                            &Point {
+                                x: *x + dx,
+                                y: *y + dy,
+                            },
+                        );
+                        (*x, *y) = (valve.x, valve.y);
+                    }
+                });
+            }
+            _ => {
+                let mut moved = Vec::new();
+                self.collect_moved(target, &mut moved, &mut HashSet::new());
+                self.edit(|view| view.translate(&moved, dx, dy));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resizes the stock, flow valve, auxiliary, module or stacked container
+    /// with `uid`, keeping its centre and any shape it is drawn with in step.
+    ///
+    /// The ends of pipes attached to a resized stock are moved onto its new
+    /// edge.
+    pub fn resize_object(&mut self, uid: Uid, width: f64, height: f64) -> Result<(), EditError> {
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return Err(EditError::InvalidSize { width, height });
+        }
+        let target = self.target(uid)?;
+        if !matches!(
+            target,
+            Target::Stock(_)
+                | Target::Flow(_)
+                | Target::Aux(_)
+                | Target::Module(_)
+                | Target::Container(_)
+        ) {
+            return Err(EditError::NotResizable(uid));
+        }
+
+        let attachments = self.attachments();
+        self.edit(|view| match target {
+            Target::Stock(index) => {
+                let stock = &mut view.stocks[index];
+                (stock.width, stock.height) = (width, height);
+                resize_shape(&mut stock.shape, width, height);
+                for (flow, ends) in attachments.iter().enumerate() {
+                    for end in 0..2 {
+                        if ends[end] == Some(index) {
+                            view.pin_to_stock(flow, end, index, ends[1 - end].is_some());
+                        }
+                    }
+                }
+            }
+            Target::Flow(index) => {
+                let flow = &mut view.flows[index];
+                (flow.width, flow.height) = (width, height);
+            }
+            Target::Aux(index) => {
+                let aux = &mut view.auxes[index];
+                (aux.width, aux.height) = (Some(width), Some(height));
+                resize_shape(&mut aux.shape, width, height);
+            }
+            Target::Module(index) => {
+                let module = &mut view.modules[index];
+                (module.width, module.height) = (width, height);
+                resize_shape(&mut module.shape, width, height);
+            }
+            Target::Container(index) => {
+                let container = &mut view.stacked_containers[index];
+                (container.width, container.height) = (width, height);
+            }
+            _ => unreachable!("checked above"),
+        });
+        Ok(())
+    }
+
+    /// Replaces the points of the pipe of the flow with `uid`.
+    ///
+    /// The points must form right angles. Ends that were attached to a stock
+    /// are moved onto that stock's edge, and the valve onto the new pipe.
+    pub fn reroute_flow(&mut self, uid: Uid, points: Vec<Point>) -> Result<(), EditError> {
+        let Target::Flow(index) = self.target(uid)? else {
+            return Err(EditError::NotAFlow(uid));
+        };
+        if points.len() < 2 {
+            return Err(EditError::TooFewPoints(uid));
+        }
+        let orthogonal = points.windows(2).all(|pair| {
+            (pair[0].x - pair[1].x).abs() <= EPSILON || (pair[0].y - pair[1].y).abs() <= EPSILON
+        });
+        if !orthogonal {
+            return Err(EditError::NotOrthogonal(uid));
+        }
+
+        let ends = self.attachments()[index];
+        self.edit(|view| {
+            view.flows[index].pts = points;
+            for end in 0..2 {
+                if let Some(stock) = ends[end] {
+                    view.pin_to_stock(index, end, stock, ends[1 - end].is_some());
+                }
+            }
+            view.snap_valve(index);
+        });
+        Ok(())
+    }
+
+    /// Connects the connector with `uid` from `from` to `to` instead of the
+    /// objects it joined.
+    ///
+    /// Its stored points are cleared, and it leaves `from` heading straight
+    /// for `to`. Connectors may start at an alias but not point to one.
+    pub fn reconnect_connector(
+        &mut self,
+        uid: Uid,
+        from: Pointer,
+        to: Pointer,
+    ) -> Result<(), EditError> {
+        let Target::Connector(index) = self.target(uid)? else {
+            return Err(EditError::NotAConnector(uid));
+        };
+        if let Pointer::Alias(alias) = to {
+            return Err(EditError::ConnectorToAlias(alias));
+        }
+        let from_outline = self
+            .outline_of(&from)
+            .ok_or_else(|| EditError::UnknownEndpoint(from.clone()))?;
+        let to_outline = self
+            .outline_of(&to)
+            .ok_or_else(|| EditError::UnknownEndpoint(to.clone()))?;
+
+        let angle = angle_between(from_outline.center(), to_outline.center());
+        let start = from_outline.anchor(angle);
+        self.edit(|view| {
+            let connector = &mut view.connectors[index];
+            connector.from = from;
+            connector.to = to;
+            connector.pts.clear();
+            connector.angle = angle;
+            (connector.x, connector.y) = (start.x, start.y);
+        });
+        Ok(())
+    }
+
+    /// The bounds of the items of the group with `uid`, or `None` when it is
+    /// not a group or none of its items has a position.
+    pub fn group_bounds(&self, uid: Uid) -> Option<Bounds> {
+        match self.target(uid).ok()? {
+            group @ Target::Group(_) => self.bounds_of(group, &mut HashSet::new()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn target(&self, uid: Uid) -> Result<Target, EditError> {
+        fn position<T>(objects: &[T], uid: Uid, uid_of: impl Fn(&T) -> Uid) -> Option<usize> {
+            objects.iter().position(|object| uid_of(object) == uid)
+        }
+        position(&self.stocks, uid, |stock| stock.uid)
+            .map(Target::Stock)
+            .or_else(|| position(&self.flows, uid, |flow| flow.uid).map(Target::Flow))
+            .or_else(|| position(&self.auxes, uid, |aux| aux.uid).map(Target::Aux))
+            .or_else(|| position(&self.modules, uid, |module| module.uid).map(Target::Module))
+            .or_else(|| position(&self.groups, uid, |group| group.uid).map(Target::Group))
+            .or_else(|| {
+                position(&self.connectors, uid, |connector| connector.uid).map(Target::Connector)
+            })
+            .or_else(|| position(&self.aliases, uid, |alias| alias.uid).map(Target::Alias))
+            .or_else(|| {
+                position(&self.stacked_containers, uid, |container| container.uid)
+                    .map(Target::Container)
+            })
+            .ok_or(EditError::UnknownObject(uid))
+    }
+
+    /// Runs `change`, then moves each group whose items it moved by as much
+    /// as the centre of its items moved.
+    fn edit(&mut self, change: impl FnOnce(&mut View)) {
+        let before: Vec<Option<Bounds>> = self
+            .groups
+            .iter()
+            .map(|group| self.group_bounds(group.uid))
+            .collect();
+        change(self);
+        for (index, before) in before.into_iter().enumerate() {
+            let after = self.group_bounds(self.groups[index].uid);
+            if let (Some(before), Some(after)) = (before, after) {
+                let (before, after) = (before.center(), after.center());
+                let group = &mut self.groups[index];
+                group.x += after.x - before.x;
+                group.y += after.y - before.y;
+            }
+        }
+    }
+
+    /// The stock, if any, that the start and end of each flow's pipe are
+    /// attached to.
+    fn attachments(&self) -> Vec<[Option<usize>; 2]> {
+        let outlines: Vec<Option<Outline>> = self.stocks.iter().map(StockObject::outline).collect();
+        let attached = |point: Option<&Point>| {
+            let point = point?;
+            outlines
+                .iter()
+                .position(|outline| outline.as_ref().is_some_and(|o| o.contains(point)))
+        };
+        self.flows
+            .iter()
+            .map(|flow| [attached(flow.pts.first()), attached(flow.pts.last())])
+            .collect()
+    }
+
+    /// Adds `target`, and the items of groups, to `moved`. Connectors are
+    /// left out: their ends follow the objects they join.
+    fn collect_moved(&self, target: Target, moved: &mut Vec<Target>, groups: &mut HashSet<usize>) {
+        if let Target::Group(index) = target {
+            if !groups.insert(index) {
+                return;
+            }
+            for item in &self.groups[index].items {
+                match self.target(*item) {
+                    Ok(Target::Connector(_)) | Err(_) => {}
+                    Ok(item) => self.collect_moved(item, moved, groups),
+                }
+            }
+        }
+        if !moved.contains(&target) {
+            moved.push(target);
+        }
+    }
+
+    /// Moves every object in `moved` by (`dx`, `dy`), then the pipe ends
+    /// and connectors attached to them.
+    fn translate(&mut self, moved: &[Target], dx: f64, dy: f64) {
+        let attachments = self.attachments();
+        let mut names = Vec::new();
+        let mut aliases = HashSet::new();
+        for target in moved {
+            match *target {
+                Target::Stock(index) => {
+                    let stock = &mut self.stocks[index];
+                    shift_position(&mut stock.x, &mut stock.y, dx, dy);
+                    names.push(stock.name.clone());
+                }
+                Target::Flow(index) => {
+                    let flow = &mut self.flows[index];
+                    shift_position(&mut flow.x, &mut flow.y, dx, dy);
+                    flow.pts.iter_mut().for_each(|point| shift(point, dx, dy));
+                    names.push(flow.name.clone());
+                }
+                Target::Aux(index) => {
+                    let aux = &mut self.auxes[index];
+                    shift_position(&mut aux.x, &mut aux.y, dx, dy);
+                    names.push(aux.name.clone());
+                }
+                Target::Module(index) => {
+                    let module = &mut self.modules[index];
+                    (module.x, module.y) = (module.x + dx, module.y + dy);
+                    names.push(module.name.clone());
+                }
+                Target::Alias(index) => {
+                    let alias = &mut self.aliases[index];
+                    (alias.x, alias.y) = (alias.x + dx, alias.y + dy);
+                    aliases.insert(alias.uid);
+                }
+                Target::Container(index) => {
+                    let container = &mut self.stacked_containers[index];
+                    (container.x, container.y) = (container.x + dx, container.y + dy);
+                }
+                // Groups with items follow them once the edit is done
+                Target::Group(index) => {
+                    if self.group_bounds(self.groups[index].uid).is_none() {
+                        let group = &mut self.groups[index];
+                        (group.x, group.y) = (group.x + dx, group.y + dy);
+                    }
+                }
+                Target::Connector(_) => {}
+            }
+        }
+
+        // Each attached end moves by how far its stock moved relative to
+        // its pipe
+        let offset = |moved_here: bool| if moved_here { (dx, dy) } else { (0.0, 0.0) };
+        for (index, ends) in attachments.iter().enumerate() {
+            let pipe = offset(moved.contains(&Target::Flow(index)));
+            let relative = ends.map(|end| {
+                let stock = offset(end.is_some_and(|stock| moved.contains(&Target::Stock(stock))));
+                (stock.0 - pipe.0, stock.1 - pipe.1)
+            });
+            let needs_move = |end: usize| ends[end].is_some() && relative[end] != (0.0, 0.0);
+            if ends.iter().all(Option::is_some) && relative[0] == relative[1] {
+                if needs_move(0) {
+                    let (dx, dy) = relative[0];
+                    let flow = &mut self.flows[index];
+                    shift_position(&mut flow.x, &mut flow.y, dx, dy);
+                    flow.pts.iter_mut().for_each(|point| shift(point, dx, dy));
+                }
+                continue;
+            }
+            let mut changed = false;
+            for end in 0..2 {
+                if needs_move(end) {
+                    let points = &mut self.flows[index].pts;
+                    let Some(point) = (if end == 0 {
+                        points.first()
+                    } else {
+                        points.last()
+                    }) else {
+                        continue;
+                    };
+                    let target = Point {
+                        x: point.x + relative[end].0,
+                        y: point.y + relative[end].1,
+                    };
+                    reattach(points, end == 1, target, ends[1 - end].is_some());
+                    changed = true;
+                }
+            }
+            if changed {
+                self.snap_valve(index);
+            }
+        }
+
+        let refers = |pointer: &Pointer| match pointer {
+            Pointer::Name(name) => names.iter().any(|moved| same_name(moved, name)),
+            Pointer::Alias(uid) => aliases.contains(uid),
+        };
+        for connector in &mut self.connectors {
+            let (from, to) = (refers(&connector.from), refers(&connector.to));
+            if from {
+                (connector.x, connector.y) = (connector.x + dx, connector.y + dy);
+            }
+            if connector.pts.len() < 2 {
+                continue;
+            }
+            let last = connector.pts.len() - 1;
+            if from && to {
+                connector
+                    .pts
+                    .iter_mut()
+                    .for_each(|point| shift(point, dx, dy));
+            } else if from {
+                shift(&mut connector.pts[0], dx, dy);
+            } else if to {
+                shift(&mut connector.pts[last], dx, dy);
+            }
+        }
+    }
+
+    /// Moves the `end` (0 for the start, 1 for the end) of the pipe of
+    /// `flow` onto the edge of `stock`, on the side the pipe leaves from.
+    fn pin_to_stock(&mut self, flow: usize, end: usize, stock: usize, other_fixed: bool) {
+        let Some(bounds) = self.stocks[stock].outline().map(|outline| outline.bounds()) else {
+            return;
+        };
+        let points = &mut self.flows[flow].pts;
+        if points.len() < 2 {
+            return;
+        }
+        let (point, next) = if end == 0 {
+            (&points[0], &points[1])
+        } else {
+            (&points[points.len() - 1], &points[points.len() - 2])
+        };
+        let center = bounds.center();
+        let target = if is_horizontal(point, next) {
+            Point {
+                x: if next.x >= center.x {
+                    bounds.max.x
+                } else {
+                    bounds.min.x
+                },
+                y: point.y.clamp(bounds.min.y, bounds.max.y),
+            }
+        } else {
+            Point {
+                x: point.x.clamp(bounds.min.x, bounds.max.x),
+                y: if next.y >= center.y {
+                    bounds.max.y
+                } else {
+                    bounds.min.y
+                },
+            }
+        };
+        reattach(points, end == 1, target, other_fixed);
+        self.snap_valve(flow);
+    }
+
+    /// Moves the valve of `flow` to the nearest point on its pipe.
+    fn snap_valve(&mut self, flow: usize) {
+        let flow = &mut self.flows[flow];
+        if let (Some(x), Some(y)) = (&mut flow.x, &mut flow.y) {
+            let valve = nearest_on_path(&flow.pts, &Point { x: *x, y: *y });
+            (*x, *y) = (valve.x, valve.y);
+        }
+    }
+
+    pub(super) fn bounds_of(&self, target: Target, groups: &mut HashSet<usize>) -> Option<Bounds> {
+        match target {
+            Target::Stock(index) => Some(self.stocks[index].outline()?.bounds()),
+            Target::Flow(index) => {
+                let flow = &self.flows[index];
+                let valve = flow.outline().map(|outline| outline.bounds());
+                let pipe = Bounds::of_points(&flow.pts);
+                match (valve, pipe) {
+                    (Some(valve), Some(pipe)) => Some(valve.union(&pipe)),
+                    (valve, pipe) => valve.or(pipe),
+                }
+            }
+            Target::Aux(index) => Some(self.auxes[index].outline()?.bounds()),
+            Target::Module(index) => Some(self.modules[index].outline().bounds()),
+            Target::Alias(index) => Some(
+                self.outline_of(&Pointer::Alias(self.aliases[index].uid))?
+                    .bounds(),
+            ),
+            Target::Container(index) => {
+                let container = &self.stacked_containers[index];
+                Some(
+                    Outline::from_shape(
+                        container.x,
+                        container.y,
+                        container.width,
+                        container.height,
+                        None,
+                    )
+                    .bounds(),
+                )
+            }
+            Target::Group(index) => {
+                if !groups.insert(index) {
+                    return None;
+                }
+                self.groups[index]
+                    .items
+                    .iter()
+                    .filter_map(|item| self.bounds_of(self.target(*item).ok()?, groups))
+                    .reduce(|a, b| a.union(&b))
+            }
+            Target::Connector(_) => None,
+        }
+    }
+}
+
+fn shift(point: &mut Point, dx: f64, dy: f64) {
+    point.x += dx;
+    point.y += dy;
+}
+
+fn shift_position(x: &mut Option<f64>, y: &mut Option<f64>, dx: f64, dy: f64) {
+    if let (Some(x), Some(y)) = (x, y) {
+        *x += dx;
+        *y += dy;
+    }
+}
+
+/// Whether the segment from `a` to `b` runs across rather than up and
+/// down; a segment of no length counts as across.
+fn is_horizontal(a: &Point, b: &Point) -> bool {
+    (a.y - b.y).abs() <= (a.x - b.x).abs()
+}
+
+/// Moves the start of `points`, or the end when `at_end`, to `target`,
+/// keeping right angles.
+///
+/// The next point slides to stay in line, unless it is the other end and
+/// that end is fixed to a stock, in which case the pipe gains a bend halfway
+/// along.
+fn reattach(points: &mut Vec<Point>, at_end: bool, target: Point, other_fixed: bool) {
+    if points.len() < 2 {
+        return;
+    }
+    if at_end {
+        points.reverse();
+    }
+    let horizontal = is_horizontal(&points[0], &points[1]);
+    let next = points[1].clone();
+    points[0] = target;
+    let start = points[0].clone();
+    if points.len() > 2 || !other_fixed {
+        if horizontal {
+            points[1].y = start.y;
+        } else {
+            points[1].x = start.x;
+        }
+    } else if horizontal && (start.y - next.y).abs() > EPSILON {
+        let x = (start.x + next.x) / 2.0;
+        let bend = [Point { x, y: start.y }, Point { x, y: next.y }];
+        points.splice(1..1, bend);
+    } else if !horizontal && (start.x - next.x).abs() > EPSILON {
+        let y = (start.y + next.y) / 2.0;
+        let bend = [Point { x: start.x, y }, Point { x: next.x, y }];
+        points.splice(1..1, bend);
+    }
+    if at_end {
+        points.reverse();
+    }
+}
+
+/// The point on the path through `points` nearest to `point`, or `point`
+/// itself when there is no path.
+fn nearest_on_path(points: &[Point], point: &Point) -> Point {
+    let distance = |a: &Point| (a.x - point.x).hypot(a.y - point.y);
+    points
+        .windows(2)
+        .map(|pair| {
+            let (start, end) = (&pair[0], &pair[1]);
+            let (dx, dy) = (end.x - start.x, end.y - start.y);
+            let length_sq = dx * dx + dy * dy;
+            if length_sq < EPSILON {
+                return start.clone();
+            }
+            let t =
+                (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_sq).clamp(0.0, 1.0);
+            lerp(start, end, t)
+        })
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .or_else(|| points.first().cloned())
+        .unwrap_or_else(|| point.clone())
+}
+
+fn resize_shape(shape: &mut Option<Shape>, new_width: f64, new_height: f64) {
+    match shape {
+        Some(Shape::Rectangle { width, height, .. }) => {
+            (*width, *height) = (new_width, new_height);
+        }
+        Some(Shape::Circle { radius }) => *radius = new_width.min(new_height) / 2.0,
+        Some(Shape::NameOnly { width, height }) => {
+            (*width, *height) = (Some(new_width), Some(new_height));
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    fn view() -> View {
+        quick_xml::de::from_str(
+            r#"<view uid="1" width="800" height="600" page_width="800" page_height="600">
+                <stock uid="2" name="Population" x="100" y="100" width="40" height="20"/>
+                <stock uid="3" name="Deaths_Total" x="300" y="100" width="40" height="20"/>
+                <flow uid="4" name="deaths" x="200" y="100" width="18" height="18">
+                    <pts><pt x="120" y="100"/><pt x="280" y="100"/></pts>
+                </flow>
+                <flow uid="5" name="births" x="60" y="100" width="18" height="18">
+                    <pts><pt x="20" y="100"/><pt x="80" y="100"/></pts>
+                </flow>
+                <aux uid="6" name="Rate" x="100" y="200" width="18" height="18"/>
+                <connector uid="7" x="100" y="191" angle="90">
+                    <from>Rate</from><to>Population</to>
+                    <pts><pt x="100" y="191"/><pt x="100" y="110"/></pts>
+                </connector>
+                <group uid="8" name="Inputs" x="100" y="200"><item uid="6"/></group>
+            </view>"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_moving_stock_keeps_pipes_attached() {
+        let mut view = view();
+        view.move_object(Uid::new(2), 0.0, 50.0).unwrap();
+
+        // Between two stocks, the pipe bends halfway to stay square
+        assert_eq!(
+            view.flows[0].pts,
+            [
+                point(120.0, 150.0),
+                point(200.0, 150.0),
+                point(200.0, 100.0),
+                point(280.0, 100.0)
+            ]
+        );
+        assert_eq!(
+            (view.flows[0].x, view.flows[0].y),
+            (Some(200.0), Some(100.0))
+        );
+        // From a cloud, the cloud and valve come along
+        assert_eq!(view.flows[1].pts, [point(20.0, 150.0), point(80.0, 150.0)]);
+        assert_eq!(view.flows[1].y, Some(150.0));
+        assert_eq!(view.connectors[0].pts[1], point(100.0, 160.0));
+
+        // The valve of an attached flow slides along its pipe
+        view.move_object(Uid::new(5), 100.0, 0.0).unwrap();
+        assert_eq!(
+            (view.flows[1].x, view.flows[1].y),
+            (Some(80.0), Some(150.0))
+        );
+        assert_eq!(
+            view.move_object(Uid::new(7), 1.0, 1.0),
+            Err(EditError::NotMovable(Uid::new(7)))
+        );
+    }
+
+    #[test]
+    fn test_resize_reroute_and_reconnect() {
+        let mut view = view();
+        view.resize_object(Uid::new(3), 80.0, 20.0).unwrap();
+        assert_eq!(view.flows[0].pts[1], point(260.0, 100.0));
+        assert_eq!(
+            view.resize_object(Uid::new(8), 1.0, 1.0),
+            Err(EditError::NotResizable(Uid::new(8)))
+        );
+
+        assert_eq!(
+            view.reroute_flow(Uid::new(4), vec![point(0.0, 0.0), point(10.0, 10.0)]),
+            Err(EditError::NotOrthogonal(Uid::new(4)))
+        );
+        view.reroute_flow(
+            Uid::new(4),
+            vec![
+                point(100.0, 100.0),
+                point(100.0, 50.0),
+                point(300.0, 50.0),
+                point(300.0, 100.0),
+            ],
+        )
+        .unwrap();
+        assert_eq!(view.flows[0].pts[0], point(100.0, 90.0));
+        assert_eq!(view.flows[0].pts[3], point(300.0, 90.0));
+        assert_eq!(
+            (view.flows[0].x, view.flows[0].y),
+            (Some(200.0), Some(50.0))
+        );
+
+        view.reconnect_connector(
+            Uid::new(7),
+            Pointer::Name("Rate".to_string()),
+            Pointer::Name("deaths_total".to_string()),
+        )
+        .unwrap();
+        let connector = &view.connectors[0];
+        assert!(connector.pts.is_empty());
+        assert!((connector.angle - 26.565).abs() < 1e-3);
+        assert_eq!(
+            view.reconnect_connector(
+                Uid::new(7),
+                Pointer::Name("Rate".to_string()),
+                Pointer::Name("Missing".to_string())
+            ),
+            Err(EditError::UnknownEndpoint(Pointer::Name(
+                "Missing".to_string()
+            )))
+        );
+
+        view.move_object(Uid::new(6), 10.0, -20.0).unwrap();
+        assert_eq!((view.groups[0].x, view.groups[0].y), (110.0, 180.0));
+        let bounds = view.group_bounds(Uid::new(8)).unwrap();
+        assert_eq!(bounds.center(), point(110.0, 180.0));
+    }
+}
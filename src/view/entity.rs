@@ -0,0 +1,531 @@
+//! Display objects that stand for model entities, and the aliases that
+//! point at them.
+//!
+//! An `<alias>` names the object it represents in its `<of>` tag, and is
+//! drawn with that object's label (Section 6.1.7). Aliases may only represent
+//! stocks, flows and auxiliaries.
+
+use crate::{Expression, Identifier, Uid};
+
+use super::View;
+use super::geometry::Outline;
+use super::objects::{
+    AliasObject, AuxObject, ConnectorObject, FlowObject, GroupObject, ModuleObject, Pointer,
+    StockObject,
+};
+use crate::equation::expression::function::FunctionTarget;
+use crate::model::entity::{self, EntityKind};
+use crate::model::vars::Variable;
+use crate::xml::schema::Model;
+
+/// The kind of model entity a display object stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewEntityKind {
+    Stock,
+    Flow,
+    Aux,
+    Module,
+}
+
+/// A display object drawn for a named model entity.
+pub trait ViewEntity {
+    fn uid(&self) -> Uid;
+
+    /// The name of the entity as written in the view.
+    fn name(&self) -> &str;
+
+    fn kind(&self) -> ViewEntityKind;
+
+    /// The outline of the object, or `None` while it has no position.
+    fn outline(&self) -> Option<Outline>;
+
+    /// The text drawn on the object's nameplate.
+    fn label(&self) -> String {
+        display_label(self.name())
+    }
+}
+
+impl ViewEntity for StockObject {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> ViewEntityKind {
+        ViewEntityKind::Stock
+    }
+
+    fn outline(&self) -> Option<Outline> {
+        StockObject::outline(self)
+    }
+}
+
+impl ViewEntity for FlowObject {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> ViewEntityKind {
+        ViewEntityKind::Flow
+    }
+
+    fn outline(&self) -> Option<Outline> {
+        FlowObject::outline(self)
+    }
+}
+
+impl FlowObject {
+    /// Whether the equation of the flow this object is drawn for applies a
+    /// graphical function of `model`, which diagrams mark on the valve.
+    pub fn uses_graphical_function(&self, model: &Model) -> bool {
+        let Ok(name) = Identifier::parse_from_attribute(&self.name) else {
+            return false;
+        };
+        let variables = &model.variables.variables;
+        let is_graphical_function = |name: &Identifier| {
+            variables.iter().any(|variable| {
+                matches!(variable, Variable::GraphicalFunction(gf) if gf.name.as_ref() == Some(name))
+            })
+        };
+        variables.iter().any(|variable| match variable {
+            Variable::Flow(flow) if flow.name == name => {
+                flow.equation.as_ref().is_some_and(|equation| {
+                    applies_graphical_function(equation, &is_graphical_function)
+                })
+            }
+            _ => false,
+        })
+    }
+}
+
+fn applies_graphical_function(
+    expression: &Expression,
+    is_graphical_function: &dyn Fn(&Identifier) -> bool,
+) -> bool {
+    let applied = match expression {
+        Expression::FunctionCall {
+            target: FunctionTarget::GraphicalFunction(_),
+            ..
+        } => true,
+        Expression::FunctionCall {
+            target: FunctionTarget::Function(name),
+            ..
+        } => is_graphical_function(name),
+        _ => false,
+    };
+    applied
+        || expression
+            .children()
+            .into_iter()
+            .any(|child| applies_graphical_function(child, is_graphical_function))
+}
+
+impl ViewEntity for AuxObject {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> ViewEntityKind {
+        ViewEntityKind::Aux
+    }
+
+    fn outline(&self) -> Option<Outline> {
+        AuxObject::outline(self)
+    }
+}
+
+impl ViewEntity for ModuleObject {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> ViewEntityKind {
+        ViewEntityKind::Module
+    }
+
+    fn outline(&self) -> Option<Outline> {
+        Some(ModuleObject::outline(self))
+    }
+}
+
+impl entity::Entity for StockObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::StockObject
+    }
+}
+
+impl entity::Entity for FlowObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::FlowObject
+    }
+}
+
+impl entity::Entity for AuxObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::AuxObject
+    }
+}
+
+impl entity::Entity for ModuleObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::ModuleObject
+    }
+}
+
+impl entity::Entity for GroupObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::GroupObject
+    }
+}
+
+impl entity::Entity for ConnectorObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::ConnectorObject
+    }
+}
+
+impl entity::Entity for AliasObject {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    /// The name of the object the alias represents.
+    fn name(&self) -> Option<&str> {
+        Some(&self.of)
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::AliasObject
+    }
+}
+
+impl entity::Entity for View {
+    fn uid(&self) -> Option<Uid> {
+        Some(self.uid)
+    }
+
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn kind(&self) -> EntityKind {
+        EntityKind::View
+    }
+}
+
+impl View {
+    /// The display objects of this view that are entities, in the order
+    /// stocks, flows, auxiliaries, modules, groups, connectors and aliases.
+    pub fn entities(&self) -> Vec<&dyn entity::Entity> {
+        let mut entities: Vec<&dyn entity::Entity> = Vec::new();
+        entities.extend(self.stocks.iter().map(|o| o as &dyn entity::Entity));
+        entities.extend(self.flows.iter().map(|o| o as &dyn entity::Entity));
+        entities.extend(self.auxes.iter().map(|o| o as &dyn entity::Entity));
+        entities.extend(self.modules.iter().map(|o| o as &dyn entity::Entity));
+        entities.extend(self.groups.iter().map(|o| o as &dyn entity::Entity));
+        entities.extend(self.connectors.iter().map(|o| o as &dyn entity::Entity));
+        entities.extend(self.aliases.iter().map(|o| o as &dyn entity::Entity));
+        entities
+    }
+
+    /// Finds the stock, flow, auxiliary or module drawn for `name`.
+    pub fn entity_named(&self, name: &str) -> Option<&dyn ViewEntity> {
+        self.aliasable_named(name).or_else(|| {
+            self.modules
+                .iter()
+                .find(|module| same_name(&module.name, name))
+                .map(|module| module as &dyn ViewEntity)
+        })
+    }
+
+    /// Finds the object in this view that `alias` represents.
+    pub fn resolve_alias(&self, alias: &AliasObject) -> Option<&dyn ViewEntity> {
+        self.aliasable_named(&alias.of)
+    }
+
+    /// The label drawn on `alias`: that of the object it represents, or
+    /// derived from its `<of>` name when that object is in another view.
+    pub fn alias_label(&self, alias: &AliasObject) -> String {
+        self.resolve_alias(alias)
+            .map(ViewEntity::label)
+            .unwrap_or_else(|| display_label(&alias.of))
+    }
+
+    /// Finds the object a connector end attaches to: the object drawn for
+    /// its name, the module of a ghost, or the object its alias represents.
+    ///
+    /// Returns `None` if the object is not drawn in this view, including
+    /// when the alias is in another view or represents an object there.
+    pub fn resolve_pointer(&self, pointer: &Pointer) -> Option<&dyn ViewEntity> {
+        match pointer {
+            Pointer::Name(name) => self.entity_named(name).or_else(|| {
+                let (module, _) = ghost_parts(name)?;
+                self.modules
+                    .iter()
+                    .find(|object| same_name(&object.name, module))
+                    .map(|object| object as &dyn ViewEntity)
+            }),
+            Pointer::Alias(uid) => {
+                let alias = self.aliases.iter().find(|alias| alias.uid == *uid)?;
+                self.resolve_alias(alias)
+            }
+        }
+    }
+
+    /// The name of the variable a connector end refers to: the name it
+    /// gives, or the `<of>` name of its alias. Ghosts keep their qualified
+    /// name, e.g. `Economy.GDP`.
+    ///
+    /// Returns `None` if the alias is not in this view.
+    pub fn pointer_variable<'a>(&'a self, pointer: &'a Pointer) -> Option<&'a str> {
+        match pointer {
+            Pointer::Name(name) => Some(name.as_str()),
+            Pointer::Alias(uid) => self
+                .aliases
+                .iter()
+                .find(|alias| alias.uid == *uid)
+                .map(|alias| alias.of.as_str()),
+        }
+    }
+
+    /// Finds the stock, flow or auxiliary drawn for `name`, the objects an
+    /// alias may represent.
+    pub(crate) fn aliasable_named(&self, name: &str) -> Option<&dyn ViewEntity> {
+        let matches = |other: &str| same_name(other, name);
+        if let Some(stock) = self.stocks.iter().find(|stock| matches(&stock.name)) {
+            return Some(stock);
+        }
+        if let Some(flow) = self.flows.iter().find(|flow| matches(&flow.name)) {
+            return Some(flow);
+        }
+        self.auxes
+            .iter()
+            .find(|aux| matches(&aux.name))
+            .map(|aux| aux as &dyn ViewEntity)
+    }
+}
+
+/// Derives the text drawn for an entity name: `\n` escapes become line
+/// breaks and underscores become spaces, e.g. `Birth_Rate\nPer_Year` is
+/// drawn as "Birth Rate" over "Per Year".
+pub fn display_label(name: &str) -> String {
+    let name = name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+        .unwrap_or(name);
+    name.replace("\\n", "\n").replace('_', " ")
+}
+
+/// Splits the name of a ghost, `Module.variable`, into the module and the
+/// variable inside it. Quoted names may contain periods and are never
+/// ghosts.
+pub(crate) fn ghost_parts(name: &str) -> Option<(&str, &str)> {
+    if name.starts_with('"') {
+        return None;
+    }
+    let (module, variable) = name.split_once('.')?;
+    (!module.is_empty() && !variable.is_empty()).then_some((module, variable))
+}
+
+/// Compares display names as identifiers, so `Birth_Rate` matches
+/// `birth rate`, falling back to exact comparison for unparsable names.
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    match (
+        Identifier::parse_from_attribute(a),
+        Identifier::parse_from_attribute(b),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view() -> View {
+        quick_xml::de::from_str(
+            r#"<view uid="1" width="800" height="600" page_width="800" page_height="600">
+                <stock uid="2" name="Population" x="100" y="100" width="40" height="20"/>
+                <aux uid="3" name="Birth_Rate" x="200" y="100"/>
+                <module uid="4" name="Economy" x="300" y="100" width="40" height="40"/>
+                <alias uid="5" x="200" y="200"><of>birth rate</of></alias>
+                <alias uid="6" x="300" y="200"><of>Economy</of></alias>
+                <alias uid="7" x="400" y="200"><of>Death_Rate\nPer_Year</of></alias>
+            </view>"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let view = view();
+        let target = view.resolve_alias(&view.aliases[0]).unwrap();
+        assert_eq!(target.uid(), Uid::new(3));
+        assert_eq!(target.kind(), ViewEntityKind::Aux);
+        assert_eq!(view.alias_label(&view.aliases[0]), "Birth Rate");
+
+        // Modules cannot be aliased
+        assert!(view.resolve_alias(&view.aliases[1]).is_none());
+        assert_eq!(
+            view.entity_named("economy").map(ViewEntity::kind),
+            Some(ViewEntityKind::Module)
+        );
+
+        // Targets in other views still get a label
+        assert!(view.resolve_alias(&view.aliases[2]).is_none());
+        assert_eq!(view.alias_label(&view.aliases[2]), "Death Rate\nPer Year");
+    }
+
+    #[test]
+    fn test_resolve_pointer() {
+        let view: View = quick_xml::de::from_str(
+            r#"<view uid="1" width="800" height="600" page_width="800" page_height="600">
+                <aux uid="3" name="Birth_Rate" x="200" y="100"/>
+                <module uid="4" name="Economy" x="300" y="100" width="40" height="40"/>
+                <alias uid="5" x="200" y="200"><of>birth rate</of></alias>
+                <connector uid="8" x="200" y="200" angle="0">
+                    <from>
+                        <alias uid="5"/>
+                    </from>
+                    <to> Economy.GDP </to>
+                </connector>
+                <connector uid="9" x="200" y="100" angle="0">
+                    <from><alias uid="6"/></from>
+                    <to>"Economy.GDP"</to>
+                </connector>
+            </view>"#,
+        )
+        .unwrap();
+        let [ghost, missing] = view.connectors.as_slice() else {
+            panic!("Expected two connectors");
+        };
+        assert_eq!(ghost.from, Pointer::Alias(Uid::new(5)));
+        assert_eq!(ghost.to, Pointer::Name("Economy.GDP".to_string()));
+
+        // Aliases resolve to what they represent, ghosts to their module
+        let from = view.resolve_pointer(&ghost.from).unwrap();
+        assert_eq!(from.uid(), Uid::new(3));
+        assert_eq!(view.pointer_variable(&ghost.from), Some("birth rate"));
+        let to = view.resolve_pointer(&ghost.to).unwrap();
+        assert_eq!(to.kind(), ViewEntityKind::Module);
+        assert_eq!(view.pointer_variable(&ghost.to), Some("Economy.GDP"));
+
+        assert!(view.resolve_pointer(&missing.from).is_none());
+        assert_eq!(view.pointer_variable(&missing.from), None);
+        assert!(view.resolve_pointer(&missing.to).is_none());
+    }
+
+    #[test]
+    fn test_flow_uses_graphical_function() {
+        let model: Model = quick_xml::de::from_str(
+            r#"<model>
+                <variables>
+                    <flow name="Births"><eqn>Population * fertility(Crowding)</eqn></flow>
+                    <flow name="Deaths"><eqn>MAX(Population / 20, 0)</eqn></flow>
+                    <gf name="fertility">
+                        <xscale min="0" max="1"/>
+                        <ypts>0.1,0</ypts>
+                    </gf>
+                </variables>
+            </model>"#,
+        )
+        .unwrap();
+        let flow = |name: &str| -> FlowObject {
+            quick_xml::de::from_str(&format!(
+                r#"<flow uid="1" name="{name}" x="0" y="0" width="18" height="18"><pts><pt x="0" y="0"/></pts></flow>"#
+            ))
+            .unwrap()
+        };
+        assert!(flow("Births").uses_graphical_function(&model));
+        assert!(!flow("Deaths").uses_graphical_function(&model));
+        assert!(!flow("Missing").uses_graphical_function(&model));
+    }
+
+    #[test]
+    fn test_view_entities() {
+        let view = view();
+        let entities: Vec<(Option<Uid>, Option<&str>, EntityKind)> = view
+            .entities()
+            .into_iter()
+            .map(|e| (e.uid(), e.name(), e.kind()))
+            .collect();
+        assert_eq!(entities.len(), 6);
+        assert_eq!(
+            entities[1],
+            (Some(Uid::new(3)), Some("Birth_Rate"), EntityKind::AuxObject)
+        );
+        assert_eq!(
+            entities[3],
+            (
+                Some(Uid::new(5)),
+                Some("birth rate"),
+                EntityKind::AliasObject
+            )
+        );
+        assert_eq!(entity::Entity::kind(&view), EntityKind::View);
+    }
+}
@@ -0,0 +1,931 @@
+//! Geometry of connectors in stock and flow diagrams.
+//!
+//! XMILE positions display objects by their centre in view coordinates, where
+//! y grows downwards, and measures connector angles in degrees counter-clockwise
+//! from 3 o'clock (Section 6.1.6). A connector leaves the edge of its start
+//! object at its `angle`, and is drawn as
+//!
+//! - an arc tangent to that angle when it has no `<pts>` or two points,
+//! - a smooth curve through its points when it has three or more,
+//!
+//! ending where it meets the edge of the object it points to. These helpers
+//! compute that path once so that renderers, hit-testing and auto-layout agree
+//! on where a connector is.
+//!
+//! A flow is drawn as a pipe through its `<pts>`, from the first point to the
+//! last, with its valve at the flow's position. Each end of the pipe is
+//! attached to the stock whose outline it lies on or inside; an end attached
+//! to no stock is drawn as a cloud, a source at the start of the pipe or a
+//! sink at its end.
+
+use std::f64::consts::PI;
+
+use super::View;
+use super::entity::ViewEntity;
+use super::objects::{
+    AliasObject, AuxObject, ConnectorObject, FlowObject, ModuleObject, Point, Pointer, Shape,
+    StockObject,
+};
+
+/// Size used for auxiliaries that give neither a width, height nor shape.
+pub const DEFAULT_AUX_SIZE: f64 = 18.0;
+
+/// Distance below which two positions are treated as the same.
+pub(super) const EPSILON: f64 = 1e-9;
+
+/// Number of straight segments used per curve segment when flattening.
+const CURVE_STEPS: usize = 16;
+
+/// The edge of a display object, against which connectors are anchored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    Rectangle {
+        center: Point,
+        width: f64,
+        height: f64,
+    },
+    Circle {
+        center: Point,
+        radius: f64,
+    },
+}
+
+impl Outline {
+    /// Builds the outline of an object centred at (`x`, `y`) drawn with
+    /// `shape`, or as a `width` by `height` rectangle without one.
+    pub fn from_shape(x: f64, y: f64, width: f64, height: f64, shape: Option<&Shape>) -> Self {
+        let center = Point { x, y };
+        match shape {
+            Some(Shape::Circle { radius }) => Outline::Circle {
+                center,
+                radius: *radius,
+            },
+            Some(Shape::Rectangle { width, height, .. }) => Outline::Rectangle {
+                center,
+                width: *width,
+                height: *height,
+            },
+            Some(Shape::NameOnly {
+                width: shape_width,
+                height: shape_height,
+            }) => Outline::Rectangle {
+                center,
+                width: shape_width.unwrap_or(width),
+                height: shape_height.unwrap_or(height),
+            },
+            None => Outline::Rectangle {
+                center,
+                width,
+                height,
+            },
+        }
+    }
+
+    pub fn center(&self) -> &Point {
+        match self {
+            Outline::Rectangle { center, .. } | Outline::Circle { center, .. } => center,
+        }
+    }
+
+    /// Returns the same outline centred on `center`.
+    pub fn moved_to(&self, center: Point) -> Self {
+        match *self {
+            Outline::Rectangle { width, height, .. } => Outline::Rectangle {
+                center,
+                width,
+                height,
+            },
+            Outline::Circle { radius, .. } => Outline::Circle { center, radius },
+        }
+    }
+
+    /// Whether `point` lies on or inside the outline.
+    pub fn contains(&self, point: &Point) -> bool {
+        match self {
+            Outline::Rectangle {
+                center,
+                width,
+                height,
+            } => {
+                (point.x - center.x).abs() <= width / 2.0 + EPSILON
+                    && (point.y - center.y).abs() <= height / 2.0 + EPSILON
+            }
+            Outline::Circle { center, radius } => distance(center, point) <= radius + EPSILON,
+        }
+    }
+
+    /// The point on the edge in the direction of `angle` (in XMILE degrees)
+    /// from the centre.
+    pub fn anchor(&self, angle: f64) -> Point {
+        let (dx, dy) = direction(angle);
+        match self {
+            Outline::Rectangle {
+                center,
+                width,
+                height,
+            } => {
+                let reach_x = if dx.abs() > EPSILON {
+                    width / 2.0 / dx.abs()
+                } else {
+                    f64::INFINITY
+                };
+                let reach_y = if dy.abs() > EPSILON {
+                    height / 2.0 / dy.abs()
+                } else {
+                    f64::INFINITY
+                };
+                let reach = reach_x.min(reach_y);
+                Point {
+                    x: center.x + reach * dx,
+                    y: center.y + reach * dy,
+                }
+            }
+            Outline::Circle { center, radius } => Point {
+                x: center.x + radius * dx,
+                y: center.y + radius * dy,
+            },
+        }
+    }
+
+    /// The point on the edge facing `point`.
+    pub fn anchor_toward(&self, point: &Point) -> Point {
+        self.anchor(angle_between(self.center(), point))
+    }
+
+    /// The smallest axis-aligned rectangle containing the outline.
+    pub fn bounds(&self) -> Bounds {
+        let (center, half_width, half_height) = match self {
+            Outline::Rectangle {
+                center,
+                width,
+                height,
+            } => (center, width / 2.0, height / 2.0),
+            Outline::Circle { center, radius } => (center, *radius, *radius),
+        };
+        Bounds {
+            min: Point {
+                x: center.x - half_width,
+                y: center.y - half_height,
+            },
+            max: Point {
+                x: center.x + half_width,
+                y: center.y + half_height,
+            },
+        }
+    }
+}
+
+/// An axis-aligned rectangle in view coordinates, given by its top-left
+/// (`min`) and bottom-right (`max`) corners.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// The bounds of `points`, or `None` when there are none.
+    pub fn of_points<'a>(points: impl IntoIterator<Item = &'a Point>) -> Option<Self> {
+        points
+            .into_iter()
+            .fold(None, |bounds: Option<Bounds>, point| {
+                let around = Bounds {
+                    min: point.clone(),
+                    max: point.clone(),
+                };
+                Some(match bounds {
+                    Some(bounds) => bounds.union(&around),
+                    None => around,
+                })
+            })
+    }
+
+    /// The smallest bounds containing both `self` and `other`.
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Point {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+            },
+            max: Point {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+            },
+        }
+    }
+
+    pub fn center(&self) -> Point {
+        lerp(&self.min, &self.max, 0.5)
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+}
+
+/// The path a connector is drawn along.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectorPath {
+    Line {
+        start: Point,
+        end: Point,
+    },
+    /// An arc of the circle around `center`, starting at `start_angle` and
+    /// turning through `sweep` degrees (positive is counter-clockwise).
+    Arc {
+        center: Point,
+        radius: f64,
+        start_angle: f64,
+        sweep: f64,
+    },
+    /// A smooth curve passing through every point in order.
+    Curve {
+        points: Vec<Point>,
+    },
+}
+
+impl ConnectorPath {
+    /// The arc leaving `start` in the direction of `angle` and ending at `end`,
+    /// or a straight line when `end` lies straight ahead.
+    pub fn arc(start: Point, angle: f64, end: Point) -> Self {
+        let (tx, ty) = direction(angle);
+        // Unit normal to the takeoff direction; the centre lies along it
+        let (nx, ny) = (-ty, tx);
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let length_sq = dx * dx + dy * dy;
+        let offset = dx * nx + dy * ny;
+        if length_sq < EPSILON || offset.abs() < EPSILON * length_sq.sqrt().max(1.0) {
+            return ConnectorPath::Line { start, end };
+        }
+
+        let signed_radius = length_sq / (2.0 * offset);
+        let center = Point {
+            x: start.x + signed_radius * nx,
+            y: start.y + signed_radius * ny,
+        };
+        let start_angle = angle_between(&center, &start);
+        let end_angle = angle_between(&center, &end);
+        // Travelling counter-clockwise, the tangent is 90 degrees ahead of the
+        // radius, so the sign of their cross product gives the direction
+        let (rx, ry) = (start.x - center.x, center.y - start.y);
+        let counter_clockwise = rx * (-ty) - ry * tx > 0.0;
+        let turn = (end_angle - start_angle).rem_euclid(360.0);
+        let sweep = if counter_clockwise {
+            turn
+        } else {
+            turn - 360.0
+        };
+        ConnectorPath::Arc {
+            center,
+            radius: signed_radius.abs(),
+            start_angle,
+            sweep,
+        }
+    }
+
+    pub fn start(&self) -> Point {
+        self.point_at(0.0)
+    }
+
+    pub fn end(&self) -> Point {
+        self.point_at(1.0)
+    }
+
+    /// The point a fraction `t` (0 to 1) of the way along the path. Curves are
+    /// parameterised per segment rather than by length.
+    pub fn point_at(&self, t: f64) -> Point {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            ConnectorPath::Line { start, end } => lerp(start, end, t),
+            ConnectorPath::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep,
+            } => {
+                let (dx, dy) = direction(start_angle + t * sweep);
+                Point {
+                    x: center.x + radius * dx,
+                    y: center.y + radius * dy,
+                }
+            }
+            ConnectorPath::Curve { points } => {
+                let segments = bezier_segments(points);
+                if segments.is_empty() {
+                    return points.first().cloned().unwrap_or(Point { x: 0.0, y: 0.0 });
+                }
+                let position = t * segments.len() as f64;
+                let index = (position.floor() as usize).min(segments.len() - 1);
+                cubic(&segments[index], position - index as f64)
+            }
+        }
+    }
+
+    /// Approximates the path by straight segments, returning their ends.
+    pub fn flatten(&self) -> Vec<Point> {
+        match self {
+            ConnectorPath::Line { start, end } => vec![start.clone(), end.clone()],
+            ConnectorPath::Arc { sweep, .. } => {
+                let steps = ((sweep.abs() / 5.0).ceil() as usize).max(1);
+                (0..=steps)
+                    .map(|step| self.point_at(step as f64 / steps as f64))
+                    .collect()
+            }
+            ConnectorPath::Curve { points } => {
+                let segments = bezier_segments(points);
+                let mut flat: Vec<Point> = points.first().cloned().into_iter().collect();
+                for segment in &segments {
+                    flat.extend(
+                        (1..=CURVE_STEPS)
+                            .map(|step| cubic(segment, step as f64 / CURVE_STEPS as f64)),
+                    );
+                }
+                flat
+            }
+        }
+    }
+
+    /// The shortest distance from `point` to the path.
+    pub fn distance_to(&self, point: &Point) -> f64 {
+        match self {
+            ConnectorPath::Line { start, end } => segment_distance(point, start, end),
+            ConnectorPath::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep,
+            } => {
+                let offset = angle_between(center, point) - start_angle;
+                let along = if *sweep >= 0.0 {
+                    offset.rem_euclid(360.0)
+                } else {
+                    -(-offset).rem_euclid(360.0)
+                };
+                if distance(center, point) > EPSILON && along.abs() <= sweep.abs() {
+                    (distance(center, point) - radius).abs()
+                } else {
+                    distance(point, &self.start()).min(distance(point, &self.end()))
+                }
+            }
+            ConnectorPath::Curve { .. } => self
+                .flatten()
+                .windows(2)
+                .map(|pair| segment_distance(point, &pair[0], &pair[1]))
+                .fold(f64::INFINITY, f64::min),
+        }
+    }
+
+    /// Whether `point` is within `tolerance` of the path.
+    pub fn hit_test(&self, point: &Point, tolerance: f64) -> bool {
+        self.distance_to(point) <= tolerance
+    }
+
+    /// The path as SVG path data, with coordinates rounded to six decimals.
+    pub fn to_svg_path(&self) -> String {
+        let at = |point: &Point| format!("{} {}", svg_number(point.x), svg_number(point.y));
+        match self {
+            ConnectorPath::Line { start, end } => format!("M {} L {}", at(start), at(end)),
+            ConnectorPath::Arc { radius, sweep, .. } => {
+                let radius = svg_number(*radius);
+                // SVG sweeps clockwise on screen for a positive flag
+                let large_arc = u8::from(sweep.abs() > 180.0);
+                let sweep_flag = u8::from(*sweep < 0.0);
+                format!(
+                    "M {} A {radius} {radius} 0 {large_arc} {sweep_flag} {}",
+                    at(&self.start()),
+                    at(&self.end())
+                )
+            }
+            ConnectorPath::Curve { points } => {
+                let Some(first) = points.first() else {
+                    return String::new();
+                };
+                let mut path = format!("M {}", at(first));
+                for [_, control1, control2, end] in bezier_segments(points) {
+                    path.push_str(&format!(
+                        " C {} {} {}",
+                        at(&control1),
+                        at(&control2),
+                        at(&end)
+                    ));
+                }
+                path
+            }
+        }
+    }
+
+    /// Shortens the end of the path to where it first meets `outline`.
+    fn trim_end(self, outline: &Outline) -> Self {
+        if !outline.contains(&self.end()) || outline.contains(&self.start()) {
+            return self;
+        }
+        // Step back from the end until outside, then bisect onto the edge
+        let mut inside = 1.0;
+        let mut outside = 1.0;
+        while outside > 0.0 {
+            outside = (outside - 1.0 / 64.0_f64).max(0.0);
+            if !outline.contains(&self.point_at(outside)) {
+                break;
+            }
+            inside = outside;
+        }
+        for _ in 0..48 {
+            let middle = (inside + outside) / 2.0;
+            if outline.contains(&self.point_at(middle)) {
+                inside = middle;
+            } else {
+                outside = middle;
+            }
+        }
+        match self {
+            ConnectorPath::Line { start, end } => ConnectorPath::Line {
+                end: lerp(&start, &end, inside),
+                start,
+            },
+            ConnectorPath::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep,
+            } => ConnectorPath::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep: sweep * inside,
+            },
+            curve => curve,
+        }
+    }
+}
+
+impl ConnectorObject {
+    /// The path of this connector between the objects it joins.
+    ///
+    /// Without `from`, the connector starts at its own `x` and `y`. With two
+    /// or more points, the first and last are used as the ends; otherwise it
+    /// ends where an arc from the start meets the edge of `to`.
+    pub fn path(&self, from: Option<&Outline>, to: Option<&Outline>) -> ConnectorPath {
+        if self.pts.len() > 2 {
+            return ConnectorPath::Curve {
+                points: self.pts.clone(),
+            };
+        }
+        let start = match (self.pts.first(), from) {
+            (Some(first), _) if self.pts.len() == 2 => first.clone(),
+            (_, Some(from)) => from.anchor(self.angle),
+            _ => Point {
+                x: self.x,
+                y: self.y,
+            },
+        };
+        match (self.pts.get(1), to) {
+            (Some(end), _) => ConnectorPath::arc(start, self.angle, end.clone()),
+            (None, Some(to)) => {
+                ConnectorPath::arc(start, self.angle, to.center().clone()).trim_end(to)
+            }
+            (None, None) => ConnectorPath::Line {
+                end: start.clone(),
+                start,
+            },
+        }
+    }
+}
+
+impl StockObject {
+    /// The outline of this stock, or `None` while it has no position.
+    pub fn outline(&self) -> Option<Outline> {
+        Some(Outline::from_shape(
+            self.x?,
+            self.y?,
+            self.width,
+            self.height,
+            self.shape.as_ref(),
+        ))
+    }
+}
+
+impl FlowObject {
+    /// The outline of this flow's valve, or `None` while it has no position.
+    pub fn outline(&self) -> Option<Outline> {
+        Some(Outline::from_shape(
+            self.x?,
+            self.y?,
+            self.width,
+            self.height,
+            None,
+        ))
+    }
+}
+
+impl AuxObject {
+    /// The outline of this auxiliary, drawn as a circle unless it has a
+    /// shape, or `None` while it has no position.
+    pub fn outline(&self) -> Option<Outline> {
+        let (x, y) = (self.x?, self.y?);
+        let width = self.width.or(self.height).unwrap_or(DEFAULT_AUX_SIZE);
+        let height = self.height.unwrap_or(width);
+        Some(match &self.shape {
+            Some(shape) => Outline::from_shape(x, y, width, height, Some(shape)),
+            None => Outline::Circle {
+                center: Point { x, y },
+                radius: width.min(height) / 2.0,
+            },
+        })
+    }
+}
+
+impl ModuleObject {
+    pub fn outline(&self) -> Outline {
+        Outline::from_shape(self.x, self.y, self.width, self.height, self.shape.as_ref())
+    }
+}
+
+impl View {
+    /// The outline of the object `pointer` refers to. An alias takes its
+    /// own shape if it has one and that of the object it represents if not.
+    pub fn outline_of(&self, pointer: &Pointer) -> Option<Outline> {
+        match pointer {
+            Pointer::Name(_) => self.resolve_pointer(pointer)?.outline(),
+            Pointer::Alias(uid) => {
+                let alias = self.aliases.iter().find(|alias| alias.uid == *uid)?;
+                let original = self.resolve_alias(alias).and_then(ViewEntity::outline);
+                alias_outline(alias, original)
+            }
+        }
+    }
+
+    /// The path of `connector` between the objects it joins in this view.
+    pub fn connector_path(&self, connector: &ConnectorObject) -> ConnectorPath {
+        connector.path(
+            self.outline_of(&connector.from).as_ref(),
+            self.outline_of(&connector.to).as_ref(),
+        )
+    }
+
+    /// The connectors whose path passes within `tolerance` of `point`,
+    /// nearest first.
+    pub fn connectors_at(&self, point: &Point, tolerance: f64) -> Vec<&ConnectorObject> {
+        let mut hits: Vec<(f64, &ConnectorObject)> = self
+            .connectors
+            .iter()
+            .map(|connector| (self.connector_path(connector).distance_to(point), connector))
+            .filter(|(distance, _)| *distance <= tolerance)
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        hits.into_iter().map(|(_, connector)| connector).collect()
+    }
+}
+
+/// One end of a flow's pipe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowEnd<'a> {
+    /// The end lies on or inside the outline of this stock.
+    Stock(&'a StockObject),
+    /// The end is drawn as a cloud centred on this point.
+    Cloud(&'a Point),
+}
+
+/// Whether a cloud is where material comes from or where it goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloudKind {
+    Source,
+    Sink,
+}
+
+/// A cloud drawn at the unattached end of a flow's pipe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cloud<'a> {
+    /// The flow whose pipe the cloud ends.
+    pub flow: &'a FlowObject,
+    pub kind: CloudKind,
+    pub position: &'a Point,
+}
+
+impl FlowObject {
+    /// The centre of this flow's valve: its position, or halfway along its
+    /// pipe while it has none. Returns `None` for a flow with neither.
+    pub fn valve(&self) -> Option<Point> {
+        if let (Some(x), Some(y)) = (self.x, self.y) {
+            return Some(Point { x, y });
+        }
+        let lengths: Vec<f64> = self
+            .pts
+            .windows(2)
+            .map(|pair| distance(&pair[0], &pair[1]))
+            .collect();
+        let mut remaining = lengths.iter().sum::<f64>() / 2.0;
+        for (pair, length) in self.pts.windows(2).zip(lengths) {
+            if remaining <= length && length > EPSILON {
+                return Some(lerp(&pair[0], &pair[1], remaining / length));
+            }
+            remaining -= length;
+        }
+        self.pts.first().cloned()
+    }
+}
+
+impl View {
+    /// The stock whose outline `point` lies on or inside.
+    pub fn stock_at(&self, point: &Point) -> Option<&StockObject> {
+        self.stocks.iter().find(|stock| {
+            stock
+                .outline()
+                .is_some_and(|outline| outline.contains(point))
+        })
+    }
+
+    /// The start and end of `flow`'s pipe, or `None` if it has fewer than
+    /// two points.
+    pub fn flow_ends<'a>(&'a self, flow: &'a FlowObject) -> Option<[FlowEnd<'a>; 2]> {
+        let [first, .., last] = flow.pts.as_slice() else {
+            return None;
+        };
+        let end = |point: &'a Point| match self.stock_at(point) {
+            Some(stock) => FlowEnd::Stock(stock),
+            None => FlowEnd::Cloud(point),
+        };
+        Some([end(first), end(last)])
+    }
+
+    /// The clouds of every flow in this view, in the order of the flows,
+    /// each source before its flow's sink.
+    pub fn clouds(&self) -> Vec<Cloud<'_>> {
+        let mut clouds = Vec::new();
+        for flow in &self.flows {
+            let Some(ends) = self.flow_ends(flow) else {
+                continue;
+            };
+            for (end, kind) in ends.into_iter().zip([CloudKind::Source, CloudKind::Sink]) {
+                if let FlowEnd::Cloud(position) = end {
+                    clouds.push(Cloud {
+                        flow,
+                        kind,
+                        position,
+                    });
+                }
+            }
+        }
+        clouds
+    }
+}
+
+fn alias_outline(alias: &AliasObject, original: Option<Outline>) -> Option<Outline> {
+    let center = Point {
+        x: alias.x,
+        y: alias.y,
+    };
+    match (&alias.shape, original) {
+        (Some(shape), original) => {
+            let (width, height) = match &original {
+                Some(Outline::Rectangle { width, height, .. }) => (*width, *height),
+                Some(Outline::Circle { radius, .. }) => (radius * 2.0, radius * 2.0),
+                None => (DEFAULT_AUX_SIZE, DEFAULT_AUX_SIZE),
+            };
+            Some(Outline::from_shape(
+                alias.x,
+                alias.y,
+                width,
+                height,
+                Some(shape),
+            ))
+        }
+        (None, Some(original)) => Some(original.moved_to(center)),
+        (None, None) => None,
+    }
+}
+
+/// The unit vector in view coordinates for an XMILE angle.
+fn direction(angle: f64) -> (f64, f64) {
+    let radians = angle * PI / 180.0;
+    (radians.cos(), -radians.sin())
+}
+
+/// The XMILE angle, in [0, 360), of `to` as seen from `from`.
+pub(super) fn angle_between(from: &Point, to: &Point) -> f64 {
+    (from.y - to.y)
+        .atan2(to.x - from.x)
+        .to_degrees()
+        .rem_euclid(360.0)
+}
+
+fn distance(a: &Point, b: &Point) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+/// Rounds `value` to six decimals for output, dropping any negative zero.
+fn svg_number(value: f64) -> f64 {
+    let rounded = (value * 1e6).round() / 1e6;
+    if rounded == 0.0 { 0.0 } else { rounded }
+}
+
+pub(super) fn lerp(a: &Point, b: &Point, t: f64) -> Point {
+    Point {
+        x: a.x + t * (b.x - a.x),
+        y: a.y + t * (b.y - a.y),
+    }
+}
+
+fn segment_distance(point: &Point, start: &Point, end: &Point) -> f64 {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_sq = dx * dx + dy * dy;
+    if length_sq < EPSILON {
+        return distance(point, start);
+    }
+    let t = (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_sq).clamp(0.0, 1.0);
+    distance(point, &lerp(start, end, t))
+}
+
+/// Converts the Catmull-Rom spline through `points` to cubic Bezier segments,
+/// each given as start, two control points and end.
+fn bezier_segments(points: &[Point]) -> Vec<[Point; 4]> {
+    (0..points.len().saturating_sub(1))
+        .map(|i| {
+            let previous = &points[i.saturating_sub(1)];
+            let (start, end) = (&points[i], &points[i + 1]);
+            let next = points.get(i + 2).unwrap_or(end);
+            [
+                start.clone(),
+                Point {
+                    x: start.x + (end.x - previous.x) / 6.0,
+                    y: start.y + (end.y - previous.y) / 6.0,
+                },
+                Point {
+                    x: end.x - (next.x - start.x) / 6.0,
+                    y: end.y - (next.y - start.y) / 6.0,
+                },
+                end.clone(),
+            ]
+        })
+        .collect()
+}
+
+fn cubic([p0, p1, p2, p3]: &[Point; 4], t: f64) -> Point {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    Point {
+        x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+        y: a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Uid;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    fn assert_near(actual: &Point, expected: &Point) {
+        assert!(
+            distance(actual, expected) < 1e-6,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    fn rectangle(x: f64, y: f64, width: f64, height: f64) -> Outline {
+        Outline::Rectangle {
+            center: point(x, y),
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_anchor_on_edges() {
+        let stock = rectangle(100.0, 100.0, 40.0, 20.0);
+        assert_near(&stock.anchor(0.0), &point(120.0, 100.0));
+        assert_near(&stock.anchor(90.0), &point(100.0, 90.0));
+        assert_near(&stock.anchor(45.0), &point(110.0, 90.0));
+
+        let aux = Outline::Circle {
+            center: point(0.0, 0.0),
+            radius: 9.0,
+        };
+        assert_near(&aux.anchor(180.0), &point(-9.0, 0.0));
+        assert_near(&aux.anchor_toward(&point(0.0, 50.0)), &point(0.0, 9.0));
+    }
+
+    #[test]
+    fn test_straight_connector_ends_at_edge() {
+        let connector_path = ConnectorPath::arc(point(0.0, 0.0), 0.0, point(100.0, 0.0))
+            .trim_end(&rectangle(100.0, 0.0, 40.0, 20.0));
+        match &connector_path {
+            ConnectorPath::Line { start, end } => {
+                assert_near(start, &point(0.0, 0.0));
+                assert_near(end, &point(80.0, 0.0));
+            }
+            other => panic!("expected a line, got {other:?}"),
+        }
+        assert!(connector_path.hit_test(&point(40.0, 2.0), 3.0));
+        assert!(!connector_path.hit_test(&point(90.0, 0.0), 3.0));
+    }
+
+    #[test]
+    fn test_arc_is_tangent_to_angle() {
+        // Leaving upwards and ending to the right bows up and to the right
+        let arc = ConnectorPath::arc(point(0.0, 0.0), 90.0, point(100.0, 0.0));
+        let ConnectorPath::Arc {
+            center,
+            radius,
+            sweep,
+            ..
+        } = &arc
+        else {
+            panic!("expected an arc, got {arc:?}");
+        };
+        assert_near(center, &point(50.0, 0.0));
+        assert!((radius - 50.0).abs() < 1e-9);
+        assert!((sweep + 180.0).abs() < 1e-9, "clockwise half turn");
+        assert_near(&arc.end(), &point(100.0, 0.0));
+        assert_near(&arc.point_at(0.5), &point(50.0, -50.0));
+        assert!(arc.hit_test(&point(50.0, -49.0), 1.5));
+        assert!(!arc.hit_test(&point(50.0, 49.0), 1.5));
+        assert_eq!(arc.to_svg_path(), "M 0 0 A 50 50 0 0 1 100 0");
+    }
+
+    #[test]
+    fn test_connector_between_view_objects() {
+        let view: View = quick_xml::de::from_str(
+            r#"<view uid="1" width="800" height="600" page_width="800" page_height="600">
+                <stock uid="2" name="Population" x="100" y="100" width="40" height="20"/>
+                <aux uid="3" name="Birth_Rate" x="200" y="100" width="20" height="20"/>
+                <alias uid="4" x="200" y="200"><of>birth rate</of></alias>
+                <connector uid="5" x="0" y="0" angle="0"><from>Population</from><to>birth rate</to></connector>
+                <connector uid="6" x="0" y="0" angle="180"><from><alias uid="4"/></from><to>Population</to></connector>
+            </view>"#,
+        )
+        .unwrap();
+
+        let direct = view.connector_path(&view.connectors[0]);
+        assert_near(&direct.start(), &point(120.0, 100.0));
+        assert_near(&direct.end(), &point(190.0, 100.0));
+
+        let from_alias = view.connector_path(&view.connectors[1]);
+        assert_near(&from_alias.start(), &point(190.0, 200.0));
+        let end = from_alias.end();
+        assert!(
+            (end.x - 100.0).abs() <= 20.0 + 1e-6 && (end.y - 100.0).abs() <= 10.0 + 1e-6,
+            "ends on the stock's edge, got {end:?}"
+        );
+
+        let hits = view.connectors_at(&point(150.0, 101.0), 2.0);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].uid, Uid::new(5));
+    }
+
+    #[test]
+    fn test_flow_valves_and_clouds() {
+        let view: View = quick_xml::de::from_str(
+            r#"<view uid="1" width="800" height="600" page_width="800" page_height="600">
+                <stock uid="2" name="Population" x="100" y="100" width="40" height="20"/>
+                <flow uid="3" name="Births" x="40" y="100" width="18" height="18">
+                    <pts><pt x="0" y="100"/><pt x="80" y="100"/></pts>
+                </flow>
+                <flow uid="4" name="Deaths" width="18" height="18">
+                    <pts><pt x="120" y="100"/><pt x="200" y="100"/><pt x="200" y="140"/></pts>
+                </flow>
+            </view>"#,
+        )
+        .unwrap();
+        let [births, deaths] = view.flows.as_slice() else {
+            panic!("Expected two flows");
+        };
+        assert_eq!(births.valve(), Some(point(40.0, 100.0)));
+        assert_near(&deaths.valve().unwrap(), &point(180.0, 100.0));
+
+        let [from, to] = view.flow_ends(births).unwrap();
+        assert_eq!(from, FlowEnd::Cloud(&births.pts[0]));
+        assert!(matches!(to, FlowEnd::Stock(stock) if stock.uid == Uid::new(2)));
+
+        let clouds: Vec<(Uid, CloudKind, &Point)> = view
+            .clouds()
+            .into_iter()
+            .map(|cloud| (cloud.flow.uid, cloud.kind, cloud.position))
+            .collect();
+        assert_eq!(
+            clouds,
+            [
+                (Uid::new(3), CloudKind::Source, &point(0.0, 100.0)),
+                (Uid::new(4), CloudKind::Sink, &point(200.0, 140.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_curve_passes_through_points() {
+        let curve = ConnectorPath::Curve {
+            points: vec![point(0.0, 0.0), point(50.0, 50.0), point(100.0, 0.0)],
+        };
+        assert_near(&curve.start(), &point(0.0, 0.0));
+        assert_near(&curve.point_at(0.5), &point(50.0, 50.0));
+        assert_near(&curve.end(), &point(100.0, 0.0));
+        assert!(curve.hit_test(&point(50.0, 50.0), 1e-6));
+        assert!(curve.to_svg_path().starts_with("M 0 0 C "));
+    }
+}
@@ -0,0 +1,2022 @@
+// Interface objects: the input, output and annotation display objects.
+//
+// These objects only appear in views and are not needed to simulate a model, so
+// they are compiled only when the `interface-objects` feature is enabled.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Uid;
+use crate::model::format::NumberFormat;
+use crate::model::object::{DisplayAs, FormatOptions};
+use crate::resource::ResourceRef;
+
+use super::media::MediaSource;
+use super::objects::{TextPadding, text_padding};
+use super::style::{
+    BorderStyle, BorderWidth, Color, FontStyle, FontWeight, TextAlign, TextDecoration,
+    VerticalTextAlign,
+};
+
+// Sliders and knobs are used to change the value of a variable in the model from the interface.  Support for these tags is OPTIONAL. Stocks can only be manipulated by knobs. Iin this case, knobs can only change the stock’s initial value, i.e., knobs attached to stocks MUST NOT be changed in the middle of a simulation run.  Sliders are defined with the <slider> tag and knobs are defined with the <knob> tag; they are otherwise the same.  An example slider tag is shown below:
+
+// <slider x="172" y="114" color="black" width="197" height="43" min="7" max="9" background=”white” z_index=”1” font_family=”Arial” font_size=”9pt” font_weight=”bold” font_style=”italic” text_decoration=”underline” text_align=”center” vertical_text_align=”center” text_padding=”2px” font_color=”blue” text_border_color=”black” text_border_width=”1px” text_border_style=”solid”>
+
+//       <entity name="Converter_1" />
+
+// <reset_to after="one_time_unit">7</reset_to>
+
+// </slider>
+
+//     Show name: OPTIONAL  show_name="…" with true/false (default: true)
+//     Show number:  OPTIONAL show_number="…" with true/false; when the number is visible it MUST be directly editable (default: true)
+//     Show input range: OPTIONAL  show_min_max="…" with true/false (default: true)
+//     Input range REQUIRED:  min="…" and max="…", overriding entity’s input range setting (default:  entity’s setting)
+//     OPTIONAL reset (slider only):  <reset_to> with the value to reset the entity to; it has one attribute that define when to reset the entity’s value:  after="…" with either one_time_unit or one_dt.
+
+// Descriptions of all other display attributes of a slider or knob can be found in Section 6.1.
+
+/// Helper struct for deserializing entity tags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct EntityTag {
+    #[serde(rename = "@name")]
+    name: String,
+}
+
+/// Helper struct for deserializing reset_to tags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ResetToTag {
+    #[serde(rename = "@after")]
+    after: String,
+    #[serde(rename = "$text")]
+    value: f64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RawSliderObject {
+    #[serde(rename = "@uid")]
+    uid: Uid,
+    #[serde(rename = "@x")]
+    x: f64,
+    #[serde(rename = "@y")]
+    y: f64,
+    #[serde(rename = "@width")]
+    width: f64,
+    #[serde(rename = "@height")]
+    height: f64,
+    #[serde(rename = "@color")]
+    color: Option<Color>,
+    #[serde(rename = "@background")]
+    background: Option<Color>,
+    #[serde(rename = "@z_index")]
+    z_index: Option<i32>,
+    #[serde(rename = "@font_family")]
+    font_family: Option<String>,
+    #[serde(rename = "@font_size")]
+    font_size: Option<f64>,
+    #[serde(rename = "@font_weight")]
+    font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style")]
+    font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration")]
+    text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align")]
+    text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background")]
+    text_background: Option<Color>,
+    #[serde(rename = "@vertical_text_align")]
+    vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    text_padding: TextPadding,
+    #[serde(rename = "@font_color")]
+    font_color: Option<Color>,
+    #[serde(rename = "@text_border_color")]
+    text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width")]
+    text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style")]
+    text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@min")]
+    min: f64,
+    #[serde(rename = "@max")]
+    max: f64,
+    #[serde(rename = "@show_name", default = "default_true")]
+    show_name: bool,
+    #[serde(rename = "@show_number", default = "default_true")]
+    show_number: bool,
+    #[serde(rename = "@show_min_max", default = "default_true")]
+    show_min_max: bool,
+    #[serde(rename = "entity")]
+    entity: Option<EntityTag>,
+    #[serde(rename = "reset_to")]
+    reset_to: Option<ResetToTag>,
+}
+
+impl From<RawSliderObject> for SliderObject {
+    fn from(raw: RawSliderObject) -> Self {
+        SliderObject {
+            uid: raw.uid,
+            x: raw.x,
+            y: raw.y,
+            width: raw.width,
+            height: raw.height,
+            color: raw.color,
+            background: raw.background,
+            z_index: raw.z_index,
+            font_family: raw.font_family,
+            font_size: raw.font_size,
+            font_weight: raw.font_weight,
+            font_style: raw.font_style,
+            text_decoration: raw.text_decoration,
+            text_align: raw.text_align,
+            text_background: raw.text_background,
+            vertical_text_align: raw.vertical_text_align,
+            text_padding: raw.text_padding,
+            font_color: raw.font_color,
+            text_border_color: raw.text_border_color,
+            text_border_width: raw.text_border_width,
+            text_border_style: raw.text_border_style,
+            entity_name: raw.entity.map(|e| e.name).unwrap_or_default(),
+            min: raw.min,
+            max: raw.max,
+            show_name: raw.show_name,
+            show_number: raw.show_number,
+            show_min_max: raw.show_min_max,
+            reset_to: raw.reset_to.map(|r| (r.value, r.after)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderObject {
+    pub uid: Uid,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub z_index: Option<i32>,
+    pub font_family: Option<String>,
+    pub font_size: Option<f64>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub text_decoration: Option<TextDecoration>,
+    pub text_align: Option<TextAlign>,
+    pub text_background: Option<Color>,
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    pub text_padding: TextPadding,
+    pub font_color: Option<Color>,
+    pub text_border_color: Option<Color>,
+    pub text_border_width: Option<BorderWidth>,
+    pub text_border_style: Option<BorderStyle>,
+    pub entity_name: String,
+    pub min: f64,
+    pub max: f64,
+    pub show_name: bool,
+    pub show_number: bool,
+    pub show_min_max: bool,
+    pub reset_to: Option<(f64, String)>, // (value, after)
+}
+
+impl<'de> serde::Deserialize<'de> for SliderObject {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = RawSliderObject::deserialize(deserializer)?;
+        Ok(SliderObject::from(raw))
+    }
+}
+
+impl serde::Serialize for SliderObject {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("slider", 25)?;
+
+        state.serialize_field("@uid", &self.uid.value)?;
+        state.serialize_field("@x", &self.x)?;
+        state.serialize_field("@y", &self.y)?;
+        state.serialize_field("@width", &self.width)?;
+        state.serialize_field("@height", &self.height)?;
+        if let Some(color) = &self.color {
+            state.serialize_field("@color", color)?;
+        }
+        if let Some(background) = &self.background {
+            state.serialize_field("@background", background)?;
+        }
+        if let Some(z_index) = &self.z_index {
+            state.serialize_field("@z_index", z_index)?;
+        }
+        if let Some(font_family) = &self.font_family {
+            state.serialize_field("@font_family", font_family)?;
+        }
+        if let Some(font_size) = &self.font_size {
+            state.serialize_field("@font_size", font_size)?;
+        }
+        if let Some(font_weight) = &self.font_weight {
+            state.serialize_field("@font_weight", font_weight)?;
+        }
+        if let Some(font_style) = &self.font_style {
+            state.serialize_field("@font_style", font_style)?;
+        }
+        if let Some(text_decoration) = &self.text_decoration {
+            state.serialize_field("@text_decoration", text_decoration)?;
+        }
+        if let Some(text_align) = &self.text_align {
+            state.serialize_field("@text_align", text_align)?;
+        }
+        if let Some(text_background) = &self.text_background {
+            state.serialize_field("@text_background", text_background)?;
+        }
+        if let Some(vertical_text_align) = &self.vertical_text_align {
+            state.serialize_field("@vertical_text_align", vertical_text_align)?;
+        }
+        if self.text_padding.is_some() {
+            state.serialize_field("@text_padding", &text_padding::format(&self.text_padding))?;
+        }
+        if let Some(font_color) = &self.font_color {
+            state.serialize_field("@font_color", font_color)?;
+        }
+        if let Some(text_border_color) = &self.text_border_color {
+            state.serialize_field("@text_border_color", text_border_color)?;
+        }
+        if let Some(text_border_width) = &self.text_border_width {
+            state.serialize_field("@text_border_width", text_border_width)?;
+        }
+        if let Some(text_border_style) = &self.text_border_style {
+            state.serialize_field("@text_border_style", text_border_style)?;
+        }
+        state.serialize_field("@min", &self.min)?;
+        state.serialize_field("@max", &self.max)?;
+        if !self.show_name {
+            state.serialize_field("@show_name", &self.show_name)?;
+        }
+        if !self.show_number {
+            state.serialize_field("@show_number", &self.show_number)?;
+        }
+        if !self.show_min_max {
+            state.serialize_field("@show_min_max", &self.show_min_max)?;
+        }
+
+        // Serialize entity tag
+        state.serialize_field(
+            "entity",
+            &EntityTag {
+                name: self.entity_name.clone(),
+            },
+        )?;
+
+        // Serialize reset_to if present
+        if let Some((value, after)) = &self.reset_to {
+            state.serialize_field(
+                "reset_to",
+                &ResetToTag {
+                    after: after.clone(),
+                    value: *value,
+                },
+            )?;
+        }
+
+        state.end()
+    }
+}
+
+// Knobs are the same as sliders but for stocks
+pub type KnobObject = SliderObject;
+
+// Switches and Radio Buttons (Option Groups)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwitchObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@label_side", skip_serializing_if = "Option::is_none")]
+    pub label_side: Option<String>,
+    #[serde(rename = "@label_angle", skip_serializing_if = "Option::is_none")]
+    pub label_angle: Option<f64>,
+    #[serde(rename = "@show_name")]
+    pub show_name: bool,
+    #[serde(rename = "@switch_style")]
+    pub switch_style: SwitchStyle,
+    #[serde(rename = "@clicking_sound")]
+    pub clicking_sound: bool,
+    #[serde(rename = "@entity_name", skip_serializing_if = "Option::is_none")]
+    pub entity_name: Option<String>,
+    #[serde(rename = "@entity_value", skip_serializing_if = "Option::is_none")]
+    pub entity_value: Option<f64>,
+    #[serde(rename = "@group_name", skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    #[serde(rename = "@module_name", skip_serializing_if = "Option::is_none")]
+    pub module_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_to: Option<(f64, String)>, // (value, after) - handled via custom deserialization if needed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwitchStyle {
+    Toggle,
+    PushButton,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionsObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@layout")]
+    pub layout: OptionsLayout,
+    #[serde(rename = "@horizontal_spacing")]
+    pub horizontal_spacing: f64,
+    #[serde(rename = "@vertical_spacing")]
+    pub vertical_spacing: f64,
+    #[serde(rename = "entity", default)]
+    pub entities: Vec<OptionEntity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptionsLayout {
+    Vertical,
+    Horizontal,
+    Grid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionEntity {
+    #[serde(rename = "@name")]
+    pub entity_name: String,
+    #[serde(rename = "@index", skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    #[serde(rename = "$text")]
+    pub value: f64,
+}
+
+// Numeric Inputs and List Input Devices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericInputObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@entity_name")]
+    pub entity_name: String,
+    #[serde(rename = "@entity_index", skip_serializing_if = "Option::is_none")]
+    pub entity_index: Option<String>,
+    #[serde(rename = "@min")]
+    pub min: f64,
+    #[serde(rename = "@max")]
+    pub max: f64,
+    #[serde(rename = "@precision", skip_serializing_if = "Option::is_none")]
+    pub precision: Option<f64>,
+    #[serde(rename = "@value")]
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListInputObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(rename = "@column_width")]
+    pub column_width: f64,
+    #[serde(rename = "numeric_input", default)]
+    pub numeric_inputs: Vec<NumericInputObject>,
+}
+
+// Graphical Inputs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicalInputObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@entity_name")]
+    pub entity_name: String,
+    #[serde(rename = "gf", skip_serializing_if = "Option::is_none")]
+    pub graphical_function: Option<GraphicalFunctionData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicalFunctionData {
+    pub xscale_min: f64,
+    pub xscale_max: f64,
+    pub ypts: Vec<f64>,
+}
+
+// Numeric Displays
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericDisplayObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@entity_name")]
+    pub entity_name: String,
+    #[serde(rename = "@show_name", default = "default_true")]
+    pub show_name: bool,
+    #[serde(rename = "@retain_ending_value", default = "default_true")]
+    pub retain_ending_value: bool,
+    #[serde(rename = "@precision", skip_serializing_if = "Option::is_none")]
+    pub precision: Option<f64>,
+    #[serde(rename = "@scale_by", skip_serializing_if = "Option::is_none")]
+    pub scale_by: Option<f64>,
+    #[serde(rename = "@display_as", skip_serializing_if = "Option::is_none")]
+    pub display_as: Option<DisplayAs>,
+    #[serde(rename = "@delimit_000s", skip_serializing_if = "Option::is_none")]
+    pub delimit_000s: Option<bool>,
+}
+
+impl NumericDisplayObject {
+    /// Returns the format attributes set on this display.
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            precision: self.precision,
+            scale_by: self.scale_by,
+            display_as: self.display_as,
+            delimit_000s: self.delimit_000s,
+        }
+    }
+
+    /// Resolves the display's number format, falling back to the shown
+    /// entity's `<format>` for attributes the display does not override.
+    pub fn number_format(&self, entity: Option<&FormatOptions>) -> NumberFormat {
+        match entity {
+            Some(entity) => self.format_options().or(entity).number_format(),
+            None => self.format_options().number_format(),
+        }
+    }
+}
+
+// Lamps and Gauges
+// Lamps and gauges indicate the current value of a single variable, colored by
+// the zone the value falls in. Zones appear in a REQUIRED <zones> tag:
+//   <lamp retain_ending_value="true" x="543" y="104" uid="3">
+//       <entity name="Converter_1" />
+//       <zones>
+//           <zone type="panic" min="8" max="8.33" color="red" />
+//           <zone type="caution" min="8.33" max="8.66" color="yellow" />
+//           <zone type="normal" min="8.66" max="9" color="green"/>
+//       </zones>
+//   </lamp>
+//     Show name: OPTIONAL show_name="" with true/false (default: false for lamps, true for gauges)
+//     Show number (gauges only): OPTIONAL show_number="" with true/false (default: true)
+//     Retain ending value: OPTIONAL retain_ending_value="" with true/false (default: true)
+//     Flash when in the panic region: OPTIONAL flash_on_panic="" with true/false (default: true)
+
+/// Helper struct for deserializing zones tags
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ZonesTag {
+    #[serde(rename = "zone", default)]
+    zones: Vec<Zone>,
+}
+
+/// Helper struct for serializing zones tags
+#[derive(Serialize)]
+struct ZonesRef<'a> {
+    #[serde(rename = "zone")]
+    zones: &'a [Zone],
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RawLampObject {
+    #[serde(rename = "@uid")]
+    uid: Uid,
+    #[serde(rename = "@x")]
+    x: f64,
+    #[serde(rename = "@y")]
+    y: f64,
+    #[serde(rename = "@width")]
+    width: f64,
+    #[serde(rename = "@height")]
+    height: f64,
+    #[serde(rename = "@color")]
+    color: Option<Color>,
+    #[serde(rename = "@background")]
+    background: Option<Color>,
+    #[serde(rename = "@z_index")]
+    z_index: Option<i32>,
+    #[serde(rename = "@font_family")]
+    font_family: Option<String>,
+    #[serde(rename = "@font_size")]
+    font_size: Option<f64>,
+    #[serde(rename = "@font_weight")]
+    font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style")]
+    font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration")]
+    text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align")]
+    text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background")]
+    text_background: Option<Color>,
+    #[serde(rename = "@vertical_text_align")]
+    vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    text_padding: TextPadding,
+    #[serde(rename = "@font_color")]
+    font_color: Option<Color>,
+    #[serde(rename = "@text_border_color")]
+    text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width")]
+    text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style")]
+    text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@show_name")]
+    show_name: Option<bool>,
+    #[serde(rename = "@show_number", default = "default_true")]
+    show_number: bool,
+    #[serde(rename = "@retain_ending_value", default = "default_true")]
+    retain_ending_value: bool,
+    #[serde(rename = "@flash_on_panic", default = "default_true")]
+    flash_on_panic: bool,
+    #[serde(rename = "entity")]
+    entity: Option<EntityTag>,
+    #[serde(rename = "zones")]
+    zones: Option<ZonesTag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LampObject {
+    pub uid: Uid,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub z_index: Option<i32>,
+    pub font_family: Option<String>,
+    pub font_size: Option<f64>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub text_decoration: Option<TextDecoration>,
+    pub text_align: Option<TextAlign>,
+    pub text_background: Option<Color>,
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    pub text_padding: TextPadding,
+    pub font_color: Option<Color>,
+    pub text_border_color: Option<Color>,
+    pub text_border_width: Option<BorderWidth>,
+    pub text_border_style: Option<BorderStyle>,
+    pub entity_name: String,
+    pub show_name: bool,
+    pub retain_ending_value: bool,
+    pub flash_on_panic: bool,
+    pub zones: Vec<Zone>,
+}
+
+impl LampObject {
+    /// Returns the zone `value` falls in, if any.
+    pub fn zone_for(&self, value: f64) -> Option<&Zone> {
+        Zone::find(&self.zones, value)
+    }
+}
+
+impl From<RawLampObject> for LampObject {
+    fn from(raw: RawLampObject) -> Self {
+        LampObject {
+            uid: raw.uid,
+            x: raw.x,
+            y: raw.y,
+            width: raw.width,
+            height: raw.height,
+            color: raw.color,
+            background: raw.background,
+            z_index: raw.z_index,
+            font_family: raw.font_family,
+            font_size: raw.font_size,
+            font_weight: raw.font_weight,
+            font_style: raw.font_style,
+            text_decoration: raw.text_decoration,
+            text_align: raw.text_align,
+            text_background: raw.text_background,
+            vertical_text_align: raw.vertical_text_align,
+            text_padding: raw.text_padding,
+            font_color: raw.font_color,
+            text_border_color: raw.text_border_color,
+            text_border_width: raw.text_border_width,
+            text_border_style: raw.text_border_style,
+            entity_name: raw.entity.map(|e| e.name).unwrap_or_default(),
+            show_name: raw.show_name.unwrap_or(false),
+            retain_ending_value: raw.retain_ending_value,
+            flash_on_panic: raw.flash_on_panic,
+            zones: raw.zones.map(|z| z.zones).unwrap_or_default(),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LampObject {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = RawLampObject::deserialize(deserializer)?;
+        Ok(LampObject::from(raw))
+    }
+}
+
+impl serde::Serialize for LampObject {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("lamp", 26)?;
+
+        serialize_meter_display(
+            &mut state,
+            &MeterDisplay {
+                uid: self.uid,
+                x: self.x,
+                y: self.y,
+                width: self.width,
+                height: self.height,
+                color: &self.color,
+                background: &self.background,
+                z_index: &self.z_index,
+                font_family: &self.font_family,
+                font_size: &self.font_size,
+                font_weight: &self.font_weight,
+                font_style: &self.font_style,
+                text_decoration: &self.text_decoration,
+                text_align: &self.text_align,
+                text_background: &self.text_background,
+                vertical_text_align: &self.vertical_text_align,
+                text_padding: &self.text_padding,
+                font_color: &self.font_color,
+                text_border_color: &self.text_border_color,
+                text_border_width: &self.text_border_width,
+                text_border_style: &self.text_border_style,
+            },
+        )?;
+        if self.show_name {
+            state.serialize_field("@show_name", &self.show_name)?;
+        }
+        if !self.retain_ending_value {
+            state.serialize_field("@retain_ending_value", &self.retain_ending_value)?;
+        }
+        if !self.flash_on_panic {
+            state.serialize_field("@flash_on_panic", &self.flash_on_panic)?;
+        }
+        serialize_meter_content(&mut state, &self.entity_name, &self.zones)?;
+
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaugeObject {
+    pub uid: Uid,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub z_index: Option<i32>,
+    pub font_family: Option<String>,
+    pub font_size: Option<f64>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub text_decoration: Option<TextDecoration>,
+    pub text_align: Option<TextAlign>,
+    pub text_background: Option<Color>,
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    pub text_padding: TextPadding,
+    pub font_color: Option<Color>,
+    pub text_border_color: Option<Color>,
+    pub text_border_width: Option<BorderWidth>,
+    pub text_border_style: Option<BorderStyle>,
+    pub entity_name: String,
+    pub show_name: bool,
+    pub show_number: bool,
+    pub retain_ending_value: bool,
+    pub flash_on_panic: bool,
+    pub zones: Vec<Zone>,
+}
+
+impl GaugeObject {
+    /// Returns the zone `value` falls in, if any.
+    ///
+    /// UI hosts can use the zone's color and type to render the current
+    /// value according to the thresholds set by the model author.
+    pub fn zone_for(&self, value: f64) -> Option<&Zone> {
+        Zone::find(&self.zones, value)
+    }
+}
+
+impl From<RawLampObject> for GaugeObject {
+    fn from(raw: RawLampObject) -> Self {
+        GaugeObject {
+            uid: raw.uid,
+            x: raw.x,
+            y: raw.y,
+            width: raw.width,
+            height: raw.height,
+            color: raw.color,
+            background: raw.background,
+            z_index: raw.z_index,
+            font_family: raw.font_family,
+            font_size: raw.font_size,
+            font_weight: raw.font_weight,
+            font_style: raw.font_style,
+            text_decoration: raw.text_decoration,
+            text_align: raw.text_align,
+            text_background: raw.text_background,
+            vertical_text_align: raw.vertical_text_align,
+            text_padding: raw.text_padding,
+            font_color: raw.font_color,
+            text_border_color: raw.text_border_color,
+            text_border_width: raw.text_border_width,
+            text_border_style: raw.text_border_style,
+            entity_name: raw.entity.map(|e| e.name).unwrap_or_default(),
+            show_name: raw.show_name.unwrap_or(true),
+            show_number: raw.show_number,
+            retain_ending_value: raw.retain_ending_value,
+            flash_on_panic: raw.flash_on_panic,
+            zones: raw.zones.map(|z| z.zones).unwrap_or_default(),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for GaugeObject {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = RawLampObject::deserialize(deserializer)?;
+        Ok(GaugeObject::from(raw))
+    }
+}
+
+impl serde::Serialize for GaugeObject {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("gauge", 27)?;
+
+        serialize_meter_display(
+            &mut state,
+            &MeterDisplay {
+                uid: self.uid,
+                x: self.x,
+                y: self.y,
+                width: self.width,
+                height: self.height,
+                color: &self.color,
+                background: &self.background,
+                z_index: &self.z_index,
+                font_family: &self.font_family,
+                font_size: &self.font_size,
+                font_weight: &self.font_weight,
+                font_style: &self.font_style,
+                text_decoration: &self.text_decoration,
+                text_align: &self.text_align,
+                text_background: &self.text_background,
+                vertical_text_align: &self.vertical_text_align,
+                text_padding: &self.text_padding,
+                font_color: &self.font_color,
+                text_border_color: &self.text_border_color,
+                text_border_width: &self.text_border_width,
+                text_border_style: &self.text_border_style,
+            },
+        )?;
+        if !self.show_name {
+            state.serialize_field("@show_name", &self.show_name)?;
+        }
+        if !self.show_number {
+            state.serialize_field("@show_number", &self.show_number)?;
+        }
+        if !self.retain_ending_value {
+            state.serialize_field("@retain_ending_value", &self.retain_ending_value)?;
+        }
+        if !self.flash_on_panic {
+            state.serialize_field("@flash_on_panic", &self.flash_on_panic)?;
+        }
+        serialize_meter_content(&mut state, &self.entity_name, &self.zones)?;
+
+        state.end()
+    }
+}
+
+/// Display attributes shared by lamps and gauges, borrowed for serialization.
+struct MeterDisplay<'a> {
+    uid: Uid,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    color: &'a Option<Color>,
+    background: &'a Option<Color>,
+    z_index: &'a Option<i32>,
+    font_family: &'a Option<String>,
+    font_size: &'a Option<f64>,
+    font_weight: &'a Option<FontWeight>,
+    font_style: &'a Option<FontStyle>,
+    text_decoration: &'a Option<TextDecoration>,
+    text_align: &'a Option<TextAlign>,
+    text_background: &'a Option<Color>,
+    vertical_text_align: &'a Option<VerticalTextAlign>,
+    text_padding: &'a TextPadding,
+    font_color: &'a Option<Color>,
+    text_border_color: &'a Option<Color>,
+    text_border_width: &'a Option<BorderWidth>,
+    text_border_style: &'a Option<BorderStyle>,
+}
+
+fn serialize_meter_display<S: serde::ser::SerializeStruct>(
+    state: &mut S,
+    display: &MeterDisplay<'_>,
+) -> Result<(), S::Error> {
+    state.serialize_field("@uid", &display.uid.value)?;
+    state.serialize_field("@x", &display.x)?;
+    state.serialize_field("@y", &display.y)?;
+    state.serialize_field("@width", &display.width)?;
+    state.serialize_field("@height", &display.height)?;
+    if let Some(color) = display.color {
+        state.serialize_field("@color", color)?;
+    }
+    if let Some(background) = display.background {
+        state.serialize_field("@background", background)?;
+    }
+    if let Some(z_index) = display.z_index {
+        state.serialize_field("@z_index", z_index)?;
+    }
+    if let Some(font_family) = display.font_family {
+        state.serialize_field("@font_family", font_family)?;
+    }
+    if let Some(font_size) = display.font_size {
+        state.serialize_field("@font_size", font_size)?;
+    }
+    if let Some(font_weight) = display.font_weight {
+        state.serialize_field("@font_weight", font_weight)?;
+    }
+    if let Some(font_style) = display.font_style {
+        state.serialize_field("@font_style", font_style)?;
+    }
+    if let Some(text_decoration) = display.text_decoration {
+        state.serialize_field("@text_decoration", text_decoration)?;
+    }
+    if let Some(text_align) = display.text_align {
+        state.serialize_field("@text_align", text_align)?;
+    }
+    if let Some(text_background) = display.text_background {
+        state.serialize_field("@text_background", text_background)?;
+    }
+    if let Some(vertical_text_align) = display.vertical_text_align {
+        state.serialize_field("@vertical_text_align", vertical_text_align)?;
+    }
+    if display.text_padding.is_some() {
+        state.serialize_field("@text_padding", &text_padding::format(display.text_padding))?;
+    }
+    if let Some(font_color) = display.font_color {
+        state.serialize_field("@font_color", font_color)?;
+    }
+    if let Some(text_border_color) = display.text_border_color {
+        state.serialize_field("@text_border_color", text_border_color)?;
+    }
+    if let Some(text_border_width) = display.text_border_width {
+        state.serialize_field("@text_border_width", text_border_width)?;
+    }
+    if let Some(text_border_style) = display.text_border_style {
+        state.serialize_field("@text_border_style", text_border_style)?;
+    }
+    Ok(())
+}
+
+fn serialize_meter_content<S: serde::ser::SerializeStruct>(
+    state: &mut S,
+    entity_name: &str,
+    zones: &[Zone],
+) -> Result<(), S::Error> {
+    state.serialize_field(
+        "entity",
+        &EntityTag {
+            name: entity_name.to_string(),
+        },
+    )?;
+    state.serialize_field("zones", &ZonesRef { zones })
+}
+
+/// A value range of a lamp or gauge, drawn in its own color.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    #[serde(rename = "@type", default)]
+    pub zone_type: ZoneType,
+    #[serde(rename = "@color")]
+    pub color: Color,
+    #[serde(rename = "@min")]
+    pub min: f64,
+    #[serde(rename = "@max")]
+    pub max: f64,
+    /// Sound to play while the value is in range.
+    #[serde(rename = "@sound", skip_serializing_if = "Option::is_none")]
+    pub sound: Option<ResourceRef>,
+}
+
+impl Zone {
+    /// Returns true if `value` lies within the zone.
+    ///
+    /// Zones include both ends of their range, so a value on the boundary
+    /// between adjacent zones lies in both.
+    pub fn contains(&self, value: f64) -> bool {
+        let (low, high) = if self.min <= self.max {
+            (self.min, self.max)
+        } else {
+            (self.max, self.min)
+        };
+        low <= value && value <= high
+    }
+
+    /// Returns the zone `value` falls in.
+    ///
+    /// A value on the boundary between two adjacent zones belongs to the
+    /// zone starting there, so that `[8, 8.33]` and `[8.33, 8.66]` place
+    /// 8.33 in the second zone. NaN falls in no zone.
+    pub fn find(zones: &[Zone], value: f64) -> Option<&Zone> {
+        zones
+            .iter()
+            .filter(|zone| zone.contains(value))
+            .max_by(|a, b| a.min.min(a.max).total_cmp(&b.min.min(b.max)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZoneType {
+    #[default]
+    Normal,
+    Caution,
+    Panic,
+}
+
+// Graphs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@graph_type")]
+    pub graph_type: GraphType,
+    #[serde(rename = "@title", skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(rename = "@doc", skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(rename = "@show_grid")]
+    pub show_grid: bool,
+    #[serde(rename = "@num_x_grid_lines")]
+    pub num_x_grid_lines: u32,
+    #[serde(rename = "@num_y_grid_lines")]
+    pub num_y_grid_lines: u32,
+    #[serde(rename = "@num_x_labels")]
+    pub num_x_labels: u32,
+    #[serde(rename = "@num_y_labels")]
+    pub num_y_labels: u32,
+    #[serde(rename = "@x_axis_title", skip_serializing_if = "Option::is_none")]
+    pub x_axis_title: Option<String>,
+    #[serde(rename = "@right_axis_title", skip_serializing_if = "Option::is_none")]
+    pub right_axis_title: Option<String>,
+    #[serde(rename = "@right_axis_auto_scale")]
+    pub right_axis_auto_scale: bool,
+    #[serde(rename = "@right_axis_multi_scale")]
+    pub right_axis_multi_scale: bool,
+    #[serde(rename = "@left_axis_title", skip_serializing_if = "Option::is_none")]
+    pub left_axis_title: Option<String>,
+    #[serde(rename = "@left_axis_auto_scale")]
+    pub left_axis_auto_scale: bool,
+    #[serde(rename = "@left_axis_multi_scale")]
+    pub left_axis_multi_scale: bool,
+    #[serde(rename = "@plot_numbers")]
+    pub plot_numbers: bool,
+    #[serde(rename = "@comparative")]
+    pub comparative: bool,
+    #[serde(rename = "@from", skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    #[serde(rename = "@to", skip_serializing_if = "Option::is_none")]
+    pub to: Option<f64>,
+    #[serde(rename = "plot", default)]
+    pub plots: Vec<Plot>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphType {
+    TimeSeries,
+    Scatter,
+    Bar,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plot {
+    #[serde(rename = "@index")]
+    pub index: u32,
+    #[serde(rename = "@pen_width")]
+    pub pen_width: f64,
+    #[serde(rename = "@pen_style")]
+    pub pen_style: PenStyle,
+    #[serde(rename = "@show_y_axis")]
+    pub show_y_axis: bool,
+    #[serde(rename = "@title")]
+    pub title: String,
+    #[serde(rename = "@right_axis")]
+    pub right_axis: bool,
+    #[serde(rename = "@entity_name")]
+    pub entity_name: String,
+    #[serde(rename = "@precision", skip_serializing_if = "Option::is_none")]
+    pub precision: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<PlotScale>,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PenStyle {
+    Solid,
+    Dotted,
+    Dashed,
+    DotDashed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotScale {
+    #[serde(rename = "@min")]
+    pub min: f64,
+    #[serde(rename = "@max")]
+    pub max: f64,
+}
+
+// Tables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@title", skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(rename = "@doc", skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(rename = "@orientation")]
+    pub orientation: TableOrientation,
+    #[serde(rename = "@column_width")]
+    pub column_width: f64,
+    #[serde(
+        rename = "@blank_column_width",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub blank_column_width: Option<f64>,
+    #[serde(rename = "@interval")]
+    pub interval: String,
+    #[serde(rename = "@report_balances")]
+    pub report_balances: ReportBalances,
+    #[serde(rename = "@report_flows")]
+    pub report_flows: ReportFlows,
+    #[serde(rename = "@comparative")]
+    pub comparative: bool,
+    #[serde(rename = "@wrap_text")]
+    pub wrap_text: bool,
+    #[serde(rename = "item", default)]
+    pub items: Vec<TableItem>,
+    // Header style attributes (prefixed with "header_")
+    #[serde(
+        rename = "@header_font_family",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_font_family: Option<String>,
+    #[serde(rename = "@header_font_size", skip_serializing_if = "Option::is_none")]
+    pub header_font_size: Option<f64>,
+    #[serde(
+        rename = "@header_font_weight",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_font_weight: Option<FontWeight>,
+    #[serde(rename = "@header_font_style", skip_serializing_if = "Option::is_none")]
+    pub header_font_style: Option<FontStyle>,
+    #[serde(
+        rename = "@header_text_decoration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@header_text_align", skip_serializing_if = "Option::is_none")]
+    pub header_text_align: Option<TextAlign>,
+    #[serde(
+        rename = "@header_vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@header_text_background",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_text_background: Option<Color>,
+    #[serde(
+        rename = "@header_text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_text_padding: TextPadding,
+    #[serde(rename = "@header_font_color", skip_serializing_if = "Option::is_none")]
+    pub header_font_color: Option<Color>,
+    #[serde(
+        rename = "@header_text_border_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_text_border_color: Option<Color>,
+    #[serde(
+        rename = "@header_text_border_width",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_text_border_width: Option<BorderWidth>,
+    #[serde(
+        rename = "@header_text_border_style",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub header_text_border_style: Option<BorderStyle>,
+}
+
+impl TableObject {
+    /// Resolves the number format of each item, in item order.
+    ///
+    /// `entity_format` looks up the `<format>` of the entity an item shows,
+    /// so items without their own format attributes inherit it.
+    pub fn number_formats<F>(&self, entity_format: F) -> Vec<NumberFormat>
+    where
+        F: Fn(&str) -> Option<FormatOptions>,
+    {
+        self.items
+            .iter()
+            .map(|item| {
+                let entity = item.entity_name.as_deref().and_then(&entity_format);
+                item.number_format(entity.as_ref())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableOrientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportBalances {
+    Beginning,
+    Ending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFlows {
+    Instantaneous,
+    Summed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableItem {
+    #[serde(rename = "@type")]
+    pub item_type: TableItemType,
+    #[serde(rename = "@entity_name", skip_serializing_if = "Option::is_none")]
+    pub entity_name: Option<String>,
+    #[serde(rename = "@precision", skip_serializing_if = "Option::is_none")]
+    pub precision: Option<f64>,
+    #[serde(rename = "@scale_by", skip_serializing_if = "Option::is_none")]
+    pub scale_by: Option<f64>,
+    #[serde(rename = "@display_as", skip_serializing_if = "Option::is_none")]
+    pub display_as: Option<DisplayAs>,
+    #[serde(rename = "@delimit_000s", skip_serializing_if = "Option::is_none")]
+    pub delimit_000s: Option<bool>,
+    #[serde(rename = "@column_width", skip_serializing_if = "Option::is_none")]
+    pub column_width: Option<f64>,
+}
+
+impl TableItem {
+    /// Returns the format attributes set on this item.
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            precision: self.precision,
+            scale_by: self.scale_by,
+            display_as: self.display_as,
+            delimit_000s: self.delimit_000s,
+        }
+    }
+
+    /// Resolves the item's number format, falling back to the shown
+    /// entity's `<format>` for attributes the item does not override.
+    pub fn number_format(&self, entity: Option<&FormatOptions>) -> NumberFormat {
+        match entity {
+            Some(entity) => self.format_options().or(entity).number_format(),
+            None => self.format_options().number_format(),
+        }
+    }
+
+    /// Formats a value of this item for display in a table cell.
+    pub fn format(&self, value: f64, entity: Option<&FormatOptions>) -> String {
+        self.number_format(entity).format(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableItemType {
+    Time,
+    Variable,
+    Blank,
+}
+
+// Text Boxes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextBoxObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@appearance")]
+    pub appearance: TextBoxAppearance,
+    #[serde(rename = "$text")]
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextBoxAppearance {
+    Transparent,
+    Normal,
+}
+
+// Graphics Frames
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicsFrameObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@border_color", skip_serializing_if = "Option::is_none")]
+    pub border_color: Option<Color>,
+    #[serde(rename = "@border_style", skip_serializing_if = "Option::is_none")]
+    pub border_style: Option<BorderStyle>,
+    #[serde(rename = "@border_width", skip_serializing_if = "Option::is_none")]
+    pub border_width: Option<BorderWidth>,
+    #[serde(rename = "$value")]
+    pub content: GraphicsFrameContent,
+}
+
+/// The content of a graphics frame: either an `<image>` or a `<video>` tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsFrameContent {
+    Image(ImageContent),
+    Video(VideoContent),
+}
+
+/// An `<image>` tag, holding either a reference to an image file or the
+/// image embedded as a Data URI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageContent {
+    #[serde(rename = "@size_to_parent", default)]
+    pub size_to_parent: bool,
+    #[serde(rename = "@width", skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(rename = "@height", skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(rename = "$text")]
+    pub source: MediaSource,
+}
+
+/// A `<video>` tag, referring to a video file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoContent {
+    #[serde(rename = "@size_to_parent", default)]
+    pub size_to_parent: bool,
+    #[serde(rename = "@width", skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(rename = "@height", skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(rename = "$text")]
+    pub resource: ResourceRef,
+}
+
+// Buttons
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ButtonObject {
+    #[serde(rename = "@uid")]
+    pub uid: Uid,
+    #[serde(rename = "@x")]
+    pub x: f64,
+    #[serde(rename = "@y")]
+    pub y: f64,
+    #[serde(rename = "@width")]
+    pub width: f64,
+    #[serde(rename = "@height")]
+    pub height: f64,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
+    pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
+    pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
+    pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
+    pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@appearance")]
+    pub appearance: ButtonAppearance,
+    #[serde(rename = "@style")]
+    pub style: ButtonStyle,
+    #[serde(rename = "@label", skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageContent>,
+    #[serde(rename = "@clicking_sound")]
+    pub clicking_sound: bool,
+    #[serde(rename = "@sound", skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub popup: Option<PopupContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<Link>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub menu_action: Option<MenuAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub switch_action: Option<SwitchAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ButtonAppearance {
+    Opaque,
+    Transparent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ButtonStyle {
+    Square,
+    Rounded,
+    Capsule,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PopupContent {
+    TextBox(Box<TextBoxObject>),
+    Image(ImageContent),
+    Video(VideoContent),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect: Option<LinkEffect>,
+    pub to_black: bool,
+    pub target: LinkTarget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkEffect {
+    Dissolve,
+    Checkerboard,
+    Bars,
+    WipeLeft,
+    WipeRight,
+    WipeTop,
+    WipeBottom,
+    WipeClockwise,
+    WipeCounterclockwise,
+    IrisIn,
+    IrisOut,
+    DoorsClose,
+    DoorsOpen,
+    VenetianLeft,
+    VenetianRight,
+    VenetianTop,
+    VenetianBottom,
+    PushBottom,
+    PushTop,
+    PushLeft,
+    PushRight,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LinkTarget {
+    View {
+        view_type: String,
+        order: String,
+    },
+    Page {
+        view_type: String,
+        order: String,
+        page: String,
+    },
+    NextPage,
+    PreviousPage,
+    HomePage,
+    NextView,
+    PreviousView,
+    HomeView,
+    BackPage,
+    BackView,
+    Url(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MenuAction {
+    File(FileAction),
+    Printing(PrintingAction),
+    Simulation(SimulationAction),
+    Restore(RestoreAction),
+    Data(DataAction),
+    Miscellaneous(MiscellaneousAction),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileAction {
+    Open,
+    Close,
+    Save,
+    SaveAs,
+    SaveAsImage,
+    Revert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrintingAction {
+    PrintSetup,
+    Print,
+    PrintScreen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimulationAction {
+    Run,
+    Pause,
+    Resume,
+    Stop,
+    RunRestore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RestoreAction {
+    RestoreAll,
+    RestoreSliders,
+    RestoreKnobs,
+    RestoreListInputs,
+    RestoreGraphicalInputs,
+    RestoreSwitches,
+    RestoreNumericDisplays,
+    RestoreGraphsTables,
+    RestoreLampsGauges,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DataAction {
+    DataManager,
+    SaveDataNow {
+        run_name: String,
+    },
+    ImportNow {
+        resource: ResourceRef,
+        worksheet: Option<String>,
+        all: bool,
+    },
+    ExportNow {
+        resource: ResourceRef,
+        worksheet: Option<String>,
+        all: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MiscellaneousAction {
+    Exit,
+    Find,
+    RunSpecs,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwitchAction {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_name: Option<String>,
+    pub value: f64,
+}
@@ -0,0 +1,262 @@
+//! Alignment, distribution and grid snapping of display objects.
+//!
+//! These are the layout commands of diagram editors. They are built on the
+//! [editing operations](super::edit), so pipes, connectors and groups follow
+//! the objects they lay out. Objects are laid out by their bounds: the
+//! outline of a stock, auxiliary, module or alias, the valve and pipe of a
+//! flow, and the items of a group.
+//!
+//! Label sizes are estimated from the font size alone, as the crate has no
+//! font metrics: [`label_size`] allows an average character width of
+//! [`AVERAGE_CHAR_WIDTH`] times the font size.
+
+use std::collections::HashSet;
+
+use super::View;
+use super::edit::{EditError, Target};
+use super::entity::ViewEntity;
+use super::geometry::Bounds;
+use crate::Uid;
+
+/// The width of an average character, as a fraction of the font size.
+pub const AVERAGE_CHAR_WIDTH: f64 = 0.5;
+
+/// The height of a line of text, as a multiple of the font size.
+pub const LINE_HEIGHT: f64 = 1.2;
+
+/// The padding around a label for objects without a `text_padding`.
+pub const DEFAULT_LABEL_PADDING: f64 = 2.0;
+
+/// The edge or centre line objects are aligned on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// The direction objects are distributed along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// Rounds `value` to the nearest multiple of `grid`.
+pub fn snap(value: f64, grid: f64) -> f64 {
+    (value / grid).round() * grid
+}
+
+/// The estimated width and height of `label` drawn at `font_size`, with
+/// one line per line break.
+pub fn label_size(label: &str, font_size: f64) -> (f64, f64) {
+    let widest = label
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines = label.lines().count().max(1);
+    (
+        widest as f64 * font_size * AVERAGE_CHAR_WIDTH,
+        lines as f64 * font_size * LINE_HEIGHT,
+    )
+}
+
+impl View {
+    /// Moves the objects with `uids` so their edges or centres line up with
+    /// the matching edge or centre of their combined bounds.
+    pub fn align(&mut self, uids: &[Uid], alignment: Alignment) -> Result<(), EditError> {
+        let bounds = self.layout_bounds(uids)?;
+        let Some(all) = bounds.iter().cloned().reduce(|a, b| a.union(&b)) else {
+            return Ok(());
+        };
+        for (uid, bounds) in uids.iter().zip(&bounds) {
+            let (dx, dy) = match alignment {
+                Alignment::Left => (all.min.x - bounds.min.x, 0.0),
+                Alignment::Center => (all.center().x - bounds.center().x, 0.0),
+                Alignment::Right => (all.max.x - bounds.max.x, 0.0),
+                Alignment::Top => (0.0, all.min.y - bounds.min.y),
+                Alignment::Middle => (0.0, all.center().y - bounds.center().y),
+                Alignment::Bottom => (0.0, all.max.y - bounds.max.y),
+            };
+            if dx != 0.0 || dy != 0.0 {
+                self.move_object(*uid, dx, dy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Spaces the objects with `uids` evenly along `axis`, leaving the same
+    /// gap between each object and the next. The first and last objects
+    /// along the axis stay where they are.
+    pub fn distribute(&mut self, uids: &[Uid], axis: Axis) -> Result<(), EditError> {
+        let bounds = self.layout_bounds(uids)?;
+        let span = |bounds: &Bounds| match axis {
+            Axis::Horizontal => (bounds.min.x, bounds.max.x),
+            Axis::Vertical => (bounds.min.y, bounds.max.y),
+        };
+        let mut objects: Vec<(Uid, f64, f64)> = uids
+            .iter()
+            .zip(&bounds)
+            .map(|(uid, bounds)| {
+                let (min, max) = span(bounds);
+                (*uid, min, max - min)
+            })
+            .collect();
+        if objects.len() < 3 {
+            return Ok(());
+        }
+        objects.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let start = objects[0].1;
+        let end = objects
+            .iter()
+            .map(|(_, min, size)| min + size)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = objects.iter().map(|(_, _, size)| size).sum();
+        let gap = (end - start - total) / (objects.len() - 1) as f64;
+
+        let mut position = start;
+        for (uid, min, size) in objects {
+            let offset = position - min;
+            if offset != 0.0 {
+                match axis {
+                    Axis::Horizontal => self.move_object(uid, offset, 0.0)?,
+                    Axis::Vertical => self.move_object(uid, 0.0, offset)?,
+                }
+            }
+            position += size + gap;
+        }
+        Ok(())
+    }
+
+    /// Moves the objects with `uids` so their positions lie on a grid of
+    /// `grid` by `grid` squares.
+    pub fn snap_to_grid(&mut self, uids: &[Uid], grid: f64) -> Result<(), EditError> {
+        if !(grid.is_finite() && grid > 0.0) {
+            return Err(EditError::InvalidGridSize(grid));
+        }
+        for uid in uids {
+            let (x, y) = match self.target(*uid)? {
+                Target::Stock(index) => (self.stocks[index].x, self.stocks[index].y),
+                Target::Flow(index) => (self.flows[index].x, self.flows[index].y),
+                Target::Aux(index) => (self.auxes[index].x, self.auxes[index].y),
+                Target::Module(index) => (Some(self.modules[index].x), Some(self.modules[index].y)),
+                Target::Group(index) => (Some(self.groups[index].x), Some(self.groups[index].y)),
+                Target::Alias(index) => (Some(self.aliases[index].x), Some(self.aliases[index].y)),
+                Target::Container(index) => (
+                    Some(self.stacked_containers[index].x),
+                    Some(self.stacked_containers[index].y),
+                ),
+                Target::Connector(_) => return Err(EditError::NotMovable(*uid)),
+            };
+            let (Some(x), Some(y)) = (x, y) else {
+                return Err(EditError::Unpositioned(*uid));
+            };
+            let (dx, dy) = (snap(x, grid) - x, snap(y, grid) - y);
+            if dx != 0.0 || dy != 0.0 {
+                self.move_object(*uid, dx, dy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resizes the stock with `uid` to the estimated size of its label and
+    /// text padding: as wide as the label, and at least as tall.
+    ///
+    /// The stock's own font size is used if it has one, and `font_size`
+    /// otherwise.
+    pub fn fit_stock_to_label(&mut self, uid: Uid, font_size: f64) -> Result<(), EditError> {
+        let Target::Stock(index) = self.target(uid)? else {
+            return Err(EditError::NotAStock(uid));
+        };
+        let stock = &self.stocks[index];
+        let (width, height) = label_size(&stock.label(), stock.font_size.unwrap_or(font_size));
+        let padding = |side: Option<f64>| side.unwrap_or(DEFAULT_LABEL_PADDING);
+        let (top, right, bottom, left) = stock.text_padding.unwrap_or_default();
+        let (top, right, bottom, left) =
+            (padding(top), padding(right), padding(bottom), padding(left));
+        let width = width + left + right;
+        let height = stock.height.max(height + top + bottom);
+        self.resize_object(uid, width, height)
+    }
+
+    /// The bounds of each object with `uids`, for laying them out.
+    fn layout_bounds(&self, uids: &[Uid]) -> Result<Vec<Bounds>, EditError> {
+        uids.iter()
+            .map(|uid| match self.target(*uid)? {
+                Target::Connector(_) => Err(EditError::NotMovable(*uid)),
+                target => self
+                    .bounds_of(target, &mut HashSet::new())
+                    .ok_or(EditError::Unpositioned(*uid)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view() -> View {
+        quick_xml::de::from_str(
+            r#"<view uid="1" width="800" height="600" page_width="800" page_height="600">
+                <stock uid="2" name="Population" x="103" y="100" width="40" height="20"/>
+                <aux uid="3" name="Rate" x="150" y="217" width="20" height="20"/>
+                <aux uid="4" name="Fraction" x="400" y="160" width="20" height="20"/>
+                <connector uid="5" x="0" y="0" angle="0"><from>Rate</from><to>Population</to></connector>
+            </view>"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_align_and_distribute() {
+        let mut view = view();
+        let objects = [Uid::new(2), Uid::new(3), Uid::new(4)];
+        view.align(&objects, Alignment::Left).unwrap();
+        assert_eq!(view.stocks[0].x, Some(103.0));
+        assert_eq!(view.auxes[0].x, Some(93.0));
+        assert_eq!(view.auxes[1].x, Some(93.0));
+
+        view.align(&objects[1..], Alignment::Top).unwrap();
+        assert_eq!(view.auxes[0].y, Some(160.0));
+
+        // Three objects 20 high between 90 and 227 leave gaps of 38.5
+        view.move_object(Uid::new(3), 0.0, 57.0).unwrap();
+        view.distribute(&objects, Axis::Vertical).unwrap();
+        assert_eq!(view.auxes[1].y, Some(158.5));
+        assert_eq!(view.auxes[0].y, Some(217.0));
+        assert_eq!(
+            view.align(&[Uid::new(5)], Alignment::Left),
+            Err(EditError::NotMovable(Uid::new(5)))
+        );
+    }
+
+    #[test]
+    fn test_snap_and_fit_label() {
+        let mut view = view();
+        view.snap_to_grid(&[Uid::new(2), Uid::new(3)], 10.0)
+            .unwrap();
+        assert_eq!(
+            (view.stocks[0].x, view.stocks[0].y),
+            (Some(100.0), Some(100.0))
+        );
+        assert_eq!(
+            (view.auxes[0].x, view.auxes[0].y),
+            (Some(150.0), Some(220.0))
+        );
+        assert_eq!(
+            view.snap_to_grid(&[Uid::new(2)], 0.0),
+            Err(EditError::InvalidGridSize(0.0))
+        );
+
+        assert_eq!(label_size("Birth\nRate", 10.0), (25.0, 24.0));
+        view.fit_stock_to_label(Uid::new(2), 10.0).unwrap();
+        assert_eq!((view.stocks[0].width, view.stocks[0].height), (54.0, 20.0));
+        assert_eq!(view.stocks[0].x, Some(100.0));
+    }
+}
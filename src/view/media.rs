@@ -0,0 +1,691 @@
+//! # Media
+//!
+//! Images and videos shown by graphics frames and buttons (Section 6.5.2).
+//!
+//! The content of an `<image>` tag is either a reference to an external file
+//! or the picture itself, embedded as a base64 Data URI
+//! (`data:image/png;base64,…`). A `<video>` tag always refers to an external
+//! file. [`MediaSource`] holds either form; content is loaded through the
+//! [`resource`](crate::resource) layer, so media inside a zip package or an
+//! in-memory store loads the same way as from the filesystem.
+//!
+//! ```rust
+//! use xmile::resource::MemoryResources;
+//! use xmile::view::{ImageContent, MediaSource};
+//!
+//! // A 1x1 GIF.
+//! let gif = b"GIF89a\x01\x00\x01\x00\x00\x00\x00;";
+//! let resources = MemoryResources::new().with("logo.gif", &gif[..]);
+//!
+//! let mut image = ImageContent {
+//!     size_to_parent: false,
+//!     width: None,
+//!     height: None,
+//!     source: MediaSource::Resource("logo.gif".into()),
+//! };
+//! image.embed(&resources).unwrap();
+//! assert!(image.source.to_string().starts_with("data:image/gif;base64,"));
+//! assert_eq!(image.detect_size(&resources).unwrap(), (1, 1));
+//! ```
+
+use std::fmt;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use super::{GraphicsFrameContent, ImageContent, VideoContent};
+use crate::resource::{ResourceError, ResourceReader, ResourceRef};
+use crate::types::{Validate, ValidationResult};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MediaError {
+    #[error(transparent)]
+    Resource(#[from] ResourceError),
+    #[error("Invalid data URI: {0}")]
+    InvalidDataUri(String),
+    #[error("Invalid base64 data: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Unsupported media format: {0}")]
+    UnsupportedFormat(String),
+    #[error("Media content is not embedded")]
+    NotEmbedded,
+    #[error("Could not determine the size of the image")]
+    UnknownSize,
+}
+
+/// A media file format.
+///
+/// The image formats are those listed for the header `<image>` tag in
+/// Section 2.2; the video formats are those commonly supported by vendors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Tiff,
+    Mp4,
+    QuickTime,
+    Avi,
+}
+
+impl MediaFormat {
+    /// Every supported format.
+    pub const ALL: [MediaFormat; 7] = [
+        MediaFormat::Png,
+        MediaFormat::Jpeg,
+        MediaFormat::Gif,
+        MediaFormat::Tiff,
+        MediaFormat::Mp4,
+        MediaFormat::QuickTime,
+        MediaFormat::Avi,
+    ];
+
+    /// The MIME type of the format.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            MediaFormat::Png => "image/png",
+            MediaFormat::Jpeg => "image/jpeg",
+            MediaFormat::Gif => "image/gif",
+            MediaFormat::Tiff => "image/tiff",
+            MediaFormat::Mp4 => "video/mp4",
+            MediaFormat::QuickTime => "video/quicktime",
+            MediaFormat::Avi => "video/x-msvideo",
+        }
+    }
+
+    /// File extensions used for the format, the preferred one first.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            MediaFormat::Png => &["png"],
+            MediaFormat::Jpeg => &["jpg", "jpeg"],
+            MediaFormat::Gif => &["gif"],
+            MediaFormat::Tiff => &["tif", "tiff"],
+            MediaFormat::Mp4 => &["mp4", "m4v"],
+            MediaFormat::QuickTime => &["mov"],
+            MediaFormat::Avi => &["avi"],
+        }
+    }
+
+    /// Returns true for image formats.
+    pub fn is_image(&self) -> bool {
+        matches!(
+            self,
+            MediaFormat::Png | MediaFormat::Jpeg | MediaFormat::Gif | MediaFormat::Tiff
+        )
+    }
+
+    /// Returns true for video formats.
+    pub fn is_video(&self) -> bool {
+        !self.is_image()
+    }
+
+    /// Looks up a format by MIME type.
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let mime_type = mime_type.trim();
+        if mime_type.eq_ignore_ascii_case("image/jpg") {
+            return Some(MediaFormat::Jpeg);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|format| format.mime_type().eq_ignore_ascii_case(mime_type))
+    }
+
+    /// Looks up a format by file extension, ignoring case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| {
+            format
+                .extensions()
+                .iter()
+                .any(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+    }
+
+    /// Guesses the format of a resource from its file extension.
+    pub fn from_resource(resource: &ResourceRef) -> Option<Self> {
+        let name = resource.file_name();
+        let name = name.split(['?', '#']).next().unwrap_or(name);
+        let (_, extension) = name.rsplit_once('.')?;
+        Self::from_extension(extension)
+    }
+
+    /// Detects the format of media data from its leading bytes.
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(MediaFormat::Png)
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(MediaFormat::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(MediaFormat::Gif)
+        } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+            Some(MediaFormat::Tiff)
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"AVI " {
+            Some(MediaFormat::Avi)
+        } else if data.len() >= 12 && &data[4..8] == b"ftyp" {
+            if &data[8..12] == b"qt  " {
+                Some(MediaFormat::QuickTime)
+            } else {
+                Some(MediaFormat::Mp4)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for MediaFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mime_type())
+    }
+}
+
+/// A base64 Data URI, e.g. `data:image/png;base64,iVBORw0KGgo…`.
+///
+/// The URI is kept as written so that files round-trip unchanged; it is only
+/// decoded on request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUri(String);
+
+impl DataUri {
+    /// Encodes `data` as a base64 Data URI with the given MIME type.
+    pub fn encode(mime_type: &str, data: &[u8]) -> Self {
+        DataUri(format!(
+            "data:{};base64,{}",
+            mime_type,
+            STANDARD.encode(data)
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Splits the URI into its header (between `data:` and the comma) and payload.
+    fn parts(&self) -> Result<(&str, &str), MediaError> {
+        self.0
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(','))
+            .ok_or_else(|| MediaError::InvalidDataUri(self.0.clone()))
+    }
+
+    /// The MIME type of the embedded data.
+    pub fn mime_type(&self) -> Result<&str, MediaError> {
+        let (header, _) = self.parts()?;
+        Ok(header.split(';').next().unwrap_or_default().trim())
+    }
+
+    /// The format of the embedded data, from its MIME type.
+    pub fn format(&self) -> Result<MediaFormat, MediaError> {
+        let mime_type = self.mime_type()?;
+        MediaFormat::from_mime_type(mime_type)
+            .ok_or_else(|| MediaError::UnsupportedFormat(mime_type.to_string()))
+    }
+
+    /// Returns true if the data is base64 encoded, the only encoding XMILE requires.
+    pub fn is_base64(&self) -> bool {
+        self.parts()
+            .map(|(header, _)| header.split(';').skip(1).any(|p| p.trim() == "base64"))
+            .unwrap_or(false)
+    }
+
+    /// Decodes the embedded data.
+    ///
+    /// Whitespace in the payload, such as line breaks added by XML writers,
+    /// is ignored.
+    pub fn decode(&self) -> Result<Vec<u8>, MediaError> {
+        if !self.is_base64() {
+            return Err(MediaError::InvalidDataUri(self.0.clone()));
+        }
+        let (_, payload) = self.parts()?;
+        let payload: String = payload.split_whitespace().collect();
+        Ok(STANDARD.decode(payload)?)
+    }
+}
+
+impl fmt::Display for DataUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The content of an `<image>` or `<video>` tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaSource {
+    /// A reference to an external file.
+    Resource(ResourceRef),
+    /// Data embedded in the file.
+    Embedded(DataUri),
+}
+
+impl MediaSource {
+    /// The referenced resource, if the content is not embedded.
+    pub fn resource(&self) -> Option<&ResourceRef> {
+        match self {
+            MediaSource::Resource(resource) => Some(resource),
+            MediaSource::Embedded(_) => None,
+        }
+    }
+
+    /// Returns true if the content is embedded.
+    pub fn is_embedded(&self) -> bool {
+        matches!(self, MediaSource::Embedded(_))
+    }
+
+    /// The format declared by the source: the Data URI MIME type or the
+    /// resource file extension.
+    pub fn declared_format(&self) -> Option<MediaFormat> {
+        match self {
+            MediaSource::Resource(resource) => MediaFormat::from_resource(resource),
+            MediaSource::Embedded(uri) => uri.format().ok(),
+        }
+    }
+
+    /// Loads the media, reading resources through `reader`.
+    ///
+    /// The format is detected from the data, falling back to the declared
+    /// format.
+    pub fn load(&self, reader: &impl ResourceReader) -> Result<Media, MediaError> {
+        let (data, name) = match self {
+            MediaSource::Resource(resource) => (reader.read(resource)?, resource.to_string()),
+            MediaSource::Embedded(uri) => (uri.decode()?, uri.mime_type()?.to_string()),
+        };
+        let format = MediaFormat::sniff(&data)
+            .or_else(|| self.declared_format())
+            .ok_or(MediaError::UnsupportedFormat(name))?;
+        Ok(Media { format, data })
+    }
+}
+
+impl fmt::Display for MediaSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaSource::Resource(resource) => write!(f, "{}", resource),
+            MediaSource::Embedded(uri) => write!(f, "{}", uri),
+        }
+    }
+}
+
+impl Serialize for MediaSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let s = s.trim();
+        if s.starts_with("data:") {
+            Ok(MediaSource::Embedded(DataUri(s.to_string())))
+        } else {
+            Ok(MediaSource::Resource(ResourceRef::new(s)))
+        }
+    }
+}
+
+/// Loaded media data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Media {
+    pub format: MediaFormat,
+    pub data: Vec<u8>,
+}
+
+impl Media {
+    /// The width and height of an image in pixels, read from its header.
+    pub fn size(&self) -> Option<(u32, u32)> {
+        match self.format {
+            MediaFormat::Png => png_size(&self.data),
+            MediaFormat::Gif => gif_size(&self.data),
+            MediaFormat::Jpeg => jpeg_size(&self.data),
+            MediaFormat::Tiff => tiff_size(&self.data),
+            _ => None,
+        }
+    }
+
+    /// Encodes the media as a Data URI.
+    pub fn to_data_uri(&self) -> DataUri {
+        DataUri::encode(self.format.mime_type(), &self.data)
+    }
+}
+
+impl ImageContent {
+    /// Loads the image, reading external files through `reader`.
+    pub fn load(&self, reader: &impl ResourceReader) -> Result<Media, MediaError> {
+        let media = self.source.load(reader)?;
+        if !media.format.is_image() {
+            return Err(MediaError::UnsupportedFormat(media.format.to_string()));
+        }
+        Ok(media)
+    }
+
+    /// Replaces a reference to an external file with the embedded image.
+    pub fn embed(&mut self, reader: &impl ResourceReader) -> Result<(), MediaError> {
+        if !self.source.is_embedded() {
+            let media = self.load(reader)?;
+            self.source = MediaSource::Embedded(media.to_data_uri());
+        }
+        Ok(())
+    }
+
+    /// Replaces the embedded image with a reference to `resource`, returning
+    /// the image so the caller can write it there.
+    pub fn extract(&mut self, resource: ResourceRef) -> Result<Media, MediaError> {
+        let MediaSource::Embedded(uri) = &self.source else {
+            return Err(MediaError::NotEmbedded);
+        };
+        let data = uri.decode()?;
+        let format = MediaFormat::sniff(&data).map_or_else(|| uri.format(), Ok)?;
+        self.source = MediaSource::Resource(resource);
+        Ok(Media { format, data })
+    }
+
+    /// Sets the width and height to the intrinsic size of the image.
+    pub fn detect_size(&mut self, reader: &impl ResourceReader) -> Result<(u32, u32), MediaError> {
+        let (width, height) = self.load(reader)?.size().ok_or(MediaError::UnknownSize)?;
+        self.width = Some(width as f64);
+        self.height = Some(height as f64);
+        Ok((width, height))
+    }
+}
+
+impl Validate for ImageContent {
+    fn validate(&self) -> ValidationResult {
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+
+        validate_size(self.width, self.height, &mut errors);
+        match &self.source {
+            MediaSource::Embedded(uri) => match (uri.format(), uri.decode()) {
+                (Err(err), _) | (_, Err(err)) => {
+                    errors.push(format!("Embedded image is invalid: {}", err));
+                }
+                (Ok(format), Ok(_)) if !format.is_image() => {
+                    errors.push(format!("Embedded image has non-image type {}.", format));
+                }
+                _ => {}
+            },
+            MediaSource::Resource(resource) => match MediaFormat::from_resource(resource) {
+                Some(format) if !format.is_image() => errors.push(format!(
+                    "Image resource '{}' is not an image ({}).",
+                    resource, format
+                )),
+                Some(_) => {}
+                None => warnings.push(format!(
+                    "Image resource '{}' has an unrecognised format.",
+                    resource
+                )),
+            },
+        }
+
+        if !errors.is_empty() {
+            ValidationResult::Invalid(warnings, errors)
+        } else if !warnings.is_empty() {
+            ValidationResult::Warnings((), warnings)
+        } else {
+            ValidationResult::Valid(())
+        }
+    }
+}
+
+impl VideoContent {
+    /// Loads the video through `reader`.
+    pub fn load(&self, reader: &impl ResourceReader) -> Result<Media, MediaError> {
+        let media = MediaSource::Resource(self.resource.clone()).load(reader)?;
+        if !media.format.is_video() {
+            return Err(MediaError::UnsupportedFormat(media.format.to_string()));
+        }
+        Ok(media)
+    }
+}
+
+impl Validate for VideoContent {
+    fn validate(&self) -> ValidationResult {
+        let mut errors = Vec::new();
+
+        validate_size(self.width, self.height, &mut errors);
+        if let Some(format) = MediaFormat::from_resource(&self.resource)
+            && !format.is_video()
+        {
+            errors.push(format!(
+                "Video resource '{}' is not a video ({}).",
+                self.resource, format
+            ));
+        }
+
+        if errors.is_empty() {
+            ValidationResult::Valid(())
+        } else {
+            ValidationResult::Invalid(Vec::new(), errors)
+        }
+    }
+}
+
+impl GraphicsFrameContent {
+    /// The external file the content refers to, if any.
+    pub fn resource(&self) -> Option<&ResourceRef> {
+        match self {
+            GraphicsFrameContent::Image(image) => image.source.resource(),
+            GraphicsFrameContent::Video(video) => Some(&video.resource),
+        }
+    }
+
+    /// Loads the image or video through `reader`.
+    pub fn load(&self, reader: &impl ResourceReader) -> Result<Media, MediaError> {
+        match self {
+            GraphicsFrameContent::Image(image) => image.load(reader),
+            GraphicsFrameContent::Video(video) => video.load(reader),
+        }
+    }
+}
+
+impl Validate for GraphicsFrameContent {
+    fn validate(&self) -> ValidationResult {
+        match self {
+            GraphicsFrameContent::Image(image) => image.validate(),
+            GraphicsFrameContent::Video(video) => video.validate(),
+        }
+    }
+}
+
+fn validate_size(width: Option<f64>, height: Option<f64>, errors: &mut Vec<String>) {
+    for (name, value) in [("width", width), ("height", height)] {
+        if let Some(value) = value
+            && value.is_sign_negative()
+        {
+            errors.push(format!("Media {} cannot be negative.", name));
+        }
+    }
+}
+
+fn png_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((read_u32(data, 16, true)?, read_u32(data, 20, true)?))
+}
+
+fn gif_size(data: &[u8]) -> Option<(u32, u32)> {
+    Some((
+        read_u16(data, 6, false)? as u32,
+        read_u16(data, 8, false)? as u32,
+    ))
+}
+
+fn jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    loop {
+        // Skip fill bytes before the marker.
+        while *data.get(pos)? == 0xff && *data.get(pos + 1)? == 0xff {
+            pos += 1;
+        }
+        if *data.get(pos)? != 0xff {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        let is_frame = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+        if is_frame {
+            let height = read_u16(data, pos + 5, true)? as u32;
+            let width = read_u16(data, pos + 7, true)? as u32;
+            return Some((width, height));
+        }
+        let length = read_u16(data, pos + 2, true)? as usize;
+        pos += 2 + length;
+    }
+}
+
+fn tiff_size(data: &[u8]) -> Option<(u32, u32)> {
+    let big_endian = data.starts_with(b"MM");
+    let ifd = read_u32(data, 4, big_endian)? as usize;
+    let count = read_u16(data, ifd, big_endian)? as usize;
+
+    let (mut width, mut height) = (None, None);
+    for index in 0..count {
+        let entry = ifd + 2 + index * 12;
+        let tag = read_u16(data, entry, big_endian)?;
+        let value = match read_u16(data, entry + 2, big_endian)? {
+            3 => read_u16(data, entry + 8, big_endian)? as u32,
+            4 => read_u32(data, entry + 8, big_endian)?,
+            _ => continue,
+        };
+        match tag {
+            256 => width = Some(value),
+            257 => height = Some(value),
+            _ => {}
+        }
+    }
+    Some((width?, height?))
+}
+
+fn read_u16(data: &[u8], pos: usize, big_endian: bool) -> Option<u16> {
+    let bytes: [u8; 2] = data.get(pos..pos + 2)?.try_into().ok()?;
+    Some(if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    })
+}
+
+fn read_u32(data: &[u8], pos: usize, big_endian: bool) -> Option<u32> {
+    let bytes: [u8; 4] = data.get(pos..pos + 4)?.try_into().ok()?;
+    Some(if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        data.extend(width.to_be_bytes());
+        data.extend(height.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn test_sniff_formats() {
+        assert_eq!(MediaFormat::sniff(&png(1, 1)), Some(MediaFormat::Png));
+        assert_eq!(
+            MediaFormat::sniff(b"\xff\xd8\xff\xe0"),
+            Some(MediaFormat::Jpeg)
+        );
+        assert_eq!(
+            MediaFormat::sniff(b"MM\0*\0\0\0\x08"),
+            Some(MediaFormat::Tiff)
+        );
+        assert_eq!(
+            MediaFormat::sniff(b"\0\0\0\x18ftypisom"),
+            Some(MediaFormat::Mp4)
+        );
+        assert_eq!(
+            MediaFormat::sniff(b"\0\0\0\x14ftypqt  "),
+            Some(MediaFormat::QuickTime)
+        );
+        assert_eq!(MediaFormat::sniff(b"<svg/>"), None);
+    }
+
+    #[test]
+    fn test_format_lookup() {
+        assert_eq!(MediaFormat::from_extension("JPG"), Some(MediaFormat::Jpeg));
+        assert_eq!(
+            MediaFormat::from_mime_type("image/jpg"),
+            Some(MediaFormat::Jpeg)
+        );
+        assert_eq!(
+            MediaFormat::from_resource(&"https://example.com/intro.mov?t=1".into()),
+            Some(MediaFormat::QuickTime)
+        );
+        assert_eq!(MediaFormat::from_resource(&"images/logo".into()), None);
+    }
+
+    #[test]
+    fn test_image_sizes() {
+        let size = |format, data: Vec<u8>| Media { format, data }.size();
+        assert_eq!(size(MediaFormat::Png, png(2509, 1932)), Some((2509, 1932)));
+        assert_eq!(
+            size(MediaFormat::Gif, b"GIF89a\x10\x00\x20\x00".to_vec()),
+            Some((16, 32))
+        );
+
+        // SOI, an APP0 segment, then a baseline SOF0 frame header.
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x00,
+            0x30, 0x00, 0x40,
+        ];
+        assert_eq!(size(MediaFormat::Jpeg, jpeg.to_vec()), Some((64, 48)));
+
+        // Little-endian TIFF with width (SHORT) and height (LONG) entries.
+        let mut tiff = b"II*\0\x08\0\0\0\x02\0".to_vec();
+        tiff.extend([0x00, 0x01, 0x03, 0x00, 0x01, 0, 0, 0, 0x64, 0x00, 0, 0]);
+        tiff.extend([0x01, 0x01, 0x04, 0x00, 0x01, 0, 0, 0, 0xc8, 0x00, 0, 0]);
+        assert_eq!(size(MediaFormat::Tiff, tiff), Some((100, 200)));
+
+        assert_eq!(size(MediaFormat::Png, b"\x89PNG".to_vec()), None);
+    }
+
+    #[test]
+    fn test_data_uri() {
+        let uri = DataUri::encode("image/png", b"abc");
+        assert_eq!(uri.as_str(), "data:image/png;base64,YWJj");
+        assert_eq!(uri.format().unwrap(), MediaFormat::Png);
+        assert_eq!(uri.decode().unwrap(), b"abc");
+
+        let wrapped = DataUri("data:image/png;base64,YW\n  Jj".to_string());
+        assert_eq!(wrapped.decode().unwrap(), b"abc");
+
+        let plain = DataUri("data:image/png,abc".to_string());
+        assert!(!plain.is_base64());
+        assert!(plain.decode().is_err());
+        assert!(DataUri("image/png".to_string()).mime_type().is_err());
+    }
+
+    #[test]
+    fn test_validate_image_content() {
+        let image = |source| ImageContent {
+            size_to_parent: true,
+            width: None,
+            height: None,
+            source,
+        };
+        let embedded = image(MediaSource::Embedded(DataUri::encode(
+            "image/png",
+            &png(1, 1),
+        )));
+        assert!(embedded.validate().is_valid());
+
+        let video = image(MediaSource::Embedded(DataUri::encode("video/mp4", b"")));
+        assert!(video.validate().is_invalid());
+        let bad = image(MediaSource::Embedded(DataUri(
+            "data:image/png;base64,!!".into(),
+        )));
+        assert!(bad.validate().is_invalid());
+
+        let unknown = image(MediaSource::Resource("logo.svg".into()));
+        assert!(matches!(unknown.validate(), ValidationResult::Warnings(..)));
+    }
+}
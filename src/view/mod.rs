@@ -1,14 +1,43 @@
 pub mod style;
+#[cfg(feature = "style")]
 pub use style::Style;
 
 use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::xml::serialize;
 use crate::{Uid, Vendor};
 
+pub mod entity;
+pub use entity::{ViewEntity, ViewEntityKind};
+
+pub mod edit;
+pub use edit::EditError;
+
+pub mod geometry;
+pub mod layout;
 pub mod objects;
 pub use objects::*;
 
+#[cfg(feature = "interface-objects")]
+pub mod interface;
+#[cfg(feature = "interface-objects")]
+pub use interface::*;
+
+#[cfg(feature = "interface-objects")]
+pub mod navigation;
+#[cfg(feature = "interface-objects")]
+pub use navigation::InterfaceNavigator;
+
+#[cfg(feature = "interface-objects")]
+pub mod media;
+#[cfg(feature = "interface-objects")]
+pub use media::{DataUri, Media, MediaError, MediaFormat, MediaSource};
+
 /// The type of a view determines what kind of display objects it can contain.
+///
+/// Vendor-specific types are written `vendor:type`, e.g. `isee:page`. For
+/// vendors without a [`Vendor`] variant the type keeps the full `vendor:type`
+/// text, so that it is written back unchanged.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ViewType {
@@ -18,6 +47,70 @@ pub enum ViewType {
     VendorSpecific(Vendor, String),
 }
 
+impl ViewType {
+    /// The vendor of a vendor-specific view type.
+    pub fn vendor(&self) -> Option<&Vendor> {
+        match self {
+            ViewType::VendorSpecific(vendor, _) => Some(vendor),
+            _ => None,
+        }
+    }
+
+    /// The vendor's name for a vendor-specific view type, without the vendor
+    /// prefix.
+    pub fn vendor_type(&self) -> Option<&str> {
+        match self {
+            ViewType::VendorSpecific(Vendor::Other, type_part) => Some(
+                type_part
+                    .split_once(':')
+                    .map_or(type_part.as_str(), |(_, type_part)| type_part),
+            ),
+            ViewType::VendorSpecific(_, type_part) => Some(type_part),
+            _ => None,
+        }
+    }
+
+    pub fn is_vendor_specific(&self) -> bool {
+        matches!(self, ViewType::VendorSpecific(..))
+    }
+}
+
+impl std::str::FromStr for ViewType {
+    type Err = String;
+
+    /// Parses a `type` attribute; types other than the standard ones must
+    /// have a `vendor:` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stock_flow" => Ok(ViewType::StockFlow),
+            "interface" => Ok(ViewType::Interface),
+            "popup" => Ok(ViewType::Popup),
+            _ => match s.split_once(':') {
+                Some((vendor, type_part)) => Ok(match parse_vendor(vendor) {
+                    Vendor::Other => ViewType::VendorSpecific(Vendor::Other, s.to_string()),
+                    vendor => ViewType::VendorSpecific(vendor, type_part.to_string()),
+                }),
+                None => Err(format!("Unknown view type '{s}'")),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ViewType {
+    /// Formats the view type as its `type` attribute.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViewType::StockFlow => write!(f, "stock_flow"),
+            ViewType::Interface => write!(f, "interface"),
+            ViewType::Popup => write!(f, "popup"),
+            ViewType::VendorSpecific(Vendor::Other, type_part) => write!(f, "{type_part}"),
+            ViewType::VendorSpecific(vendor, type_part) => {
+                write!(f, "{}:{type_part}", vendor_name(vendor))
+            }
+        }
+    }
+}
+
 /// A view contains XMILE display objects and represents a page or screen
 /// of a model's stock and flow diagram, or its interface.
 #[derive(Debug, Clone, PartialEq)]
@@ -39,6 +132,7 @@ pub struct View {
     pub home_page: u32,
     pub home_view: bool,
     /// Optional style definitions that apply to all objects within this view.
+    #[cfg(feature = "style")]
     pub style: Option<Style>,
     /// Stock and flow diagram objects
     pub stocks: Vec<StockObject>,
@@ -51,22 +145,37 @@ pub struct View {
     /// Container objects
     pub stacked_containers: Vec<StackedContainerObject>,
     /// Input objects
+    #[cfg(feature = "interface-objects")]
     pub sliders: Vec<SliderObject>,
+    #[cfg(feature = "interface-objects")]
     pub knobs: Vec<KnobObject>,
+    #[cfg(feature = "interface-objects")]
     pub switches: Vec<SwitchObject>,
+    #[cfg(feature = "interface-objects")]
     pub options: Vec<OptionsObject>,
+    #[cfg(feature = "interface-objects")]
     pub numeric_inputs: Vec<NumericInputObject>,
+    #[cfg(feature = "interface-objects")]
     pub list_inputs: Vec<ListInputObject>,
+    #[cfg(feature = "interface-objects")]
     pub graphical_inputs: Vec<GraphicalInputObject>,
     /// Output objects
+    #[cfg(feature = "interface-objects")]
     pub numeric_displays: Vec<NumericDisplayObject>,
+    #[cfg(feature = "interface-objects")]
     pub lamps: Vec<LampObject>,
+    #[cfg(feature = "interface-objects")]
     pub gauges: Vec<GaugeObject>,
+    #[cfg(feature = "interface-objects")]
     pub graphs: Vec<GraphObject>,
+    #[cfg(feature = "interface-objects")]
     pub tables: Vec<TableObject>,
     /// Annotation objects
+    #[cfg(feature = "interface-objects")]
     pub text_boxes: Vec<TextBoxObject>,
+    #[cfg(feature = "interface-objects")]
     pub graphics_frames: Vec<GraphicsFrameObject>,
+    #[cfg(feature = "interface-objects")]
     pub buttons: Vec<ButtonObject>,
 }
 
@@ -120,6 +229,7 @@ struct RawView {
     home_page: u32,
     #[serde(rename = "@home_view", default = "default_false")]
     home_view: bool,
+    #[cfg(feature = "style")]
     #[serde(rename = "style")]
     style: Option<Style>,
     // Stock and flow diagram objects
@@ -141,36 +251,51 @@ struct RawView {
     #[serde(rename = "stacked_container", default)]
     stacked_containers: Vec<StackedContainerObject>,
     // Input objects
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "slider", default)]
     sliders: Vec<SliderObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "knob", default)]
     knobs: Vec<KnobObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "switch", default)]
     switches: Vec<SwitchObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "options", default)]
     options: Vec<OptionsObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "numeric_input", default)]
     numeric_inputs: Vec<NumericInputObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "list_input", default)]
     list_inputs: Vec<ListInputObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "graphical_input", default)]
     graphical_inputs: Vec<GraphicalInputObject>,
     // Output objects
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "numeric_display", default)]
     numeric_displays: Vec<NumericDisplayObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "lamp", default)]
     lamps: Vec<LampObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "gauge", default)]
     gauges: Vec<GaugeObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "graph", default)]
     graphs: Vec<GraphObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "table", default)]
     tables: Vec<TableObject>,
     // Annotation objects
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "text_box", default)]
     text_boxes: Vec<TextBoxObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "graphics_frame", default)]
     graphics_frames: Vec<GraphicsFrameObject>,
+    #[cfg(feature = "interface-objects")]
     #[serde(rename = "button", default)]
     buttons: Vec<ButtonObject>,
 }
@@ -207,29 +332,29 @@ fn parse_vendor(s: &str) -> Vendor {
     }
 }
 
+fn vendor_name(vendor: &Vendor) -> &'static str {
+    match vendor {
+        Vendor::Anylogic => "anylogic",
+        Vendor::Forio => "forio",
+        Vendor::Insightmaker => "insightmaker",
+        Vendor::Isee => "isee",
+        Vendor::Powersim => "powersim",
+        Vendor::Simanticssd => "simanticssd",
+        Vendor::Simile => "simile",
+        Vendor::Sysdea => "sysdea",
+        Vendor::Vensim => "vensim",
+        Vendor::SimLab => "simlab",
+        Vendor::Other => "other",
+    }
+}
+
 impl From<RawView> for View {
     fn from(raw: RawView) -> Self {
-        // Parse view_type from type attribute
-        let view_type = if let Some(type_str) = raw.r#type {
-            // Try to parse as ViewType
-            match type_str.as_str() {
-                "stock_flow" => ViewType::StockFlow,
-                "interface" => ViewType::Interface,
-                "popup" => ViewType::Popup,
-                _ => {
-                    // Try to parse as vendor-specific
-                    // Format: "vendor:type" or just use as-is
-                    if let Some((vendor_str, type_part)) = type_str.split_once(':') {
-                        let vendor = parse_vendor(vendor_str);
-                        ViewType::VendorSpecific(vendor, type_part.to_string())
-                    } else {
-                        ViewType::StockFlow // Default fallback
-                    }
-                }
-            }
-        } else {
-            ViewType::StockFlow // Default when type is not specified
-        };
+        // Unknown types without a vendor prefix fall back to stock and flow
+        let view_type = raw
+            .r#type
+            .and_then(|type_str| type_str.parse().ok())
+            .unwrap_or(ViewType::StockFlow);
 
         View {
             uid: Uid::new(raw.uid),
@@ -248,6 +373,7 @@ impl From<RawView> for View {
             show_pages: raw.show_pages,
             home_page: raw.home_page,
             home_view: raw.home_view,
+            #[cfg(feature = "style")]
             style: raw.style,
             stocks: raw.stocks,
             flows: raw.flows,
@@ -257,25 +383,76 @@ impl From<RawView> for View {
             connectors: raw.connectors,
             aliases: raw.aliases,
             stacked_containers: raw.stacked_containers,
+            #[cfg(feature = "interface-objects")]
             sliders: raw.sliders,
+            #[cfg(feature = "interface-objects")]
             knobs: raw.knobs,
+            #[cfg(feature = "interface-objects")]
             switches: raw.switches,
+            #[cfg(feature = "interface-objects")]
             options: raw.options,
+            #[cfg(feature = "interface-objects")]
             numeric_inputs: raw.numeric_inputs,
+            #[cfg(feature = "interface-objects")]
             list_inputs: raw.list_inputs,
+            #[cfg(feature = "interface-objects")]
             graphical_inputs: raw.graphical_inputs,
+            #[cfg(feature = "interface-objects")]
             numeric_displays: raw.numeric_displays,
+            #[cfg(feature = "interface-objects")]
             lamps: raw.lamps,
+            #[cfg(feature = "interface-objects")]
             gauges: raw.gauges,
+            #[cfg(feature = "interface-objects")]
             graphs: raw.graphs,
+            #[cfg(feature = "interface-objects")]
             tables: raw.tables,
+            #[cfg(feature = "interface-objects")]
             text_boxes: raw.text_boxes,
+            #[cfg(feature = "interface-objects")]
             graphics_frames: raw.graphics_frames,
+            #[cfg(feature = "interface-objects")]
             buttons: raw.buttons,
         }
     }
 }
 
+impl View {
+    /// The size of one page of the view, falling back to the whole view when
+    /// no page size is given.
+    pub fn page_size(&self) -> (f64, f64) {
+        let width = if self.page_width > 0.0 {
+            self.page_width
+        } else {
+            self.width
+        };
+        let height = if self.page_height > 0.0 {
+            self.page_height
+        } else {
+            self.height
+        };
+        (width, height)
+    }
+
+    /// The number of page columns and rows the view spans.
+    pub fn page_grid(&self) -> (usize, usize) {
+        let (page_width, page_height) = self.page_size();
+        let span = |length: f64, page: f64| {
+            if page > 0.0 {
+                (length / page).ceil().max(1.0) as usize
+            } else {
+                1
+            }
+        };
+        (span(self.width, page_width), span(self.height, page_height))
+    }
+
+    pub fn page_count(&self) -> usize {
+        let (columns, rows) = self.page_grid();
+        columns * rows
+    }
+}
+
 impl<'de> Deserialize<'de> for View {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -296,32 +473,31 @@ impl Serialize for View {
 
         state.serialize_field("@uid", &self.uid)?;
 
-        // Serialize view_type
-        let type_str = match &self.view_type {
-            ViewType::StockFlow => "stock_flow",
-            ViewType::Interface => "interface",
-            ViewType::Popup => "popup",
-            ViewType::VendorSpecific(_vendor, _type_part) => {
-                // For vendor-specific, we'd need to serialize as "vendor:type"
-                // For now, serialize as stock_flow and note this might need adjustment
-                "stock_flow"
-            }
-        };
-        state.serialize_field("@type", type_str)?;
+        let omit_defaults = serialize::omit_defaults();
+        let view_type = self.view_type.to_string();
+        if !(omit_defaults && view_type == "stock_flow") {
+            state.serialize_field("@type", &view_type)?;
+        }
 
         if let Some(order) = &self.order {
             state.serialize_field("@order", order)?;
         }
         state.serialize_field("@width", &self.width)?;
         state.serialize_field("@height", &self.height)?;
-        if let Some(zoom) = &self.zoom {
-            state.serialize_field("@zoom", zoom)?;
+        if let Some(zoom) = self.zoom.filter(|&zoom| !(omit_defaults && zoom == 100.0)) {
+            state.serialize_field("@zoom", &zoom)?;
         }
-        if let Some(scroll_x) = &self.scroll_x {
-            state.serialize_field("@scroll_x", scroll_x)?;
+        if let Some(scroll_x) = self
+            .scroll_x
+            .filter(|&scroll_x| !(omit_defaults && scroll_x == 0.0))
+        {
+            state.serialize_field("@scroll_x", &scroll_x)?;
         }
-        if let Some(scroll_y) = &self.scroll_y {
-            state.serialize_field("@scroll_y", scroll_y)?;
+        if let Some(scroll_y) = self
+            .scroll_y
+            .filter(|&scroll_y| !(omit_defaults && scroll_y == 0.0))
+        {
+            state.serialize_field("@scroll_y", &scroll_y)?;
         }
         if let Some(background) = &self.background {
             state.serialize_field("@background", background)?;
@@ -331,9 +507,14 @@ impl Serialize for View {
         state.serialize_field("@page_sequence", &self.page_sequence)?;
         state.serialize_field("@page_orientation", &self.page_orientation)?;
         state.serialize_field("@show_pages", &self.show_pages)?;
-        state.serialize_field("@home_page", &self.home_page)?;
-        state.serialize_field("@home_view", &self.home_view)?;
+        if !(omit_defaults && self.home_page == 0) {
+            state.serialize_field("@home_page", &self.home_page)?;
+        }
+        if self.home_view || !omit_defaults {
+            state.serialize_field("@home_view", &self.home_view)?;
+        }
 
+        #[cfg(feature = "style")]
         if let Some(style) = &self.style {
             state.serialize_field("style", style)?;
         }
@@ -363,48 +544,63 @@ impl Serialize for View {
         if !self.stacked_containers.is_empty() {
             state.serialize_field("stacked_container", &self.stacked_containers)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.sliders.is_empty() {
             state.serialize_field("slider", &self.sliders)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.knobs.is_empty() {
             state.serialize_field("knob", &self.knobs)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.switches.is_empty() {
             state.serialize_field("switch", &self.switches)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.options.is_empty() {
             state.serialize_field("options", &self.options)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.numeric_inputs.is_empty() {
             state.serialize_field("numeric_input", &self.numeric_inputs)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.list_inputs.is_empty() {
             state.serialize_field("list_input", &self.list_inputs)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.graphical_inputs.is_empty() {
             state.serialize_field("graphical_input", &self.graphical_inputs)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.numeric_displays.is_empty() {
             state.serialize_field("numeric_display", &self.numeric_displays)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.lamps.is_empty() {
             state.serialize_field("lamp", &self.lamps)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.gauges.is_empty() {
             state.serialize_field("gauge", &self.gauges)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.graphs.is_empty() {
             state.serialize_field("graph", &self.graphs)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.tables.is_empty() {
             state.serialize_field("table", &self.tables)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.text_boxes.is_empty() {
             state.serialize_field("text_box", &self.text_boxes)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.graphics_frames.is_empty() {
             state.serialize_field("graphics_frame", &self.graphics_frames)?;
         }
+        #[cfg(feature = "interface-objects")]
         if !self.buttons.is_empty() {
             state.serialize_field("button", &self.buttons)?;
         }
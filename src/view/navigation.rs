@@ -0,0 +1,410 @@
+//! Navigation between the screens of a model's interface.
+//!
+//! Interfaces built from several views are navigated with buttons whose
+//! `<link>` names a target: a view by type and order, a page of one, the
+//! next, previous or home page or view, the last page or view visited, or a
+//! URL. The home view is the one marked `home_view`, opened at
+//! its `home_page`. Views of type `popup` open over the current view rather
+//! than replacing it, as does a button's `<popup>` content.
+//!
+//! Pages are numbered from zero, like `home_page`, in the view's
+//! `page_sequence` order.
+
+use crate::Uid;
+
+use super::{ButtonObject, LinkTarget, PopupContent, View, ViewType};
+
+/// A page of one of the views being navigated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ViewLocation {
+    /// The index of the view in the list being navigated.
+    pub view: usize,
+    pub page: usize,
+}
+
+/// The outcome of following a link or pressing a button.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Navigation<'a> {
+    /// The current location changed.
+    Moved(ViewLocation),
+    /// A popup view opened over the current view.
+    OpenedPopup(ViewLocation),
+    /// A button's popup content should be shown.
+    ShowContent(&'a PopupContent),
+    /// An external page should be opened.
+    OpenUrl(&'a str),
+    /// Nothing happened, because the target does not exist or is where
+    /// navigation already is.
+    Stayed,
+}
+
+/// Where a button leads, as seen from the view holding it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Destination {
+    /// A page of a view. The page is `None` for moves to the next or
+    /// previous page, which depend on the current page.
+    View {
+        view: usize,
+        page: Option<usize>,
+    },
+    /// The popup content of the button itself.
+    Content,
+    /// Wherever was visited before, which depends on history.
+    Back,
+    Url(String),
+    /// A view or page that the file does not define.
+    Unresolved,
+}
+
+/// A button leading from one view elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavigationEdge {
+    /// The index of the view holding the button.
+    pub from: usize,
+    pub button: Uid,
+    pub to: Destination,
+}
+
+/// Tracks the screen a host is showing and resolves links between views.
+#[derive(Debug, Clone)]
+pub struct InterfaceNavigator<'a> {
+    views: &'a [View],
+    /// Indices of the non-popup views, in `order` order.
+    sequence: Vec<usize>,
+    current: ViewLocation,
+    popup: Option<ViewLocation>,
+    history: Vec<ViewLocation>,
+}
+
+impl<'a> InterfaceNavigator<'a> {
+    /// Starts navigating `views` at the home view, or `None` without views.
+    pub fn new(views: &'a [View]) -> Option<Self> {
+        if views.is_empty() {
+            return None;
+        }
+        let mut sequence: Vec<usize> = (0..views.len())
+            .filter(|&index| views[index].view_type != ViewType::Popup)
+            .collect();
+        sequence.sort_by_key(|&index| (views[index].order.unwrap_or(u32::MAX), index));
+        let mut navigator = InterfaceNavigator {
+            views,
+            sequence,
+            current: ViewLocation { view: 0, page: 0 },
+            popup: None,
+            history: Vec::new(),
+        };
+        navigator.current = navigator.home();
+        Some(navigator)
+    }
+
+    /// The home view at its home page: the first view marked `home_view`,
+    /// else the first view in order.
+    pub fn home(&self) -> ViewLocation {
+        let view = self
+            .views
+            .iter()
+            .position(|view| view.home_view)
+            .or_else(|| self.sequence.first().copied())
+            .unwrap_or(0);
+        self.home_page_of(view)
+    }
+
+    pub fn current(&self) -> ViewLocation {
+        self.current
+    }
+
+    pub fn current_view(&self) -> &'a View {
+        &self.views[self.current.view]
+    }
+
+    /// The popup view open over the current view, if any.
+    pub fn popup(&self) -> Option<ViewLocation> {
+        self.popup
+    }
+
+    pub fn close_popup(&mut self) {
+        self.popup = None;
+    }
+
+    /// Moves to `location`, remembering the current location for
+    /// [`LinkTarget::BackPage`]. Popup views open over the current view.
+    pub fn go_to(&mut self, location: ViewLocation) -> Navigation<'a> {
+        let Some(view) = self.views.get(location.view) else {
+            return Navigation::Stayed;
+        };
+        let location = ViewLocation {
+            view: location.view,
+            page: location.page.min(view.page_count() - 1),
+        };
+        if view.view_type == ViewType::Popup {
+            self.popup = Some(location);
+            return Navigation::OpenedPopup(location);
+        }
+        if location == self.current {
+            return Navigation::Stayed;
+        }
+        self.history.push(self.current);
+        self.current = location;
+        self.popup = None;
+        Navigation::Moved(location)
+    }
+
+    /// Follows a link from the current location.
+    pub fn follow(&mut self, target: &'a LinkTarget) -> Navigation<'a> {
+        if let LinkTarget::Url(url) = target {
+            return Navigation::OpenUrl(url);
+        }
+        match target {
+            LinkTarget::BackPage => match self.history.pop() {
+                Some(location) => self.go_back_to(location),
+                None => Navigation::Stayed,
+            },
+            LinkTarget::BackView => {
+                let view = self.current.view;
+                while let Some(location) = self.history.pop() {
+                    if location.view != view {
+                        return self.go_back_to(location);
+                    }
+                }
+                Navigation::Stayed
+            }
+            _ => match self.resolve(self.current.view, target) {
+                Destination::View { view, page } => {
+                    let page = page.unwrap_or_else(|| self.relative_page(target));
+                    self.go_to(ViewLocation { view, page })
+                }
+                _ => Navigation::Stayed,
+            },
+        }
+    }
+
+    /// Presses `button`, showing its popup content or following its link.
+    pub fn press(&mut self, button: &'a ButtonObject) -> Navigation<'a> {
+        if let Some(content) = &button.popup {
+            return Navigation::ShowContent(content);
+        }
+        match &button.link {
+            Some(link) => self.follow(&link.target),
+            None => Navigation::Stayed,
+        }
+    }
+
+    /// Every button link and popup in the views, resolved from the view
+    /// holding the button.
+    pub fn graph(&self) -> Vec<NavigationEdge> {
+        let mut edges = Vec::new();
+        for (from, view) in self.views.iter().enumerate() {
+            for button in &view.buttons {
+                let to = if button.popup.is_some() {
+                    Destination::Content
+                } else if let Some(link) = &button.link {
+                    self.resolve(from, &link.target)
+                } else {
+                    continue;
+                };
+                edges.push(NavigationEdge {
+                    from,
+                    button: button.uid,
+                    to,
+                });
+            }
+        }
+        edges
+    }
+
+    /// Resolves `target` as seen from the view at index `from`.
+    fn resolve(&self, from: usize, target: &LinkTarget) -> Destination {
+        let step = |offset: isize| {
+            let position = self.sequence.iter().position(|&index| index == from)?;
+            let next = position.checked_add_signed(offset)?;
+            self.sequence.get(next).copied()
+        };
+        let view_at = |view: Option<usize>| match view {
+            Some(view) => Destination::View {
+                view,
+                page: Some(self.views[view].home_page as usize),
+            },
+            None => Destination::Unresolved,
+        };
+        match target {
+            LinkTarget::View { view_type, order } => view_at(self.find(view_type, order)),
+            LinkTarget::Page {
+                view_type,
+                order,
+                page,
+            } => match (self.find(view_type, order), page.trim().parse::<usize>()) {
+                (Some(view), Ok(page)) if page < self.views[view].page_count() => {
+                    Destination::View {
+                        view,
+                        page: Some(page),
+                    }
+                }
+                _ => Destination::Unresolved,
+            },
+            LinkTarget::NextPage | LinkTarget::PreviousPage => Destination::View {
+                view: from,
+                page: None,
+            },
+            LinkTarget::HomePage => view_at(Some(from)),
+            LinkTarget::NextView => view_at(step(1)),
+            LinkTarget::PreviousView => view_at(step(-1)),
+            LinkTarget::HomeView => {
+                let home = self.home();
+                Destination::View {
+                    view: home.view,
+                    page: Some(home.page),
+                }
+            }
+            LinkTarget::BackPage | LinkTarget::BackView => Destination::Back,
+            LinkTarget::Url(url) => Destination::Url(url.clone()),
+        }
+    }
+
+    /// Finds the view with the given `type` attribute and `order`.
+    fn find(&self, view_type: &str, order: &str) -> Option<usize> {
+        let order = order.trim().parse::<u32>().ok()?;
+        self.views
+            .iter()
+            .position(|view| view.view_type.to_string() == view_type && view.order == Some(order))
+    }
+
+    /// The page a next or previous page link moves to.
+    fn relative_page(&self, target: &LinkTarget) -> usize {
+        let page = self.current.page;
+        match target {
+            LinkTarget::NextPage => page + 1,
+            LinkTarget::PreviousPage => page.saturating_sub(1),
+            _ => page,
+        }
+    }
+
+    fn home_page_of(&self, view: usize) -> ViewLocation {
+        let page = (self.views[view].home_page as usize).min(self.views[view].page_count() - 1);
+        ViewLocation { view, page }
+    }
+
+    /// Returns to `location` without recording the move in history.
+    fn go_back_to(&mut self, location: ViewLocation) -> Navigation<'a> {
+        self.current = location;
+        self.popup = None;
+        Navigation::Moved(location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builders::{ObjectKind, ViewBuilder};
+    use crate::view::Link;
+
+    fn link(target: LinkTarget) -> Option<Link> {
+        Some(Link {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            effect: None,
+            to_black: false,
+            target,
+        })
+    }
+
+    /// A two page home view, a second interface view and a popup view.
+    fn views() -> Vec<View> {
+        let view = |uid: i32, view_type: &str| ViewBuilder::new(uid).view_type(view_type);
+        let mut home = view(1, "interface")
+            .with(ObjectKind::Button)
+            .with(ObjectKind::Button)
+            .build()
+            .unwrap();
+        home.order = Some(1);
+        home.home_view = true;
+        home.width = 1600.0;
+        home.page_width = 800.0;
+        home.buttons[0].link = link(LinkTarget::View {
+            view_type: "interface".to_string(),
+            order: "2".to_string(),
+        });
+        home.buttons[1].link = link(LinkTarget::View {
+            view_type: "popup".to_string(),
+            order: "3".to_string(),
+        });
+
+        let mut second = view(2, "interface")
+            .with(ObjectKind::Button)
+            .build()
+            .unwrap();
+        second.order = Some(2);
+        second.buttons[0].link = link(LinkTarget::Page {
+            view_type: "interface".to_string(),
+            order: "9".to_string(),
+            page: "0".to_string(),
+        });
+
+        let mut popup = view(3, "popup").build().unwrap();
+        popup.order = Some(3);
+        vec![second, home, popup]
+    }
+
+    #[test]
+    fn test_navigation() {
+        let views = views();
+        let (next_page, previous_view, back_view) = (
+            LinkTarget::NextPage,
+            LinkTarget::PreviousView,
+            LinkTarget::BackView,
+        );
+        let mut navigator = InterfaceNavigator::new(&views).unwrap();
+        let home = ViewLocation { view: 1, page: 0 };
+        assert_eq!(navigator.current(), home);
+
+        assert_eq!(
+            navigator.follow(&next_page),
+            Navigation::Moved(ViewLocation { view: 1, page: 1 })
+        );
+        assert_eq!(navigator.follow(&next_page), Navigation::Stayed);
+
+        let to_second = &views[1].buttons[0];
+        assert_eq!(
+            navigator.press(to_second),
+            Navigation::Moved(ViewLocation { view: 0, page: 0 })
+        );
+        assert_eq!(navigator.follow(&previous_view), Navigation::Moved(home));
+        assert_eq!(
+            navigator.follow(&back_view),
+            Navigation::Moved(ViewLocation { view: 0, page: 0 })
+        );
+
+        let to_popup = &views[1].buttons[1];
+        assert_eq!(
+            navigator.press(to_popup),
+            Navigation::OpenedPopup(ViewLocation { view: 2, page: 0 })
+        );
+        assert_eq!(navigator.current().view, 0);
+        navigator.close_popup();
+        assert_eq!(navigator.popup(), None);
+    }
+
+    #[test]
+    fn test_graph() {
+        let views = views();
+        let navigator = InterfaceNavigator::new(&views).unwrap();
+        let graph = navigator.graph();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph[0].from, 0);
+        assert_eq!(graph[0].to, Destination::Unresolved);
+        assert_eq!(
+            graph[1].to,
+            Destination::View {
+                view: 0,
+                page: Some(0)
+            }
+        );
+        assert_eq!(
+            graph[2].to,
+            Destination::View {
+                view: 2,
+                page: Some(0)
+            }
+        );
+    }
+}
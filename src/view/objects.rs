@@ -49,9 +49,137 @@ use super::style::{
     VerticalTextAlign,
 };
 
+/// Serde support for `text_padding` attributes, written like `padding` in
+/// Section 5.2.1 as a comma separated list of one to four numbers, e.g.
+/// `text_padding="2,4"`. A `px` unit on each number is accepted.
+pub(crate) mod text_padding {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::TextPadding;
+
+    pub fn serialize<S>(padding: &TextPadding, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match padding {
+            Some(_) => serializer.serialize_str(&format(padding)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<TextPadding, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(value) => parse(&value).map_err(D::Error::custom),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a padding attribute; an empty attribute means no padding.
+    pub fn parse(value: &str) -> Result<TextPadding, String> {
+        let values = value
+            .split([',', ' '])
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                part.trim_end_matches("px")
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid padding value '{part}'"))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        match values.as_slice() {
+            [] => Ok(None),
+            [top] => Ok(Some((Some(*top), None, None, None))),
+            [top, right] => Ok(Some((Some(*top), Some(*right), None, None))),
+            [top, right, bottom] => Ok(Some((Some(*top), Some(*right), Some(*bottom), None))),
+            [top, right, bottom, left] => {
+                Ok(Some((Some(*top), Some(*right), Some(*bottom), Some(*left))))
+            }
+            _ => Err(format!("Padding has more than four values: '{value}'")),
+        }
+    }
+
+    /// Formats padding as an attribute value.
+    pub fn format(padding: &TextPadding) -> String {
+        let Some((top, right, bottom, left)) = padding else {
+            return String::new();
+        };
+        [top, right, bottom, left]
+            .into_iter()
+            .map_while(|value| value.map(|value| value.to_string()))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/// Serde support for `<pts>` tags holding a list of `<pt x="…" y="…"/>`.
+pub(crate) mod points {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Point;
+
+    #[derive(Serialize)]
+    struct PointsRef<'a> {
+        #[serde(rename = "pt")]
+        pts: &'a [Point],
+    }
+
+    #[derive(Deserialize)]
+    struct Points {
+        #[serde(rename = "pt", default)]
+        pts: Vec<Point>,
+    }
+
+    pub fn serialize<S>(pts: &[Point], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        PointsRef { pts }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Point>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Points::deserialize(deserializer)?.pts)
+    }
+}
+
+/// Serde support for `<item uid="…"/>` tags listing the members of a group.
+mod item_uids {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::Uid;
+
+    #[derive(Serialize, Deserialize)]
+    struct ItemTag {
+        #[serde(rename = "@uid")]
+        uid: Uid,
+    }
+
+    pub fn serialize<S>(items: &[Uid], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let tags: Vec<ItemTag> = items.iter().map(|&uid| ItemTag { uid }).collect();
+        tags.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Uid>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let tags = Vec::<ItemTag>::deserialize(deserializer)?;
+        Ok(tags.into_iter().map(|tag| tag.uid).collect())
+    }
+}
+
 /// Shape tags allow stock, auxiliary, module, or alias objects to be represented
 /// using a different symbol than the default.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Shape {
     Rectangle {
         width: f64,
@@ -67,6 +195,89 @@ pub enum Shape {
     },
 }
 
+/// The attributes of a `<shape>` tag.
+#[derive(Serialize, Deserialize)]
+struct RawShape {
+    #[serde(rename = "@type")]
+    shape_type: String,
+    #[serde(rename = "@width", skip_serializing_if = "Option::is_none")]
+    width: Option<f64>,
+    #[serde(rename = "@height", skip_serializing_if = "Option::is_none")]
+    height: Option<f64>,
+    #[serde(rename = "@corner_radius", skip_serializing_if = "Option::is_none")]
+    corner_radius: Option<f64>,
+    #[serde(rename = "@radius", skip_serializing_if = "Option::is_none")]
+    radius: Option<f64>,
+}
+
+impl<'de> Deserialize<'de> for Shape {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let raw = RawShape::deserialize(deserializer)?;
+        let required = |value: Option<f64>, name: &str| {
+            value.ok_or_else(|| D::Error::custom(format!("Shape is missing its {name}")))
+        };
+        match raw.shape_type.as_str() {
+            "rectangle" => Ok(Shape::Rectangle {
+                width: required(raw.width, "width")?,
+                height: required(raw.height, "height")?,
+                corner_radius: raw.corner_radius,
+            }),
+            "circle" => Ok(Shape::Circle {
+                radius: required(raw.radius, "radius")?,
+            }),
+            "name_only" => Ok(Shape::NameOnly {
+                width: raw.width,
+                height: raw.height,
+            }),
+            other => Err(D::Error::unknown_variant(
+                other,
+                &["rectangle", "circle", "name_only"],
+            )),
+        }
+    }
+}
+
+impl Serialize for Shape {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let raw = match *self {
+            Shape::Rectangle {
+                width,
+                height,
+                corner_radius,
+            } => RawShape {
+                shape_type: "rectangle".to_string(),
+                width: Some(width),
+                height: Some(height),
+                corner_radius,
+                radius: None,
+            },
+            Shape::Circle { radius } => RawShape {
+                shape_type: "circle".to_string(),
+                width: None,
+                height: None,
+                corner_radius: None,
+                radius: Some(radius),
+            },
+            Shape::NameOnly { width, height } => RawShape {
+                shape_type: "name_only".to_string(),
+                width,
+                height,
+                corner_radius: None,
+                radius: None,
+            },
+        };
+        raw.serialize(serializer)
+    }
+}
+
 // The <stock> tag in the context of a <view> tag is used to describe the appearance of an XMILE stock equation object.  Support is REQUIRED for any implementation supporting views.  An example tag is shown below:
 // <stock name=”Bathtub” x=”50” y=”100” width=”45” height=”35” label_side=”top” color=”blue” background=”white” z_index=”1” font_family=”Arial” font_size=”9pt” font_weight=”bold” font_style=”italic” text_decoration=”underline” text_align=”center” vertical_text_align=”center” text_padding=”2px” font_color=”blue” text_border_color=”black” text_border_width=”1px” text_border_style=”solid”/>
 // Descriptions of all the display attributes of a stock can be found in Section 6.1.
@@ -77,32 +288,59 @@ pub struct StockObject {
     pub uid: Uid,
     #[serde(rename = "@name")]
     pub name: String,
-    #[serde(rename = "@x")]
+    #[serde(rename = "@x", skip_serializing_if = "Option::is_none")]
     pub x: Option<f64>, // May be aliased
-    #[serde(rename = "@y")]
+    #[serde(rename = "@y", skip_serializing_if = "Option::is_none")]
     pub y: Option<f64>, // May be aliased
     #[serde(rename = "@width")]
     pub width: f64,
     #[serde(rename = "@height")]
     pub height: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shape: Option<Shape>,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
+    #[serde(rename = "@label_side", skip_serializing_if = "Option::is_none")]
     pub label_side: Option<String>,
+    #[serde(rename = "@label_angle", skip_serializing_if = "Option::is_none")]
     pub label_angle: Option<f64>,
 }
 
@@ -130,51 +368,59 @@ pub struct FlowObject {
     pub uid: Uid,
     #[serde(rename = "@name")]
     pub name: String,
-    #[serde(rename = "@x")]
+    #[serde(rename = "@x", skip_serializing_if = "Option::is_none")]
     pub x: Option<f64>, // May be aliased
-    #[serde(rename = "@y")]
+    #[serde(rename = "@y", skip_serializing_if = "Option::is_none")]
     pub y: Option<f64>, // May be aliased
     #[serde(rename = "@width")]
     pub width: f64,
     #[serde(rename = "@height")]
     pub height: f64,
-    #[serde(rename = "@color")]
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(rename = "@background")]
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@label_side")]
+    #[serde(rename = "@label_side", skip_serializing_if = "Option::is_none")]
     pub label_side: Option<String>,
-    #[serde(rename = "@label_angle")]
+    #[serde(rename = "@label_angle", skip_serializing_if = "Option::is_none")]
     pub label_angle: Option<f64>,
-    #[serde(rename = "pts")]
+    #[serde(rename = "pts", with = "points")]
     pub pts: Vec<Point>,
 }
 
@@ -188,50 +434,59 @@ pub struct AuxObject {
     pub uid: Uid,
     #[serde(rename = "@name")]
     pub name: String,
-    #[serde(rename = "@x")]
+    #[serde(rename = "@x", skip_serializing_if = "Option::is_none")]
     pub x: Option<f64>, // May be aliased
-    #[serde(rename = "@y")]
+    #[serde(rename = "@y", skip_serializing_if = "Option::is_none")]
     pub y: Option<f64>, // May be aliased
-    #[serde(rename = "@width")]
+    #[serde(rename = "@width", skip_serializing_if = "Option::is_none")]
     pub width: Option<f64>,
-    #[serde(rename = "@height")]
+    #[serde(rename = "@height", skip_serializing_if = "Option::is_none")]
     pub height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shape: Option<Shape>,
-    #[serde(rename = "@color")]
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(rename = "@background")]
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@label_side")]
+    #[serde(rename = "@label_side", skip_serializing_if = "Option::is_none")]
     pub label_side: Option<String>,
-    #[serde(rename = "@label_angle")]
+    #[serde(rename = "@label_angle", skip_serializing_if = "Option::is_none")]
     pub label_angle: Option<f64>,
 }
 
@@ -253,42 +508,51 @@ pub struct ModuleObject {
     pub width: f64,
     #[serde(rename = "@height")]
     pub height: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shape: Option<Shape>,
-    #[serde(rename = "@color")]
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(rename = "@background")]
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@label_side")]
+    #[serde(rename = "@label_side", skip_serializing_if = "Option::is_none")]
     pub label_side: Option<String>,
-    #[serde(rename = "@label_angle")]
+    #[serde(rename = "@label_angle", skip_serializing_if = "Option::is_none")]
     pub label_angle: Option<f64>,
 }
 
@@ -310,41 +574,54 @@ pub struct GroupObject {
     pub x: f64,
     #[serde(rename = "@y")]
     pub y: f64,
-    #[serde(rename = "@color")]
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(rename = "@background")]
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@locked")]
+    #[serde(rename = "@locked", default)]
     pub locked: bool,
-    #[serde(rename = "item", default)]
+    #[serde(
+        rename = "item",
+        default,
+        with = "item_uids",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub items: Vec<Uid>,
 }
 
@@ -371,18 +648,48 @@ pub struct GroupObject {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Polarity {
+    #[serde(rename = "+")]
     Positive,
+    #[serde(rename = "-")]
     Negative,
+    #[serde(rename = "none", alias = "")]
     None,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LineStyle {
     Solid,
     Dashed,
     VendorSpecific(String),
 }
 
+impl<'de> Deserialize<'de> for LineStyle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Ok(match value.as_str() {
+            "solid" => LineStyle::Solid,
+            "dashed" => LineStyle::Dashed,
+            _ => LineStyle::VendorSpecific(value),
+        })
+    }
+}
+
+impl Serialize for LineStyle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            LineStyle::Solid => serializer.serialize_str("solid"),
+            LineStyle::Dashed => serializer.serialize_str("dashed"),
+            LineStyle::VendorSpecific(style) => serializer.serialize_str(style),
+        }
+    }
+}
+
 /// Helper struct for deserializing alias tags within from/to
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AliasTag {
@@ -393,7 +700,11 @@ pub struct AliasTag {
 /// A pointer to a model entity, either by alias or by name
 #[derive(Debug, Clone, PartialEq)]
 pub enum Pointer {
+    /// The alias with this UID, in the same view as the connector.
     Alias(Uid),
+    /// The object drawn for this name. A qualified name such as
+    /// `Economy.GDP` is the ghost of a variable inside a module, and
+    /// attaches to the module's object.
     Name(String),
 }
 
@@ -419,13 +730,27 @@ impl<'de> serde::Deserialize<'de> for Pointer {
             where
                 M: serde::de::MapAccess<'de>,
             {
-                // Try to deserialize as an alias tag
-                if let Ok(Some((key, tag))) = map.next_entry::<String, AliasTag>()
-                    && key == "alias"
-                {
-                    return Ok(Pointer::Alias(tag.uid));
+                // Drain every entry so the deserializer is left at the end
+                // of the element. An alias tag wins over text, which may be
+                // split around comments and other elements
+                let mut alias = None;
+                let mut text = String::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "alias" if alias.is_none() => {
+                            alias = Some(map.next_value::<AliasTag>()?.uid);
+                        }
+                        "$text" => text.push_str(&map.next_value::<String>()?),
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                match (alias, text.trim()) {
+                    (Some(uid), _) => Ok(Pointer::Alias(uid)),
+                    (None, "") => Err(de::Error::custom("Expected alias tag or text content")),
+                    (None, name) => Ok(Pointer::Name(name.to_string())),
                 }
-                Err(de::Error::custom("Expected alias tag or text content"))
             }
 
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -433,7 +758,10 @@ impl<'de> serde::Deserialize<'de> for Pointer {
                 E: de::Error,
             {
                 // Text content means it's a name
-                Ok(Pointer::Name(v.to_string()))
+                match v.trim() {
+                    "" => Err(de::Error::custom("Expected alias tag or text content")),
+                    name => Ok(Pointer::Name(name.to_string())),
+                }
             }
         }
 
@@ -450,8 +778,8 @@ impl serde::Serialize for Pointer {
         match self {
             Pointer::Alias(uid) => {
                 use serde::ser::SerializeStruct;
-                let mut state = serializer.serialize_struct("alias", 1)?;
-                state.serialize_field("@uid", &uid.value)?;
+                let mut state = serializer.serialize_struct("Pointer", 1)?;
+                state.serialize_field("alias", &AliasTag { uid: *uid })?;
                 state.end()
             }
             Pointer::Name(name) => serializer.serialize_str(name),
@@ -469,49 +797,62 @@ pub struct ConnectorObject {
     pub y: f64,
     #[serde(rename = "@angle")]
     pub angle: f64,
-    #[serde(rename = "@line_style")]
+    #[serde(rename = "@line_style", skip_serializing_if = "Option::is_none")]
     pub line_style: Option<LineStyle>,
-    #[serde(rename = "@delay_mark")]
+    #[serde(rename = "@delay_mark", default)]
     pub delay_mark: bool,
-    #[serde(rename = "@color")]
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(rename = "@background")]
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@polarity")]
+    #[serde(rename = "@polarity", skip_serializing_if = "Option::is_none")]
     pub polarity: Option<Polarity>,
     #[serde(rename = "from")]
     pub from: Pointer,
     #[serde(rename = "to")]
     pub to: Pointer,
-    #[serde(rename = "pts")]
+    #[serde(
+        rename = "pts",
+        default,
+        with = "points",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub pts: Vec<Point>,
 }
 
@@ -534,43 +875,52 @@ pub struct AliasObject {
     pub y: f64,
     #[serde(rename = "of")]
     pub of: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shape: Option<Shape>,
     // Additional properties to match the aliased object (optional overrides)
-    #[serde(rename = "@color")]
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(rename = "@background")]
+    #[serde(rename = "@background", skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
+    #[serde(rename = "@z_index", skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
+    #[serde(rename = "@font_family", skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
+    #[serde(rename = "@font_size", skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
+    #[serde(rename = "@font_weight", skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
+    #[serde(rename = "@font_style", skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
+    #[serde(rename = "@text_decoration", skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
+    #[serde(rename = "@text_align", skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
+    #[serde(rename = "@text_background", skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
+    #[serde(
+        rename = "@vertical_text_align",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
+    #[serde(
+        rename = "@text_padding",
+        default,
+        with = "text_padding",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
+    #[serde(rename = "@font_color", skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
+    #[serde(rename = "@text_border_color", skip_serializing_if = "Option::is_none")]
     pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
+    #[serde(rename = "@text_border_width", skip_serializing_if = "Option::is_none")]
     pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
+    #[serde(rename = "@text_border_style", skip_serializing_if = "Option::is_none")]
     pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@label_side")]
+    #[serde(rename = "@label_side", skip_serializing_if = "Option::is_none")]
     pub label_side: Option<String>,
-    #[serde(rename = "@label_angle")]
+    #[serde(rename = "@label_angle", skip_serializing_if = "Option::is_none")]
     pub label_angle: Option<f64>,
 }
 
@@ -601,1403 +951,3 @@ pub struct StackedContainerObject {
     #[serde(rename = "@visible_index")]
     pub visible_index: usize,
 }
-
-// Sliders and knobs are used to change the value of a variable in the model from the interface.  Support for these tags is OPTIONAL. Stocks can only be manipulated by knobs. Iin this case, knobs can only change the stock’s initial value, i.e., knobs attached to stocks MUST NOT be changed in the middle of a simulation run.  Sliders are defined with the <slider> tag and knobs are defined with the <knob> tag; they are otherwise the same.  An example slider tag is shown below:
-
-// <slider x="172" y="114" color="black" width="197" height="43" min="7" max="9" background=”white” z_index=”1” font_family=”Arial” font_size=”9pt” font_weight=”bold” font_style=”italic” text_decoration=”underline” text_align=”center” vertical_text_align=”center” text_padding=”2px” font_color=”blue” text_border_color=”black” text_border_width=”1px” text_border_style=”solid”>
-
-//       <entity name="Converter_1" />
-
-// <reset_to after="one_time_unit">7</reset_to>
-
-// </slider>
-
-//     Show name: OPTIONAL  show_name="…" with true/false (default: true)
-//     Show number:  OPTIONAL show_number="…" with true/false; when the number is visible it MUST be directly editable (default: true)
-//     Show input range: OPTIONAL  show_min_max="…" with true/false (default: true)
-//     Input range REQUIRED:  min="…" and max="…", overriding entity’s input range setting (default:  entity’s setting)
-//     OPTIONAL reset (slider only):  <reset_to> with the value to reset the entity to; it has one attribute that define when to reset the entity’s value:  after="…" with either one_time_unit or one_dt.
-
-// Descriptions of all other display attributes of a slider or knob can be found in Section 6.1.
-
-/// Helper struct for deserializing entity tags
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-struct EntityTag {
-    #[serde(rename = "@name")]
-    name: String,
-}
-
-/// Helper struct for deserializing reset_to tags
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-struct ResetToTag {
-    #[serde(rename = "@after")]
-    after: String,
-    #[serde(rename = "#text")]
-    value: f64,
-}
-
-fn default_true() -> bool {
-    true
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-struct RawSliderObject {
-    #[serde(rename = "@uid")]
-    uid: Uid,
-    #[serde(rename = "@x")]
-    x: f64,
-    #[serde(rename = "@y")]
-    y: f64,
-    #[serde(rename = "@width")]
-    width: f64,
-    #[serde(rename = "@height")]
-    height: f64,
-    #[serde(rename = "@color")]
-    color: Option<Color>,
-    #[serde(rename = "@background")]
-    background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@min")]
-    min: f64,
-    #[serde(rename = "@max")]
-    max: f64,
-    #[serde(rename = "@show_name", default = "default_true")]
-    show_name: bool,
-    #[serde(rename = "@show_number", default = "default_true")]
-    show_number: bool,
-    #[serde(rename = "@show_min_max", default = "default_true")]
-    show_min_max: bool,
-    #[serde(rename = "entity")]
-    entity: Option<EntityTag>,
-    #[serde(rename = "reset_to")]
-    reset_to: Option<ResetToTag>,
-}
-
-impl From<RawSliderObject> for SliderObject {
-    fn from(raw: RawSliderObject) -> Self {
-        SliderObject {
-            uid: raw.uid,
-            x: raw.x,
-            y: raw.y,
-            width: raw.width,
-            height: raw.height,
-            color: raw.color,
-            background: raw.background,
-            z_index: raw.z_index,
-            font_family: raw.font_family,
-            font_size: raw.font_size,
-            font_weight: raw.font_weight,
-            font_style: raw.font_style,
-            text_decoration: raw.text_decoration,
-            text_align: raw.text_align,
-            text_background: raw.text_background,
-            vertical_text_align: raw.vertical_text_align,
-            text_padding: raw.text_padding,
-            font_color: raw.font_color,
-            text_border_color: raw.text_border_color,
-            text_border_width: raw.text_border_width,
-            text_border_style: raw.text_border_style,
-            entity_name: raw.entity.map(|e| e.name).unwrap_or_default(),
-            min: raw.min,
-            max: raw.max,
-            show_name: raw.show_name,
-            show_number: raw.show_number,
-            show_min_max: raw.show_min_max,
-            reset_to: raw.reset_to.map(|r| (r.value, r.after)),
-        }
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct SliderObject {
-    pub uid: Uid,
-    pub x: f64,
-    pub y: f64,
-    pub width: f64,
-    pub height: f64,
-    pub color: Option<Color>,
-    pub background: Option<Color>,
-    pub z_index: Option<i32>,
-    pub font_family: Option<String>,
-    pub font_size: Option<f64>,
-    pub font_weight: Option<FontWeight>,
-    pub font_style: Option<FontStyle>,
-    pub text_decoration: Option<TextDecoration>,
-    pub text_align: Option<TextAlign>,
-    pub text_background: Option<Color>,
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    pub text_padding: TextPadding,
-    pub font_color: Option<Color>,
-    pub text_border_color: Option<Color>,
-    pub text_border_width: Option<BorderWidth>,
-    pub text_border_style: Option<BorderStyle>,
-    pub entity_name: String,
-    pub min: f64,
-    pub max: f64,
-    pub show_name: bool,
-    pub show_number: bool,
-    pub show_min_max: bool,
-    pub reset_to: Option<(f64, String)>, // (value, after)
-}
-
-impl<'de> serde::Deserialize<'de> for SliderObject {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let raw = RawSliderObject::deserialize(deserializer)?;
-        Ok(SliderObject::from(raw))
-    }
-}
-
-impl serde::Serialize for SliderObject {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("slider", 25)?;
-
-        state.serialize_field("@uid", &self.uid.value)?;
-        state.serialize_field("@x", &self.x)?;
-        state.serialize_field("@y", &self.y)?;
-        state.serialize_field("@width", &self.width)?;
-        state.serialize_field("@height", &self.height)?;
-        if let Some(color) = &self.color {
-            state.serialize_field("@color", color)?;
-        }
-        if let Some(background) = &self.background {
-            state.serialize_field("@background", background)?;
-        }
-        if let Some(z_index) = &self.z_index {
-            state.serialize_field("@z_index", z_index)?;
-        }
-        if let Some(font_family) = &self.font_family {
-            state.serialize_field("@font_family", font_family)?;
-        }
-        if let Some(font_size) = &self.font_size {
-            state.serialize_field("@font_size", font_size)?;
-        }
-        if let Some(font_weight) = &self.font_weight {
-            state.serialize_field("@font_weight", font_weight)?;
-        }
-        if let Some(font_style) = &self.font_style {
-            state.serialize_field("@font_style", font_style)?;
-        }
-        if let Some(text_decoration) = &self.text_decoration {
-            state.serialize_field("@text_decoration", text_decoration)?;
-        }
-        if let Some(text_align) = &self.text_align {
-            state.serialize_field("@text_align", text_align)?;
-        }
-        if let Some(text_background) = &self.text_background {
-            state.serialize_field("@text_background", text_background)?;
-        }
-        if let Some(vertical_text_align) = &self.vertical_text_align {
-            state.serialize_field("@vertical_text_align", vertical_text_align)?;
-        }
-        if let Some(text_padding) = &self.text_padding {
-            state.serialize_field("@text_padding", text_padding)?;
-        }
-        if let Some(font_color) = &self.font_color {
-            state.serialize_field("@font_color", font_color)?;
-        }
-        if let Some(text_border_color) = &self.text_border_color {
-            state.serialize_field("@text_border_color", text_border_color)?;
-        }
-        if let Some(text_border_width) = &self.text_border_width {
-            state.serialize_field("@text_border_width", text_border_width)?;
-        }
-        if let Some(text_border_style) = &self.text_border_style {
-            state.serialize_field("@text_border_style", text_border_style)?;
-        }
-        state.serialize_field("@min", &self.min)?;
-        state.serialize_field("@max", &self.max)?;
-        if !self.show_name {
-            state.serialize_field("@show_name", &self.show_name)?;
-        }
-        if !self.show_number {
-            state.serialize_field("@show_number", &self.show_number)?;
-        }
-        if !self.show_min_max {
-            state.serialize_field("@show_min_max", &self.show_min_max)?;
-        }
-
-        // Serialize entity tag
-        state.serialize_field(
-            "entity",
-            &EntityTag {
-                name: self.entity_name.clone(),
-            },
-        )?;
-
-        // Serialize reset_to if present
-        if let Some((value, after)) = &self.reset_to {
-            state.serialize_field(
-                "reset_to",
-                &ResetToTag {
-                    after: after.clone(),
-                    value: *value,
-                },
-            )?;
-        }
-
-        state.end()
-    }
-}
-
-// Knobs are the same as sliders but for stocks
-pub type KnobObject = SliderObject;
-
-// Switches and Radio Buttons (Option Groups)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct SwitchObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@label_side")]
-    pub label_side: Option<String>,
-    #[serde(rename = "@label_angle")]
-    pub label_angle: Option<f64>,
-    #[serde(rename = "@show_name")]
-    pub show_name: bool,
-    #[serde(rename = "@switch_style")]
-    pub switch_style: SwitchStyle,
-    #[serde(rename = "@clicking_sound")]
-    pub clicking_sound: bool,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: Option<String>,
-    #[serde(rename = "@entity_value")]
-    pub entity_value: Option<f64>,
-    #[serde(rename = "@group_name")]
-    pub group_name: Option<String>,
-    #[serde(rename = "@module_name")]
-    pub module_name: Option<String>,
-    pub reset_to: Option<(f64, String)>, // (value, after) - handled via custom deserialization if needed
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum SwitchStyle {
-    Toggle,
-    PushButton,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct OptionsObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@layout")]
-    pub layout: OptionsLayout,
-    #[serde(rename = "@horizontal_spacing")]
-    pub horizontal_spacing: f64,
-    #[serde(rename = "@vertical_spacing")]
-    pub vertical_spacing: f64,
-    #[serde(rename = "entity", default)]
-    pub entities: Vec<OptionEntity>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum OptionsLayout {
-    Vertical,
-    Horizontal,
-    Grid,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct OptionEntity {
-    #[serde(rename = "@name")]
-    pub entity_name: String,
-    #[serde(rename = "@index")]
-    pub index: Option<String>,
-    #[serde(rename = "#text")]
-    pub value: f64,
-}
-
-// Numeric Inputs and List Input Devices
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct NumericInputObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: String,
-    #[serde(rename = "@entity_index")]
-    pub entity_index: Option<String>,
-    #[serde(rename = "@min")]
-    pub min: f64,
-    #[serde(rename = "@max")]
-    pub max: f64,
-    #[serde(rename = "@precision")]
-    pub precision: Option<f64>,
-    #[serde(rename = "@value")]
-    pub value: f64,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ListInputObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@name")]
-    pub name: String,
-    #[serde(rename = "@column_width")]
-    pub column_width: f64,
-    #[serde(rename = "numeric_input", default)]
-    pub numeric_inputs: Vec<NumericInputObject>,
-}
-
-// Graphical Inputs
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GraphicalInputObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: String,
-    #[serde(rename = "gf")]
-    pub graphical_function: Option<GraphicalFunctionData>,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GraphicalFunctionData {
-    pub xscale_min: f64,
-    pub xscale_max: f64,
-    pub ypts: Vec<f64>,
-}
-
-// Numeric Displays
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct NumericDisplayObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: String,
-    #[serde(rename = "@show_name")]
-    pub show_name: bool,
-    #[serde(rename = "@retain_ending_value")]
-    pub retain_ending_value: bool,
-    #[serde(rename = "@precision")]
-    pub precision: Option<f64>,
-    #[serde(rename = "@delimit_000s")]
-    pub delimit_000s: bool,
-}
-
-// Lamps and Gauges
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct LampObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: String,
-    #[serde(rename = "@show_name")]
-    pub show_name: bool,
-    #[serde(rename = "@retain_ending_value")]
-    pub retain_ending_value: bool,
-    #[serde(rename = "@flash_on_panic")]
-    pub flash_on_panic: bool,
-    #[serde(rename = "zone", default)]
-    pub zones: Vec<Zone>,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GaugeObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: String,
-    #[serde(rename = "@show_name")]
-    pub show_name: bool,
-    #[serde(rename = "@show_number")]
-    pub show_number: bool,
-    #[serde(rename = "@retain_ending_value")]
-    pub retain_ending_value: bool,
-    #[serde(rename = "zone", default)]
-    pub zones: Vec<Zone>,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Zone {
-    #[serde(rename = "@type")]
-    pub zone_type: ZoneType,
-    #[serde(rename = "@color")]
-    pub color: Color,
-    #[serde(rename = "@min")]
-    pub min: f64,
-    #[serde(rename = "@max")]
-    pub max: f64,
-    #[serde(rename = "@sound")]
-    pub sound: Option<String>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ZoneType {
-    Normal,
-    Caution,
-    Panic,
-}
-
-// Graphs
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GraphObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@graph_type")]
-    pub graph_type: GraphType,
-    #[serde(rename = "@title")]
-    pub title: Option<String>,
-    #[serde(rename = "@doc")]
-    pub doc: Option<String>,
-    #[serde(rename = "@show_grid")]
-    pub show_grid: bool,
-    #[serde(rename = "@num_x_grid_lines")]
-    pub num_x_grid_lines: u32,
-    #[serde(rename = "@num_y_grid_lines")]
-    pub num_y_grid_lines: u32,
-    #[serde(rename = "@num_x_labels")]
-    pub num_x_labels: u32,
-    #[serde(rename = "@num_y_labels")]
-    pub num_y_labels: u32,
-    #[serde(rename = "@x_axis_title")]
-    pub x_axis_title: Option<String>,
-    #[serde(rename = "@right_axis_title")]
-    pub right_axis_title: Option<String>,
-    #[serde(rename = "@right_axis_auto_scale")]
-    pub right_axis_auto_scale: bool,
-    #[serde(rename = "@right_axis_multi_scale")]
-    pub right_axis_multi_scale: bool,
-    #[serde(rename = "@left_axis_title")]
-    pub left_axis_title: Option<String>,
-    #[serde(rename = "@left_axis_auto_scale")]
-    pub left_axis_auto_scale: bool,
-    #[serde(rename = "@left_axis_multi_scale")]
-    pub left_axis_multi_scale: bool,
-    #[serde(rename = "@plot_numbers")]
-    pub plot_numbers: bool,
-    #[serde(rename = "@comparative")]
-    pub comparative: bool,
-    #[serde(rename = "@from")]
-    pub from: Option<f64>,
-    #[serde(rename = "@to")]
-    pub to: Option<f64>,
-    #[serde(rename = "plot", default)]
-    pub plots: Vec<Plot>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum GraphType {
-    TimeSeries,
-    Scatter,
-    Bar,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Plot {
-    #[serde(rename = "@index")]
-    pub index: u32,
-    #[serde(rename = "@pen_width")]
-    pub pen_width: f64,
-    #[serde(rename = "@pen_style")]
-    pub pen_style: PenStyle,
-    #[serde(rename = "@show_y_axis")]
-    pub show_y_axis: bool,
-    #[serde(rename = "@title")]
-    pub title: String,
-    #[serde(rename = "@right_axis")]
-    pub right_axis: bool,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: String,
-    #[serde(rename = "@precision")]
-    pub precision: Option<f64>,
-    pub scale: Option<PlotScale>,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum PenStyle {
-    Solid,
-    Dotted,
-    Dashed,
-    DotDashed,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct PlotScale {
-    #[serde(rename = "@min")]
-    pub min: f64,
-    #[serde(rename = "@max")]
-    pub max: f64,
-}
-
-// Tables
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct TableObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@title")]
-    pub title: Option<String>,
-    #[serde(rename = "@doc")]
-    pub doc: Option<String>,
-    #[serde(rename = "@orientation")]
-    pub orientation: TableOrientation,
-    #[serde(rename = "@column_width")]
-    pub column_width: f64,
-    #[serde(rename = "@blank_column_width")]
-    pub blank_column_width: Option<f64>,
-    #[serde(rename = "@interval")]
-    pub interval: String,
-    #[serde(rename = "@report_balances")]
-    pub report_balances: ReportBalances,
-    #[serde(rename = "@report_flows")]
-    pub report_flows: ReportFlows,
-    #[serde(rename = "@comparative")]
-    pub comparative: bool,
-    #[serde(rename = "@wrap_text")]
-    pub wrap_text: bool,
-    #[serde(rename = "item", default)]
-    pub items: Vec<TableItem>,
-    // Header style attributes (prefixed with "header_")
-    #[serde(rename = "@header_font_family")]
-    pub header_font_family: Option<String>,
-    #[serde(rename = "@header_font_size")]
-    pub header_font_size: Option<f64>,
-    #[serde(rename = "@header_font_weight")]
-    pub header_font_weight: Option<FontWeight>,
-    #[serde(rename = "@header_font_style")]
-    pub header_font_style: Option<FontStyle>,
-    #[serde(rename = "@header_text_decoration")]
-    pub header_text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@header_text_align")]
-    pub header_text_align: Option<TextAlign>,
-    #[serde(rename = "@header_vertical_text_align")]
-    pub header_vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@header_text_background")]
-    pub header_text_background: Option<Color>,
-    #[serde(rename = "@header_text_padding")]
-    pub header_text_padding: TextPadding,
-    #[serde(rename = "@header_font_color")]
-    pub header_font_color: Option<Color>,
-    #[serde(rename = "@header_text_border_color")]
-    pub header_text_border_color: Option<Color>,
-    #[serde(rename = "@header_text_border_width")]
-    pub header_text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@header_text_border_style")]
-    pub header_text_border_style: Option<BorderStyle>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum TableOrientation {
-    Horizontal,
-    Vertical,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ReportBalances {
-    Beginning,
-    Ending,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ReportFlows {
-    Instantaneous,
-    Summed,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct TableItem {
-    #[serde(rename = "@type")]
-    pub item_type: TableItemType,
-    #[serde(rename = "@entity_name")]
-    pub entity_name: Option<String>,
-    #[serde(rename = "@precision")]
-    pub precision: Option<f64>,
-    #[serde(rename = "@delimit_000s")]
-    pub delimit_000s: bool,
-    #[serde(rename = "@column_width")]
-    pub column_width: Option<f64>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum TableItemType {
-    Time,
-    Variable,
-    Blank,
-}
-
-// Text Boxes
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct TextBoxObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@appearance")]
-    pub appearance: TextBoxAppearance,
-    #[serde(rename = "#text")]
-    pub content: String,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum TextBoxAppearance {
-    Transparent,
-    Normal,
-}
-
-// Graphics Frames
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GraphicsFrameObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@border_color")]
-    pub border_color: Option<Color>,
-    #[serde(rename = "@border_style")]
-    pub border_style: Option<BorderStyle>,
-    #[serde(rename = "@border_width")]
-    pub border_width: Option<BorderWidth>,
-    pub content: GraphicsFrameContent,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum GraphicsFrameContent {
-    Image(ImageContent),
-    Video(VideoContent),
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ImageContent {
-    pub size_to_parent: bool,
-    pub width: f64,
-    pub height: f64,
-    pub resource: Option<String>,
-    pub data: Option<String>, // base64 encoded data URI
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct VideoContent {
-    pub size_to_parent: bool,
-    pub width: f64,
-    pub height: f64,
-    pub resource: String,
-}
-
-// Buttons
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ButtonObject {
-    #[serde(rename = "@uid")]
-    pub uid: Uid,
-    #[serde(rename = "@x")]
-    pub x: f64,
-    #[serde(rename = "@y")]
-    pub y: f64,
-    #[serde(rename = "@width")]
-    pub width: f64,
-    #[serde(rename = "@height")]
-    pub height: f64,
-    #[serde(rename = "@color")]
-    pub color: Option<Color>,
-    #[serde(rename = "@background")]
-    pub background: Option<Color>,
-    #[serde(rename = "@z_index")]
-    pub z_index: Option<i32>,
-    #[serde(rename = "@font_family")]
-    pub font_family: Option<String>,
-    #[serde(rename = "@font_size")]
-    pub font_size: Option<f64>,
-    #[serde(rename = "@font_weight")]
-    pub font_weight: Option<FontWeight>,
-    #[serde(rename = "@font_style")]
-    pub font_style: Option<FontStyle>,
-    #[serde(rename = "@text_decoration")]
-    pub text_decoration: Option<TextDecoration>,
-    #[serde(rename = "@text_align")]
-    pub text_align: Option<TextAlign>,
-    #[serde(rename = "@text_background")]
-    pub text_background: Option<Color>,
-    #[serde(rename = "@vertical_text_align")]
-    pub vertical_text_align: Option<VerticalTextAlign>,
-    #[serde(rename = "@text_padding")]
-    pub text_padding: TextPadding,
-    #[serde(rename = "@font_color")]
-    pub font_color: Option<Color>,
-    #[serde(rename = "@text_border_color")]
-    pub text_border_color: Option<Color>,
-    #[serde(rename = "@text_border_width")]
-    pub text_border_width: Option<BorderWidth>,
-    #[serde(rename = "@text_border_style")]
-    pub text_border_style: Option<BorderStyle>,
-    #[serde(rename = "@appearance")]
-    pub appearance: ButtonAppearance,
-    #[serde(rename = "@style")]
-    pub style: ButtonStyle,
-    #[serde(rename = "@label")]
-    pub label: Option<String>,
-    pub image: Option<ImageContent>,
-    #[serde(rename = "@clicking_sound")]
-    pub clicking_sound: bool,
-    #[serde(rename = "@sound")]
-    pub sound: Option<String>,
-    pub popup: Option<PopupContent>,
-    pub link: Option<Link>,
-    pub menu_action: Option<MenuAction>,
-    pub switch_action: Option<SwitchAction>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ButtonAppearance {
-    Opaque,
-    Transparent,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ButtonStyle {
-    Square,
-    Rounded,
-    Capsule,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum PopupContent {
-    TextBox(Box<TextBoxObject>),
-    Image(ImageContent),
-    Video(VideoContent),
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Link {
-    pub x: f64,
-    pub y: f64,
-    pub zoom: f64,
-    pub effect: Option<LinkEffect>,
-    pub to_black: bool,
-    pub target: LinkTarget,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum LinkEffect {
-    Dissolve,
-    Checkerboard,
-    Bars,
-    WipeLeft,
-    WipeRight,
-    WipeTop,
-    WipeBottom,
-    WipeClockwise,
-    WipeCounterclockwise,
-    IrisIn,
-    IrisOut,
-    DoorsClose,
-    DoorsOpen,
-    VenetianLeft,
-    VenetianRight,
-    VenetianTop,
-    VenetianBottom,
-    PushBottom,
-    PushTop,
-    PushLeft,
-    PushRight,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum LinkTarget {
-    View {
-        view_type: String,
-        order: String,
-    },
-    Page {
-        view_type: String,
-        order: String,
-        page: String,
-    },
-    NextPage,
-    PreviousPage,
-    HomePage,
-    NextView,
-    PreviousView,
-    HomeView,
-    BackPage,
-    BackView,
-    Url(String),
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum MenuAction {
-    File(FileAction),
-    Printing(PrintingAction),
-    Simulation(SimulationAction),
-    Restore(RestoreAction),
-    Data(DataAction),
-    Miscellaneous(MiscellaneousAction),
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum FileAction {
-    Open,
-    Close,
-    Save,
-    SaveAs,
-    SaveAsImage,
-    Revert,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum PrintingAction {
-    PrintSetup,
-    Print,
-    PrintScreen,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum SimulationAction {
-    Run,
-    Pause,
-    Resume,
-    Stop,
-    RunRestore,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum RestoreAction {
-    RestoreAll,
-    RestoreSliders,
-    RestoreKnobs,
-    RestoreListInputs,
-    RestoreGraphicalInputs,
-    RestoreSwitches,
-    RestoreNumericDisplays,
-    RestoreGraphsTables,
-    RestoreLampsGauges,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum DataAction {
-    DataManager,
-    SaveDataNow {
-        run_name: String,
-    },
-    ImportNow {
-        resource: String,
-        worksheet: Option<String>,
-        all: bool,
-    },
-    ExportNow {
-        resource: String,
-        worksheet: Option<String>,
-        all: bool,
-    },
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum MiscellaneousAction {
-    Exit,
-    Find,
-    RunSpecs,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct SwitchAction {
-    pub entity_name: Option<String>,
-    pub group_name: Option<String>,
-    pub module_name: Option<String>,
-    pub value: f64,
-}
@@ -16,8 +16,12 @@
 // </style>
 // Note that when style information applies to a specific object, that style cannot be overridden at a lower level (e.g., within a view) by a change to the overall style (i.e., by the options on the <style> tag). Using the example above, to override the color of connectors at a lower level (e.g., the Display), the <connector> tag must explicitly appear in that level’s style block. If it does not appear there, connectors will be magenta at that level by default, even if the style block at that level sets the default color of all objects to green. In other words, object-specific styles at any level above an object take precedence over an overall style defined at any lower level.
 
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "style")]
 /// Style information that cascades across multiple levels:
 /// 1. Styles for the given entity
 /// 2. Styles for all entities in a specific view
@@ -27,74 +31,133 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Style {
     /// Global style attributes that apply to all objects
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border_width: Option<BorderWidth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border_style: Option<BorderStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub padding: Option<Padding>,
     /// Object-specific style overrides
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stock: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub flow: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub aux: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub module: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub group: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub connector: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alias: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub slider: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub knob: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub switch: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub numeric_input: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub list_input: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub graphical_input: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub numeric_display: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lamp: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gauge: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub graph: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub table: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_box: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub graphics_frame: Option<ObjectStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub button: Option<ObjectStyle>,
 }
 
+#[cfg(feature = "style")]
 /// Style attributes for a specific object type
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ObjectStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub background: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub z_index: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border_width: Option<BorderWidth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border_style: Option<BorderStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_style: Option<FontStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_weight: Option<FontWeight>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_decoration: Option<TextDecoration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_align: Option<TextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vertical_text_align: Option<VerticalTextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text_background: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub font_size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub padding: Option<Padding>,
 }
 
+#[cfg(feature = "style")]
 /// Padding specification supporting 1-4 values
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Padding {
     pub top: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub right: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bottom: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub left: Option<f64>,
 }
 
@@ -131,6 +194,7 @@ pub struct Padding {
 
 // ** The specification for the padding attributes appears in Section 5.2.1
 
+#[cfg(feature = "style")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StyleTag {
     Color(Color),
@@ -156,13 +220,73 @@ pub enum StyleTag {
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A color, written as a hex code (`#ff0000`), one of the predefined color
+/// names (`red`) or any other CSS color value.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Color {
     Hex(String),
     Predefined(PredefinedColor),
+    /// Any other CSS color value, e.g. `magenta` or `rgb(255, 0, 255)`, kept as written.
+    Other(String),
+}
+
+impl Color {
+    /// Returns the hex code of the color, if it is a hex or predefined color.
+    pub fn to_hex(&self) -> Option<&str> {
+        match self {
+            Color::Hex(hex) => Some(hex),
+            Color::Predefined(color) => Some(color.to_hex()),
+            Color::Other(_) => None,
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Color cannot be empty".to_string());
+        }
+        if let Some(hex) = s.strip_prefix('#') {
+            if matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Ok(Color::Hex(s.to_string()));
+            }
+            return Err(format!("Invalid hex color '{}'", s));
+        }
+        Ok(PredefinedColor::ALL
+            .into_iter()
+            .find(|color| color.name().eq_ignore_ascii_case(s))
+            .map_or_else(|| Color::Other(s.to_string()), Color::Predefined))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Hex(hex) => f.write_str(hex),
+            Color::Predefined(color) => f.write_str(color.name()),
+            Color::Other(other) => f.write_str(other),
+        }
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PredefinedColor {
     Aqua,
     Black,
@@ -183,7 +307,48 @@ pub enum PredefinedColor {
 }
 
 impl PredefinedColor {
-    pub fn to_hex(&self) -> &str {
+    pub const ALL: [PredefinedColor; 16] = [
+        PredefinedColor::Aqua,
+        PredefinedColor::Black,
+        PredefinedColor::Blue,
+        PredefinedColor::Fuchsia,
+        PredefinedColor::Gray,
+        PredefinedColor::Green,
+        PredefinedColor::Lime,
+        PredefinedColor::Maroon,
+        PredefinedColor::Navy,
+        PredefinedColor::Olive,
+        PredefinedColor::Purple,
+        PredefinedColor::Red,
+        PredefinedColor::Silver,
+        PredefinedColor::Teal,
+        PredefinedColor::White,
+        PredefinedColor::Yellow,
+    ];
+
+    /// The name of the color as written in XMILE files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PredefinedColor::Aqua => "aqua",
+            PredefinedColor::Black => "black",
+            PredefinedColor::Blue => "blue",
+            PredefinedColor::Fuchsia => "fuchsia",
+            PredefinedColor::Gray => "gray",
+            PredefinedColor::Green => "green",
+            PredefinedColor::Lime => "lime",
+            PredefinedColor::Maroon => "maroon",
+            PredefinedColor::Navy => "navy",
+            PredefinedColor::Olive => "olive",
+            PredefinedColor::Purple => "purple",
+            PredefinedColor::Red => "red",
+            PredefinedColor::Silver => "silver",
+            PredefinedColor::Teal => "teal",
+            PredefinedColor::White => "white",
+            PredefinedColor::Yellow => "yellow",
+        }
+    }
+
+    pub fn to_hex(&self) -> &'static str {
         match self {
             PredefinedColor::Aqua => "#00FFFF",
             PredefinedColor::Black => "#000000",
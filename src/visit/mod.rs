@@ -0,0 +1,397 @@
+//! Traversal of the whole object model of a file.
+//!
+//! [`XmileVisitor`] has a method for each kind of node, visited in the order
+//! header, models, variables, the expressions of each variable, views and
+//! finally macros. Every method defaults to the `walk_*` function of the same
+//! name, which visits the children of the node, so a visitor only overrides
+//! the nodes it is interested in. An overriding method calls the `walk_*`
+//! function itself to keep descending. [`XmileVisitorMut`] is the same over
+//! mutable references, for transformations such as renames.
+//!
+//! ```rust
+//! use xmile::equation::Expression;
+//! use xmile::visit::XmileVisitor;
+//! use xmile::xml::XmileFile;
+//!
+//! #[derive(Default)]
+//! struct CountExpressions(usize);
+//!
+//! impl XmileVisitor for CountExpressions {
+//!     fn visit_expression(&mut self, _expression: &Expression) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <model><variables>
+//!         <stock name="Population"><eqn>100</eqn><inflow>births</inflow></stock>
+//!         <flow name="births"><eqn>Population * 0.1</eqn></flow>
+//!     </variables></model>
+//! </xmile>"#).unwrap();
+//! let mut count = CountExpressions::default();
+//! count.visit_file(&file);
+//! assert_eq!(count.0, 2);
+//! ```
+
+use crate::equation::Expression;
+use crate::header::Header;
+use crate::model::vars::Variable;
+use crate::model::vars::stock::Stock;
+use crate::xml::schema::{Model, XmileFile};
+
+#[cfg(feature = "macros")]
+use crate::r#macro::Macro;
+#[cfg(feature = "views")]
+use crate::view::View;
+
+/// Visits the nodes of a file by shared reference.
+pub trait XmileVisitor {
+    fn visit_file(&mut self, file: &XmileFile) {
+        walk_file(self, file);
+    }
+
+    fn visit_header(&mut self, _header: &Header) {}
+
+    fn visit_model(&mut self, model: &Model) {
+        walk_model(self, model);
+    }
+
+    fn visit_variable(&mut self, variable: &Variable) {
+        walk_variable(self, variable);
+    }
+
+    /// Visits an equation of a variable or macro. Subexpressions are not
+    /// visited separately.
+    fn visit_expression(&mut self, _expression: &Expression) {}
+
+    #[cfg(feature = "views")]
+    fn visit_view(&mut self, _view: &View) {}
+
+    #[cfg(feature = "macros")]
+    fn visit_macro(&mut self, r#macro: &Macro) {
+        walk_macro(self, r#macro);
+    }
+}
+
+/// Visits the nodes of a file by mutable reference.
+pub trait XmileVisitorMut {
+    fn visit_file_mut(&mut self, file: &mut XmileFile) {
+        walk_file_mut(self, file);
+    }
+
+    fn visit_header_mut(&mut self, _header: &mut Header) {}
+
+    fn visit_model_mut(&mut self, model: &mut Model) {
+        walk_model_mut(self, model);
+    }
+
+    fn visit_variable_mut(&mut self, variable: &mut Variable) {
+        walk_variable_mut(self, variable);
+    }
+
+    /// Visits an equation of a variable or macro. Subexpressions are not
+    /// visited separately.
+    fn visit_expression_mut(&mut self, _expression: &mut Expression) {}
+
+    #[cfg(feature = "views")]
+    fn visit_view_mut(&mut self, _view: &mut View) {}
+
+    #[cfg(feature = "macros")]
+    fn visit_macro_mut(&mut self, r#macro: &mut Macro) {
+        walk_macro_mut(self, r#macro);
+    }
+}
+
+/// Visits the header, the models and the macros of `file`.
+pub fn walk_file<V: XmileVisitor + ?Sized>(visitor: &mut V, file: &XmileFile) {
+    visitor.visit_header(&file.header);
+    for model in &file.models {
+        visitor.visit_model(model);
+    }
+    #[cfg(feature = "macros")]
+    for r#macro in &file.macros {
+        visitor.visit_macro(r#macro);
+    }
+}
+
+/// Visits the variables and then the views of `model`.
+pub fn walk_model<V: XmileVisitor + ?Sized>(visitor: &mut V, model: &Model) {
+    for variable in &model.variables.variables {
+        visitor.visit_variable(variable);
+    }
+    #[cfg(feature = "views")]
+    if let Some(views) = &model.views {
+        for view in &views.views {
+            visitor.visit_view(view);
+        }
+    }
+}
+
+/// Visits the equations of `variable`: the equation or initial equation
+/// first, then any other equations of a conveyor, then those of each array
+/// element.
+pub fn walk_variable<V: XmileVisitor + ?Sized>(visitor: &mut V, variable: &Variable) {
+    for expression in expressions(variable) {
+        visitor.visit_expression(expression);
+    }
+}
+
+/// Visits the equation and then the variables of `macro`.
+#[cfg(feature = "macros")]
+pub fn walk_macro<V: XmileVisitor + ?Sized>(visitor: &mut V, r#macro: &Macro) {
+    visitor.visit_expression(&r#macro.eqn);
+    for variable in r#macro.variables.iter().flatten() {
+        visitor.visit_variable(variable);
+    }
+}
+
+/// Visits the header, the models and the macros of `file`.
+pub fn walk_file_mut<V: XmileVisitorMut + ?Sized>(visitor: &mut V, file: &mut XmileFile) {
+    visitor.visit_header_mut(&mut file.header);
+    for model in &mut file.models {
+        visitor.visit_model_mut(model);
+    }
+    #[cfg(feature = "macros")]
+    for r#macro in &mut file.macros {
+        visitor.visit_macro_mut(r#macro);
+    }
+}
+
+/// Visits the variables and then the views of `model`.
+pub fn walk_model_mut<V: XmileVisitorMut + ?Sized>(visitor: &mut V, model: &mut Model) {
+    for variable in &mut model.variables.variables {
+        visitor.visit_variable_mut(variable);
+    }
+    #[cfg(feature = "views")]
+    if let Some(views) = &mut model.views {
+        for view in &mut views.views {
+            visitor.visit_view_mut(view);
+        }
+    }
+}
+
+/// Visits the equations of `variable` in the same order as [`walk_variable`].
+pub fn walk_variable_mut<V: XmileVisitorMut + ?Sized>(visitor: &mut V, variable: &mut Variable) {
+    for expression in expressions_mut(variable) {
+        visitor.visit_expression_mut(expression);
+    }
+}
+
+/// Visits the equation and then the variables of `macro`.
+#[cfg(feature = "macros")]
+pub fn walk_macro_mut<V: XmileVisitorMut + ?Sized>(visitor: &mut V, r#macro: &mut Macro) {
+    visitor.visit_expression_mut(&mut r#macro.eqn);
+    for variable in r#macro.variables.iter_mut().flatten() {
+        visitor.visit_variable_mut(variable);
+    }
+}
+
+fn expressions(variable: &Variable) -> Vec<&Expression> {
+    let mut expressions = Vec::new();
+    match variable {
+        Variable::Auxiliary(aux) => {
+            expressions.push(&aux.equation);
+            #[cfg(feature = "arrays")]
+            push_elements(&mut expressions, &aux.elements);
+        }
+        Variable::Stock(stock) => match stock.as_ref() {
+            Stock::Basic(stock) => {
+                expressions.push(&stock.initial_equation);
+                #[cfg(feature = "arrays")]
+                push_elements(&mut expressions, &stock.elements);
+            }
+            Stock::Conveyor(stock) => {
+                expressions.push(&stock.initial_equation);
+                expressions.push(&stock.length);
+                expressions.extend(
+                    [
+                        &stock.capacity,
+                        &stock.inflow_limit,
+                        &stock.sample,
+                        &stock.arrest_value,
+                    ]
+                    .into_iter()
+                    .flatten(),
+                );
+                #[cfg(feature = "arrays")]
+                push_elements(&mut expressions, &stock.elements);
+            }
+            Stock::Queue(stock) => {
+                expressions.push(&stock.initial_equation);
+                #[cfg(feature = "arrays")]
+                push_elements(&mut expressions, &stock.elements);
+            }
+        },
+        Variable::Flow(flow) => {
+            expressions.extend(&flow.equation);
+            #[cfg(feature = "arrays")]
+            push_elements(&mut expressions, &flow.elements);
+        }
+        Variable::GraphicalFunction(gf) => {
+            expressions.extend(&gf.equation);
+            #[cfg(feature = "arrays")]
+            push_elements(&mut expressions, &gf.elements);
+        }
+        #[cfg(feature = "submodels")]
+        Variable::Module(_) => {}
+        Variable::Group(_) => {}
+    }
+    expressions
+}
+
+#[cfg(feature = "arrays")]
+fn push_elements<'a>(
+    expressions: &mut Vec<&'a Expression>,
+    elements: &'a [crate::model::vars::array::ArrayElement],
+) {
+    for element in elements {
+        expressions.extend(&element.eqn);
+        if let Some(gf) = &element.gf {
+            expressions.extend(&gf.equation);
+        }
+    }
+}
+
+fn expressions_mut(variable: &mut Variable) -> Vec<&mut Expression> {
+    let mut expressions = Vec::new();
+    match variable {
+        Variable::Auxiliary(aux) => {
+            expressions.push(&mut aux.equation);
+            #[cfg(feature = "arrays")]
+            push_elements_mut(&mut expressions, &mut aux.elements);
+        }
+        Variable::Stock(stock) => match stock.as_mut() {
+            Stock::Basic(stock) => {
+                expressions.push(&mut stock.initial_equation);
+                #[cfg(feature = "arrays")]
+                push_elements_mut(&mut expressions, &mut stock.elements);
+            }
+            Stock::Conveyor(stock) => {
+                expressions.push(&mut stock.initial_equation);
+                expressions.push(&mut stock.length);
+                expressions.extend(
+                    [
+                        &mut stock.capacity,
+                        &mut stock.inflow_limit,
+                        &mut stock.sample,
+                        &mut stock.arrest_value,
+                    ]
+                    .into_iter()
+                    .flatten(),
+                );
+                #[cfg(feature = "arrays")]
+                push_elements_mut(&mut expressions, &mut stock.elements);
+            }
+            Stock::Queue(stock) => {
+                expressions.push(&mut stock.initial_equation);
+                #[cfg(feature = "arrays")]
+                push_elements_mut(&mut expressions, &mut stock.elements);
+            }
+        },
+        Variable::Flow(flow) => {
+            expressions.extend(&mut flow.equation);
+            #[cfg(feature = "arrays")]
+            push_elements_mut(&mut expressions, &mut flow.elements);
+        }
+        Variable::GraphicalFunction(gf) => {
+            expressions.extend(&mut gf.equation);
+            #[cfg(feature = "arrays")]
+            push_elements_mut(&mut expressions, &mut gf.elements);
+        }
+        #[cfg(feature = "submodels")]
+        Variable::Module(_) => {}
+        Variable::Group(_) => {}
+    }
+    expressions
+}
+
+#[cfg(feature = "arrays")]
+fn push_elements_mut<'a>(
+    expressions: &mut Vec<&'a mut Expression>,
+    elements: &'a mut [crate::model::vars::array::ArrayElement],
+) {
+    for element in elements {
+        expressions.extend(&mut element.eqn);
+        if let Some(gf) = &mut element.gf {
+            expressions.extend(&mut gf.equation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file() -> XmileFile {
+        XmileFile::from_str(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+            <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+            <model><variables>
+                <stock name="Population"><eqn>100</eqn><inflow>births</inflow></stock>
+                <flow name="births"><eqn>Population * rate</eqn></flow>
+                <aux name="rate"><eqn>0.1</eqn></aux>
+            </variables></model>
+            <model name="Other"><variables>
+                <aux name="x"><eqn>1</eqn></aux>
+            </variables></model>
+        </xmile>"#,
+        )
+        .unwrap()
+    }
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl XmileVisitor for Trace {
+        fn visit_header(&mut self, header: &Header) {
+            self.0.push(format!("header {}", header.vendor));
+        }
+
+        fn visit_model(&mut self, model: &Model) {
+            self.0.push(format!("model {:?}", model.name));
+            walk_model(self, model);
+        }
+
+        fn visit_expression(&mut self, expression: &Expression) {
+            self.0.push(expression.to_string());
+        }
+    }
+
+    #[test]
+    fn test_visit_order() {
+        let mut trace = Trace::default();
+        trace.visit_file(&file());
+        assert_eq!(
+            trace.0,
+            [
+                "header Test",
+                "model None",
+                "100",
+                "Population * rate",
+                "0.1",
+                "model Some(\"Other\")",
+                "1",
+            ]
+        );
+    }
+
+    struct Parenthesize;
+
+    impl XmileVisitorMut for Parenthesize {
+        fn visit_expression_mut(&mut self, expression: &mut Expression) {
+            let inner = std::mem::replace(expression, Expression::InlineComment(String::new()));
+            *expression = Expression::parentheses(inner);
+        }
+    }
+
+    #[test]
+    fn test_visit_mut() {
+        let mut file = file();
+        Parenthesize.visit_file_mut(&mut file);
+        let mut trace = Trace::default();
+        trace.visit_file(&file);
+        assert_eq!(trace.0[3], "(Population * rate)");
+        assert_eq!(trace.0[6], "(1)");
+    }
+}
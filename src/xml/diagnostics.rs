@@ -0,0 +1,401 @@
+//! Configurable severities for validation diagnostics.
+//!
+//! Each warning and error reported by [`XmileFile::validate`] is classified
+//! under a stable [`DiagnosticCode`], such as `reserved-name` or `units`. A
+//! [`ValidationConfig`] changes the [`Severity`] of a code, like a linter
+//! allow-list, either for the whole file or for the diagnostics of one
+//! element path, so files from other vendors can be checked without noise
+//! that cannot be fixed:
+//!
+//! ```rust
+//! use xmile::xml::XmileFile;
+//! use xmile::xml::diagnostics::{DiagnosticCode, Severity, ValidationConfig};
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <model><variables>
+//!         <aux name="Rate"><eqn>0.1</eqn></aux>
+//!         <aux name="Rate"><eqn>0.2</eqn></aux>
+//!     </variables></model>
+//! </xmile>"#).unwrap();
+//! assert!(file.validate().is_err());
+//!
+//! let config = ValidationConfig::new().set(DiagnosticCode::DuplicateName, Severity::Warning);
+//! let result = file.validate_with(&config);
+//! assert!(result.has_warnings());
+//! ```
+//!
+//! The path of a diagnostic is where [`validate`](XmileFile::validate)
+//! reports it, such as `model[0]`, `macro[1]` or `xmile@version`, followed by
+//! `/` and the first name quoted in its message, if any, such as
+//! `model[0]/Rate`. A rule for a path applies to that path and to the paths
+//! under it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{XmileError, XmileFile};
+use crate::types::ValidationResult;
+
+/// The kind of problem a diagnostic reports.
+///
+/// Codes and their [`as_str`](DiagnosticCode::as_str) names are stable. New
+/// codes may be added, taking diagnostics from [`Other`](DiagnosticCode::Other).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticCode {
+    /// The file is not XMILE 1.0.
+    UnsupportedVersion,
+    /// A function call is not resolved to a macro, graphical function or
+    /// array.
+    UnresolvedFunction,
+    /// Two variables of a model have the same name.
+    DuplicateName,
+    /// A variable is named after a reserved word.
+    ReservedName,
+    /// An equation refers to a variable named after a built-in without
+    /// quotes.
+    BuiltinShadowing,
+    /// The data of a graphical function is unusable.
+    GraphicalFunction,
+    /// An array element is missing, duplicated or out of bounds.
+    ArrayElement,
+    /// A dimension is undefined, duplicated or empty.
+    Dimension,
+    /// Units are invalid or inconsistent.
+    Units,
+    /// Two display objects of a view have the same UID.
+    DuplicateUid,
+    /// A display object refers to a variable that does not exist.
+    ViewReference,
+    /// The zones of a lamp or gauge are missing or invalid.
+    Zones,
+    /// A group refers to an entity that does not exist.
+    GroupReference,
+    /// Any other problem.
+    Other,
+}
+
+impl DiagnosticCode {
+    pub const ALL: [DiagnosticCode; 14] = [
+        DiagnosticCode::UnsupportedVersion,
+        DiagnosticCode::UnresolvedFunction,
+        DiagnosticCode::DuplicateName,
+        DiagnosticCode::ReservedName,
+        DiagnosticCode::BuiltinShadowing,
+        DiagnosticCode::GraphicalFunction,
+        DiagnosticCode::ArrayElement,
+        DiagnosticCode::Dimension,
+        DiagnosticCode::Units,
+        DiagnosticCode::DuplicateUid,
+        DiagnosticCode::ViewReference,
+        DiagnosticCode::Zones,
+        DiagnosticCode::GroupReference,
+        DiagnosticCode::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::UnsupportedVersion => "unsupported-version",
+            DiagnosticCode::UnresolvedFunction => "unresolved-function",
+            DiagnosticCode::DuplicateName => "duplicate-name",
+            DiagnosticCode::ReservedName => "reserved-name",
+            DiagnosticCode::BuiltinShadowing => "builtin-shadowing",
+            DiagnosticCode::GraphicalFunction => "graphical-function",
+            DiagnosticCode::ArrayElement => "array-element",
+            DiagnosticCode::Dimension => "dimension",
+            DiagnosticCode::Units => "units",
+            DiagnosticCode::DuplicateUid => "duplicate-uid",
+            DiagnosticCode::ViewReference => "view-reference",
+            DiagnosticCode::Zones => "zones",
+            DiagnosticCode::GroupReference => "group-reference",
+            DiagnosticCode::Other => "other",
+        }
+    }
+
+    /// Classifies a validation message.
+    pub fn classify(message: &str) -> DiagnosticCode {
+        let lower = message.to_lowercase();
+        let has = |pattern: &str| lower.contains(pattern);
+
+        if has("unsupported xmile version") {
+            DiagnosticCode::UnsupportedVersion
+        } else if has("resolv") {
+            DiagnosticCode::UnresolvedFunction
+        } else if has("duplicate variable name") {
+            DiagnosticCode::DuplicateName
+        } else if has("is a reserved word") {
+            DiagnosticCode::ReservedName
+        } else if has("which names the built-in") {
+            DiagnosticCode::BuiltinShadowing
+        } else if lower.starts_with("graphical function '")
+            || has("y-value")
+            || has("x-scale")
+            || has("discrete functions")
+            || lower.starts_with("scale ")
+        {
+            DiagnosticCode::GraphicalFunction
+        } else if has("array element") || has("non-apply-to-all") {
+            DiagnosticCode::ArrayElement
+        } else if has("dimension") {
+            DiagnosticCode::Dimension
+        } else if has("units") {
+            DiagnosticCode::Units
+        } else if has("duplicate uid") {
+            DiagnosticCode::DuplicateUid
+        } else if has("zone") {
+            DiagnosticCode::Zones
+        } else if lower.starts_with("group '") {
+            DiagnosticCode::GroupReference
+        } else if has("display object") || has("flow object") || lower.starts_with("alias ") {
+            DiagnosticCode::ViewReference
+        } else {
+            DiagnosticCode::Other
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DiagnosticCode {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        DiagnosticCode::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == code)
+            .ok_or_else(|| format!("Unknown diagnostic code: {code}"))
+    }
+}
+
+/// How a diagnostic is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Not reported.
+    Allow,
+    /// Reported without failing validation.
+    Warning,
+    /// Reported, failing validation.
+    Error,
+}
+
+/// A warning or error found while validating a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    /// Where the diagnostic was found; see the [module](self) documentation.
+    pub path: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(path: &str, message: String, severity: Severity) -> Self {
+        let path = match quoted_name(&message) {
+            Some(name) if path.is_empty() => name.to_string(),
+            Some(name) => format!("{path}/{name}"),
+            None => path.to_string(),
+        };
+        Diagnostic {
+            code: DiagnosticCode::classify(&message),
+            severity,
+            path,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Allow => "allowed",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}[{}]", self.code)?;
+        if !self.path.is_empty() {
+            write!(f, " {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The first name between single quotes in `message`.
+fn quoted_name(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once('\'')?;
+    let (name, _) = rest.split_once('\'')?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// Changes to the severity of validation diagnostics.
+///
+/// A rule for a path takes precedence over a rule for the whole file, and
+/// among the rules for paths containing a diagnostic, the rule for the
+/// longest path applies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationConfig {
+    codes: HashMap<DiagnosticCode, Severity>,
+    paths: Vec<(String, DiagnosticCode, Severity)>,
+}
+
+impl ValidationConfig {
+    pub fn new() -> Self {
+        ValidationConfig::default()
+    }
+
+    /// Reports `code` with `severity` throughout the file.
+    pub fn set(mut self, code: DiagnosticCode, severity: Severity) -> Self {
+        self.codes.insert(code, severity);
+        self
+    }
+
+    /// Reports `code` with `severity` at `path` and the paths under it.
+    pub fn set_at(mut self, path: &str, code: DiagnosticCode, severity: Severity) -> Self {
+        self.paths.retain(|(p, c, _)| !(p == path && *c == code));
+        self.paths.push((path.to_string(), code, severity));
+        self
+    }
+
+    /// Suppresses `code` throughout the file.
+    pub fn allow(self, code: DiagnosticCode) -> Self {
+        self.set(code, Severity::Allow)
+    }
+
+    /// Suppresses `code` at `path` and the paths under it.
+    pub fn allow_at(self, path: &str, code: DiagnosticCode) -> Self {
+        self.set_at(path, code, Severity::Allow)
+    }
+
+    /// The severity of `code` at `path`, or `default` if no rule applies.
+    pub fn severity(&self, code: DiagnosticCode, path: &str, default: Severity) -> Severity {
+        self.paths
+            .iter()
+            .filter(|(rule, rule_code, _)| *rule_code == code && contains(rule, path))
+            .max_by_key(|(rule, _, _)| rule.len())
+            .map(|(_, _, severity)| *severity)
+            .or_else(|| self.codes.get(&code).copied())
+            .unwrap_or(default)
+    }
+
+    /// Applies the rules to `diagnostic`.
+    pub fn apply(&self, mut diagnostic: Diagnostic) -> Diagnostic {
+        diagnostic.severity = self.severity(diagnostic.code, &diagnostic.path, diagnostic.severity);
+        diagnostic
+    }
+}
+
+/// Whether `path` is `rule` or a path under it.
+fn contains(rule: &str, path: &str) -> bool {
+    path.strip_prefix(rule)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+impl XmileFile {
+    /// The warnings and errors found by [`validate`](XmileFile::validate),
+    /// with their default severities.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for error in self.validation_errors(true).errors() {
+            collect(error, &mut diagnostics);
+        }
+        diagnostics
+    }
+
+    /// Validates the file with the severities changed by `config`. Allowed
+    /// diagnostics are left out.
+    pub fn validate_with(
+        &self,
+        config: &ValidationConfig,
+    ) -> ValidationResult<(), Diagnostic, Diagnostic> {
+        let (warnings, errors) = self
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| config.apply(diagnostic))
+            .filter(|diagnostic| diagnostic.severity != Severity::Allow)
+            .partition(|diagnostic| diagnostic.severity == Severity::Warning);
+        ValidationResult::from_parts((), warnings, errors)
+    }
+}
+
+fn collect(error: &XmileError, diagnostics: &mut Vec<Diagnostic>) {
+    match error {
+        XmileError::Validation(validation) => {
+            let path = validation.context.parsing.as_deref().unwrap_or("");
+            for warning in &validation.warnings {
+                diagnostics.push(Diagnostic::new(path, warning.clone(), Severity::Warning));
+            }
+            for error in &validation.errors {
+                diagnostics.push(Diagnostic::new(path, error.clone(), Severity::Error));
+            }
+        }
+        XmileError::Multiple(errors) => {
+            for error in errors {
+                collect(error, diagnostics);
+            }
+        }
+        error => diagnostics.push(Diagnostic::new("", error.to_string(), Severity::Error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file() -> XmileFile {
+        XmileFile::from_str(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+            <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+            <model><variables>
+                <aux name="Rate"><eqn>0.1</eqn></aux>
+                <aux name="Rate"><eqn>0.2</eqn></aux>
+                <aux name="Other"><eqn>1</eqn></aux>
+                <aux name="Other"><eqn>2</eqn></aux>
+            </variables></model>
+        </xmile>"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diagnostics_are_classified() {
+        let diagnostics = file().diagnostics();
+        assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+        assert!(diagnostics.iter().all(|diagnostic| {
+            diagnostic.code == DiagnosticCode::DuplicateName
+                && diagnostic.severity == Severity::Error
+        }));
+        assert_eq!(diagnostics[0].path, "model[0]/Rate");
+        assert!(
+            diagnostics[0]
+                .to_string()
+                .starts_with("error[duplicate-name] model[0]/Rate: ")
+        );
+        assert_eq!("units".parse(), Ok(DiagnosticCode::Units));
+    }
+
+    #[test]
+    fn test_config_by_code_and_path() {
+        let file = file();
+        let config =
+            ValidationConfig::new().allow_at("model[0]/Rate", DiagnosticCode::DuplicateName);
+        let (_, warnings, errors) = file.validate_with(&config).into_parts();
+        assert!(warnings.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "model[0]/Other");
+
+        let config = config
+            .set(DiagnosticCode::DuplicateName, Severity::Warning)
+            .set_at("model[0]", DiagnosticCode::DuplicateName, Severity::Error);
+        let (_, warnings, errors) = file.validate_with(&config).into_parts();
+        assert!(warnings.is_empty());
+        assert_eq!(errors.len(), 1);
+
+        let config = ValidationConfig::new().allow(DiagnosticCode::DuplicateName);
+        assert!(file.validate_with(&config).is_valid());
+    }
+}
@@ -10,6 +10,7 @@ use thiserror::Error;
 /// This enum provides detailed error information including context like
 /// file locations, line numbers, and specific failure reasons.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum XmileError {
     /// IO error occurred while reading the file.
     #[error("IO error reading file: {0}")]
@@ -0,0 +1,204 @@
+//! Resource limits for parsing untrusted XMILE documents.
+//!
+//! The XML layer never resolves external entities: document type declarations
+//! are not processed, so only the predefined XML entities and character
+//! references are expanded. [`ParseLimits`] additionally bounds the size and
+//! shape of a document before it is deserialized, so that services accepting
+//! uploads cannot be made to spend unbounded memory or stack on one request.
+//!
+//! ```rust
+//! use xmile::xml::{ParseLimits, XmileFile};
+//!
+//! let limits = ParseLimits {
+//!     max_input_size: 1024,
+//!     ..ParseLimits::default()
+//! };
+//! let result = XmileFile::from_str_with_limits(&"x".repeat(2048), &limits);
+//! assert!(result.is_err());
+//! ```
+
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use thiserror::Error;
+
+use super::ParseError;
+
+/// A limit exceeded while checking a document against [`ParseLimits`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum LimitError {
+    #[error("Input exceeds the limit of {0} bytes")]
+    InputSize(usize),
+    #[error("Element nesting exceeds the maximum depth of {0}")]
+    Depth(usize),
+    #[error("Document has more than {0} elements")]
+    Elements(usize),
+    #[error("Document has more than {0} entity and character references")]
+    EntityReferences(usize),
+    #[error("Document type declarations are not allowed")]
+    DocType,
+}
+
+/// Limits applied when parsing a document with the `*_with_limits` methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum size of the document in bytes.
+    pub max_input_size: usize,
+    /// Maximum element nesting depth.
+    pub max_depth: usize,
+    /// Maximum number of elements in the document.
+    pub max_elements: usize,
+    /// Maximum number of entity and character references (e.g. `&lt;`,
+    /// `&#65;`) in text and attribute values.
+    pub max_entity_references: usize,
+    /// Whether a `<!DOCTYPE ...>` declaration is accepted. The declaration is
+    /// never processed, so entities it declares are not expanded either way.
+    pub allow_doctype: bool,
+}
+
+impl Default for ParseLimits {
+    /// Defaults sized well above real-world models: 64 MiB of input, a depth
+    /// of 64, one million elements and one million references.
+    fn default() -> Self {
+        ParseLimits {
+            max_input_size: 64 * 1024 * 1024,
+            max_depth: 64,
+            max_elements: 1_000_000,
+            max_entity_references: 1_000_000,
+            allow_doctype: false,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Limits that accept any document, matching the behaviour of the
+    /// methods without limits.
+    pub fn unlimited() -> Self {
+        ParseLimits {
+            max_input_size: usize::MAX,
+            max_depth: usize::MAX,
+            max_elements: usize::MAX,
+            max_entity_references: usize::MAX,
+            allow_doctype: true,
+        }
+    }
+
+    /// Checks a document against these limits without deserializing it.
+    pub fn check(&self, xml: &str) -> Result<(), ParseError> {
+        if xml.len() > self.max_input_size {
+            return Err(LimitError::InputSize(self.max_input_size).into());
+        }
+
+        let mut reader = Reader::from_str(xml);
+        let mut depth = 0usize;
+        let mut elements = 0usize;
+        let mut references = 0usize;
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| ParseError::Xml(e.to_string()))?;
+            match event {
+                Event::Start(ref start) | Event::Empty(ref start) => {
+                    elements += 1;
+                    if elements > self.max_elements {
+                        return Err(LimitError::Elements(self.max_elements).into());
+                    }
+                    if depth + 1 > self.max_depth {
+                        return Err(LimitError::Depth(self.max_depth).into());
+                    }
+                    if matches!(event, Event::Start(_)) {
+                        depth += 1;
+                    }
+                    references += count_references(start.attributes_raw());
+                }
+                Event::End(_) => depth = depth.saturating_sub(1),
+                Event::Text(text) => references += count_references(&text),
+                Event::DocType(_) if !self.allow_doctype => {
+                    return Err(LimitError::DocType.into());
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            if references > self.max_entity_references {
+                return Err(LimitError::EntityReferences(self.max_entity_references).into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn count_references(raw: &[u8]) -> usize {
+    raw.iter().filter(|&&b| b == b'&').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nested(depth: usize) -> String {
+        format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth))
+    }
+
+    #[test]
+    fn test_default_limits_accept_document() {
+        let xml = r#"<xmile><model><aux name="x"><eqn>a &lt; b</eqn></aux></model></xmile>"#;
+        assert!(ParseLimits::default().check(xml).is_ok());
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let limits = ParseLimits {
+            max_depth: 3,
+            ..ParseLimits::default()
+        };
+        assert!(limits.check(&nested(3)).is_ok());
+        assert!(matches!(
+            limits.check(&nested(4)),
+            Err(ParseError::Limit(LimitError::Depth(3)))
+        ));
+        assert!(matches!(
+            limits.check("<a><b><c><d/></c></b></a>"),
+            Err(ParseError::Limit(LimitError::Depth(3)))
+        ));
+    }
+
+    #[test]
+    fn test_element_limit() {
+        let limits = ParseLimits {
+            max_elements: 2,
+            ..ParseLimits::default()
+        };
+        assert!(limits.check("<a><b/></a>").is_ok());
+        assert!(matches!(
+            limits.check("<a><b/><c/></a>"),
+            Err(ParseError::Limit(LimitError::Elements(2)))
+        ));
+    }
+
+    #[test]
+    fn test_entity_reference_limit() {
+        let limits = ParseLimits {
+            max_entity_references: 2,
+            ..ParseLimits::default()
+        };
+        assert!(limits.check("<a b=\"&amp;\">&lt;</a>").is_ok());
+        assert!(matches!(
+            limits.check("<a b=\"&amp;\">&lt;&#65;</a>"),
+            Err(ParseError::Limit(LimitError::EntityReferences(2)))
+        ));
+    }
+
+    #[test]
+    fn test_doctype_rejected() {
+        let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE xmile [<!ENTITY xxe SYSTEM "file:///etc/passwd">]>
+<xmile>&xxe;</xmile>"#;
+        assert!(matches!(
+            ParseLimits::default().check(xml),
+            Err(ParseError::Limit(LimitError::DocType))
+        ));
+        assert!(ParseLimits::unlimited().check(xml).is_ok());
+    }
+}
@@ -46,21 +46,43 @@
 
 // Display objects do not have names or any other way to specifically refer to individual objects. Therefore any display object which is referred to anywhere else in the XMILE file MUST provide a uid="<int>" attribute. This attribute is a unique linearly increasing integer which gives each display object a way to be referred to specifically while reading in an XMILE file. UIDs are NOT REQUIRED to be stable across successive reads and writes. Objects requiring a uid are listed in Chapter 6 of this specification. UIDs MUST be unique per XMILE model.
 
+pub mod diagnostics;
 pub mod errors;
+pub mod limits;
+pub mod namespaces;
 pub mod schema;
+pub mod serialize;
+pub mod shared;
+pub mod stmx;
+pub mod upgrade;
 pub mod validation;
 
 pub use errors::{ErrorCollection, ErrorContext, ToXmileError, XmileError};
-pub use schema::{Model, Views, XmileFile};
+pub use limits::{LimitError, ParseLimits};
+pub use namespaces::SUPPORTED_VERSION;
+#[cfg(feature = "views")]
+pub use schema::Views;
+pub use schema::{Model, XmileFile};
+pub use serialize::{FloatFormat, Newline, Quote, SerializeOptions};
+pub use shared::SharedModel;
+pub use stmx::{Dialect, Extension};
+pub use upgrade::{Transformation, UpgradeReport};
 
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufReader, Read};
 use std::path::Path;
 
+use crate::header::Include;
+use crate::model::vars::Variable;
+use crate::resource::{self, AsyncResourceReader, Resource, ResourceError, ResourceReader};
+use crate::trace;
+
 use crate::types::Validate;
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ParseError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -68,6 +90,8 @@ pub enum ParseError {
     Xml(String),
     #[error("Deserialization error: {0}")]
     Deserialize(String),
+    #[error("Parse limit exceeded: {0}")]
+    Limit(#[from] LimitError),
 }
 
 impl XmileFile {
@@ -75,28 +99,104 @@ impl XmileFile {
     ///
     /// After parsing, function calls in expressions are automatically resolved
     /// using the registries built from macros and model variables.
+    ///
+    /// Files declaring the isee namespace are read in the
+    /// [`Stella`](Dialect::Stella) dialect; see [`stmx`].
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(xml: &str) -> Result<Self, ParseError> {
-        let mut file: XmileFile =
-            serde_xml_rs::from_str(xml).map_err(|e| ParseError::Deserialize(e.to_string()))?;
+        Self::from_str_as(xml, Dialect::of_document(xml))
+    }
+
+    /// Parse an XMILE file from a string written in `dialect`.
+    pub fn from_str_as(xml: &str, dialect: Dialect) -> Result<Self, ParseError> {
+        trace::enter_span!("xmile.parse", bytes = xml.len());
+        let mut file: XmileFile = {
+            trace::enter_span!("deserialize");
+            quick_xml::de::from_str(xml).map_err(|e| {
+                trace::event!(warn, error = %e, "deserialization failed");
+                ParseError::Deserialize(e.to_string())
+            })?
+        };
+        file.namespaces = namespaces::root_namespaces(xml);
+        file.read_extensions(xml, dialect);
 
         // Automatically resolve function calls in expressions
-        if let Err(errors) = file.resolve_all_expressions() {
+        let resolved = {
+            trace::enter_span!("resolve");
+            file.resolve_all_expressions()
+        };
+        if let Err(errors) = resolved {
+            trace::event!(
+                warn,
+                errors = errors.len(),
+                "function call resolution failed"
+            );
             return Err(ParseError::Deserialize(format!(
                 "Error resolving function calls: {}",
                 errors.join("; ")
             )));
         }
 
+        trace::event!(
+            debug,
+            models = file.models.len(),
+            variables = file.variables().count(),
+            "parsed"
+        );
         Ok(file)
     }
 
+    /// Parse an XMILE file written against 1.0 or a pre-1.0 draft of the
+    /// specification, upgrading draft constructs to their 1.0 form.
+    ///
+    /// See [`upgrade`] for the constructs recognized. The report lists the
+    /// transformations applied, and is empty for XMILE 1.0 files.
+    pub fn from_str_upgrading(xml: &str) -> Result<(Self, UpgradeReport), ParseError> {
+        let (xml, report) = upgrade::upgrade(xml)?;
+        Ok((Self::from_str(&xml)?, report))
+    }
+
+    /// Parse an XMILE file from an untrusted string, enforcing `limits`.
+    ///
+    /// The document is checked against the limits before it is deserialized.
+    pub fn from_str_with_limits(xml: &str, limits: &ParseLimits) -> Result<Self, ParseError> {
+        limits.check(xml)?;
+        Self::from_str(xml)
+    }
+
+    /// Parse an XMILE file from an untrusted reader, enforcing `limits`.
+    ///
+    /// At most `limits.max_input_size` bytes are read before giving up.
+    pub fn from_reader_with_limits<R: Read>(
+        reader: R,
+        limits: &ParseLimits,
+    ) -> Result<Self, ParseError> {
+        let mut bytes = Vec::new();
+        let max = limits.max_input_size.saturating_add(1) as u64;
+        reader.take(max).read_to_end(&mut bytes)?;
+        if bytes.len() > limits.max_input_size {
+            return Err(LimitError::InputSize(limits.max_input_size).into());
+        }
+        let xml = String::from_utf8(bytes).map_err(|e| ParseError::Xml(e.to_string()))?;
+        Self::from_str_with_limits(&xml, limits)
+    }
+
+    /// Parse an XMILE file from an untrusted file path, enforcing `limits`.
+    pub fn from_file_with_limits<P: AsRef<Path>>(
+        path: P,
+        limits: &ParseLimits,
+    ) -> Result<Self, ParseError> {
+        let file = File::open(path)?;
+        Self::from_reader_with_limits(file, limits)
+    }
+
     /// Parse an XMILE file from a string with enhanced error reporting.
     ///
     /// After parsing, function calls in expressions are automatically resolved
     /// using the registries built from macros and model variables.
     pub fn from_str_with_context(xml: &str) -> Result<Self, XmileError> {
-        let mut file: XmileFile = serde_xml_rs::from_str(xml).map_err(|e| {
+        trace::enter_span!("xmile.parse", bytes = xml.len());
+        let mut file: XmileFile = quick_xml::de::from_str(xml).map_err(|e| {
             // Try to extract line number from error message if available
             let error_str = e.to_string();
             let context = extract_context_from_error(&error_str);
@@ -106,6 +206,8 @@ impl XmileFile {
                 context,
             }
         })?;
+        file.namespaces = namespaces::root_namespaces(xml);
+        file.read_extensions(xml, Dialect::of_document(xml));
 
         // Automatically resolve function calls in expressions
         if let Err(resolution_errors) = file.resolve_all_expressions() {
@@ -130,18 +232,9 @@ impl XmileFile {
     /// After parsing, function calls in expressions are automatically resolved
     /// using the registries built from macros and model variables.
     pub fn from_reader<R: Read>(reader: R) -> Result<Self, ParseError> {
-        let mut file: XmileFile = serde_xml_rs::from_reader(reader)
-            .map_err(|e| ParseError::Deserialize(e.to_string()))?;
-
-        // Automatically resolve function calls in expressions
-        if let Err(errors) = file.resolve_all_expressions() {
-            return Err(ParseError::Deserialize(format!(
-                "Error resolving function calls: {}",
-                errors.join("; ")
-            )));
-        }
-
-        Ok(file)
+        let mut xml = String::new();
+        BufReader::new(reader).read_to_string(&mut xml)?;
+        Self::from_str(&xml)
     }
 
     /// Parse an XMILE file from a reader with enhanced error reporting.
@@ -149,41 +242,22 @@ impl XmileFile {
     /// After parsing, function calls in expressions are automatically resolved
     /// using the registries built from macros and model variables.
     pub fn from_reader_with_context<R: Read>(reader: R) -> Result<Self, XmileError> {
-        let mut file: XmileFile = serde_xml_rs::from_reader(reader).map_err(|e| {
-            let error_str = e.to_string();
-            let context = extract_context_from_error(&error_str);
-
-            XmileError::Deserialize {
-                message: error_str,
-                context,
-            }
-        })?;
-
-        // Automatically resolve function calls in expressions
-        if let Err(resolution_errors) = file.resolve_all_expressions() {
-            return Err(XmileError::Validation(Box::new(
-                crate::xml::errors::ValidationError {
-                    message: format!(
-                        "Error resolving function calls: {}",
-                        resolution_errors.join("; ")
-                    ),
-                    context: ErrorContext::new(),
-                    warnings: Vec::new(),
-                    errors: resolution_errors,
-                },
-            )));
-        }
-
-        Ok(file)
+        let mut xml = String::new();
+        BufReader::new(reader).read_to_string(&mut xml)?;
+        Self::from_str_with_context(&xml)
     }
 
     /// Parse an XMILE file from a file path.
     ///
     /// After parsing, function calls in expressions are automatically resolved
     /// using the registries built from macros and model variables.
+    ///
+    /// `.stmx` and `.itmx` files, and files declaring the isee namespace,
+    /// are read in the [`Stella`](Dialect::Stella) dialect.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
-        let file = File::open(path)?;
-        Self::from_reader(file)
+        let mut xml = String::new();
+        BufReader::new(File::open(&path)?).read_to_string(&mut xml)?;
+        Self::from_str_as(&xml, Dialect::of_file(path, &xml))
     }
 
     /// Parse an XMILE file from a file path with enhanced error reporting.
@@ -192,46 +266,161 @@ impl XmileFile {
     /// using the registries built from macros and model variables.
     pub fn from_file_with_context<P: AsRef<Path>>(path: P) -> Result<Self, XmileError> {
         let path_buf = path.as_ref().to_path_buf();
-        let file = File::open(&path_buf)?;
-
-        let mut xmile_file: XmileFile = serde_xml_rs::from_reader(file).map_err(|e| {
-            let error_str = e.to_string();
-            let mut context = extract_context_from_error(&error_str);
-            context.file_path = Some(path_buf);
+        let xml = std::fs::read_to_string(&path_buf)?;
 
+        let dialect = Dialect::of_file(&path_buf, &xml);
+        let mut file = Self::from_str_with_context(&xml).map_err(|error| match error {
             XmileError::Deserialize {
-                message: error_str,
-                context,
+                message,
+                mut context,
+            } => {
+                context.file_path = Some(path_buf);
+                XmileError::Deserialize { message, context }
             }
+            error => error,
         })?;
+        file.read_extensions(&xml, dialect);
+        Ok(file)
+    }
 
-        // Automatically resolve function calls in expressions
-        if let Err(resolution_errors) = xmile_file.resolve_all_expressions() {
-            return Err(XmileError::Validation(Box::new(
-                crate::xml::errors::ValidationError {
-                    message: format!(
-                        "Error resolving function calls: {}",
-                        resolution_errors.join("; ")
-                    ),
-                    context: ErrorContext::new(),
-                    warnings: Vec::new(),
-                    errors: resolution_errors,
-                },
-            )));
+    /// Load the files listed under `<includes>` in the header, in order.
+    ///
+    /// Wildcards are expanded by the reader. The included files are returned
+    /// unparsed; merging them into this file (Section 2.11.3) is left to the
+    /// caller.
+    pub fn resolve_includes(
+        &self,
+        reader: &impl ResourceReader,
+    ) -> Result<Vec<Resource>, ResourceError> {
+        let mut resources = Vec::new();
+        for include in self.includes() {
+            resources.extend(resource::load_all(reader, &include.resource)?);
+        }
+        Ok(resources)
+    }
+
+    /// Load the files listed under `<includes>` in the header without blocking.
+    ///
+    /// See [`XmileFile::resolve_includes`].
+    pub async fn resolve_includes_async(
+        &self,
+        reader: &impl AsyncResourceReader,
+    ) -> Result<Vec<Resource>, ResourceError> {
+        let mut resources = Vec::new();
+        for include in self.includes() {
+            resources.extend(resource::load_all_async(reader, &include.resource).await?);
+        }
+        Ok(resources)
+    }
+
+    /// Iterate over the variables of every model, with their names qualified
+    /// by the modules containing them.
+    ///
+    /// See [`qualified`](crate::model::qualified) for the order of iteration.
+    pub fn variables(
+        &self,
+    ) -> impl Iterator<Item = (crate::model::qualified::QualifiedName, &Variable)> + '_ {
+        crate::model::qualified::qualified_variables(&self.models).into_iter()
+    }
+
+    /// Keeps the vendor extensions of `xml` if it is written in a dialect
+    /// whose extensions are kept.
+    fn read_extensions(&mut self, xml: &str, dialect: Dialect) {
+        if dialect == Dialect::Stella {
+            self.extensions = stmx::extensions(xml, &self.namespaces);
         }
+    }
+
+    fn includes(&self) -> &[Include] {
+        self.header
+            .includes
+            .as_ref()
+            .map(|includes| includes.includes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Serialize the XMILE file to an XML string, including the XML declaration.
+    pub fn to_xml_string(&self) -> Result<String, ParseError> {
+        self.to_xml_string_with(&SerializeOptions::default())
+    }
+
+    /// Serialize the XMILE file to an XML string with the given options.
+    pub fn to_xml_string_with(&self, options: &SerializeOptions) -> Result<String, ParseError> {
+        let mut xml = serialize::with_options(options, || self.write_xml_string(options))?;
+        xml = stmx::restore(xml, &self.extensions)?;
+        xml = namespaces::declare_root_namespaces(xml, &self.namespaces);
+        if !self.extensions.is_empty() {
+            xml = namespaces::declare_root_namespaces(
+                xml,
+                &[(
+                    stmx::ISEE_PREFIX.to_string(),
+                    stmx::ISEE_NAMESPACE.to_string(),
+                )],
+            );
+        }
+        xml = serialize::requote(&xml, options.quote);
+        Ok(serialize::convert_newlines(xml, options.newline))
+    }
 
-        Ok(xmile_file)
+    fn write_xml_string(&self, options: &SerializeOptions) -> Result<String, ParseError> {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let mut serializer = quick_xml::se::Serializer::new(&mut xml);
+        // Equations routinely contain quoted identifiers, so leave quotes in
+        // text content unescaped.
+        serializer.set_quote_level(quick_xml::se::QuoteLevel::Partial);
+        serializer.expand_empty_elements(!options.self_closing);
+        if let Some(width) = options.indent {
+            serializer.indent(' ', width);
+        }
+        self.serialize(serializer)
+            .map_err(|e| ParseError::Xml(e.to_string()))?;
+        Ok(xml)
     }
 
     /// Validate the parsed XMILE file and return detailed errors if validation fails.
     ///
     /// This includes validation of:
+    /// - The XMILE version, which must be [`SUPPORTED_VERSION`]
     /// - Model structure and variable definitions
+    /// - Variable and macro names against reserved words
     /// - Expression resolution (macros, graphical functions, arrays)
     /// - Function call resolution validation
+    /// - Stock and flow units against the model's time units
     pub fn validate(&self) -> Result<(), XmileError> {
+        trace::enter_span!("xmile.validate", models = self.models.len());
+        let error_collection = self.validation_errors(false);
+        trace::event!(debug, errors = error_collection.len(), "validated");
+        if let Some(error) = error_collection.into_error() {
+            Err(error)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Runs the checks of [`validate`](XmileFile::validate), collecting the
+    /// failed checks, and with `with_warnings` those that passed with
+    /// warnings.
+    fn validation_errors(&self, with_warnings: bool) -> ErrorCollection {
+        let report = |result: &crate::types::ValidationResult| {
+            result.is_invalid() || (with_warnings && result.has_warnings())
+        };
         let mut error_collection = ErrorCollection::new();
 
+        if self.version != SUPPORTED_VERSION {
+            let message = format!(
+                "Unsupported XMILE version '{}'; expected '{}'.",
+                self.version, SUPPORTED_VERSION
+            );
+            error_collection.push(XmileError::Validation(Box::new(
+                crate::xml::errors::ValidationError {
+                    message: message.clone(),
+                    context: ErrorContext::new().with_parsing("xmile@version"),
+                    warnings: Vec::new(),
+                    errors: vec![message],
+                },
+            )));
+        }
+
         // Validate macro resolution at file level
         #[cfg(feature = "macros")]
         {
@@ -378,6 +567,33 @@ impl XmileFile {
             }
         }
 
+        // Option filters must be declared in the header's options
+        #[cfg(feature = "macros")]
+        let option_filters = self
+            .header
+            .options
+            .as_ref()
+            .and_then(|options| options.uses_macros.as_ref())
+            .is_some_and(|uses_macros| uses_macros.option_filters);
+        #[cfg(feature = "macros")]
+        for (idx, macro_def) in self.macros.iter().enumerate() {
+            let validation_result = if macro_def.filter.is_some() && !option_filters {
+                macro_def.validate().merge(crate::types::ValidationResult::Invalid(
+                    Vec::new(),
+                    vec![format!(
+                        "Macro '{}' is an option filter, but the header does not declare <uses_macros option_filters=\"true\"/>.",
+                        macro_def.name
+                    )],
+                ))
+            } else {
+                macro_def.validate()
+            };
+            if report(&validation_result) {
+                let context = ErrorContext::new().with_parsing(format!("macro[{}]", idx));
+                error_collection.push(validation_result.to_xmile_error(context));
+            }
+        }
+
         // Merge file-level and model-level dimensions for array validation
         #[cfg(feature = "arrays")]
         let file_dimensions = &self.dimensions;
@@ -483,8 +699,8 @@ impl XmileFile {
                                 elems,
                                 &merged_dimensions,
                             ) {
-                                ValidationResult::Valid(_) => {}
-                                ValidationResult::Warnings(_, warns) => {
+                                crate::types::ValidationResult::Valid(_) => {}
+                                crate::types::ValidationResult::Warnings(_, warns) => {
                                     for warn in warns {
                                         error_collection.push(XmileError::Validation(Box::new(
                                             crate::xml::errors::ValidationError {
@@ -499,7 +715,7 @@ impl XmileFile {
                                         )));
                                     }
                                 }
-                                ValidationResult::Invalid(warns, errs) => {
+                                crate::types::ValidationResult::Invalid(warns, errs) => {
                                     error_collection.push(XmileError::Validation(Box::new(
                                         crate::xml::errors::ValidationError {
                                             message: format!(
@@ -522,25 +738,36 @@ impl XmileFile {
             }
 
             let validation_result = model.validate();
-            if validation_result.is_invalid() {
-                error_collection.push(validation_result.to_xmile_error(context));
+            if report(&validation_result) {
+                error_collection.push(validation_result.to_xmile_error(context.clone()));
             }
-        }
 
-        if let Some(error) = error_collection.into_error() {
-            Err(error)
-        } else {
-            Ok(())
+            // Validate stock and flow units against the model's time units
+            let time_units = model
+                .sim_specs
+                .as_ref()
+                .or(self.sim_specs.as_ref())
+                .and_then(|specs| specs.time_units.as_deref());
+            let units_result = crate::units::consistency::validate_stock_flow_units(
+                model,
+                time_units,
+                self.model_units.as_ref(),
+            );
+            if report(&units_result) {
+                error_collection.push(units_result.to_xmile_error(context));
+            }
         }
+
+        error_collection
     }
 }
 
 /// Extract context information from error messages (line numbers, etc.).
 ///
-/// Since serde-xml-rs doesn't provide structured error information,
+/// Since the serde deserializer doesn't provide structured error information,
 /// we parse the error message string to extract what context we can.
-/// This function handles various error message patterns that serde-xml-rs
-/// and underlying XML parsers may produce.
+/// This function handles various error message patterns that quick-xml
+/// and the serde layer may produce.
 fn extract_context_from_error(error_str: &str) -> ErrorContext {
     let mut context = ErrorContext::new();
 
@@ -0,0 +1,120 @@
+//! Namespace declarations on the root `<xmile>` element.
+//!
+//! Vendors declare their own namespaces on the root element, such as
+//! `xmlns:isee="http://iseesystems.com/XMILE"`, and use the prefix for
+//! extension elements and attributes throughout the file. The declarations
+//! other than the default `xmlns` are kept in
+//! [`XmileFile::namespaces`](super::XmileFile::namespaces) when a file is
+//! read and written back on the root element, in the same order, so that a
+//! file written by this crate still declares every prefix it uses.
+//!
+//! ```rust
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0" xmlns:isee="http://iseesystems.com/XMILE">
+//!     <header><vendor>isee</vendor><product version="1.0">Stella</product></header>
+//!     <model><variables/></model>
+//! </xmile>"#).unwrap();
+//! assert_eq!(file.namespaces, vec![("isee".to_string(), "http://iseesystems.com/XMILE".to_string())]);
+//! assert!(file.to_xml_string().unwrap().contains(r#"xmlns:isee="http://iseesystems.com/XMILE""#));
+//! ```
+
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+
+/// The version of the XMILE specification this crate reads and writes.
+pub const SUPPORTED_VERSION: &str = "1.0";
+
+/// Reads the prefixed namespace declarations on the root element of a
+/// document, as prefix and URI in document order.
+///
+/// Documents that are not well-formed yield the declarations read before the
+/// error; deserializing such a document reports the error itself.
+pub(crate) fn root_namespaces(xml: &str) -> Vec<(String, String)> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) | Ok(Event::Empty(start)) => {
+                return start
+                    .attributes()
+                    .map_while(Result::ok)
+                    .filter_map(|attribute| {
+                        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+                        let prefix = key.strip_prefix("xmlns:")?.to_string();
+                        let uri = attribute.unescape_value().ok()?.into_owned();
+                        Some((prefix, uri))
+                    })
+                    .collect();
+            }
+            Ok(Event::Eof) | Err(_) => return Vec::new(),
+            Ok(_) => {}
+        }
+    }
+}
+
+/// Adds namespace declarations to the start tag of the root `<xmile>`
+/// element of a serialized document.
+pub(crate) fn declare_root_namespaces(xml: String, namespaces: &[(String, String)]) -> String {
+    let Some(start) = xml.find("<xmile") else {
+        return xml;
+    };
+    let Some(end) = xml[start..].find('>').map(|offset| start + offset) else {
+        return xml;
+    };
+    let insert_at = if xml[..end].ends_with('/') {
+        end - 1
+    } else {
+        end
+    };
+
+    let mut declarations = String::new();
+    for (prefix, uri) in namespaces {
+        let attribute = format!("xmlns:{}=", prefix);
+        if xml[start..end].contains(&attribute) {
+            continue;
+        }
+        declarations.push_str(&format!(" {}\"{}\"", attribute, escape(uri.as_str())));
+    }
+
+    let mut xml = xml;
+    xml.insert_str(insert_at, &declarations);
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_namespaces() {
+        let xml = r#"<?xml version="1.0"?><xmile version="1.0" xmlns="urn:x" xmlns:isee="urn:isee" xmlns:acme="urn:a&amp;b"><model xmlns:inner="urn:inner"/></xmile>"#;
+        assert_eq!(
+            root_namespaces(xml),
+            vec![
+                ("isee".to_string(), "urn:isee".to_string()),
+                ("acme".to_string(), "urn:a&b".to_string()),
+            ]
+        );
+        assert!(root_namespaces("not xml").is_empty());
+    }
+
+    #[test]
+    fn test_declare_root_namespaces() {
+        let namespaces = vec![
+            ("isee".to_string(), "urn:isee".to_string()),
+            ("acme".to_string(), "urn:a&b".to_string()),
+        ];
+        assert_eq!(
+            declare_root_namespaces(
+                r#"<?xml?><xmile version="1.0"><a/></xmile>"#.into(),
+                &namespaces
+            ),
+            r#"<?xml?><xmile version="1.0" xmlns:isee="urn:isee" xmlns:acme="urn:a&amp;b"><a/></xmile>"#
+        );
+        assert_eq!(
+            declare_root_namespaces(r#"<xmile xmlns:isee="urn:isee"/>"#.into(), &namespaces),
+            r#"<xmile xmlns:isee="urn:isee" xmlns:acme="urn:a&amp;b"/>"#
+        );
+    }
+}
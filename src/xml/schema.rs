@@ -13,13 +13,20 @@ use crate::{
     model::vars::flow::Flow,
     model::vars::gf::{GraphicalFunction, GraphicalFunctionRegistry},
     model::vars::stock::Stock,
+    resource::ResourceRef,
     specs::SimulationSpecs,
     types::{Validate, ValidationResult},
     units::ModelUnits,
-    view::{Style, View},
+    xml::stmx::Extension,
     xml::validation::*,
 };
 
+#[cfg(feature = "views")]
+use crate::view::View;
+
+#[cfg(feature = "style")]
+use crate::view::Style;
+
 #[cfg(feature = "macros")]
 use crate::r#macro::{Macro, MacroRegistry};
 
@@ -80,22 +87,39 @@ pub struct XmileFile {
     /// The XML namespace for XMILE.
     #[serde(rename = "@xmlns", default = "default_xmlns")]
     pub xmlns: String,
+    /// Prefixed namespace declarations on the root element, such as
+    /// `xmlns:isee`, as prefix and URI in document order.
+    #[serde(skip)]
+    pub namespaces: Vec<(String, String)>,
+    /// Vendor elements and attributes kept verbatim from a file read in the
+    /// [`Stella`](super::stmx::Dialect::Stella) dialect, and written back
+    /// in place. See [`stmx`](super::stmx).
+    #[serde(skip)]
+    pub extensions: Vec<Extension>,
     /// The header information for the XMILE file.
     pub header: Header,
     /// Optional simulation specifications for the XMILE file.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sim_specs: Option<SimulationSpecs>,
     /// Optional model units defined in the XMILE file.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub model_units: Option<ModelUnits>,
     /// Optional dimensions defined in the XMILE file.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub dimensions: Option<Dimensions>,
     /// Optional behavior specifications for the XMILE file.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub behavior: Option<Behavior>,
     /// Optional style definitions for the XMILE file.
+    #[cfg(feature = "style")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub style: Option<Style>,
     /// Optional data definitions for the XMILE file.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Data>,
-    /// A list of models defined in the XMILE file.
-    #[serde(rename = "model")]
+    /// A list of models defined in the XMILE file. Files that only define
+    /// macros, such as macro libraries, have no models.
+    #[serde(rename = "model", default)]
     pub models: Vec<Model>,
     /// A list of macros defined in the XMILE file.
     #[cfg(feature = "macros")]
@@ -124,18 +148,22 @@ pub struct XmileFile {
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Model {
     /// Optional name attribute for the model (required if model is a submodel).
-    #[serde(rename = "@name")]
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// Optional resource attribute referencing an external file containing the model.
-    #[serde(rename = "@resource")]
-    pub resource: Option<String>,
+    #[serde(rename = "@resource", skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ResourceRef>,
     /// Optional simulation specifications for this model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sim_specs: Option<SimulationSpecs>,
     /// Optional behavior specifications for this model.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub behavior: Option<Behavior>,
     /// The variables defined in this model (REQUIRED).
     pub variables: Variables,
     /// Optional views for this model.
+    #[cfg(feature = "views")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub views: Option<Views>,
 }
 
@@ -868,6 +896,11 @@ impl Model {
 
 impl Validate for Model {
     fn validate(&self) -> ValidationResult {
+        crate::trace::enter_span!(
+            "model.validate",
+            model = self.name.as_deref().unwrap_or(""),
+            variables = self.variables.variables.len()
+        );
         let mut warnings = Vec::new();
         let mut errors = Vec::new();
 
@@ -881,6 +914,29 @@ impl Validate for Model {
             }
         }
 
+        // Validate that variable names do not collide with reserved words
+        match validate_reserved_names(&self.variables.variables) {
+            ValidationResult::Valid(_) => {}
+            ValidationResult::Warnings(_, warns) => warnings.extend(warns),
+            ValidationResult::Invalid(warns, errs) => {
+                warnings.extend(warns);
+                errors.extend(errs);
+            }
+        }
+
+        // Validate the data of graphical functions, which would otherwise
+        // evaluate silently
+        for variable in &self.variables.variables {
+            if let Variable::GraphicalFunction(gf) = variable
+                && let ValidationResult::Invalid(warns, errs) = gf.validate()
+            {
+                let name = get_variable_name(variable).map_or("", |name| name.normalized());
+                let context = |message: String| format!("Graphical function '{name}': {message}");
+                warnings.extend(warns.into_iter().map(context));
+                errors.extend(errs.into_iter().map(context));
+            }
+        }
+
         // Validate that all function calls are properly resolved
         // Note: This validation uses only model-level registries (GFs and arrays).
         // Macro validation happens at the file level since macros are file-level.
@@ -1161,6 +1217,7 @@ impl Validate for Model {
         }
 
         // Validate view object references
+        #[cfg(feature = "views")]
         if let Some(ref views) = self.views {
             for view in &views.views {
                 match validate_view_object_references(view, &self.variables.variables) {
@@ -1181,6 +1238,51 @@ impl Validate for Model {
                         errors.extend(errs);
                     }
                 }
+
+                // Validate that connectors attach to aliases and ghosts
+                match validate_connector_endpoints(view) {
+                    ValidationResult::Valid(_) => {}
+                    ValidationResult::Warnings(_, warns) => warnings.extend(warns),
+                    ValidationResult::Invalid(warns, errs) => {
+                        warnings.extend(warns);
+                        errors.extend(errs);
+                    }
+                }
+
+                // Validate graphics frame and button media
+                #[cfg(feature = "interface-objects")]
+                match validate_view_media(view) {
+                    ValidationResult::Valid(_) => {}
+                    ValidationResult::Warnings(_, warns) => warnings.extend(warns),
+                    ValidationResult::Invalid(warns, errs) => {
+                        warnings.extend(warns);
+                        errors.extend(errs);
+                    }
+                }
+
+                // Validate lamp and gauge zones
+                #[cfg(feature = "interface-objects")]
+                match validate_view_zones(view) {
+                    ValidationResult::Valid(_) => {}
+                    ValidationResult::Warnings(_, warns) => warnings.extend(warns),
+                    ValidationResult::Invalid(warns, errs) => {
+                        warnings.extend(warns);
+                        errors.extend(errs);
+                    }
+                }
+            }
+        }
+
+        // Validate that aliases represent objects shown in the model's views
+        #[cfg(feature = "views")]
+        if let Some(ref views) = self.views {
+            match validate_alias_targets(&views.views) {
+                ValidationResult::Valid(_) => {}
+                ValidationResult::Warnings(_, warns) => warnings.extend(warns),
+                ValidationResult::Invalid(warns, errs) => {
+                    warnings.extend(warns);
+                    errors.extend(errs);
+                }
             }
         }
 
@@ -1209,6 +1311,12 @@ impl Validate for Model {
             }
         }
 
+        crate::trace::event!(
+            debug,
+            warnings = warnings.len(),
+            errors = errors.len(),
+            "validated"
+        );
         if errors.is_empty() {
             if warnings.is_empty() {
                 ValidationResult::Valid(())
@@ -1349,15 +1457,18 @@ impl Serialize for Variables {
 /// the layout, content and appearance of the user interface and stock and flow diagram.
 /// The <views> tag can also contain an OPTIONAL visible_view attribute specifying
 /// the index of the view which the user desires to be active upon loading of the file.
+#[cfg(feature = "views")]
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Views {
     /// The index of the view which should be active upon loading.
     /// The index refers to the full list of views regardless of the view's type.
-    #[serde(rename = "@visible_view")]
+    #[serde(rename = "@visible_view", skip_serializing_if = "Option::is_none")]
     pub visible_view: Option<u32>,
     /// A list of views defined in this model.
     #[serde(rename = "view")]
     pub views: Vec<View>,
     /// Optional style definitions that apply to all views within this <views> tag.
+    #[cfg(feature = "style")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub style: Option<Style>,
 }
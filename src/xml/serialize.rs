@@ -0,0 +1,318 @@
+//! Options controlling how XMILE documents are written.
+//!
+//! [`XmileFile::to_xml_string_with`](super::XmileFile::to_xml_string_with)
+//! writes a file with the given [`SerializeOptions`], which control
+//! indentation, line endings, whether empty elements are self-closing, how
+//! attribute values are quoted, how the points of graphical functions are
+//! written and whether attributes set to their default value are kept.
+//!
+//! The options are in effect for the duration of the call, so values nested
+//! anywhere in the document, such as the points of graphical functions or
+//! the attributes of views, are written with them without each type needing
+//! to be passed the options.
+//!
+//! By default files are written on one line with LF line endings,
+//! self-closing empty elements, double-quoted attribute values and every
+//! attribute the file sets. Numbers are written in the shortest form that
+//! reads back as the same `f64`, so writing a file read from disk never
+//! loses precision. Only the points of graphical functions can be written
+//! otherwise, rounded with [`SerializeOptions::point_format`].
+//!
+//! The XML writer always puts attribute values between double quotes, so
+//! single quotes are swapped in once the document is written. Only the
+//! quotes around attribute values change, and a `'` inside a value is
+//! written as `&apos;`; text, CDATA sections, comments and references such
+//! as `&#10;` are copied as they were written.
+//!
+//! ```rust
+//! use xmile::xml::{FloatFormat, Newline, SerializeOptions};
+//!
+//! let options = SerializeOptions {
+//!     indent: Some(2),
+//!     newline: Newline::CrLf,
+//!     point_format: FloatFormat::Significant(3),
+//!     ..SerializeOptions::default()
+//! };
+//! assert_eq!(options.point_format.format(0.123456), "0.123");
+//! assert_eq!(FloatFormat::Shortest.format(0.1 + 0.2), "0.30000000000000004");
+//! ```
+
+use std::cell::RefCell;
+
+/// How floating-point numbers are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// The shortest decimal that reads back as exactly the same value.
+    #[default]
+    Shortest,
+    /// Rounded to this many significant digits, without trailing zeros.
+    Significant(usize),
+    /// Rounded to this many digits after the decimal point, without trailing
+    /// zeros.
+    Decimals(usize),
+}
+
+impl FloatFormat {
+    /// Formats `value`. Non-finite values are written as Rust writes them.
+    pub fn format(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        let rounded = match self {
+            FloatFormat::Shortest => value,
+            FloatFormat::Significant(digits) => {
+                let digits = (*digits).max(1);
+                format!("{:.*e}", digits - 1, value)
+                    .parse()
+                    .unwrap_or(value)
+            }
+            FloatFormat::Decimals(decimals) => {
+                format!("{:.*}", decimals, value).parse().unwrap_or(value)
+            }
+        };
+        // Writing the rounded value in shortest form drops trailing zeros and
+        // any digits rounding added.
+        if rounded == 0.0 {
+            "0".to_string()
+        } else {
+            rounded.to_string()
+        }
+    }
+}
+
+/// The line ending written between lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Newline {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// The quote character attribute values are written between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quote {
+    /// `name="value"`.
+    #[default]
+    Double,
+    /// `name='value'`.
+    Single,
+}
+
+/// Options for writing XMILE documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerializeOptions {
+    /// The number of spaces to indent each level of nesting by, or `None` to
+    /// write elements without line breaks between them.
+    pub indent: Option<usize>,
+    pub newline: Newline,
+    /// Whether empty elements are written as `<a/>` rather than `<a></a>`.
+    pub self_closing: bool,
+    /// How attribute values, including those of the XML declaration, are
+    /// quoted.
+    pub quote: Quote,
+    /// How the points of graphical functions, in `<xpts>` and `<ypts>`, are
+    /// written. Other numbers are always written in shortest form.
+    pub point_format: FloatFormat,
+    /// Whether attributes set to the default value the XMILE specification
+    /// gives them, such as `method="euler"` on `<sim_specs>`, are written.
+    pub emit_defaults: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            indent: None,
+            newline: Newline::Lf,
+            self_closing: true,
+            quote: Quote::Double,
+            point_format: FloatFormat::Shortest,
+            emit_defaults: true,
+        }
+    }
+}
+
+/// Rewrites a document written with double-quoted attribute values so that
+/// they are between `quote`.
+///
+/// The document is scanned rather than parsed, so nothing is unescaped and
+/// escaped again: only the quotes around attribute values change, with a
+/// `'` inside a double-quoted value written as `&apos;`. Values already
+/// between single quotes, such as those of restored vendor elements, are
+/// kept as they are.
+pub(crate) fn requote(xml: &str, quote: Quote) -> String {
+    if quote == Quote::Double {
+        return xml.to_string();
+    }
+    let mut output = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Comments, CDATA sections and doctype declarations have no
+        // attributes
+        let verbatim = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<!", ">")]
+            .into_iter()
+            .find(|(open, _)| rest.starts_with(open));
+        if let Some((_, close)) = verbatim {
+            let end = rest.find(close).map_or(rest.len(), |end| end + close.len());
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        // A tag or the XML declaration, up to the `>` outside any value
+        let mut delimiter = None;
+        let mut end = rest.len();
+        for (index, c) in rest.char_indices() {
+            match (delimiter, c) {
+                (None, '"') => {
+                    delimiter = Some('"');
+                    output.push('\'');
+                }
+                (None, '\'') => {
+                    delimiter = Some('\'');
+                    output.push('\'');
+                }
+                (None, '>') => {
+                    output.push('>');
+                    end = index + 1;
+                    break;
+                }
+                (Some('"'), '"') => {
+                    delimiter = None;
+                    output.push('\'');
+                }
+                (Some('"'), '\'') => output.push_str("&apos;"),
+                (Some('\''), '\'') => {
+                    delimiter = None;
+                    output.push('\'');
+                }
+                (_, c) => output.push(c),
+            }
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Converts the line endings of a document.
+pub(crate) fn convert_newlines(xml: String, newline: Newline) -> String {
+    match newline {
+        Newline::Lf => xml,
+        Newline::CrLf => xml.replace("\r\n", "\n").replace('\n', "\r\n"),
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<SerializeOptions> = RefCell::new(SerializeOptions::default());
+}
+
+/// Runs `f` with `options` as the current serialization options, restoring
+/// the previous options afterwards.
+pub(crate) fn with_options<R>(options: &SerializeOptions, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<SerializeOptions>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Some(previous) = self.0.take() {
+                CURRENT.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+    }
+
+    let previous = CURRENT.with(|current| current.replace(options.clone()));
+    let _restore = Restore(Some(previous));
+    f()
+}
+
+/// Formats a point of a graphical function with the current serialization
+/// options.
+pub(crate) fn format_point(value: f64) -> String {
+    CURRENT.with(|current| current.borrow().point_format.format(value))
+}
+
+/// Whether attributes set to their default value are left out with the
+/// current serialization options.
+pub(crate) fn omit_defaults() -> bool {
+    CURRENT.with(|current| !current.borrow().emit_defaults)
+}
+
+/// Whether to leave out a flag attribute that defaults to false: when it is
+/// unset, or set to false while defaults are left out.
+pub(crate) fn skip_false(value: &Option<bool>) -> bool {
+    value.is_none_or(|value| !value && omit_defaults())
+}
+
+/// Whether to leave out an attribute that defaults to the keyword `default`:
+/// when it is unset, or set to the default while defaults are left out.
+pub(crate) fn skip_keyword(value: Option<&str>, default: &str) -> bool {
+    value.is_none_or(|value| omit_defaults() && value.trim().eq_ignore_ascii_case(default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float_formats() {
+        assert_eq!(FloatFormat::Shortest.format(1.0), "1");
+        assert_eq!(FloatFormat::Shortest.format(-0.0), "0");
+        assert_eq!(FloatFormat::Significant(2).format(12345.0), "12000");
+        assert_eq!(FloatFormat::Significant(4).format(2.0 / 3.0), "0.6667");
+        assert_eq!(FloatFormat::Decimals(2).format(1.005001), "1.01");
+        assert_eq!(FloatFormat::Decimals(2).format(1.0), "1");
+        assert_eq!(FloatFormat::Decimals(0).format(-2.6), "-3");
+
+        let options = SerializeOptions {
+            point_format: FloatFormat::Decimals(1),
+            ..SerializeOptions::default()
+        };
+        assert_eq!(with_options(&options, || format_point(0.26)), "0.3");
+        assert_eq!(format_point(0.26), "0.26");
+    }
+
+    #[test]
+    fn test_requote() {
+        let xml = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<a b="it's &quot;x&quot;&#10;" c='say "hi"'>"#,
+            r#"<!-- "c" --><![CDATA[<d e="f">]]>"quoted" &amp; it's<g/></a>"#,
+        );
+        assert_eq!(
+            requote(xml, Quote::Single),
+            concat!(
+                r#"<?xml version='1.0' encoding='UTF-8'?>"#,
+                r#"<a b='it&apos;s &quot;x&quot;&#10;' c='say "hi"'>"#,
+                r#"<!-- "c" --><![CDATA[<d e="f">]]>"quoted" &amp; it's<g/></a>"#,
+            )
+        );
+        assert_eq!(requote(xml, Quote::Double), xml);
+        assert_eq!(
+            convert_newlines("<a>\n<b/>\r\n</a>".to_string(), Newline::CrLf),
+            "<a>\r\n<b/>\r\n</a>"
+        );
+    }
+
+    #[test]
+    fn test_skip_defaults() {
+        let omitting = SerializeOptions {
+            emit_defaults: false,
+            ..SerializeOptions::default()
+        };
+        assert!(skip_false(&None));
+        assert!(!skip_false(&Some(false)));
+        assert!(with_options(&omitting, || skip_false(&Some(false))));
+        assert!(!with_options(&omitting, || skip_false(&Some(true))));
+        assert!(!skip_keyword(Some("Euler"), "euler"));
+        assert!(with_options(&omitting, || skip_keyword(
+            Some("Euler"),
+            "euler"
+        )));
+        assert!(!with_options(&omitting, || skip_keyword(
+            Some("rk4"),
+            "euler"
+        )));
+    }
+}
@@ -0,0 +1,223 @@
+//! Sharing a parsed file between threads.
+//!
+//! [`XmileFile`] and everything it contains are plain owned data with no
+//! interior mutability, so the file is `Send + Sync`: any number of threads
+//! may read, validate, evaluate or serialize one file at the same time. The
+//! options used by [`XmileFile::to_xml_string_with`] are held per thread, so
+//! threads serializing with different options do not affect each other. The
+//! registries built from a file, [`GraphicalFunctionTable`], and the
+//! [`Simulator`]s and [`Run`]s prepared from a file are `Send + Sync` as
+//! well. These guarantees are checked at compile time below.
+//!
+//! [`SharedModel`] is a cheaply cloned handle to a file for services that
+//! serve many requests against one model. Each thread can read the file, or
+//! prepare a [`Simulator`] of its own with [`SharedModel::simulator`]:
+//!
+//! ```rust
+//! use std::thread;
+//! use xmile::xml::SharedModel;
+//!
+//! let model = SharedModel::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <sim_specs><start>0</start><stop>10</stop></sim_specs>
+//!     <model><variables><aux name="Rate"><eqn>0.1</eqn></aux></variables></model>
+//! </xmile>"#).unwrap();
+//!
+//! let workers: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let model = model.clone();
+//!         thread::spawn(move || {
+//!             let specs = model.sim_specs.as_ref().unwrap();
+//!             let run = model.simulator(specs).unwrap().run().unwrap();
+//!             (model.variables().count(), run.times.len())
+//!         })
+//!     })
+//!     .collect();
+//! for worker in workers {
+//!     assert_eq!(worker.join().unwrap(), (1, 11));
+//! }
+//! ```
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use super::{ParseError, XmileFile};
+use crate::model::vars::gf::{GraphicalFunctionRegistry, GraphicalFunctionTable};
+use crate::simulation::{Run, SimulationError, Simulator};
+use crate::specs::SimulationSpecs;
+
+#[cfg(feature = "macros")]
+use crate::r#macro::MacroRegistry;
+
+/// A read-only file shared between threads.
+///
+/// Cloning the handle does not copy the file. Use
+/// [`make_mut`](SharedModel::make_mut) to edit it: the edit is made to a
+/// private copy if other handles exist, so readers never see a file change
+/// under them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedModel {
+    file: Arc<XmileFile>,
+}
+
+impl SharedModel {
+    pub fn new(file: XmileFile) -> Self {
+        SharedModel {
+            file: Arc::new(file),
+        }
+    }
+
+    /// Parses a file to share. See [`XmileFile::from_str`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(xml: &str) -> Result<Self, ParseError> {
+        XmileFile::from_str(xml).map(SharedModel::new)
+    }
+
+    pub fn file(&self) -> &XmileFile {
+        &self.file
+    }
+
+    /// The file for editing, copied first if other handles share it.
+    pub fn make_mut(&mut self) -> &mut XmileFile {
+        Arc::make_mut(&mut self.file)
+    }
+
+    /// Prepares the file's root model for running with `specs`, as
+    /// [`Simulator::from_file`] does. The simulator owns everything it needs,
+    /// so each thread can prepare and run its own.
+    pub fn simulator(&self, specs: &SimulationSpecs) -> Result<Simulator, SimulationError> {
+        Simulator::from_file(&self.file, specs)
+    }
+
+    /// Whether `self` and `other` are handles to the same file, rather than
+    /// to equal files.
+    pub fn ptr_eq(&self, other: &SharedModel) -> bool {
+        Arc::ptr_eq(&self.file, &other.file)
+    }
+}
+
+impl Deref for SharedModel {
+    type Target = XmileFile;
+
+    fn deref(&self) -> &XmileFile {
+        &self.file
+    }
+}
+
+impl From<XmileFile> for SharedModel {
+    fn from(file: XmileFile) -> Self {
+        SharedModel::new(file)
+    }
+}
+
+impl From<Arc<XmileFile>> for SharedModel {
+    fn from(file: Arc<XmileFile>) -> Self {
+        SharedModel { file }
+    }
+}
+
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<XmileFile>();
+    assert_send_sync::<SharedModel>();
+    assert_send_sync::<GraphicalFunctionRegistry>();
+    assert_send_sync::<GraphicalFunctionTable>();
+    assert_send_sync::<Simulator>();
+    assert_send_sync::<Run>();
+    #[cfg(feature = "macros")]
+    assert_send_sync::<MacroRegistry>();
+};
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn model() -> SharedModel {
+        SharedModel::from_str(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+            <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+            <model><variables><aux name="Rate"><eqn>0.1</eqn></aux></variables></model>
+        </xmile>"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_clone_shares_file() {
+        let model = model();
+        let other = model.clone();
+        assert!(model.ptr_eq(&other));
+        assert_eq!(other.models.len(), 1);
+    }
+
+    #[test]
+    fn test_make_mut_copies_on_write() {
+        let model = model();
+        let mut edited = model.clone();
+        edited.make_mut().models[0].name = Some("Edited".to_string());
+        assert!(!model.ptr_eq(&edited));
+        assert_eq!(model.models[0].name, None);
+        assert_eq!(edited.models[0].name.as_deref(), Some("Edited"));
+    }
+
+    #[test]
+    fn test_simulators_per_thread() {
+        let model = SharedModel::from_str(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+            <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+            <model><variables>
+                <stock name="Balance"><eqn>100</eqn><inflow>Interest</inflow></stock>
+                <flow name="Interest"><eqn>Balance * Rate</eqn></flow>
+                <aux name="Rate"><eqn>0.1</eqn></aux>
+            </variables></model>
+        </xmile>"#,
+        )
+        .unwrap();
+
+        // Each thread runs the shared model for its own number of years
+        let workers: Vec<_> = (1..=4)
+            .map(|years| {
+                let model = model.clone();
+                thread::spawn(move || {
+                    let specs = SimulationSpecs {
+                        start: 0.0,
+                        stop: years as f64,
+                        dt: None,
+                        method: None,
+                        time_units: None,
+                        pause: None,
+                        run_by: None,
+                    };
+                    let run = model.simulator(&specs).unwrap().run().unwrap();
+                    *run.series("Balance").unwrap().last().unwrap()
+                })
+            })
+            .collect();
+        let balances: Vec<f64> = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect();
+        for (years, balance) in (1..=4).zip(balances) {
+            assert!((balance - 100.0 * 1.1f64.powi(years)).abs() < 1e-9);
+        }
+
+        // A file of macros alone has no model to prepare
+        let mut empty = model.clone();
+        empty.make_mut().models.clear();
+        assert!(matches!(
+            empty.simulator(&SimulationSpecs {
+                start: 0.0,
+                stop: 1.0,
+                dt: None,
+                method: None,
+                time_units: None,
+                pause: None,
+                run_by: None,
+            }),
+            Err(SimulationError::NoModel)
+        ));
+    }
+}
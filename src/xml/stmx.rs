@@ -0,0 +1,381 @@
+//! Stella and iThink (`.stmx`, `.itmx`) files.
+//!
+//! isee systems tools write XMILE with extensions of their own: elements
+//! such as `<isee:prefs>` in the header and `<isee:dependencies>` in the
+//! model, and attributes such as `isee:sim_duration` on `<sim_specs>`, all
+//! in the `http://iseesystems.com/XMILE` namespace. The crate does not model
+//! these extensions, so without special handling they would be lost when a
+//! Stella file is read and written back.
+//!
+//! A file is read in the [`Dialect::Stella`] dialect when its root element
+//! declares the isee namespace, or when it is read from a path with a
+//! `.stmx` or `.itmx` extension. Every isee element and attribute is then
+//! kept verbatim in [`XmileFile::extensions`](super::XmileFile::extensions),
+//! with the path of the element it belongs to, and written back in place
+//! when the file is serialized:
+//!
+//! ```rust
+//! use xmile::xml::XmileFile;
+//!
+//! let file = XmileFile::from_str(r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0" xmlns:isee="http://iseesystems.com/XMILE">
+//!     <header><vendor>isee systems, inc.</vendor><product version="3.0">Stella Architect</product><isee:prefs show_module_prefix="true"/></header>
+//!     <model><variables/></model>
+//! </xmile>"#).unwrap();
+//! assert_eq!(file.extensions.len(), 1);
+//! assert!(file.to_xml_string().unwrap().contains(r#"<isee:prefs show_module_prefix="true"/></header>"#));
+//! ```
+//!
+//! Extensions are placed by the path of their element, such as the second
+//! `<view>` of the first model's `<views>`. An extension whose element no
+//! longer exists when the file is written, because the element was removed,
+//! is dropped.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+use super::ParseError;
+
+/// The namespace of isee systems extensions.
+pub const ISEE_NAMESPACE: &str = "http://iseesystems.com/XMILE";
+
+/// The prefix isee systems tools bind to [`ISEE_NAMESPACE`].
+pub const ISEE_PREFIX: &str = "isee";
+
+/// The flavour of XMILE a document is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Dialect {
+    /// XMILE as written by any tool; vendor extensions are not kept.
+    #[default]
+    Xmile,
+    /// XMILE written by Stella or iThink, whose isee extensions are kept
+    /// verbatim.
+    Stella,
+}
+
+impl Dialect {
+    /// The dialect implied by the extension of `path`, if it implies one.
+    pub fn of_path(path: impl AsRef<Path>) -> Option<Dialect> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        if extension.eq_ignore_ascii_case("stmx") || extension.eq_ignore_ascii_case("itmx") {
+            Some(Dialect::Stella)
+        } else if extension.eq_ignore_ascii_case("xmile") || extension.eq_ignore_ascii_case("xml") {
+            Some(Dialect::Xmile)
+        } else {
+            None
+        }
+    }
+
+    /// The dialect of `xml`, by the namespaces declared on its root element.
+    pub fn of_document(xml: &str) -> Dialect {
+        if super::namespaces::root_namespaces(xml)
+            .iter()
+            .any(|(_, uri)| uri == ISEE_NAMESPACE)
+        {
+            Dialect::Stella
+        } else {
+            Dialect::Xmile
+        }
+    }
+
+    /// The dialect of `xml` read from `path`: the dialect implied by the
+    /// path, and otherwise the dialect of the document.
+    pub fn of_file(path: impl AsRef<Path>, xml: &str) -> Dialect {
+        match Dialect::of_path(path) {
+            Some(Dialect::Stella) => Dialect::Stella,
+            _ => Dialect::of_document(xml),
+        }
+    }
+}
+
+/// A vendor element or attribute kept verbatim from a document.
+///
+/// Elements are identified by their path from the root, with the position
+/// of each element among its siblings of the same name, as in
+/// `/xmile[0]/model[0]/views[0]/view[1]`. Positions count only elements the
+/// crate reads, so they match the document as the crate writes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Extension {
+    /// An element, as its source text, within the element at `parent`.
+    Element { parent: String, xml: String },
+    /// An attribute of the element at `element`, with its value as written
+    /// in the source, escapes included.
+    Attribute {
+        element: String,
+        name: String,
+        value: String,
+    },
+}
+
+/// Collects the isee elements and attributes of `xml`, given the namespace
+/// declarations on its root element.
+pub(crate) fn extensions(xml: &str, namespaces: &[(String, String)]) -> Vec<Extension> {
+    let mut prefixes: Vec<&str> = namespaces
+        .iter()
+        .filter(|(_, uri)| uri == ISEE_NAMESPACE)
+        .map(|(prefix, _)| prefix.as_str())
+        .collect();
+    if !prefixes.contains(&ISEE_PREFIX) {
+        prefixes.push(ISEE_PREFIX);
+    }
+    let is_vendor = |name: &str| {
+        name.split_once(':')
+            .is_some_and(|(prefix, _)| prefixes.contains(&prefix))
+    };
+
+    let mut extensions = Vec::new();
+    let mut reader = Reader::from_str(xml);
+    let mut paths = Paths::default();
+    loop {
+        let before = reader.buffer_position() as usize;
+        let (start, is_empty) = match reader.read_event() {
+            Ok(Event::Start(start)) => (start, false),
+            Ok(Event::Empty(start)) => (start, true),
+            Ok(Event::End(_)) => {
+                paths.close();
+                continue;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        let name = element_name(&start);
+        if is_vendor(&name) {
+            if !is_empty && reader.read_to_end(start.name()).is_err() {
+                break;
+            }
+            let after = reader.buffer_position() as usize;
+            extensions.push(Extension::Element {
+                parent: paths.current().to_string(),
+                xml: xml[before..after].to_string(),
+            });
+            continue;
+        }
+
+        let path = paths.open(&name);
+        for attribute in start.attributes().map_while(Result::ok) {
+            let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            if is_vendor(&key) {
+                extensions.push(Extension::Attribute {
+                    element: path.clone(),
+                    name: key,
+                    value: String::from_utf8_lossy(&attribute.value).into_owned(),
+                });
+            }
+        }
+        if is_empty {
+            paths.close();
+        }
+    }
+    extensions
+}
+
+/// Writes `extensions` back into `xml`, a document serialized by the crate.
+pub(crate) fn restore(xml: String, extensions: &[Extension]) -> Result<String, ParseError> {
+    if extensions.is_empty() {
+        return Ok(xml);
+    }
+    let mut elements: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut attributes: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+    for extension in extensions {
+        match extension {
+            Extension::Element { parent, xml } => {
+                elements.entry(parent).or_default().push(xml);
+            }
+            Extension::Attribute {
+                element,
+                name,
+                value,
+            } => {
+                attributes.entry(element).or_default().push((name, value));
+            }
+        }
+    }
+    let attributes_of = |path: &str| {
+        attributes
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| format!(" {name}=\"{value}\""))
+            .collect::<String>()
+    };
+
+    let mut output = String::with_capacity(xml.len());
+    let mut reader = Reader::from_str(&xml);
+    let mut paths = Paths::default();
+    let mut copied = 0;
+    loop {
+        let before = reader.buffer_position() as usize;
+        let event = reader
+            .read_event()
+            .map_err(|e| ParseError::Xml(e.to_string()))?;
+        let after = reader.buffer_position() as usize;
+        output.push_str(&xml[copied..before]);
+        copied = after;
+        let tag = &xml[before..after];
+        match event {
+            Event::Start(start) => {
+                let path = paths.open(&element_name(&start));
+                output.push_str(&tag[..tag.len() - 1]);
+                output.push_str(&attributes_of(&path));
+                output.push('>');
+            }
+            Event::Empty(start) => {
+                let name = element_name(&start);
+                let path = paths.open(&name);
+                output.push_str(tag[..tag.len() - 2].trim_end());
+                output.push_str(&attributes_of(&path));
+                match elements.get(path.as_str()) {
+                    Some(children) => {
+                        output.push('>');
+                        output.extend(children.iter().copied());
+                        output.push_str(&format!("</{name}>"));
+                    }
+                    None => output.push_str("/>"),
+                }
+                paths.close();
+            }
+            Event::End(_) => {
+                if let Some(children) = elements.get(paths.current()) {
+                    output.extend(children.iter().copied());
+                }
+                output.push_str(tag);
+                paths.close();
+            }
+            Event::Eof => break,
+            _ => output.push_str(tag),
+        }
+    }
+    output.push_str(&xml[copied..]);
+    Ok(output)
+}
+
+fn element_name(start: &BytesStart) -> String {
+    String::from_utf8_lossy(start.name().as_ref()).into_owned()
+}
+
+/// The paths of the open elements of a document being read, with the
+/// number of children of each name seen in each.
+#[derive(Default)]
+struct Paths {
+    open: Vec<(String, HashMap<String, usize>)>,
+    root: HashMap<String, usize>,
+}
+
+impl Paths {
+    /// The path of the innermost open element, or `""` at the root.
+    fn current(&self) -> &str {
+        self.open.last().map_or("", |(path, _)| path.as_str())
+    }
+
+    /// Opens a child element of the innermost open element, returning its
+    /// path.
+    fn open(&mut self, name: &str) -> String {
+        let (parent, counts) = match self.open.last_mut() {
+            Some((path, counts)) => (path.as_str(), counts),
+            None => ("", &mut self.root),
+        };
+        let count = counts.entry(name.to_string()).or_default();
+        let path = format!("{parent}/{name}[{count}]");
+        *count += 1;
+        self.open.push((path.clone(), HashMap::new()));
+        path
+    }
+
+    fn close(&mut self) {
+        self.open.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xml::XmileFile;
+
+    const STELLA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0" xmlns:isee="http://iseesystems.com/XMILE">
+    <header>
+        <vendor>isee systems, inc.</vendor>
+        <product version="3.0" isee:build_number="2517">Stella Architect</product>
+        <isee:prefs show_module_prefix="true" layer="model">
+            <isee:window width="1024" height="768"/>
+        </isee:prefs>
+    </header>
+    <sim_specs isee:sim_duration="1.5" method="Euler" time_units="Months">
+        <start>0</start>
+        <stop>12</stop>
+        <dt>0.25</dt>
+    </sim_specs>
+    <model>
+        <variables>
+            <aux name="Rate"><eqn>0.1</eqn></aux>
+            <aux name="Fraction"><eqn>0.5</eqn><isee:delay_aux/></aux>
+        </variables>
+    </model>
+</xmile>"#;
+
+    #[test]
+    fn test_detect_dialect() {
+        assert_eq!(Dialect::of_path("model.STMX"), Some(Dialect::Stella));
+        assert_eq!(Dialect::of_path("model.itmx"), Some(Dialect::Stella));
+        assert_eq!(Dialect::of_path("model.xmile"), Some(Dialect::Xmile));
+        assert_eq!(Dialect::of_path("model.txt"), None);
+        assert_eq!(Dialect::of_document(STELLA), Dialect::Stella);
+        assert_eq!(
+            Dialect::of_document(r#"<xmile version="1.0"><header/></xmile>"#),
+            Dialect::Xmile
+        );
+        assert_eq!(
+            Dialect::of_file("model.stmx", r#"<xmile version="1.0"/>"#),
+            Dialect::Stella
+        );
+
+        let extensions = extensions(
+            STELLA,
+            &[(ISEE_PREFIX.to_string(), ISEE_NAMESPACE.to_string())],
+        );
+        assert_eq!(extensions.len(), 4);
+        assert_eq!(
+            extensions[0],
+            Extension::Attribute {
+                element: "/xmile[0]/header[0]/product[0]".to_string(),
+                name: "isee:build_number".to_string(),
+                value: "2517".to_string(),
+            }
+        );
+        assert!(matches!(
+            &extensions[1],
+            Extension::Element { parent, xml } if parent == "/xmile[0]/header[0]"
+                && xml.starts_with("<isee:prefs") && xml.ends_with("</isee:prefs>")
+        ));
+        assert!(matches!(
+            &extensions[3],
+            Extension::Element { parent, .. } if parent == "/xmile[0]/model[0]/variables[0]/aux[1]"
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_keeps_extensions() {
+        let file = XmileFile::from_str(STELLA).unwrap();
+        let xml = file.to_xml_string().unwrap();
+        assert!(xml.contains(r#"xmlns:isee="http://iseesystems.com/XMILE""#));
+        assert!(xml.contains(r#" isee:build_number="2517">Stella Architect</product>"#));
+        assert!(xml.contains(r#"<isee:window width="1024" height="768"/>"#));
+        assert!(xml.contains(r#" isee:sim_duration="1.5""#));
+        assert!(xml.contains("<isee:delay_aux/></aux>"));
+
+        let again = XmileFile::from_str(&xml).unwrap();
+        assert_eq!(again.extensions, file.extensions);
+        assert_eq!(again, file);
+
+        // Files in other dialects keep nothing
+        let plain = STELLA.replace(r#" xmlns:isee="http://iseesystems.com/XMILE""#, "");
+        let plain = plain.replace("<isee:delay_aux/>", "");
+        assert!(XmileFile::from_str(&plain).unwrap().extensions.is_empty());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.stmx");
+        std::fs::write(&path, &plain).unwrap();
+        assert_eq!(XmileFile::from_file(&path).unwrap().extensions.len(), 3);
+    }
+}
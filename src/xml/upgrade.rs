@@ -0,0 +1,399 @@
+//! Upgrading files written against pre-1.0 drafts of the XMILE specification.
+//!
+//! Drafts of XMILE circulated before the 1.0 standard, and archives of
+//! models written by tools of that period differ from 1.0 in small ways:
+//!
+//! - the root element declares the draft namespace
+//!   `http://www.systemdynamics.org/XMILE` rather than the OASIS one;
+//! - the `version` attribute is a draft number such as `0.9`, or missing;
+//! - the root element carries a `level` attribute, which 1.0 removed;
+//! - element and attribute names are spelled with hyphens rather than
+//!   underscores, as in `<sim-specs>` or `time-units="Months"`.
+//!
+//! [`upgrade`] rewrites such a document into its 1.0 spelling and reports
+//! every [`Transformation`] it applied, and
+//! [`XmileFile::from_str_upgrading`](super::XmileFile::from_str_upgrading)
+//! parses the result. Documents declaring version 1.0 in the 1.0 namespace
+//! are returned unchanged.
+//!
+//! ```rust
+//! use xmile::xml::XmileFile;
+//!
+//! let (file, report) = XmileFile::from_str_upgrading(r#"<xmile version="0.9" level="2" xmlns="http://www.systemdynamics.org/XMILE">
+//!     <header><vendor>Test</vendor><product version="1.0">Test</product></header>
+//!     <model><variables><aux name="Rate"><eqn>1</eqn></aux></variables></model>
+//! </xmile>"#).unwrap();
+//! assert_eq!(file.version, "1.0");
+//! assert_eq!(report.from_version.as_deref(), Some("0.9"));
+//! assert_eq!(report.transformations.len(), 3);
+//! ```
+
+use std::fmt;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, Writer};
+
+use super::ParseError;
+use super::namespaces::SUPPORTED_VERSION;
+
+/// The namespace of the XMILE 1.0 specification.
+const XMILE_NAMESPACE: &str = "http://docs.oasis-open.org/xmile/ns/XMILE/v1.0";
+
+/// Namespaces declared by files written against drafts of the specification.
+const DRAFT_NAMESPACES: &[&str] = &[
+    "http://www.systemdynamics.org/XMILE",
+    "http://www.systemdynamics.org/XMILE/",
+];
+
+/// A change made to a document while upgrading it to XMILE 1.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transformation {
+    /// The draft namespace was replaced with the 1.0 namespace.
+    Namespace { from: String },
+    /// The `version` attribute was set to `1.0`.
+    Version { from: Option<String> },
+    /// An attribute of the root element not part of 1.0 was removed.
+    RootAttributeRemoved { name: String },
+    /// Elements were renamed to their 1.0 spelling.
+    ElementRenamed {
+        from: String,
+        to: String,
+        count: usize,
+    },
+    /// Attributes were renamed to their 1.0 spelling.
+    AttributeRenamed {
+        from: String,
+        to: String,
+        count: usize,
+    },
+}
+
+impl fmt::Display for Transformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transformation::Namespace { from } => {
+                write!(
+                    f,
+                    "Replaced draft namespace '{}' with '{}'",
+                    from, XMILE_NAMESPACE
+                )
+            }
+            Transformation::Version { from: Some(from) } => {
+                write!(f, "Upgraded version '{}' to '{}'", from, SUPPORTED_VERSION)
+            }
+            Transformation::Version { from: None } => {
+                write!(f, "Added missing version '{}'", SUPPORTED_VERSION)
+            }
+            Transformation::RootAttributeRemoved { name } => {
+                write!(f, "Removed root attribute '{}'", name)
+            }
+            Transformation::ElementRenamed { from, to, count } => {
+                write!(f, "Renamed element <{}> to <{}>", from, to)?;
+                write_count(f, *count)
+            }
+            Transformation::AttributeRenamed { from, to, count } => {
+                write!(f, "Renamed attribute '{}' to '{}'", from, to)?;
+                write_count(f, *count)
+            }
+        }
+    }
+}
+
+fn write_count(f: &mut fmt::Formatter<'_>, count: usize) -> fmt::Result {
+    if count > 1 {
+        write!(f, " ({} times)", count)?;
+    }
+    Ok(())
+}
+
+/// The transformations applied while upgrading a document.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UpgradeReport {
+    /// The version the document declared, if any.
+    pub from_version: Option<String>,
+    /// The transformations applied, in the order first applied.
+    pub transformations: Vec<Transformation>,
+}
+
+impl UpgradeReport {
+    /// Whether the document was already XMILE 1.0.
+    pub fn is_empty(&self) -> bool {
+        self.transformations.is_empty()
+    }
+
+    fn renamed_element(&mut self, from: &str, to: &str) {
+        for transformation in &mut self.transformations {
+            if let Transformation::ElementRenamed {
+                from: existing,
+                count,
+                ..
+            } = transformation
+                && existing == from
+            {
+                *count += 1;
+                return;
+            }
+        }
+        self.transformations.push(Transformation::ElementRenamed {
+            from: from.to_string(),
+            to: to.to_string(),
+            count: 1,
+        });
+    }
+
+    fn renamed_attribute(&mut self, from: &str, to: &str) {
+        for transformation in &mut self.transformations {
+            if let Transformation::AttributeRenamed {
+                from: existing,
+                count,
+                ..
+            } = transformation
+                && existing == from
+            {
+                *count += 1;
+                return;
+            }
+        }
+        self.transformations.push(Transformation::AttributeRenamed {
+            from: from.to_string(),
+            to: to.to_string(),
+            count: 1,
+        });
+    }
+}
+
+impl fmt::Display for UpgradeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No changes; the document is XMILE {}", SUPPORTED_VERSION);
+        }
+        for transformation in &self.transformations {
+            writeln!(f, "{}", transformation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Upgrades a document written against a draft of XMILE to version 1.0.
+///
+/// A document is treated as a draft when its root element declares a draft
+/// namespace, or a version that is missing or a number below 1.0.
+pub fn upgrade(xml: &str) -> Result<(String, UpgradeReport), ParseError> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::with_capacity(xml.len()));
+    let mut report = UpgradeReport::default();
+    let xml_error = |e: &dyn fmt::Display| ParseError::Xml(e.to_string());
+    let mut root = true;
+    let mut draft = false;
+
+    loop {
+        let event = reader.read_event().map_err(|e| xml_error(&e))?;
+        let event = match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let upgraded = if root {
+                    root = false;
+                    let version = attribute(start, "version")?;
+                    let namespace = attribute(start, "xmlns")?;
+                    report.from_version = version.clone();
+                    draft = is_draft(version.as_deref(), namespace.as_deref());
+                    if !draft {
+                        return Ok((xml.to_string(), report));
+                    }
+                    upgrade_root(start, &mut report)?
+                } else {
+                    upgrade_element(start, &mut report)?
+                };
+                if matches!(event, Event::Start(_)) {
+                    Event::Start(upgraded)
+                } else {
+                    Event::Empty(upgraded)
+                }
+            }
+            Event::End(ref end) if draft => {
+                let name = String::from_utf8_lossy(end.name().as_ref()).into_owned();
+                match upgraded_name(&name) {
+                    Some(name) => Event::End(quick_xml::events::BytesEnd::new(name)),
+                    None => event,
+                }
+            }
+            Event::Eof => break,
+            event => event,
+        };
+        writer.write_event(event).map_err(|e| xml_error(&e))?;
+    }
+
+    let upgraded = String::from_utf8(writer.into_inner()).map_err(|e| xml_error(&e))?;
+    Ok((upgraded, report))
+}
+
+fn attribute(start: &BytesStart, name: &str) -> Result<Option<String>, ParseError> {
+    start
+        .try_get_attribute(name)
+        .map_err(|e| ParseError::Xml(e.to_string()))?
+        .map(|attribute| {
+            attribute
+                .unescape_value()
+                .map(|value| value.into_owned())
+                .map_err(|e| ParseError::Xml(e.to_string()))
+        })
+        .transpose()
+}
+
+fn is_draft(version: Option<&str>, namespace: Option<&str>) -> bool {
+    if namespace.is_some_and(|namespace| DRAFT_NAMESPACES.contains(&namespace)) {
+        return true;
+    }
+    match version {
+        None => true,
+        Some(version) => version
+            .trim()
+            .parse::<f64>()
+            .is_ok_and(|version| version < 1.0),
+    }
+}
+
+/// The 1.0 spelling of a draft element or attribute name, if it differs.
+/// Drafts spelled multi-word names with hyphens where 1.0 uses underscores;
+/// names with a vendor prefix are left alone.
+fn upgraded_name(name: &str) -> Option<String> {
+    if name.contains(':') || !name.contains('-') {
+        return None;
+    }
+    Some(name.replace('-', "_"))
+}
+
+fn upgrade_root(
+    start: &BytesStart,
+    report: &mut UpgradeReport,
+) -> Result<BytesStart<'static>, ParseError> {
+    let mut upgraded = BytesStart::new("xmile");
+    let mut has_version = false;
+    let mut has_namespace = false;
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| ParseError::Xml(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| ParseError::Xml(e.to_string()))?
+            .into_owned();
+        match key.as_str() {
+            "version" => {
+                has_version = true;
+                if value != SUPPORTED_VERSION {
+                    report
+                        .transformations
+                        .push(Transformation::Version { from: Some(value) });
+                }
+                upgraded.push_attribute(("version", SUPPORTED_VERSION));
+            }
+            "xmlns" => {
+                has_namespace = true;
+                if DRAFT_NAMESPACES.contains(&value.as_str()) {
+                    report
+                        .transformations
+                        .push(Transformation::Namespace { from: value });
+                }
+                upgraded.push_attribute(("xmlns", XMILE_NAMESPACE));
+            }
+            "level" => report
+                .transformations
+                .push(Transformation::RootAttributeRemoved { name: key }),
+            _ => match upgraded_name(&key) {
+                Some(name) => {
+                    report.renamed_attribute(&key, &name);
+                    upgraded.push_attribute((name.as_str(), value.as_str()));
+                }
+                None => upgraded.push_attribute(attribute),
+            },
+        }
+    }
+    if !has_version {
+        report
+            .transformations
+            .push(Transformation::Version { from: None });
+        upgraded.push_attribute(("version", SUPPORTED_VERSION));
+    }
+    if !has_namespace {
+        upgraded.push_attribute(("xmlns", XMILE_NAMESPACE));
+    }
+    Ok(upgraded)
+}
+
+fn upgrade_element(
+    start: &BytesStart,
+    report: &mut UpgradeReport,
+) -> Result<BytesStart<'static>, ParseError> {
+    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut upgraded = match upgraded_name(&name) {
+        Some(renamed) => {
+            report.renamed_element(&name, &renamed);
+            BytesStart::new(renamed)
+        }
+        None => BytesStart::new(name),
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| ParseError::Xml(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        match upgraded_name(&key) {
+            Some(renamed) => {
+                report.renamed_attribute(&key, &renamed);
+                upgraded.push_attribute(quick_xml::events::attributes::Attribute {
+                    key: quick_xml::name::QName(renamed.as_bytes()),
+                    value: attribute.value,
+                });
+            }
+            None => upgraded.push_attribute(attribute),
+        }
+    }
+    Ok(upgraded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_version_unchanged() {
+        let xml = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0"><sim-specs/></xmile>"#;
+        let (upgraded, report) = upgrade(xml).unwrap();
+        assert_eq!(upgraded, xml);
+        assert!(report.is_empty());
+        assert_eq!(report.from_version.as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn test_draft_upgraded() {
+        let xml = r#"<xmile level="2" xmlns="http://www.systemdynamics.org/XMILE" xmlns:isee="urn:isee"><sim-specs isee:sim-duration="1"><time-units>Months</time-units></sim-specs><gf><non-negative/></gf><view page-width="800" page-height="600"/><view page-width="800"/></xmile>"#;
+        let (upgraded, report) = upgrade(xml).unwrap();
+        assert_eq!(
+            upgraded,
+            r#"<xmile xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0" xmlns:isee="urn:isee" version="1.0"><sim_specs isee:sim-duration="1"><time_units>Months</time_units></sim_specs><gf><non_negative/></gf><view page_width="800" page_height="600"/><view page_width="800"/></xmile>"#
+        );
+        assert_eq!(
+            report.transformations[..3],
+            [
+                Transformation::RootAttributeRemoved {
+                    name: "level".to_string()
+                },
+                Transformation::Namespace {
+                    from: "http://www.systemdynamics.org/XMILE".to_string()
+                },
+                Transformation::Version { from: None },
+            ]
+        );
+        assert!(
+            report
+                .transformations
+                .contains(&Transformation::AttributeRenamed {
+                    from: "page-width".to_string(),
+                    to: "page_width".to_string(),
+                    count: 2,
+                })
+        );
+        assert!(
+            report
+                .to_string()
+                .contains("Renamed attribute 'page-width' to 'page_width' (2 times)")
+        );
+    }
+}
@@ -3,11 +3,14 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
-    Identifier, Uid,
+    Identifier,
     model::vars::{Var, Variable},
     types::ValidationResult,
 };
 
+#[cfg(feature = "views")]
+use crate::Uid;
+
 /// Extract variable name from a Variable enum variant
 pub fn get_variable_name(var: &Variable) -> Option<&Identifier> {
     match var {
@@ -26,19 +29,25 @@ pub fn get_variable_name(var: &Variable) -> Option<&Identifier> {
 }
 
 /// Validate that variable names are unique within a model
+///
+/// Names are compared as identifiers, so `Birth Rate`, `birth_rate` and
+/// `"BIRTH RATE"` all name the same variable.
 pub fn validate_variable_name_uniqueness(variables: &[Variable]) -> ValidationResult {
     let warnings = Vec::new();
     let mut errors = Vec::new();
 
-    let mut seen_names: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut seen_names: HashMap<&Identifier, Vec<usize>> = HashMap::new();
 
     for (idx, var) in variables.iter().enumerate() {
         if let Some(name) = get_variable_name(var) {
-            let name_str = name.to_string();
-            seen_names.entry(name_str).or_default().push(idx);
+            seen_names.entry(name).or_default().push(idx);
         }
     }
 
+    // Report duplicates in the order they first appear
+    let mut seen_names: Vec<(&Identifier, Vec<usize>)> = seen_names.into_iter().collect();
+    seen_names.sort_by_key(|(_, indices)| indices[0]);
+
     for (name, indices) in seen_names {
         if indices.len() > 1 {
             let var_list = if indices.len() == 2 {
@@ -53,9 +62,30 @@ pub fn validate_variable_name_uniqueness(variables: &[Variable]) -> ValidationRe
                         .join(", ")
                 )
             };
+            let mut spellings: Vec<&str> = Vec::new();
+            for &idx in &indices {
+                if let Some(spelling) = get_variable_name(&variables[idx])
+                    .map(|name| name.raw().trim_matches('"'))
+                    .filter(|spelling| !spellings.contains(spelling))
+                {
+                    spellings.push(spelling);
+                }
+            }
+            let spelled = if spellings.len() > 1 {
+                format!(
+                    " as {}, which are the same name because names ignore case and treat underscores as spaces",
+                    spellings
+                        .iter()
+                        .map(|spelling| format!("'{}'", spelling))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            } else {
+                String::new()
+            };
             errors.push(format!(
-                "Duplicate variable name '{}' found {} times in the model (at {}). Each variable must have a unique name. Consider renaming one or more of these variables.",
-                name, indices.len(), var_list
+                "Duplicate variable name '{}' found {} times in the model (at {}){}. Each variable must have a unique name. Consider renaming one or more of these variables.",
+                name, indices.len(), var_list, spelled
             ));
         }
     }
@@ -67,7 +97,292 @@ pub fn validate_variable_name_uniqueness(variables: &[Variable]) -> ValidationRe
     }
 }
 
+/// Validate that variable names do not collide with reserved words
+///
+/// A variable named after a keyword or built-in function, such as `TIME`,
+/// can only be referred to in quotes. Unquoted references to it in other
+/// equations name the builtin instead, and are reported as errors; the name
+/// itself is reported as a warning.
+pub fn validate_reserved_names(variables: &[Variable]) -> ValidationResult {
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    let names: Vec<&Identifier> = variables.iter().filter_map(get_variable_name).collect();
+    for name in names.iter().filter(|name| name.is_reserved_word()) {
+        let suggestion = suggest_rename(name, "value", &names);
+        let mut referenced = false;
+        for variable in variables {
+            let Some(equation) = variable_equation(variable) else {
+                continue;
+            };
+            if !refers_unquoted(equation, name) {
+                continue;
+            }
+            referenced = true;
+            errors.push(format!(
+                "The equation of '{}' refers to '{}' without quotes, which names the built-in rather than the variable. Write it as \"{}\" or rename the variable, e.g. to '{}'.",
+                get_variable_name(variable).map(|n| n.to_string()).unwrap_or_default(),
+                name,
+                name,
+                suggestion
+            ));
+        }
+        if !referenced {
+            warnings.push(format!(
+                "Variable name '{}' is a reserved word and can only be referred to in quotes. Consider renaming it, e.g. to '{}'.",
+                name, suggestion
+            ));
+        }
+    }
+
+    if !errors.is_empty() {
+        ValidationResult::Invalid(warnings, errors)
+    } else if !warnings.is_empty() {
+        ValidationResult::Warnings((), warnings)
+    } else {
+        ValidationResult::Valid(())
+    }
+}
+
+/// Suggests a name for `name` that is not a reserved word and not in `taken`,
+/// by appending `suffix` and then a number.
+pub(crate) fn suggest_rename(name: &Identifier, suffix: &str, taken: &[&Identifier]) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => format!("{} {}", name, suffix),
+            n => format!("{} {} {}", name, suffix, n),
+        })
+        .find(|candidate| {
+            Identifier::parse_from_attribute(candidate)
+                .is_ok_and(|candidate| !taken.contains(&&candidate))
+        })
+        .expect("some numbered suffix is free")
+}
+
+fn variable_equation(variable: &Variable) -> Option<&crate::Expression> {
+    match variable {
+        Variable::Auxiliary(aux) => aux.equation(),
+        Variable::Stock(stock) => match stock.as_ref() {
+            crate::model::vars::stock::Stock::Basic(b) => b.equation(),
+            crate::model::vars::stock::Stock::Conveyor(c) => c.equation(),
+            crate::model::vars::stock::Stock::Queue(q) => q.equation(),
+        },
+        Variable::Flow(flow) => flow.equation(),
+        Variable::GraphicalFunction(gf) => gf.equation(),
+        _ => None,
+    }
+}
+
+/// Whether `expression` refers to `name` without quotes.
+fn refers_unquoted(expression: &crate::Expression, name: &Identifier) -> bool {
+    use crate::Expression;
+
+    match expression {
+        Expression::Subscript(id, indices) => {
+            (!id.is_quoted() && id == name)
+                || indices.iter().any(|index| refers_unquoted(index, name))
+        }
+        Expression::Parentheses(inner)
+        | Expression::UnaryPlus(inner)
+        | Expression::UnaryMinus(inner)
+        | Expression::Not(inner)
+        | Expression::Transpose(inner) => refers_unquoted(inner, name),
+        Expression::Exponentiation(lhs, rhs)
+        | Expression::Multiply(lhs, rhs)
+        | Expression::Divide(lhs, rhs)
+        | Expression::Modulo(lhs, rhs)
+        | Expression::Add(lhs, rhs)
+        | Expression::Subtract(lhs, rhs)
+        | Expression::LessThan(lhs, rhs)
+        | Expression::LessThanOrEq(lhs, rhs)
+        | Expression::GreaterThan(lhs, rhs)
+        | Expression::GreaterThanOrEq(lhs, rhs)
+        | Expression::Equal(lhs, rhs)
+        | Expression::NotEqual(lhs, rhs)
+        | Expression::And(lhs, rhs)
+        | Expression::Or(lhs, rhs)
+        | Expression::Range(lhs, rhs) => refers_unquoted(lhs, name) || refers_unquoted(rhs, name),
+        Expression::FunctionCall { parameters, .. } => parameters
+            .iter()
+            .any(|parameter| refers_unquoted(parameter, name)),
+        Expression::IfElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            refers_unquoted(condition, name)
+                || refers_unquoted(then_branch, name)
+                || refers_unquoted(else_branch, name)
+        }
+        Expression::Constant(_) | Expression::Wildcard | Expression::InlineComment(_) => false,
+    }
+}
+
+/// Validate that variables of submodels do not shadow names of the models
+/// that use them
+///
+/// Each model not used as a submodel is walked through its modules, and
+/// every submodel variable with the same name as a variable of an enclosing
+/// model is reported as a warning, naming the qualified names that tell
+/// them apart. A variable connected to the parent variable of the same name
+/// by a `<connect>` is not reported.
+#[cfg(feature = "submodels")]
+pub fn validate_module_shadowing(models: &[crate::xml::schema::Model]) -> ValidationResult {
+    let mut warnings = Vec::new();
+
+    let model_name = |model: &crate::xml::schema::Model| {
+        model
+            .name
+            .as_deref()
+            .and_then(|name| Identifier::parse_from_attribute(name).ok())
+    };
+    let submodels: Vec<Identifier> = models
+        .iter()
+        .flat_map(|model| &model.variables.variables)
+        .filter_map(|variable| match variable {
+            Variable::Module(module) if module.resource.is_none() => Some(module.name.clone()),
+            _ => None,
+        })
+        .collect();
+
+    for model in models {
+        if model_name(model).is_some_and(|name| submodels.contains(&name)) {
+            continue;
+        }
+        let mut scopes = vec![ModuleScope {
+            path: String::new(),
+            model: model_name(model),
+            names: model
+                .variables
+                .variables
+                .iter()
+                .filter_map(get_variable_name)
+                .collect(),
+        }];
+        check_module_shadowing(models, model, &mut scopes, &mut warnings);
+    }
+
+    if warnings.is_empty() {
+        ValidationResult::Valid(())
+    } else {
+        ValidationResult::Warnings((), warnings)
+    }
+}
+
+/// A model enclosing the one being checked: its qualified path from the root
+/// model, the submodel it instantiates and its variable names.
+#[cfg(feature = "submodels")]
+struct ModuleScope<'a> {
+    path: String,
+    model: Option<Identifier>,
+    names: Vec<&'a Identifier>,
+}
+
+#[cfg(feature = "submodels")]
+fn check_module_shadowing<'a>(
+    models: &'a [crate::xml::schema::Model],
+    model: &'a crate::xml::schema::Model,
+    scopes: &mut Vec<ModuleScope<'a>>,
+    warnings: &mut Vec<String>,
+) {
+    let qualify = |path: &str, name: &Identifier| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+
+    for variable in &model.variables.variables {
+        let Variable::Module(module) = variable else {
+            continue;
+        };
+        if module.resource.is_some() {
+            continue;
+        }
+        let Some(submodel) = models.iter().find(|candidate| {
+            candidate
+                .name
+                .as_deref()
+                .and_then(|name| Identifier::parse_from_attribute(name).ok())
+                .is_some_and(|name| name == module.name)
+        }) else {
+            continue;
+        };
+        // Recursive modules are left to the checks that report them
+        if scopes
+            .iter()
+            .any(|scope| scope.model.as_ref() == Some(&module.name))
+        {
+            continue;
+        }
+
+        let parent_path = scopes
+            .last()
+            .map(|scope| scope.path.clone())
+            .unwrap_or_default();
+        let path = qualify(&parent_path, &module.name);
+        let names: Vec<&Identifier> = submodel
+            .variables
+            .variables
+            .iter()
+            .filter_map(get_variable_name)
+            .collect();
+
+        for name in &names {
+            let connected = module.connections.iter().any(|connection| {
+                let last = |qualified: &str| {
+                    let qualified = qualified.trim_start_matches('.');
+                    Identifier::parse_from_attribute(
+                        qualified.rsplit('.').next().unwrap_or(qualified),
+                    )
+                    .ok()
+                };
+                last(&connection.to).as_ref() == Some(*name)
+                    && last(&connection.from).as_ref() == Some(*name)
+            });
+
+            for (depth, scope) in scopes.iter().enumerate().rev() {
+                let is_parent = depth + 1 == scopes.len();
+                if scope.names.contains(name) && !(is_parent && connected) {
+                    let enclosing = if scope.path.is_empty() {
+                        "the root model".to_string()
+                    } else {
+                        format!("module '{}'", scope.path)
+                    };
+                    let mut message = format!(
+                        "Variable '{}' of module '{}' shadows '{}' in {}; within the module the name refers to the module's own variable. Refer to them as '{}' and '{}' to tell them apart",
+                        name,
+                        path,
+                        name,
+                        enclosing,
+                        qualify(&path, name),
+                        qualify(&scope.path, name)
+                    );
+                    if is_parent {
+                        message.push_str(&format!(
+                            ", or connect them with <connect to=\"{}\" from=\"{}\"/> if they are the same quantity",
+                            name, name
+                        ));
+                    }
+                    message.push('.');
+                    warnings.push(message);
+                }
+            }
+        }
+
+        scopes.push(ModuleScope {
+            path,
+            model: Some(module.name.clone()),
+            names,
+        });
+        check_module_shadowing(models, submodel, scopes, warnings);
+        scopes.pop();
+    }
+}
+
 /// Validate that UIDs are unique within a view
+#[cfg(feature = "views")]
 pub fn validate_view_uids_unique(view: &crate::view::View) -> ValidationResult {
     let warnings = Vec::new();
     let mut errors = Vec::new();
@@ -117,95 +432,98 @@ pub fn validate_view_uids_unique(view: &crate::view::View) -> ValidationResult {
             .or_default()
             .push(format!("alias '{}'", alias.of));
     }
-    for slider in &view.sliders {
-        seen_uids
-            .entry(slider.uid)
-            .or_default()
-            .push("slider".to_string());
-    }
-    for knob in &view.knobs {
-        seen_uids
-            .entry(knob.uid)
-            .or_default()
-            .push("knob".to_string());
-    }
-    for switch in &view.switches {
-        seen_uids
-            .entry(switch.uid)
-            .or_default()
-            .push("switch".to_string());
-    }
-    for options in &view.options {
-        seen_uids
-            .entry(options.uid)
-            .or_default()
-            .push("options".to_string());
-    }
-    for numeric_input in &view.numeric_inputs {
-        seen_uids
-            .entry(numeric_input.uid)
-            .or_default()
-            .push("numeric_input".to_string());
-    }
-    for list_input in &view.list_inputs {
-        seen_uids
-            .entry(list_input.uid)
-            .or_default()
-            .push("list_input".to_string());
-    }
-    for graphical_input in &view.graphical_inputs {
-        seen_uids
-            .entry(graphical_input.uid)
-            .or_default()
-            .push("graphical_input".to_string());
-    }
-    for numeric_display in &view.numeric_displays {
-        seen_uids
-            .entry(numeric_display.uid)
-            .or_default()
-            .push("numeric_display".to_string());
-    }
-    for lamp in &view.lamps {
-        seen_uids
-            .entry(lamp.uid)
-            .or_default()
-            .push("lamp".to_string());
-    }
-    for gauge in &view.gauges {
-        seen_uids
-            .entry(gauge.uid)
-            .or_default()
-            .push("gauge".to_string());
-    }
-    for graph in &view.graphs {
-        seen_uids
-            .entry(graph.uid)
-            .or_default()
-            .push("graph".to_string());
-    }
-    for table in &view.tables {
-        seen_uids
-            .entry(table.uid)
-            .or_default()
-            .push("table".to_string());
-    }
-    for text_box in &view.text_boxes {
-        seen_uids
-            .entry(text_box.uid)
-            .or_default()
-            .push("text_box".to_string());
-    }
-    for graphics_frame in &view.graphics_frames {
-        seen_uids
-            .entry(graphics_frame.uid)
-            .or_default()
-            .push("graphics_frame".to_string());
-    }
-    for button in &view.buttons {
-        seen_uids
-            .entry(button.uid)
-            .or_default()
-            .push("button".to_string());
+    #[cfg(feature = "interface-objects")]
+    {
+        for slider in &view.sliders {
+            seen_uids
+                .entry(slider.uid)
+                .or_default()
+                .push("slider".to_string());
+        }
+        for knob in &view.knobs {
+            seen_uids
+                .entry(knob.uid)
+                .or_default()
+                .push("knob".to_string());
+        }
+        for switch in &view.switches {
+            seen_uids
+                .entry(switch.uid)
+                .or_default()
+                .push("switch".to_string());
+        }
+        for options in &view.options {
+            seen_uids
+                .entry(options.uid)
+                .or_default()
+                .push("options".to_string());
+        }
+        for numeric_input in &view.numeric_inputs {
+            seen_uids
+                .entry(numeric_input.uid)
+                .or_default()
+                .push("numeric_input".to_string());
+        }
+        for list_input in &view.list_inputs {
+            seen_uids
+                .entry(list_input.uid)
+                .or_default()
+                .push("list_input".to_string());
+        }
+        for graphical_input in &view.graphical_inputs {
+            seen_uids
+                .entry(graphical_input.uid)
+                .or_default()
+                .push("graphical_input".to_string());
+        }
+        for numeric_display in &view.numeric_displays {
+            seen_uids
+                .entry(numeric_display.uid)
+                .or_default()
+                .push("numeric_display".to_string());
+        }
+        for lamp in &view.lamps {
+            seen_uids
+                .entry(lamp.uid)
+                .or_default()
+                .push("lamp".to_string());
+        }
+        for gauge in &view.gauges {
+            seen_uids
+                .entry(gauge.uid)
+                .or_default()
+                .push("gauge".to_string());
+        }
+        for graph in &view.graphs {
+            seen_uids
+                .entry(graph.uid)
+                .or_default()
+                .push("graph".to_string());
+        }
+        for table in &view.tables {
+            seen_uids
+                .entry(table.uid)
+                .or_default()
+                .push("table".to_string());
+        }
+        for text_box in &view.text_boxes {
+            seen_uids
+                .entry(text_box.uid)
+                .or_default()
+                .push("text_box".to_string());
+        }
+        for graphics_frame in &view.graphics_frames {
+            seen_uids
+                .entry(graphics_frame.uid)
+                .or_default()
+                .push("graphics_frame".to_string());
+        }
+        for button in &view.buttons {
+            seen_uids
+                .entry(button.uid)
+                .or_default()
+                .push("button".to_string());
+        }
     }
 
     // Check for duplicates
@@ -291,6 +609,7 @@ pub fn validate_dimension_references(
 }
 
 /// Validate that view object names match variable names
+#[cfg(feature = "views")]
 pub fn validate_view_object_references(
     view: &crate::view::View,
     variables: &[Variable],
@@ -355,6 +674,187 @@ pub fn validate_view_object_references(
     }
 }
 
+/// Validate that every alias represents a stock, flow or auxiliary shown in
+/// some view of the same model
+#[cfg(feature = "views")]
+pub fn validate_alias_targets(views: &[crate::view::View]) -> ValidationResult {
+    let warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    for view in views {
+        for alias in &view.aliases {
+            if views
+                .iter()
+                .all(|other| other.aliasable_named(&alias.of).is_none())
+            {
+                errors.push(format!(
+                    "Alias (UID {}) in view {} represents '{}', which is not a stock, flow or auxiliary shown in any view of the model.",
+                    alias.uid.value, view.uid.value, alias.of
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        ValidationResult::Valid(())
+    } else {
+        ValidationResult::Invalid(warnings, errors)
+    }
+}
+
+/// Validate that connectors attach to aliases and module ghosts shown in
+/// their view
+#[cfg(feature = "views")]
+pub fn validate_connector_endpoints(view: &crate::view::View) -> ValidationResult {
+    use crate::view::Pointer;
+    use crate::view::entity::{ghost_parts, same_name};
+
+    let warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    for connector in &view.connectors {
+        for pointer in [&connector.from, &connector.to] {
+            match pointer {
+                Pointer::Alias(uid) => {
+                    if !view.aliases.iter().any(|alias| alias.uid == *uid) {
+                        errors.push(format!(
+                            "Connector (UID {}) in view {} attaches to alias {}, which is not in the view.",
+                            connector.uid.value, view.uid.value, uid.value
+                        ));
+                    }
+                }
+                Pointer::Name(name) => {
+                    if view.entity_named(name).is_some() {
+                        continue;
+                    }
+                    if let Some((module, _)) = ghost_parts(name)
+                        && !view
+                            .modules
+                            .iter()
+                            .any(|object| same_name(&object.name, module))
+                    {
+                        errors.push(format!(
+                            "Connector (UID {}) in view {} attaches to '{}', but module '{}' is not shown in the view.",
+                            connector.uid.value, view.uid.value, name, module
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        ValidationResult::Valid(())
+    } else {
+        ValidationResult::Invalid(warnings, errors)
+    }
+}
+
+/// Validate the format of images and videos shown by graphics frames and buttons
+#[cfg(feature = "interface-objects")]
+pub fn validate_view_media(view: &crate::view::View) -> ValidationResult {
+    use crate::types::Validate;
+    use crate::view::PopupContent;
+
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut results: Vec<(Uid, &str, ValidationResult)> = Vec::new();
+    for frame in &view.graphics_frames {
+        results.push((frame.uid, "Graphics frame", frame.content.validate()));
+    }
+    for button in &view.buttons {
+        if let Some(image) = &button.image {
+            results.push((button.uid, "Button", image.validate()));
+        }
+        match &button.popup {
+            Some(PopupContent::Image(image)) => {
+                results.push((button.uid, "Button popup", image.validate()))
+            }
+            Some(PopupContent::Video(video)) => {
+                results.push((button.uid, "Button popup", video.validate()))
+            }
+            _ => {}
+        }
+    }
+
+    for (uid, object, result) in results {
+        let prefix = |message: String| format!("{} (UID {}): {}", object, uid.value, message);
+        match result {
+            ValidationResult::Valid(_) => {}
+            ValidationResult::Warnings(_, warns) => warnings.extend(warns.into_iter().map(prefix)),
+            ValidationResult::Invalid(warns, errs) => {
+                warnings.extend(warns.into_iter().map(prefix));
+                errors.extend(errs.into_iter().map(prefix));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        ValidationResult::Invalid(warnings, errors)
+    } else if !warnings.is_empty() {
+        ValidationResult::Warnings((), warnings)
+    } else {
+        ValidationResult::Valid(())
+    }
+}
+
+/// Validate that lamps and gauges have non-overlapping zones
+#[cfg(feature = "interface-objects")]
+pub fn validate_view_zones(view: &crate::view::View) -> ValidationResult {
+    let warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    let meters = view
+        .lamps
+        .iter()
+        .map(|lamp| ("Lamp", lamp.uid, &lamp.zones))
+        .chain(
+            view.gauges
+                .iter()
+                .map(|gauge| ("Gauge", gauge.uid, &gauge.zones)),
+        );
+
+    for (object, uid, zones) in meters {
+        if zones.is_empty() {
+            errors.push(format!(
+                "{} (UID {}) has no zones. Lamps and gauges require a <zones> list.",
+                object, uid.value
+            ));
+            continue;
+        }
+
+        let mut ranges = Vec::new();
+        for zone in zones {
+            if zone.min.is_nan() || zone.max.is_nan() || zone.min > zone.max {
+                errors.push(format!(
+                    "{} (UID {}) has a zone with invalid range [{}, {}].",
+                    object, uid.value, zone.min, zone.max
+                ));
+            } else {
+                ranges.push((zone.min, zone.max));
+            }
+        }
+
+        // Adjacent zones may share a boundary but must not otherwise overlap.
+        ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for pair in ranges.windows(2) {
+            if pair[1].0 < pair[0].1 {
+                errors.push(format!(
+                    "{} (UID {}) has overlapping zones [{}, {}] and [{}, {}].",
+                    object, uid.value, pair[0].0, pair[0].1, pair[1].0, pair[1].1
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        ValidationResult::Valid(())
+    } else {
+        ValidationResult::Invalid(warnings, errors)
+    }
+}
+
 /// Validate that group entity references exist
 pub fn validate_group_entity_references(
     groups: &[crate::model::groups::Group],
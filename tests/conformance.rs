@@ -0,0 +1,127 @@
+//! Conformance tests against the sample models in `data/conformance`, and
+//! the `.xmile` files under `XMILE_TEST_MODELS` if it is set.
+//!
+//! Every model must parse, validate and survive a serialization round-trip.
+//! Add new sample models to the directory to have them picked up
+//! automatically. Models declaring features this build does not support
+//! are skipped, and each skip is reported.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xmile::conformance::{self, ConformanceReport, Feature};
+
+/// The `.xmile` files in `dir` and its subdirectories.
+fn xmile_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in
+        fs::read_dir(dir).unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e))
+    {
+        let path = entry.expect("Failed to read directory entry").path();
+        if path.is_dir() {
+            files.extend(xmile_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "xmile") {
+            files.push(path);
+        }
+    }
+    files
+}
+
+fn read_models(dir: &Path) -> Vec<(String, String)> {
+    let mut models: Vec<_> = xmile_files(dir)
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .into_owned();
+            let xml = fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+            (name, xml)
+        })
+        .collect();
+    models.sort();
+    models
+}
+
+fn conformance_models() -> Vec<(String, String)> {
+    read_models(&Path::new(env!("CARGO_MANIFEST_DIR")).join("data/conformance"))
+}
+
+/// Checks `models`, reporting skipped models on stderr, and fails with
+/// every model that was checked and is not conformant.
+fn check(models: &[(String, String)]) -> Vec<ConformanceReport> {
+    let (checked, skipped): (Vec<_>, Vec<_>) = models
+        .iter()
+        .map(|(name, xml)| conformance::check_str(name, xml))
+        .partition(|report| report.unsupported_features.is_empty());
+    for report in &skipped {
+        eprint!("skipped {report}");
+    }
+
+    let failures: Vec<_> = checked
+        .iter()
+        .filter(|report| !report.is_conformant())
+        .map(|report| report.to_string())
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "Conformance failures:\n{}",
+        failures.join("\n")
+    );
+    skipped
+}
+
+#[test]
+fn test_conformance_models_present() {
+    assert!(
+        !conformance_models().is_empty(),
+        "No sample models found in data/conformance"
+    );
+}
+
+#[test]
+fn test_conformance_models() {
+    let skipped = check(&conformance_models());
+    // A build with every feature has no reason to skip a model
+    if cfg!(feature = "full") {
+        assert!(
+            skipped.is_empty(),
+            "Skipped in a full build:\n{}",
+            skipped.iter().map(ToString::to_string).collect::<String>()
+        );
+    }
+}
+
+#[test]
+fn test_external_test_models() {
+    let Some(dir) = std::env::var_os("XMILE_TEST_MODELS") else {
+        eprintln!("XMILE_TEST_MODELS is not set; no external test models checked");
+        return;
+    };
+    let models = read_models(Path::new(&dir));
+    assert!(!models.is_empty(), "No .xmile files found under {dir:?}");
+    check(&models);
+}
+
+#[test]
+fn test_teacup_declares_outputs() {
+    let xml = include_str!("../data/conformance/teacup.xmile");
+    let report = conformance::check_str("teacup.xmile", xml);
+
+    assert!(report.is_conformant(), "{}", report);
+    assert_eq!(report.declared_features, vec![Feature::Outputs]);
+    assert_eq!(
+        report.unsupported_features.is_empty(),
+        cfg!(feature = "interface-objects")
+    );
+}
+
+#[test]
+fn test_known_gaps_documented() {
+    for gap in conformance::known_gaps() {
+        assert!(!gap.section.is_empty());
+        assert!(!gap.description.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "interface-objects")]
+
+use xmile::data::{DataExport, ExportData, ExportError, ExportInterval, ExportOrientation};
+use xmile::types::Validate;
+use xmile::xml::XmileFile;
+
+const MODEL: &str = r#"
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor>
+        <product version="1.0">Test Product</product>
+    </header>
+    <data>
+        <export resource="results.csv" orientation="vertical" interval="DT">
+            <table uid=".7" use_settings="true"/>
+        </export>
+        <export resource="raw.csv" orientation="horizontal" interval="1">
+            <table uid=".7"/>
+        </export>
+        <export resource="all.csv">
+            <all/>
+        </export>
+        <export resource="missing.csv">
+            <table uid="Hares.7"/>
+        </export>
+    </data>
+    <model>
+        <variables>
+            <stock name="Population">
+                <eqn>1000</eqn>
+                <format precision="1" delimit_000s="true"/>
+            </stock>
+            <aux name="Birth Rate">
+                <eqn>0.1</eqn>
+                <format precision="0.1" display_as="percent"/>
+            </aux>
+        </variables>
+        <views>
+            <view uid="1" type="interface" width="800" height="600" page_width="800" page_height="600">
+                <table uid="7" x="0" y="0" width="300" height="200" orientation="horizontal" column_width="60" interval="5" report_balances="beginning" report_flows="summed" comparative="false" wrap_text="false">
+                    <item type="time"/>
+                    <item type="variable" entity_name="Population"/>
+                    <item type="variable" entity_name="Birth_Rate" precision="0.01"/>
+                </table>
+            </view>
+        </views>
+    </model>
+</xmile>
+"#;
+
+fn data() -> ExportData {
+    ExportData::new(vec![0.0, 5.0])
+        .with_series("Population", vec![1000.0, 1628.9])
+        .with_series("Birth Rate", vec![0.1, 0.1])
+}
+
+#[test]
+fn test_export_uses_table_settings() {
+    let file = XmileFile::from_str(MODEL).expect("Failed to parse model");
+    let exports = &file.data.as_ref().unwrap().exports;
+
+    let settings = exports[0].settings(&file).unwrap();
+    assert_eq!(settings.orientation, ExportOrientation::Horizontal);
+    assert_eq!(settings.interval, ExportInterval::Every(5.0));
+    // The export's own interval is ignored, so it should not appear.
+    assert!(exports[0].validate().has_warnings());
+
+    let mut out = Vec::new();
+    settings.write_csv(&data(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Time,0,5\nPopulation,\"1,000\",\"1,629\"\nBirth_Rate,10.00%,10.00%\n"
+    );
+}
+
+#[test]
+fn test_export_without_table_settings() {
+    let file = XmileFile::from_str(MODEL).expect("Failed to parse model");
+    let exports = &file.data.as_ref().unwrap().exports;
+
+    // The table only selects the variables; the export's attributes apply.
+    let settings = exports[1].settings(&file).unwrap();
+    assert_eq!(settings.orientation, ExportOrientation::Horizontal);
+    assert_eq!(settings.interval, ExportInterval::Every(1.0));
+    assert!(exports[1].validate().is_valid());
+    let columns = settings.columns.as_ref().unwrap();
+    assert_eq!(columns.len(), 2);
+    assert!(columns.iter().all(|column| column.format.is_none()));
+
+    let settings = exports[2].settings(&file).unwrap();
+    assert_eq!(settings.orientation, ExportOrientation::Vertical);
+    assert_eq!(settings.interval, ExportInterval::Once);
+    assert!(settings.columns.is_none());
+    let mut out = Vec::new();
+    settings.write_csv(&data(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Time,Population,Birth Rate\n5,1628.9,0.1\n"
+    );
+
+    assert!(matches!(
+        exports[3].settings(&file),
+        Err(ExportError::ModelNotFound(_))
+    ));
+}
+
+#[test]
+fn test_export_interval_round_trip() {
+    let xml = r#"<export resource="out.csv" interval="DT"><all/></export>"#;
+    let export: DataExport = quick_xml::de::from_str(xml).unwrap();
+    assert_eq!(export.interval, Some(ExportInterval::EveryDt));
+    let serialized = quick_xml::se::to_string(&export).unwrap();
+    assert!(serialized.contains(r#"interval="DT""#));
+
+    let xml = r#"<export resource="out.csv" interval="-2"><all/></export>"#;
+    let export: DataExport = quick_xml::de::from_str(xml).unwrap();
+    assert!(export.validate().is_invalid());
+
+    let xml = r#"<export resource="out.csv" interval="often"><all/></export>"#;
+    assert!(quick_xml::de::from_str::<DataExport>(xml).is_err());
+}
@@ -1,5 +1,5 @@
 use xmile::xml::schema::XmileFile;
-use xmile::xml::{ErrorContext, XmileError};
+use xmile::xml::{ErrorContext, LimitError, ParseError, ParseLimits, XmileError};
 
 #[test]
 fn test_enhanced_error_with_context() {
@@ -93,3 +93,55 @@ fn test_error_collection() {
 
 // Note: ParseError::from(XmileError) is implemented in src/xml/mod.rs
 // but we don't test it here to avoid circular dependencies
+
+#[test]
+fn test_external_entities_not_resolved() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE xmile [<!ENTITY xxe SYSTEM "file:///etc/passwd">]>
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>&xxe;</vendor>
+        <product version="1.0">Test</product>
+    </header>
+</xmile>"#;
+
+    // Without limits the declaration is ignored and the entity is unknown.
+    assert!(XmileFile::from_str(xml).is_err());
+
+    let err = XmileFile::from_str_with_limits(xml, &ParseLimits::default()).unwrap_err();
+    assert!(matches!(err, ParseError::Limit(LimitError::DocType)));
+}
+
+#[test]
+fn test_parse_limits_applied_to_reader() {
+    let xml = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor>
+        <product version="1.0">Test</product>
+    </header>
+    <model>
+        <variables>
+            <aux name="x">
+                <eqn>1</eqn>
+            </aux>
+        </variables>
+    </model>
+</xmile>"#;
+
+    let limits = ParseLimits::default();
+    assert!(XmileFile::from_reader_with_limits(xml.as_bytes(), &limits).is_ok());
+
+    let small = ParseLimits {
+        max_input_size: 64,
+        ..ParseLimits::default()
+    };
+    let err = XmileFile::from_reader_with_limits(xml.as_bytes(), &small).unwrap_err();
+    assert!(matches!(err, ParseError::Limit(LimitError::InputSize(64))));
+
+    let shallow = ParseLimits {
+        max_depth: 3,
+        ..ParseLimits::default()
+    };
+    let err = XmileFile::from_str_with_limits(xml, &shallow).unwrap_err();
+    assert!(matches!(err, ParseError::Limit(LimitError::Depth(3))));
+}
@@ -117,7 +117,7 @@ fn test_round_trip_quoted_identifiers() {
     "#;
 
     let file1 = XmileFile::from_str(xml).expect("Failed to parse");
-    let serialized = serde_xml_rs::to_string(&file1).expect("Failed to serialize");
+    let serialized = file1.to_xml_string().expect("Failed to serialize");
 
     // Verify the serialized XML contains quoted identifiers
     assert!(
@@ -19,13 +19,14 @@ fn test_macro_basic() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     assert_eq!(file.macros.len(), 1);
     let macro_def = &file.macros[0];
     // Identifier normalizes underscores to spaces
     assert_eq!(&macro_def.name.to_string(), "test macro");
     assert_eq!(macro_def.parameters.len(), 0);
     assert!(macro_def.variables.is_none());
+    #[cfg(feature = "views")]
     assert!(macro_def.views.is_none());
 }
 
@@ -49,7 +50,7 @@ fn test_macro_with_parameters() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     assert_eq!(file.macros.len(), 1);
     let macro_def = &file.macros[0];
     assert_eq!(&macro_def.name.to_string(), "add");
@@ -59,3 +60,194 @@ fn test_macro_with_parameters() {
     assert_eq!(&macro_def.parameters[1].name.to_string(), "b");
     assert!(macro_def.parameters[1].default.is_some());
 }
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_macro_shadowing_builtin() {
+    use xmile::types::Validate;
+
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <macro name="MIN">
+            <parm>a</parm>
+            <parm>b</parm>
+            <eqn>IF a &lt; b THEN a ELSE b</eqn>
+        </macro>
+        <macro name="MAX" namespace="user">
+            <parm>a</parm>
+            <parm>b</parm>
+            <eqn>IF a &gt; b THEN a ELSE b</eqn>
+        </macro>
+        <model>
+            <variables/>
+        </model>
+    </xmile>
+    "#;
+
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
+    match file.macros[0].validate() {
+        xmile::types::ValidationResult::Invalid(_, errors) => {
+            assert_eq!(
+                errors,
+                vec![
+                    "Macro 'MIN' shadows the built-in 'MIN'. Give it an explicit namespace, e.g. namespace=\"user\", or rename it, e.g. to 'MIN macro'."
+                ]
+            );
+        }
+        _ => panic!("Expected MIN to be reported"),
+    }
+    assert!(file.macros[1].validate().is_valid());
+}
+
+#[cfg(feature = "macros")]
+const OPTION_FILTERS: &str = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+            <options>
+                <uses_macros recursive_macros="false" option_filters="true"/>
+            </options>
+        </header>
+        <macro filter="flow" name="non_negative">
+            <parm>flow</parm>
+            <parm>option</parm>
+            <eqn>IF option THEN MAX(flow, 0) ELSE flow</eqn>
+        </macro>
+        <macro filter="stock" name="non_negative" applyto="outflows">
+            <parm>flow</parm>
+            <parm>value</parm>
+            <parm>stock</parm>
+            <parm>outflow_sum</parm>
+            <eqn>IF value THEN MAX(stock/DT - outflow_sum, flow) ELSE flow</eqn>
+        </macro>
+        <model>
+            <variables/>
+        </model>
+    </xmile>
+    "#;
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_option_filters() {
+    use xmile::Identifier;
+    use xmile::r#macro::{ApplyTo, FilterArgument, FilterType};
+
+    let file = XmileFile::from_str(OPTION_FILTERS).unwrap();
+    assert!(file.validate().is_ok(), "{:?}", file.validate());
+
+    let stock_filter = file.macros[1].filter.unwrap();
+    assert_eq!(stock_filter.filter, FilterType::Stock);
+    assert_eq!(stock_filter.apply_to, Some(ApplyTo::Outflows));
+    assert_eq!(stock_filter.arguments()[3], FilterArgument::OutflowSum);
+
+    // Filters sharing the name of their option are kept apart, and are not
+    // callable by name
+    let registry = file.build_macro_registry();
+    let option = Identifier::parse_default("non_negative").unwrap();
+    assert!(!registry.contains(&option));
+    assert!(
+        registry
+            .option_filter(&option, FilterType::Flow, None)
+            .is_some()
+    );
+    assert!(
+        registry
+            .option_filter(&option, FilterType::Stock, Some(ApplyTo::Outflows))
+            .is_some()
+    );
+    assert!(
+        registry
+            .option_filter(&option, FilterType::Stock, None)
+            .is_none()
+    );
+
+    let xml = file.to_xml_string().unwrap();
+    assert!(xml.contains(r#"filter="stock""#), "{xml}");
+    assert!(xml.contains(r#"applyto="outflows""#), "{xml}");
+    assert_eq!(XmileFile::from_str(&xml).unwrap().macros, file.macros);
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_invalid_option_filters() {
+    use xmile::types::Validate;
+
+    let undeclared =
+        OPTION_FILTERS.replace(r#"option_filters="true""#, r#"option_filters="false""#);
+    let error = XmileFile::from_str(&undeclared)
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("does not declare <uses_macros option_filters"),
+        "{error}"
+    );
+
+    let inconsistent = OPTION_FILTERS.replace(r#"applyto="outflows""#, r#"applyto="upstream""#);
+    let file = XmileFile::from_str(&inconsistent).unwrap();
+    assert!(file.macros[1].validate().is_invalid());
+
+    let wrong_arity = OPTION_FILTERS.replace("<parm>outflow_sum</parm>", "");
+    let file = XmileFile::from_str(&wrong_arity).unwrap();
+    match file.macros[1].validate() {
+        xmile::types::ValidationResult::Invalid(_, errors) => assert_eq!(
+            errors,
+            ["Option filter 'non negative' is passed 4 values, but takes 3 parameters."]
+        ),
+        _ => panic!("Expected the parameter count to be reported"),
+    }
+
+    let unknown = OPTION_FILTERS.replace(r#"filter="flow""#, r#"filter="module""#);
+    assert!(XmileFile::from_str(&unknown).is_err());
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_option_filters_are_not_simulated() {
+    use xmile::simulation::{SimulationError, Simulator};
+    use xmile::specs::SimulationSpecs;
+
+    let specs = SimulationSpecs {
+        start: 0.0,
+        stop: 1.0,
+        dt: None,
+        method: None,
+        time_units: None,
+        pause: None,
+        run_by: None,
+    };
+    let file = XmileFile::from_str(OPTION_FILTERS).unwrap();
+    match Simulator::from_file(&file, &specs) {
+        Err(SimulationError::Unsupported {
+            variable,
+            construct,
+        }) => assert_eq!(
+            (variable.as_str(), construct.as_str()),
+            ("non negative", "option filter")
+        ),
+        other => panic!("Expected the option filter to be rejected, got {other:?}"),
+    }
+
+    // Declaring option filters without defining any is rejected as well,
+    // since the filters may come from a library the file includes
+    let declared = OPTION_FILTERS
+        .replace(r#"filter="stock""#, "")
+        .replace(r#"filter="flow""#, "");
+    let declared = declared.replace(r#" applyto="outflows""#, "");
+    let file = XmileFile::from_str(&declared).unwrap();
+    assert!(matches!(
+        Simulator::from_file(&file, &specs),
+        Err(SimulationError::Unsupported { .. })
+    ));
+
+    let undeclared = declared.replace(r#"option_filters="true""#, r#"option_filters="false""#);
+    let file = XmileFile::from_str(&undeclared).unwrap();
+    assert!(Simulator::from_file(&file, &specs).is_ok());
+}
@@ -18,7 +18,7 @@ fn test_module_basic() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     assert_eq!(model.variables.variables.len(), 1);
 
@@ -52,7 +52,7 @@ fn test_module_with_resource() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     match &model.variables.variables[0] {
         xmile::model::vars::Variable::Module(module) => {
@@ -83,7 +83,7 @@ fn test_module_with_connections() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     match &model.variables.variables[0] {
         xmile::model::vars::Variable::Module(module) => {
@@ -116,7 +116,7 @@ fn test_module_with_documentation() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     match &model.variables.variables[0] {
         xmile::model::vars::Variable::Module(module) => {
@@ -133,3 +133,60 @@ fn test_module_with_documentation() {
         _ => panic!("Expected Module variant"),
     }
 }
+
+#[cfg(feature = "submodels")]
+#[test]
+fn test_module_shadowing() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <aux name="Rate">
+                    <eqn>0.1</eqn>
+                </aux>
+                <aux name="Price">
+                    <eqn>10</eqn>
+                </aux>
+                <module name="Sector">
+                    <connect to="Price" from="Price"/>
+                </module>
+            </variables>
+        </model>
+        <model name="Sector">
+            <variables>
+                <aux name="Rate">
+                    <eqn>0.2</eqn>
+                </aux>
+                <aux name="Price">
+                    <eqn>0</eqn>
+                </aux>
+                <module name="Plant"/>
+            </variables>
+        </model>
+        <model name="Plant">
+            <variables>
+                <aux name="rate">
+                    <eqn>0.3</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
+    match xmile::xml::validation::validate_module_shadowing(&file.models) {
+        xmile::types::ValidationResult::Warnings(_, warnings) => {
+            assert_eq!(warnings.len(), 3, "{warnings:?}");
+            assert!(warnings[0].starts_with(
+                "Variable 'Rate' of module 'Sector' shadows 'Rate' in the root model; within the module the name refers to the module's own variable. Refer to them as 'Sector.Rate' and 'Rate' to tell them apart, or connect them"
+            ));
+            assert!(warnings[1].contains("Refer to them as 'Sector.Plant.rate' and 'Sector.rate'"));
+            assert!(warnings[2].contains("Refer to them as 'Sector.Plant.rate' and 'rate'"));
+        }
+        _ => panic!("Expected shadowing warnings"),
+    }
+}
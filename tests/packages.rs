@@ -0,0 +1,96 @@
+#![cfg(feature = "packages")]
+
+use std::fs::File;
+use std::io::Write;
+
+use xmile::resource::{Package, PackageError, ResourceReader};
+use xmile::xml::XmileFile;
+use zip::write::SimpleFileOptions;
+
+const MAIN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor>
+        <product version="1.0">Test Product</product>
+        <includes>
+            <include resource="macros/*.xml"/>
+        </includes>
+    </header>
+    <data>
+        <import resource="data/input.csv"/>
+    </data>
+    <model>
+        <variables>
+            <aux name="x">
+                <eqn>1</eqn>
+            </aux>
+        </variables>
+    </model>
+</xmile>
+"#;
+
+fn write_zip(path: &std::path::Path, entries: &[(&str, &str)]) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    for (name, contents) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_from_zip_reads_main_document_and_resources() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.zip");
+    write_zip(
+        &path,
+        &[
+            ("model/macros/a.xml", "<xmile/>"),
+            ("model/main.xmile", MAIN),
+            ("model/data/input.csv", "time,x\n0,1\n"),
+            ("readme.txt", "packaged model"),
+        ],
+    );
+
+    let (file, package) = XmileFile::from_zip(&path).unwrap();
+    assert_eq!(package.main_name(), "model/main.xmile");
+    assert_eq!(file.models.len(), 1);
+
+    let includes = file.resolve_includes(&package).unwrap();
+    assert_eq!(includes.len(), 1);
+    assert_eq!(includes[0].location, "macros/a.xml");
+
+    let data = file.data.as_ref().unwrap();
+    let input = data.imports[0].fetch(&package).unwrap();
+    assert_eq!(input.as_str().unwrap(), "time,x\n0,1\n");
+
+    assert_eq!(
+        package.read(&"../readme.txt".into()).unwrap(),
+        b"packaged model"
+    );
+}
+
+#[test]
+fn test_zip_without_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("empty.zip");
+    write_zip(&path, &[("data.csv", "time,x")]);
+
+    assert!(matches!(
+        Package::open(&path),
+        Err(PackageError::NoMainDocument)
+    ));
+}
+
+#[test]
+fn test_from_gzip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.xmile.gz");
+    let mut encoder =
+        flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+    encoder.write_all(MAIN.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+
+    let file = XmileFile::from_gzip(&path).unwrap();
+    assert_eq!(file, XmileFile::from_str(MAIN).unwrap());
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use xmile::data::DataImport;
+use xmile::resource::{
+    AsyncResourceReader, Blocking, FileReader, MemoryResources, ResourceError, ResourceReader,
+    ResourceRef,
+};
+use xmile::xml::XmileFile;
+
+const MAIN: &str = r#"
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor>
+        <product version="1.0">Test Product</product>
+        <includes>
+            <include resource="common.xml"/>
+            <include resource="macros/supplychain-*.xml"/>
+        </includes>
+    </header>
+    <model>
+        <variables>
+            <aux name="x">
+                <eqn>1</eqn>
+            </aux>
+        </variables>
+    </model>
+</xmile>
+"#;
+
+/// Drives a future to completion on the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// An async reader serving resources from memory, standing in for an HTTP client.
+struct MemoryReader(HashMap<String, Vec<u8>>);
+
+impl AsyncResourceReader for MemoryReader {
+    fn read(
+        &self,
+        resource: &ResourceRef,
+    ) -> impl Future<Output = Result<Vec<u8>, ResourceError>> + Send {
+        let result = self
+            .0
+            .get(resource.as_str())
+            .cloned()
+            .ok_or_else(|| ResourceError::NotFound(resource.to_string()));
+        async move { result }
+    }
+}
+
+fn data_import(resource: &str) -> DataImport {
+    DataImport {
+        data_type: None,
+        enabled: None,
+        frequency: None,
+        orientation: None,
+        resource: Some(resource.into()),
+        worksheet: None,
+    }
+}
+
+#[test]
+fn test_resolve_includes_from_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("macros")).unwrap();
+    fs::write(dir.path().join("main.xmile"), MAIN).unwrap();
+    fs::write(dir.path().join("common.xml"), "<xmile/>").unwrap();
+    fs::write(dir.path().join("macros/supplychain-2.0.xml"), "two").unwrap();
+    fs::write(dir.path().join("macros/supplychain-1.0.xml"), "one").unwrap();
+    fs::write(dir.path().join("macros/other-1.0.xml"), "other").unwrap();
+
+    let path = dir.path().join("main.xmile");
+    let file = XmileFile::from_file(&path).unwrap();
+    let reader = FileReader::for_file(&path);
+
+    let includes = file.resolve_includes(&reader).unwrap();
+    let locations: Vec<_> = includes.iter().map(|r| r.location.as_str()).collect();
+    assert_eq!(
+        locations,
+        vec![
+            "common.xml",
+            "macros/supplychain-1.0.xml",
+            "macros/supplychain-2.0.xml"
+        ]
+    );
+    assert_eq!(includes[1].as_str().unwrap(), "one");
+
+    let async_includes = block_on(file.resolve_includes_async(&Blocking(reader))).unwrap();
+    assert_eq!(includes, async_includes);
+}
+
+#[test]
+fn test_resolve_includes_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = XmileFile::from_str(MAIN).unwrap();
+    let reader = FileReader::new(dir.path());
+
+    let err = file.resolve_includes(&reader).unwrap_err();
+    assert!(matches!(err, ResourceError::NotFound(location) if location == "common.xml"));
+}
+
+#[test]
+fn test_resolve_includes_async_reader() {
+    let file = XmileFile::from_str(MAIN).unwrap();
+    let reader = MemoryReader(HashMap::from([
+        ("common.xml".to_string(), b"common".to_vec()),
+        ("macros/supplychain-*.xml".to_string(), b"library".to_vec()),
+    ]));
+
+    let includes = block_on(file.resolve_includes_async(&reader)).unwrap();
+    assert_eq!(includes.len(), 2);
+    assert_eq!(includes[0].as_str().unwrap(), "common");
+}
+
+#[test]
+fn test_data_import_fetch() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("data.csv"), "time,x\n0,1\n").unwrap();
+    let reader = FileReader::new(dir.path());
+    let import = data_import("data.csv");
+
+    let resource = import.fetch(&reader).unwrap();
+    assert_eq!(resource.as_str().unwrap(), "time,x\n0,1\n");
+
+    let fetched = block_on(import.fetch_async(&Blocking(reader.clone()))).unwrap();
+    assert_eq!(resource, fetched);
+
+    let url = data_import("https://example.com/data.csv");
+    assert!(matches!(
+        url.fetch(&reader),
+        Err(ResourceError::Unsupported(_))
+    ));
+    let memory = MemoryReader(HashMap::from([(
+        "https://example.com/data.csv".to_string(),
+        b"time,x".to_vec(),
+    )]));
+    assert_eq!(
+        block_on(url.fetch_async(&memory)).unwrap().contents,
+        b"time,x"
+    );
+}
+
+#[test]
+fn test_data_import_without_resource() {
+    let mut import = data_import("unused");
+    import.resource = None;
+    let reader = FileReader::new(".");
+    assert!(matches!(
+        import.fetch(&reader),
+        Err(ResourceError::MissingResource)
+    ));
+    assert!(reader.read(&"no-such-file.csv".into()).is_err());
+}
+
+#[test]
+fn test_resolve_includes_from_memory() {
+    let file = XmileFile::from_str(MAIN).unwrap();
+    let resources = MemoryResources::new()
+        .with("models/common.xml", "common")
+        .with("models/macros/supplychain-1.0.xml", "one")
+        .with("models/macros/other-1.0.xml", "other")
+        .with_base("models");
+
+    let includes = file.resolve_includes(&resources).unwrap();
+    let locations: Vec<_> = includes.iter().map(|r| r.location.as_str()).collect();
+    assert_eq!(locations, vec!["common.xml", "macros/supplychain-1.0.xml"]);
+    assert_eq!(includes[1].as_str().unwrap(), "one");
+}
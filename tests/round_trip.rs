@@ -12,7 +12,8 @@ fn round_trip_test(xml: &str, description: &str) {
         .unwrap_or_else(|e| panic!("Failed to parse {}: {:?}", description, e));
 
     // Serialize back to XML
-    let serialized = serde_xml_rs::to_string(&file1)
+    let serialized = file1
+        .to_xml_string()
         .unwrap_or_else(|e| panic!("Failed to serialize {}: {:?}", description, e));
 
     // Parse the serialized XML
@@ -149,6 +150,56 @@ fn test_round_trip_with_sim_specs() {
     round_trip_test(xml, "model with sim_specs");
 }
 
+#[test]
+fn test_round_trip_sim_specs_attributes() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <sim_specs method="RK4" time_units="Months" pause="5" run_by="group">
+            <start>0</start>
+            <stop>12</stop>
+            <dt reciprocal="true">4</dt>
+        </sim_specs>
+        <model>
+            <sim_specs method="Euler" time_units="Days">
+                <start>0</start>
+                <stop>30</stop>
+            </sim_specs>
+            <variables>
+                <stock name="TestStock">
+                    <eqn>100</eqn>
+                </stock>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    round_trip_test(xml, "model with sim_specs attributes");
+
+    let file = XmileFile::from_str(xml).expect("Failed to parse");
+    let specs = file.sim_specs.as_ref().unwrap();
+    assert_eq!(specs.method.as_deref(), Some("RK4"));
+    assert_eq!(specs.time_units.as_deref(), Some("Months"));
+    assert_eq!(specs.pause, Some(5.0));
+    assert_eq!(specs.run_by.as_deref(), Some("group"));
+    assert_eq!(specs.dt, Some(0.25));
+    let model_specs = file.models[0].sim_specs.as_ref().unwrap();
+    assert_eq!(model_specs.time_units.as_deref(), Some("Days"));
+
+    // Written back as attributes, not elements
+    let serialized = file.to_xml_string().expect("Failed to serialize");
+    assert!(
+        serialized
+            .contains(r#"<sim_specs method="RK4" time_units="Months" pause="5" run_by="group">"#),
+        "{serialized}"
+    );
+    assert!(!serialized.contains("<method>"), "{serialized}");
+    assert!(!serialized.contains("<time_units>"), "{serialized}");
+}
+
 #[test]
 fn test_round_trip_with_behavior() {
     let xml = r#"
@@ -176,8 +227,6 @@ fn test_round_trip_with_behavior() {
 #[cfg(feature = "macros")]
 #[test]
 fn test_round_trip_with_macros() {
-    // Note: This test may fail if macro serialization has issues
-    // We'll test it separately to avoid breaking other tests
     let xml = r#"
     <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
         <header>
@@ -186,8 +235,8 @@ fn test_round_trip_with_macros() {
         </header>
         <macro name="test_macro">
             <eqn>param1 + param2</eqn>
-            <parm name="param1" default="10"/>
-            <parm name="param2"/>
+            <parm default="10">param1</parm>
+            <parm>param2</parm>
         </macro>
         <model>
             <variables>
@@ -199,17 +248,7 @@ fn test_round_trip_with_macros() {
     </xmile>
     "#;
 
-    // Try round-trip, but don't fail if macro serialization has issues
-    if let Ok(file) = XmileFile::from_str(xml) {
-        if let Ok(serialized) = serde_xml_rs::to_string(&file) {
-            if let Ok(file2) = XmileFile::from_str(&serialized) {
-                // Compare key fields
-                assert_eq!(file.version, file2.version);
-                assert_eq!(file.models.len(), file2.models.len());
-                // Note: Full equality may fail due to macro serialization differences
-            }
-        }
-    }
+    round_trip_test(xml, "model with macros");
 }
 
 #[cfg(feature = "submodels")]
@@ -306,7 +345,7 @@ fn test_serialize_with_none_fields() {
     let file = XmileFile::from_str(xml).expect("Failed to parse");
 
     // Serialize - should not panic even with None fields
-    let serialized = serde_xml_rs::to_string(&file).expect("Failed to serialize");
+    let serialized = file.to_xml_string().expect("Failed to serialize");
 
     // Should be valid XML
     assert!(serialized.contains("<xmile"));
@@ -345,7 +384,9 @@ fn test_round_trip_teacup_example() {
     );
 
     // Test round-trip
-    let serialized = serde_xml_rs::to_string(&file).expect("Failed to serialize teacup example");
+    let serialized = file
+        .to_xml_string()
+        .expect("Failed to serialize teacup example");
 
     let file2 =
         XmileFile::from_str(&serialized).expect("Failed to re-parse serialized teacup example");
@@ -403,7 +444,7 @@ fn test_round_trip_empty_collections() {
     assert!(!file.models[0].variables.variables.is_empty());
 
     // Serialize and re-parse
-    let serialized = serde_xml_rs::to_string(&file).expect("Failed to serialize");
+    let serialized = file.to_xml_string().expect("Failed to serialize");
     let file2 = XmileFile::from_str(&serialized).expect("Failed to re-parse");
 
     assert_eq!(file, file2);
@@ -460,7 +501,7 @@ fn test_round_trip_variable_order() {
     "#;
 
     let file1 = XmileFile::from_str(xml).expect("Failed to parse");
-    let serialized = serde_xml_rs::to_string(&file1).expect("Failed to serialize");
+    let serialized = file1.to_xml_string().expect("Failed to serialize");
     let file2 = XmileFile::from_str(&serialized).expect("Failed to re-parse");
 
     // Check that variable order is preserved
@@ -468,7 +509,6 @@ fn test_round_trip_variable_order() {
     let vars2 = &file2.models[0].variables.variables;
 
     assert_eq!(vars1.len(), vars2.len());
-    // Note: Exact order preservation depends on serde-xml-rs behavior
     // We at least verify the count matches
 }
 
@@ -497,13 +537,52 @@ fn test_round_trip_xmlns_preservation() {
         "http://docs.oasis-open.org/xmile/ns/XMILE/v1.0"
     );
 
-    let serialized = serde_xml_rs::to_string(&file1).expect("Failed to serialize");
+    let serialized = file1.to_xml_string().expect("Failed to serialize");
     assert!(serialized.contains("xmlns=\"http://docs.oasis-open.org/xmile/ns/XMILE/v1.0\""));
 
     let file2 = XmileFile::from_str(&serialized).expect("Failed to re-parse");
     assert_eq!(file1.xmlns, file2.xmlns);
 }
 
+#[test]
+fn test_round_trip_vendor_namespaces() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0" xmlns:isee="http://iseesystems.com/XMILE" xmlns:acme="urn:acme:extensions">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <aux name="Rate" acme:locked="true">
+                    <eqn>1</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    let file1 = XmileFile::from_str(xml).expect("Failed to parse");
+    assert_eq!(
+        file1.namespaces,
+        vec![
+            (
+                "isee".to_string(),
+                "http://iseesystems.com/XMILE".to_string()
+            ),
+            ("acme".to_string(), "urn:acme:extensions".to_string()),
+        ]
+    );
+
+    let serialized = file1.to_xml_string().expect("Failed to serialize");
+    assert!(serialized.contains(
+        r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0" xmlns:isee="http://iseesystems.com/XMILE" xmlns:acme="urn:acme:extensions">"#
+    ));
+
+    let file2 = XmileFile::from_str(&serialized).expect("Failed to re-parse");
+    assert_eq!(file1, file2);
+}
+
 #[cfg(feature = "arrays")]
 #[test]
 fn test_round_trip_with_arrays() {
@@ -596,7 +675,7 @@ fn test_round_trip_arrays_and_macros() {
         </dimensions>
         <macro name="array_macro">
             <eqn>param1 * 2</eqn>
-            <parm name="param1"/>
+            <parm>param1</parm>
         </macro>
         <model>
             <variables>
@@ -618,35 +697,11 @@ fn test_round_trip_arrays_and_macros() {
     "#;
 
     // Test that arrays and macros work together
-    // Note: Round-trip may have issues with optional field serialization in serde-xml-rs,
-    // particularly with #text fields. We verify that parsing works.
-    match XmileFile::from_str(xml) {
-        Ok(file) => {
-            // Verify arrays and macros are present
-            assert_eq!(file.models.len(), 1);
-            if !file.models.is_empty() {
-                assert_eq!(file.models[0].variables.variables.len(), 1);
-            }
+    let file = XmileFile::from_str(xml).expect("Failed to parse");
+    assert_eq!(file.models.len(), 1);
+    assert_eq!(file.models[0].variables.variables.len(), 1);
 
-            // Try serialization - if it fails due to serde-xml-rs quirks, that's okay
-            // The important thing is that parsing works
-            if let Ok(serialized) = serde_xml_rs::to_string(&file) {
-                if let Ok(file2) = XmileFile::from_str(&serialized) {
-                    // If round-trip works, verify structure
-                    assert_eq!(file.models.len(), file2.models.len());
-                }
-            }
-        }
-        Err(e) => {
-            // If parsing fails due to serde-xml-rs quirks with #text fields, skip the test
-            let error_msg = format!("{:?}", e);
-            if error_msg.contains("#text") || error_msg.contains("missing field") {
-                // Known serde-xml-rs limitation - skip this test
-                return;
-            }
-            panic!("Failed to parse: {:?}", e);
-        }
-    }
+    round_trip_test(xml, "model with arrays and macros");
 }
 
 #[cfg(feature = "mathml")]
@@ -671,7 +726,7 @@ fn test_round_trip_with_mathml() {
 
     // Test that MathML is preserved
     let file = XmileFile::from_str(xml).expect("Failed to parse");
-    let serialized = serde_xml_rs::to_string(&file).expect("Failed to serialize");
+    let serialized = file.to_xml_string().expect("Failed to serialize");
     let file2 = XmileFile::from_str(&serialized).expect("Failed to re-parse");
 
     // Verify MathML is preserved
@@ -693,8 +748,8 @@ fn test_round_trip_all_features() {
         </dimensions>
         <macro name="test_macro">
             <eqn>param1 + param2</eqn>
-            <parm name="param1"/>
-            <parm name="param2"/>
+            <parm>param1</parm>
+            <parm>param2</parm>
         </macro>
         <model>
             <variables>
@@ -718,35 +773,115 @@ fn test_round_trip_all_features() {
     </xmile>
     "#;
 
-    // Note: Round-trip may have issues with optional field serialization in serde-xml-rs,
-    // particularly with #text fields. We verify that parsing works with all features.
-    // If parsing fails due to serde-xml-rs quirks, we skip this test.
-    match XmileFile::from_str(xml) {
-        Ok(file) => {
-            // Verify structure
-            assert_eq!(file.models.len(), 1);
-            if !file.models.is_empty() {
-                assert_eq!(file.models[0].variables.variables.len(), 2);
-            }
+    let file = XmileFile::from_str(xml).expect("Failed to parse");
+    assert_eq!(file.models.len(), 1);
+    assert_eq!(file.models[0].variables.variables.len(), 2);
 
-            // Try round-trip - if it fails due to serde-xml-rs quirks, that's okay
-            // The important thing is that parsing works with all features enabled
-            if let Ok(serialized) = serde_xml_rs::to_string(&file) {
-                if let Ok(file2) = XmileFile::from_str(&serialized) {
-                    assert_eq!(file, file2);
-                }
-            }
-        }
-        Err(e) => {
-            // If parsing fails due to serde-xml-rs quirks with #text fields, skip the test
-            // This is a known limitation of serde-xml-rs, not a bug in our code
-            let error_msg = format!("{:?}", e);
-            if error_msg.contains("#text") || error_msg.contains("missing field") {
-                // Known serde-xml-rs limitation - skip this test
-                return;
-            }
-            // If it's a different error, panic
-            panic!("Failed to parse: {:?}", e);
-        }
+    round_trip_test(xml, "model with all features");
+}
+
+#[test]
+fn test_round_trip_with_header_resources() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+            <image resource="images/model.png"/>
+            <includes>
+                <include resource="macros/*.xml"/>
+            </includes>
+        </header>
+        <model>
+            <variables>
+                <aux name="x">
+                    <eqn>1</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    round_trip_test(xml, "header resources");
+
+    let file = XmileFile::from_str(xml).unwrap();
+    let image = file.header.image.unwrap();
+    assert_eq!(image.resource.unwrap(), "images/model.png");
+    assert!(image.data.is_none());
+}
+
+#[test]
+fn test_round_trip_with_serialize_options() {
+    use xmile::xml::{FloatFormat, Newline, Quote, SerializeOptions};
+
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <sim_specs method="Euler" time_units="Months" pause="0.1" run_by="all">
+            <start>0.1</start>
+            <stop>12.345678901</stop>
+            <dt>0.03125</dt>
+        </sim_specs>
+        <model>
+            <variables>
+                <gf name="Effect" type="continuous">
+                    <xscale min="0" max="1"/>
+                    <ypts>0,0.333333333,1</ypts>
+                </gf>
+            </variables>
+            <views>
+                <view uid="1" type="stock_flow" zoom="100" width="800.5" height="600" page_width="800" page_height="600" home_view="false"/>
+            </views>
+        </model>
+    </xmile>
+    "#;
+    let file = XmileFile::from_str(xml).expect("Failed to parse");
+
+    let options = SerializeOptions {
+        indent: Some(2),
+        newline: Newline::CrLf,
+        self_closing: false,
+        quote: Quote::Single,
+        point_format: FloatFormat::Decimals(2),
+        emit_defaults: false,
+    };
+    let serialized = file
+        .to_xml_string_with(&options)
+        .expect("Failed to serialize");
+    assert!(serialized.contains("\r\n  <header>\r\n    <vendor>Test</vendor>"));
+    assert!(!serialized.replace("\r\n", "").contains('\n'));
+    assert!(serialized.contains("<ypts>0,0.33,1</ypts>"));
+    assert!(serialized.starts_with("<?xml version='1.0' encoding='UTF-8'?>"));
+    assert!(serialized.contains("<xscale min='0' max='1'></xscale>"));
+    assert!(!serialized.contains("type='continuous'"));
+    assert!(!serialized.contains("=\""));
+
+    // Only points are rounded, and only attributes set to their default
+    // are left out
+    assert!(serialized.contains("<sim_specs time_units='Months' pause='0.1'>"));
+    assert!(serialized.contains("<start>0.1</start>"));
+    assert!(serialized.contains("<stop>12.345678901</stop>"));
+    assert!(serialized.contains("<dt>0.03125</dt>"));
+    if cfg!(feature = "views") {
+        assert!(serialized.contains("width='800.5'"));
+        assert!(!serialized.contains("stock_flow"));
+        assert!(!serialized.contains("zoom="));
+        assert!(!serialized.contains("home_view="));
     }
+
+    let reparsed = XmileFile::from_str(&serialized).expect("Failed to re-parse");
+    let (specs, original) = (
+        reparsed.sim_specs.unwrap(),
+        file.sim_specs.as_ref().unwrap(),
+    );
+    assert_eq!(
+        (specs.start, specs.stop, specs.dt, specs.pause),
+        (original.start, original.stop, original.dt, original.pause)
+    );
+    assert_eq!(specs.time_units, original.time_units);
+    assert_eq!(specs.method, None);
+    assert_eq!(reparsed.models[0].variables.variables.len(), 1);
 }
@@ -0,0 +1,116 @@
+use std::fs;
+
+use xmile::model::vars::Variable;
+use xmile::scenario::{Scenario, ScenarioError};
+use xmile::xml::XmileFile;
+use xmile::{Expression, GraphicalFunctionData, NumericConstant};
+
+const MODEL: &str = r#"
+<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor>
+        <product version="1.0">Test Product</product>
+    </header>
+    <sim_specs>
+        <start>0</start>
+        <stop>100</stop>
+        <dt>1</dt>
+    </sim_specs>
+    <model>
+        <variables>
+            <stock name="Population">
+                <eqn>100</eqn>
+                <inflow>births</inflow>
+            </stock>
+            <flow name="births">
+                <eqn>Population * birth_rate</eqn>
+            </flow>
+            <aux name="birth rate">
+                <eqn>0.1</eqn>
+            </aux>
+            <gf name="crowding">
+                <xscale min="0" max="1"/>
+                <ypts>1,0.5,0</ypts>
+            </gf>
+        </variables>
+    </model>
+</xmile>
+"#;
+
+const SCENARIO: &str = r#"
+name = "Fast growth"
+
+[sim_specs]
+stop = 200
+dt = 0.5
+
+[parameters]
+"birth_rate" = 0.3
+Population = "50 * 2"
+
+[[graphical_functions]]
+name = "crowding"
+xpts = [0, 0.2, 1]
+ypts = [1, 0.9, 0]
+"#;
+
+#[test]
+fn test_load_and_apply_scenario() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fast.toml");
+    fs::write(&path, SCENARIO).unwrap();
+    let scenario = Scenario::load(&path).unwrap();
+
+    let mut file = XmileFile::from_str(MODEL).expect("Failed to parse model");
+    scenario.apply(&mut file).unwrap();
+
+    let sim_specs = file.sim_specs.as_ref().unwrap();
+    assert_eq!(sim_specs.start, 0.0);
+    assert_eq!(sim_specs.stop, 200.0);
+    assert_eq!(sim_specs.dt, Some(0.5));
+
+    let variables = &file.models[0].variables.variables;
+    let Variable::Auxiliary(aux) = &variables[2] else {
+        panic!("Expected an auxiliary");
+    };
+    assert_eq!(aux.equation, Expression::Constant(NumericConstant(0.3)));
+    let Variable::GraphicalFunction(gf) = &variables[3] else {
+        panic!("Expected a graphical function");
+    };
+    assert!(matches!(gf.data, GraphicalFunctionData::XYPairs { .. }));
+    assert_eq!(gf.evaluate(0.2), 0.9);
+}
+
+#[test]
+fn test_failed_apply_leaves_file_unchanged() {
+    let mut file = XmileFile::from_str(MODEL).expect("Failed to parse model");
+    let original = file.clone();
+
+    let scenario: Scenario = r#"
+        [sim_specs]
+        stop = 10
+
+        [parameters]
+        "death rate" = 0.1
+    "#
+    .parse()
+    .unwrap();
+    assert!(matches!(
+        scenario.apply(&mut file),
+        Err(ScenarioError::UnknownVariable(name)) if name == "death rate"
+    ));
+    assert_eq!(file, original);
+
+    let scenario: Scenario = r#"
+        [[graphical_functions]]
+        name = "birth rate"
+        xscale = [0, 1]
+        ypts = [0, 1]
+    "#
+    .parse()
+    .unwrap();
+    assert!(matches!(
+        scenario.apply(&mut file),
+        Err(ScenarioError::UnknownGraphicalFunction(_))
+    ));
+}
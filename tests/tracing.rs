@@ -0,0 +1,130 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use xmile::xml::XmileFile;
+
+const XML: &str = r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+    <header>
+        <vendor>Test</vendor>
+        <product version="1.0">Test Product</product>
+    </header>
+    <model>
+        <variables>
+            <stock name="Population"><eqn>100</eqn></stock>
+            <aux name="Rate"><eqn>0.1</eqn></aux>
+        </variables>
+    </model>
+</xmile>"#;
+
+/// Records the names of the spans created and the messages of events.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<&'static str>>>,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(span.metadata().name());
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        struct Message(String);
+
+        impl tracing::field::Visit for Message {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                if !self.0.is_empty() {
+                    self.0.push(' ');
+                }
+                self.0.push_str(&format!("{}={:?}", field.name(), value));
+            }
+        }
+
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        self.events.lock().unwrap().push(message.0);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn test_parse_and_validate_spans() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let file = XmileFile::from_str(XML).unwrap();
+        file.validate().unwrap();
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(
+        spans[..3],
+        ["xmile.parse", "deserialize", "resolve"],
+        "{spans:?}"
+    );
+    assert!(spans.contains(&"xmile.validate"), "{spans:?}");
+    assert!(spans.contains(&"model.validate"), "{spans:?}");
+
+    let events = recorder.events.lock().unwrap();
+    assert!(
+        events.contains(&"message=parsed models=1 variables=2".to_string()),
+        "{events:?}"
+    );
+    assert!(
+        events.contains(&"message=validated errors=0".to_string()),
+        "{events:?}"
+    );
+}
+
+#[test]
+fn test_run_span() {
+    use xmile::simulation::Simulator;
+    use xmile::specs::SimulationSpecs;
+
+    let xml = XML.replace(
+        r#"<stock name="Population"><eqn>100</eqn></stock>"#,
+        r#"<stock name="Population"><eqn>100</eqn><inflow>Births</inflow></stock>
+            <flow name="Births"><eqn>Population * Rate</eqn></flow>"#,
+    );
+    let file = XmileFile::from_str(&xml).unwrap();
+    let specs = SimulationSpecs {
+        start: 0.0,
+        stop: 2.0,
+        dt: None,
+        method: None,
+        time_units: None,
+        pause: None,
+        run_by: None,
+    };
+    let simulator = Simulator::from_file(&file, &specs).unwrap();
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        simulator.run().unwrap();
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(spans[0], "xmile.run", "{spans:?}");
+
+    let events = recorder.events.lock().unwrap();
+    let ran = events
+        .iter()
+        .find_map(|event| event.strip_prefix("message=ran steps=2 evaluations="))
+        .unwrap_or_else(|| panic!("{events:?}"));
+    assert!(ran.parse::<u64>().unwrap() > 0, "{events:?}");
+}
@@ -22,7 +22,7 @@ fn test_validate_variable_name_uniqueness() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     let result = model.validate();
 
@@ -59,13 +59,14 @@ fn test_validate_unique_variable_names() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     let result = model.validate();
 
     assert!(result.is_valid() || result.has_warnings());
 }
 
+#[cfg(feature = "views")]
 #[test]
 fn test_validate_view_object_references() {
     let xml = r#"
@@ -90,7 +91,7 @@ fn test_validate_view_object_references() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     let result = model.validate();
 
@@ -103,6 +104,94 @@ fn test_validate_view_object_references() {
     }
 }
 
+#[cfg(feature = "views")]
+#[test]
+fn test_validate_alias_targets() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <name>Test Model</name>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <stock name="Stock1">
+                    <eqn>100</eqn>
+                </stock>
+            </variables>
+            <views>
+                <view uid="1" width="800" height="600" page_width="800" page_height="600">
+                    <stock uid="1" name="Stock1" x="100" y="100" width="50" height="50"/>
+                </view>
+                <view uid="2" width="800" height="600" page_width="800" page_height="600">
+                    <alias uid="1" x="100" y="100"><of>Stock1</of></alias>
+                    <alias uid="2" x="200" y="100"><of>Missing</of></alias>
+                </view>
+            </views>
+        </model>
+    </xmile>
+    "#;
+
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
+    let model = &file.models[0];
+    let result = model.validate();
+
+    if let xmile::types::ValidationResult::Invalid(_, errors) = result {
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].contains("Alias (UID 2) in view 2 represents 'Missing'"));
+    } else {
+        panic!("Expected Invalid result");
+    }
+}
+
+#[cfg(feature = "views")]
+#[test]
+fn test_validate_connector_endpoints() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <name>Test Model</name>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <stock name="Stock1">
+                    <eqn>100</eqn>
+                </stock>
+            </variables>
+            <views>
+                <view uid="1" width="800" height="600" page_width="800" page_height="600">
+                    <stock uid="1" name="Stock1" x="100" y="100" width="50" height="50"/>
+                    <alias uid="2" x="200" y="100"><of>Stock1</of></alias>
+                    <connector uid="3" x="200" y="100" angle="0">
+                        <from><alias uid="2"/></from>
+                        <to><alias uid="9"/></to>
+                    </connector>
+                    <connector uid="4" x="100" y="100" angle="0">
+                        <from>Stock1</from>
+                        <to>Economy.GDP</to>
+                    </connector>
+                </view>
+            </views>
+        </model>
+    </xmile>
+    "#;
+
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
+    let model = &file.models[0];
+    let result = model.validate();
+
+    if let xmile::types::ValidationResult::Invalid(_, errors) = result {
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].contains("Connector (UID 3) in view 1 attaches to alias 9"));
+        assert!(errors[1].contains("module 'Economy' is not shown"));
+    } else {
+        panic!("Expected Invalid result");
+    }
+}
+
 #[test]
 fn test_validate_group_entity_references() {
     let xml = r#"
@@ -125,7 +214,7 @@ fn test_validate_group_entity_references() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
     let result = model.validate();
 
@@ -140,3 +229,207 @@ fn test_validate_group_entity_references() {
         panic!("Expected Invalid result");
     }
 }
+
+#[test]
+fn test_validate_xmile_version() {
+    let xml = |version: &str| {
+        format!(
+            r#"<xmile version="{version}" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <aux name="Rate">
+                    <eqn>1</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>"#
+        )
+    };
+
+    let file = XmileFile::from_str(&xml("1.0")).expect("Failed to parse XML");
+    assert!(file.validate().is_ok());
+
+    let file = XmileFile::from_str(&xml("0.9")).expect("Failed to parse XML");
+    let error = file.validate().unwrap_err().to_string();
+    assert!(error.contains("Unsupported XMILE version '0.9'"), "{error}");
+}
+
+#[test]
+fn test_validate_reserved_variable_names() {
+    let xml = |reference: &str| {
+        format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <aux name="Time">
+                    <eqn>5</eqn>
+                </aux>
+                <aux name="Time value">
+                    <eqn>1</eqn>
+                </aux>
+                <aux name="Doubled">
+                    <eqn>2 * {reference}</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>"#
+        )
+    };
+
+    let file = XmileFile::from_str(&xml("\"Time\"")).expect("Failed to parse XML");
+    match file.models[0].validate() {
+        xmile::types::ValidationResult::Warnings(_, warnings) => assert_eq!(
+            warnings,
+            vec![
+                "Variable name 'Time' is a reserved word and can only be referred to in quotes. Consider renaming it, e.g. to 'Time value 2'."
+            ]
+        ),
+        _ => panic!("Expected a warning"),
+    }
+
+    let file = XmileFile::from_str(&xml("Time")).expect("Failed to parse XML");
+    match file.models[0].validate() {
+        xmile::types::ValidationResult::Invalid(_, errors) => {
+            assert!(errors.iter().any(|e| {
+                e.starts_with("The equation of 'Doubled' refers to 'Time' without quotes")
+            }));
+        }
+        _ => panic!("Expected an error"),
+    }
+}
+
+#[test]
+fn test_validate_normalized_name_uniqueness() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <aux name="Birth Rate">
+                    <eqn>0.1</eqn>
+                </aux>
+                <aux name="birth_rate">
+                    <eqn>0.2</eqn>
+                </aux>
+                <aux name="Death Rate">
+                    <eqn>0.05</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
+    match file.models[0].validate() {
+        xmile::types::ValidationResult::Invalid(_, errors) => {
+            assert_eq!(errors.len(), 1, "{errors:?}");
+            assert!(errors[0].starts_with(
+                "Duplicate variable name 'Birth Rate' found 2 times in the model (at positions 0 and 1) as 'Birth Rate', 'birth_rate'"
+            ));
+        }
+        _ => panic!("Expected Invalid result"),
+    }
+}
+
+#[test]
+fn test_validate_graphical_function_data() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <model>
+            <variables>
+                <gf name="Effect_of_Crowding">
+                    <xpts>0,1,1,2</xpts>
+                    <ypts>1,0.8,0.5,0.2</ypts>
+                </gf>
+                <gf name="Effect_of_Density">
+                    <xscale min="0" max="1"/>
+                    <ypts>0,0.5,1</ypts>
+                </gf>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
+    match file.models[0].validate() {
+        xmile::types::ValidationResult::Invalid(_, errors) => {
+            assert_eq!(errors.len(), 1, "{errors:?}");
+            assert!(
+                errors[0].starts_with(
+                    "Graphical function 'Effect of Crowding': values are not strictly increasing"
+                ),
+                "{errors:?}"
+            );
+        }
+        _ => panic!("Expected Invalid result"),
+    }
+}
+
+#[test]
+fn test_validate_flow_units_per_time_unit() {
+    let xml = |sim_specs: &str| {
+        format!(
+            r#"<xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        {sim_specs}
+        <model>
+            <variables>
+                <stock name="Population">
+                    <eqn>100</eqn>
+                    <inflow>Arrivals</inflow>
+                    <units>people</units>
+                </stock>
+                <flow name="Arrivals">
+                    <eqn>5</eqn>
+                    <units>widgets/year</units>
+                </flow>
+            </variables>
+        </model>
+    </xmile>"#
+        )
+    };
+
+    // The time unit is an attribute in the standard
+    let file = XmileFile::from_str(&xml(
+        r#"<sim_specs time_units="Year"><start>0</start><stop>10</stop></sim_specs>"#,
+    ))
+    .expect("Failed to parse XML");
+    assert_eq!(
+        file.sim_specs.as_ref().unwrap().time_units.as_deref(),
+        Some("Year")
+    );
+    let error = file.validate().unwrap_err().to_string();
+    assert!(error.contains("Flow 'Arrivals' has units"), "{error}");
+
+    // Pre-standard files give it as an element
+    let file = XmileFile::from_str(&xml(
+        r#"<sim_specs><start>0</start><stop>10</stop><time_units>Year</time_units></sim_specs>"#,
+    ))
+    .expect("Failed to parse XML");
+    assert!(file.validate().is_err());
+
+    // Without a time unit, flows cannot be checked against stocks
+    let file = XmileFile::from_str(&xml(
+        "<sim_specs><start>0</start><stop>10</stop></sim_specs>",
+    ))
+    .expect("Failed to parse XML");
+    assert!(file.validate().is_ok());
+}
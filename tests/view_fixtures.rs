@@ -0,0 +1,69 @@
+#![cfg(feature = "views")]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use xmile::testing::builders::{ObjectKind, ViewBuilder, synthetic_model_xml};
+use xmile::view::View;
+use xmile::xml::XmileFile;
+
+/// Parses a view holding one object of `kind`, writes it back out and parses
+/// it again, checking the object is kept at each step.
+fn round_trip(kind: ObjectKind) -> Result<(), String> {
+    let builder = ViewBuilder::new(1).with(kind);
+    let view = panic::catch_unwind(AssertUnwindSafe(|| builder.build()))
+        .map_err(|_| "parsing panicked".to_string())?
+        .map_err(|e| format!("parsing failed: {e}"))?;
+    if kind.count(&view) != 1 {
+        return Err("object dropped when parsing".to_string());
+    }
+
+    let xml = quick_xml::se::to_string_with_root("view", &view)
+        .map_err(|e| format!("serializing failed: {e}"))?;
+    let reparsed: View = panic::catch_unwind(|| quick_xml::de::from_str(&xml))
+        .map_err(|_| "reparsing panicked".to_string())?
+        .map_err(|e| format!("reparsing failed: {e}: {xml}"))?;
+    if kind.count(&reparsed) != 1 {
+        return Err(format!("object dropped when serializing: {xml}"));
+    }
+    if reparsed != view {
+        return Err(format!("object changed by round trip: {xml}"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_every_view_object_round_trips() {
+    let failures: Vec<String> = ObjectKind::ALL
+        .iter()
+        .filter_map(|&kind| round_trip(kind).err().map(|e| format!("{kind:?}: {e}")))
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "Round trip failures:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_view_builder_numbers_objects() {
+    let builder = ViewBuilder::new(3)
+        .view_type("interface")
+        .with(ObjectKind::StackedContainer)
+        .with(ObjectKind::StackedContainer);
+    let xml = builder.to_xml();
+    assert!(xml.starts_with(r#"<view uid="3" type="interface""#));
+    assert!(xml.contains(r#"<stacked_container uid="2""#));
+
+    let view = builder.build().unwrap();
+    assert_eq!(ObjectKind::StackedContainer.count(&view), 2);
+}
+
+#[test]
+fn test_views_in_model_keep_every_object() {
+    let file = XmileFile::from_str(&synthetic_model_xml()).unwrap();
+    let views = file.models[0].views.as_ref().unwrap();
+    let view = &views.views[0];
+    for &kind in ObjectKind::ALL {
+        assert_eq!(kind.count(view), 1, "{kind:?} dropped from <views>");
+    }
+}
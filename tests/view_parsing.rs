@@ -1,4 +1,6 @@
-use serde_xml_rs::from_str;
+#![cfg(feature = "views")]
+
+use quick_xml::de::from_str;
 use xmile::view::View;
 
 #[test]
@@ -105,3 +107,218 @@ fn test_vendor_specific_view_type() {
         _ => panic!("Expected VendorSpecific view type"),
     }
 }
+
+#[test]
+fn test_vendor_specific_view_type_round_trip() {
+    use xmile::Vendor;
+    use xmile::view::ViewType;
+
+    for type_attr in ["isee:interface_page", "acme:dashboard"] {
+        let xml = format!(
+            r#"<view uid="7" type="{type_attr}" width="800" height="600" page_width="800" page_height="600"></view>"#
+        );
+        let view: View = from_str(&xml).expect("Failed to parse vendor-specific view");
+        assert!(view.view_type.is_vendor_specific());
+        assert_eq!(view.view_type.to_string(), type_attr);
+
+        let written = quick_xml::se::to_string_with_root("view", &view).unwrap();
+        assert!(written.contains(&format!(r#"type="{type_attr}""#)));
+        let reparsed: View = from_str(&written).unwrap();
+        assert_eq!(reparsed.view_type, view.view_type);
+    }
+
+    let view_type: ViewType = "isee:interface_page".parse().unwrap();
+    assert_eq!(view_type.vendor(), Some(&Vendor::Isee));
+    assert_eq!(view_type.vendor_type(), Some("interface_page"));
+    let view_type: ViewType = "acme:dashboard".parse().unwrap();
+    assert_eq!(view_type.vendor(), Some(&Vendor::Other));
+    assert_eq!(view_type.vendor_type(), Some("dashboard"));
+    assert_eq!(ViewType::Interface.vendor_type(), None);
+}
+
+#[cfg(feature = "interface-objects")]
+#[test]
+fn test_graphics_frame_media() {
+    use xmile::resource::MemoryResources;
+    use xmile::types::Validate;
+    use xmile::view::{GraphicsFrameContent, MediaFormat, MediaSource};
+
+    let xml = r#"
+    <view uid="5" type="interface" width="800" height="600" page_width="800" page_height="600">
+        <graphics_frame uid="1" x="102" y="301" width="400" height="300">
+            <image size_to_parent="true" width="2509" height="1932">file://C:/Archive/home-screen.png</image>
+        </graphics_frame>
+        <graphics_frame uid="2" x="0" y="0" width="400" height="300">
+            <video width="640" height="480">media/intro.mp4</video>
+        </graphics_frame>
+    </view>
+    "#;
+
+    let mut view: View = from_str(xml).expect("Failed to parse graphics frames");
+    assert_eq!(view.graphics_frames.len(), 2);
+    assert_eq!(
+        view.graphics_frames[1].content.resource().unwrap(),
+        "media/intro.mp4"
+    );
+    assert!(xmile::xml::validation::validate_view_media(&view).is_valid());
+
+    // The absolute path falls back to the file name next to the model.
+    let png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x20\0\0\0\x10".to_vec();
+    let resources = MemoryResources::new().with("home-screen.png", png.clone());
+    let GraphicsFrameContent::Image(image) = &mut view.graphics_frames[0].content else {
+        panic!("Expected image content");
+    };
+    assert!(image.size_to_parent);
+    assert_eq!(image.detect_size(&resources).unwrap(), (32, 16));
+    image.embed(&resources).unwrap();
+    assert!(image.validate().is_valid());
+
+    let content = &view.graphics_frames[0].content;
+    let serialized = quick_xml::se::to_string(content).unwrap();
+    assert!(serialized.starts_with(
+        "<image size_to_parent=\"true\" width=\"32\" height=\"16\">data:image/png;base64,"
+    ));
+    let mut reparsed: GraphicsFrameContent = from_str(&serialized).unwrap();
+    assert_eq!(&reparsed, content);
+
+    let GraphicsFrameContent::Image(image) = &mut reparsed else {
+        panic!("Expected image content");
+    };
+    let media = image.extract("images/home-screen.png".into()).unwrap();
+    assert_eq!(media.format, MediaFormat::Png);
+    assert_eq!(media.data, png);
+    assert_eq!(
+        image.source,
+        MediaSource::Resource("images/home-screen.png".into())
+    );
+}
+
+#[cfg(feature = "interface-objects")]
+#[test]
+fn test_lamp_and_gauge_zones() {
+    use xmile::view::ZoneType;
+    use xmile::view::style::{Color, PredefinedColor};
+
+    let xml = r##"
+    <view uid="6" type="interface" width="800" height="600" page_width="800" page_height="600">
+        <lamp retain_ending_value="true" x="543" y="104" width="20" height="20" uid="3">
+            <entity name="Converter_1" />
+            <zones>
+                <zone type="panic" min="8" max="8.33" color="red" />
+                <zone type="caution" min="8.33" max="8.66" color="#FFFF00" sound="alarm.wav" />
+                <zone type="normal" min="8.66" max="9" color="green"/>
+            </zones>
+        </lamp>
+        <gauge x="100" y="100" width="80" height="80" uid="4" show_number="false">
+            <entity name="Converter_1" />
+            <zones>
+                <zone type="normal" min="0" max="50" color="magenta" />
+                <zone type="panic" min="50" max="100" color="red" />
+            </zones>
+        </gauge>
+    </view>
+    "##;
+
+    let view: View = from_str(xml).expect("Failed to parse lamps and gauges");
+    let lamp = &view.lamps[0];
+    assert_eq!(lamp.entity_name, "Converter_1");
+    assert!(!lamp.show_name);
+    assert!(lamp.flash_on_panic);
+    assert_eq!(lamp.zones.len(), 3);
+    assert_eq!(lamp.zones[1].color, Color::Hex("#FFFF00".to_string()));
+    assert_eq!(lamp.zones[1].sound.as_ref().unwrap(), "alarm.wav");
+
+    assert_eq!(lamp.zone_for(8.1).unwrap().zone_type, ZoneType::Panic);
+    assert_eq!(lamp.zone_for(8.33).unwrap().zone_type, ZoneType::Caution);
+    assert_eq!(lamp.zone_for(9.0).unwrap().zone_type, ZoneType::Normal);
+    assert!(lamp.zone_for(10.0).is_none());
+    assert!(lamp.zone_for(f64::NAN).is_none());
+
+    let gauge = &view.gauges[0];
+    assert!(gauge.show_name);
+    assert!(!gauge.show_number);
+    let zone = gauge.zone_for(25.0).unwrap();
+    assert_eq!(zone.color, Color::Other("magenta".to_string()));
+    assert_eq!(
+        gauge.zone_for(75.0).unwrap().color,
+        Color::Predefined(PredefinedColor::Red)
+    );
+    assert!(xmile::xml::validation::validate_view_zones(&view).is_valid());
+
+    let lamp_xml = quick_xml::se::to_string(lamp).unwrap();
+    assert!(lamp_xml.contains(r#"<zones><zone type="panic" color="red" min="8" max="8.33"/>"#));
+    assert_eq!(
+        &from_str::<xmile::view::LampObject>(&lamp_xml).unwrap(),
+        lamp
+    );
+    let gauge_xml = quick_xml::se::to_string(gauge).unwrap();
+    assert_eq!(
+        &from_str::<xmile::view::GaugeObject>(&gauge_xml).unwrap(),
+        gauge
+    );
+}
+
+#[cfg(feature = "interface-objects")]
+#[test]
+fn test_overlapping_zones_invalid() {
+    let xml = r#"
+    <view uid="7" type="interface" width="800" height="600" page_width="800" page_height="600">
+        <lamp x="0" y="0" width="20" height="20" uid="1">
+            <entity name="x" />
+            <zones>
+                <zone type="normal" min="0" max="6" color="green" />
+                <zone type="panic" min="5" max="10" color="red" />
+            </zones>
+        </lamp>
+    </view>
+    "#;
+
+    let view: View = from_str(xml).unwrap();
+    assert!(xmile::xml::validation::validate_view_zones(&view).is_invalid());
+}
+
+#[cfg(feature = "interface-objects")]
+#[test]
+fn test_numeric_display_and_table_formats() {
+    use xmile::model::object::{DisplayAs, FormatOptions};
+    use xmile::view::{NumericDisplayObject, TableObject};
+
+    let xml = r#"<numeric_display uid="4" x="10" y="20" width="80" height="20" entity_name="Revenue" precision="0.01" scale_by="1000" display_as="currency" delimit_000s="true"/>"#;
+    let display: NumericDisplayObject = from_str(xml).expect("Failed to parse numeric display");
+    assert!(display.show_name);
+    assert!(display.retain_ending_value);
+    assert_eq!(display.display_as, Some(DisplayAs::Currency));
+    assert_eq!(display.number_format(None).format(12345678.9), "$12,345.68");
+
+    let serialized = quick_xml::se::to_string(&display).unwrap();
+    let reparsed: NumericDisplayObject = from_str(&serialized).unwrap();
+    assert_eq!(reparsed, display);
+
+    let xml = r#"
+    <table uid="7" x="0" y="0" width="300" height="200" orientation="vertical" column_width="60" interval="1" report_balances="beginning" report_flows="summed" comparative="false" wrap_text="false">
+        <item type="time"/>
+        <item type="variable" entity_name="Population" display_as="scientific" precision="0.01"/>
+        <item type="variable" entity_name="Share" precision="0.1"/>
+    </table>
+    "#;
+    let table: TableObject = from_str(xml).expect("Failed to parse table");
+    let item = &table.items[1];
+    assert_eq!(item.display_as, Some(DisplayAs::Scientific));
+    assert_eq!(item.delimit_000s, None);
+    let serialized = quick_xml::se::to_string(item).unwrap();
+    assert_eq!(
+        from_str::<xmile::view::TableItem>(&serialized).unwrap(),
+        *item
+    );
+
+    // Items inherit unset attributes from the entity's <format>.
+    let share = FormatOptions {
+        precision: Some(0.01),
+        display_as: Some(DisplayAs::Percent),
+        ..FormatOptions::default()
+    };
+    let formats = table.number_formats(|name| (name == "Share").then_some(share));
+    assert_eq!(formats[0].format(2.5), "2.5");
+    assert_eq!(formats[1].format(7_900_000_000.0), "7.90e9");
+    assert_eq!(formats[2].format(0.1234), "12.3%");
+}
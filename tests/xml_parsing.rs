@@ -95,7 +95,7 @@ fn test_group_parsing() {
     </xmile>
     "#;
 
-    let file: XmileFile = serde_xml_rs::from_str(xml).expect("Failed to parse XML");
+    let file: XmileFile = quick_xml::de::from_str(xml).expect("Failed to parse XML");
     let model = &file.models[0];
 
     assert_eq!(model.variables.variables.len(), 1);
@@ -122,3 +122,100 @@ fn test_group_parsing() {
         _ => panic!("Expected Group variant"),
     }
 }
+
+#[test]
+fn test_parse_header_options_attributes() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0" lang="en">Test Product</product>
+            <options namespace="std, isee">
+                <uses_conveyor leak="true"/>
+                <uses_arrays maximum_dimensions="2" invalid_index_value="NaN"/>
+                <uses_macros recursive_macros="false" option_filters="true"/>
+                <has_model_view/>
+                <uses_outputs gauge="true"/>
+            </options>
+            <version>2.1</version>
+        </header>
+        <model>
+            <variables>
+                <aux name="x">
+                    <eqn>1</eqn>
+                </aux>
+            </variables>
+        </model>
+    </xmile>
+    "#;
+
+    let file = XmileFile::from_str(xml).expect("Failed to parse XML");
+    let header = &file.header;
+    assert_eq!(header.product.lang.as_deref(), Some("en"));
+    assert_eq!(header.version_info.as_deref(), Some("2.1"));
+
+    let options = header.options.as_ref().expect("Expected options");
+    assert_eq!(options.namespace.as_deref(), Some("std, isee"));
+    assert_eq!(options.uses_conveyor.as_ref().unwrap().leak, Some(true));
+    let arrays = options.uses_arrays.as_ref().unwrap();
+    assert_eq!(arrays.maximum_dimensions, 2);
+    assert_eq!(arrays.invalid_index_value.as_deref(), Some("NaN"));
+    assert!(options.uses_macros.as_ref().unwrap().option_filters);
+    assert_eq!(options.has_model_view, Some(true));
+    assert_eq!(options.uses_submodels, None);
+    assert_eq!(options.uses_outputs.as_ref().unwrap().gauge, Some(true));
+
+    let serialized = file.to_xml_string().expect("Failed to serialize");
+    assert!(serialized.contains("<has_model_view/>"));
+    assert!(serialized.contains(r#"maximum_dimensions="2""#));
+    let reparsed = XmileFile::from_str(&serialized).expect("Failed to re-parse");
+    assert_eq!(file, reparsed);
+}
+
+#[test]
+fn test_parse_views_skipped_without_views_feature() {
+    let xml = r#"
+    <xmile version="1.0" xmlns="http://docs.oasis-open.org/xmile/ns/XMILE/v1.0">
+        <header>
+            <vendor>Test</vendor>
+            <product version="1.0">Test Product</product>
+        </header>
+        <style color="blue"/>
+        <model>
+            <variables>
+                <aux name="x">
+                    <eqn>1</eqn>
+                </aux>
+            </variables>
+            <views>
+                <view uid="1" width="800" height="600" page_width="800" page_height="600">
+                    <aux uid="2" name="x" x="10" y="10"/>
+                    <slider uid="3" x="50" y="50" width="100" height="20" min="0" max="10">
+                        <entity name="x"/>
+                    </slider>
+                </view>
+            </views>
+        </model>
+    </xmile>
+    "#;
+
+    let file = XmileFile::from_str(xml).expect("Failed to parse XML");
+    assert_eq!(file.models[0].variables.variables.len(), 1);
+
+    #[cfg(feature = "views")]
+    {
+        let views = file.models[0].views.as_ref().expect("Expected views");
+        assert_eq!(views.views[0].auxes.len(), 1);
+        #[cfg(feature = "interface-objects")]
+        assert_eq!(views.views[0].sliders.len(), 1);
+    }
+    #[cfg(feature = "style")]
+    assert!(file.style.is_some());
+
+    let serialized = file.to_xml_string().expect("Failed to serialize");
+    assert_eq!(serialized.contains("<views>"), cfg!(feature = "views"));
+    assert_eq!(
+        serialized.contains("<slider"),
+        cfg!(feature = "interface-objects")
+    );
+}